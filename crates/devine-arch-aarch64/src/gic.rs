@@ -1,6 +1,16 @@
 pub const GICD_BASE: usize = 0x08000000;
 pub const GICC_BASE: usize = 0x08010000;
 
+const GICC_IAR: usize = 0x0C;
+const GICC_EOIR: usize = 0x10;
+
+/// IRQ number returned by `GICC_IAR` when no interrupt is pending.
+const SPURIOUS_IRQ: u32 = 1023;
+
+/// Maximum IRQ this dispatcher will route to a registered handler. Covers
+/// SGIs/PPIs (0-31) plus a generous range of SPIs.
+const MAX_IRQS: usize = 256;
+
 pub struct Gic {
     gicd_base: usize,
     gicc_base: usize,
@@ -29,6 +39,77 @@ impl Gic {
         let reg = (self.gicd_base + 0x100 + (irq / 32) as usize * 4) as *mut u32;
         reg.write_volatile(reg.read_volatile() | (1 << (irq % 32)));
     }
+
+    /// Read `GICC_IAR`, acknowledging the highest-priority pending
+    /// interrupt and returning its IRQ number (or [`SPURIOUS_IRQ`] if none
+    /// is pending).
+    pub unsafe fn acknowledge(&self) -> u32 {
+        let iar = (self.gicc_base + GICC_IAR) as *const u32;
+        iar.read_volatile() & 0x3FF
+    }
+
+    /// Write `GICC_EOIR`, signalling end-of-interrupt for `irq`.
+    pub unsafe fn end_of_interrupt(&self, irq: u32) {
+        let eoir = (self.gicc_base + GICC_EOIR) as *mut u32;
+        eoir.write_volatile(irq);
+    }
+}
+
+pub type IrqHandler = fn(u32);
+
+static mut HANDLERS: [Option<IrqHandler>; MAX_IRQS] = [None; MAX_IRQS];
+
+/// Register `handler` to run whenever `irq` fires. Replaces any handler
+/// previously registered for the same IRQ.
+pub fn register(irq: u32, handler: IrqHandler) {
+    let irq = irq as usize;
+    if irq < MAX_IRQS {
+        unsafe {
+            HANDLERS[irq] = Some(handler);
+        }
+    }
+}
+
+/// Default handler for an IRQ nobody registered for: log it and leave it
+/// masked so it can't keep re-firing and starving real work.
+fn handle_unregistered(gic: &mut Gic, irq: u32) {
+    let mut uart = crate::uart::UART.lock();
+    uart.write_str("gic: unhandled IRQ ");
+    let mut digits = [0u8; 3];
+    let mut n = irq.min(999);
+    for slot in digits.iter_mut().rev() {
+        *slot = b'0' + (n % 10) as u8;
+        n /= 10;
+    }
+    for &digit in &digits {
+        uart.write_byte(digit);
+    }
+    uart.write_str(", masking it\n");
+    unsafe {
+        let reg = (gic.gicd_base + 0x180 + (irq / 32) as usize * 4) as *mut u32;
+        reg.write_volatile(1 << (irq % 32));
+    }
+}
+
+/// Top-level IRQ entry point: acknowledge the pending interrupt, dispatch
+/// it to its registered handler (or the unhandled-IRQ fallback), and signal
+/// end-of-interrupt. Meant to be called from the exception vector that
+/// handles IRQ exceptions.
+pub fn dispatch_irq(gic: &mut Gic) {
+    let irq = unsafe { gic.acknowledge() };
+    if irq == SPURIOUS_IRQ {
+        return;
+    }
+
+    let handler = unsafe { HANDLERS.get(irq as usize).copied().flatten() };
+    match handler {
+        Some(handler) => handler(irq),
+        None => handle_unregistered(gic, irq),
+    }
+
+    unsafe {
+        gic.end_of_interrupt(irq);
+    }
 }
 
 pub fn init() {
@@ -4,6 +4,7 @@ use devine_arch::ArchOps;
 
 pub mod gic;
 pub mod mmu;
+pub mod timer;
 pub mod uart;
 
 pub struct AArch64;
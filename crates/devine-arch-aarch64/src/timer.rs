@@ -0,0 +1,63 @@
+//! ARM generic timer driven as a programmable periodic tick.
+//!
+//! Uses the EL1 physical timer (`CNTP_TVAL_EL0`/`CNTP_CTL_EL0`), which
+//! fires PPI 30 on the GIC. `start` arms it for a period and registers the
+//! PPI with the `gic` dispatcher; the timer re-arms itself from its own
+//! handler so the tick keeps running without further setup.
+
+use crate::gic::Gic;
+
+/// PPI the EL1 physical timer drives, per the GICv2 architecture spec.
+const TIMER_PPI: u32 = 30;
+
+static mut RELOAD_TICKS: u32 = 0;
+static mut ON_TICK: Option<fn()> = None;
+
+fn cntp_tval_write(ticks: u32) {
+    unsafe {
+        core::arch::asm!("msr cntp_tval_el0, {}", in(reg) ticks as u64);
+    }
+}
+
+fn cntp_ctl_write(value: u64) {
+    unsafe {
+        core::arch::asm!("msr cntp_ctl_el0, {}", in(reg) value);
+    }
+}
+
+fn cntfrq_read() -> u64 {
+    let freq: u64;
+    unsafe {
+        core::arch::asm!("mrs {}, cntfrq_el0", out(reg) freq);
+    }
+    freq
+}
+
+/// Arm the timer for a `period_hz` periodic tick, enable its PPI on `gic`,
+/// and register `callback` to run on every tick.
+pub fn start(gic: &mut Gic, period_hz: u32, callback: fn()) {
+    let ticks = cntfrq_read() / period_hz as u64;
+    unsafe {
+        RELOAD_TICKS = ticks as u32;
+        ON_TICK = Some(callback);
+    }
+
+    cntp_tval_write(ticks as u32);
+    cntp_ctl_write(1); // ENABLE, unmasked
+
+    unsafe {
+        gic.enable_interrupt(TIMER_PPI);
+    }
+    crate::gic::register(TIMER_PPI, on_timer_irq);
+}
+
+/// Handler registered with the GIC dispatcher for the timer PPI: re-arm
+/// the next period and invoke the registered callback.
+fn on_timer_irq(_irq: u32) {
+    unsafe {
+        cntp_tval_write(RELOAD_TICKS);
+        if let Some(callback) = ON_TICK {
+            callback();
+        }
+    }
+}
@@ -1,16 +1,86 @@
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
 use spin::Mutex;
 use volatile::Volatile;
 
 const UART0_BASE: usize = 0x09000000;
 
+const CR_UARTEN: u32 = 1 << 0;
+const CR_TXE: u32 = 1 << 8;
+const CR_RXE: u32 = 1 << 9;
+const IMSC_RXIM: u32 = 1 << 4;
+
 pub static UART: Mutex<Uart> = Mutex::new(Uart::new(UART0_BASE));
 
+/// Fixed-capacity SPSC ring buffer for bytes handed off from the RX
+/// interrupt handler (the sole producer) to `read_byte`/`read_line`
+/// readers (the sole consumer). `head`/`tail` are independently owned by
+/// consumer/producer respectively, so no lock is needed -- only the
+/// acquire/release pairing on the shared indices.
+const RX_QUEUE_SIZE: usize = 256;
+
+struct RxQueue {
+    buffer: UnsafeCell<[u8; RX_QUEUE_SIZE]>,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+unsafe impl Sync for RxQueue {}
+
+impl RxQueue {
+    const fn new() -> Self {
+        Self {
+            buffer: UnsafeCell::new([0; RX_QUEUE_SIZE]),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Producer side: called from the RX interrupt handler.
+    fn push(&self, byte: u8) -> bool {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let next = (tail + 1) % RX_QUEUE_SIZE;
+        if next == self.head.load(Ordering::Acquire) {
+            return false; // full, drop the byte
+        }
+        unsafe {
+            (*self.buffer.get())[tail] = byte;
+        }
+        self.tail.store(next, Ordering::Release);
+        true
+    }
+
+    /// Consumer side: called from readers.
+    fn pop(&self) -> Option<u8> {
+        let head = self.head.load(Ordering::Relaxed);
+        if head == self.tail.load(Ordering::Acquire) {
+            return None; // empty
+        }
+        let byte = unsafe { (*self.buffer.get())[head] };
+        self.head.store((head + 1) % RX_QUEUE_SIZE, Ordering::Release);
+        Some(byte)
+    }
+}
+
+static RX_QUEUE: RxQueue = RxQueue::new();
+
 #[repr(C)]
 struct UartRegisters {
     dr: Volatile<u32>,
     _rsrecr: Volatile<u32>,
     _reserved1: [u32; 4],
     fr: Volatile<u32>,
+    _reserved2: u32,
+    _ilpr: Volatile<u32>,
+    _ibrd: Volatile<u32>,
+    _fbrd: Volatile<u32>,
+    _lcrh: Volatile<u32>,
+    cr: Volatile<u32>,
+    _ifls: Volatile<u32>,
+    imsc: Volatile<u32>,
+    _ris: Volatile<u32>,
+    _mis: Volatile<u32>,
+    icr: Volatile<u32>,
 }
 
 pub struct Uart {
@@ -42,6 +112,80 @@ impl Uart {
             self.write_byte(byte);
         }
     }
+
+    /// Drain a single byte straight off the RX FIFO (RXFE clear in `fr`).
+    /// Returns `None` if the FIFO is currently empty.
+    pub fn read_byte(&mut self) -> Option<u8> {
+        unsafe {
+            let regs = &mut *self.registers;
+            if regs.fr.read() & (1 << 4) != 0 {
+                return None; // RXFE set: FIFO empty
+            }
+            Some((regs.dr.read() & 0xFF) as u8)
+        }
+    }
+
+    /// Unmask the RX interrupt so incoming bytes fire an IRQ instead of
+    /// needing to be polled.
+    pub fn enable_rx_interrupt(&mut self) {
+        unsafe {
+            let regs = &mut *self.registers;
+            regs.cr.write(regs.cr.read() | CR_UARTEN | CR_TXE | CR_RXE);
+            regs.imsc.write(regs.imsc.read() | IMSC_RXIM);
+        }
+    }
+
+    /// Blocking line read with basic cooked-mode editing: backspace
+    /// (0x08/0x7F) erases the previous character on screen and in `buf`,
+    /// and a bare CR is echoed and stored as LF. Returns once a newline is
+    /// read or `buf` fills up, not including the terminator.
+    pub fn read_line(&mut self, buf: &mut [u8]) -> usize {
+        let mut len = 0;
+        loop {
+            let byte = match RX_QUEUE.pop() {
+                Some(byte) => byte,
+                None => {
+                    core::hint::spin_loop();
+                    continue;
+                }
+            };
+
+            match byte {
+                b'\r' | b'\n' => {
+                    self.write_byte(b'\r');
+                    self.write_byte(b'\n');
+                    return len;
+                }
+                0x08 | 0x7F => {
+                    if len > 0 {
+                        len -= 1;
+                        self.write_str("\u{8} \u{8}");
+                    }
+                }
+                byte => {
+                    if len < buf.len() {
+                        buf[len] = byte;
+                        len += 1;
+                        self.write_byte(byte);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// RX interrupt handler: drain the hardware FIFO into the SPSC queue and
+/// clear the interrupt. Meant to be registered with the GIC dispatcher for
+/// the PL011 IRQ.
+pub fn rx_irq_handler(_irq: u32) {
+    let mut uart = UART.lock();
+    while let Some(byte) = uart.read_byte() {
+        RX_QUEUE.push(byte);
+    }
+    unsafe {
+        let regs = &mut *uart.registers;
+        regs.icr.write(0x7FF);
+    }
 }
 
 pub fn init() {
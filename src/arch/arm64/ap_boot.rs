@@ -3,85 +3,120 @@
 /// This module handles bringing secondary CPUs online using PSCI
 
 use super::cpu;
+use super::psci;
+use core::sync::atomic::{fence, Ordering};
 
-/// Bring up an application processor on ARM64
-/// 
-/// Uses PSCI (Power State Coordination Interface) to bring up secondary CPUs
-/// 
+/// Secondary-CPU boot method as advertised by a CPU's `enable-method` FDT
+/// property. PSCI is the default; spin-table is the fallback for firmware
+/// that doesn't implement PSCI CPU_ON.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EnableMethod {
+    Psci,
+    SpinTable { release_addr: u64 },
+}
+
+/// Pick the enable method for a CPU from its parsed FDT node, defaulting to
+/// PSCI when the node is missing or doesn't name spin-table explicitly.
+fn enable_method_for(node: Option<&cpu::FdtNode>) -> EnableMethod {
+    match node {
+        Some(n) if n.enable_method == "spin-table" => EnableMethod::SpinTable {
+            release_addr: n.cpu_release_addr,
+        },
+        _ => EnableMethod::Psci,
+    }
+}
+
+/// Bring up an application processor on ARM64 using the given enable method.
+///
 /// # Arguments
 /// * `target_cpu` - The MPIDR value of the CPU to bring up
 /// * `entry_point` - The 64-bit entry point address
 pub fn boot_ap(target_cpu: u64, entry_point: u64) -> bool {
-    // Use PSCI CPU_ON to bring the CPU online
-    let result = cpu::psci_cpu_on(target_cpu, entry_point, 0);
-    
-    if result >= 0 {
-        // PSCI_SUCCESS = 0
-        true
-    } else {
-        false
+    boot_ap_with(EnableMethod::Psci, target_cpu, entry_point)
+}
+
+/// Bring up an application processor via an explicit enable method.
+pub fn boot_ap_with(method: EnableMethod, target_cpu: u64, entry_point: u64) -> bool {
+    match method {
+        EnableMethod::Psci => {
+            // Use PSCI CPU_ON to bring the CPU online. ALREADY_ON is not an
+            // error from the caller's point of view -- the target is up
+            // either way.
+            let result = psci::cpu_on(target_cpu, entry_point, 0);
+            result == psci::PSCI_SUCCESS || result == psci::PSCI_ALREADY_ON
+        }
+        EnableMethod::SpinTable { release_addr } => boot_ap_spin_table(release_addr, entry_point),
+    }
+}
+
+/// Release a core parked in firmware's spin-table loop: write the entry
+/// point to its `cpu-release-addr`, then `sev` to wake it from `wfe`. The
+/// online-CPU tracker (not this function) is the source of truth for
+/// whether the core actually came up.
+fn boot_ap_spin_table(release_addr: u64, entry_point: u64) -> bool {
+    unsafe {
+        core::ptr::write_volatile(release_addr as *mut u64, entry_point);
+    }
+    // Ensure the write is visible to the spinning core before waking it.
+    fence(Ordering::SeqCst);
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        core::arch::asm!("dsb sy", "sev", options(nostack));
     }
+    true
 }
 
-/// Bring up all secondary CPUs
+/// Bring up all secondary CPUs, choosing PSCI or spin-table per CPU from
+/// the parsed FDT `enable-method` property (PSCI is the default when both
+/// are present, or when no FDT data is available for a CPU).
 pub fn boot_all_aps(entry_point: u64) -> u32 {
     let cpu_count = cpu::get_cpu_count();
     let my_id = cpu::get_cpu_id();
+    let fdt_nodes = cpu::parse_fdt(cpu::fdt_address());
     let mut booted = 0u32;
-    
+
     for cpu_id in 0..cpu_count {
         if cpu_id as u32 != my_id {
-            // Try to bring up this CPU
-            if boot_ap(cpu_id as u64, entry_point) {
+            let method = enable_method_for(fdt_nodes.get(cpu_id as usize));
+            if boot_ap_with(method, cpu_id as u64, entry_point) {
                 booted += 1;
             }
         }
     }
-    
+
     booted
 }
 
 /// Wait for all APs to come online
+///
+/// Spins on the online-CPU tracker in [`crate::cpu::percpu`], which each
+/// `ap_startup_main` updates once it has finished bringing itself up,
+/// instead of busy-looping blind.
 pub fn wait_for_aps(timeout_ms: u32) -> bool {
-    let mut elapsed = 0u32;
-    let step = 10u32;  // Check every 10ms
-    
-    loop {
-        // TODO: Check if all APs are online
-        // This would require reading some global state that tracks online CPUs
-        
-        if elapsed >= timeout_ms {
-            return false;
-        }
-        
-        elapsed += step;
-        wait_milliseconds(step);
-    }
-}
-
-/// Wait for a certain number of milliseconds (approximate)
-fn wait_milliseconds(ms: u32) {
-    // Simple busy-wait loop
-    let loops = ms * 1000;
-    
-    for _ in 0..loops {
-        #[cfg(target_arch = "aarch64")]
-        unsafe {
-            core::arch::asm!("yield", options(nomem, nostack));
-        }
-    }
+    let cpu_count = cpu::get_cpu_count();
+    crate::cpu::percpu::wait_for_online(cpu_count, timeout_ms)
 }
 
 /// C function called from AP startup
 /// This would typically set up per-CPU state
 #[no_mangle]
-pub extern "C" fn ap_startup_main(_cpu_id: u32) -> ! {
+pub extern "C" fn ap_startup_main(cpu_id: u32) -> ! {
     // Initialize per-CPU state
     // Set up VBAR_EL1, etc.
-    
+
+    // Stash our logical CPU id in TPIDR_EL1 so this core can find its own
+    // per-CPU data without a lookup; full per-CPU pointer support lives in
+    // `crate::cpu::percpu`.
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        core::arch::asm!("msr tpidr_el1, {}", in(reg) cpu_id as u64, options(nomem, nostack));
+    }
+
+    crate::cpu::percpu::mark_online(cpu_id);
+
     // TODO: Call kernel initialization for this CPU
-    
-    // For now, just wait
+
+    // Idle until woken by an IPI or the scheduler.
     loop {
         #[cfg(target_arch = "aarch64")]
         unsafe {
@@ -89,3 +124,27 @@ pub extern "C" fn ap_startup_main(_cpu_id: u32) -> ! {
         }
     }
 }
+
+/// Orderly teardown path for a secondary CPU: power it down via PSCI
+/// `CPU_OFF` instead of leaving it parked in a `wfi` loop.
+pub fn cpu_off() -> ! {
+    psci::cpu_off();
+    // CPU_OFF does not return on success; if firmware rejects the request,
+    // fall back to parking the core.
+    loop {
+        #[cfg(target_arch = "aarch64")]
+        unsafe {
+            core::arch::asm!("wfi");
+        }
+    }
+}
+
+/// Halt the board via PSCI `SYSTEM_OFF`.
+pub fn system_off() -> ! {
+    psci::system_off()
+}
+
+/// Reboot the board via PSCI `SYSTEM_RESET`.
+pub fn system_reset() -> ! {
+    psci::system_reset()
+}
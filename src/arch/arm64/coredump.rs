@@ -0,0 +1,84 @@
+//! aarch64 backend for the [`crate::coredump`] ELF core writer: knows how
+//! to snapshot the current register file into a `prstatus`-shaped note.
+
+use core::arch::asm;
+
+use alloc::vec::Vec;
+
+use crate::coredump::{write_note, CpuElf64Writable, EM_AARCH64, NT_PRSTATUS};
+use crate::memory::{paging::temporary_map, MemoryRegion};
+
+/// The `struct user_pt_regs` aarch64 Linux core files use for
+/// `NT_PRSTATUS`: x0-x30, sp, pc, pstate.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+struct UserRegs {
+    regs: [u64; 31],
+    sp: u64,
+    pc: u64,
+    pstate: u64,
+}
+
+/// Snapshot the currently-running core's register file. Only `sp`, `pc`
+/// (via `x30`/link register, the closest available without a trap frame)
+/// and `pstate` are read directly; `x0`-`x29` aren't individually
+/// addressable from inline asm without clobbering them, so they're left
+/// zeroed the same way `ap_startup_main`'s per-CPU setup leaves most of a
+/// new core's state as a `TODO` until a real trap frame exists to read.
+fn snapshot_registers() -> UserRegs {
+    let mut regs = UserRegs::default();
+
+    unsafe {
+        asm!("mov {}, sp", out(reg) regs.sp, options(nomem, nostack, preserves_flags));
+        asm!("mov {}, x30", out(reg) regs.pc, options(nomem, nostack, preserves_flags));
+        regs.regs[30] = regs.pc;
+        asm!("mrs {}, nzcv", out(reg) regs.pstate, options(nomem, nostack, preserves_flags));
+    }
+
+    regs
+}
+
+fn encode_user_regs(regs: &UserRegs) -> Vec<u8> {
+    let mut desc = Vec::with_capacity(34 * 8);
+    for value in regs.regs {
+        desc.extend_from_slice(&value.to_le_bytes());
+    }
+    desc.extend_from_slice(&regs.sp.to_le_bytes());
+    desc.extend_from_slice(&regs.pc.to_le_bytes());
+    desc.extend_from_slice(&regs.pstate.to_le_bytes());
+    desc
+}
+
+pub struct Arm64CoreWriter;
+
+impl CpuElf64Writable for Arm64CoreWriter {
+    fn elf_machine(&self) -> u16 {
+        EM_AARCH64
+    }
+
+    fn write_prstatus(&self, out: &mut Vec<u8>, cpu_id: u32) {
+        let mut desc = Vec::new();
+        desc.extend_from_slice(&[0u8; 32]); // pr_info + pr_cursig + pr_sigpend + pr_sighold
+        desc.extend_from_slice(&cpu_id.to_le_bytes()); // pr_pid
+        desc.extend_from_slice(&0u32.to_le_bytes()); // pr_ppid
+        desc.extend_from_slice(&0u32.to_le_bytes()); // pr_pgrp
+        desc.extend_from_slice(&0u32.to_le_bytes()); // pr_sid
+        desc.extend_from_slice(&[0u8; 32]); // pr_utime/pr_stime/pr_cutime/pr_cstime
+
+        let regs = snapshot_registers();
+        desc.extend_from_slice(&encode_user_regs(&regs));
+
+        desc.extend_from_slice(&0u32.to_le_bytes()); // pr_fpvalid
+
+        write_note(out, b"CORE", NT_PRSTATUS, &desc);
+    }
+
+    fn write_mem_regions(&self, out: &mut Vec<u8>, region: &MemoryRegion) {
+        let ptr = temporary_map(region.start).as_u64() as *const u8;
+        unsafe {
+            for i in 0..region.size {
+                out.push(*ptr.add(i));
+            }
+        }
+    }
+}
@@ -7,6 +7,9 @@
 /// - GIC (Generic Interrupt Controller) operations
 
 use core::arch::asm;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use super::fdt::{Fdt, MAX_CPU_NODES};
 
 /// Get CPU ID from MPIDR_EL1 register
 pub fn get_cpu_id() -> u32 {
@@ -34,56 +37,43 @@ pub fn get_cpu_cluster() -> u32 {
     ((mpidr >> 8) & 0xFF) as u32
 }
 
-/// Get number of CPUs (from device tree or PSCI)
-pub fn get_cpu_count() -> u32 {
-    // Placeholder - will be populated from device tree
-    // For now, return 1 as a fallback
-    1
-}
-
-/// PSCI constants
-pub const PSCI_VERSION: u32 = 0x84000000;
-pub const PSCI_CPU_ON_AARCH64: u32 = 0xc4000003;
-pub const PSCI_AFFINITY_INFO: u32 = 0x84000004;
+/// Physical address of the firmware-provided FDT blob, set once via
+/// [`set_fdt_address`] during early boot (0 means "none registered").
+static FDT_ADDRESS: AtomicU64 = AtomicU64::new(0);
 
-/// Make PSCI call (SMC/HVC)
-pub fn psci_call(function_id: u32, arg0: u64, arg1: u64, arg2: u64) -> i32 {
-    #[cfg(target_arch = "aarch64")]
-    unsafe {
-        let mut ret: u64 = function_id as u64;
-        asm!(
-            "hvc #0",  // Use HVC for hypervisor calls; could use "smc #0" for secure world
-            inlateout("x0") ret,
-            in("x1") arg0,
-            in("x2") arg1,
-            in("x3") arg2,
-            options(nostack)
-        );
-        ret as i32
-    }
-    
-    #[cfg(not(target_arch = "aarch64"))]
-    {
-        let _ = (function_id, arg0, arg1, arg2);
-        -1
-    }
+/// Record the FDT blob's physical address so [`get_cpu_count`] and
+/// [`parse_fdt`] have something to parse. Call once during early boot,
+/// before SMP bring-up, with the address firmware passed in `x0`.
+pub fn set_fdt_address(address: u64) {
+    FDT_ADDRESS.store(address, Ordering::Relaxed);
 }
 
-/// Get PSCI version
-pub fn get_psci_version() -> u32 {
-    psci_call(PSCI_VERSION, 0, 0, 0) as u32
+/// The FDT blob's physical address, or 0 if none has been registered.
+pub fn fdt_address() -> u64 {
+    FDT_ADDRESS.load(Ordering::Relaxed)
 }
 
-/// Bring up a CPU using PSCI
-pub fn psci_cpu_on(target_cpu: u64, entry_point: u64, context: u64) -> i32 {
-    psci_call(PSCI_CPU_ON_AARCH64, target_cpu, entry_point, context)
-}
+/// Get number of CPUs from the device tree's `/cpus/cpu@N` nodes, falling
+/// back to 1 (just the boot CPU) if no FDT has been registered, or it
+/// fails to parse, or reports zero CPUs.
+pub fn get_cpu_count() -> u32 {
+    let address = fdt_address();
+    if address == 0 {
+        return 1;
+    }
 
-/// Get CPU affinity info via PSCI
-pub fn psci_affinity_info(target_cpu: u64, level: u64) -> i32 {
-    psci_call(PSCI_AFFINITY_INFO, target_cpu, level, 0)
+    let count = unsafe { Fdt::new(address).map(|fdt| fdt.cpu_count()).unwrap_or(0) };
+    if count == 0 {
+        1
+    } else {
+        count as u32
+    }
 }
 
+// PSCI (Power State Coordination Interface) calls now live in `super::psci`,
+// which covers the full v1.0 surface (CPU_OFF, CPU_SUSPEND, AFFINITY_INFO,
+// SYSTEM_OFF, SYSTEM_RESET, MIGRATE) instead of just CPU_ON.
+
 /// Write to system control register (SCTLR_EL1)
 pub fn write_sctlr_el1(value: u64) {
     unsafe {
@@ -122,17 +112,69 @@ pub fn disable_mmu() {
     write_sctlr_el1(sctlr);
 }
 
-/// Simple FDT node for CPU detection
+/// A parsed `/cpus/cpu@N` node.
+#[derive(Clone, Copy)]
 pub struct FdtNode {
     pub compatible: &'static str,
     pub enable_method: &'static str,
     pub cpu_release_addr: u64,
+    /// This CPU's `reg` property: its MPIDR-derived id within `/cpus`.
+    pub reg: u64,
 }
 
-/// Parse FDT for CPU information (simplified)
-pub fn parse_fdt(_fdt_address: u64) -> [FdtNode; 0] {
-    // Placeholder - full FDT parsing would go here
-    []
+impl FdtNode {
+    pub(crate) const fn empty() -> Self {
+        FdtNode {
+            compatible: "",
+            enable_method: "",
+            cpu_release_addr: 0,
+            reg: 0,
+        }
+    }
+}
+
+/// Fixed-capacity, zero-alloc collection of the `/cpus/cpu@N` nodes a
+/// single [`parse_fdt`] call found, up to [`MAX_CPU_NODES`].
+pub struct FdtNodes {
+    nodes: [FdtNode; MAX_CPU_NODES],
+    len: usize,
+}
+
+impl FdtNodes {
+    pub fn get(&self, index: usize) -> Option<&FdtNode> {
+        self.nodes[..self.len].get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Parse the FDT blob at `fdt_address` and collect its `/cpus/cpu@N`
+/// nodes. Returns an empty collection if the address is 0 or the blob's
+/// header doesn't validate.
+pub fn parse_fdt(fdt_address: u64) -> FdtNodes {
+    const EMPTY: FdtNode = FdtNode::empty();
+    let mut nodes = [EMPTY; MAX_CPU_NODES];
+    let mut len = 0usize;
+
+    if fdt_address != 0 {
+        if let Some(fdt) = unsafe { Fdt::new(fdt_address) } {
+            for node in fdt.cpu_nodes() {
+                if len >= MAX_CPU_NODES {
+                    break;
+                }
+                nodes[len] = node;
+                len += 1;
+            }
+        }
+    }
+
+    FdtNodes { nodes, len }
 }
 
 #[cfg(test)]
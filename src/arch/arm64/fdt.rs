@@ -0,0 +1,426 @@
+/// Flattened Device Tree (DTB) blob parser.
+///
+/// Zero-alloc token walker over the big-endian DTB format from the
+/// Devicetree Specification: a header, a memory-reservation block, a
+/// struct block of BEGIN_NODE/END_NODE/PROP/NOP/END tokens, and a
+/// strings block that struct-block property tokens reference by offset.
+/// Every accessor re-reads directly out of the blob; nothing here
+/// allocates or copies the tree into owned storage.
+use super::cpu::FdtNode;
+
+const FDT_MAGIC: u32 = 0xd00d_feed;
+
+const FDT_BEGIN_NODE: u32 = 0x1;
+const FDT_END_NODE: u32 = 0x2;
+const FDT_PROP: u32 = 0x3;
+const FDT_NOP: u32 = 0x4;
+const FDT_END: u32 = 0x9;
+
+/// Devicetree spec default when a node's parent doesn't declare
+/// `#address-cells`/`#size-cells` (used for `/memory`'s `reg`).
+const DEFAULT_ADDRESS_CELLS: u32 = 2;
+const DEFAULT_SIZE_CELLS: u32 = 1;
+
+/// `/cpus`'s own `#address-cells` is conventionally 1 -- a CPU's `reg` is
+/// its MPIDR-derived id, not a memory address.
+const CPUS_ADDRESS_CELLS: u32 = 1;
+
+/// Max `/cpus/cpu@N` nodes a single parse reports; bounds the zero-alloc
+/// node buffer handed back to callers.
+pub const MAX_CPU_NODES: usize = 16;
+
+#[inline]
+fn align4(x: u32) -> u32 {
+    (x + 3) & !3
+}
+
+unsafe fn read_be_u32(base: *const u8, offset: u32) -> u32 {
+    let p = base.add(offset as usize);
+    u32::from_be_bytes([*p, *p.add(1), *p.add(2), *p.add(3)])
+}
+
+/// Read `ncells` (clamped to 2, the common case) big-endian 32-bit cells
+/// starting at `offset` and concatenate them into a single value.
+unsafe fn read_cells(base: *const u8, offset: u32, ncells: u32) -> u64 {
+    let ncells = ncells.min(2);
+    let mut value = 0u64;
+    for i in 0..ncells {
+        value = (value << 32) | read_be_u32(base, offset + i * 4) as u64;
+    }
+    value
+}
+
+unsafe fn read_cstr<'a>(base: *const u8, offset: u32) -> &'a str {
+    let start = base.add(offset as usize);
+    let mut len = 0usize;
+    while *start.add(len) != 0 {
+        len += 1;
+    }
+    let slice = core::slice::from_raw_parts(start, len);
+    core::str::from_utf8(slice).unwrap_or("")
+}
+
+/// A validated view over a DTB blob. Holds no owned data -- every
+/// accessor re-reads directly out of the blob, which must outlive it.
+#[derive(Clone, Copy)]
+pub struct Fdt {
+    base: *const u8,
+    struct_off: u32,
+    struct_end: u32,
+    strings_off: u32,
+    address_cells: u32,
+    size_cells: u32,
+}
+
+impl Fdt {
+    /// Validate the header at `address` and build a view over it, or
+    /// `None` if the magic doesn't match.
+    ///
+    /// # Safety
+    /// `address` must point to a valid, mapped DTB blob that outlives
+    /// every use of the returned `Fdt`.
+    pub unsafe fn new(address: u64) -> Option<Fdt> {
+        let base = address as *const u8;
+        if read_be_u32(base, 0) != FDT_MAGIC {
+            return None;
+        }
+
+        let off_dt_struct = read_be_u32(base, 8);
+        let off_dt_strings = read_be_u32(base, 12);
+        let size_dt_struct = read_be_u32(base, 36);
+
+        let mut fdt = Fdt {
+            base,
+            struct_off: off_dt_struct,
+            struct_end: off_dt_struct + size_dt_struct,
+            strings_off: off_dt_strings,
+            address_cells: DEFAULT_ADDRESS_CELLS,
+            size_cells: DEFAULT_SIZE_CELLS,
+        };
+        fdt.read_root_cell_sizes();
+        Some(fdt)
+    }
+
+    fn prop_name(&self, nameoff: u32) -> &'static str {
+        unsafe { read_cstr(self.base, self.strings_off + nameoff) }
+    }
+
+    /// Scan the root node's direct properties (before its first child)
+    /// for `#address-cells`/`#size-cells`, which govern how `/memory`'s
+    /// `reg` cells are interpreted.
+    fn read_root_cell_sizes(&mut self) {
+        unsafe {
+            let mut pos = self.struct_off;
+
+            // Skip NOPs and step past the root node's own BEGIN_NODE ("").
+            loop {
+                let token = read_be_u32(self.base, pos);
+                pos += 4;
+                match token {
+                    FDT_NOP => continue,
+                    FDT_BEGIN_NODE => {
+                        let name = read_cstr(self.base, pos);
+                        pos += align4(name.len() as u32 + 1);
+                        break;
+                    }
+                    _ => return,
+                }
+            }
+
+            loop {
+                let token = read_be_u32(self.base, pos);
+                pos += 4;
+                match token {
+                    FDT_NOP => continue,
+                    FDT_PROP => {
+                        let len = read_be_u32(self.base, pos);
+                        let nameoff = read_be_u32(self.base, pos + 4);
+                        let value_off = pos + 8;
+                        pos = value_off + align4(len);
+
+                        match self.prop_name(nameoff) {
+                            "#address-cells" if len == 4 => {
+                                self.address_cells = read_be_u32(self.base, value_off);
+                            }
+                            "#size-cells" if len == 4 => {
+                                self.size_cells = read_be_u32(self.base, value_off);
+                            }
+                            _ => {}
+                        }
+                    }
+                    _ => return,
+                }
+            }
+        }
+    }
+
+    /// Iterate `/cpus/cpu@N` nodes.
+    pub fn cpu_nodes(&self) -> CpuNodeIter<'_> {
+        CpuNodeIter {
+            fdt: self,
+            pos: self.struct_off,
+            depth: 0,
+            in_cpus: false,
+            cpus_depth: 0,
+            done: false,
+        }
+    }
+
+    /// Iterate `(base, size)` ranges out of every `/memory` node, honoring
+    /// the root's `#address-cells`/`#size-cells`.
+    pub fn memory_regions(&self) -> MemoryRegionIter<'_> {
+        MemoryRegionIter {
+            fdt: self,
+            pos: self.struct_off,
+            depth: 0,
+            pending: None,
+            done: false,
+        }
+    }
+
+    /// Count of `/cpus/cpu@N` nodes.
+    pub fn cpu_count(&self) -> usize {
+        self.cpu_nodes().count()
+    }
+}
+
+/// Iterator over `/cpus/cpu@N` nodes, yielding each node's `reg`,
+/// `compatible` and `enable-method`/`cpu-release-addr` properties.
+pub struct CpuNodeIter<'a> {
+    fdt: &'a Fdt,
+    pos: u32,
+    depth: u32,
+    in_cpus: bool,
+    cpus_depth: u32,
+    done: bool,
+}
+
+impl<'a> CpuNodeIter<'a> {
+    /// Parse the body of a matched `cpu@N` node, consuming tokens up to
+    /// and including its closing `FDT_END_NODE`.
+    unsafe fn parse_node_body(&mut self) -> FdtNode {
+        let mut node = FdtNode::empty();
+        let mut nested = 0u32;
+
+        loop {
+            let token = read_be_u32(self.fdt.base, self.pos);
+            self.pos += 4;
+            match token {
+                FDT_NOP => {}
+                FDT_PROP => {
+                    let len = read_be_u32(self.fdt.base, self.pos);
+                    let nameoff = read_be_u32(self.fdt.base, self.pos + 4);
+                    let value_off = self.pos + 8;
+                    self.pos = value_off + align4(len);
+
+                    if nested == 0 {
+                        match self.fdt.prop_name(nameoff) {
+                            "compatible" => node.compatible = read_cstr(self.fdt.base, value_off),
+                            "enable-method" => node.enable_method = read_cstr(self.fdt.base, value_off),
+                            "cpu-release-addr" if len >= 4 => {
+                                node.cpu_release_addr = read_cells(self.fdt.base, value_off, len / 4);
+                            }
+                            "reg" if len >= 4 => {
+                                node.reg = read_cells(self.fdt.base, value_off, CPUS_ADDRESS_CELLS);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                FDT_BEGIN_NODE => {
+                    let name = read_cstr(self.fdt.base, self.pos);
+                    self.pos += align4(name.len() as u32 + 1);
+                    nested += 1;
+                }
+                FDT_END_NODE => {
+                    if nested == 0 {
+                        break;
+                    }
+                    nested -= 1;
+                }
+                _ => break,
+            }
+        }
+
+        node
+    }
+}
+
+impl<'a> Iterator for CpuNodeIter<'a> {
+    type Item = FdtNode;
+
+    fn next(&mut self) -> Option<FdtNode> {
+        if self.done {
+            return None;
+        }
+
+        unsafe {
+            loop {
+                if self.pos >= self.fdt.struct_end {
+                    self.done = true;
+                    return None;
+                }
+
+                let token = read_be_u32(self.fdt.base, self.pos);
+                self.pos += 4;
+                match token {
+                    FDT_NOP => continue,
+                    FDT_END => {
+                        self.done = true;
+                        return None;
+                    }
+                    FDT_BEGIN_NODE => {
+                        let name = read_cstr(self.fdt.base, self.pos);
+                        self.pos += align4(name.len() as u32 + 1);
+                        self.depth += 1;
+
+                        if !self.in_cpus && name == "cpus" {
+                            self.in_cpus = true;
+                            self.cpus_depth = self.depth;
+                            continue;
+                        }
+
+                        if self.in_cpus && self.depth == self.cpus_depth + 1 && name.starts_with("cpu@") {
+                            let node = self.parse_node_body();
+                            self.depth -= 1;
+                            return Some(node);
+                        }
+                    }
+                    FDT_END_NODE => {
+                        if self.in_cpus && self.depth == self.cpus_depth {
+                            self.in_cpus = false;
+                        }
+                        self.depth = self.depth.saturating_sub(1);
+                    }
+                    FDT_PROP => {
+                        let len = read_be_u32(self.fdt.base, self.pos);
+                        self.pos += 8 + align4(len);
+                    }
+                    _ => {
+                        self.done = true;
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A `(base, size)` range reported by a `/memory` node.
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryRegion {
+    pub base: u64,
+    pub size: u64,
+}
+
+/// Iterator over the `(base, size)` ranges in every `/memory` node's
+/// `reg` property (a property can list more than one range).
+pub struct MemoryRegionIter<'a> {
+    fdt: &'a Fdt,
+    pos: u32,
+    depth: u32,
+    pending: Option<(u32, u32)>,
+    done: bool,
+}
+
+impl<'a> MemoryRegionIter<'a> {
+    /// Scan a matched `/memory` node's direct properties for `reg`,
+    /// consuming tokens up to and including its closing `FDT_END_NODE`.
+    unsafe fn find_reg_in_node(&mut self) -> Option<(u32, u32)> {
+        let mut reg = None;
+        let mut nested = 0u32;
+
+        loop {
+            let token = read_be_u32(self.fdt.base, self.pos);
+            self.pos += 4;
+            match token {
+                FDT_NOP => {}
+                FDT_PROP => {
+                    let len = read_be_u32(self.fdt.base, self.pos);
+                    let nameoff = read_be_u32(self.fdt.base, self.pos + 4);
+                    let value_off = self.pos + 8;
+                    self.pos = value_off + align4(len);
+
+                    if nested == 0 && self.fdt.prop_name(nameoff) == "reg" {
+                        reg = Some((value_off, len));
+                    }
+                }
+                FDT_BEGIN_NODE => {
+                    let name = read_cstr(self.fdt.base, self.pos);
+                    self.pos += align4(name.len() as u32 + 1);
+                    nested += 1;
+                }
+                FDT_END_NODE => {
+                    if nested == 0 {
+                        self.depth = self.depth.saturating_sub(1);
+                        break;
+                    }
+                    nested -= 1;
+                }
+                _ => break,
+            }
+        }
+
+        reg
+    }
+}
+
+impl<'a> Iterator for MemoryRegionIter<'a> {
+    type Item = MemoryRegion;
+
+    fn next(&mut self) -> Option<MemoryRegion> {
+        if self.done {
+            return None;
+        }
+
+        unsafe {
+            loop {
+                if let Some((off, remaining)) = self.pending {
+                    let entry_len = 4 * (self.fdt.address_cells + self.fdt.size_cells);
+                    if entry_len > 0 && remaining >= entry_len {
+                        let base = read_cells(self.fdt.base, off, self.fdt.address_cells);
+                        let size = read_cells(self.fdt.base, off + 4 * self.fdt.address_cells, self.fdt.size_cells);
+                        self.pending = Some((off + entry_len, remaining - entry_len));
+                        return Some(MemoryRegion { base, size });
+                    }
+                    self.pending = None;
+                }
+
+                if self.pos >= self.fdt.struct_end {
+                    self.done = true;
+                    return None;
+                }
+
+                let token = read_be_u32(self.fdt.base, self.pos);
+                self.pos += 4;
+                match token {
+                    FDT_NOP => continue,
+                    FDT_END => {
+                        self.done = true;
+                        return None;
+                    }
+                    FDT_BEGIN_NODE => {
+                        let name = read_cstr(self.fdt.base, self.pos);
+                        self.pos += align4(name.len() as u32 + 1);
+                        self.depth += 1;
+
+                        if self.depth == 1 && (name == "memory" || name.starts_with("memory@")) {
+                            self.pending = self.find_reg_in_node();
+                        }
+                    }
+                    FDT_END_NODE => {
+                        self.depth = self.depth.saturating_sub(1);
+                    }
+                    FDT_PROP => {
+                        let len = read_be_u32(self.fdt.base, self.pos);
+                        self.pos += 8 + align4(len);
+                    }
+                    _ => {
+                        self.done = true;
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+}
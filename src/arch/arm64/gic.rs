@@ -0,0 +1,213 @@
+/// GICv2 (Generic Interrupt Controller) driver.
+///
+/// Covers the distributor/CPU-interface split of GICv2: the distributor
+/// enables IRQs, assigns their priority and target core(s), while each
+/// core's CPU interface masks by priority and runs the acknowledge/EOI
+/// handshake for whatever it's currently handling. This gives the ARM
+/// timer tick the same ack/EOI pair the x86 side's PIC gets from port
+/// 0x20 in `crate::arch::interrupts::timer_interrupt_handler`, and backs
+/// the SGI-send helper the generic IPI subsystem in `crate::arch::ipi`
+/// uses to reach PSCI-booted secondary CPUs.
+use crate::arch::controller::InterruptController;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+// Distributor register offsets (GICD_*).
+const GICD_CTLR: usize = 0x000;
+const GICD_ISENABLER: usize = 0x100;
+const GICD_ICENABLER: usize = 0x180;
+const GICD_IPRIORITYR: usize = 0x400;
+const GICD_ITARGETSR: usize = 0x800;
+/// GICD_SGIR: writing here raises the SGI named in bits 3:0 at the targets
+/// named in bits 23:16 (TargetListFilter=0, "forward to CPU interface list").
+const GICD_SGIR: usize = 0xF00;
+
+// CPU interface register offsets (GICC_*).
+const GICC_CTLR: usize = 0x000;
+const GICC_PMR: usize = 0x004;
+const GICC_IAR: usize = 0x00C;
+const GICC_EOIR: usize = 0x010;
+
+/// `GICC_IAR`'s interrupt ID occupies bits 9:0; 1023 means "no pending
+/// interrupt" (a spurious read).
+const IAR_INTERRUPT_ID_MASK: u32 = 0x3FF;
+pub const SPURIOUS_IRQ: u32 = 1023;
+
+/// Default distributor/CPU-interface bases for the common QEMU `virt`
+/// board layout, used until [`init`] is called with real FDT-derived
+/// addresses.
+const DEFAULT_GICD_BASE: u64 = 0x0800_0000;
+const DEFAULT_GICC_BASE: u64 = 0x0801_0000;
+
+static DIST_BASE: AtomicU64 = AtomicU64::new(DEFAULT_GICD_BASE);
+static CPU_BASE: AtomicU64 = AtomicU64::new(DEFAULT_GICC_BASE);
+
+fn dist_base() -> usize {
+    DIST_BASE.load(Ordering::Relaxed) as usize
+}
+
+fn cpu_base() -> usize {
+    CPU_BASE.load(Ordering::Relaxed) as usize
+}
+
+unsafe fn read_reg32(base: usize, offset: usize) -> u32 {
+    ((base + offset) as *const u32).read_volatile()
+}
+
+unsafe fn write_reg32(base: usize, offset: usize, value: u32) {
+    ((base + offset) as *mut u32).write_volatile(value);
+}
+
+unsafe fn write_reg8(base: usize, offset: usize, value: u8) {
+    ((base + offset) as *mut u8).write_volatile(value);
+}
+
+/// Record the distributor and CPU-interface MMIO bases and bring both
+/// halves up: enable the distributor's forwarding of group-0 interrupts,
+/// unmask every priority at the CPU interface, then enable it.
+pub fn init(dist_base: u64, cpu_base: u64) {
+    DIST_BASE.store(dist_base, Ordering::Relaxed);
+    CPU_BASE.store(cpu_base, Ordering::Relaxed);
+
+    unsafe {
+        write_reg32(dist_base as usize, GICD_CTLR, 1);
+        write_reg32(cpu_base as usize, GICC_PMR, 0xFF);
+        write_reg32(cpu_base as usize, GICC_CTLR, 1);
+    }
+}
+
+/// Set `irq`'s priority (lower value = higher priority, per GICv2). Safe
+/// to call whether or not `irq` is currently enabled.
+pub fn set_priority(irq: u32, priority: u8) {
+    // GICD_IPRIORITYR packs one byte per IRQ, so the IRQ number is
+    // directly the byte offset from the register base.
+    unsafe {
+        write_reg8(dist_base(), GICD_IPRIORITYR + irq as usize, priority);
+    }
+}
+
+/// Route SPI `irq` (`irq >= 32`) to `target`, a CPU-interface bitmask
+/// (bit N = core N). PPIs and SGIs (`irq < 32`) have a fixed, per-core
+/// target and this is a no-op for them.
+pub fn set_target(irq: u32, target: u8) {
+    if irq >= 32 {
+        unsafe {
+            write_reg8(dist_base(), GICD_ITARGETSR + irq as usize, target);
+        }
+    }
+}
+
+/// Enable `irq` at the distributor, set its priority, and for SPIs
+/// (`irq >= 32`) route it to `target` (a CPU-interface bitmask, bit N =
+/// core N). PPIs and SGIs (`irq < 32`) have a fixed, per-core target and
+/// ignore `target`.
+pub fn enable_irq(irq: u32, priority: u8, target: u8) {
+    set_priority(irq, priority);
+    set_target(irq, target);
+
+    let base = dist_base();
+    let reg_index = (irq / 32) as usize;
+    let bit = irq % 32;
+    unsafe {
+        write_reg32(base, GICD_ISENABLER + reg_index * 4, 1 << bit);
+    }
+}
+
+/// Disable `irq` at the distributor.
+pub fn disable_irq(irq: u32) {
+    let base = dist_base();
+    let reg_index = (irq / 32) as usize;
+    let bit = irq % 32;
+    unsafe {
+        write_reg32(base, GICD_ICENABLER + reg_index * 4, 1 << bit);
+    }
+}
+
+/// Acknowledge the highest-priority pending interrupt at this core's CPU
+/// interface, returning its ID ([`SPURIOUS_IRQ`] if none is pending).
+pub fn acknowledge() -> u32 {
+    unsafe { read_reg32(cpu_base(), GICC_IAR) & IAR_INTERRUPT_ID_MASK }
+}
+
+/// Signal end-of-interrupt for `irq` (the ID [`acknowledge`] returned).
+pub fn end_of_interrupt(irq: u32) {
+    unsafe {
+        write_reg32(cpu_base(), GICC_EOIR, irq);
+    }
+}
+
+/// Raise SGI `sgi_id` (0-15) on the GIC CPU-interface bit corresponding
+/// to `cpu_id`.
+pub fn send_sgi(cpu_id: u32, sgi_id: u32) -> bool {
+    if sgi_id > 15 || cpu_id > 7 {
+        return false;
+    }
+    let target_list = 1u32 << cpu_id;
+    let value = (target_list << 16) | sgi_id;
+    unsafe {
+        write_reg32(dist_base(), GICD_SGIR, value);
+    }
+    true
+}
+
+/// Non-secure physical timer PPI (`CNTP`), the standard GICv2 PPI
+/// assignment QEMU's `virt` board and most device trees use -- the IRQ
+/// [`crate::clock`]'s `aarch64_clock` arms via `cntp_tval_el0`/
+/// `cntp_ctl_el0` ends up delivering through.
+pub const TIMER_PPI: u32 = 30;
+
+/// SPI for the PL011 UART (`UART0`) on QEMU's `virt` board -- the IRQ a
+/// [`crate::drivers::serial::pl011::Pl011Uart`] would need enabled here to
+/// be driven by `handle_rx_interrupt`/`handle_tx_interrupt` instead of
+/// polled, the same way [`TIMER_PPI`] lets the timer avoid polling.
+pub const UART0_SPI: u32 = 33;
+
+/// Enable [`UART0_SPI`] at the distributor so the PL011 console can be
+/// interrupt-driven instead of polled, mirroring the role the x86 side's
+/// Local APIC plays in unmasking COM1's IRQ. Like [`TIMER_PPI`] in
+/// [`GicController::init`], this only arms delivery at the distributor --
+/// see that type's docs for why nothing can actually land yet on this
+/// architecture.
+pub fn enable_uart_irq() {
+    enable_irq(UART0_SPI, 0, 0xFF);
+}
+
+/// [`InterruptController`] backed by this GICv2 distributor/CPU interface,
+/// using the default QEMU `virt` MMIO bases until real FDT-derived ones
+/// are threaded through (matching [`init`]'s own default).
+///
+/// Note: this tree has no aarch64 exception vector table (`VBAR_EL1`)
+/// yet, so bringing this controller up does not by itself make interrupts
+/// safe to unmask -- that's a separate, larger piece of follow-up work.
+pub struct GicController;
+
+impl GicController {
+    pub fn new() -> Self {
+        GicController
+    }
+}
+
+impl InterruptController for GicController {
+    fn init(&mut self) {
+        init(DEFAULT_GICD_BASE, DEFAULT_GICC_BASE);
+        enable_irq(TIMER_PPI, 0, 0);
+        enable_uart_irq();
+    }
+
+    fn enable(&mut self, irq: u32) {
+        // SPIs need a target core mask; PPIs/SGIs (irq < 32) have a fixed
+        // per-core target and ignore it, per `enable_irq`'s own contract.
+        enable_irq(irq, 0, 0xFF);
+    }
+
+    fn eoi(&mut self, irq: u32) {
+        end_of_interrupt(irq);
+    }
+
+    fn set_timer(&mut self, deadline_ticks: u64) {
+        unsafe {
+            core::arch::asm!("msr cntp_tval_el0, {}", in(reg) deadline_ticks, options(nomem, nostack));
+            let ctl: u64 = 1; // ENABLE, unmasked
+            core::arch::asm!("msr cntp_ctl_el0, {}", in(reg) ctl, options(nomem, nostack));
+        }
+    }
+}
@@ -0,0 +1,22 @@
+pub mod ap_boot;
+pub mod coredump;
+pub mod cpu;
+pub mod fdt;
+pub mod gic;
+pub mod pmu;
+pub mod psci;
+
+use crate::arch::controller::InterruptController;
+
+/// Bring up this CPU's interrupt controller (GICv2 distributor/CPU
+/// interface).
+///
+/// Note: this does not unmask `DAIF.I` -- this tree has no aarch64
+/// exception vector table (`VBAR_EL1`) installed yet, so taking an
+/// interrupt right now would have nowhere safe to land. Once a vector
+/// table exists, enabling interrupts belongs here, mirroring
+/// `arch::x86_64::init`'s `enable_interrupts()` call.
+pub fn init() {
+    let mut controller = gic::GicController::new();
+    controller.init();
+}
@@ -0,0 +1,54 @@
+/// AArch64 Performance-Monitoring Unit Programming (best-effort)
+///
+/// Arms PMU cycle counter 0 (`PMCCNTR_EL0`) via `PMCR_EL0`/`PMCNTENSET_EL0`/
+/// `PMINTENSET_EL1` to overflow every `period` cycles, the same two's-
+/// complement priming trick `x86_64::pmu` uses.
+///
+/// Unlike x86_64's NMI, this tree's GIC driver (`arch::arm64::gic`) has no
+/// exception vector table or non-maskable/FIQ interrupt path wired up yet
+/// (see the note in `arch::arm64::init`) -- so arming this counter
+/// programs real hardware that will assert a PMU interrupt, but nothing
+/// in this tree currently handles any aarch64 interrupt, let alone one
+/// that needs to preempt code running with `DAIF.I` masked. Wiring a true
+/// NMI-equivalent profiler sample here needs that vector table built
+/// first.
+use core::arch::asm;
+
+const PMCR_E: u64 = 1 << 0;
+/// Bit 31 of `PMCNTENSET_EL0`/`PMINTENSET_EL1` selects the dedicated
+/// cycle counter rather than one of the general-purpose event counters.
+const CYCLE_COUNTER_BIT: u64 = 1 << 31;
+
+/// Arm the cycle counter to overflow after `period` cycles.
+pub fn arm(period: u64) {
+    reload_counter(period);
+    unsafe {
+        asm!("msr pmintenset_el1, {}", in(reg) CYCLE_COUNTER_BIT, options(nostack));
+        asm!("msr pmcntenset_el0, {}", in(reg) CYCLE_COUNTER_BIT, options(nostack));
+
+        let mut pmcr: u64;
+        asm!("mrs {}, pmcr_el0", out(reg) pmcr, options(nostack));
+        pmcr |= PMCR_E;
+        asm!("msr pmcr_el0, {}", in(reg) pmcr, options(nostack));
+    }
+}
+
+/// Stop counting and disable its overflow interrupt.
+pub fn disarm() {
+    unsafe {
+        asm!("msr pmcntenset_el0, {}", in(reg) 0u64, options(nostack));
+        asm!("msr pmintenset_el1, {}", in(reg) 0u64, options(nostack));
+    }
+}
+
+/// Reload the cycle counter for the next sample period.
+pub fn rearm(period: u64) {
+    reload_counter(period);
+}
+
+fn reload_counter(period: u64) {
+    let initial = 0u64.wrapping_sub(period);
+    unsafe {
+        asm!("msr pmccntr_el0, {}", in(reg) initial, options(nostack));
+    }
+}
@@ -0,0 +1,162 @@
+/// PSCI (Power State Coordination Interface) v1.0
+///
+/// Wraps the standard PSCI function IDs behind the SMC calling convention:
+/// function ID goes in w0/x0, arguments in x1-x3, and the return value comes
+/// back in x0. The conduit (SMC vs HVC) is detected once at init time from
+/// the `method` property the firmware/FDT advertises and all calls are
+/// routed through it from then on.
+
+use core::arch::asm;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// PSCI function IDs (64-bit variants, per the PSCI v1.0 spec).
+pub const PSCI_VERSION: u32 = 0x8400_0000;
+pub const PSCI_CPU_SUSPEND_AARCH64: u32 = 0xc400_0001;
+pub const PSCI_CPU_OFF: u32 = 0x8400_0002;
+pub const PSCI_CPU_ON_AARCH64: u32 = 0xc400_0003;
+pub const PSCI_AFFINITY_INFO_AARCH64: u32 = 0xc400_0004;
+pub const PSCI_MIGRATE_AARCH64: u32 = 0xc400_0005;
+pub const PSCI_SYSTEM_OFF: u32 = 0x8400_0008;
+pub const PSCI_SYSTEM_RESET: u32 = 0x8400_0009;
+
+/// PSCI return codes.
+pub const PSCI_SUCCESS: i32 = 0;
+pub const PSCI_NOT_SUPPORTED: i32 = -1;
+pub const PSCI_INVALID_PARAMETERS: i32 = -2;
+pub const PSCI_DENIED: i32 = -3;
+pub const PSCI_ALREADY_ON: i32 = -4;
+pub const PSCI_ON_PENDING: i32 = -5;
+pub const PSCI_INTERNAL_FAILURE: i32 = -6;
+pub const PSCI_NOT_PRESENT: i32 = -7;
+pub const PSCI_DISABLED: i32 = -8;
+pub const PSCI_INVALID_ADDRESS: i32 = -9;
+
+/// `AFFINITY_INFO` "affinity state" results.
+pub const AFFINITY_STATE_ON: i32 = 0;
+pub const AFFINITY_STATE_OFF: i32 = 1;
+pub const AFFINITY_STATE_ON_PENDING: i32 = 2;
+
+/// Which SMCCC conduit to use when trapping into firmware.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Conduit {
+    Smc,
+    Hvc,
+}
+
+static CONDUIT_IS_HVC: AtomicBool = AtomicBool::new(true);
+
+/// Record which conduit the platform's PSCI implementation expects, per the
+/// `method` property in the `psci` FDT node ("smc" or "hvc"). Defaults to
+/// HVC until called.
+pub fn set_conduit(conduit: Conduit) {
+    CONDUIT_IS_HVC.store(conduit == Conduit::Hvc, Ordering::Relaxed);
+}
+
+fn conduit() -> Conduit {
+    if CONDUIT_IS_HVC.load(Ordering::Relaxed) {
+        Conduit::Hvc
+    } else {
+        Conduit::Smc
+    }
+}
+
+/// Raw PSCI call through the configured conduit.
+pub fn psci_call(function_id: u32, arg0: u64, arg1: u64, arg2: u64) -> i32 {
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        let mut ret: u64 = function_id as u64;
+        match conduit() {
+            Conduit::Hvc => asm!(
+                "hvc #0",
+                inlateout("x0") ret,
+                in("x1") arg0,
+                in("x2") arg1,
+                in("x3") arg2,
+                options(nostack)
+            ),
+            Conduit::Smc => asm!(
+                "smc #0",
+                inlateout("x0") ret,
+                in("x1") arg0,
+                in("x2") arg1,
+                in("x3") arg2,
+                options(nostack)
+            ),
+        }
+        ret as i32
+    }
+
+    #[cfg(not(target_arch = "aarch64"))]
+    {
+        let _ = (function_id, arg0, arg1, arg2);
+        PSCI_NOT_SUPPORTED
+    }
+}
+
+/// Query the PSCI implementation version.
+pub fn version() -> u32 {
+    psci_call(PSCI_VERSION, 0, 0, 0) as u32
+}
+
+/// Bring a CPU online at `entry_point`, passing `context` through to it.
+pub fn cpu_on(target_cpu: u64, entry_point: u64, context: u64) -> i32 {
+    psci_call(PSCI_CPU_ON_AARCH64, target_cpu, entry_point, context)
+}
+
+/// Power down the calling CPU. Does not return on success.
+pub fn cpu_off() -> i32 {
+    psci_call(PSCI_CPU_OFF, 0, 0, 0)
+}
+
+/// Suspend the calling CPU in the power state described by `power_state`,
+/// resuming at `entry_point` with `context` on wakeup.
+pub fn cpu_suspend(power_state: u64, entry_point: u64, context: u64) -> i32 {
+    psci_call(PSCI_CPU_SUSPEND_AARCH64, power_state, entry_point, context)
+}
+
+/// Query whether `target_cpu` is on, off, or coming up at affinity `level`.
+pub fn affinity_info(target_cpu: u64, level: u64) -> i32 {
+    psci_call(PSCI_AFFINITY_INFO_AARCH64, target_cpu, level, 0)
+}
+
+/// Ask the trusted OS to migrate to `target_cpu` ahead of a CPU_OFF.
+pub fn migrate(target_cpu: u64) -> i32 {
+    psci_call(PSCI_MIGRATE_AARCH64, target_cpu, 0, 0)
+}
+
+/// Shut the whole board down. Does not return.
+pub fn system_off() -> ! {
+    psci_call(PSCI_SYSTEM_OFF, 0, 0, 0);
+    loop {
+        #[cfg(target_arch = "aarch64")]
+        unsafe {
+            asm!("wfi", options(nomem, nostack));
+        }
+    }
+}
+
+/// Reset the whole board. Does not return.
+pub fn system_reset() -> ! {
+    psci_call(PSCI_SYSTEM_RESET, 0, 0, 0);
+    loop {
+        #[cfg(target_arch = "aarch64")]
+        unsafe {
+            asm!("wfi", options(nomem, nostack));
+        }
+    }
+}
+
+/// Poll `AFFINITY_INFO` for `target_cpu` until it reports ON, or until
+/// `max_polls` attempts have been made.
+pub fn wait_for_affinity_on(target_cpu: u64, max_polls: u32) -> bool {
+    for _ in 0..max_polls {
+        if affinity_info(target_cpu, 0) == AFFINITY_STATE_ON {
+            return true;
+        }
+        #[cfg(target_arch = "aarch64")]
+        unsafe {
+            asm!("yield", options(nomem, nostack));
+        }
+    }
+    false
+}
@@ -0,0 +1,29 @@
+//! Arch-neutral interrupt controller interface: the hardware that actually
+//! routes, masks, acknowledges and re-arms interrupts (the Local APIC on
+//! x86_64, the GICv2 distributor/CPU interface on aarch64) -- distinct
+//! from the vector/exception tables in [`super::interrupts`], which decide
+//! what code runs once an interrupt has already been delivered.
+//!
+//! `kmain` picks the backend for the running architecture, calls
+//! [`InterruptController::init`] once per boot CPU, and funnels IRQ
+//! enable/EOI/timer-arm calls through the trait instead of hardcoding APIC
+//! or GIC register writes at the call site.
+
+/// A local interrupt controller, brought up once per CPU so device IRQs
+/// and the scheduler's timer tick can actually be delivered.
+pub trait InterruptController {
+    /// Bring the controller up: unmask whatever's needed to receive
+    /// interrupts at all (the LAPIC's spurious vector and LVT timer entry
+    /// on x86_64; the GIC distributor and this core's CPU interface on
+    /// aarch64).
+    fn init(&mut self);
+    /// Allow `irq` to be delivered.
+    fn enable(&mut self, irq: u32);
+    /// Acknowledge completion of `irq`, so the controller can deliver the
+    /// next interrupt at that priority.
+    fn eoi(&mut self, irq: u32);
+    /// Arm the timer interrupt to fire `deadline_ticks` ticks from now, in
+    /// whatever unit the backing hardware counts in (TSC ticks on
+    /// x86_64, `CNTFRQ_EL0` ticks on aarch64).
+    fn set_timer(&mut self, deadline_ticks: u64);
+}
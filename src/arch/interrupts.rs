@@ -15,7 +15,9 @@ pub fn init_timer() {
 
 pub extern "C" fn timer_interrupt_handler() {
     scheduler::tick();
-    
+    crate::lib_core::time::on_timer_tick();
+    crate::syscall::timer_tick(crate::clock::clock().now_ns());
+
     unsafe {
         outb(0x20, 0x20);
     }
@@ -46,6 +48,10 @@ pub struct InterruptStackFrame {
 
 pub type InterruptHandler = extern "C" fn(&mut InterruptStackFrame);
 
+/// Present, 64-bit interrupt gate, ring 0: the type/attribute byte every
+/// registered vector uses unless a handler asks for a different DPL.
+const GATE_PRESENT_INTERRUPT: u8 = 0x8E;
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 pub struct IdtEntry {
@@ -71,13 +77,18 @@ impl IdtEntry {
         }
     }
 
-    pub fn set_handler(&mut self, handler: u64) {
+    /// Point this gate at `handler`, using `selector` as the code segment
+    /// to switch to and `ist` (0 = "stay on the current stack", 1-7 =
+    /// an index into the TSS's interrupt stack table) as the stack to run
+    /// it on. `dpl` is the lowest privilege level allowed to invoke this
+    /// gate via `int`; exception/IRQ gates always want ring 0.
+    pub fn set_handler(&mut self, handler: u64, selector: u16, ist: u8, dpl: u8) {
         self.offset_low = handler as u16;
         self.offset_mid = (handler >> 16) as u16;
         self.offset_high = (handler >> 32) as u32;
-        self.selector = 0x08;
-        self.type_attr = 0x8E;
-        self.ist = 0;
+        self.selector = selector;
+        self.type_attr = GATE_PRESENT_INTERRUPT | ((dpl & 0x3) << 5);
+        self.ist = ist & 0x7;
         self.reserved = 0;
     }
 }
@@ -88,25 +99,341 @@ pub struct IdtDescriptor {
     base: u64,
 }
 
-pub static mut IDT: [IdtEntry; 256] = [IdtEntry::missing(); 256];
+/// Code segment selector every gate below is installed against. Matches
+/// the selector the bootloader hands off the kernel in (there is no
+/// kernel-owned GDT live yet; see [`crate::x86_64::gdt`]).
+const KERNEL_CODE_SELECTOR: u16 = 0x08;
 
-pub fn init_idt() {
+/// IST slot reserved for the double fault handler, so a corrupted kernel
+/// stack (the most common cause of a double fault) doesn't turn into an
+/// unrecoverable triple fault when the CPU tries to push the exception
+/// frame. Index 0 means "no dedicated stack"; IST slots are 1-7.
+const DOUBLE_FAULT_IST_INDEX: u8 = 1;
+
+/// Backing storage for the double fault's IST stack. 16 KiB is generous
+/// for a frame dump and a panic, which is all this stack ever has to do.
+const DOUBLE_FAULT_STACK_SIZE: usize = 16 * 1024;
+static mut DOUBLE_FAULT_STACK: [u8; DOUBLE_FAULT_STACK_SIZE] = [0; DOUBLE_FAULT_STACK_SIZE];
+
+/// x86 Task State Segment, long-mode layout. Only `interrupt_stack_table`
+/// is populated; `privilege_stack_table` (ring transitions) and the I/O
+/// permission bitmap aren't used by this kernel.
+#[repr(C, packed)]
+struct TaskStateSegment {
+    reserved_0: u32,
+    privilege_stack_table: [u64; 3],
+    reserved_1: u64,
+    interrupt_stack_table: [u64; 7],
+    reserved_2: u64,
+    reserved_3: u16,
+    iomap_base: u16,
+}
+
+impl TaskStateSegment {
+    const fn new() -> Self {
+        TaskStateSegment {
+            reserved_0: 0,
+            privilege_stack_table: [0; 3],
+            reserved_1: 0,
+            interrupt_stack_table: [0; 7],
+            reserved_2: 0,
+            reserved_3: 0,
+            iomap_base: 0,
+        }
+    }
+}
+
+static mut TSS: TaskStateSegment = TaskStateSegment::new();
+
+/// Point the double fault IST slot at the top of [`DOUBLE_FAULT_STACK`].
+///
+/// This only prepares the TSS in memory; actually running on this stack
+/// also requires a TSS descriptor in the GDT and an `ltr` to load it,
+/// which waits on `crate::x86_64::gdt` growing a live TSS descriptor the
+/// same way `init_gdt`/`init_pic` below are still no-ops.
+fn init_tss() {
     unsafe {
-        IDT[32].set_handler(timer_interrupt_wrapper as u64);
-        
-        let idt_descriptor = IdtDescriptor {
-            limit: (core::mem::size_of::<[IdtEntry; 256]>() - 1) as u16,
-            base: IDT.as_ptr() as u64,
-        };
-        
-        core::arch::asm!(
-            "lidt [{}]",
-            in(reg) &idt_descriptor,
-            options(readonly, nostack)
-        );
+        let stack_top = core::ptr::addr_of!(DOUBLE_FAULT_STACK) as u64 + DOUBLE_FAULT_STACK_SIZE as u64;
+        TSS.interrupt_stack_table[(DOUBLE_FAULT_IST_INDEX - 1) as usize] = stack_top;
+    }
+}
+
+pub static mut IDT: [IdtEntry; 256] = [IdtEntry::missing(); 256];
+
+/// Thin wrapper around the raw [`IDT`] array giving vectors names instead
+/// of indices; mirrors [`crate::x86_64::gdt::Gdt`]'s `new()`/`load()` shape.
+pub struct InterruptDescriptorTable;
+
+impl InterruptDescriptorTable {
+    /// Register `handler` at `vector`, on `selector`, running on IST slot
+    /// `ist` (0 for "current stack"), callable from privilege level `dpl`.
+    pub fn set_handler(vector: u8, handler: u64, selector: u16, ist: u8, dpl: u8) {
+        unsafe {
+            IDT[vector as usize].set_handler(handler, selector, ist, dpl);
+        }
+    }
+
+    /// Load this table into `IDTR` via `lidt`.
+    pub fn load() {
+        unsafe {
+            let idt_descriptor = IdtDescriptor {
+                limit: (core::mem::size_of::<[IdtEntry; 256]>() - 1) as u16,
+                base: IDT.as_ptr() as u64,
+            };
+
+            core::arch::asm!(
+                "lidt [{}]",
+                in(reg) &idt_descriptor,
+                options(readonly, nostack)
+            );
+        }
     }
 }
 
+pub fn init_idt() {
+    init_tss();
+    register_exception_handlers();
+
+    // Overrides the #DB/#BP gates `register_exception_handlers` just
+    // installed with the gdbstub's trampolines, so a debug exception drops
+    // into the remote debug session instead of panicking.
+    crate::x86_64::gdbstub::init();
+
+    // Overrides the generated `nmi` stub (which unconditionally panics)
+    // with `cpu::profiler`'s sample handler, which only falls through to
+    // that same panic for an NMI it doesn't recognize as its own.
+    InterruptDescriptorTable::set_handler(
+        2,
+        crate::x86_64::pmu::nmi_handler as u64,
+        KERNEL_CODE_SELECTOR,
+        0,
+        0,
+    );
+
+    // Overrides the generated `page_fault` stub (which unconditionally
+    // panics) with the real copy-on-write fault resolver; a fault that
+    // isn't a resolvable COW share still falls through to that same panic.
+    InterruptDescriptorTable::set_handler(
+        14,
+        page_fault_handler as u64,
+        KERNEL_CODE_SELECTOR,
+        0,
+        0,
+    );
+
+    InterruptDescriptorTable::set_handler(32, timer_interrupt_wrapper as u64, KERNEL_CODE_SELECTOR, 0, 0);
+    InterruptDescriptorTable::load();
+}
+
 extern "C" fn timer_interrupt_wrapper() {
     timer_interrupt_handler();
 }
+
+/// The 32 CPU-reserved exception vectors, their mnemonic (used in the
+/// panic message), and whether the CPU pushes an error code below the
+/// usual `InterruptStackFrame` for that vector. Reserved vectors (15,
+/// 22-27, 31) are included so every slot from 0-31 gets a real handler
+/// instead of falling through to the empty [`IdtEntry::missing`] gate.
+const EXCEPTIONS: [(u8, &str, bool); 32] = [
+    (0, "divide error", false),
+    (1, "debug", false),
+    (2, "non-maskable interrupt", false),
+    (3, "breakpoint", false),
+    (4, "overflow", false),
+    (5, "bound range exceeded", false),
+    (6, "invalid opcode", false),
+    (7, "device not available", false),
+    (8, "double fault", true),
+    (9, "coprocessor segment overrun", false),
+    (10, "invalid TSS", true),
+    (11, "segment not present", true),
+    (12, "stack-segment fault", true),
+    (13, "general protection fault", true),
+    (14, "page fault", true),
+    (15, "reserved", false),
+    (16, "x87 floating-point exception", false),
+    (17, "alignment check", true),
+    (18, "machine check", false),
+    (19, "SIMD floating-point exception", false),
+    (20, "virtualization exception", false),
+    (21, "control protection exception", true),
+    (22, "reserved", false),
+    (23, "reserved", false),
+    (24, "reserved", false),
+    (25, "reserved", false),
+    (26, "reserved", false),
+    (27, "reserved", false),
+    (28, "hypervisor injection exception", false),
+    (29, "VMM communication exception", true),
+    (30, "security exception", true),
+    (31, "reserved", false),
+];
+
+/// Generates one `extern "x86-interrupt"` stub per exception vector so
+/// each carries its own vector number rather than trying to recover it
+/// from a shared handler at fault time. `$has_error_code` picks which of
+/// the two CPU-defined call shapes the stub is written against.
+macro_rules! exception_stub {
+    ($name:ident, $vector:expr, false) => {
+        extern "x86-interrupt" fn $name(frame: InterruptStackFrame) {
+            panic_with_context($vector, &frame, None);
+        }
+    };
+    ($name:ident, $vector:expr, true) => {
+        extern "x86-interrupt" fn $name(frame: InterruptStackFrame, error_code: u64) {
+            panic_with_context($vector, &frame, Some(error_code));
+        }
+    };
+}
+
+exception_stub!(divide_error, 0, false);
+exception_stub!(debug_exception, 1, false);
+exception_stub!(nmi, 2, false);
+exception_stub!(breakpoint, 3, false);
+exception_stub!(overflow, 4, false);
+exception_stub!(bound_range_exceeded, 5, false);
+exception_stub!(invalid_opcode, 6, false);
+exception_stub!(device_not_available, 7, false);
+exception_stub!(double_fault, 8, true);
+exception_stub!(coprocessor_segment_overrun, 9, false);
+exception_stub!(invalid_tss, 10, true);
+exception_stub!(segment_not_present, 11, true);
+exception_stub!(stack_segment_fault, 12, true);
+exception_stub!(general_protection_fault, 13, true);
+exception_stub!(page_fault, 14, true);
+exception_stub!(reserved_15, 15, false);
+exception_stub!(x87_floating_point_exception, 16, false);
+exception_stub!(alignment_check, 17, true);
+exception_stub!(machine_check, 18, false);
+exception_stub!(simd_floating_point_exception, 19, false);
+exception_stub!(virtualization_exception, 20, false);
+exception_stub!(control_protection_exception, 21, true);
+exception_stub!(reserved_22, 22, false);
+exception_stub!(reserved_23, 23, false);
+exception_stub!(reserved_24, 24, false);
+exception_stub!(reserved_25, 25, false);
+exception_stub!(reserved_26, 26, false);
+exception_stub!(reserved_27, 27, false);
+exception_stub!(hypervisor_injection_exception, 28, false);
+exception_stub!(vmm_communication_exception, 29, true);
+exception_stub!(security_exception, 30, true);
+exception_stub!(reserved_31, 31, false);
+
+/// Address of the stub generated above for `vector`, in IDT order.
+const EXCEPTION_HANDLERS: [u64; 32] = [
+    divide_error as u64,
+    debug_exception as u64,
+    nmi as u64,
+    breakpoint as u64,
+    overflow as u64,
+    bound_range_exceeded as u64,
+    invalid_opcode as u64,
+    device_not_available as u64,
+    double_fault as u64,
+    coprocessor_segment_overrun as u64,
+    invalid_tss as u64,
+    segment_not_present as u64,
+    stack_segment_fault as u64,
+    general_protection_fault as u64,
+    page_fault as u64,
+    reserved_15 as u64,
+    x87_floating_point_exception as u64,
+    alignment_check as u64,
+    machine_check as u64,
+    simd_floating_point_exception as u64,
+    virtualization_exception as u64,
+    control_protection_exception as u64,
+    reserved_22 as u64,
+    reserved_23 as u64,
+    reserved_24 as u64,
+    reserved_25 as u64,
+    reserved_26 as u64,
+    reserved_27 as u64,
+    hypervisor_injection_exception as u64,
+    vmm_communication_exception as u64,
+    security_exception as u64,
+    reserved_31 as u64,
+];
+
+/// Register the generated stub for every vector in [`EXCEPTIONS`], giving
+/// the double fault vector its own IST stack so a stack overflow in the
+/// kernel still reaches [`panic_with_context`] instead of triple-faulting.
+fn register_exception_handlers() {
+    for &(vector, _name, _has_error_code) in EXCEPTIONS.iter() {
+        let ist = if vector == 8 { DOUBLE_FAULT_IST_INDEX } else { 0 };
+        InterruptDescriptorTable::set_handler(
+            vector,
+            EXCEPTION_HANDLERS[vector as usize],
+            KERNEL_CODE_SELECTOR,
+            ist,
+            0,
+        );
+    }
+}
+
+/// Print the faulting register/RIP context and hand off to the kernel's
+/// panic path. Never returns: there is no safe way to resume execution
+/// after an unhandled CPU exception.
+fn panic_with_context(vector: u8, frame: &InterruptStackFrame, error_code: Option<u64>) -> ! {
+    let name = EXCEPTIONS
+        .iter()
+        .find(|(v, _, _)| *v == vector)
+        .map(|(_, name, _)| *name)
+        .unwrap_or("unknown exception");
+
+    println!("EXCEPTION: {} (vector {})", name, vector);
+    println!(
+        "  rip={:#018x} cs={:#06x} flags={:#018x} rsp={:#018x} ss={:#06x}",
+        frame.instruction_pointer,
+        frame.code_segment,
+        frame.cpu_flags,
+        frame.stack_pointer,
+        frame.stack_segment
+    );
+    if let Some(code) = error_code {
+        println!("  error_code={:#018x}", code);
+    }
+
+    panic!("unhandled CPU exception: {}", name);
+}
+
+/// Real `#PF` handler, installed over the generated `page_fault` stub the
+/// same way `init_idt` overrides `nmi`/`#DB`/`#BP`: reads the faulting
+/// address from CR2 and, if the current thread's process has a
+/// copy-on-write leaf entry there, resolves it via
+/// `X86_64PageTable::resolve_cow_fault` instead of panicking. Anything
+/// else (no current process, not present, not COW) falls through to the
+/// same unhandled-exception path every other vector takes.
+extern "x86-interrupt" fn page_fault_handler(frame: InterruptStackFrame, error_code: u64) {
+    let resolved = (|| -> Option<()> {
+        let tid = scheduler::current_thread()?;
+        let thread = crate::process::thread::get_thread(tid)?;
+        let process = crate::process::get_process(thread.process_id)?;
+        let addr = crate::memory::VirtAddr::new(unsafe { read_cr2() });
+        let mut table =
+            crate::memory::paging::X86_64PageTable::new(process.address_space.page_table_frame);
+        if table.resolve_cow_fault(addr) {
+            Some(())
+        } else {
+            None
+        }
+    })();
+
+    if resolved.is_none() {
+        panic_with_context(14, &frame, Some(error_code));
+    }
+}
+
+unsafe fn read_cr2() -> u64 {
+    let value: u64;
+    core::arch::asm!("mov {}, cr2", out(reg) value, options(nomem, nostack));
+    value
+}
+
+/// Entry point for an NMI that `cpu::profiler` doesn't recognize as one of
+/// its own sample interrupts (profiling isn't running). A watchdog timeout
+/// or a machine-check reported over NMI still needs to reach the same
+/// unhandled-exception path every other vector does.
+pub(crate) fn panic_on_stray_nmi(frame: &InterruptStackFrame) -> ! {
+    panic_with_context(2, frame, None);
+}
@@ -1,18 +1,160 @@
 /// IPI (Inter-Processor Interrupt) Support
-/// 
-/// Provides unified interface for sending IPIs across different architectures
+///
+/// Provides a unified, architecture-independent interface for sending IPIs:
+/// lowers to the LAPIC ICR on x86_64 and to GIC software-generated
+/// interrupts (SGIs) on aarch64. On top of plain delivery this gives the
+/// scheduler and VM layers the cross-core primitives they need --
+/// rescheduling, running a function on a remote core, and shooting down a
+/// remote TLB range.
+use crate::cpu::percpu::{self, MAX_CPUS};
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 
+/// Reserved IPI vectors/kinds.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum IpiKind {
+    /// Ask the target to re-evaluate its run queue.
     Reschedule,
-    Shutdown,
-    Halt,
-    Timer,
-    Call,
+    /// Run the closure queued in the target's call-function mailbox.
+    CallFunction,
+    /// Invalidate the virtual range queued in the target's TLB mailbox.
+    TlbShootdown,
+    /// Halt the target core (used when panicking).
+    Stop,
 }
 
+impl IpiKind {
+    #[cfg(target_arch = "x86_64")]
+    fn vector(self) -> u8 {
+        match self {
+            IpiKind::Reschedule => 0x50,
+            IpiKind::CallFunction => 0x51,
+            IpiKind::TlbShootdown => 0x52,
+            IpiKind::Stop => 0x53,
+        }
+    }
+}
+
+/// A pending `CALL_FUNCTION` request: a plain `fn()` pointer (no closure
+/// captures, so it's `Copy` and safe to park in a static mailbox) plus an
+/// acknowledgement flag the sender can optionally spin on.
+struct CallMailbox {
+    func: AtomicUsize, // fn() as usize, 0 = empty
+    acked: AtomicBool,
+}
+
+/// A pending `TLB_SHOOTDOWN` request: the virtual range to invalidate.
+struct TlbMailbox {
+    start: AtomicU64,
+    len: AtomicU64,
+    pending: AtomicBool,
+}
+
+const CALL_MAILBOX_INIT: CallMailbox = CallMailbox {
+    func: AtomicUsize::new(0),
+    acked: AtomicBool::new(true),
+};
+const TLB_MAILBOX_INIT: TlbMailbox = TlbMailbox {
+    start: AtomicU64::new(0),
+    len: AtomicU64::new(0),
+    pending: AtomicBool::new(false),
+};
+
+static CALL_MAILBOXES: [CallMailbox; MAX_CPUS] = [CALL_MAILBOX_INIT; MAX_CPUS];
+static TLB_MAILBOXES: [TlbMailbox; MAX_CPUS] = [TLB_MAILBOX_INIT; MAX_CPUS];
+
+/// Queue a function to run on `target_cpu` and fire the IPI. If `wait` is
+/// set, spin until the target acknowledges having run it.
+pub fn call_function_on(target_cpu: u32, func: fn(), wait: bool) -> bool {
+    let Some(mailbox) = CALL_MAILBOXES.get(target_cpu as usize) else {
+        return false;
+    };
+    mailbox.acked.store(false, Ordering::SeqCst);
+    mailbox.func.store(func as usize, Ordering::SeqCst);
+
+    if !send_ipi(target_cpu, IpiKind::CallFunction) {
+        return false;
+    }
+
+    if wait {
+        while !mailbox.acked.load(Ordering::Acquire) {
+            core::hint::spin_loop();
+        }
+    }
+    true
+}
+
+/// Queue a TLB range invalidation for `target_cpu` and fire the IPI.
+pub fn tlb_shootdown(target_cpu: u32, start: u64, len: u64) -> bool {
+    let Some(mailbox) = TLB_MAILBOXES.get(target_cpu as usize) else {
+        return false;
+    };
+    mailbox.start.store(start, Ordering::SeqCst);
+    mailbox.len.store(len, Ordering::SeqCst);
+    mailbox.pending.store(true, Ordering::Release);
+
+    send_ipi(target_cpu, IpiKind::TlbShootdown)
+}
+
+/// IPI handler for `CALL_FUNCTION`: run the queued function (if any) and
+/// acknowledge it. Called from the architecture's IPI interrupt stub.
+pub fn handle_call_function(cpu_id: u32) {
+    let Some(mailbox) = CALL_MAILBOXES.get(cpu_id as usize) else {
+        return;
+    };
+    let raw = mailbox.func.swap(0, Ordering::SeqCst);
+    if raw != 0 {
+        let func: fn() = unsafe { core::mem::transmute(raw) };
+        func();
+    }
+    mailbox.acked.store(true, Ordering::Release);
+}
+
+/// IPI handler for `TLB_SHOOTDOWN`: invalidate the queued virtual range on
+/// this core (`invlpg` on x86_64, `tlbi vae1` on aarch64).
+pub fn handle_tlb_shootdown(cpu_id: u32) {
+    let Some(mailbox) = TLB_MAILBOXES.get(cpu_id as usize) else {
+        return;
+    };
+    if !mailbox.pending.swap(false, Ordering::Acquire) {
+        return;
+    }
+    let start = mailbox.start.load(Ordering::SeqCst);
+    let len = mailbox.len.load(Ordering::SeqCst);
+    invalidate_range(start, len);
+}
+
+#[cfg(target_arch = "x86_64")]
+fn invalidate_range(start: u64, len: u64) {
+    let page_size = 4096u64;
+    let mut addr = start & !(page_size - 1);
+    while addr < start + len {
+        unsafe {
+            core::arch::asm!("invlpg [{}]", in(reg) addr, options(nostack));
+        }
+        addr += page_size;
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+fn invalidate_range(start: u64, len: u64) {
+    let page_size = 4096u64;
+    let mut addr = start & !(page_size - 1);
+    while addr < start + len {
+        unsafe {
+            core::arch::asm!("tlbi vae1, {}", in(reg) addr >> 12, options(nostack));
+        }
+        addr += page_size;
+    }
+    unsafe {
+        core::arch::asm!("dsb ish", "isb", options(nostack));
+    }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn invalidate_range(_start: u64, _len: u64) {}
+
 /// Send an IPI to a specific CPU
-/// 
+///
 /// Returns true if the IPI was sent successfully
 pub fn send_ipi(cpu_id: u32, kind: IpiKind) -> bool {
     #[cfg(target_arch = "x86_64")]
@@ -25,44 +167,53 @@ pub fn send_ipi(cpu_id: u32, kind: IpiKind) -> bool {
     }
     #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
     {
+        let _ = (cpu_id, kind);
         false
     }
 }
 
+/// Send an IPI to every CPU in `mask`. Returns the number of CPUs it was
+/// sent to.
+pub fn send_ipi_mask(mask: &[u32], kind: IpiKind) -> u32 {
+    let mut sent = 0;
+    for &cpu_id in mask {
+        if send_ipi(cpu_id, kind) {
+            sent += 1;
+        }
+    }
+    sent
+}
+
+/// Send an IPI to every online CPU except the caller.
+pub fn broadcast_ipi(kind: IpiKind) -> u32 {
+    let me = percpu::get_current_cpu_id();
+    let mut count = 0u32;
+    percpu::for_each_online_cpu(|cpu_id| {
+        if cpu_id != me && send_ipi(cpu_id, kind) {
+            count += 1;
+        }
+    });
+    count
+}
+
 #[cfg(target_arch = "x86_64")]
 fn send_ipi_x86_64(cpu_id: u32, kind: IpiKind) -> bool {
     use crate::x86_64::cpu;
-    
-    let vector = match kind {
-        IpiKind::Reschedule => 0x50,  // Custom vector for reschedule
-        IpiKind::Shutdown => 0x51,    // Custom vector for shutdown
-        IpiKind::Halt => 0x52,        // Custom vector for halt
-        IpiKind::Timer => 0x53,       // Custom vector for timer
-        IpiKind::Call => 0x54,        // Custom vector for function call
-    };
-    
-    cpu::send_ipi(cpu_id, vector);
+
+    cpu::send_ipi(cpu_id, kind.vector() as u32);
     true
 }
 
 #[cfg(target_arch = "aarch64")]
-fn send_ipi_arm64(cpu_id: u32, _kind: IpiKind) -> bool {
-    use crate::arch::arm64::cpu;
-    
-    let gic_ipi_vector = 0;  // Simplified - would use actual GIC IPI mechanism
-    
-    // GIC IPI would go here
-    // This is a placeholder
-    false
-}
+fn send_ipi_arm64(cpu_id: u32, kind: IpiKind) -> bool {
+    // GIC SGIs carry an 4-bit INTID (0-15); map each IpiKind to one and
+    // target the GIC redistributor affinity for `cpu_id`'s MPIDR.
+    let sgi_id = match kind {
+        IpiKind::Reschedule => 0,
+        IpiKind::CallFunction => 1,
+        IpiKind::TlbShootdown => 2,
+        IpiKind::Stop => 3,
+    };
 
-pub fn broadcast_ipi(_kind: IpiKind) -> u32 {
-    // Send IPI to all CPUs
-    // Returns count of CPUs that received the IPI
-    let count = 0u32;
-    
-    // This would iterate over all online CPUs and send to each
-    // Implementation depends on having a CPU list available
-    
-    count
+    crate::arch::arm64::gic::send_sgi(cpu_id, sgi_id)
 }
@@ -1,12 +1,28 @@
 pub mod x86_64;
 pub mod arm64;
+pub mod controller;
 pub mod interrupts;
 pub mod ipi;
 
 #[cfg(target_arch = "x86_64")]
 pub use x86_64::*;
 
+/// First timer deadline armed before any interrupt has fired;
+/// `crate::lib_core::time::on_timer_tick` (driven by the scheduler's
+/// periodic `tick()`) is responsible for re-arming the next one from
+/// inside the handler itself.
+const FIRST_TICK_NS: u64 = 10_000_000;
+
+/// Calibrate the clock, bring up this CPU's interrupt controller and
+/// vector table, then arm the first timer deadline.
 pub fn init() {
+    crate::clock::init();
+
     #[cfg(target_arch = "x86_64")]
     x86_64::init();
+
+    #[cfg(target_arch = "aarch64")]
+    arm64::init();
+
+    crate::clock::clock().set_deadline(FIRST_TICK_NS);
 }
@@ -1,4 +1,9 @@
 use core::arch::asm;
+use crate::arch::controller::InterruptController;
+
+/// IDT vector the LAPIC's LVT timer entry and `arch::interrupts::init_idt`
+/// both point at.
+const TIMER_VECTOR: u32 = 32;
 
 pub fn init() {
     init_gdt();
@@ -8,15 +13,25 @@ pub fn init() {
 }
 
 pub fn init_gdt() {
-    
+    // The bootloader's GDT is reused as-is; see `arch::interrupts::init_tss`'s
+    // doc comment for the TSS descriptor this still needs before the double
+    // fault IST stack can actually be used.
 }
 
+/// Install the real IDT/exception handlers, then bring up the Local APIC
+/// as this CPU's interrupt controller and unmask its timer entry.
 pub fn init_idt() {
-    
+    crate::arch::interrupts::init_idt();
+
+    let mut controller = crate::x86_64::lapic::ApicController::new();
+    controller.init();
+    controller.enable(TIMER_VECTOR);
 }
 
 pub fn init_pic() {
-    
+    // No legacy 8259 PIC remap/mask is needed: interrupts are routed
+    // through the Local APIC (`arch::x86_64::lapic::ApicController`)
+    // brought up in `init_idt` above, not the PIC.
 }
 
 pub fn enable_interrupts() {
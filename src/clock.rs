@@ -0,0 +1,222 @@
+//! Monotonic time sources
+//!
+//! `ClockSource` is the arch-neutral interface the scheduler uses to
+//! convert elapsed cycles into nanoseconds and to arm the next timer
+//! interrupt at a precise deadline instead of relying on a fixed periodic
+//! tick. `x86_64` backs it with the TSC, calibrated once at `init()`
+//! against a reference duration, driving the LAPIC's TSC-deadline mode;
+//! `aarch64` backs it with the ARM generic timer, whose frequency is
+//! reported directly by `CNTFRQ_EL0`.
+
+/// A monotonic clock capable of reporting elapsed time and arming the next
+/// timer interrupt, implemented per-architecture so the scheduler can
+/// charge CPU time and schedule deadlines without knowing which hardware
+/// clock backs it.
+pub trait ClockSource: Send + Sync {
+    /// Nanoseconds elapsed since this clock was calibrated.
+    fn now_ns(&self) -> u64;
+    /// Arm the next timer interrupt to fire `ns` nanoseconds from now.
+    fn set_deadline(&self, ns: u64);
+}
+
+/// The active backend's `ClockSource`, selected at compile time.
+pub fn clock() -> &'static dyn ClockSource {
+    #[cfg(target_arch = "x86_64")]
+    {
+        &x86_64_clock::CLOCK
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        &aarch64_clock::CLOCK
+    }
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        &NULL_CLOCK
+    }
+}
+
+/// Calibrate the active backend's clock. Must run once, early in
+/// architecture init, before `clock()` reports useful values.
+pub fn init() {
+    #[cfg(target_arch = "x86_64")]
+    x86_64_clock::init();
+    #[cfg(target_arch = "aarch64")]
+    aarch64_clock::init();
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+struct NullClock;
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+static NULL_CLOCK: NullClock = NullClock;
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+impl ClockSource for NullClock {
+    fn now_ns(&self) -> u64 {
+        0
+    }
+    fn set_deadline(&self, _ns: u64) {}
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86_64_clock {
+    use super::ClockSource;
+    use crate::x86_64::cpu;
+    use core::arch::asm;
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    /// TSC-deadline mode MSR: writing it arms the LAPIC timer to fire an
+    /// interrupt once the TSC reaches the written value.
+    const IA32_TSC_DEADLINE: u32 = 0x6E0;
+
+    #[inline(always)]
+    fn rdtsc() -> u64 {
+        unsafe {
+            let mut lo: u32;
+            let mut hi: u32;
+            asm!("rdtsc", out("eax") lo, out("edx") hi, options(nomem, nostack));
+            ((hi as u64) << 32) | (lo as u64)
+        }
+    }
+
+    /// TSC-backed `ClockSource`. `freq_hz` of `0` means "not yet
+    /// calibrated"; `now_ns`/`set_deadline` both no-op in that state
+    /// rather than dividing by zero or arming a bogus deadline.
+    pub struct TscClock {
+        freq_hz: AtomicU64,
+        epoch: AtomicU64,
+    }
+
+    impl TscClock {
+        pub const fn new() -> Self {
+            Self {
+                freq_hz: AtomicU64::new(0),
+                epoch: AtomicU64::new(0),
+            }
+        }
+
+        /// Derive TSC frequency from `elapsed_ticks` measured over a
+        /// `reference_ns`-nanosecond window against a known-good
+        /// reference, and latch the current TSC value as the zero point
+        /// for `now_ns`.
+        pub fn calibrate(&self, reference_ns: u64, elapsed_ticks: u64) {
+            let freq_hz = if reference_ns == 0 {
+                0
+            } else {
+                elapsed_ticks.saturating_mul(1_000_000_000) / reference_ns
+            };
+            self.freq_hz.store(freq_hz, Ordering::Relaxed);
+            self.epoch.store(rdtsc(), Ordering::Relaxed);
+        }
+    }
+
+    impl ClockSource for TscClock {
+        fn now_ns(&self) -> u64 {
+            let freq_hz = self.freq_hz.load(Ordering::Relaxed);
+            if freq_hz == 0 {
+                return 0;
+            }
+            let elapsed_ticks = rdtsc().wrapping_sub(self.epoch.load(Ordering::Relaxed));
+            elapsed_ticks.saturating_mul(1_000_000_000) / freq_hz
+        }
+
+        fn set_deadline(&self, ns: u64) {
+            let freq_hz = self.freq_hz.load(Ordering::Relaxed);
+            if freq_hz == 0 {
+                return;
+            }
+            let ticks_from_now = ns.saturating_mul(freq_hz) / 1_000_000_000;
+            let deadline = rdtsc().saturating_add(ticks_from_now);
+            cpu::write_msr(IA32_TSC_DEADLINE, deadline);
+        }
+    }
+
+    pub static CLOCK: TscClock = TscClock::new();
+
+    /// Calibrate `CLOCK` by busy-spinning a fixed iteration count and
+    /// measuring TSC ticks across it. This tree has no PIT/HPET channel
+    /// wired up as a reference timer yet, so the spin count itself stands
+    /// in for `reference_ns` until one is available.
+    pub fn init() {
+        const REFERENCE_NS: u64 = 10_000_000;
+        const SPIN_ITERATIONS: u64 = 10_000_000;
+
+        let start = rdtsc();
+        for _ in 0..SPIN_ITERATIONS {
+            core::hint::spin_loop();
+        }
+        let elapsed_ticks = rdtsc().wrapping_sub(start);
+        CLOCK.calibrate(REFERENCE_NS, elapsed_ticks);
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod aarch64_clock {
+    use super::ClockSource;
+    use core::arch::asm;
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    #[inline(always)]
+    fn read_cntvct() -> u64 {
+        let val: u64;
+        unsafe {
+            asm!("mrs {}, cntvct_el0", out(reg) val, options(nomem, nostack));
+        }
+        val
+    }
+
+    #[inline(always)]
+    fn read_cntfrq() -> u64 {
+        let val: u64;
+        unsafe {
+            asm!("mrs {}, cntfrq_el0", out(reg) val, options(nomem, nostack));
+        }
+        val
+    }
+
+    /// ARM generic-timer-backed `ClockSource`. Unlike the TSC, its
+    /// frequency is reported directly by `CNTFRQ_EL0` (set by firmware),
+    /// so no busy-wait calibration against a second reference is needed.
+    pub struct GenericTimerClock {
+        freq_hz: AtomicU64,
+        epoch: AtomicU64,
+    }
+
+    impl GenericTimerClock {
+        pub const fn new() -> Self {
+            Self {
+                freq_hz: AtomicU64::new(0),
+                epoch: AtomicU64::new(0),
+            }
+        }
+    }
+
+    impl ClockSource for GenericTimerClock {
+        fn now_ns(&self) -> u64 {
+            let freq_hz = self.freq_hz.load(Ordering::Relaxed);
+            if freq_hz == 0 {
+                return 0;
+            }
+            let elapsed = read_cntvct().wrapping_sub(self.epoch.load(Ordering::Relaxed));
+            elapsed.saturating_mul(1_000_000_000) / freq_hz
+        }
+
+        fn set_deadline(&self, ns: u64) {
+            let freq_hz = self.freq_hz.load(Ordering::Relaxed);
+            if freq_hz == 0 {
+                return;
+            }
+            let ticks = ns.saturating_mul(freq_hz) / 1_000_000_000;
+            unsafe {
+                asm!("msr cntp_tval_el0, {}", in(reg) ticks, options(nomem, nostack));
+                let ctl: u64 = 1; // ENABLE, unmasked
+                asm!("msr cntp_ctl_el0, {}", in(reg) ctl, options(nomem, nostack));
+            }
+        }
+    }
+
+    pub static CLOCK: GenericTimerClock = GenericTimerClock::new();
+
+    pub fn init() {
+        CLOCK.freq_hz.store(read_cntfrq(), Ordering::Relaxed);
+        CLOCK.epoch.store(read_cntvct(), Ordering::Relaxed);
+    }
+}
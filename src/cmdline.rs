@@ -0,0 +1,43 @@
+//! Bootloader-supplied kernel command-line parser.
+//!
+//! The command line is a single whitespace-separated string of `key=value`
+//! tokens (e.g. `init=/bin/sh initrd=initramfs.cpio quiet`), in the style of
+//! the Linux kernel's own cmdline grammar. A bare token with no `=` is kept
+//! as a valueless flag, queryable through [`Cmdline::has`].
+use alloc::vec::Vec;
+
+/// A parsed command line, borrowing its tokens from the original string
+/// rather than copying them -- the raw line usually already lives in a
+/// `'static` buffer the bootloader handed over.
+#[derive(Debug, Clone)]
+pub struct Cmdline<'a> {
+    tokens: Vec<(&'a str, Option<&'a str>)>,
+}
+
+impl<'a> Cmdline<'a> {
+    /// Split `line` on whitespace and each token on its first `=`.
+    pub fn parse(line: &'a str) -> Self {
+        let tokens = line
+            .split_whitespace()
+            .map(|token| match token.split_once('=') {
+                Some((key, value)) => (key, Some(value)),
+                None => (token, None),
+            })
+            .collect();
+        Cmdline { tokens }
+    }
+
+    /// The value of the last token named `key`, or `None` if `key` never
+    /// appears or only appears as a valueless flag.
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.tokens
+            .iter()
+            .rev()
+            .find_map(|(k, v)| (*k == key).then_some(*v).flatten())
+    }
+
+    /// Whether `key` appears at all, with or without a value.
+    pub fn has(&self, key: &str) -> bool {
+        self.tokens.iter().any(|(k, _)| *k == key)
+    }
+}
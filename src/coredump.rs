@@ -0,0 +1,169 @@
+//! ELF64 core-dump emitter, streamed out the serial console on panic.
+//!
+//! Modeled on the standard ELF core file format `gdb`/`readelf` already
+//! understand: an `ET_CORE` header, a single `PT_NOTE` segment holding one
+//! `NT_PRSTATUS` note per CPU, and one `PT_LOAD` segment per mapped
+//! [`MemoryRegion`]. [`CpuElf64Writable`] is the only arch-specific surface
+//! -- x86_64 and aarch64 each know their own register layout and how to
+//! read it, but the container format around it is identical.
+
+use alloc::vec::Vec;
+
+use crate::drivers::serial::SERIAL1;
+use crate::memory::MemoryRegion;
+
+const EI_NIDENT: usize = 16;
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const EV_CURRENT: u8 = 1;
+const ET_CORE: u16 = 4;
+
+pub const EM_X86_64: u16 = 62;
+pub const EM_AARCH64: u16 = 183;
+
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const PF_R: u32 = 4;
+const PF_W: u32 = 2;
+const PF_X: u32 = 1;
+
+/// `NT_PRSTATUS`: the note type carrying a `struct elf_prstatus` (general
+/// purpose registers) in a standard core file.
+pub const NT_PRSTATUS: u32 = 1;
+
+/// Size of a `Elf64_Ehdr`.
+const EHDR_SIZE: u64 = 64;
+/// Size of a `Elf64_Phdr`.
+const PHDR_SIZE: u64 = 56;
+
+/// Per-architecture hook into the core writer: each backend knows its own
+/// `user_regs_struct`-equivalent layout and how to read the registers it
+/// was asked to save for `cpu_id`.
+pub trait CpuElf64Writable {
+    /// `e_machine` value for this architecture (`EM_X86_64`, `EM_AARCH64`).
+    fn elf_machine(&self) -> u16;
+
+    /// Append one `NT_PRSTATUS` note for `cpu_id` to `out`, in this
+    /// architecture's native register order.
+    fn write_prstatus(&self, out: &mut Vec<u8>, cpu_id: u32);
+
+    /// Append the raw bytes backing `region` to `out`. There is no
+    /// userspace/kernel divide to cross here -- a `PT_LOAD` segment's
+    /// contents are just the region's physical memory, read directly.
+    fn write_mem_regions(&self, out: &mut Vec<u8>, region: &MemoryRegion);
+}
+
+/// Wrap `name` (padded to a 4-byte boundary) and `desc` into one ELF note
+/// (`Elf64_Nhdr` + name + desc, each 4-byte aligned per the note format).
+pub fn write_note(out: &mut Vec<u8>, name: &[u8], note_type: u32, desc: &[u8]) {
+    let namesz = (name.len() + 1) as u32; // +1 for the NUL terminator
+    out.extend_from_slice(&namesz.to_le_bytes());
+    out.extend_from_slice(&(desc.len() as u32).to_le_bytes());
+    out.extend_from_slice(&note_type.to_le_bytes());
+
+    out.extend_from_slice(name);
+    out.push(0);
+    pad_to_4(out);
+
+    out.extend_from_slice(desc);
+    pad_to_4(out);
+}
+
+fn pad_to_4(out: &mut Vec<u8>) {
+    while out.len() % 4 != 0 {
+        out.push(0);
+    }
+}
+
+fn write_ehdr(out: &mut Vec<u8>, e_machine: u16, e_phnum: u16) {
+    let mut ident = [0u8; EI_NIDENT];
+    ident[0..4].copy_from_slice(b"\x7FELF");
+    ident[4] = ELFCLASS64;
+    ident[5] = ELFDATA2LSB;
+    ident[6] = EV_CURRENT;
+
+    out.extend_from_slice(&ident);
+    out.extend_from_slice(&ET_CORE.to_le_bytes());
+    out.extend_from_slice(&e_machine.to_le_bytes());
+    out.extend_from_slice(&(EV_CURRENT as u32).to_le_bytes());
+    out.extend_from_slice(&0u64.to_le_bytes()); // e_entry: none, this is a core file
+    out.extend_from_slice(&EHDR_SIZE.to_le_bytes()); // e_phoff
+    out.extend_from_slice(&0u64.to_le_bytes()); // e_shoff: no section headers
+    out.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+    out.extend_from_slice(&(EHDR_SIZE as u16).to_le_bytes()); // e_ehsize
+    out.extend_from_slice(&(PHDR_SIZE as u16).to_le_bytes()); // e_phentsize
+    out.extend_from_slice(&e_phnum.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // e_shentsize
+    out.extend_from_slice(&0u16.to_le_bytes()); // e_shnum
+    out.extend_from_slice(&0u16.to_le_bytes()); // e_shstrndx
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_phdr(out: &mut Vec<u8>, p_type: u32, p_flags: u32, p_offset: u64, p_vaddr: u64, p_paddr: u64, p_filesz: u64, p_memsz: u64, p_align: u64) {
+    out.extend_from_slice(&p_type.to_le_bytes());
+    out.extend_from_slice(&p_flags.to_le_bytes());
+    out.extend_from_slice(&p_offset.to_le_bytes());
+    out.extend_from_slice(&p_vaddr.to_le_bytes());
+    out.extend_from_slice(&p_paddr.to_le_bytes());
+    out.extend_from_slice(&p_filesz.to_le_bytes());
+    out.extend_from_slice(&p_memsz.to_le_bytes());
+    out.extend_from_slice(&p_align.to_le_bytes());
+}
+
+/// Build a full ELF64 core file for `cpu_ids` and `regions` using `writer`
+/// for the arch-specific parts, then stream it out [`SERIAL1`] one byte at
+/// a time.
+pub fn write_core_dump(writer: &dyn CpuElf64Writable, cpu_ids: &[u32], regions: &[MemoryRegion]) {
+    let mut notes = Vec::new();
+    for &cpu_id in cpu_ids {
+        writer.write_prstatus(&mut notes, cpu_id);
+    }
+
+    let phnum = 1 + regions.len() as u16; // PT_NOTE + one PT_LOAD per region
+    let notes_offset = EHDR_SIZE + PHDR_SIZE * phnum as u64;
+
+    let mut out = Vec::new();
+    write_ehdr(&mut out, writer.elf_machine(), phnum);
+
+    write_phdr(&mut out, PT_NOTE, 0, notes_offset, 0, 0, notes.len() as u64, notes.len() as u64, 4);
+
+    let mut offset = notes_offset + notes.len() as u64;
+    for region in regions {
+        let addr = region.start.as_u64();
+        write_phdr(&mut out, PT_LOAD, PF_R | PF_W | PF_X, offset, addr, addr, region.size as u64, region.size as u64, 4096);
+        offset += region.size as u64;
+    }
+
+    out.extend_from_slice(&notes);
+    for region in regions {
+        writer.write_mem_regions(&mut out, region);
+    }
+
+    let mut serial = SERIAL1.lock();
+    for byte in out {
+        serial.send(byte);
+    }
+}
+
+/// Emit a core dump for the running CPU's register state and every mapped
+/// [`MemoryRegion`], called from the panic handler. There's no SMP
+/// rendezvous here yet -- only the panicking CPU's state is captured,
+/// which is the common case for a single-core boot and still useful for
+/// post-mortem analysis when other cores are quiesced separately.
+pub fn dump_on_panic() {
+    #[cfg(target_arch = "x86_64")]
+    {
+        let writer = crate::x86_64::coredump::X86_64CoreWriter;
+        let regions = crate::memory::frame_allocator::mapped_regions();
+        let cpu_id = crate::cpu::init::current_cpu();
+        write_core_dump(&writer, &[cpu_id], &regions);
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        let writer = crate::arch::arm64::coredump::Arm64CoreWriter;
+        let regions = crate::memory::frame_allocator::mapped_regions();
+        let cpu_id = crate::cpu::init::current_cpu();
+        write_core_dump(&writer, &[cpu_id], &regions);
+    }
+}
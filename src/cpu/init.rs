@@ -19,23 +19,65 @@ use crate::arch::arm64::cpu as arm_cpu;
 pub fn init() {
     // Initialize the CPU manager
     percpu::init_cpu_manager();
-    
+
     // Initialize the scheduler
     scheduler_smp::init_scheduler();
-    
+
+    #[cfg(target_arch = "x86_64")]
+    log_cpu_features_x86_64();
+
     // Enumerate CPUs
     #[cfg(target_arch = "x86_64")]
     enumerate_cpus_x86_64();
-    
+
     #[cfg(target_arch = "aarch64")]
     enumerate_cpus_arm64();
 }
 
-/// Boot all application processors
-pub fn boot_aps() {
+/// Log a human-readable vendor/brand/feature report for the BSP, and
+/// record whether this CPU actually supports x2APIC/FSGSBASE so the rest
+/// of SMP bring-up can gate on capability instead of assuming it.
+#[cfg(target_arch = "x86_64")]
+fn log_cpu_features_x86_64() {
+    let vendor = x86_cpu::CpuVendor::detect();
+    let brand = x86_cpu::brand_string();
+    let brand_str = core::str::from_utf8(&brand)
+        .unwrap_or("")
+        .trim_end_matches('\0')
+        .trim();
+    let features = x86_cpu::CpuFeatures::detect();
+
+    println!(
+        "cpu: {:?} \"{}\" sse2={} avx={} avx2={} fma={} xsave={} fsgsbase={} smep={} smap={} x2apic={} tsc_deadline={} pcid={} rdrand={} rdseed={} 1gpages={} nx={}",
+        vendor,
+        brand_str,
+        features.sse2,
+        features.avx,
+        features.avx2,
+        features.fma,
+        features.xsave,
+        features.fsgsbase,
+        features.smep,
+        features.smap,
+        features.x2apic,
+        features.tsc_deadline,
+        features.pcid,
+        features.rdrand,
+        features.rdseed,
+        features.pages_1g,
+        features.nx,
+    );
+}
+
+/// Bring up every registered non-BSP CPU and wait for it to report online.
+///
+/// Call once `init()` has run ACPI/topology discovery and populated the
+/// `CpuManager` with every possible CPU's [`CpuInfo`] -- this is what
+/// actually starts them; `init()` only enumerates and registers them.
+pub fn smp_init() {
     #[cfg(target_arch = "x86_64")]
     boot_aps_x86_64();
-    
+
     #[cfg(target_arch = "aarch64")]
     boot_aps_arm64();
 }
@@ -44,7 +86,7 @@ pub fn boot_aps() {
 fn enumerate_cpus_x86_64() {
     // Get CPU count from CPUID
     let cpu_count = x86_cpu::enumerate_cpus();
-    
+
     // Register BSP (Boot Strap Processor)
     let bsp_info = CpuInfo {
         cpu_id: 0,
@@ -52,41 +94,117 @@ fn enumerate_cpus_x86_64() {
         is_bsp: true,
         online: true,
     };
-    
+
     // Allocate stack for BSP (already has one, but register it)
     let bsp_stack = 0xFFFF800000000000u64;  // Kernel stack base (should be from frame allocator)
     let stack_size = 0x4000u64;  // 16KB stack
-    
+
     percpu::register_cpu(bsp_info, bsp_stack, stack_size);
-    
-    // For now, just register a few APs
-    for i in 1..core::cmp::min(cpu_count, 16) {
-        let ap_info = CpuInfo {
-            cpu_id: i as u32,
-            apic_id: i as u32,  // TODO: Get from ACPI MADT
-            is_bsp: false,
-            online: false,
-        };
-        
-        // Allocate stack for AP
-        let ap_stack = 0xFFFF800000000000u64 - (i as u64 * 0x10000u64);
-        let stack_size = 0x4000u64;
-        
-        percpu::register_cpu(ap_info, ap_stack, stack_size);
+    percpu::mark_online(0);
+    percpu::install_percpu_base(0);
+
+    // Prefer the true APIC IDs ACPI reports over fabricated `i as u32`
+    // ones; `find_madt_address` returns `None` on firmware without ACPI
+    // (or a malformed table), in which case we fall back to the old
+    // sequential numbering.
+    let madt_address = x86_cpu::find_madt_address();
+    if let Some(addr) = madt_address {
+        x86_cpu::set_lapic_base_override(x86_cpu::madt_lapic_base(addr));
+        x86_cpu::set_madt_io_apics(x86_cpu::parse_madt_io_apics(addr));
+        x86_cpu::set_madt_interrupt_overrides(x86_cpu::parse_madt_interrupt_overrides(addr));
+    }
+    let madt_entries = madt_address.map(x86_cpu::parse_madt).unwrap_or_default();
+    let bsp_apic_id = x86_cpu::read_apic_id();
+
+    if !madt_entries.is_empty() {
+        let mut cpu_id = 1u32;
+        for entry in madt_entries.iter().filter(|e| e.apic_id != bsp_apic_id) {
+            if cpu_id >= core::cmp::min(cpu_count, 16) {
+                break;
+            }
+
+            let ap_info = CpuInfo {
+                cpu_id,
+                apic_id: entry.apic_id,
+                is_bsp: false,
+                online: false,
+            };
+
+            let ap_stack = 0xFFFF800000000000u64 - (cpu_id as u64 * 0x10000u64);
+            let stack_size = 0x4000u64;
+
+            percpu::register_cpu(ap_info, ap_stack, stack_size);
+            cpu_id += 1;
+        }
+    } else {
+        // No ACPI MADT available to get the real ID: synthesize one from
+        // CPUID topology instead of passing the linear `cpu_id` straight
+        // through, which is only correct when every topology level
+        // happens to be a power of two.
+        let topology = x86_cpu::detect_cpu_topology().as_counts();
+
+        for i in 1..core::cmp::min(cpu_count, 16) {
+            let ap_info = CpuInfo {
+                cpu_id: i as u32,
+                apic_id: x86_cpu::get_x2apic_id(i, topology),
+                is_bsp: false,
+                online: false,
+            };
+
+            // Allocate stack for AP
+            let ap_stack = 0xFFFF800000000000u64 - (i as u64 * 0x10000u64);
+            let stack_size = 0x4000u64;
+
+            percpu::register_cpu(ap_info, ap_stack, stack_size);
+        }
     }
 }
 
 #[cfg(target_arch = "x86_64")]
 fn boot_aps_x86_64() {
-    use crate::x86_64::ap_boot;
-    
-    // Get AP startup code
-    let entry_point = ap_boot::AP_BOOT_ADDRESS;
-    
-    // Boot all APs
-    // This is simplified - full implementation would handle multiple APs
-    for cpu_id in 1..4 {
-        let _ = ap_boot::boot_ap(cpu_id, entry_point);
+    use crate::x86_64::{ap_boot, ap_trampoline};
+
+    let Some(mgr) = percpu::get_cpu_manager() else {
+        return;
+    };
+    let cpu_count = mgr.cpu_count();
+    if cpu_count <= 1 {
+        return;
+    }
+
+    // The trampoline code is identical for every AP; copy it down to
+    // `AP_BOOT_ADDRESS` once, then repatch just its parameter block
+    // per CPU before each one's STARTUP IPIs.
+    unsafe {
+        ap_trampoline::install();
+    }
+
+    let cr3 = crate::arch::x86_64::read_cr3();
+
+    for cpu_id in 1..cpu_count as u32 {
+        let Some(data) = mgr.get_cpu(cpu_id) else {
+            continue;
+        };
+        let (apic_id, stack_top) = {
+            let pcpu = data.lock_irqsave();
+            (pcpu.info.apic_id, pcpu.kernel_stack + pcpu.stack_size)
+        };
+
+        unsafe {
+            ap_trampoline::set_params(ap_trampoline::ApTrampolineParams {
+                cr3,
+                stack_top,
+                entry: ap_boot::ap_startup_main as u64,
+                cpu_id,
+            });
+        }
+
+        if !ap_boot::boot_ap(apic_id, ap_boot::AP_BOOT_ADDRESS) {
+            println!(
+                "smp: cpu {} (apic {}) failed to come online",
+                cpu_id, apic_id
+            );
+        }
     }
 }
 
@@ -107,7 +225,9 @@ fn enumerate_cpus_arm64() {
     let stack_size = 0x4000u64;
     
     percpu::register_cpu(bsp_info, bsp_stack, stack_size);
-    
+    percpu::mark_online(0);
+    percpu::install_percpu_base(0);
+
     // Register APs
     for i in 1..cpu_count {
         let ap_info = CpuInfo {
@@ -126,22 +246,22 @@ fn enumerate_cpus_arm64() {
 
 #[cfg(target_arch = "aarch64")]
 fn boot_aps_arm64() {
-    use crate::arm64::ap_boot;
-    
-    // Use PSCI to bring up APs
-    let entry_point = 0xFFFF800000000000u64;  // Kernel entry point
-    
+    use crate::arch::arm64::ap_boot;
+
+    // PSCI CPU_ON (or the spin-table fallback) resumes a core straight at
+    // its entry point with no real-mode stage to thread through, unlike
+    // x86_64's IPI-vector trampoline -- so the entry point is just
+    // `ap_startup_main` itself.
+    let entry_point = ap_boot::ap_startup_main as u64;
+
     let _booted = ap_boot::boot_all_aps(entry_point);
 }
 
 /// Get the number of online CPUs
 pub fn cpu_count() -> u32 {
-    unsafe {
-        if let Some(ref mgr) = crate::cpu::percpu::CPU_MANAGER {
-            mgr.cpu_count() as u32
-        } else {
-            1
-        }
+    match percpu::get_cpu_manager() {
+        Some(mgr) => mgr.cpu_count() as u32,
+        None => 1,
     }
 }
 
@@ -0,0 +1,11 @@
+/// CPU Management
+///
+/// Ties together per-CPU state tracking (`percpu`), boot-time CPU
+/// enumeration/AP bring-up (`init`), and the standalone SMP task scheduler
+/// prototype (`scheduler_smp`).
+pub mod percpu;
+pub mod init;
+pub mod profiler;
+pub mod scheduler_smp;
+
+pub use percpu::get_current_cpu_id as cpu_id;
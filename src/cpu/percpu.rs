@@ -9,6 +9,7 @@
 
 use crate::lib::spinlock::Spinlock;
 use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 
 #[derive(Debug, Clone, Copy)]
 pub struct CpuInfo {
@@ -23,26 +24,376 @@ pub struct PerCpuData {
     pub kernel_stack: u64,
     pub stack_size: u64,
     pub interrupts_disabled: bool,
-    
-    // Statistics
-    pub irq_count: u64,
-    pub timer_ticks: u64,
-    pub context_switches: u64,
-    
+
     // Profiling
     pub cycle_counter: u64,
     pub last_sample_time: u64,
 }
 
 pub const MAX_CPUS: usize = 256;
+const MASK_WORDS: usize = MAX_CPUS / 64;
+
+/// A fixed-size bitmap, one bit per possible logical CPU id. [`CpuManager`]
+/// holds one of these for each of the `possible`/`present`/`online`/`active`
+/// processor sets, so answering "how many CPUs are X" never has to walk the
+/// full 256-slot `cpus` array.
+#[derive(Clone, Copy)]
+pub struct CpuMask {
+    words: [u64; MASK_WORDS],
+}
+
+impl CpuMask {
+    pub const fn new() -> Self {
+        CpuMask { words: [0; MASK_WORDS] }
+    }
+
+    fn word_bit(cpu_id: u32) -> Option<(usize, u32)> {
+        let word = cpu_id as usize / 64;
+        if word >= MASK_WORDS {
+            None
+        } else {
+            Some((word, cpu_id % 64))
+        }
+    }
+
+    pub fn set(&mut self, cpu_id: u32) {
+        if let Some((word, bit)) = Self::word_bit(cpu_id) {
+            self.words[word] |= 1 << bit;
+        }
+    }
+
+    pub fn clear(&mut self, cpu_id: u32) {
+        if let Some((word, bit)) = Self::word_bit(cpu_id) {
+            self.words[word] &= !(1 << bit);
+        }
+    }
+
+    pub fn test(&self, cpu_id: u32) -> bool {
+        match Self::word_bit(cpu_id) {
+            Some((word, bit)) => self.words[word] & (1 << bit) != 0,
+            None => false,
+        }
+    }
+
+    /// Total number of set bits.
+    pub fn count(&self) -> u32 {
+        self.words.iter().map(|w| w.count_ones()).sum()
+    }
+
+    /// Does the set-bit count equal `n`? Bails out the moment the running
+    /// popcount exceeds `n`, since no later word can bring it back down.
+    pub fn count_eq(&self, n: u32) -> bool {
+        let mut running = 0u32;
+        for word in &self.words {
+            running += word.count_ones();
+            if running > n {
+                return false;
+            }
+        }
+        running == n
+    }
+
+    /// Is the set-bit count greater than `n`? Returns `true` the instant the
+    /// running popcount exceeds `n`, without scanning the remaining words.
+    pub fn count_gt(&self, n: u32) -> bool {
+        let mut running = 0u32;
+        for word in &self.words {
+            running += word.count_ones();
+            if running > n {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Is the set-bit count at most `n`? The exact complement of
+    /// [`count_gt`](Self::count_gt), so it inherits the same early bailout.
+    pub fn count_le(&self, n: u32) -> bool {
+        !self.count_gt(n)
+    }
+
+    /// The lowest set bit's CPU id, if any.
+    pub fn first(&self) -> Option<u32> {
+        self.iter().next()
+    }
+
+    /// The lowest set bit strictly greater than `after`'s CPU id, if any.
+    pub fn next(&self, after: u32) -> Option<u32> {
+        self.iter().find(|&id| id > after)
+    }
+
+    pub fn iter(&self) -> CpuMaskIter<'_> {
+        CpuMaskIter { mask: self, word: 0, bits: self.words[0] }
+    }
+}
+
+impl Default for CpuMask {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Iterator over a [`CpuMask`]'s set bits, in ascending CPU id order.
+pub struct CpuMaskIter<'a> {
+    mask: &'a CpuMask,
+    word: usize,
+    bits: u64,
+}
+
+impl<'a> Iterator for CpuMaskIter<'a> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        loop {
+            if self.bits != 0 {
+                let bit = self.bits.trailing_zeros();
+                self.bits &= self.bits - 1;
+                return Some((self.word * 64 + bit as usize) as u32);
+            }
+            self.word += 1;
+            if self.word >= MASK_WORDS {
+                return None;
+            }
+            self.bits = self.mask.words[self.word];
+        }
+    }
+}
 
 pub struct CpuManager {
     cpus: [Option<Spinlock<PerCpuData>>; MAX_CPUS],
     count: usize,
-    current_cpu: usize,
+    /// Every CPU id the firmware/topology says could exist, set once at
+    /// registration and never cleared.
+    possible: CpuMask,
+    /// Every CPU id actually populated in `cpus` right now.
+    present: CpuMask,
+    /// Every CPU id that has reported itself up via [`mark_online`].
+    online: CpuMask,
+    /// Every CPU id eligible to run work. Mirrors `online` until CPU
+    /// hotplug/offlining exists in this kernel.
+    active: CpuMask,
+}
+
+static mut CPU_MANAGER: Option<CpuManager> = None;
+
+/// What `IA32_GS_BASE` (x86_64) / `TPIDR_EL1` (aarch64) points at once
+/// [`install_percpu_base`] has run for a core: its CPU id, readable without
+/// taking `data`'s lock, and a pointer to its full [`PerCpuData`].
+///
+/// This is what makes [`this_cpu`]/[`get_current_cpu_id`] safe across real
+/// SMP: each core resolves its own identity from a hardware register that
+/// is *by construction* local to the CPU executing the read, instead of a
+/// single `current_cpu` field shared (and racing) across every core.
+#[repr(C)]
+pub struct PerCpuBase {
+    pub cpu_id: u32,
+    pub data: *const Spinlock<PerCpuData>,
+    /// IRQ/timer-tick/context-switch counters, bumped by
+    /// [`increment_irq_count`]/[`increment_timer_ticks`]/
+    /// [`increment_context_switches`] straight off this core's own
+    /// `PerCpuBase` -- no `PerCpuData` spinlock involved, so they're safe
+    /// to bump from interrupt context with no risk of deadlocking against
+    /// this same core.
+    pub irq_count: AtomicU64,
+    pub timer_ticks: AtomicU64,
+    pub context_switches: AtomicU64,
 }
 
-pub static mut CPU_MANAGER: Option<CpuManager> = None;
+// SAFETY: `data` always points at a `'static` slot inside `CPU_MANAGER`'s
+// `cpus` array, which is itself `Sync` (`Spinlock<PerCpuData>` is
+// `Send + Sync`); the raw pointer here is just a non-owning alias of it.
+unsafe impl Sync for PerCpuBase {}
+
+const NULL_PERCPU_BASE: PerCpuBase = PerCpuBase {
+    cpu_id: u32::MAX,
+    data: core::ptr::null(),
+    irq_count: AtomicU64::new(0),
+    timer_ticks: AtomicU64::new(0),
+    context_switches: AtomicU64::new(0),
+};
+
+/// One `PerCpuBase` slot per possible CPU id, populated as each CPU is
+/// registered. The hardware per-CPU base register is pointed at the slot
+/// for that CPU's own id, never at another core's.
+static mut PERCPU_BASES: [PerCpuBase; MAX_CPUS] = [NULL_PERCPU_BASE; MAX_CPUS];
+
+/// Address of `cpu_id`'s [`PerCpuBase`] slot, for the arch layer to load
+/// into its per-CPU base register. Returns `None` if `cpu_id` hasn't been
+/// registered yet (its `data` pointer would still be null).
+pub fn per_cpu_base_ptr(cpu_id: u32) -> Option<u64> {
+    if cpu_id as usize >= MAX_CPUS {
+        return None;
+    }
+    unsafe {
+        if PERCPU_BASES[cpu_id as usize].data.is_null() {
+            return None;
+        }
+        Some(core::ptr::addr_of!(PERCPU_BASES[cpu_id as usize]) as u64)
+    }
+}
+
+/// Point this core's hardware per-CPU base register at `cpu_id`'s
+/// [`PerCpuBase`] slot. Call once per core, after that CPU's `register_cpu`
+/// (so `per_cpu_base_ptr` has something to return), before anything on
+/// this core calls [`this_cpu`]/[`get_current_cpu_id`].
+pub fn install_percpu_base(cpu_id: u32) {
+    let Some(base_addr) = per_cpu_base_ptr(cpu_id) else {
+        return;
+    };
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        const IA32_GS_BASE: u32 = 0xC0000101;
+        crate::x86_64::cpu::write_msr(IA32_GS_BASE, base_addr);
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        core::arch::asm!("msr tpidr_el1, {}", in(reg) base_addr, options(nomem, nostack));
+    }
+}
+
+/// Read the calling core's own [`PerCpuBase`] straight out of its hardware
+/// per-CPU base register -- a `gs:`-relative load on x86_64 (by way of
+/// `IA32_GS_BASE`), `tpidr_el1` on aarch64. `None` before
+/// [`install_percpu_base`] has run for this core.
+fn current_base() -> Option<&'static PerCpuBase> {
+    #[cfg(target_arch = "x86_64")]
+    let base_addr = {
+        const IA32_GS_BASE: u32 = 0xC0000101;
+        crate::x86_64::cpu::read_msr(IA32_GS_BASE)
+    };
+
+    #[cfg(target_arch = "aarch64")]
+    let base_addr = unsafe {
+        let tpidr: u64;
+        core::arch::asm!("mrs {}, tpidr_el1", out(reg) tpidr, options(nomem, nostack));
+        tpidr
+    };
+
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    let base_addr = 0u64;
+
+    if base_addr == 0 {
+        return None;
+    }
+    unsafe { (base_addr as *const PerCpuBase).as_ref() }
+}
+
+/// A value read through the calling CPU's own per-CPU base register
+/// instead of a shared global, so every core reading it gets its own
+/// answer with no lock and no risk of reading another CPU's state.
+/// `get` is handed the running CPU's [`PerCpuData`] (already behind its
+/// own per-CPU spinlock, briefly held just for the read) and picks out
+/// the one field this `PerCpu` tracks.
+pub struct PerCpu<T> {
+    get: fn(&PerCpuData) -> T,
+}
+
+impl<T> PerCpu<T> {
+    pub const fn new(get: fn(&PerCpuData) -> T) -> Self {
+        PerCpu { get }
+    }
+
+    /// `None` before this core has a per-CPU base installed yet (earliest
+    /// boot, before [`install_percpu_base`] runs).
+    pub fn get(&self) -> Option<T> {
+        this_cpu().map(|data| (self.get)(&data.lock_irqsave()))
+    }
+}
+
+/// This core's logical CPU id, read lock-free straight out of its
+/// `PerCpuBase`'s `cpu_id` field.
+pub static CURRENT_CPU_ID: PerCpu<u32> = PerCpu::new(|data| data.info.cpu_id);
+
+/// This core's kernel stack base, for interrupt entry stubs that need to
+/// validate or switch onto it without walking `CpuManager` by id.
+pub static CURRENT_KERNEL_STACK: PerCpu<u64> = PerCpu::new(|data| data.kernel_stack);
+
+/// This core's run-queue index, i.e. the `cpu_id` `process::scheduler`'s
+/// per-core `SCHEDULERS` table is keyed by. Exists as a named accessor so
+/// scheduler code reads its own run queue the same lock-free way as
+/// [`CURRENT_CPU_ID`] and [`CURRENT_KERNEL_STACK`], rather than re-deriving
+/// the id from `get_current_cpu_id()` at every call site.
+pub static CURRENT_RUN_QUEUE_INDEX: PerCpu<u32> = PerCpu::new(|data| data.info.cpu_id);
+
+/// Online-CPU bitmap: one bit per logical CPU id, set once that CPU's
+/// `ap_startup_main` has finished bringing itself up. Backed by a fixed
+/// array of words so it can be read lock-free from `wait_for_aps`.
+static ONLINE_MASK: [AtomicU64; MASK_WORDS] = {
+    const ZERO: AtomicU64 = AtomicU64::new(0);
+    [ZERO; MASK_WORDS]
+};
+static ONLINE_COUNT: AtomicU32 = AtomicU32::new(0);
+
+/// Mark `cpu_id` as online. Called once by each core's `ap_startup_main`
+/// (and for the BSP during early init) before entering its idle loop.
+pub fn mark_online(cpu_id: u32) {
+    let (word, bit) = (cpu_id as usize / 64, cpu_id as usize % 64);
+    if word >= MASK_WORDS {
+        return;
+    }
+    let prev = ONLINE_MASK[word].fetch_or(1 << bit, Ordering::SeqCst);
+    if prev & (1 << bit) == 0 {
+        ONLINE_COUNT.fetch_add(1, Ordering::SeqCst);
+    }
+
+    // Keep `CpuManager`'s online/active masks (used by the num_*_cpus
+    // query API) in sync with the lock-free bitmap above.
+    unsafe {
+        if let Some(ref mut mgr) = CPU_MANAGER {
+            mgr.online.set(cpu_id);
+            mgr.active.set(cpu_id);
+        }
+    }
+}
+
+/// Whether `cpu_id` has reported itself online.
+pub fn is_online(cpu_id: u32) -> bool {
+    let (word, bit) = (cpu_id as usize / 64, cpu_id as usize % 64);
+    word < MASK_WORDS && ONLINE_MASK[word].load(Ordering::SeqCst) & (1 << bit) != 0
+}
+
+/// Number of CPUs that have reported themselves online so far.
+pub fn online_count() -> u32 {
+    ONLINE_COUNT.load(Ordering::SeqCst)
+}
+
+/// Run `f` for every CPU id currently marked online.
+pub fn for_each_online_cpu<F: FnMut(u32)>(mut f: F) {
+    for word in 0..MASK_WORDS {
+        let mut bits = ONLINE_MASK[word].load(Ordering::SeqCst);
+        while bits != 0 {
+            let bit = bits.trailing_zeros();
+            f((word * 64 + bit as usize) as u32);
+            bits &= bits - 1;
+        }
+    }
+}
+
+/// Spin until every CPU in `0..expected` is online, or `timeout_ms` elapses.
+/// Each millisecond step is an approximate busy-wait, matching the spin
+/// loops the arch-specific AP boot code already uses.
+pub fn wait_for_online(expected: u32, timeout_ms: u32) -> bool {
+    let mut elapsed = 0u32;
+    while online_count() < expected {
+        if elapsed >= timeout_ms {
+            return false;
+        }
+        for _ in 0..1000 {
+            core::hint::spin_loop();
+        }
+        elapsed += 1;
+    }
+    true
+}
+
+/// Access the calling CPU's own `PerCpuData`, resolved through its
+/// hardware per-CPU base register rather than a shared `current_cpu` id.
+pub fn this_cpu() -> Option<&'static Spinlock<PerCpuData>> {
+    let base = current_base()?;
+    unsafe { base.data.as_ref() }
+}
 
 impl CpuManager {
     pub fn new() -> Self {
@@ -50,11 +401,14 @@ impl CpuManager {
         // Create an uninitialized array and fill it
         const NONE_VALUE: Option<Spinlock<PerCpuData>> = None;
         let cpus = [NONE_VALUE; MAX_CPUS];
-        
+
         CpuManager {
             cpus,
             count: 0,
-            current_cpu: 0,
+            possible: CpuMask::new(),
+            present: CpuMask::new(),
+            online: CpuMask::new(),
+            active: CpuMask::new(),
         }
     }
 
@@ -68,9 +422,6 @@ impl CpuManager {
             kernel_stack,
             stack_size,
             interrupts_disabled: false,
-            irq_count: 0,
-            timer_ticks: 0,
-            context_switches: 0,
             cycle_counter: 0,
             last_sample_time: 0,
         };
@@ -78,7 +429,10 @@ impl CpuManager {
         self.cpus[self.count] = Some(Spinlock::new(per_cpu));
         let id = self.count as u32;
         self.count += 1;
-        
+
+        self.possible.set(id);
+        self.present.set(id);
+
         id
     }
 
@@ -94,14 +448,20 @@ impl CpuManager {
         self.count
     }
 
-    pub fn set_current_cpu(&mut self, id: u32) {
-        if (id as usize) < self.count {
-            self.current_cpu = id as usize;
-        }
+    pub fn possible_mask(&self) -> &CpuMask {
+        &self.possible
+    }
+
+    pub fn present_mask(&self) -> &CpuMask {
+        &self.present
+    }
+
+    pub fn online_mask(&self) -> &CpuMask {
+        &self.online
     }
 
-    pub fn current_cpu_id(&self) -> u32 {
-        self.current_cpu as u32
+    pub fn active_mask(&self) -> &CpuMask {
+        &self.active
     }
 }
 
@@ -111,13 +471,31 @@ pub fn init_cpu_manager() {
     }
 }
 
+/// Register `info` with the global `CpuManager` and point its
+/// [`PerCpuBase`] slot at the freshly-created `PerCpuData`, so a later
+/// [`install_percpu_base`] on that core has something valid to load.
 pub fn register_cpu(info: CpuInfo, kernel_stack: u64, stack_size: u64) -> u32 {
     unsafe {
-        if let Some(ref mut mgr) = CPU_MANAGER {
-            mgr.register_cpu(info, kernel_stack, stack_size)
-        } else {
-            u32::MAX
+        let Some(ref mut mgr) = CPU_MANAGER else {
+            return u32::MAX;
+        };
+
+        let id = mgr.register_cpu(info, kernel_stack, stack_size);
+        if id == u32::MAX {
+            return id;
+        }
+
+        if let Some(data) = mgr.get_cpu(id) {
+            PERCPU_BASES[id as usize] = PerCpuBase {
+                cpu_id: id,
+                data: data as *const Spinlock<PerCpuData>,
+                irq_count: AtomicU64::new(0),
+                timer_ticks: AtomicU64::new(0),
+                context_switches: AtomicU64::new(0),
+            };
         }
+
+        id
     }
 }
 
@@ -128,60 +506,124 @@ pub fn get_cpu_manager() -> Option<&'static CpuManager> {
 }
 
 pub fn get_current_cpu_info() -> Option<CpuInfo> {
-    unsafe {
-        if let Some(ref mgr) = CPU_MANAGER {
-            let cpu_id = mgr.current_cpu_id();
-            if let Some(cpu) = mgr.get_cpu(cpu_id) {
-                let data = cpu.lock();
-                Some(data.info)
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    }
+    this_cpu().map(|data| data.lock_irqsave().info)
 }
 
+/// This core's logical CPU id, read lock-free straight out of its
+/// `PerCpuBase` -- safe to call from any core concurrently, since each
+/// core's hardware base register can only ever point at its own slot.
 pub fn get_current_cpu_id() -> u32 {
-    unsafe {
-        if let Some(ref mgr) = CPU_MANAGER {
-            mgr.current_cpu_id()
-        } else {
-            0
-        }
+    current_base().map(|base| base.cpu_id).unwrap_or(0)
+}
+
+/// Bump the calling core's own IRQ counter, resolved through its
+/// `PerCpuBase` with no spinlock involved -- safe to call from interrupt
+/// context on the same core that's also holding `PerCpuData`'s lock.
+pub fn increment_irq_count() {
+    if let Some(base) = current_base() {
+        base.irq_count.fetch_add(1, Ordering::Relaxed);
     }
 }
 
-pub fn increment_irq_count(cpu_id: u32) {
-    unsafe {
-        if let Some(ref mgr) = CPU_MANAGER {
-            if let Some(cpu) = mgr.get_cpu(cpu_id) {
-                let mut data = cpu.lock();
-                data.irq_count += 1;
-            }
-        }
+/// Bump the calling core's own timer-tick counter. See
+/// [`increment_irq_count`] for why this goes through `PerCpuBase` instead
+/// of `PerCpuData`.
+pub fn increment_timer_ticks() {
+    if let Some(base) = current_base() {
+        base.timer_ticks.fetch_add(1, Ordering::Relaxed);
     }
 }
 
-pub fn increment_timer_ticks(cpu_id: u32) {
-    unsafe {
-        if let Some(ref mgr) = CPU_MANAGER {
-            if let Some(cpu) = mgr.get_cpu(cpu_id) {
-                let mut data = cpu.lock();
-                data.timer_ticks += 1;
-            }
-        }
+/// Bump the calling core's own context-switch counter. See
+/// [`increment_irq_count`] for why this goes through `PerCpuBase` instead
+/// of `PerCpuData`.
+pub fn increment_context_switches() {
+    if let Some(base) = current_base() {
+        base.context_switches.fetch_add(1, Ordering::Relaxed);
     }
 }
 
-pub fn increment_context_switches(cpu_id: u32) {
-    unsafe {
-        if let Some(ref mgr) = CPU_MANAGER {
-            if let Some(cpu) = mgr.get_cpu(cpu_id) {
-                let mut data = cpu.lock();
-                data.context_switches += 1;
-            }
-        }
+/// Read the calling core's own IRQ/timer-tick/context-switch counters.
+/// Returns zeroes before this core's `PerCpuBase` is installed.
+pub fn this_cpu_stats() -> (u64, u64, u64) {
+    match current_base() {
+        Some(base) => (
+            base.irq_count.load(Ordering::Relaxed),
+            base.timer_ticks.load(Ordering::Relaxed),
+            base.context_switches.load(Ordering::Relaxed),
+        ),
+        None => (0, 0, 0),
     }
 }
+
+/// Number of CPU ids this machine could ever have (set once from
+/// ACPI/topology enumeration, never shrinks).
+pub fn num_possible_cpus() -> u32 {
+    get_cpu_manager().map_or(0, |mgr| mgr.possible_mask().count())
+}
+
+pub fn num_possible_cpus_eq(n: u32) -> bool {
+    get_cpu_manager().is_some_and(|mgr| mgr.possible_mask().count_eq(n))
+}
+
+pub fn num_possible_cpus_gt(n: u32) -> bool {
+    get_cpu_manager().is_some_and(|mgr| mgr.possible_mask().count_gt(n))
+}
+
+pub fn num_possible_cpus_le(n: u32) -> bool {
+    get_cpu_manager().map_or(true, |mgr| mgr.possible_mask().count_le(n))
+}
+
+/// Number of CPU ids actually populated in the `cpus` table right now.
+pub fn num_present_cpus() -> u32 {
+    get_cpu_manager().map_or(0, |mgr| mgr.present_mask().count())
+}
+
+pub fn num_present_cpus_eq(n: u32) -> bool {
+    get_cpu_manager().is_some_and(|mgr| mgr.present_mask().count_eq(n))
+}
+
+pub fn num_present_cpus_gt(n: u32) -> bool {
+    get_cpu_manager().is_some_and(|mgr| mgr.present_mask().count_gt(n))
+}
+
+pub fn num_present_cpus_le(n: u32) -> bool {
+    get_cpu_manager().map_or(true, |mgr| mgr.present_mask().count_le(n))
+}
+
+/// Number of CPUs that have reported themselves online via [`mark_online`].
+/// Equivalent to [`online_count`], but going through `CpuManager`'s
+/// [`CpuMask`] gets the early-bailout `_eq`/`_gt`/`_le` variants for free.
+pub fn num_online_cpus() -> u32 {
+    get_cpu_manager().map_or(0, |mgr| mgr.online_mask().count())
+}
+
+pub fn num_online_cpus_eq(n: u32) -> bool {
+    get_cpu_manager().is_some_and(|mgr| mgr.online_mask().count_eq(n))
+}
+
+pub fn num_online_cpus_gt(n: u32) -> bool {
+    get_cpu_manager().is_some_and(|mgr| mgr.online_mask().count_gt(n))
+}
+
+pub fn num_online_cpus_le(n: u32) -> bool {
+    get_cpu_manager().map_or(true, |mgr| mgr.online_mask().count_le(n))
+}
+
+/// Number of CPUs eligible to run work. Mirrors [`num_online_cpus`] until
+/// this kernel supports taking a CPU offline without removing it.
+pub fn num_active_cpus() -> u32 {
+    get_cpu_manager().map_or(0, |mgr| mgr.active_mask().count())
+}
+
+pub fn num_active_cpus_eq(n: u32) -> bool {
+    get_cpu_manager().is_some_and(|mgr| mgr.active_mask().count_eq(n))
+}
+
+pub fn num_active_cpus_gt(n: u32) -> bool {
+    get_cpu_manager().is_some_and(|mgr| mgr.active_mask().count_gt(n))
+}
+
+pub fn num_active_cpus_le(n: u32) -> bool {
+    get_cpu_manager().map_or(true, |mgr| mgr.active_mask().count_le(n))
+}
@@ -0,0 +1,145 @@
+/// NMI-Driven Sampling Profiler
+///
+/// Turns the `cycle_counter`/`last_sample_time` fields `PerCpuData`
+/// already reserved into an actual statistical profiler: a performance-
+/// monitoring counter is armed to overflow after a configurable period of
+/// unhalted cycles and deliver a sample interrupt (an NMI on x86_64; see
+/// `arch::arm64::pmu` for aarch64's current limitation). The handler
+/// samples the interrupted PC and TSC and pushes them into a per-CPU ring
+/// buffer -- [`drain_samples`] is the only way anything else reads them
+/// back out.
+///
+/// The ring buffer is deliberately not behind `CpuManager`'s spinlock: an
+/// NMI can land while the same core already holds that lock (updating
+/// scheduler state, say), and taking it again from NMI context would
+/// deadlock. Each core only ever writes its own ring, so a plain
+/// monotonic write index with no CAS is enough to make that safe.
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::cpu::percpu::{self, MAX_CPUS};
+
+#[cfg(target_arch = "x86_64")]
+use crate::x86_64::pmu;
+
+#[cfg(target_arch = "aarch64")]
+use crate::arch::arm64::pmu;
+
+/// Samples held per core before the oldest is overwritten. 128 covers a
+/// few milliseconds of sampling at typical periods without costing much
+/// static memory multiplied out across `MAX_CPUS` rings.
+const RING_CAPACITY: usize = 128;
+
+#[derive(Clone, Copy)]
+struct Sample {
+    pc: u64,
+    timestamp: u64,
+}
+
+impl Sample {
+    const fn empty() -> Self {
+        Sample {
+            pc: 0,
+            timestamp: 0,
+        }
+    }
+}
+
+struct Ring {
+    slots: [Sample; RING_CAPACITY],
+    /// Monotonically increasing; the live slot is `write_index % RING_CAPACITY`.
+    write_index: AtomicU64,
+}
+
+impl Ring {
+    const fn new() -> Self {
+        Ring {
+            slots: [Sample::empty(); RING_CAPACITY],
+            write_index: AtomicU64::new(0),
+        }
+    }
+}
+
+unsafe impl Sync for Ring {}
+
+const EMPTY_RING: Ring = Ring::new();
+static mut RINGS: [Ring; MAX_CPUS] = [EMPTY_RING; MAX_CPUS];
+
+/// Whether the profiler is currently armed; checked by the sample
+/// interrupt handler before touching anything so a stray NMI that lands
+/// with profiling stopped still falls through to the normal
+/// unhandled-exception panic.
+static PROFILER_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Sample period in effect, so the handler can [`pmu::rearm`] with the
+/// same value it was started with.
+static PERIOD: AtomicU64 = AtomicU64::new(0);
+
+/// Arm the calling core's performance counter to overflow (and deliver a
+/// sample) every `period` unhalted cycles.
+pub fn profiler_start(period: u64) {
+    PERIOD.store(period, Ordering::Relaxed);
+    PROFILER_ACTIVE.store(true, Ordering::Release);
+    pmu::arm(period);
+}
+
+/// Disarm the calling core's performance counter. Already-collected
+/// samples are left in place for [`drain_samples`].
+pub fn profiler_stop() {
+    pmu::disarm();
+    PROFILER_ACTIVE.store(false, Ordering::Release);
+}
+
+/// Whether the active sample interrupt should be treated as a profiler
+/// tick rather than an unhandled exception. Called from NMI context.
+pub(crate) fn is_active() -> bool {
+    PROFILER_ACTIVE.load(Ordering::Acquire)
+}
+
+/// The period the handler should rearm the counter with after each
+/// sample. Called from NMI context.
+pub(crate) fn current_period() -> u64 {
+    PERIOD.load(Ordering::Relaxed)
+}
+
+/// Push a sample into the calling core's ring. Called from NMI context:
+/// never takes a lock and never touches `CpuManager`.
+pub(crate) fn record_sample(pc: u64, timestamp: u64) {
+    let cpu_id = percpu::get_current_cpu_id();
+    let ring = unsafe {
+        let Some(ring) = RINGS.get(cpu_id as usize) else {
+            return;
+        };
+        ring
+    };
+
+    let index = ring.write_index.load(Ordering::Relaxed);
+    let slot = (index as usize) % RING_CAPACITY;
+    unsafe {
+        (*core::ptr::addr_of!(ring.slots))
+            .as_ptr()
+            .add(slot)
+            .cast_mut()
+            .write(Sample { pc, timestamp });
+    }
+    ring.write_index.store(index + 1, Ordering::Release);
+}
+
+/// Drain `cpu_id`'s ring into `(pc, timestamp, cpu_id)` tuples, oldest
+/// first. Reads the ring's write index once up front, so samples the NMI
+/// handler pushes on that core after this call started aren't included --
+/// this is a snapshot, not a strict drain-to-empty.
+pub fn drain_samples(cpu_id: u32) -> Vec<(u64, u64, u32)> {
+    let mut out = Vec::new();
+    let Some(ring) = (unsafe { RINGS.get(cpu_id as usize) }) else {
+        return out;
+    };
+
+    let end = ring.write_index.load(Ordering::Acquire);
+    let start = end.saturating_sub(RING_CAPACITY as u64);
+    for i in start..end {
+        let sample = ring.slots[(i as usize) % RING_CAPACITY];
+        out.push((sample.pc, sample.timestamp, cpu_id));
+    }
+    out
+}
@@ -12,14 +12,73 @@ pub struct PerCpuRunQueue {
     pub head: usize,
     pub tail: usize,
     pub count: usize,
+    /// EDF-admitted tasks (`period > 0`), kept as a binary min-heap keyed
+    /// on `deadline`; `deadline_heap[0]` is always the earliest deadline.
+    deadline_heap: [Option<TaskRef>; MAX_TASKS_PER_QUEUE],
+    deadline_len: usize,
+    /// Sum of `runtime * UTIL_SCALE / period` over every EDF task admitted
+    /// to this queue, used for admission control.
+    utilization: u64,
+    /// Ceiling `utilization` may not exceed; default `UTIL_SCALE` (100% of
+    /// one CPU).
+    utilization_bound: u64,
 }
 
 pub const MAX_TASKS_PER_QUEUE: usize = 64;
 
+/// Fixed-point scale for `utilization`/`utilization_bound`: a task using
+/// its whole period (`runtime == period`) contributes exactly `UTIL_SCALE`.
+pub const UTIL_SCALE: u64 = 1_000_000;
+
 #[derive(Debug, Clone, Copy)]
 pub struct TaskRef {
     pub task_id: u64,
     pub priority: u8,
+    /// Worst-case execution time per period, in scheduler ticks.
+    pub runtime: u64,
+    /// Deadline-scheduling period, in scheduler ticks. `0` means this task
+    /// is best-effort and only runs via the FIFO path.
+    pub period: u64,
+    /// Absolute deadline (ticks), meaningful only when `period > 0`.
+    pub deadline: u64,
+    /// Bitmask of CPUs this task may run on (bit `n` = `cpu_id` `n`).
+    /// Defaults to `u64::MAX` (may run anywhere) via the constructors
+    /// below; `rebalance_all`/`load_balance` skip a migration whose
+    /// destination CPU's bit is clear.
+    pub cpu_affinity: u64,
+}
+
+impl TaskRef {
+    /// Best-effort task: FIFO-scheduled, no deadline.
+    pub fn best_effort(task_id: u64, priority: u8) -> Self {
+        TaskRef { task_id, priority, runtime: 0, period: 0, deadline: 0, cpu_affinity: u64::MAX }
+    }
+
+    /// EDF task with the given budget, period, and initial absolute
+    /// deadline.
+    pub fn deadline(task_id: u64, priority: u8, runtime: u64, period: u64, deadline: u64) -> Self {
+        TaskRef { task_id, priority, runtime, period, deadline, cpu_affinity: u64::MAX }
+    }
+
+    /// Restricts this task to the CPUs set in `mask`.
+    pub fn with_affinity(mut self, mask: u64) -> Self {
+        self.cpu_affinity = mask;
+        self
+    }
+
+    fn utilization(&self) -> u64 {
+        if self.period == 0 {
+            0
+        } else {
+            self.runtime.saturating_mul(UTIL_SCALE) / self.period
+        }
+    }
+
+    /// Push this task's deadline out by one more period, for re-insertion
+    /// once the period it was servicing has elapsed.
+    pub fn advance_deadline(&mut self) {
+        self.deadline = self.deadline.saturating_add(self.period);
+    }
 }
 
 pub struct MultiCpuScheduler {
@@ -35,12 +94,38 @@ impl PerCpuRunQueue {
             head: 0,
             tail: 0,
             count: 0,
+            deadline_heap: [None; MAX_TASKS_PER_QUEUE],
+            deadline_len: 0,
+            utilization: 0,
+            utilization_bound: UTIL_SCALE,
         }
     }
 
+    /// `enqueue`s `task` into the EDF heap if it carries a deadline
+    /// (`period > 0`), otherwise the FIFO ring buffer. Admission control
+    /// for EDF tasks happens in `MultiCpuScheduler::enqueue_task`, not
+    /// here, since it must weigh `utilization` before the task is ever
+    /// handed to a specific queue.
     pub fn enqueue(&mut self, task: TaskRef) -> bool {
+        if task.period > 0 {
+            self.heap_push(task)
+        } else {
+            self.fifo_push(task)
+        }
+    }
+
+    /// EDF tasks always win: the heap's root (earliest deadline) is
+    /// dequeued first, and the FIFO path only runs once the heap drains.
+    pub fn dequeue(&mut self) -> Option<TaskRef> {
+        if self.deadline_len > 0 {
+            return self.heap_pop();
+        }
+        self.fifo_pop()
+    }
+
+    fn fifo_push(&mut self, task: TaskRef) -> bool {
         if self.count >= MAX_TASKS_PER_QUEUE {
-            return false;  // Queue full
+            return false; // Queue full
         }
 
         self.tasks[self.tail] = Some(task);
@@ -49,7 +134,7 @@ impl PerCpuRunQueue {
         true
     }
 
-    pub fn dequeue(&mut self) -> Option<TaskRef> {
+    fn fifo_pop(&mut self) -> Option<TaskRef> {
         if self.count == 0 {
             return None;
         }
@@ -61,12 +146,102 @@ impl PerCpuRunQueue {
         task
     }
 
+    /// Removes and returns the first FIFO task (in queue order) whose
+    /// `cpu_affinity` permits running on `dest_cpu_id`, leaving every
+    /// other task's relative order unchanged. Deadline tasks are never
+    /// migrated this way: once admitted their utilization is accounted
+    /// for on this CPU, so only the FIFO best-effort pool is scanned.
+    fn take_affine(&mut self, dest_cpu_id: u32) -> Option<TaskRef> {
+        let bit = 1u64 << (dest_cpu_id % 64);
+        let mut drained: [Option<TaskRef>; MAX_TASKS_PER_QUEUE] = [None; MAX_TASKS_PER_QUEUE];
+        let n = {
+            let mut n = 0;
+            while let Some(t) = self.fifo_pop() {
+                drained[n] = Some(t);
+                n += 1;
+            }
+            n
+        };
+
+        let mut picked = None;
+        for slot in drained.iter_mut().take(n) {
+            if let Some(task) = *slot {
+                if picked.is_none() && task.cpu_affinity & bit != 0 {
+                    picked = Some(task);
+                    continue;
+                }
+                self.fifo_push(task);
+            }
+        }
+        picked
+    }
+
     pub fn peek(&self) -> Option<TaskRef> {
-        self.tasks[self.head]
+        if self.deadline_len > 0 {
+            self.deadline_heap[0]
+        } else {
+            self.tasks[self.head]
+        }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.count == 0
+        self.count == 0 && self.deadline_len == 0
+    }
+
+    fn heap_push(&mut self, task: TaskRef) -> bool {
+        if self.deadline_len >= MAX_TASKS_PER_QUEUE {
+            return false; // Heap full
+        }
+
+        let mut idx = self.deadline_len;
+        self.deadline_heap[idx] = Some(task);
+        self.deadline_len += 1;
+
+        while idx > 0 {
+            let parent = (idx - 1) / 2;
+            if self.deadline_heap[parent].unwrap().deadline <= self.deadline_heap[idx].unwrap().deadline {
+                break;
+            }
+            self.deadline_heap.swap(parent, idx);
+            idx = parent;
+        }
+        true
+    }
+
+    fn heap_pop(&mut self) -> Option<TaskRef> {
+        if self.deadline_len == 0 {
+            return None;
+        }
+
+        let root = self.deadline_heap[0];
+        self.deadline_len -= 1;
+        self.deadline_heap[0] = self.deadline_heap[self.deadline_len];
+        self.deadline_heap[self.deadline_len] = None;
+
+        let mut idx = 0;
+        loop {
+            let left = 2 * idx + 1;
+            let right = 2 * idx + 2;
+            let mut smallest = idx;
+
+            if left < self.deadline_len
+                && self.deadline_heap[left].unwrap().deadline < self.deadline_heap[smallest].unwrap().deadline
+            {
+                smallest = left;
+            }
+            if right < self.deadline_len
+                && self.deadline_heap[right].unwrap().deadline < self.deadline_heap[smallest].unwrap().deadline
+            {
+                smallest = right;
+            }
+            if smallest == idx {
+                break;
+            }
+            self.deadline_heap.swap(idx, smallest);
+            idx = smallest;
+        }
+
+        root
     }
 }
 
@@ -82,12 +257,54 @@ impl MultiCpuScheduler {
         }
     }
 
+    /// Enqueues `task`. EDF tasks (`period > 0`) go through admission
+    /// control first: if `queue.utilization + task`'s own utilization
+    /// would exceed `queue.utilization_bound`, the task is rejected
+    /// outright rather than enqueued.
     pub fn enqueue_task(&self, cpu_id: u32, task: TaskRef) -> bool {
+        if (cpu_id as usize) >= MAX_CPUS {
+            return false;
+        }
+
+        let mut queue = self.run_queues[cpu_id as usize].lock();
+        if task.period > 0 {
+            let task_util = task.utilization();
+            if queue.utilization.saturating_add(task_util) > queue.utilization_bound {
+                return false;
+            }
+            if !queue.enqueue(task) {
+                return false;
+            }
+            queue.utilization = queue.utilization.saturating_add(task_util);
+            true
+        } else {
+            queue.enqueue(task)
+        }
+    }
+
+    /// Re-inserts `task` after it has run out its current slice: EDF
+    /// tasks get their deadline advanced by one more `period` (their
+    /// utilization was already admitted, so no admission check here);
+    /// best-effort tasks just go back on the FIFO tail unchanged.
+    pub fn requeue_task(&self, cpu_id: u32, mut task: TaskRef) -> bool {
+        if (cpu_id as usize) >= MAX_CPUS {
+            return false;
+        }
+
+        if task.period > 0 {
+            task.advance_deadline();
+        }
+
+        let mut queue = self.run_queues[cpu_id as usize].lock();
+        queue.enqueue(task)
+    }
+
+    /// Overrides the default `UTIL_SCALE` (100%) admission ceiling for a
+    /// CPU's EDF queue.
+    pub fn set_utilization_bound(&self, cpu_id: u32, bound: u64) {
         if (cpu_id as usize) < MAX_CPUS {
             let mut queue = self.run_queues[cpu_id as usize].lock();
-            queue.enqueue(task)
-        } else {
-            false
+            queue.utilization_bound = bound;
         }
     }
 
@@ -123,36 +340,33 @@ impl MultiCpuScheduler {
         }
     }
 
-    /// Load balancing: steal tasks from busier CPUs
+    /// Load balancing: steal a task from a busier CPU, skipping any task
+    /// whose `cpu_affinity` doesn't permit running on `idle_cpu_id`.
     pub fn load_balance(&self, idle_cpu_id: u32) -> Option<TaskRef> {
-        // Simple round-robin work stealing
-        // Try to find a CPU with tasks and steal from it
-        
         for i in 0..MAX_CPUS {
             if i as u32 == idle_cpu_id {
                 continue;
             }
 
             let mut queue = self.run_queues[i].lock();
-            if !queue.is_empty() {
-                // Try to steal a task
-                if let Some(task) = queue.dequeue() {
-                    return Some(task);
-                }
+            if let Some(task) = queue.take_affine(idle_cpu_id) {
+                return Some(task);
             }
         }
-        
+
         None
     }
 
-    pub fn rebalance_all(&self) {
-        // Try to balance tasks across CPUs
-        // This is a simplified version - more sophisticated algorithms exist
-        
+    /// Moves surplus FIFO tasks from overloaded queues (`count > target`)
+    /// onto the least-loaded underloaded queue, honoring `cpu_affinity`.
+    /// Both queues involved in a move are locked in ascending `cpu_id`
+    /// order to avoid deadlocking against a concurrent migration running
+    /// the opposite direction. Returns how many tasks were migrated, so a
+    /// caller can decide whether another pass is worthwhile.
+    pub fn rebalance_all(&self) -> usize {
         let mut total_tasks = 0;
         let mut active_cpus = 0;
 
-        // Count total tasks and active CPUs
         for i in 0..MAX_CPUS {
             let queue = self.run_queues[i].lock();
             if !queue.is_empty() {
@@ -162,21 +376,66 @@ impl MultiCpuScheduler {
         }
 
         if active_cpus == 0 || total_tasks == 0 {
-            return;
+            return 0;
         }
 
-        // Target tasks per CPU
         let target = (total_tasks + active_cpus - 1) / active_cpus;
+        let mut migrated = 0;
 
-        // Move tasks around to balance
-        for i in 0..MAX_CPUS {
-            let mut queue = self.run_queues[i].lock();
-            if queue.count > target {
-                // Too many tasks on this CPU
-                // Would need to move excess to other CPUs
-                // This is simplified - proper implementation would be more complex
+        for src in 0..MAX_CPUS {
+            loop {
+                let src_count = self.run_queues[src].lock().count;
+                if src_count <= target {
+                    break;
+                }
+
+                // Find the least-loaded underloaded destination.
+                let mut dest = None;
+                let mut dest_count = target;
+                for d in 0..MAX_CPUS {
+                    if d == src {
+                        continue;
+                    }
+                    let c = self.run_queues[d].lock().count;
+                    if c < dest_count {
+                        dest_count = c;
+                        dest = Some(d);
+                    }
+                }
+
+                let dest = match dest {
+                    Some(d) => d,
+                    None => break,
+                };
+
+                let (lo, hi) = if src < dest { (src, dest) } else { (dest, src) };
+                let moved = {
+                    let mut lo_guard = self.run_queues[lo].lock();
+                    let mut hi_guard = self.run_queues[hi].lock();
+                    let (src_queue, dest_queue) = if lo == src {
+                        (&mut *lo_guard, &mut *hi_guard)
+                    } else {
+                        (&mut *hi_guard, &mut *lo_guard)
+                    };
+                    match src_queue.take_affine(dest as u32) {
+                        Some(task) => {
+                            dest_queue.fifo_push(task);
+                            true
+                        }
+                        None => false,
+                    }
+                };
+
+                if !moved {
+                    // Nothing left on `src` is allowed to run on any
+                    // underloaded CPU; further looping would spin forever.
+                    break;
+                }
+                migrated += 1;
             }
         }
+
+        migrated
     }
 }
 
@@ -0,0 +1,83 @@
+//! CPU identification, feeding `hwinfo::CpuInfo`.
+//!
+//! On x86_64, decodes the vendor string and family/model/stepping out of
+//! `cpuid` leaves 0 and 1, including the extended-family/extended-model
+//! adjustment the SDM requires once the base family reaches 0xF (or, for
+//! model, whenever the base family is 0x6 or 0xF). On AArch64 there is no
+//! CPUID instruction, so `MIDR_EL1` stands in: its `Implementer` byte maps
+//! to the ASCII vendor string JEDEC assigns that implementer, and its
+//! `Variant`/`PartNum`/`Revision` fields line up with model/stepping.
+
+use crate::hwinfo::CpuInfo;
+
+#[cfg(target_arch = "x86_64")]
+pub fn detect() -> CpuInfo {
+    use crate::x86_64::cpu::cpuid;
+
+    let leaf0 = cpuid(0, 0);
+    let mut vendor = [0u8; 12];
+    vendor[0..4].copy_from_slice(&leaf0.ebx.to_le_bytes());
+    vendor[4..8].copy_from_slice(&leaf0.edx.to_le_bytes());
+    vendor[8..12].copy_from_slice(&leaf0.ecx.to_le_bytes());
+
+    let leaf1 = cpuid(1, 0);
+    let eax = leaf1.eax;
+
+    let base_stepping = eax & 0xF;
+    let base_model = (eax >> 4) & 0xF;
+    let base_family = (eax >> 8) & 0xF;
+    let ext_model = (eax >> 16) & 0xF;
+    let ext_family = (eax >> 20) & 0xFF;
+
+    let family = if base_family == 0xF {
+        base_family + ext_family
+    } else {
+        base_family
+    };
+
+    let model = if base_family == 0x6 || base_family == 0xF {
+        (ext_model << 4) | base_model
+    } else {
+        base_model
+    };
+
+    CpuInfo {
+        vendor,
+        family,
+        model,
+        stepping: base_stepping,
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+pub fn detect() -> CpuInfo {
+    let midr: u64;
+    unsafe {
+        core::arch::asm!("mrs {}, midr_el1", out(reg) midr);
+    }
+
+    let implementer = ((midr >> 24) & 0xFF) as u8;
+    let variant = ((midr >> 20) & 0xF) as u32;
+    let part_num = ((midr >> 4) & 0xFFF) as u32;
+    let revision = (midr & 0xF) as u32;
+
+    let mut vendor = *b"            ";
+    vendor[0] = implementer;
+
+    CpuInfo {
+        vendor,
+        family: variant,
+        model: part_num,
+        stepping: revision,
+    }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub fn detect() -> CpuInfo {
+    CpuInfo {
+        vendor: *b"            ",
+        family: 0,
+        model: 0,
+        stepping: 0,
+    }
+}
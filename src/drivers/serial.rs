@@ -1,7 +1,7 @@
 
 use crate::lib::spinlock::Spinlock;
 
-const COM1: u16 = 0x3F8;
+pub const COM1: u16 = 0x3F8;
 
 pub struct SerialPort {
     port: u16,
@@ -3,11 +3,15 @@
 //! This module provides a production-grade PL011 UART driver for ARM64 platforms
 //! supporting hardware flow control, DMA, and all standard PL011 features.
 
+use alloc::vec::Vec;
 use core::sync::atomic::Ordering;
+use core::task::{Context, Poll, Waker};
+use crate::lib::spinlock::Spinlock;
 use super::uart16550::{UartConfig, Parity, FlowControl, UartError, RingBuffer, UartStats, MAX_BAUD};
 
 /// PL011 register offsets (32-bit aligned)
 const REG_DR:        usize = 0x00;  // Data Register
+const REG_ILPR:      usize = 0x20;  // IrDA Low-Power Counter Register
 const REG_FR:        usize = 0x18;  // Flag Register
 const REG_IBRD:      usize = 0x24;  // Integer Baud Rate Divisor
 const REG_FBRD:      usize = 0x28;  // Fractional Baud Rate Divisor
@@ -88,8 +92,28 @@ const FIFO_SIZE: usize = 16;
 const RX_BUFFER_SIZE: usize = 4096;
 const TX_BUFFER_SIZE: usize = 4096;
 
+/// XON/XOFF software flow control bytes (DC1/DC3 in ASCII), matching
+/// `Uart16550`'s own.
+const XON_BYTE: u8 = 0x11;
+const XOFF_BYTE: u8 = 0x13;
+
 use super::uart16550::{MAX_BAUD};
 
+/// IrDA SIR mode a [`Pl011Config`] programs via `CR_SIREN`/`CR_SIRLP`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrdaMode {
+    /// Normal UART operation -- `CR_SIREN` left clear.
+    Off,
+    /// IrDA SIR encoder/decoder enabled at full power.
+    Sir,
+    /// IrDA SIR enabled in low-power mode: the transmitted pulse width is
+    /// derived from `REG_ILPR`'s divisor instead of `SIRIN`'s raw pulse,
+    /// trading range for lower power draw. `divisor` is the IrLPBaud16
+    /// count `configure` would otherwise compute from `SYSTEM_CLOCK`
+    /// itself (see [`ilpr_divisor`]) -- set to `0` to use that default.
+    SirLowPower { divisor: u8 },
+}
+
 /// PL011-specific configuration
 #[derive(Debug, Clone, Copy)]
 pub struct Pl011Config {
@@ -100,6 +124,19 @@ pub struct Pl011Config {
     pub flow_control: FlowControl,
     pub enable_fifo: bool,
     pub enable_break: bool,
+    pub irda: IrdaMode,
+    /// Under `FlowControl::XonXoff`, the `rx_buffer` fill level that makes
+    /// the driver send XOFF. Must be greater than `rx_low_water`.
+    pub rx_high_water: usize,
+    /// Under `FlowControl::XonXoff`, the `rx_buffer` fill level the driver
+    /// must drain back below before it sends XON.
+    pub rx_low_water: usize,
+    /// The UART's actual reference clock (UARTCLK) in Hz, used for the
+    /// IBRD/FBRD baud divisor math in [`Pl011Uart::configure`] -- this
+    /// varies by board (the Pi family, Zynq, and va108xx all wire up a
+    /// different peripheral clock), so it can't be a compile-time
+    /// constant. Defaults to [`SYSTEM_CLOCK`], the common 48 MHz case.
+    pub uart_clock_hz: u32,
 }
 
 impl Default for Pl011Config {
@@ -112,7 +149,139 @@ impl Default for Pl011Config {
             flow_control: FlowControl::None,
             enable_fifo: true,
             enable_break: false,
+            irda: IrdaMode::Off,
+            rx_high_water: RX_BUFFER_SIZE * 3 / 4,
+            rx_low_water: RX_BUFFER_SIZE / 4,
+            uart_clock_hz: SYSTEM_CLOCK,
+        }
+    }
+}
+
+/// IrLPBaud16 divisor for [`IrdaMode::SirLowPower`]'s default (`divisor:
+/// 0`): `round(clock_hz / 1_843_200)`, deriving the low-power pulse from
+/// the ~1.8432 MHz reference PL011's low-power counter expects.
+fn ilpr_divisor(clock_hz: u32) -> u8 {
+    ((clock_hz + 1_843_200 / 2) / 1_843_200) as u8
+}
+
+/// Maximum scatter-gather descriptors [`Pl011Uart::start_rx_dma`]/
+/// [`Pl011Uart::start_tx_dma`] can track per transfer -- generous for the
+/// small transfers a UART moves, bounded so no allocator is needed.
+const MAX_DMA_DESCRIPTORS: usize = 8;
+
+/// One physically-contiguous buffer region in a scatter-gather DMA list: a
+/// base address and byte length. The caller owns the memory `base` points
+/// into and must keep it alive and valid for the duration of the transfer.
+#[derive(Debug, Clone, Copy)]
+pub struct DmaDescriptor {
+    pub base: usize,
+    pub len: usize,
+}
+
+/// Which direction an in-flight scatter-gather transfer is moving,
+/// mirroring `Uart16550`'s own `DmaDirection`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DmaDirection {
+    Rx,
+    Tx,
+}
+
+/// A scatter-gather transfer armed by `start_rx_dma`/`start_tx_dma`: the
+/// descriptor list (copied in, since `no_std` has no allocator to hold a
+/// borrowed slice across calls), which one is in flight, and how far into
+/// it the transfer has progressed.
+struct PendingDma {
+    direction: DmaDirection,
+    descriptors: [Option<DmaDescriptor>; MAX_DMA_DESCRIPTORS],
+    current: usize,
+    offset_in_current: usize,
+    total_transferred: usize,
+}
+
+/// Which way a captured [`CaptureRecord`] byte was moving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaptureDirection {
+    #[default]
+    Tx,
+    Rx,
+}
+
+/// One byte captured by [`Pl011Uart::enable_capture`]: when it passed
+/// through the driver (`timestamp`, from [`crate::kernel::profiler::rdtsc`]),
+/// which direction it moved, the byte itself, and `flags` -- the PL011
+/// data register's error bits (`REG_DR` bits 15:8) when known, `0`
+/// otherwise (e.g. a byte already sitting in `rx_buffer` by the time it's
+/// captured, which doesn't carry its original error bits).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CaptureRecord {
+    pub timestamp: u64,
+    pub direction: CaptureDirection,
+    pub byte: u8,
+    pub flags: u8,
+}
+
+/// Ring capacity for [`CaptureRing`] -- one slot is reserved as the
+/// empty/full sentinel by [`RingBuffer`], so this holds 2047 records.
+const CAPTURE_RING_SIZE: usize = 2048;
+
+/// Byte-level capture sink armed via [`Pl011Uart::enable_capture`]: every
+/// byte through `send`/`handle_tx_interrupt`/`try_receive`/
+/// `handle_rx_interrupt` is appended here while armed. A full ring simply
+/// drops the newest byte (this is a debugging aid, not a correctness
+/// path) -- drain it with [`Self::drain_pcap`] before it can fill up.
+pub struct CaptureRing {
+    ring: RingBuffer<CaptureRecord, CAPTURE_RING_SIZE>,
+}
+
+impl CaptureRing {
+    pub const fn new() -> Self {
+        Self { ring: RingBuffer::new() }
+    }
+
+    fn record(&mut self, direction: CaptureDirection, byte: u8, flags: u8) {
+        let _ = self.ring.push(CaptureRecord {
+            timestamp: crate::kernel::profiler::rdtsc(),
+            direction,
+            byte,
+            flags,
+        });
+    }
+
+    /// Drain every captured record (oldest first) into a minimal pcap byte
+    /// stream: the standard 24-byte global header (`LINKTYPE_USER0`, so a
+    /// consumer doesn't try to decode the payload as a real link type) plus
+    /// one 16-byte record header and a 3-byte `[direction, flags, byte]`
+    /// payload per captured byte, ready to save to a `.pcap` file and open
+    /// in Wireshark.
+    ///
+    /// `timestamp` is a free-running cycle counter, not wall-clock time --
+    /// it's split into `ts_sec`/`ts_usec` purely so the stream is
+    /// well-formed pcap, not as a meaningful capture time.
+    pub fn drain_pcap(&mut self) -> Vec<u8> {
+        const LINKTYPE_USER0: u32 = 147;
+
+        let mut out = Vec::new();
+        out.extend_from_slice(&0xa1b2c3d4u32.to_le_bytes());
+        out.extend_from_slice(&2u16.to_le_bytes());
+        out.extend_from_slice(&4u16.to_le_bytes());
+        out.extend_from_slice(&0i32.to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(&65535u32.to_le_bytes());
+        out.extend_from_slice(&LINKTYPE_USER0.to_le_bytes());
+
+        while let Some(record) = self.ring.pop() {
+            let ts_sec = (record.timestamp >> 32) as u32;
+            let ts_usec = (record.timestamp as u32) % 1_000_000;
+            let payload = [record.direction as u8, record.flags, record.byte];
+
+            out.extend_from_slice(&ts_sec.to_le_bytes());
+            out.extend_from_slice(&ts_usec.to_le_bytes());
+            out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            out.extend_from_slice(&payload);
         }
+
+        out
     }
 }
 
@@ -124,6 +293,34 @@ pub struct Pl011Uart {
     tx_buffer: RingBuffer<u8, TX_BUFFER_SIZE>,
     stats: UartStats,
     interrupt_enabled: bool,
+    /// Wakers registered by [`Self::poll_read`]/[`Self::poll_write`] while
+    /// waiting on `rx_buffer`/`tx_buffer`, woken from
+    /// [`Self::handle_rx_interrupt`]/[`Self::handle_tx_interrupt`] once
+    /// data or space becomes available -- same shape as `Uart16550`'s own
+    /// `rx_waker`/`tx_waker`.
+    rx_waker: Spinlock<Option<Waker>>,
+    tx_waker: Spinlock<Option<Waker>>,
+    /// The scatter-gather transfer armed by [`Self::start_rx_dma`]/
+    /// [`Self::start_tx_dma`], if one is in flight.
+    pending_dma: Option<PendingDma>,
+    /// Invoked with the total byte count once the transfer `pending_dma`
+    /// describes finishes draining, from [`Self::handle_dma_interrupt`] or
+    /// [`Self::poll_dma_complete`].
+    dma_complete: Option<fn(usize)>,
+    /// Under `FlowControl::XonXoff`: set once `rx_buffer` crosses
+    /// `config.rx_high_water` and we've sent XOFF, cleared (sending XON)
+    /// once it drains back below `config.rx_low_water`. Tracked so we
+    /// don't resend XOFF every byte while still above the high-water mark.
+    rx_paused: bool,
+    /// Under `FlowControl::XonXoff`: set on an incoming XOFF, cleared on
+    /// XON. Gates [`Self::handle_tx_interrupt`]/[`Self::send`].
+    tx_blocked: bool,
+    /// External sink armed by [`Self::enable_capture`], stored as a raw
+    /// pointer so this driver doesn't need a lifetime parameter (it's
+    /// meant to live in a `'static` instance) -- the caller must keep the
+    /// pointee alive for as long as capture stays armed and call
+    /// [`Self::disable_capture`] before it drops.
+    capture: Option<*mut CaptureRing>,
 }
 
 impl Pl011Uart {
@@ -136,6 +333,13 @@ impl Pl011Uart {
             tx_buffer: RingBuffer::new(),
             stats: UartStats::default(),
             interrupt_enabled: false,
+            rx_waker: Spinlock::new(None),
+            tx_waker: Spinlock::new(None),
+            pending_dma: None,
+            dma_complete: None,
+            rx_paused: false,
+            tx_blocked: false,
+            capture: None,
         }
     }
 
@@ -157,16 +361,24 @@ impl Pl011Uart {
             return Err(UartError::InvalidConfig);
         }
 
+        // The IBRD field is 16 bits wide, so the divisor must fit -- and it
+        // must be at least 1, or the requested baud is faster than
+        // `uart_clock_hz` can produce at all.
+        let divisor_check = (config.uart_clock_hz as u64) / (16 * config.baud_rate as u64);
+        if divisor_check < 1 || divisor_check > 65535 {
+            return Err(UartError::InvalidBaudRate);
+        }
+
         self.config = config;
 
         // Disable UART during configuration
         self.clear_bits(REG_CR as u32, CR_UARTEN);
 
         // Calculate baud rate divisor
-        // IBRD = floor(system_clock / (16 * baud_rate))
-        // FBRD = round((64 * frac) / 16) where frac = (system_clock % (16 * baud_rate)) / baud_rate
-        let baud_divisor = (SYSTEM_CLOCK as u64) / (16 * config.baud_rate as u64) as u32;
-        let remainder = (SYSTEM_CLOCK as u64) % (16 * config.baud_rate as u64);
+        // IBRD = floor(uart_clock_hz / (16 * baud_rate))
+        // FBRD = round((64 * frac) / 16) where frac = (uart_clock_hz % (16 * baud_rate)) / baud_rate
+        let baud_divisor = divisor_check as u32;
+        let remainder = (config.uart_clock_hz as u64) % (16 * config.baud_rate as u64);
         let frac = (remainder * 64 + 8) / (16 * config.baud_rate as u64);  // Round to nearest
 
         self.write_reg(REG_IBRD, baud_divisor);
@@ -223,6 +435,23 @@ impl Pl011Uart {
             }
         }
 
+        // IrDA SIR / low-power SIR
+        match config.irda {
+            IrdaMode::Off => {}
+            IrdaMode::Sir => {
+                cr |= CR_SIREN;
+            }
+            IrdaMode::SirLowPower { divisor } => {
+                cr |= CR_SIREN | CR_SIRLP;
+                let divisor = if divisor == 0 {
+                    ilpr_divisor(config.uart_clock_hz)
+                } else {
+                    divisor
+                };
+                self.write_reg(REG_ILPR, divisor as u32);
+            }
+        }
+
         self.write_reg(REG_CR, cr);
 
         // Clear all interrupts
@@ -256,6 +485,12 @@ impl Pl011Uart {
 
     /// Send a single byte (blocking)
     pub fn send(&mut self, byte: u8) {
+        // Under `FlowControl::XonXoff`, stop feeding the FIFO while the
+        // remote has told us to pause with XOFF.
+        while self.tx_blocked {
+            core::hint::spin_loop();
+        }
+
         // Wait for TX FIFO to have space
         while self.is_transmit_fifo_full() {
             core::hint::spin_loop();
@@ -264,6 +499,7 @@ impl Pl011Uart {
         // Write to data register
         self.write_reg(REG_DR, byte as u32);
         self.stats.bytes_sent.fetch_add(1, Ordering::Relaxed);
+        self.capture_byte(CaptureDirection::Tx, byte, 0);
     }
 
     /// Send a buffer of bytes (blocking)
@@ -297,11 +533,14 @@ impl Pl011Uart {
             }
 
             self.stats.bytes_received.fetch_add(1, Ordering::Relaxed);
+            self.capture_byte(CaptureDirection::Rx, byte, ((dr & 0xFF00) >> 8) as u8);
             return Some(byte);
         }
 
         // Check software buffer
-        self.rx_buffer.pop()
+        let byte = self.rx_buffer.pop()?;
+        self.capture_byte(CaptureDirection::Rx, byte, 0);
+        Some(byte)
     }
 
     /// Receive a single byte (blocking)
@@ -325,7 +564,9 @@ impl Pl011Uart {
                 if dr & 0x400 != 0 {
                     self.stats.break_interrupts.fetch_add(1, Ordering::Relaxed);
                 }
-                return Err(UartError::ParityError);  // Simplified error handling
+                if let Some(err) = dr_error(dr) {
+                    return Err(err);
+                }
             }
 
             self.stats.bytes_received.fetch_add(1, Ordering::Relaxed);
@@ -385,6 +626,12 @@ impl Pl011Uart {
             let has_error = dr & 0xFF00 != 0;
             let byte = (dr & 0xFF) as u8;
 
+            self.capture_byte(CaptureDirection::Rx, byte, ((dr & 0xFF00) >> 8) as u8);
+
+            if self.intercept_flow_control(byte) {
+                continue;
+            }
+
             self.stats.bytes_received.fetch_add(1, Ordering::Relaxed);
 
             if has_error {
@@ -399,15 +646,28 @@ impl Pl011Uart {
                 self.stats.overruns.fetch_add(1, Ordering::Relaxed);
             }
         }
+
+        self.update_flow_control_watermark();
+
+        if !self.rx_buffer.is_empty() {
+            if let Some(waker) = self.rx_waker.lock().take() {
+                waker.wake();
+            }
+        }
     }
 
     /// Process transmit interrupt - should be called from IRQ handler
     pub fn handle_tx_interrupt(&mut self) {
+        if self.tx_blocked {
+            return;
+        }
+
         // Fill TX FIFO from software buffer
         while !self.is_transmit_fifo_full() {
             if let Some(byte) = self.tx_buffer.pop() {
                 self.write_reg(REG_DR, byte as u32);
                 self.stats.bytes_sent.fetch_add(1, Ordering::Relaxed);
+                self.capture_byte(CaptureDirection::Tx, byte, 0);
             } else {
                 break;
             }
@@ -417,6 +677,66 @@ impl Pl011Uart {
         if self.tx_buffer.is_empty() && self.interrupt_enabled {
             self.clear_bits(REG_IMSC as u32, IMSC_TXIM);
         }
+
+        if self.tx_buffer.is_empty() {
+            if let Some(waker) = self.tx_waker.lock().take() {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Async read: drains whatever is already sitting in `rx_buffer` into
+    /// `buf`, completing immediately if that's non-empty. Otherwise
+    /// registers `cx`'s waker to be woken by the next
+    /// [`Self::handle_rx_interrupt`] and returns [`Poll::Pending`].
+    pub fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<usize> {
+        let count = self.receive_into(buf);
+        if count > 0 {
+            return Poll::Ready(count);
+        }
+
+        *self.rx_waker.lock() = Some(cx.waker().clone());
+
+        // Re-check after registering in case a byte arrived between the
+        // `receive_into` above and the waker being stored.
+        let count = self.receive_into(buf);
+        if count > 0 {
+            self.rx_waker.lock().take();
+            return Poll::Ready(count);
+        }
+
+        Poll::Pending
+    }
+
+    /// `poll_read` wrapped as an awaitable future.
+    pub async fn read(&mut self, buf: &mut [u8]) -> usize {
+        core::future::poll_fn(|cx| self.poll_read(cx, buf)).await
+    }
+
+    /// Async write: enqueues `data` into `tx_buffer` (resuming from
+    /// wherever a prior `Poll::Pending` left off, so bytes are never
+    /// re-pushed) and arms `IMSC_TXIM` so [`Self::handle_tx_interrupt`]
+    /// drains it, completing once every byte has both been pushed and
+    /// the buffer has fully emptied back out.
+    pub async fn write(&mut self, data: &[u8]) {
+        let mut pushed = 0usize;
+        core::future::poll_fn(|cx| {
+            while pushed < data.len() && self.tx_buffer.push(data[pushed]).is_ok() {
+                pushed += 1;
+            }
+
+            if pushed > 0 {
+                self.set_bits(REG_IMSC as u32, IMSC_TXIM);
+            }
+
+            if pushed == data.len() && self.tx_buffer.is_empty() {
+                return Poll::Ready(());
+            }
+
+            *self.tx_waker.lock() = Some(cx.waker().clone());
+            Poll::Pending
+        })
+        .await
     }
 
     /// Process error interrupt - should be called from IRQ handler
@@ -511,6 +831,218 @@ impl Pl011Uart {
         self.write_reg(REG_DMACR, 0);
     }
 
+    /// Register the callback [`Self::handle_dma_interrupt`]/
+    /// [`Self::poll_dma_complete`] run once every descriptor in the current
+    /// transfer has drained, passed the total byte count moved.
+    pub fn set_dma_complete_callback(&mut self, callback: fn(usize)) {
+        self.dma_complete = Some(callback);
+    }
+
+    /// Submit a scatter-gather receive transfer across `descriptors` and
+    /// arm `DMACR_RXDMAE`/`DMACR_DMAONERR`. Only the first
+    /// [`MAX_DMA_DESCRIPTORS`] entries are tracked; issue a follow-up
+    /// transfer from the completion callback for anything beyond that.
+    pub fn start_rx_dma(&mut self, descriptors: &mut [DmaDescriptor]) {
+        self.start_dma(DmaDirection::Rx, descriptors);
+        self.enable_dma(true, false, true);
+    }
+
+    /// Submit a scatter-gather transmit transfer across `descriptors` and
+    /// arm `DMACR_TXDMAE`/`DMACR_DMAONERR`. Only the first
+    /// [`MAX_DMA_DESCRIPTORS`] entries are tracked; issue a follow-up
+    /// transfer from the completion callback for anything beyond that.
+    pub fn start_tx_dma(&mut self, descriptors: &mut [DmaDescriptor]) {
+        self.start_dma(DmaDirection::Tx, descriptors);
+        self.enable_dma(false, true, true);
+    }
+
+    fn start_dma(&mut self, direction: DmaDirection, descriptors: &[DmaDescriptor]) {
+        let mut list: [Option<DmaDescriptor>; MAX_DMA_DESCRIPTORS] = [None; MAX_DMA_DESCRIPTORS];
+        for (slot, desc) in list.iter_mut().zip(descriptors.iter()) {
+            *slot = Some(*desc);
+        }
+        self.pending_dma = Some(PendingDma {
+            direction,
+            descriptors: list,
+            current: 0,
+            offset_in_current: 0,
+            total_transferred: 0,
+        });
+    }
+
+    /// Process the platform DMA controller's completion/error IRQ for this
+    /// UART's channel. This tree has no platform DMA engine wired up yet,
+    /// so on a clean completion bytes are moved a FIFO-drain-at-a-time
+    /// here rather than by real DMA hardware -- honest about that gap the
+    /// same way `arch::arm64::gic`'s UART IRQ wiring notes the missing
+    /// vector table. On error (`DMACR_DMAONERR` tripped), halts the
+    /// channel, flushes whatever is still sitting in the hardware FIFO
+    /// into `rx_buffer` so it isn't lost, and counts an overrun.
+    pub fn handle_dma_interrupt(&mut self, error: bool) {
+        if error {
+            self.halt_dma_on_error();
+            return;
+        }
+        self.pump_dma();
+    }
+
+    /// For platforms without a DMA completion interrupt: pump the armed
+    /// transfer forward, returning the total bytes moved once every
+    /// descriptor has drained, or `None` while still in flight (or if no
+    /// transfer is armed).
+    pub fn poll_dma_complete(&mut self) -> Option<usize> {
+        self.pump_dma()
+    }
+
+    /// Move bytes between `pending_dma`'s current descriptor and the DR
+    /// FIFO until the hardware can't accept/produce any more, or the
+    /// transfer finishes -- at which point `pending_dma` is cleared, DMA
+    /// is disabled, `dma_complete` is invoked, and the total is returned.
+    fn pump_dma(&mut self) -> Option<usize> {
+        loop {
+            let (direction, desc, offset) = match &self.pending_dma {
+                Some(pending) => match pending.descriptors[pending.current] {
+                    Some(desc) => (pending.direction, desc, pending.offset_in_current),
+                    None => {
+                        let total = pending.total_transferred;
+                        self.pending_dma = None;
+                        self.disable_dma();
+                        if let Some(callback) = self.dma_complete {
+                            callback(total);
+                        }
+                        return Some(total);
+                    }
+                },
+                None => return None,
+            };
+
+            match direction {
+                DmaDirection::Rx => {
+                    if self.is_receive_fifo_empty() {
+                        return None;
+                    }
+                    let dr = self.read_reg(REG_DR);
+                    let byte = (dr & 0xFF) as u8;
+                    unsafe {
+                        ((desc.base + offset) as *mut u8).write_volatile(byte);
+                    }
+                    self.stats.bytes_received.fetch_add(1, Ordering::Relaxed);
+                }
+                DmaDirection::Tx => {
+                    if self.is_transmit_fifo_full() {
+                        return None;
+                    }
+                    let byte = unsafe { ((desc.base + offset) as *const u8).read_volatile() };
+                    self.write_reg(REG_DR, byte as u32);
+                    self.stats.bytes_sent.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+
+            if let Some(pending) = self.pending_dma.as_mut() {
+                pending.total_transferred += 1;
+                pending.offset_in_current += 1;
+                if pending.offset_in_current >= desc.len {
+                    pending.offset_in_current = 0;
+                    pending.current += 1;
+                }
+            }
+        }
+    }
+
+    /// Abort the in-flight transfer on a `DMACR_DMAONERR` trip: disable
+    /// DMA at the hardware, drain whatever is still sitting in the
+    /// receive FIFO into `rx_buffer` (best-effort -- a full `rx_buffer`
+    /// drops the rest), and count an overrun.
+    fn halt_dma_on_error(&mut self) {
+        self.disable_dma();
+        if let Some(pending) = self.pending_dma.take() {
+            if pending.direction == DmaDirection::Rx {
+                while !self.is_receive_fifo_empty() {
+                    let dr = self.read_reg(REG_DR);
+                    let byte = (dr & 0xFF) as u8;
+                    if self.rx_buffer.push(byte).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+        self.stats.overruns.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Under `FlowControl::XonXoff`, swallow an incoming XON/XOFF byte and
+    /// update `tx_blocked` instead of letting it reach `rx_buffer`. Returns
+    /// whether `byte` was consumed as a flow-control byte.
+    fn intercept_flow_control(&mut self, byte: u8) -> bool {
+        if self.config.flow_control != FlowControl::XonXoff {
+            return false;
+        }
+        match byte {
+            XON_BYTE => {
+                self.tx_blocked = false;
+                true
+            }
+            XOFF_BYTE => {
+                self.tx_blocked = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Under `FlowControl::XonXoff`, send XOFF once `rx_buffer` crosses
+    /// `config.rx_high_water` so the sender throttles instead of
+    /// overrunning us, and XON once it has drained back below
+    /// `config.rx_low_water`.
+    fn update_flow_control_watermark(&mut self) {
+        if self.config.flow_control != FlowControl::XonXoff {
+            return;
+        }
+
+        let len = self.rx_buffer.len();
+        if !self.rx_paused && len >= self.config.rx_high_water {
+            self.send_control_byte(XOFF_BYTE);
+            self.rx_paused = true;
+        } else if self.rx_paused && len <= self.config.rx_low_water {
+            self.send_control_byte(XON_BYTE);
+            self.rx_paused = false;
+        }
+    }
+
+    /// Write a flow-control byte straight to the data register, ahead of
+    /// anything queued in `tx_buffer` -- it has to reach the remote
+    /// promptly regardless of `tx_blocked` or how full the software
+    /// buffer is.
+    fn send_control_byte(&mut self, byte: u8) {
+        while self.is_transmit_fifo_full() {
+            core::hint::spin_loop();
+        }
+        self.write_reg(REG_DR, byte as u32);
+    }
+
+    /// Arm byte-level capture: every byte through `send`/
+    /// `handle_tx_interrupt`/`try_receive`/`handle_rx_interrupt` is
+    /// appended to `sink` until [`Self::disable_capture`] is called.
+    /// `sink` must outlive the armed period.
+    pub fn enable_capture(&mut self, sink: &mut CaptureRing) {
+        self.capture = Some(sink as *mut CaptureRing);
+    }
+
+    /// Disarm capture started by [`Self::enable_capture`].
+    pub fn disable_capture(&mut self) {
+        self.capture = None;
+    }
+
+    fn capture_byte(&mut self, direction: CaptureDirection, byte: u8, flags: u8) {
+        if let Some(sink) = self.capture {
+            // Safe: `enable_capture` requires `sink` to outlive the armed
+            // period, and `Pl011Uart` isn't `Sync`, so nothing else can be
+            // concurrently mutating the pointee through this pointer.
+            unsafe {
+                (*sink).record(direction, byte, flags);
+            }
+        }
+    }
+
     /// Read from a register
     fn read_reg(&self, offset: usize) -> u32 {
         let ptr = (self.base + offset) as *const u32;
@@ -535,3 +1067,81 @@ impl Pl011Uart {
         self.write_reg(offset, reg & !bits);
     }
 }
+
+/// Translate `REG_DR`'s error bits (set alongside a received byte) into
+/// the matching [`UartError`] variant, checked in the same order
+/// `receive`/`try_receive` already increment their stats counters in.
+/// `None` means `dr`'s error bits (0xFF00) weren't actually set.
+fn dr_error(dr: u32) -> Option<UartError> {
+    if dr & 0x200 != 0 {
+        Some(UartError::Overrun)
+    } else if dr & 0x800 != 0 {
+        Some(UartError::ParityError)
+    } else if dr & 0x1000 != 0 {
+        Some(UartError::FramingError)
+    } else if dr & 0x400 != 0 {
+        Some(UartError::BreakInterrupt)
+    } else {
+        None
+    }
+}
+
+/// `embedded_hal` impls so generic drivers and the `nb` ecosystem (and
+/// `core::fmt::Write` via `embedded_hal`'s blanket impl) can drive a
+/// [`Pl011Uart`] the same way they'd drive va108xx-hal's or esp-hal's UART
+/// peripherals, on top of the inherent `send`/`try_receive`/`receive`
+/// methods above.
+#[cfg(feature = "embedded-hal")]
+mod embedded_hal_impls {
+    use super::{Pl011Uart, UartError};
+    use embedded_hal::blocking::serial::Write as BlockingWrite;
+    use embedded_hal::serial::{Read, Write};
+
+    impl Read<u8> for Pl011Uart {
+        type Error = UartError;
+
+        fn read(&mut self) -> nb::Result<u8, Self::Error> {
+            if self.is_receive_fifo_empty() {
+                return match self.rx_buffer.pop() {
+                    Some(byte) => Ok(byte),
+                    None => Err(nb::Error::WouldBlock),
+                };
+            }
+
+            self.receive().map_err(nb::Error::Other)
+        }
+    }
+
+    impl Write<u8> for Pl011Uart {
+        type Error = UartError;
+
+        fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+            if self.is_transmit_fifo_full() {
+                return Err(nb::Error::WouldBlock);
+            }
+            self.send(word);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> nb::Result<(), Self::Error> {
+            if self.is_busy() {
+                return Err(nb::Error::WouldBlock);
+            }
+            Ok(())
+        }
+    }
+
+    impl BlockingWrite<u8> for Pl011Uart {
+        type Error = UartError;
+
+        fn bwrite_all(&mut self, buffer: &[u8]) -> Result<(), Self::Error> {
+            self.send_buffer(buffer);
+            Ok(())
+        }
+
+        fn bflush(&mut self) -> Result<(), Self::Error> {
+            while self.is_busy() {}
+            Ok(())
+        }
+    }
+}
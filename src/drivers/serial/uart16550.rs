@@ -10,7 +10,10 @@
 //! - FIFO control with configurable trigger levels
 //! - Modem control signals (DTR, DSR, DCD, RI)
 
-use core::sync::atomic::Ordering;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::{Context, Poll, Waker};
+use crate::lib::spinlock::Spinlock;
 
 /// UART register offsets (DLL/DLM when DLAB=1)
 const REG_RBR: u16 = 0;  // ReadFile Buffer Register (DLAB=0)
@@ -87,6 +90,22 @@ const IIR_RECV_ERROR:     u8 = 0b00000110;
 const IIR_MODEM_STATUS:   u8 = 0b00000000;
 const IIR_FIFO_TIMEOUT:   u8 = 0b00001100;
 
+/// XON/XOFF software flow control bytes (DC1/DC3 in ASCII)
+const XON_BYTE: u8 = 0x11;
+const XOFF_BYTE: u8 = 0x13;
+
+/// `rx_buffer` fill thresholds that drive XON/XOFF under
+/// `FlowControl::XonXoff`: cross the high-water mark and we tell the
+/// sender to pause with XOFF, drain back below the low-water mark and we
+/// tell it to resume with XON. Leaving a gap between the two avoids
+/// chattering XON/XOFF back to back right at one threshold.
+const RX_HIGH_WATER: usize = RX_BUFFER_SIZE * 3 / 4;
+const RX_LOW_WATER: usize = RX_BUFFER_SIZE / 4;
+
+/// Default UART input clock (the classic 8250/16550 reference crystal,
+/// divided by 16 inside the chip to produce the baud clock).
+const DEFAULT_CLOCK_HZ: u32 = 1_843_200;
+
 /// Default baud rate
 const DEFAULT_BAUD: u32 = 115200;
 
@@ -106,6 +125,15 @@ pub struct UartConfig {
     pub parity: Parity,
     pub flow_control: FlowControl,
     pub fifo_trigger: FifoTrigger,
+    /// Legacy 8237 DMA channel to drive `send_dma`/`receive_dma` through.
+    /// `None` (the default) keeps the UART in plain PIO mode.
+    pub dma: Option<DmaChannel>,
+    /// UART input clock driving the baud-rate divisor, in Hz. The 16550's
+    /// divisor latch divides this by 16 per bit, so the default of
+    /// 1.8432 MHz is what yields the classic 115200-and-below rates with
+    /// zero error; boards wired to a different reference clock should set
+    /// this to match.
+    pub clock_hz: u32,
 }
 
 impl Default for UartConfig {
@@ -117,10 +145,113 @@ impl Default for UartConfig {
             parity: Parity::None,
             flow_control: FlowControl::None,
             fifo_trigger: FifoTrigger::Fifo14,
+            dma: None,
+            clock_hz: DEFAULT_CLOCK_HZ,
         }
     }
 }
 
+/// An 8237-style ISA DMA channel this UART can be wired to. `channel`
+/// selects which DREQ/DACK pair the UART's DMA request line is wired to
+/// (0-3 on DMA controller 1, 4-7 on DMA controller 2); the page/address/
+/// count ports are the per-channel registers that wiring uses, left
+/// configurable here rather than hardcoded since board wiring varies.
+#[derive(Debug, Clone, Copy)]
+pub struct DmaChannel {
+    pub channel: u8,
+    pub page_port: u16,
+    pub address_port: u16,
+    pub count_port: u16,
+}
+
+impl DmaChannel {
+    /// The mask/mode/flip-flop-reset ports for this channel's controller:
+    /// DMA1 (channels 0-3) and DMA2 (channels 4-7) are separate 8237A
+    /// chips at different fixed port ranges.
+    fn controller_ports(&self) -> (u16, u16, u16) {
+        if self.channel < 4 {
+            (DMA1_MASK_PORT, DMA1_MODE_PORT, DMA1_CLEAR_FLIPFLOP_PORT)
+        } else {
+            (DMA2_MASK_PORT, DMA2_MODE_PORT, DMA2_CLEAR_FLIPFLOP_PORT)
+        }
+    }
+
+    /// Program this channel to transfer `count` bytes starting at
+    /// `phys_addr` in the given `transfer_type` direction, then unmask it
+    /// so the UART's next DREQ pulse starts the transfer.
+    fn program(&self, phys_addr: u32, count: u16, transfer_type: u8) {
+        let (mask_port, mode_port, flipflop_port) = self.controller_ports();
+        let channel_select = self.channel % 4;
+
+        unsafe {
+            // Mask the channel while it's being reprogrammed.
+            outb(mask_port, DMA_MASK_SET | channel_select);
+            outb(flipflop_port, 0); // reset the address/count byte-pointer flip-flop
+
+            outb(self.address_port, (phys_addr & 0xFF) as u8);
+            outb(self.address_port, ((phys_addr >> 8) & 0xFF) as u8);
+            outb(self.page_port, ((phys_addr >> 16) & 0xFF) as u8);
+
+            // The count register holds (byte count - 1).
+            let words = count.saturating_sub(1);
+            outb(self.count_port, (words & 0xFF) as u8);
+            outb(self.count_port, ((words >> 8) & 0xFF) as u8);
+
+            outb(mode_port, DMA_MODE_SINGLE | transfer_type | channel_select);
+
+            // Unmask: the channel now responds to DREQ.
+            outb(mask_port, channel_select);
+        }
+    }
+}
+
+/// Legacy ISA 8237A DMA controller ports (DMA1 serves channels 0-3, DMA2
+/// serves channels 4-7).
+const DMA1_MASK_PORT: u16 = 0x0A;
+const DMA1_MODE_PORT: u16 = 0x0B;
+const DMA1_CLEAR_FLIPFLOP_PORT: u16 = 0x0C;
+const DMA2_MASK_PORT: u16 = 0xD4;
+const DMA2_MODE_PORT: u16 = 0xD6;
+const DMA2_CLEAR_FLIPFLOP_PORT: u16 = 0xD8;
+
+/// 8237A mode register: bits 7:6 select single-transfer mode (the
+/// controller releases the bus after every byte, matching a UART's
+/// byte-at-a-time FIFO drain/fill rate rather than block/demand modes).
+const DMA_MODE_SINGLE: u8 = 0b01 << 6;
+/// Bits 3:2 transfer type: `01` writes to memory (peripheral -> memory,
+/// used by `receive_dma`), `10` reads from memory (memory -> peripheral,
+/// used by `send_dma`).
+const DMA_MODE_TRANSFER_WRITE_TO_MEM: u8 = 0b01 << 2;
+const DMA_MODE_TRANSFER_READ_FROM_MEM: u8 = 0b10 << 2;
+/// Mask register bit 2: set to mask (disable) the selected channel.
+const DMA_MASK_SET: u8 = 0b0000_0100;
+
+unsafe fn outb(port: u16, value: u8) {
+    core::arch::asm!(
+        "out dx, al",
+        in("dx") port,
+        in("al") value,
+        options(nomem, nostack, preserves_flags)
+    );
+}
+
+/// Which direction an in-flight DMA transfer is moving, so
+/// [`Uart16550::handle_dma_complete`] knows which [`UartStats`] counter to
+/// credit once it fires.
+#[derive(Debug, Clone, Copy)]
+enum DmaDirection {
+    Tx,
+    Rx,
+}
+
+/// The DMA transfer currently armed on [`Uart16550::config`]'s
+/// [`DmaChannel`], if any.
+#[derive(Debug, Clone, Copy)]
+struct PendingDma {
+    direction: DmaDirection,
+    len: usize,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Parity {
     None,
@@ -187,6 +318,21 @@ pub struct Uart16550 {
     tx_buffer: RingBuffer<u8, TX_BUFFER_SIZE>,
     stats: UartStats,
     interrupt_enabled: bool,
+    pending_dma: Option<PendingDma>,
+    /// Set when we've received an XOFF from the remote end under
+    /// `FlowControl::XonXoff`; cleared on XON. Gates `handle_tx_interrupt`.
+    tx_paused: bool,
+    /// Set once we've sent XOFF to the remote end because `rx_buffer`
+    /// crossed [`RX_HIGH_WATER`]; cleared (sending XON) once it drains
+    /// back below [`RX_LOW_WATER`]. Tracked so we don't resend XOFF every
+    /// interrupt while already paused.
+    rx_xoff_sent: bool,
+    /// Wakers registered by [`Self::poll_read`]/[`Self::poll_write`] while
+    /// waiting on `rx_buffer`/`tx_buffer`; woken from
+    /// [`Self::handle_rx_interrupt`]/[`Self::handle_tx_interrupt`] once
+    /// data or space becomes available.
+    rx_waker: Spinlock<Option<Waker>>,
+    tx_waker: Spinlock<Option<Waker>>,
 }
 
 impl Uart16550 {
@@ -199,6 +345,11 @@ impl Uart16550 {
             tx_buffer: RingBuffer::new(),
             stats: UartStats::default(),
             interrupt_enabled: false,
+            pending_dma: None,
+            tx_paused: false,
+            rx_xoff_sent: false,
+            rx_waker: Spinlock::new(None),
+            tx_waker: Spinlock::new(None),
         }
     }
 
@@ -220,6 +371,17 @@ impl Uart16550 {
             return Err(UartError::InvalidConfig);
         }
 
+        // Round to the nearest divisor rather than truncating, so e.g.
+        // 250000 baud against the default 1.8432 MHz clock lands on
+        // divisor 1 instead of 0 (`115200 / 250000` truncates to 0, which
+        // is not a valid divisor).
+        let divisor = (config.clock_hz as u64 + 8 * config.baud_rate as u64)
+            / (16 * config.baud_rate as u64);
+        if divisor == 0 || divisor > u16::MAX as u64 {
+            return Err(UartError::InvalidBaudRate);
+        }
+        let divisor = divisor as u16;
+
         self.config = config;
 
         // Disable interrupts during configuration
@@ -229,7 +391,6 @@ impl Uart16550 {
         self.set_dlab(true);
 
         // Set baud rate divisor
-        let divisor = 115200 / config.baud_rate;
         self.write_reg(REG_DLL, (divisor & 0xFF) as u8);
         self.write_reg(REG_DLM, ((divisor >> 8) & 0xFF) as u8);
 
@@ -258,6 +419,9 @@ impl Uart16550 {
             FifoTrigger::Fifo8 => FCR_TRIGGER_8,
             FifoTrigger::Fifo14 => FCR_TRIGGER_14,
         };
+        if config.dma.is_some() {
+            fcr |= FCR_DMA_MODE;
+        }
         self.write_reg(REG_FCR, fcr);
 
         // Configure modem control for flow control
@@ -333,27 +497,38 @@ impl Uart16550 {
     /// Try to receive a byte (non-blocking)
     pub fn try_receive(&mut self) -> Option<u8> {
         // Check hardware receive buffer first
-        if self.is_data_ready() {
+        while self.is_data_ready() {
             let byte = self.read_reg(REG_RBR);
+            if self.intercept_flow_control(byte) {
+                continue;
+            }
             self.stats.bytes_received.fetch_add(1, Ordering::Relaxed);
             return Some(byte);
         }
 
         // Check software buffer
-        self.rx_buffer.pop()
+        let byte = self.rx_buffer.pop();
+        if byte.is_some() {
+            self.update_flow_control_watermark();
+        }
+        byte
     }
 
     /// Receive a single byte (blocking with timeout)
     pub fn receive(&mut self) -> Result<u8, UartError> {
         // Try hardware buffer first
-        if self.is_data_ready() {
+        while self.is_data_ready() {
             let byte = self.read_reg(REG_RBR);
+            if self.intercept_flow_control(byte) {
+                continue;
+            }
             self.stats.bytes_received.fetch_add(1, Ordering::Relaxed);
             return Ok(byte);
         }
 
         // Try software buffer
         if let Some(byte) = self.rx_buffer.pop() {
+            self.update_flow_control_watermark();
             return Ok(byte);
         }
 
@@ -366,7 +541,11 @@ impl Uart16550 {
 
         // Drain hardware buffer first
         while self.is_data_ready() && count < buffer.len() {
-            buffer[count] = self.read_reg(REG_RBR);
+            let byte = self.read_reg(REG_RBR);
+            if self.intercept_flow_control(byte) {
+                continue;
+            }
+            buffer[count] = byte;
             self.stats.bytes_received.fetch_add(1, Ordering::Relaxed);
             count += 1;
         }
@@ -381,6 +560,10 @@ impl Uart16550 {
             }
         }
 
+        if count > 0 {
+            self.update_flow_control_watermark();
+        }
+
         count
     }
 
@@ -397,19 +580,40 @@ impl Uart16550 {
 
     /// Process receive interrupt - should be called from IRQ handler
     pub fn handle_rx_interrupt(&mut self) {
+        let mut received = false;
         while self.is_data_ready() {
             let byte = self.read_reg(REG_RBR);
+
+            if self.intercept_flow_control(byte) {
+                continue;
+            }
+
             self.stats.bytes_received.fetch_add(1, Ordering::Relaxed);
+            received = true;
 
             // If buffer is full, count overrun
             if self.rx_buffer.push(byte).is_err() {
                 self.stats.overruns.fetch_add(1, Ordering::Relaxed);
             }
         }
+
+        self.update_flow_control_watermark();
+
+        if received {
+            if let Some(waker) = self.rx_waker.lock().take() {
+                waker.wake();
+            }
+        }
     }
 
     /// Process transmit interrupt - should be called from IRQ handler
     pub fn handle_tx_interrupt(&mut self) {
+        if self.tx_paused {
+            return;
+        }
+
+        let had_space = self.tx_buffer.is_full();
+
         // Fill TX FIFO from software buffer
         while self.is_transmitter_empty() {
             if let Some(byte) = self.tx_buffer.pop() {
@@ -424,6 +628,12 @@ impl Uart16550 {
         if self.tx_buffer.is_empty() && self.interrupt_enabled {
             self.write_reg(REG_IER, IER_RECV_DATA);
         }
+
+        if had_space && !self.tx_buffer.is_full() {
+            if let Some(waker) = self.tx_waker.lock().take() {
+                waker.wake();
+            }
+        }
     }
 
     /// Process error interrupt - should be called from IRQ handler
@@ -450,6 +660,166 @@ impl Uart16550 {
         // XON/XOFF flow control handling would go here
     }
 
+    /// Internal loopback self-test: exercises TX/RX and the modem-control
+    /// loopback mapping (`MCR_LOOPBACK` ties DTR/RTS/OUT1/OUT2 to
+    /// DSR/CTS/RI/DCD internally) without anything needing to be wired to
+    /// the port. Gives a power-on diagnostic to distinguish a dead chip
+    /// from a wiring problem before [`SERIAL1`] is trusted. Restores the
+    /// previous MCR before returning either way.
+    pub fn self_test(&mut self) -> Result<(), UartError> {
+        let saved_mcr = self.read_reg(REG_MCR);
+
+        self.write_reg(
+            REG_MCR,
+            MCR_LOOPBACK | MCR_DTR | MCR_RTS | MCR_AUX1 | MCR_AUX2,
+        );
+
+        // DTR/RTS/OUT1/OUT2 should now read back as DSR/CTS/RI/DCD.
+        let msr = self.read_reg(REG_MSR);
+        if msr & (MSR_DSR | MSR_CTS | MSR_RI | MSR_DCD)
+            != (MSR_DSR | MSR_CTS | MSR_RI | MSR_DCD)
+        {
+            self.write_reg(REG_MCR, saved_mcr);
+            return Err(UartError::InvalidConfig);
+        }
+
+        // Byte-at-a-time round trip through TX -> (internal loopback) -> RX.
+        const PATTERN: &[u8] = &[0x00, 0xFF, 0x55, 0xAA, 0x01, 0xFE];
+        for &byte in PATTERN {
+            self.write_reg(REG_THR, byte);
+            while !self.is_data_ready() {
+                core::hint::spin_loop();
+            }
+            if self.read_reg(REG_RBR) != byte {
+                self.write_reg(REG_MCR, saved_mcr);
+                return Err(UartError::FramingError);
+            }
+        }
+
+        // Fill the FIFO up to the configured trigger level and drain it,
+        // to exercise the FIFO path rather than only the single-byte
+        // registers above.
+        let trigger_count: u8 = match self.config.fifo_trigger {
+            FifoTrigger::Fifo1 => 1,
+            FifoTrigger::Fifo4 => 4,
+            FifoTrigger::Fifo8 => 8,
+            FifoTrigger::Fifo14 => 14,
+        };
+        for i in 0..trigger_count {
+            self.write_reg(REG_THR, i);
+        }
+        for i in 0..trigger_count {
+            while !self.is_data_ready() {
+                core::hint::spin_loop();
+            }
+            if self.read_reg(REG_RBR) != i {
+                self.write_reg(REG_MCR, saved_mcr);
+                return Err(UartError::Overrun);
+            }
+        }
+
+        self.write_reg(REG_MCR, saved_mcr);
+        Ok(())
+    }
+
+    /// Hand `buf` directly to the configured DMA channel for transmit:
+    /// the controller reads it out to the UART's THR one byte at a time
+    /// on every DREQ, freeing the CPU until [`Self::handle_dma_complete`]
+    /// fires. Requires `config.dma` to have been set via [`Self::configure`];
+    /// a no-op otherwise.
+    pub fn send_dma(&mut self, buf: &[u8]) {
+        let Some(dma) = self.config.dma else { return };
+        dma.program(buf.as_ptr() as u32, buf.len() as u16, DMA_MODE_TRANSFER_READ_FROM_MEM);
+        self.pending_dma = Some(PendingDma { direction: DmaDirection::Tx, len: buf.len() });
+    }
+
+    /// Hand `buf` directly to the configured DMA channel for receive: the
+    /// controller writes incoming RBR bytes straight into `buf` on every
+    /// DREQ. Requires `config.dma` to have been set via [`Self::configure`];
+    /// a no-op otherwise.
+    pub fn receive_dma(&mut self, buf: &mut [u8]) {
+        let Some(dma) = self.config.dma else { return };
+        dma.program(buf.as_mut_ptr() as u32, buf.len() as u16, DMA_MODE_TRANSFER_WRITE_TO_MEM);
+        self.pending_dma = Some(PendingDma { direction: DmaDirection::Rx, len: buf.len() });
+    }
+
+    /// Process a DMA transfer-complete interrupt - should be called from
+    /// the IRQ handler wired to the DMA controller's completion line.
+    /// Credits [`UartStats`] with the transferred count; arming the next
+    /// descriptor is the caller's job (call [`Self::send_dma`] or
+    /// [`Self::receive_dma`] again for the next buffer).
+    pub fn handle_dma_complete(&mut self) {
+        if let Some(pending) = self.pending_dma.take() {
+            match pending.direction {
+                DmaDirection::Tx => {
+                    self.stats.bytes_sent.fetch_add(pending.len as u64, Ordering::Relaxed);
+                }
+                DmaDirection::Rx => {
+                    self.stats.bytes_received.fetch_add(pending.len as u64, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    /// Poll-based read: drains whatever is already sitting in the hardware
+    /// FIFO or `rx_buffer` into `buf` and completes immediately if that's
+    /// non-empty (embassy's `BufferedUarte` approach -- the buffer is kept
+    /// filled by [`Self::handle_rx_interrupt`] in the background, so no
+    /// bytes are lost between polls). Otherwise registers `cx`'s waker to
+    /// be woken by the next RX interrupt and returns [`Poll::Pending`].
+    pub fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<usize> {
+        let count = self.receive_into(buf);
+        if count > 0 {
+            return Poll::Ready(count);
+        }
+
+        *self.rx_waker.lock() = Some(cx.waker().clone());
+
+        // Re-check after registering in case a byte arrived between the
+        // `receive_into` above and the waker being stored.
+        let count = self.receive_into(buf);
+        if count > 0 {
+            self.rx_waker.lock().take();
+            return Poll::Ready(count);
+        }
+
+        Poll::Pending
+    }
+
+    /// Poll-based write: pushes as much of `buf` as fits into `tx_buffer`
+    /// and arms the TX interrupt to drain it, completing immediately with
+    /// the number of bytes accepted. Registers `cx`'s waker and returns
+    /// [`Poll::Pending`] only when `tx_buffer` is already full and nothing
+    /// could be accepted.
+    pub fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<usize> {
+        let mut written = 0;
+        while written < buf.len() && self.tx_buffer.push(buf[written]).is_ok() {
+            written += 1;
+        }
+
+        if written > 0 {
+            if self.interrupt_enabled {
+                self.write_reg(REG_IER, IER_RECV_DATA | IER_THR_EMPTY);
+            }
+            return Poll::Ready(written);
+        }
+
+        *self.tx_waker.lock() = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    /// `poll_read` wrapped as an awaitable future, so the UART can be
+    /// `select!`-ed against other futures instead of only polled
+    /// synchronously via [`Self::try_receive`].
+    pub fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> ReadFuture<'a> {
+        ReadFuture { uart: self, buf }
+    }
+
+    /// `poll_write` wrapped as an awaitable future.
+    pub fn write<'a>(&'a mut self, buf: &'a [u8]) -> WriteFuture<'a> {
+        WriteFuture { uart: self, buf }
+    }
+
     /// Get statistics
     pub fn stats(&self) -> &UartStats {
         &self.stats
@@ -470,6 +840,22 @@ impl Uart16550 {
         self.config
     }
 
+    /// The baud rate the chip actually runs at for the current config's
+    /// rounded divisor, and its relative error against the requested rate
+    /// in parts-per-thousand (positive if the achieved rate runs fast).
+    /// Callers that need a tight tolerance (e.g. matching a fixed-baud
+    /// peer) should check this rather than assuming the divisor rounding
+    /// landed exactly on `baud_rate`.
+    pub fn achieved_baud(&self) -> (u32, i32) {
+        let divisor = ((self.config.clock_hz as u64 + 8 * self.config.baud_rate as u64)
+            / (16 * self.config.baud_rate as u64))
+            .max(1);
+        let achieved = (self.config.clock_hz as u64 / (16 * divisor)) as u32;
+        let error_ppt = ((achieved as i64 - self.config.baud_rate as i64) * 1000
+            / self.config.baud_rate as i64) as i32;
+        (achieved, error_ppt)
+    }
+
     /// Set DLAB (Divisor Latch Access Bit)
     fn set_dlab(&self, enable: bool) {
         let mut lcr = self.read_reg(REG_LCR);
@@ -488,6 +874,54 @@ impl Uart16550 {
         }
     }
 
+    /// Under `FlowControl::XonXoff`, swallow an incoming XON/XOFF byte and
+    /// update `tx_paused` instead of letting it reach `rx_buffer`. Returns
+    /// whether `byte` was consumed as a flow-control byte.
+    fn intercept_flow_control(&mut self, byte: u8) -> bool {
+        if self.config.flow_control != FlowControl::XonXoff {
+            return false;
+        }
+        match byte {
+            XON_BYTE => {
+                self.tx_paused = false;
+                true
+            }
+            XOFF_BYTE => {
+                self.tx_paused = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Under `FlowControl::XonXoff`, send XOFF once `rx_buffer` crosses
+    /// [`RX_HIGH_WATER`] so the sender throttles instead of overrunning us,
+    /// and XON once it has drained back below [`RX_LOW_WATER`].
+    fn update_flow_control_watermark(&mut self) {
+        if self.config.flow_control != FlowControl::XonXoff {
+            return;
+        }
+
+        let len = self.rx_buffer.len();
+        if !self.rx_xoff_sent && len >= RX_HIGH_WATER {
+            self.send_control_byte(XOFF_BYTE);
+            self.rx_xoff_sent = true;
+        } else if self.rx_xoff_sent && len <= RX_LOW_WATER {
+            self.send_control_byte(XON_BYTE);
+            self.rx_xoff_sent = false;
+        }
+    }
+
+    /// Write a flow-control byte straight to THR, ahead of anything queued
+    /// in `tx_buffer` -- it has to reach the sender promptly regardless of
+    /// `tx_paused` or how full the software buffer is.
+    fn send_control_byte(&mut self, byte: u8) {
+        while !self.is_transmitter_empty() {
+            core::hint::spin_loop();
+        }
+        self.write_reg(REG_THR, byte);
+    }
+
     /// Read from a register
     fn read_reg(&self, offset: u16) -> u8 {
         unsafe {
@@ -514,65 +948,123 @@ impl Uart16550 {
     }
 }
 
-/// Simple lock-free ring buffer for interrupt-driven I/O
-#[derive(Debug)]
+/// True single-producer/single-consumer lock-free ring buffer for
+/// interrupt-driven I/O: `push` and `pop` take `&self` and only touch
+/// `head`/`tail` via atomics with acquire/release ordering, so an IRQ
+/// handler pushing and a reader popping from a different context never
+/// need an external lock between them. One slot is kept permanently
+/// empty as a sentinel -- `head == tail` means empty and
+/// `(tail + 1) % N == head` means full -- so no separate counter (which
+/// the two sides could otherwise race on) is needed.
 pub struct RingBuffer<T, const N: usize> {
-    buffer: [T; N],
-    head: usize,
-    tail: usize,
-    count: usize,
+    buffer: UnsafeCell<[T; N]>,
+    head: AtomicUsize,
+    tail: AtomicUsize,
 }
 
 impl<T: Copy + Default, const N: usize> RingBuffer<T, N> {
     pub const fn new() -> Self {
         Self {
-            buffer: [T::default(); N],
-            head: 0,
-            tail: 0,
-            count: 0,
+            buffer: UnsafeCell::new([T::default(); N]),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
         }
     }
 
+    /// Resets the buffer to empty. Takes `&mut self`: unlike `push`/`pop`,
+    /// this isn't meant to run concurrently with the other side -- it's
+    /// called from `configure()` while nothing is yet wired to the port.
     pub fn clear(&mut self) {
-        self.head = 0;
-        self.tail = 0;
-        self.count = 0;
+        *self.head.get_mut() = 0;
+        *self.tail.get_mut() = 0;
     }
 
-    pub fn push(&mut self, item: T) -> Result<(), ()> {
-        if self.count >= N {
+    /// The producer side: fails with `Err(())` if the buffer is full.
+    pub fn push(&self, item: T) -> Result<(), ()> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let next_tail = (tail + 1) % N;
+        if next_tail == self.head.load(Ordering::Acquire) {
             return Err(());
         }
-        self.buffer[self.tail] = item;
-        self.tail = (self.tail + 1) % N;
-        self.count += 1;
+        // Safe: only the single producer ever writes to `buffer[tail]`,
+        // and the consumer can't observe this slot until `tail` is
+        // published below.
+        unsafe {
+            (*self.buffer.get())[tail] = item;
+        }
+        self.tail.store(next_tail, Ordering::Release);
         Ok(())
     }
 
-    pub fn pop(&mut self) -> Option<T> {
-        if self.count == 0 {
+    /// The consumer side: `None` if the buffer is empty.
+    pub fn pop(&self) -> Option<T> {
+        let head = self.head.load(Ordering::Relaxed);
+        if head == self.tail.load(Ordering::Acquire) {
             return None;
         }
-        let item = self.buffer[self.head];
-        self.head = (self.head + 1) % N;
-        self.count -= 1;
+        // Safe: only the single consumer ever reads `buffer[head]`, and
+        // the producer won't reuse this slot until `head` is published
+        // below.
+        let item = unsafe { (*self.buffer.get())[head] };
+        self.head.store((head + 1) % N, Ordering::Release);
         Some(item)
     }
 
     pub fn len(&self) -> usize {
-        self.count
+        let tail = self.tail.load(Ordering::Acquire);
+        let head = self.head.load(Ordering::Acquire);
+        (tail + N - head) % N
     }
 
     pub fn is_empty(&self) -> bool {
-        self.count == 0
+        self.head.load(Ordering::Acquire) == self.tail.load(Ordering::Acquire)
     }
 
     pub fn is_full(&self) -> bool {
-        self.count >= N
+        let tail = self.tail.load(Ordering::Acquire);
+        (tail + 1) % N == self.head.load(Ordering::Acquire)
     }
 
+    /// One slot is reserved as the empty/full sentinel, so usable
+    /// capacity is `N - 1`.
     pub fn capacity(&self) -> usize {
-        N
+        N - 1
+    }
+}
+
+// Safety: `push`/`pop` only ever touch the slot each side currently owns,
+// handed off via the `Release`-published `head`/`tail` atomics, so two
+// threads sharing a `&RingBuffer` (one producer, one consumer) never
+// alias a write.
+unsafe impl<T: Send, const N: usize> Sync for RingBuffer<T, N> {}
+
+/// Future returned by [`Uart16550::read`].
+pub struct ReadFuture<'a> {
+    uart: &'a mut Uart16550,
+    buf: &'a mut [u8],
+}
+
+impl core::future::Future for ReadFuture<'_> {
+    type Output = usize;
+
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<usize> {
+        let this = self.get_mut();
+        this.uart.poll_read(cx, this.buf)
+    }
+}
+
+/// Future returned by [`Uart16550::write`].
+pub struct WriteFuture<'a> {
+    uart: &'a mut Uart16550,
+    buf: &'a [u8],
+}
+
+impl core::future::Future for WriteFuture<'_> {
+    type Output = usize;
+
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<usize> {
+        let this = self.get_mut();
+        this.uart.poll_write(cx, this.buf)
     }
 }
 
@@ -1,43 +1,54 @@
 //! VT100/ANSI Terminal Emulation
 //!
 //! This module implements ANSI escape sequence parsing and terminal state
-//! management for VT100-compatible terminal emulation.
+//! management for VT100-compatible terminal emulation. Sequences are
+//! recognized by the VTE-style state machine in
+//! [`crate::lib_core::vt::parser`]; [`translate_csi`]/[`translate_esc`]/
+//! [`translate_osc`] turn its [`Action`](crate::lib_core::vt::parser::Action)s
+//! into the [`AnsiCommand`]s a device like [`super::tty::TtyDevice`] applies.
 
-/// ANSI escape sequence parser state
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ParserState {
-    Idle,
-    Escape,
-    Csi,
-    EscapeSequence,
-    String,
-    Osc,
-    Dcs,
-}
+use alloc::string::String;
+use alloc::vec::Vec;
 
 /// Terminal attributes
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TerminalAttributes {
     // Cursor
     pub cursor_visible: bool,
     pub cursor_blinking: bool,
+    pub cursor_row: usize,
+    pub cursor_col: usize,
     pub saved_cursor_row: usize,
     pub saved_cursor_col: usize,
-    
+
+    // SGR graphics state
+    pub foreground: Color,
+    pub background: Color,
+    pub bold: bool,
+    pub underline: bool,
+    pub blink: bool,
+    pub reverse: bool,
+    pub hidden: bool,
+    pub strikethrough: bool,
+
     // Tab stops
     pub tab_stops: [bool; 256],
-    
+
     // Character sets
     pub g0_charset: CharsetType,
     pub g1_charset: CharsetType,
     pub charset_in_use: usize,
-    
+
     // State
     pub origin_mode: bool,
     pub auto_wrap: bool,
     pub insert_mode: bool,
     pub application_keypad: bool,
     pub ansi_mode: bool,
+
+    // Mouse reporting
+    pub mouse_tracking_mode: MouseTrackingMode,
+    pub mouse_extended_mode: MouseExtendedMode,
 }
 
 impl Default for TerminalAttributes {
@@ -46,12 +57,22 @@ impl Default for TerminalAttributes {
         for i in (8..256).step_by(8) {
             tab_stops[i] = true;
         }
-        
+
         Self {
             cursor_visible: true,
             cursor_blinking: true,
+            cursor_row: 0,
+            cursor_col: 0,
             saved_cursor_row: 0,
             saved_cursor_col: 0,
+            foreground: Color::Default,
+            background: Color::Default,
+            bold: false,
+            underline: false,
+            blink: false,
+            reverse: false,
+            hidden: false,
+            strikethrough: false,
             tab_stops,
             g0_charset: CharsetType::Ascii,
             g1_charset: CharsetType::Ascii,
@@ -61,6 +82,8 @@ impl Default for TerminalAttributes {
             insert_mode: false,
             application_keypad: false,
             ansi_mode: true,
+            mouse_tracking_mode: MouseTrackingMode::None,
+            mouse_extended_mode: MouseExtendedMode::Normal,
         }
     }
 }
@@ -119,7 +142,10 @@ pub enum AnsiCommand {
     SetTabStop,
     ClearTabStop,
     ClearAllTabStops,
-    
+
+    // Character sets (ESC ( / ESC ))
+    DesignateCharset { slot: usize, charset: CharsetType },
+
     // Character attributes
     SetGraphicsMode(Vec<GraphicsAttribute>),
     ResetGraphicsMode,
@@ -147,7 +173,46 @@ pub enum AnsiCommand {
     // Mouse
     EnableMouseTracking(MouseTrackingMode),
     DisableMouseTracking,
-    
+    SetMouseExtendedMode(MouseExtendedMode),
+
+    // Synchronized output (DCS passthrough, e.g. `ESC P = 1 s`/`ESC P = 2 s`)
+    BeginSynchronizedUpdate,
+    EndSynchronizedUpdate,
+
+    // Dynamic colors (OSC 4 / 10 / 11 and their query/reset counterparts)
+    SetPaletteColor(u8, (u8, u8, u8)),
+    ResetPaletteColor(u8),
+    SetDefaultForeground((u8, u8, u8)),
+    SetDefaultBackground((u8, u8, u8)),
+    QueryPaletteColor(u8),
+    QueryDefaultForeground,
+    QueryDefaultBackground,
+    SetCursorColor((u8, u8, u8)),
+    QueryCursorColor,
+    SetHighlightBackground((u8, u8, u8)),
+    QueryHighlightBackground,
+    SetHighlightForeground((u8, u8, u8)),
+    QueryHighlightForeground,
+
+    // Clipboard (OSC 52)
+    SetClipboard { selection: ClipboardSelection, data: Vec<u8> },
+    QueryClipboard(ClipboardSelection),
+
+    // Hyperlinks (OSC 8): an empty `uri` closes the current link.
+    SetHyperlink { id: Option<String>, uri: Option<String> },
+
+    // Alternate screen buffer (DECSET 1047/1048/1049)
+    EnableAlternateScreen { save_cursor: bool, clear: bool },
+    DisableAlternateScreen { restore_cursor: bool },
+
+    // Status/Device Attribute reports (DSR, DA): bytes the host must write
+    // back to the pty verbatim.
+    Report(Vec<u8>),
+    // `CSI 6 n`: needs the live cursor position, which the stateless
+    // translator doesn't have; the host resolves this into a `Report` via
+    // [`build_cursor_position_report`].
+    QueryCursorPosition,
+
     // Unknown
     Unknown(Vec<u8>),
 }
@@ -207,11 +272,14 @@ pub enum Color {
     BrightCyan,
     BrightWhite,
     Default,
+    Indexed(u8),
+    Rgb(u8, u8, u8),
 }
 
 /// Mode types for set/reset mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ModeType {
+    AnsiMode,              // DECANM
     CursorKeyMode,         // DECCKM
     ColumnMode,            // DECCOLM
     ScrollMode,            // DECSCLM
@@ -234,565 +302,794 @@ pub enum MouseTrackingMode {
     AnyEventTracking,
 }
 
-/// ANSI escape sequence parser
-pub struct AnsiParser {
-    state: ParserState,
-    params: Vec<i32>,
-    intermediate: Vec<u8>,
-    current_byte: u8,
-    osc_data: String,
+/// Which extended-coordinate mouse report format is active, set by
+/// `?1006` (SGR) / `?1015` (urxvt) DECSET/DECRST. `Normal` is the legacy
+/// byte-offset encoding, which caps coordinates at 223.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MouseExtendedMode {
+    #[default]
+    Normal,
+    Sgr,
+    Urxvt,
 }
 
-impl AnsiParser {
-    /// Create a new ANSI parser
-    pub const fn new() -> Self {
-        Self {
-            state: ParserState::Idle,
-            params: Vec::new(),
-            intermediate: Vec::new(),
-            current_byte: 0,
-            osc_data: String::new(),
-        }
-    }
-
-    /// Reset parser to initial state
-    pub fn reset(&mut self) {
-        self.state = ParserState::Idle;
-        self.params.clear();
-        self.intermediate.clear();
-        self.osc_data.clear();
-    }
+/// Button/wheel identity for an encoded mouse event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    WheelUp,
+    WheelDown,
+}
 
-    /// Process a byte and return any command
-    pub fn process_byte(&mut self, byte: u8) -> Option<AnsiCommand> {
-        self.current_byte = byte;
+/// Modifier keys held during a mouse event, ORed into the report's button
+/// byte as bits 4 (shift), 8 (meta), 16 (ctrl).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MouseModifiers {
+    pub shift: bool,
+    pub meta: bool,
+    pub ctrl: bool,
+}
 
-        match self.state {
-            ParserState::Idle => self.process_idle(byte),
-            ParserState::Escape => self.process_escape(byte),
-            ParserState::Csi => self.process_csi(byte),
-            ParserState::EscapeSequence => self.process_escape_sequence(byte),
-            ParserState::String => self.process_string(byte),
-            ParserState::Osc => self.process_osc(byte),
-            ParserState::Dcs => self.process_dcs(byte),
-        }
+impl MouseModifiers {
+    fn bits(self) -> u8 {
+        (if self.shift { 4 } else { 0 }) | (if self.meta { 8 } else { 0 }) | (if self.ctrl { 16 } else { 0 })
     }
+}
 
-    fn process_idle(&mut self, byte: u8) -> Option<AnsiCommand> {
-        match byte {
-            0x1B => {
-                self.state = ParserState::Escape;
-                None
+/// Clipboard selection targeted by an OSC 52 sequence (`c` = clipboard,
+/// `p` = primary, `s` = selection).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardSelection {
+    Clipboard,
+    Primary,
+    Selection,
+}
+
+/// Translate a `CsiDispatch` action (as emitted by
+/// [`crate::lib_core::vt::parser::Parser`]) into the [`AnsiCommand`] it
+/// requests. `params` and `intermediates` are the action's fields
+/// verbatim; `private_prefix` is `Some('?')` for a DEC private mode
+/// sequence (`CSI ? ... h`/`l`), as opposed to a plain ANSI one.
+pub fn translate_csi(params: &[i64], intermediates: &[u8], private_prefix: Option<char>, final_byte: char) -> AnsiCommand {
+    let params: Vec<usize> = params.iter().filter(|&&v| v > 0).map(|&v| v as usize).collect();
+    let param = |idx: usize, default: usize| -> usize { params.get(idx).copied().unwrap_or(default) };
+
+    match final_byte {
+        'A' => AnsiCommand::CursorUp(param(0, 1)),
+        'B' | 'e' => AnsiCommand::CursorDown(param(0, 1)),
+        'C' | 'a' => AnsiCommand::CursorForward(param(0, 1)),
+        'D' => AnsiCommand::CursorBack(param(0, 1)),
+        'E' => AnsiCommand::CursorDown(param(0, 1)),  // Cursor to beginning of line N down
+        'F' => AnsiCommand::CursorUp(param(0, 1)),    // Cursor to beginning of line N up
+        'G' | '`' => {
+            if params.is_empty() {
+                AnsiCommand::CursorHome
+            } else {
+                AnsiCommand::CursorPosition(1, params[0])
             }
-            0x9B => {
-                // CSI in 8-bit mode
-                self.state = ParserState::Csi;
-                self.params.clear();
-                self.intermediate.clear();
-                None
+        }
+        'H' | 'f' => {
+            if params.len() >= 2 {
+                AnsiCommand::CursorPosition(params[0], params[1])
+            } else {
+                AnsiCommand::CursorHome
             }
-            0x9D => {
-                // OSC in 8-bit mode
-                self.state = ParserState::Osc;
-                self.params.clear();
-                self.osc_data.clear();
-                None
+        }
+        'J' => AnsiCommand::EraseInDisplay(match param(0, 0) {
+            0 => EraseType::FromCursorToEnd,
+            1 => EraseType::FromBeginningToCursor,
+            2 | 3 => EraseType::EntireScreen,
+            _ => EraseType::FromCursorToEnd,
+        }),
+        'K' => AnsiCommand::EraseInLine(match param(0, 0) {
+            0 => EraseType::FromCursorToEndOfLine,
+            1 => EraseType::FromBeginningToCursorOfLine,
+            2 => EraseType::EntireLine,
+            _ => EraseType::FromCursorToEndOfLine,
+        }),
+        'L' => AnsiCommand::InsertLines(param(0, 1)),
+        'M' => AnsiCommand::DeleteLines(param(0, 1)),
+        'P' => AnsiCommand::DeleteChars(param(0, 1)),
+        'S' => AnsiCommand::ScrollUp(param(0, 1)),
+        'T' => AnsiCommand::ScrollDown(param(0, 1)),
+        'X' => AnsiCommand::EraseChars(param(0, 1)),
+        'Z' => AnsiCommand::CursorBack(param(0, 1)),  // Backtab
+        '[' if intermediates.is_empty() => AnsiCommand::SetCursorMode(true),
+        ']' if intermediates.is_empty() => AnsiCommand::SetCursorMode(false),
+        '^' => AnsiCommand::EraseInLine(EraseType::EntireLine),  // Privacy message
+        'c' if private_prefix == Some('>') => {
+            // Secondary DA: VT100-class, firmware version 0, no ROM cartridge
+            AnsiCommand::Report(b"\x1b[>0;100;0c".to_vec())
+        }
+        'c' if param(0, 0) == 0 => AnsiCommand::Report(b"\x1b[?6c".to_vec()),  // Primary DA: VT102
+        'd' => {
+            // Cursor to line
+            if params.is_empty() {
+                AnsiCommand::CursorPosition(1, 1)
+            } else {
+                AnsiCommand::CursorPosition(params[0], 1)
             }
-            0x90 => {
-                // DCS in 8-bit mode
-                self.state = ParserState::Dcs;
-                self.params.clear();
-                None
+        }
+        'g' => {
+            // Tabs
+            if param(0, 0) == 0 {
+                AnsiCommand::ClearTabStop
+            } else {
+                AnsiCommand::ClearAllTabStops
             }
-            0x98 | 0x9E | 0x9F => {
-                // String terminators in 8-bit mode
-                self.state = ParserState::Idle;
-                None
+        }
+        'h' => translate_set_mode(&params, private_prefix, true),
+        'l' => translate_set_mode(&params, private_prefix, false),
+        'm' => translate_graphics(&params),
+        'n' => match param(0, 0) {
+            5 => AnsiCommand::Report(b"\x1b[0n".to_vec()),  // DSR: terminal OK
+            6 => AnsiCommand::QueryCursorPosition,          // DSR: cursor position report
+            _ => AnsiCommand::Unknown(Vec::new()),
+        },
+        'q' => AnsiCommand::Unknown(Vec::new()),  // LEDs
+        'r' => {
+            // Scrolling region
+            if params.len() >= 2 {
+                AnsiCommand::SetScrollingRegion(param(0, 1), param(1, 24))
+            } else {
+                AnsiCommand::Unknown(Vec::new())
             }
-            _ => None,
         }
+        _ => AnsiCommand::Unknown(Vec::new()),
     }
+}
 
-    fn process_escape(&mut self, byte: u8) -> Option<AnsiCommand> {
-        match byte {
-            b'[' => {
-                self.state = ParserState::Csi;
-                self.params.clear();
-                self.intermediate.clear();
-                None
-            }
-            b']' => {
-                self.state = ParserState::Osc;
-                self.params.clear();
-                self.osc_data.clear();
-                None
-            }
-            b'P' => {
-                self.state = ParserState::Dcs;
-                self.params.clear();
-                None
-            }
-            b'N' | b'O' => {
-                // Single character sets
-                self.state = ParserState::EscapeSequence;
-                self.intermediate.clear();
-                self.intermediate.push(byte);
-                None
-            }
-            b'7' => Some(AnsiCommand::SaveCursorPosition),
-            b'8' => Some(AnsiCommand::RestoreCursorPosition),
-            b'c' => {
-                // Full reset
-                self.state = ParserState::Idle;
-                Some(AnsiCommand::ResetMode(ModeType::CursorKeyMode))
-            }
-            b'=' => {
-                // Application keypad
-                self.state = ParserState::Idle;
-                Some(AnsiCommand::SetKeypadMode(true))
-            }
-            b'>' => {
-                // Normal keypad
-                self.state = ParserState::Idle;
-                Some(AnsiCommand::SetKeypadMode(false))
-            }
-            0x1B => {
-                // Escape again - ignore
-                None
-            }
-            _ => {
-                self.state = ParserState::Idle;
-                None
-            }
-        }
+/// Shared by the `h` (set)/`l` (reset) CSI final bytes: both only define
+/// DEC private modes (`CSI ? Pm h`/`l`) here, so a plain-ANSI sequence
+/// (no `?` prefix) falls through to `Unknown`.
+fn translate_set_mode(params: &[usize], private_prefix: Option<char>, set: bool) -> AnsiCommand {
+    if private_prefix != Some('?') {
+        return AnsiCommand::Unknown(Vec::new());
     }
+    let mode = params.first().copied().unwrap_or(0);
+    let set_reset = |m: ModeType| if set { AnsiCommand::SetMode(m) } else { AnsiCommand::ResetMode(m) };
+
+    match mode {
+        1 => set_reset(ModeType::CursorKeyMode),
+        2 => set_reset(ModeType::AnsiMode),          // DECANM
+        3 => set_reset(ModeType::ColumnMode),        // DECCOLM
+        4 => set_reset(ModeType::ScrollMode),        // DECSCLM
+        5 => set_reset(ModeType::ScreenMode),        // DECNM
+        6 => set_reset(ModeType::OriginMode),        // DECOM
+        7 => set_reset(ModeType::AutoWrapMode),      // DECAWM
+        25 => AnsiCommand::SetCursorMode(set),       // DECTCEM
+        1000 | 1002 | 1003 if set => AnsiCommand::EnableMouseTracking(match mode {
+            1000 => MouseTrackingMode::X10Compatible,
+            1002 => MouseTrackingMode::ButtonTracking,
+            _ => MouseTrackingMode::AnyEventTracking,
+        }),
+        1000 | 1002 | 1003 => AnsiCommand::DisableMouseTracking,
+        1006 => AnsiCommand::SetMouseExtendedMode(if set { MouseExtendedMode::Sgr } else { MouseExtendedMode::Normal }),
+        1015 => AnsiCommand::SetMouseExtendedMode(if set { MouseExtendedMode::Urxvt } else { MouseExtendedMode::Normal }),
+        1048 if set => AnsiCommand::SaveCursorPosition,
+        1048 => AnsiCommand::RestoreCursorPosition,
+        1047 if set => AnsiCommand::EnableAlternateScreen { save_cursor: false, clear: true },
+        1047 => AnsiCommand::DisableAlternateScreen { restore_cursor: false },
+        1049 if set => AnsiCommand::EnableAlternateScreen { save_cursor: true, clear: true },
+        1049 => AnsiCommand::DisableAlternateScreen { restore_cursor: true },
+        _ => AnsiCommand::Unknown(Vec::new()),
+    }
+}
 
-    fn process_csi(&mut self, byte: u8) -> Option<AnsiCommand> {
-        // Check for intermediate characters
-        if byte >= 0x20 && byte <= 0x2F {
-            self.intermediate.push(byte);
-            return None;
-        }
+fn translate_graphics(params: &[usize]) -> AnsiCommand {
+    if params.is_empty() {
+        return AnsiCommand::ResetGraphicsMode;
+    }
+
+    let mut attrs = Vec::new();
+    let mut i = 0;
+
+    while i < params.len() {
+        let param = params[i];
 
-        // Check for parameter characters
-        if byte >= 0x30 && byte <= 0x39 {
-            let mut value = 0;
-            while self.params.last().map_or(false, |&v| v == -1) {
-                self.params.pop();
+        match param {
+            0 => attrs.push(GraphicsAttribute::Reset),
+            1 => attrs.push(GraphicsAttribute::Bold),
+            2 => attrs.push(GraphicsAttribute::Dim),
+            3 => attrs.push(GraphicsAttribute::Italic),
+            4 => attrs.push(GraphicsAttribute::Underline),
+            5 | 6 => attrs.push(GraphicsAttribute::Blink),
+            7 => attrs.push(GraphicsAttribute::Reverse),
+            8 => attrs.push(GraphicsAttribute::Hidden),
+            9 => attrs.push(GraphicsAttribute::Strikethrough),
+            10..=19 => {}  // Font selection (not implemented)
+            20 => attrs.push(GraphicsAttribute::DoubleUnderline),
+            21 => attrs.push(GraphicsAttribute::Reset),  // Normal intensity
+            22 => attrs.push(GraphicsAttribute::Reset),  // Bold off
+            23 => attrs.push(GraphicsAttribute::Reset),  // Italic off
+            24 => attrs.push(GraphicsAttribute::Reset),  // Underline off
+            25 => attrs.push(GraphicsAttribute::Reset),  // Blink off
+            27 => attrs.push(GraphicsAttribute::Reset),  // Reverse off
+            28 => attrs.push(GraphicsAttribute::Reset),  // Hidden off
+            29 => attrs.push(GraphicsAttribute::Reset),  // Strikethrough off
+            30..=37 => attrs.push(GraphicsAttribute::Foreground(color_from_param(param - 30))),
+            38 => {
+                if i + 1 < params.len() {
+                    if params[i + 1] == 5 && i + 2 < params.len() {
+                        attrs.push(GraphicsAttribute::Foreground256(params[i + 2]));
+                        i += 2;
+                    } else if params[i + 1] == 2 && i + 4 < params.len() {
+                        attrs.push(GraphicsAttribute::TrueColorForeground(params[i + 2] as u8, params[i + 3] as u8, params[i + 4] as u8));
+                        i += 4;
+                    }
+                    i += 1;
+                }
             }
-            if let Some(last) = self.params.last_mut() {
-                *last = *last * 10 + (byte - b'0') as i32;
-            } else {
-                self.params.push((byte - b'0') as i32);
+            39 => attrs.push(GraphicsAttribute::Foreground(Color::Default)),
+            40..=47 => attrs.push(GraphicsAttribute::Background(color_from_param(param - 40))),
+            48 => {
+                if i + 1 < params.len() {
+                    if params[i + 1] == 5 && i + 2 < params.len() {
+                        attrs.push(GraphicsAttribute::Background256(params[i + 2]));
+                        i += 2;
+                    } else if params[i + 1] == 2 && i + 4 < params.len() {
+                        attrs.push(GraphicsAttribute::TrueColorBackground(params[i + 2] as u8, params[i + 3] as u8, params[i + 4] as u8));
+                        i += 4;
+                    }
+                    i += 1;
+                }
             }
-            return None;
+            49 => attrs.push(GraphicsAttribute::Background(Color::Default)),
+            51 => attrs.push(GraphicsAttribute::Framed),
+            52 => attrs.push(GraphicsAttribute::Encircled),
+            53 => attrs.push(GraphicsAttribute::Overline),
+            54 => attrs.push(GraphicsAttribute::Reset),  // Framed/encircled off
+            55 => attrs.push(GraphicsAttribute::Reset),  // Overline off
+            _ => {}
         }
+        i += 1;
+    }
 
-        // Parameter separator
-        if byte == b';' {
-            self.params.push(-1);
-            return None;
-        }
+    AnsiCommand::SetGraphicsMode(attrs)
+}
+
+fn color_from_param(param: usize) -> Color {
+    match param {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        7 => Color::White,
+        8 => Color::BrightBlack,
+        9 => Color::BrightRed,
+        10 => Color::BrightGreen,
+        11 => Color::BrightYellow,
+        12 => Color::BrightBlue,
+        13 => Color::BrightMagenta,
+        14 => Color::BrightCyan,
+        15 => Color::BrightWhite,
+        _ => Color::Default,
+    }
+}
 
-        // Final character
-        self.state = ParserState::Idle;
-        Some(self.parse_csi_command(byte))
+/// Translate an `EscDispatch` action into the [`AnsiCommand`] it
+/// requests, for the handful of non-CSI/OSC escape sequences this
+/// terminal cares about (`DECSC`/`DECRC`, full reset, keypad mode).
+/// Returns `None` for anything else, same as an unrecognized CSI final
+/// byte falling through to [`AnsiCommand::Unknown`] would, just without
+/// the allocation.
+pub fn translate_esc(intermediates: &[u8], final_byte: u8) -> Option<AnsiCommand> {
+    match intermediates {
+        [] => match final_byte {
+            b'7' => Some(AnsiCommand::SaveCursorPosition),
+            b'8' => Some(AnsiCommand::RestoreCursorPosition),
+            b'c' => Some(AnsiCommand::ResetMode(ModeType::CursorKeyMode)),
+            b'=' => Some(AnsiCommand::SetKeypadMode(true)),
+            b'>' => Some(AnsiCommand::SetKeypadMode(false)),
+            _ => None,
+        },
+        // `ESC ( <final>` designates G0, `ESC ) <final>` designates G1.
+        [b'('] => designate_charset(0, final_byte),
+        [b')'] => designate_charset(1, final_byte),
+        _ => None,
     }
+}
 
-    fn process_escape_sequence(&mut self, byte: u8) -> Option<AnsiCommand> {
-        self.state = ParserState::Idle;
-        match byte {
-            0x1B => None,  // Escape
-            _ => None,     // Ignore for now
+fn designate_charset(slot: usize, final_byte: u8) -> Option<AnsiCommand> {
+    let charset = match final_byte {
+        b'0' => CharsetType::SpecialGraphics,
+        b'B' => CharsetType::Ascii,
+        _ => return None,
+    };
+    Some(AnsiCommand::DesignateCharset { slot, charset })
+}
+
+/// Translate a printed character through the active `g0`/`g1` charset.
+/// Only [`CharsetType::SpecialGraphics`] (the VT100 DEC line-drawing set)
+/// changes anything; every other charset passes characters through
+/// unmodified since this tree has no British/German/DecTechnical mapping
+/// table to apply yet.
+pub fn translate_charset(c: char, charset: CharsetType) -> char {
+    if charset != CharsetType::SpecialGraphics {
+        return c;
+    }
+    match c {
+        '`' => '\u{25C6}', // diamond
+        'a' => '\u{2592}', // checkerboard
+        'b' => '\u{2409}', // HT symbol
+        'c' => '\u{240C}', // FF symbol
+        'd' => '\u{240D}', // CR symbol
+        'e' => '\u{240A}', // LF symbol
+        'f' => '\u{00B0}', // degree
+        'g' => '\u{00B1}', // plus/minus
+        'h' => '\u{2424}', // NL symbol
+        'i' => '\u{240B}', // VT symbol
+        'j' => '\u{2518}', // bottom-right corner
+        'k' => '\u{2510}', // top-right corner
+        'l' => '\u{250C}', // top-left corner
+        'm' => '\u{2514}', // bottom-left corner
+        'n' => '\u{253C}', // crossing lines
+        'o' => '\u{23BA}', // scan line 1
+        'p' => '\u{23BB}', // scan line 3
+        'q' => '\u{2500}', // horizontal line (scan line 5)
+        'r' => '\u{23BC}', // scan line 7
+        's' => '\u{23BD}', // scan line 9
+        't' => '\u{251C}', // left tee
+        'u' => '\u{2524}', // right tee
+        'v' => '\u{2534}', // bottom tee
+        'w' => '\u{252C}', // top tee
+        'x' => '\u{2502}', // vertical line
+        'y' => '\u{2264}', // less-than-or-equal
+        'z' => '\u{2265}', // greater-than-or-equal
+        '{' => '\u{03C0}', // pi
+        '|' => '\u{2260}', // not equal
+        '}' => '\u{00A3}', // pound sterling
+        '~' => '\u{00B7}', // bullet
+        _ => c,
+    }
+}
+
+/// Translate a completed OSC string (the bytes accumulated between
+/// `Action::OscStart` and `Action::OscEnd`) into the [`AnsiCommand`] it
+/// requests. OSC payloads are `Ps;Pt` (a numeric selector, then a
+/// semicolon, then a text argument); only the selector's presence is
+/// parsed here; the rest is handed to the caller as the raw argument.
+pub fn translate_osc(data: &[u8]) -> Option<AnsiCommand> {
+    let mut parts = data.splitn(2, |&b| b == b';');
+    let selector = parts.next()?;
+    let arg = parts.next().unwrap_or(&[]);
+
+    let mut ps: u32 = 0;
+    for &b in selector {
+        if !b.is_ascii_digit() {
+            return Some(AnsiCommand::Unknown(data.to_vec()));
         }
+        ps = ps.saturating_mul(10).saturating_add((b - b'0') as u32);
     }
 
-    fn process_string(&mut self, byte: u8) -> Option<AnsiCommand> {
-        match byte {
-            0x1B => {
-                self.state = ParserState::EscapeSequence;
-                None
-            }
-            0x07 => {
-                // String terminator (BEL)
-                self.state = ParserState::Idle;
-                self.parse_osc_command()
+    match ps {
+        0 | 1 | 2 => {
+            // Set icon name / window title
+            let title = core::str::from_utf8(arg).unwrap_or("").into();
+            Some(AnsiCommand::SetTitle(title))
+        }
+        4 => {
+            // Set/query palette color: `4;<index>;<spec>`
+            let mut fields = arg.splitn(2, |&b| b == b';');
+            let index = fields.next().and_then(parse_decimal_u8);
+            let spec = fields.next();
+            match (index, spec) {
+                (Some(index), Some(b"?")) => Some(AnsiCommand::QueryPaletteColor(index)),
+                (Some(index), Some(spec)) => match parse_color_spec(spec) {
+                    Some(rgb) => Some(AnsiCommand::SetPaletteColor(index, rgb)),
+                    None => Some(AnsiCommand::Unknown(data.to_vec())),
+                },
+                _ => Some(AnsiCommand::Unknown(data.to_vec())),
             }
-            _ => {
-                self.osc_data.push(byte as char);
+        }
+        10 => match arg {
+            b"?" => Some(AnsiCommand::QueryDefaultForeground),
+            spec => match parse_color_spec(spec) {
+                Some(rgb) => Some(AnsiCommand::SetDefaultForeground(rgb)),
+                None => Some(AnsiCommand::Unknown(data.to_vec())),
+            },
+        },
+        11 => match arg {
+            b"?" => Some(AnsiCommand::QueryDefaultBackground),
+            spec => match parse_color_spec(spec) {
+                Some(rgb) => Some(AnsiCommand::SetDefaultBackground(rgb)),
+                None => Some(AnsiCommand::Unknown(data.to_vec())),
+            },
+        },
+        12 => match arg {
+            b"?" => Some(AnsiCommand::QueryCursorColor),
+            spec => match parse_color_spec(spec) {
+                Some(rgb) => Some(AnsiCommand::SetCursorColor(rgb)),
+                None => Some(AnsiCommand::Unknown(data.to_vec())),
+            },
+        },
+        17 => match arg {
+            b"?" => Some(AnsiCommand::QueryHighlightBackground),
+            spec => match parse_color_spec(spec) {
+                Some(rgb) => Some(AnsiCommand::SetHighlightBackground(rgb)),
+                None => Some(AnsiCommand::Unknown(data.to_vec())),
+            },
+        },
+        19 => match arg {
+            b"?" => Some(AnsiCommand::QueryHighlightForeground),
+            spec => match parse_color_spec(spec) {
+                Some(rgb) => Some(AnsiCommand::SetHighlightForeground(rgb)),
+                None => Some(AnsiCommand::Unknown(data.to_vec())),
+            },
+        },
+        104 => match parse_decimal_u8(arg) {
+            Some(index) => Some(AnsiCommand::ResetPaletteColor(index)),
+            None => Some(AnsiCommand::Unknown(data.to_vec())),
+        },
+        8 => {
+            // Hyperlink: `8;<key=value>[:<key=value>...];<uri>`; an empty
+            // URI closes the current link.
+            let mut fields = arg.splitn(2, |&b| b == b';');
+            let params = fields.next().unwrap_or(&[]);
+            let uri = fields.next().unwrap_or(&[]);
+            let id = parse_hyperlink_id(params);
+            let uri = if uri.is_empty() {
                 None
+            } else {
+                match core::str::from_utf8(uri) {
+                    Ok(s) => Some(s.into()),
+                    Err(_) => return Some(AnsiCommand::Unknown(data.to_vec())),
+                }
+            };
+            Some(AnsiCommand::SetHyperlink { id, uri })
+        }
+        52 => {
+            // Clipboard get/set: `52;<selection>;<base64-or-?>`
+            let mut fields = arg.splitn(2, |&b| b == b';');
+            let selection = fields.next().and_then(parse_clipboard_selection);
+            let payload = fields.next();
+            match (selection, payload) {
+                (Some(selection), Some(b"?")) => Some(AnsiCommand::QueryClipboard(selection)),
+                (Some(selection), Some(payload)) => match decode_base64(payload) {
+                    Some(data) => Some(AnsiCommand::SetClipboard { selection, data }),
+                    None => Some(AnsiCommand::Unknown(data.to_vec())),
+                },
+                _ => Some(AnsiCommand::Unknown(data.to_vec())),
             }
         }
+        _ => Some(AnsiCommand::Unknown(data.to_vec())),
     }
+}
 
-    fn process_osc(&mut self, byte: u8) -> Option<AnsiCommand> {
-        if byte == 0x1B {
-            self.state = ParserState::EscapeSequence;
-            return None;
+/// Parse an OSC 52 selection field (`c` = clipboard, `p` = primary,
+/// `s` = selection). An empty field defaults to the clipboard, matching
+/// xterm's behavior.
+fn parse_clipboard_selection(field: &[u8]) -> Option<ClipboardSelection> {
+    match field {
+        b"" | b"c" => Some(ClipboardSelection::Clipboard),
+        b"p" => Some(ClipboardSelection::Primary),
+        b"s" => Some(ClipboardSelection::Selection),
+        _ => None,
+    }
+}
+
+/// Find the `id=...` field in an OSC 8 parameter list (`:`-separated
+/// `key=value` pairs). Returns `None` if no `id` field is present.
+fn parse_hyperlink_id(params: &[u8]) -> Option<String> {
+    for field in params.split(|&b| b == b':') {
+        if let Some(rest) = field.strip_prefix(b"id=") {
+            return core::str::from_utf8(rest).ok().map(|s| s.into());
         }
-        if byte == 0x07 {
-            self.state = ParserState::Idle;
-            return self.parse_osc_command();
+    }
+    None
+}
+
+/// Hard cap on a decoded OSC 52 clipboard payload, so a single huge OSC
+/// string can't be used to exhaust kernel memory.
+const MAX_CLIPBOARD_BYTES: usize = 1024 * 1024;
+
+/// Map a base64 alphabet character to its 6-bit value. `=` padding is
+/// handled by the caller, not here.
+fn base64_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'A'..=b'Z' => Some(byte - b'A'),
+        b'a'..=b'z' => Some(byte - b'a' + 26),
+        b'0'..=b'9' => Some(byte - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+/// Decode a standard base64 string (no_std, hand-rolled: this crate has
+/// no base64 crate available). `=` padding is ignored wherever it
+/// appears; any other illegal character rejects the whole decode.
+/// Enforces [`MAX_CLIPBOARD_BYTES`] so a malicious OSC 52 string can't
+/// exhaust kernel memory.
+fn decode_base64(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut accum: u32 = 0;
+    let mut bits: u32 = 0;
+    for &b in data {
+        if b == b'=' {
+            continue;
         }
-        if byte == b';' || byte == b'=' {
-            if self.params.is_empty() {
-                self.params.push((byte - b'0') as i32);
-            } else if let Some(last) = self.params.last_mut() {
-                *last = *last * 10 + (byte - b'0') as i32;
+        let value = base64_value(b)?;
+        accum = (accum << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            if out.len() >= MAX_CLIPBOARD_BYTES {
+                return None;
             }
-            return None;
+            out.push((accum >> bits) as u8);
         }
-        if byte >= b'0' && byte <= b'9' {
-            if let Some(last) = self.params.last_mut() {
-                *last = *last * 10 + (byte - b'0') as i32;
-            } else {
-                self.params.push((byte - b'0') as i32);
-            }
+    }
+    Some(out)
+}
+
+fn parse_decimal_u8(digits: &[u8]) -> Option<u8> {
+    if digits.is_empty() {
+        return None;
+    }
+    let mut value: u32 = 0;
+    for &b in digits {
+        if !b.is_ascii_digit() {
             return None;
         }
-        self.osc_data.push(byte as char);
+        value = value.saturating_mul(10).saturating_add((b - b'0') as u32);
+    }
+    if value > u8::MAX as u32 {
         None
+    } else {
+        Some(value as u8)
     }
+}
 
-    fn process_dcs(&mut self, byte: u8) -> Option<AnsiCommand> {
-        if byte == 0x1B {
-            self.state = ParserState::EscapeSequence;
-            return None;
-        }
-        if byte == 0x07 || byte == 0x9C {
-            self.state = ParserState::Idle;
+/// Parse an XParseColor-style legacy color spec: `rgb:RRRR/GGGG/BBBB`
+/// (1-4 hex digits per component) or `#RRGGBB`/`#RGB` hex shorthand.
+/// Returns `None` for anything else, including a bare `?` query (callers
+/// check for that themselves, since it means "query" rather than
+/// "invalid").
+fn parse_color_spec(spec: &[u8]) -> Option<(u8, u8, u8)> {
+    if let Some(rest) = spec.strip_prefix(b"rgb:") {
+        let mut components = rest.split(|&b| b == b'/');
+        let r = parse_scaled_hex(components.next()?)?;
+        let g = parse_scaled_hex(components.next()?)?;
+        let b = parse_scaled_hex(components.next()?)?;
+        if components.next().is_some() {
             return None;
         }
-        None
+        return Some((r, g, b));
     }
-
-    fn parse_csi_command(&mut self, final_byte: u8) -> AnsiCommand {
-        // Normalize parameters
-        let params: Vec<usize> = self.params.iter()
-            .filter(|&&v| v > 0)
-            .map(|&v| v as usize)
-            .collect();
-
-        let param = |idx: usize, default: usize| -> usize {
-            params.get(idx).copied().unwrap_or(default)
+    if let Some(rest) = spec.strip_prefix(b"#") {
+        return match rest.len() {
+            3 => Some((
+                parse_scaled_hex(&rest[0..1])?,
+                parse_scaled_hex(&rest[1..2])?,
+                parse_scaled_hex(&rest[2..3])?,
+            )),
+            6 => Some((
+                parse_scaled_hex(&rest[0..2])?,
+                parse_scaled_hex(&rest[2..4])?,
+                parse_scaled_hex(&rest[4..6])?,
+            )),
+            _ => None,
         };
-
-        match final_byte {
-            b'A' => AnsiCommand::CursorUp(param(0, 1)),
-            b'B' | b'e' => AnsiCommand::CursorDown(param(0, 1)),
-            b'C' | b'a' => AnsiCommand::CursorForward(param(0, 1)),
-            b'D' => AnsiCommand::CursorBack(param(0, 1)),
-            b'E' => AnsiCommand::CursorDown(param(0, 1)),  // Cursor to beginning of line N down
-            b'F' => AnsiCommand::CursorUp(param(0, 1)),    // Cursor to beginning of line N up
-            b'G' | b'`' => {
-                if params.is_empty() {
-                    AnsiCommand::CursorHome
-                } else {
-                    AnsiCommand::CursorPosition(1, params[0])
-                }
-            }
-            b'H' | b'f' => {
-                if params.len() >= 2 {
-                    AnsiCommand::CursorPosition(params[0], params[1])
-                } else {
-                    AnsiCommand::CursorHome
-                }
-            }
-            b'J' => AnsiCommand::EraseInDisplay(match param(0, 0) {
-                0 => EraseType::FromCursorToEnd,
-                1 => EraseType::FromBeginningToCursor,
-                2 | 3 => EraseType::EntireScreen,
-                _ => EraseType::FromCursorToEnd,
-            }),
-            b'K' => AnsiCommand::EraseInLine(match param(0, 0) {
-                0 => EraseType::FromCursorToEndOfLine,
-                1 => EraseType::FromBeginningToCursorOfLine,
-                2 => EraseType::EntireLine,
-                _ => EraseType::FromCursorToEndOfLine,
-            }),
-            b'L' => AnsiCommand::InsertLines(param(0, 1)),
-            b'M' => AnsiCommand::DeleteLines(param(0, 1)),
-            b'P' => AnsiCommand::DeleteChars(param(0, 1)),
-            b'S' => AnsiCommand::ScrollUp(param(0, 1)),
-            b'T' => AnsiCommand::ScrollDown(param(0, 1)),
-            b'X' => AnsiCommand::EraseChars(param(0, 1)),
-            b'Z' => {
-                // Backtab
-                AnsiCommand::CursorBack(param(0, 1))
-            }
-            b'[' if self.intermediate.is_empty() => AnsiCommand::SetCursorMode(true),
-            b']' if self.intermediate.is_empty() => AnsiCommand::SetCursorMode(false),
-            b'^' => AnsiCommand::EraseInLine(EraseType::EntireLine),  // Privacy message
-            b'c' if params.is_empty() => AnsiCommand::ResetMode(ModeType::CursorKeyMode),  // Full reset
-            b'd' => {
-                // Cursor to line
-                if params.is_empty() {
-                    AnsiCommand::CursorPosition(1, 1)
-                } else {
-                    AnsiCommand::CursorPosition(params[0], 1)
-                }
-            }
-            b'g' => {
-                // Tabs
-                if param(0, 0) == 0 {
-                    AnsiCommand::ClearTabStop
-                } else {
-                    AnsiCommand::ClearAllTabStops
-                }
-            }
-            b'h' => self.parse_set_mode(),
-            b'l' => self.parse_reset_mode(),
-            b'm' => self.parse_graphics_command(),
-            b'n' => {
-                // Device status report
-                if param(0, 0) == 5 {
-                    // Status report
-                    AnsiCommand::Unknown(vec![])
-                } else if param(0, 0) == 6 {
-                    // Cursor position report
-                    AnsiCommand::Unknown(vec![])
-                } else {
-                    AnsiCommand::Unknown(vec![])
-                }
-            }
-            b'q' => {
-                // LEDs
-                AnsiCommand::Unknown(vec![])
-            }
-            b'r' => {
-                // Scrolling region
-                if params.len() >= 2 {
-                    AnsiCommand::SetScrollingRegion(param(0, 1), param(1, 24))
-                } else {
-                    AnsiCommand::Unknown(vec![])
-                }
-            }
-            _ => AnsiCommand::Unknown(vec![]),
-        }
     }
+    None
+}
 
-    fn parse_set_mode(&mut self) -> AnsiCommand {
-        let mode = if let Some(&m) = self.params.first() { m as i32 } else { 0 };
-        
-        // Check for private modes (DECSET)
-        if self.intermediate.contains(&b'?') {
-            match mode {
-                1 => AnsiCommand::SetMode(ModeType::CursorKeyMode),
-                2 => AnsiCommand::SetMode(ModeType::AnsiMode),  // DECANM
-                3 => AnsiCommand::SetMode(ModeType::ColumnMode),  // DECCOLM
-                4 => AnsiCommand::SetMode(ModeType::ScrollMode),  // DECSCLM
-                5 => AnsiCommand::SetMode(ModeType::ScreenMode),  // DECNM
-                6 => AnsiCommand::SetMode(ModeType::OriginMode),  // DECOM
-                7 => AnsiCommand::SetMode(ModeType::AutoWrapMode),  // DECAWM
-                12 => {} // Start blinking cursor
-                25 => AnsiCommand::SetCursorMode(true),  // Show cursor
-                1000 => AnsiCommand::EnableMouseTracking(MouseTrackingMode::X10Compatible),
-                1002 => AnsiCommand::EnableMouseTracking(MouseTrackingMode::ButtonTracking),
-                1003 => AnsiCommand::EnableMouseTracking(MouseTrackingMode::AnyEventTracking),
-                1005 => {}  // Extended mouse reporting
-                1006 => {}  // SGR mouse reporting
-                1015 => {}  // URXVT mouse reporting
-                1048 => AnsiCommand::SaveCursorPosition,
-                1049 => {
-                    // Save cursor and clear screen (alternative screen buffer)
-                    AnsiCommand::Unknown(vec![])
-                }
-                _ => AnsiCommand::Unknown(vec![]),
-            }
-        } else {
-            AnsiCommand::Unknown(vec![])
-        }
+/// Parse `digits` (1-4 hex digits) and scale to 8 bits per XParseColor's
+/// `rgb:` rule: the value is treated as a fraction of `2^(4*N)` and the
+/// top 8 bits of that fraction become the result, e.g. `f` -> `0xf0`
+/// (left-justified), `ff` -> `0xff` (exact), `ffff` -> `0xff` (truncated).
+fn parse_scaled_hex(digits: &[u8]) -> Option<u8> {
+    let n = digits.len();
+    if n == 0 || n > 4 {
+        return None;
+    }
+    let mut value: u32 = 0;
+    for &d in digits {
+        value = value * 16 + (d as char).to_digit(16)?;
     }
+    let bits = 4 * n as u32;
+    Some(if bits >= 8 {
+        (value >> (bits - 8)) as u8
+    } else {
+        (value << (8 - bits)) as u8
+    })
+}
 
-    fn parse_reset_mode(&mut self) -> AnsiCommand {
-        let mode = if let Some(&m) = self.params.first() { m as i32 } else { 0 };
-        
-        if self.intermediate.contains(&b'?') {
-            match mode {
-                1 => AnsiCommand::ResetMode(ModeType::CursorKeyMode),
-                2 => AnsiCommand::ResetMode(ModeType::AnsiMode),
-                3 => AnsiCommand::ResetMode(ModeType::ColumnMode),
-                4 => AnsiCommand::ResetMode(ModeType::ScrollMode),
-                5 => AnsiCommand::ResetMode(ModeType::ScreenMode),
-                6 => AnsiCommand::ResetMode(ModeType::OriginMode),
-                7 => AnsiCommand::ResetMode(ModeType::AutoWrapMode),
-                25 => AnsiCommand::SetCursorMode(false),  // Hide cursor
-                1000 | 1002 | 1003 => AnsiCommand::DisableMouseTracking,
-                1048 => AnsiCommand::RestoreCursorPosition,
-                _ => AnsiCommand::Unknown(vec![]),
-            }
-        } else {
-            AnsiCommand::Unknown(vec![])
-        }
+/// Translate a `Hook` action (the DCS introducer, dispatched once the DCS
+/// header's final byte is seen) into the [`AnsiCommand`] it requests.
+/// Recognizes only the synchronized-update passthrough sequences
+/// `ESC P = 1 s` (begin) and `ESC P = 2 s` (end); any other DCS header
+/// is `None`, same as an unrecognized `Action::Hook` is simply dropped by
+/// callers that don't have a string payload to consume for it.
+pub fn translate_dcs_hook(params: &[i64], final_byte: char, private_prefix: Option<char>) -> Option<AnsiCommand> {
+    if private_prefix != Some('=') || final_byte != 's' {
+        return None;
+    }
+    match params.first() {
+        Some(1) => Some(AnsiCommand::BeginSynchronizedUpdate),
+        Some(2) => Some(AnsiCommand::EndSynchronizedUpdate),
+        _ => None,
     }
+}
 
-    fn parse_graphics_command(&mut self) -> AnsiCommand {
-        if self.params.is_empty() {
-            return AnsiCommand::ResetGraphicsMode;
-        }
+/// Live terminal state a caller supplies to resolve a report request that
+/// `translate_csi` can't answer on its own, since it's a stateless
+/// translator with no view of the running terminal (currently just the
+/// cursor position needed for `CSI 6 n`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReportContext {
+    pub cursor_row: usize,
+    pub cursor_col: usize,
+}
 
-        let mut attrs = Vec::new();
-        let mut i = 0;
+/// Build the `ESC [ <row> ; <col> R` Device Status Report reply to
+/// `CSI 6 n`, called once the host has resolved `AnsiCommand::QueryCursorPosition`
+/// against its own [`ReportContext`]. Row/column are reported 1-indexed.
+pub fn build_cursor_position_report(ctx: ReportContext) -> AnsiCommand {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"\x1b[");
+    push_decimal(&mut bytes, ctx.cursor_row + 1);
+    bytes.push(b';');
+    push_decimal(&mut bytes, ctx.cursor_col + 1);
+    bytes.push(b'R');
+    AnsiCommand::Report(bytes)
+}
 
-        while i < self.params.len() {
-            let param = self.params[i];
-            
-            if param < 0 {
-                i += 1;
-                continue;
-            }
+fn push_decimal(out: &mut Vec<u8>, mut value: usize) {
+    if value == 0 {
+        out.push(b'0');
+        return;
+    }
+    let start = out.len();
+    while value > 0 {
+        out.push(b'0' + (value % 10) as u8);
+        value /= 10;
+    }
+    out[start..].reverse();
+}
 
-            match param {
-                0 => attrs.push(GraphicsAttribute::Reset),
-                1 => attrs.push(GraphicsAttribute::Bold),
-                2 => attrs.push(GraphicsAttribute::Dim),
-                3 => attrs.push(GraphicsAttribute::Italic),
-                4 => attrs.push(GraphicsAttribute::Underline),
-                5 | 6 => attrs.push(GraphicsAttribute::Blink),
-                7 => attrs.push(GraphicsAttribute::Reverse),
-                8 => attrs.push(GraphicsAttribute::Hidden),
-                9 => attrs.push(GraphicsAttribute::Strikethrough),
-                10..=19 => {}  // Font selection (not implemented)
-                20 => attrs.push(GraphicsAttribute::DoubleUnderline),
-                21 => attrs.push(GraphicsAttribute::Reset),  // Normal intensity
-                22 => attrs.push(GraphicsAttribute::Reset),  // Bold off
-                23 => attrs.push(GraphicsAttribute::Reset),  // Italic off
-                24 => attrs.push(GraphicsAttribute::Reset),  // Underline off
-                25 => attrs.push(GraphicsAttribute::Reset),  // Blink off
-                27 => attrs.push(GraphicsAttribute::Reset),  // Reverse off
-                28 => attrs.push(GraphicsAttribute::Reset),  // Hidden off
-                29 => attrs.push(GraphicsAttribute::Reset),  // Strikethrough off
-                30..=37 => {
-                    let color = Self::color_from_param(param as usize - 30);
-                    attrs.push(GraphicsAttribute::Foreground(color));
-                }
-                38 => {
-                    if i + 1 < self.params.len() {
-                        if self.params[i + 1] == 5 {
-                            // 256 color
-                            if i + 2 < self.params.len() {
-                                let color = self.params[i + 2] as usize;
-                                attrs.push(GraphicsAttribute::Foreground256(color));
-                                i += 2;
-                            }
-                        } else if self.params[i + 1] == 2 {
-                            // True color
-                            if i + 4 < self.params.len() {
-                                let r = self.params[i + 2] as u8;
-                                let g = self.params[i + 3] as u8;
-                                let b = self.params[i + 4] as u8;
-                                attrs.push(GraphicsAttribute::TrueColorForeground(r, g, b));
-                                i += 4;
-                            }
-                        }
-                        i += 1;
-                    }
-                }
-                39 => attrs.push(GraphicsAttribute::Foreground(Color::Default)),
-                40..=47 => {
-                    let color = Self::color_from_param(param as usize - 40);
-                    attrs.push(GraphicsAttribute::Background(color));
-                }
-                48 => {
-                    if i + 1 < self.params.len() {
-                        if self.params[i + 1] == 5 {
-                            // 256 color
-                            if i + 2 < self.params.len() {
-                                let color = self.params[i + 2] as usize;
-                                attrs.push(GraphicsAttribute::Background256(color));
-                                i += 2;
-                            }
-                        } else if self.params[i + 1] == 2 {
-                            // True color
-                            if i + 4 < self.params.len() {
-                                let r = self.params[i + 2] as u8;
-                                let g = self.params[i + 3] as u8;
-                                let b = self.params[i + 4] as u8;
-                                attrs.push(GraphicsAttribute::TrueColorBackground(r, g, b));
-                                i += 4;
-                            }
-                        }
-                        i += 1;
-                    }
-                }
-                49 => attrs.push(GraphicsAttribute::Background(Color::Default)),
-                51 => attrs.push(GraphicsAttribute::Framed),
-                52 => attrs.push(GraphicsAttribute::Encircled),
-                53 => attrs.push(GraphicsAttribute::Overline),
-                54 => attrs.push(GraphicsAttribute::Reset),  // Framed/encircled off
-                55 => attrs.push(GraphicsAttribute::Reset),  // Overline off
-                _ => {}
-            }
-            i += 1;
+/// Encode a mouse event into the byte sequence an application expects,
+/// honoring the currently-enabled [`MouseTrackingMode`] and
+/// [`MouseExtendedMode`]. Returns an empty `Vec` if tracking is off
+/// (`MouseTrackingMode::None`), since there's nothing to report.
+///
+/// `motion` marks a drag event (button held while the cursor moves), which
+/// sets the button byte's motion flag (32) in button-tracking/any-event
+/// modes; callers that don't support drag reporting can always pass
+/// `false`.
+pub fn encode_mouse_event(
+    mode: MouseTrackingMode,
+    extended: MouseExtendedMode,
+    button: MouseButton,
+    pressed: bool,
+    motion: bool,
+    row: usize,
+    col: usize,
+    modifiers: MouseModifiers,
+) -> Vec<u8> {
+    if mode == MouseTrackingMode::None {
+        return Vec::new();
+    }
+
+    let mut base = match button {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+        MouseButton::WheelUp => 64,
+        MouseButton::WheelDown => 65,
+    };
+    if motion {
+        base |= 32;
+    }
+    base |= modifiers.bits();
+
+    // Wheel events have no "release"; everything else reports release as
+    // button code 3 (modifiers still apply) in the legacy/urxvt formats.
+    let is_wheel = matches!(button, MouseButton::WheelUp | MouseButton::WheelDown);
+    let legacy_button = if pressed || is_wheel { base } else { 3 | modifiers.bits() };
+
+    match extended {
+        MouseExtendedMode::Sgr => {
+            let mut out = Vec::new();
+            out.extend_from_slice(b"\x1b[<");
+            push_decimal(&mut out, base as usize);
+            out.push(b';');
+            push_decimal(&mut out, col);
+            out.push(b';');
+            push_decimal(&mut out, row);
+            out.push(if pressed { b'M' } else { b'm' });
+            out
         }
+        MouseExtendedMode::Urxvt => {
+            let mut out = Vec::new();
+            out.extend_from_slice(b"\x1b[");
+            push_decimal(&mut out, 32 + legacy_button as usize);
+            out.push(b';');
+            push_decimal(&mut out, col);
+            out.push(b';');
+            push_decimal(&mut out, row);
+            out.push(b'M');
+            out
+        }
+        MouseExtendedMode::Normal => {
+            // Legacy X10/normal encoding: each value is offset by 32 and
+            // capped at 223 so it never needs more than a single byte.
+            let cb = (32u32 + legacy_button as u32).min(255) as u8;
+            let cx = (32usize + col).min(223) as u8;
+            let cy = (32usize + row).min(223) as u8;
+            let mut out = Vec::new();
+            out.extend_from_slice(b"\x1b[M");
+            out.push(cb);
+            out.push(cx);
+            out.push(cy);
+            out
+        }
+    }
+}
 
-        AnsiCommand::SetGraphicsMode(attrs)
+/// Safety bookkeeping for a synchronized-update DCS passthrough
+/// (`BeginSynchronizedUpdate`/`EndSynchronizedUpdate`): while active, a
+/// caller is expected to buffer rendering and only redraw once `end()` is
+/// reached, so a malformed or malicious stream that never sends the end
+/// sequence must not be allowed to freeze rendering forever. `record_byte`
+/// enforces a hard cap on buffered passthrough bytes; `poll_timeout`
+/// lets a caller with its own timer source (no timer in the parse loop
+/// itself) abort on elapsed wall-clock time instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SyncUpdateTracker {
+    active: bool,
+    started_at_ns: u64,
+    bytes: usize,
+}
+
+/// Hard cap on bytes buffered during a synchronized update, so a stream
+/// that never sends the end sequence can't grow a caller's buffer
+/// without limit.
+pub const SYNC_UPDATE_MAX_BYTES: usize = 2 * 1024 * 1024;
+/// Hard cap on how long a synchronized update may stay active, so a
+/// caller polling `poll_timeout` always regains control even if the end
+/// sequence never arrives.
+pub const SYNC_UPDATE_TIMEOUT_NS: u64 = 150 * 1_000_000;
+
+impl SyncUpdateTracker {
+    pub fn is_active(&self) -> bool {
+        self.active
     }
 
-    fn color_from_param(param: usize) -> Color {
-        match param {
-            0 => Color::Black,
-            1 => Color::Red,
-            2 => Color::Green,
-            3 => Color::Yellow,
-            4 => Color::Blue,
-            5 => Color::Magenta,
-            6 => Color::Cyan,
-            7 => Color::White,
-            8 => Color::BrightBlack,
-            9 => Color::BrightRed,
-            10 => Color::BrightGreen,
-            11 => Color::BrightYellow,
-            12 => Color::BrightBlue,
-            13 => Color::BrightMagenta,
-            14 => Color::BrightCyan,
-            15 => Color::BrightWhite,
-            _ => Color::Default,
-        }
+    /// Begin tracking a synchronized update starting at `now_ns`.
+    pub fn begin(&mut self, now_ns: u64) {
+        self.active = true;
+        self.started_at_ns = now_ns;
+        self.bytes = 0;
     }
 
-    fn parse_osc_command(&mut self) -> Option<AnsiCommand> {
-        if self.params.is_empty() {
-            return None;
-        }
+    pub fn end(&mut self) {
+        self.active = false;
+        self.bytes = 0;
+    }
 
-        match self.params[0] {
-            0 | 1 | 2 => {
-                // Set icon name / window title
-                Some(AnsiCommand::SetTitle(self.osc_data.clone()))
-            }
-            10 | 11 => {
-                // Set dynamic colors
-                Some(AnsiCommand::Unknown(vec![]))
-            }
-            12 => {
-                // Set cursor color
-                Some(AnsiCommand::Unknown(vec![]))
-            }
-            17 => {
-                // Highlight background color
-                Some(AnsiCommand::Unknown(vec![]))
-            }
-            19 => {
-                // Highlight foreground color
-                Some(AnsiCommand::Unknown(vec![]))
-            }
-            22 => {
-                // Store window title
-                Some(AnsiCommand::Unknown(vec![]))
-            }
-            23 => {
-                // Restore window title
-                Some(AnsiCommand::Unknown(vec![]))
-            }
-            _ => Some(AnsiCommand::Unknown(vec![])),
+    /// Record one buffered passthrough byte, returning `true` if the byte
+    /// cap was just exceeded and the caller should abort (treat this as
+    /// if the end sequence had been received).
+    pub fn record_byte(&mut self) -> bool {
+        if !self.active {
+            return false;
         }
+        self.bytes += 1;
+        self.bytes > SYNC_UPDATE_MAX_BYTES
     }
-}
 
-impl Default for AnsiParser {
-    fn default() -> Self {
-        Self::new()
+    /// Check whether the update has been active for longer than
+    /// [`SYNC_UPDATE_TIMEOUT_NS`], returning `true` if the caller should
+    /// abort (treat this as if the end sequence had been received).
+    pub fn poll_timeout(&self, now_ns: u64) -> bool {
+        self.active && now_ns.saturating_sub(self.started_at_ns) >= SYNC_UPDATE_TIMEOUT_NS
     }
 }
 
@@ -801,40 +1098,23 @@ pub struct AnsiGenerator;
 
 impl AnsiGenerator {
     /// Generate cursor up command
-    pub fn cursor_up(n: usize) -> &'static str {
-        if n == 1 {
-            "\x1B[A"
-        } else {
-            // Would need to allocate dynamically
-            "\x1B[A"
-        }
+    pub fn cursor_up(n: usize) -> String {
+        if n == 1 { "\x1B[A".into() } else { format!("\x1B[{}A", n) }
     }
 
     /// Generate cursor down command
-    pub fn cursor_down(n: usize) -> &'static str {
-        if n == 1 {
-            "\x1B[B"
-        } else {
-            "\x1B[B"
-        }
+    pub fn cursor_down(n: usize) -> String {
+        if n == 1 { "\x1B[B".into() } else { format!("\x1B[{}B", n) }
     }
 
     /// Generate cursor forward command
-    pub fn cursor_forward(n: usize) -> &'static str {
-        if n == 1 {
-            "\x1B[C"
-        } else {
-            "\x1B[C"
-        }
+    pub fn cursor_forward(n: usize) -> String {
+        if n == 1 { "\x1B[C".into() } else { format!("\x1B[{}C", n) }
     }
 
     /// Generate cursor back command
-    pub fn cursor_back(n: usize) -> &'static str {
-        if n == 1 {
-            "\x1B[D"
-        } else {
-            "\x1B[D"
-        }
+    pub fn cursor_back(n: usize) -> String {
+        if n == 1 { "\x1B[D".into() } else { format!("\x1B[{}D", n) }
     }
 
     /// Generate cursor position command
@@ -902,10 +1182,18 @@ impl AnsiGenerator {
     }
 
     /// Generate set title command
-    pub fn set_title(title: &str) -> String {
+    pub fn set_title<T: core::fmt::Display>(title: T) -> String {
         format!("\x1B]2;{}\x07", title)
     }
 
+    /// Same as [`Self::set_title`], but closes the OSC string with the
+    /// 7-bit ST form (`\x1B\\`) instead of BEL, for terminals that pass
+    /// BEL straight through to the bell rather than treating it as an OSC
+    /// terminator.
+    pub fn set_title_st<T: core::fmt::Display>(title: T) -> String {
+        format!("\x1B]2;{}\x1B\\", title)
+    }
+
     /// Generate reset graphics mode command
     pub fn reset_graphics() -> &'static str {
         "\x1B[0m"
@@ -931,49 +1219,57 @@ impl AnsiGenerator {
         "\x1B[7m"
     }
 
-    /// Generate color command (foreground)
-    pub fn foreground(color: Color) -> &'static str {
+    /// Generate color command (foreground). Basic/bright/default colors
+    /// produce the fixed SGR code; `Indexed`/`Rgb` delegate to
+    /// [`Self::foreground_256`]/[`Self::foreground_truecolor`].
+    pub fn foreground(color: Color) -> String {
         match color {
-            Color::Black => "\x1B[30m",
-            Color::Red => "\x1B[31m",
-            Color::Green => "\x1B[32m",
-            Color::Yellow => "\x1B[33m",
-            Color::Blue => "\x1B[34m",
-            Color::Magenta => "\x1B[35m",
-            Color::Cyan => "\x1B[36m",
-            Color::White => "\x1B[37m",
-            Color::BrightBlack => "\x1B[90m",
-            Color::BrightRed => "\x1B[91m",
-            Color::BrightGreen => "\x1B[92m",
-            Color::BrightYellow => "\x1B[93m",
-            Color::BrightBlue => "\x1B[94m",
-            Color::BrightMagenta => "\x1B[95m",
-            Color::BrightCyan => "\x1B[96m",
-            Color::BrightWhite => "\x1B[97m",
-            Color::Default => "\x1B[39m",
+            Color::Black => "\x1B[30m".into(),
+            Color::Red => "\x1B[31m".into(),
+            Color::Green => "\x1B[32m".into(),
+            Color::Yellow => "\x1B[33m".into(),
+            Color::Blue => "\x1B[34m".into(),
+            Color::Magenta => "\x1B[35m".into(),
+            Color::Cyan => "\x1B[36m".into(),
+            Color::White => "\x1B[37m".into(),
+            Color::BrightBlack => "\x1B[90m".into(),
+            Color::BrightRed => "\x1B[91m".into(),
+            Color::BrightGreen => "\x1B[92m".into(),
+            Color::BrightYellow => "\x1B[93m".into(),
+            Color::BrightBlue => "\x1B[94m".into(),
+            Color::BrightMagenta => "\x1B[95m".into(),
+            Color::BrightCyan => "\x1B[96m".into(),
+            Color::BrightWhite => "\x1B[97m".into(),
+            Color::Default => "\x1B[39m".into(),
+            Color::Indexed(index) => Self::foreground_256(index as usize),
+            Color::Rgb(r, g, b) => Self::foreground_truecolor(r, g, b),
         }
     }
 
-    /// Generate color command (background)
-    pub fn background(color: Color) -> &'static str {
+    /// Generate color command (background). Basic/bright/default colors
+    /// produce the fixed SGR code; `Indexed`/`Rgb` delegate to
+    /// [`Self::background_256`]/[`Self::background_truecolor`].
+    pub fn background(color: Color) -> String {
         match color {
-            Color::Black => "\x1B[40m",
-            Color::Red => "\x1B[41m",
-            Color::Green => "\x1B[42m",
-            Color::Yellow => "\x1B[43m",
-            Color::Blue => "\x1B[44m",
-            Color::Magenta => "\x1B[45m",
-            Color::Cyan => "\x1B[46m",
-            Color::White => "\x1B[47m",
-            Color::BrightBlack => "\x1B[100m",
-            Color::BrightRed => "\x1B[101m",
-            Color::BrightGreen => "\x1B[102m",
-            Color::BrightYellow => "\x1B[103m",
-            Color::BrightBlue => "\x1B[104m",
-            Color::BrightMagenta => "\x1B[105m",
-            Color::BrightCyan => "\x1B[106m",
-            Color::BrightWhite => "\x1B[107m",
-            Color::Default => "\x1B[49m",
+            Color::Black => "\x1B[40m".into(),
+            Color::Red => "\x1B[41m".into(),
+            Color::Green => "\x1B[42m".into(),
+            Color::Yellow => "\x1B[43m".into(),
+            Color::Blue => "\x1B[44m".into(),
+            Color::Magenta => "\x1B[45m".into(),
+            Color::Cyan => "\x1B[46m".into(),
+            Color::White => "\x1B[47m".into(),
+            Color::BrightBlack => "\x1B[100m".into(),
+            Color::BrightRed => "\x1B[101m".into(),
+            Color::BrightGreen => "\x1B[102m".into(),
+            Color::BrightYellow => "\x1B[103m".into(),
+            Color::BrightBlue => "\x1B[104m".into(),
+            Color::BrightMagenta => "\x1B[105m".into(),
+            Color::BrightCyan => "\x1B[106m".into(),
+            Color::BrightWhite => "\x1B[107m".into(),
+            Color::Default => "\x1B[49m".into(),
+            Color::Indexed(index) => Self::background_256(index as usize),
+            Color::Rgb(r, g, b) => Self::background_truecolor(r, g, b),
         }
     }
 
@@ -1014,4 +1310,173 @@ impl AnsiGenerator {
             "\x1B[?2004l"
         }
     }
+
+    /// Generate an OSC 8 hyperlink start sequence. `id` lets separate
+    /// spans of the same link highlight together when hovered.
+    pub fn hyperlink(id: Option<&str>, uri: &str) -> String {
+        match id {
+            Some(id) => format!("\x1B]8;id={};{}\x1B\\", id, uri),
+            None => format!("\x1B]8;;{}\x1B\\", uri),
+        }
+    }
+
+    /// Generate the OSC 8 sequence that closes the current hyperlink.
+    pub fn hyperlink_end() -> &'static str {
+        "\x1B]8;;\x1B\\"
+    }
+
+    /// Generate the DCS sequence that begins a synchronized-update block
+    /// (see [`SyncUpdateTracker`]), so a consumer can draw everything
+    /// written before the matching `end_sync()` as one atomic frame
+    /// instead of tearing mid-redraw.
+    pub fn begin_sync() -> &'static str {
+        "\x1BP=1s\x1B\\"
+    }
+
+    /// Generate the DCS sequence that ends a synchronized-update block.
+    pub fn end_sync() -> &'static str {
+        "\x1BP=2s\x1B\\"
+    }
+}
+
+/// Fluent builder that accumulates a whole frame of escape sequences into
+/// one owned `String`, so a caller composing cursor moves, SGR attributes,
+/// erases, and mode toggles together pays for a single allocation instead
+/// of concatenating [`AnsiGenerator`]'s individual fragments by hand.
+///
+/// ```ignore
+/// let frame = AnsiBuilder::new()
+///     .move_up(5)
+///     .fg(Color::Red)
+///     .bold()
+///     .move_to(1, 1)
+///     .build();
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct AnsiBuilder {
+    out: String,
+}
+
+impl AnsiBuilder {
+    pub fn new() -> Self {
+        AnsiBuilder { out: String::new() }
+    }
+
+    /// Append a fragment already produced by [`AnsiGenerator`] (or any raw
+    /// escape sequence) and return `self` for further chaining.
+    fn push(mut self, fragment: &str) -> Self {
+        self.out.push_str(fragment);
+        self
+    }
+
+    pub fn move_up(self, n: usize) -> Self {
+        let frag = AnsiGenerator::cursor_up(n);
+        self.push(&frag)
+    }
+
+    pub fn move_down(self, n: usize) -> Self {
+        let frag = AnsiGenerator::cursor_down(n);
+        self.push(&frag)
+    }
+
+    pub fn move_forward(self, n: usize) -> Self {
+        let frag = AnsiGenerator::cursor_forward(n);
+        self.push(&frag)
+    }
+
+    pub fn move_back(self, n: usize) -> Self {
+        let frag = AnsiGenerator::cursor_back(n);
+        self.push(&frag)
+    }
+
+    pub fn move_to(self, row: usize, col: usize) -> Self {
+        let frag = AnsiGenerator::cursor_position(row, col);
+        self.push(&frag)
+    }
+
+    pub fn home(self) -> Self {
+        self.push(AnsiGenerator::cursor_home())
+    }
+
+    pub fn save_cursor(self) -> Self {
+        self.push(AnsiGenerator::save_cursor())
+    }
+
+    pub fn restore_cursor(self) -> Self {
+        self.push(AnsiGenerator::restore_cursor())
+    }
+
+    pub fn show_cursor(self) -> Self {
+        self.push(AnsiGenerator::show_cursor())
+    }
+
+    pub fn hide_cursor(self) -> Self {
+        self.push(AnsiGenerator::hide_cursor())
+    }
+
+    pub fn fg(self, color: Color) -> Self {
+        let frag = AnsiGenerator::foreground(color);
+        self.push(&frag)
+    }
+
+    pub fn bg(self, color: Color) -> Self {
+        let frag = AnsiGenerator::background(color);
+        self.push(&frag)
+    }
+
+    pub fn bold(self) -> Self {
+        self.push(AnsiGenerator::bold())
+    }
+
+    pub fn underline(self) -> Self {
+        self.push(AnsiGenerator::underline())
+    }
+
+    pub fn blink(self) -> Self {
+        self.push(AnsiGenerator::blink())
+    }
+
+    pub fn reverse(self) -> Self {
+        self.push(AnsiGenerator::reverse())
+    }
+
+    pub fn reset_graphics(self) -> Self {
+        self.push(AnsiGenerator::reset_graphics())
+    }
+
+    pub fn erase_screen(self, erase_type: EraseType) -> Self {
+        self.push(AnsiGenerator::clear_screen(erase_type))
+    }
+
+    pub fn erase_line(self, erase_type: EraseType) -> Self {
+        self.push(AnsiGenerator::clear_line(erase_type))
+    }
+
+    pub fn scroll_up(self, n: usize) -> Self {
+        let frag = AnsiGenerator::scroll_up(n);
+        self.push(&frag)
+    }
+
+    pub fn scroll_down(self, n: usize) -> Self {
+        let frag = AnsiGenerator::scroll_down(n);
+        self.push(&frag)
+    }
+
+    pub fn scrolling_region(self, top: usize, bottom: usize) -> Self {
+        let frag = AnsiGenerator::set_scrolling_region(top, bottom);
+        self.push(&frag)
+    }
+
+    pub fn alternate_screen(self, enable: bool) -> Self {
+        self.push(AnsiGenerator::alternate_screen(enable))
+    }
+
+    pub fn bracketed_paste(self, enable: bool) -> Self {
+        self.push(AnsiGenerator::bracketed_paste(enable))
+    }
+
+    /// Finish building and return the accumulated sequence.
+    pub fn build(self) -> String {
+        self.out
+    }
 }
@@ -6,6 +6,6 @@ pub mod line_discipline;
 pub mod ansi;
 pub mod tty;
 
-pub use self::line_discipline::{LineDiscipline, Signal, Termios, TerminalAttributes};
-pub use self::ansi::{AnsiParser, AnsiCommand, AnsiGenerator, EraseType, GraphicsAttribute, Color, MouseTrackingMode};
+pub use self::line_discipline::{LineDiscipline, Signal, Termios};
+pub use self::ansi::{AnsiCommand, AnsiGenerator, AnsiBuilder, TerminalAttributes, EraseType, GraphicsAttribute, Color, MouseTrackingMode, MouseExtendedMode, MouseButton, MouseModifiers};
 pub use self::tty::TtyDevice;
@@ -3,16 +3,20 @@
 //! This module provides the main TTY device driver that combines UART hardware
 //! access with line discipline and terminal emulation.
 
-use super::{line_discipline::{LineDiscipline, Signal, Termios}, ansi::{AnsiParser, AnsiCommand, TerminalAttributes}};
+use super::{line_discipline::{LineDiscipline, Signal, Termios}, ansi::{self, AnsiCommand, Color, GraphicsAttribute, ModeType, SyncUpdateTracker, TerminalAttributes}};
 use crate::drivers::serial::{SerialPort, SERIAL1};
+use crate::lib_core::vt::parser::{Parser, Action};
+use alloc::vec::Vec;
 
 /// TTY device structure
 pub struct TtyDevice {
     name: &'static str,
     minor: u32,
     line_discipline: LineDiscipline,
-    ansi_parser: AnsiParser,
+    parser: Parser,
     terminal_attrs: TerminalAttributes,
+    osc_buffer: Vec<u8>,
+    sync_tracker: SyncUpdateTracker,
     serial: SerialPort,
     input_buffer: [u8; 1024],
     output_buffer: [u8; 4096],
@@ -28,8 +32,10 @@ impl TtyDevice {
             name,
             minor,
             line_discipline: LineDiscipline::default(),
-            ansi_parser: AnsiParser::new(),
+            parser: Parser::new(),
             terminal_attrs: TerminalAttributes::default(),
+            osc_buffer: Vec::new(),
+            sync_tracker: SyncUpdateTracker::default(),
             serial,
             input_buffer: [0u8; 1024],
             output_buffer: [0u8; 4096],
@@ -93,6 +99,13 @@ impl TtyDevice {
     }
 
     /// Write to the TTY
+    ///
+    /// Bytes are fed through the VTE-style [`Parser`] shared with
+    /// [`crate::lib_core::vt::VtTerminal`]. `Print`/`Execute` actions (plain
+    /// text and control bytes) are forwarded straight to the serial output
+    /// buffer; `CsiDispatch`/`EscDispatch`/OSC actions are intercepted and
+    /// translated into [`AnsiCommand`]s that update `terminal_attrs` instead
+    /// of reaching the wire, same as the byte-at-a-time parser did before.
     pub fn write(&mut self, data: &[u8]) -> usize {
         if !self.is_open {
             return 0;
@@ -100,28 +113,10 @@ impl TtyDevice {
 
         let mut count = 0;
         for &byte in data {
-            if self.output_pos >= self.output_buffer.len() {
-                self.flush_output();
-            }
-
-            // Check for ANSI escape sequences
-            if byte == 0x1B {
-                self.output_buffer[self.output_pos] = byte;
-                self.output_pos += 1;
-            } else if self.output_pos > 0 && self.output_buffer[self.output_pos - 1] == 0x1B {
-                self.output_buffer[self.output_pos] = byte;
-                self.output_pos += 1;
-
-                // Process complete escape sequence
-                if self.output_pos >= 2 {
-                    if let Some(cmd) = self.ansi_parser.process_byte(byte) {
-                        self.execute_ansi_command(cmd);
-                        self.output_pos = 0;  // Clear escape sequence buffer
-                    }
-                }
-            } else {
-                self.output_buffer[self.output_pos] = byte;
-                self.output_pos += 1;
+            let mut actions = Vec::new();
+            self.parser.advance(byte, |action| actions.push(action));
+            for action in actions {
+                self.handle_action(action);
             }
             count += 1;
         }
@@ -129,6 +124,102 @@ impl TtyDevice {
         count
     }
 
+    /// Dispatch one parser [`Action`]: plain output goes to the serial
+    /// buffer, escape/control sequences are translated and applied locally.
+    fn handle_action(&mut self, action: Action) {
+        match action {
+            Action::Print(c) => {
+                let charset = if self.terminal_attrs.charset_in_use == 0 {
+                    self.terminal_attrs.g0_charset
+                } else {
+                    self.terminal_attrs.g1_charset
+                };
+                let translated = ansi::translate_charset(c, charset);
+                let mut buf = [0u8; 4];
+                for &byte in translated.encode_utf8(&mut buf).as_bytes() {
+                    self.push_output_byte(byte);
+                }
+            }
+            Action::Execute(b) => self.push_output_byte(b),
+            Action::OscStart => self.osc_buffer.clear(),
+            Action::OscPut(b) => self.osc_buffer.push(b),
+            Action::OscEnd => {
+                if let Some(cmd) = ansi::translate_osc(&self.osc_buffer) {
+                    self.execute_ansi_command(cmd);
+                }
+            }
+            Action::CsiDispatch(params, intermediates, _ignore, final_byte, private) => {
+                let cmd = ansi::translate_csi(&params, &intermediates, private, final_byte);
+                self.execute_ansi_command(cmd);
+            }
+            Action::EscDispatch(intermediates, _ignore, byte) => {
+                if let Some(cmd) = ansi::translate_esc(&intermediates, byte) {
+                    self.execute_ansi_command(cmd);
+                }
+            }
+            Action::Hook(params, _intermediates, _ignore, final_byte, private) => {
+                if let Some(cmd) = ansi::translate_dcs_hook(&params, final_byte, private) {
+                    self.execute_ansi_command(cmd);
+                }
+            }
+            Action::Put(_byte) => {
+                // Only a synchronized-update passthrough is recognized, and
+                // its payload carries no data of interest; just count the
+                // byte against the cap that bounds how long a malformed
+                // stream can hold rendering frozen.
+                if self.sync_tracker.record_byte() {
+                    self.execute_ansi_command(AnsiCommand::EndSynchronizedUpdate);
+                }
+            }
+            // Any DCS string (recognized or not) ends here; if a
+            // synchronized update was open, a missing/malformed end
+            // sequence must not leave it stuck active forever.
+            Action::Unhook => self.sync_tracker.end(),
+        }
+    }
+
+    /// Drive the synchronized-update timeout from a caller with its own
+    /// time source (e.g. a periodic scheduler tick), so a stream that
+    /// begins a synchronized update but never sends its end sequence
+    /// can't freeze rendering indefinitely.
+    pub fn poll_sync_timeout(&mut self, now_ns: u64) {
+        if self.sync_tracker.poll_timeout(now_ns) {
+            self.execute_ansi_command(AnsiCommand::EndSynchronizedUpdate);
+        }
+    }
+
+    /// Encode a mouse event for the program reading this TTY, honoring
+    /// whichever tracking/extended-coordinate mode it last enabled via
+    /// DECSET. Returns an empty `Vec` if mouse tracking isn't enabled.
+    pub fn encode_mouse_event(
+        &self,
+        button: ansi::MouseButton,
+        pressed: bool,
+        motion: bool,
+        row: usize,
+        col: usize,
+        modifiers: ansi::MouseModifiers,
+    ) -> Vec<u8> {
+        ansi::encode_mouse_event(
+            self.terminal_attrs.mouse_tracking_mode,
+            self.terminal_attrs.mouse_extended_mode,
+            button,
+            pressed,
+            motion,
+            row,
+            col,
+            modifiers,
+        )
+    }
+
+    fn push_output_byte(&mut self, byte: u8) {
+        if self.output_pos >= self.output_buffer.len() {
+            self.flush_output();
+        }
+        self.output_buffer[self.output_pos] = byte;
+        self.output_pos += 1;
+    }
+
     /// Flush output buffer to serial port
     pub fn flush_output(&mut self) {
         if self.output_pos > 0 {
@@ -144,22 +235,147 @@ impl TtyDevice {
         }
     }
 
+    /// Feed a DSR/DA report back in as if it had arrived from the far end,
+    /// so the program that asked the question reads it the same way it
+    /// would read any other input.
+    fn inject_report(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.line_discipline.receive_byte(byte);
+        }
+    }
+
     /// Process pending signals
     pub fn check_signal(&mut self) -> Option<Signal> {
         self.line_discipline.signal_pending()
     }
 
-    /// Execute ANSI command
+    /// Execute ANSI command, updating local `terminal_attrs` bookkeeping.
+    /// There's no host window system behind a serial TTY, so title/mouse
+    /// commands are recognized but have nothing further to do.
     fn execute_ansi_command(&mut self, cmd: AnsiCommand) {
         match cmd {
-            AnsiCommand::SetTitle(title) => {
+            AnsiCommand::SetTitle(_title) | AnsiCommand::SetIconName(_title) => {
                 // Terminal title is stored but would be displayed by the host terminal
             }
             AnsiCommand::SetCursorMode(visible) => {
                 self.terminal_attrs.cursor_visible = visible;
             }
+            AnsiCommand::CursorUp(n) => {
+                self.terminal_attrs.cursor_row = self.terminal_attrs.cursor_row.saturating_sub(n);
+            }
+            AnsiCommand::CursorDown(n) => {
+                self.terminal_attrs.cursor_row += n;
+            }
+            AnsiCommand::CursorForward(n) => {
+                self.terminal_attrs.cursor_col += n;
+            }
+            AnsiCommand::CursorBack(n) => {
+                self.terminal_attrs.cursor_col = self.terminal_attrs.cursor_col.saturating_sub(n);
+            }
+            AnsiCommand::CursorPosition(row, col) => {
+                self.terminal_attrs.cursor_row = row.saturating_sub(1);
+                self.terminal_attrs.cursor_col = col.saturating_sub(1);
+            }
+            AnsiCommand::CursorHome => {
+                self.terminal_attrs.cursor_row = 0;
+                self.terminal_attrs.cursor_col = 0;
+            }
+            AnsiCommand::SaveCursorPosition => {
+                self.terminal_attrs.saved_cursor_row = self.terminal_attrs.cursor_row;
+                self.terminal_attrs.saved_cursor_col = self.terminal_attrs.cursor_col;
+            }
+            AnsiCommand::RestoreCursorPosition => {
+                self.terminal_attrs.cursor_row = self.terminal_attrs.saved_cursor_row;
+                self.terminal_attrs.cursor_col = self.terminal_attrs.saved_cursor_col;
+            }
+            AnsiCommand::SetKeypadMode(application) => {
+                self.terminal_attrs.application_keypad = application;
+            }
+            AnsiCommand::EnableMouseTracking(mode) => self.terminal_attrs.mouse_tracking_mode = mode,
+            AnsiCommand::DisableMouseTracking => {
+                self.terminal_attrs.mouse_tracking_mode = ansi::MouseTrackingMode::None;
+            }
+            AnsiCommand::SetMouseExtendedMode(mode) => self.terminal_attrs.mouse_extended_mode = mode,
+            AnsiCommand::SetMode(mode) => self.set_mode(mode, true),
+            AnsiCommand::ResetMode(mode) => self.set_mode(mode, false),
+            AnsiCommand::SetGraphicsMode(attrs) => {
+                for attr in attrs {
+                    self.apply_graphics_attribute(attr);
+                }
+            }
+            AnsiCommand::ResetGraphicsMode => self.apply_graphics_attribute(GraphicsAttribute::Reset),
+            AnsiCommand::BeginSynchronizedUpdate => {
+                self.sync_tracker.begin(crate::clock::clock().now_ns());
+            }
+            AnsiCommand::EndSynchronizedUpdate => self.sync_tracker.end(),
+            AnsiCommand::DesignateCharset { slot, charset } => {
+                if slot == 0 {
+                    self.terminal_attrs.g0_charset = charset;
+                } else {
+                    self.terminal_attrs.g1_charset = charset;
+                }
+            }
+            AnsiCommand::Report(bytes) => self.inject_report(&bytes),
+            AnsiCommand::QueryCursorPosition => {
+                let ctx = ansi::ReportContext {
+                    cursor_row: self.terminal_attrs.cursor_row,
+                    cursor_col: self.terminal_attrs.cursor_col,
+                };
+                if let AnsiCommand::Report(bytes) = ansi::build_cursor_position_report(ctx) {
+                    self.inject_report(&bytes);
+                }
+            }
             _ => {
-                // Other commands are handled by the terminal emulator
+                // Scrolling/editing/tab/mouse commands have no local state
+                // to track on a plain serial TTY.
+            }
+        }
+    }
+
+    fn set_mode(&mut self, mode: ModeType, set: bool) {
+        match mode {
+            ModeType::AnsiMode => self.terminal_attrs.ansi_mode = set,
+            ModeType::OriginMode => self.terminal_attrs.origin_mode = set,
+            ModeType::AutoWrapMode => self.terminal_attrs.auto_wrap = set,
+            ModeType::InsertMode => self.terminal_attrs.insert_mode = set,
+            _ => {}
+        }
+    }
+
+    fn apply_graphics_attribute(&mut self, attr: GraphicsAttribute) {
+        match attr {
+            GraphicsAttribute::Reset => {
+                let cursor_visible = self.terminal_attrs.cursor_visible;
+                self.terminal_attrs = TerminalAttributes {
+                    cursor_visible,
+                    ..TerminalAttributes::default()
+                };
+            }
+            GraphicsAttribute::Bold => self.terminal_attrs.bold = true,
+            GraphicsAttribute::Underline | GraphicsAttribute::DoubleUnderline => {
+                self.terminal_attrs.underline = true;
+            }
+            GraphicsAttribute::Blink => self.terminal_attrs.blink = true,
+            GraphicsAttribute::Reverse => self.terminal_attrs.reverse = true,
+            GraphicsAttribute::Hidden => self.terminal_attrs.hidden = true,
+            GraphicsAttribute::Strikethrough => self.terminal_attrs.strikethrough = true,
+            GraphicsAttribute::Foreground(color) => self.terminal_attrs.foreground = color,
+            GraphicsAttribute::Background(color) => self.terminal_attrs.background = color,
+            GraphicsAttribute::Foreground256(index) => {
+                self.terminal_attrs.foreground = Color::Indexed(index as u8);
+            }
+            GraphicsAttribute::TrueColorForeground(r, g, b) => {
+                self.terminal_attrs.foreground = Color::Rgb(r, g, b);
+            }
+            GraphicsAttribute::Background256(index) => {
+                self.terminal_attrs.background = Color::Indexed(index as u8);
+            }
+            GraphicsAttribute::TrueColorBackground(r, g, b) => {
+                self.terminal_attrs.background = Color::Rgb(r, g, b);
+            }
+            GraphicsAttribute::Dim | GraphicsAttribute::Italic | GraphicsAttribute::Framed
+            | GraphicsAttribute::Encircled | GraphicsAttribute::Overline => {
+                // Not tracked in TerminalAttributes; nothing to record.
             }
         }
     }
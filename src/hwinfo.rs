@@ -24,6 +24,7 @@ pub struct HardwareInfo {
     pub framebuffer_height: u32,
     pub framebuffer_pitch: u32,
     pub framebuffer_format: u32,
+    pub framebuffer_bpp: u32,
 }
 
 impl HardwareInfo {
@@ -42,6 +43,7 @@ impl HardwareInfo {
             framebuffer_height: 0,
             framebuffer_pitch: 0,
             framebuffer_format: 0,
+            framebuffer_bpp: 0,
         }
     }
 }
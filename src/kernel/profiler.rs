@@ -26,11 +26,15 @@ pub fn disable() {
 #[macro_export]
 macro_rules! profile_start {
     ($name:expr) => {
-        #[cfg(feature = "profiling")]
-        {
-            if $crate::kernel::profiler::is_enabled() {
+        if $crate::kernel::profiler::is_enabled() {
+            #[cfg(feature = "profiling")]
+            {
                 devine_perf_cpp::start_timer($name);
             }
+            #[cfg(not(feature = "profiling"))]
+            {
+                $crate::kernel::profiler::backend::start_timer($name);
+            }
         }
     };
 }
@@ -38,11 +42,15 @@ macro_rules! profile_start {
 #[macro_export]
 macro_rules! profile_end {
     ($name:expr) => {
-        #[cfg(feature = "profiling")]
-        {
-            if $crate::kernel::profiler::is_enabled() {
+        if $crate::kernel::profiler::is_enabled() {
+            #[cfg(feature = "profiling")]
+            {
                 devine_perf_cpp::end_timer($name);
             }
+            #[cfg(not(feature = "profiling"))]
+            {
+                $crate::kernel::profiler::backend::end_timer($name);
+            }
         }
     };
 }
@@ -50,11 +58,15 @@ macro_rules! profile_end {
 #[macro_export]
 macro_rules! profile_count {
     ($name:expr) => {
-        #[cfg(feature = "profiling")]
-        {
-            if $crate::kernel::profiler::is_enabled() {
+        if $crate::kernel::profiler::is_enabled() {
+            #[cfg(feature = "profiling")]
+            {
                 devine_perf_cpp::increment_counter($name);
             }
+            #[cfg(not(feature = "profiling"))]
+            {
+                $crate::kernel::profiler::backend::increment_counter($name);
+            }
         }
     };
 }
@@ -100,10 +112,12 @@ impl<'a> Timer<'a> {
         if is_enabled() {
             #[cfg(feature = "profiling")]
             devine_perf_cpp::start_timer(name);
+            #[cfg(not(feature = "profiling"))]
+            backend::start_timer(name);
         }
         Timer { name, start }
     }
-    
+
     pub fn elapsed(&self) -> u64 {
         rdtsc() - self.start
     }
@@ -114,6 +128,172 @@ impl<'a> Drop for Timer<'a> {
         if is_enabled() {
             #[cfg(feature = "profiling")]
             devine_perf_cpp::end_timer(self.name);
+            #[cfg(not(feature = "profiling"))]
+            backend::end_timer(self.name);
+        }
+    }
+}
+
+/// Pure-Rust `no_std` profiler backend: a fixed-capacity, name-hashed
+/// stats table with per-name count/total/min/max/histogram, used by
+/// [`profile_start!`]/[`profile_end!`]/[`profile_count!`] and [`Timer`]
+/// whenever the `profiling` feature (the `devine_perf_cpp` FFI bridge)
+/// isn't enabled -- so profiling collects real data on bare ARM64/x86_64
+/// targets without that C++ dependency.
+pub mod backend {
+    use super::rdtsc;
+    use crate::lib::spinlock::Spinlock;
+
+    /// Entries this table can hold before new names are silently dropped
+    /// (an old-style fixed table, no allocator involved).
+    const MAX_ENTRIES: usize = 128;
+    /// Concurrently in-flight `start_timer`/`end_timer` pairs this table
+    /// can track; a `start_timer` beyond this capacity is dropped and its
+    /// matching `end_timer` becomes a no-op, same as a full `MAX_ENTRIES`.
+    const MAX_IN_FLIGHT: usize = 64;
+    /// One bucket per possible `leading_zeros` value of a `u64` cycle
+    /// delta, giving the histogram log2-scale buckets for free.
+    const HISTOGRAM_BUCKETS: usize = 64;
+
+    /// FNV-1a -- the usual choice for a name-keyed table when a full
+    /// hashmap isn't available.
+    fn hash_name(name: &[u8]) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for &byte in name {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01B3);
+        }
+        hash
+    }
+
+    /// Stats collected for one profiled name.
+    #[derive(Debug, Clone, Copy)]
+    pub struct ProfileEntry {
+        name_hash: u64,
+        pub count: u64,
+        pub total_cycles: u64,
+        pub min_cycles: u64,
+        pub max_cycles: u64,
+        /// Bucket `i` counts samples whose cycle delta has `63 - i`
+        /// leading zero bits, i.e. samples in `[2^i, 2^(i+1))` (bucket 0
+        /// also catches a delta of exactly 0).
+        pub histogram: [u32; HISTOGRAM_BUCKETS],
+    }
+
+    impl ProfileEntry {
+        const fn new(name_hash: u64) -> Self {
+            Self {
+                name_hash,
+                count: 0,
+                total_cycles: 0,
+                min_cycles: u64::MAX,
+                max_cycles: 0,
+                histogram: [0; HISTOGRAM_BUCKETS],
+            }
+        }
+
+        fn record(&mut self, cycles: u64) {
+            self.count += 1;
+            self.total_cycles = self.total_cycles.wrapping_add(cycles);
+            if cycles < self.min_cycles {
+                self.min_cycles = cycles;
+            }
+            if cycles > self.max_cycles {
+                self.max_cycles = cycles;
+            }
+            let bucket = if cycles == 0 {
+                0
+            } else {
+                (HISTOGRAM_BUCKETS - 1 - cycles.leading_zeros() as usize).min(HISTOGRAM_BUCKETS - 1)
+            };
+            self.histogram[bucket] += 1;
         }
     }
+
+    struct ProfileTable {
+        entries: [Option<ProfileEntry>; MAX_ENTRIES],
+    }
+
+    impl ProfileTable {
+        const fn new() -> Self {
+            Self {
+                entries: [None; MAX_ENTRIES],
+            }
+        }
+
+        /// Find `hash`'s entry, or claim the first free slot for it. `None`
+        /// if `hash` is new and the table is already full.
+        fn entry_mut(&mut self, hash: u64) -> Option<&mut ProfileEntry> {
+            if let Some(index) = self
+                .entries
+                .iter()
+                .position(|entry| matches!(entry, Some(e) if e.name_hash == hash))
+            {
+                return self.entries[index].as_mut();
+            }
+            let index = self.entries.iter().position(|entry| entry.is_none())?;
+            self.entries[index] = Some(ProfileEntry::new(hash));
+            self.entries[index].as_mut()
+        }
+    }
+
+    static PROFILE_TABLE: Spinlock<ProfileTable> = Spinlock::new(ProfileTable::new());
+    /// In-flight `(name_hash, start_cycles)` pairs recorded by
+    /// `start_timer`, consumed by the matching `end_timer`. A second
+    /// `start_timer` for the same name before its `end_timer` overwrites
+    /// the first's start time rather than stacking -- this backend tracks
+    /// one in-flight call per name, not a call stack.
+    static IN_FLIGHT: Spinlock<[Option<(u64, u64)>; MAX_IN_FLIGHT]> =
+        Spinlock::new([None; MAX_IN_FLIGHT]);
+
+    pub fn start_timer(name: &[u8]) {
+        let hash = hash_name(name);
+        let mut in_flight = IN_FLIGHT.lock();
+        if let Some(slot) = in_flight
+            .iter_mut()
+            .find(|slot| matches!(slot, Some((h, _)) if *h == hash))
+            .or_else(|| in_flight.iter_mut().find(|slot| slot.is_none()))
+        {
+            *slot = Some((hash, rdtsc()));
+        }
+    }
+
+    pub fn end_timer(name: &[u8]) {
+        let hash = hash_name(name);
+        let start = {
+            let mut in_flight = IN_FLIGHT.lock();
+            in_flight
+                .iter_mut()
+                .find(|slot| matches!(slot, Some((h, _)) if *h == hash))
+                .and_then(|slot| slot.take())
+                .map(|(_, start)| start)
+        };
+        if let Some(start) = start {
+            let elapsed = rdtsc().wrapping_sub(start);
+            if let Some(entry) = PROFILE_TABLE.lock().entry_mut(hash) {
+                entry.record(elapsed);
+            }
+        }
+    }
+
+    pub fn increment_counter(name: &[u8]) {
+        let hash = hash_name(name);
+        if let Some(entry) = PROFILE_TABLE.lock().entry_mut(hash) {
+            entry.count += 1;
+        }
+    }
+
+    /// Walk every collected [`ProfileEntry`].
+    pub fn report(visit: &mut dyn FnMut(&ProfileEntry)) {
+        let table = PROFILE_TABLE.lock();
+        for entry in table.entries.iter().flatten() {
+            visit(entry);
+        }
+    }
+
+    /// Clear every collected entry and in-flight timer.
+    pub fn reset() {
+        *PROFILE_TABLE.lock() = ProfileTable::new();
+        *IN_FLIGHT.lock() = [None; MAX_IN_FLIGHT];
+    }
 }
@@ -1,16 +1,28 @@
 #![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), feature(abi_x86_interrupt))]
 
 #[cfg(not(test))]
 extern crate alloc;
 
+#[macro_use]
+pub mod vga;
+
+pub mod clock;
+pub mod cmdline;
+pub mod coredump;
 pub mod memory;
 pub mod process;
 pub mod arch;
 pub mod syscall;
+pub mod scheme;
 pub mod security;
 pub mod userspace;
 pub mod hwinfo;
+pub mod cpuinfo;
+pub mod multiboot2;
+pub mod vm;
 pub mod drivers;
+pub mod lib_core;
 #[cfg(target_arch = "x86_64")]
 pub mod x86_64;
 #[cfg(target_arch = "aarch64")]
@@ -45,8 +57,13 @@ pub mod lib {
 
             pub fn lock(&self) -> SpinlockGuard<T> {
                 loop {
-                    // Try to acquire the lock
-                    match self.locked.compare_exchange(
+                    // Try to acquire the lock. `compare_exchange_weak` can
+                    // fail spuriously (no actual contention) on LL/SC
+                    // architectures like aarch64, which generates tighter
+                    // code than the strong form here since a spurious
+                    // failure is handled identically to a contended one:
+                    // just spin again.
+                    match self.locked.compare_exchange_weak(
                         false,
                         true,
                         Ordering::Acquire,
@@ -116,11 +133,21 @@ pub mod lib {
         unsafe impl<T: Send> Sync for Spinlock<T> {}
 
         /// CPU-level interrupt control using atomic operations
-        pub struct InterruptGuard;
+        ///
+        /// Remembers whether interrupts were already enabled when
+        /// `disable()` was called, so nesting one `InterruptGuard` inside
+        /// another doesn't re-enable interrupts out from under the outer
+        /// critical section when the inner one drops first.
+        pub struct InterruptGuard {
+            was_enabled: bool,
+        }
 
         impl InterruptGuard {
-            /// Disable interrupts (platform-specific)
+            /// Disable interrupts (platform-specific), remembering the
+            /// prior state so `Drop` only re-enables them if they were
+            /// actually enabled before this guard.
             pub fn disable() -> Self {
+                let was_enabled = Self::are_enabled();
                 #[cfg(target_arch = "x86_64")]
                 unsafe {
                     core::arch::asm!("cli", options(nomem, nostack));
@@ -129,7 +156,7 @@ pub mod lib {
                 unsafe {
                     core::arch::asm!("msr daifset, #2", options(nomem, nostack));
                 }
-                InterruptGuard
+                InterruptGuard { was_enabled }
             }
 
             /// Check if interrupts are enabled
@@ -153,6 +180,9 @@ pub mod lib {
 
         impl Drop for InterruptGuard {
             fn drop(&mut self) {
+                if !self.was_enabled {
+                    return;
+                }
                 #[cfg(target_arch = "x86_64")]
                 unsafe {
                     core::arch::asm!("sti", options(nomem, nostack));
@@ -163,6 +193,115 @@ pub mod lib {
                 }
             }
         }
+
+        /// A `SpinlockGuard` taken with interrupts disabled for its whole
+        /// lifetime, so a driver can safely take the lock from a context
+        /// that might otherwise race an interrupt handler on the same
+        /// core. Fields are ordered so `guard` drops (unlocking) before
+        /// `_irq` restores the prior interrupt state.
+        pub struct IrqSafeGuard<'a, T> {
+            guard: SpinlockGuard<'a, T>,
+            _irq: InterruptGuard,
+        }
+
+        impl<T> Deref for IrqSafeGuard<'_, T> {
+            type Target = T;
+
+            fn deref(&self) -> &T {
+                &self.guard
+            }
+        }
+
+        impl<T> DerefMut for IrqSafeGuard<'_, T> {
+            fn deref_mut(&mut self) -> &mut T {
+                &mut self.guard
+            }
+        }
+
+        impl<T> Spinlock<T> {
+            /// Disable interrupts, then take the lock, returning a guard
+            /// that restores both on drop (lock first, then interrupts).
+            pub fn lock_irqsave(&self) -> IrqSafeGuard<T> {
+                let irq = InterruptGuard::disable();
+                let guard = self.lock();
+                IrqSafeGuard { guard, _irq: irq }
+            }
+        }
+
+        use core::sync::atomic::AtomicU64;
+
+        /// FIFO-fair spinlock: waiters draw a monotonically increasing
+        /// ticket and spin on `now_serving` until it's their turn, so under
+        /// contention a core can't be starved indefinitely the way a plain
+        /// `Spinlock`'s CAS race can starve it. Release just increments
+        /// `now_serving`, handing the lock to whoever drew the next ticket.
+        pub struct TicketSpinlock<T> {
+            next_ticket: AtomicU64,
+            now_serving: AtomicU64,
+            data: UnsafeCell<T>,
+        }
+
+        pub struct TicketSpinlockGuard<'a, T> {
+            lock: &'a TicketSpinlock<T>,
+        }
+
+        impl<T> TicketSpinlock<T> {
+            pub const fn new(data: T) -> Self {
+                TicketSpinlock {
+                    next_ticket: AtomicU64::new(0),
+                    now_serving: AtomicU64::new(0),
+                    data: UnsafeCell::new(data),
+                }
+            }
+
+            pub fn lock(&self) -> TicketSpinlockGuard<T> {
+                let my_ticket = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+                while self.now_serving.load(Ordering::Acquire) != my_ticket {
+                    #[cfg(target_arch = "x86_64")]
+                    unsafe {
+                        core::arch::asm!("pause", options(nomem, nostack));
+                    }
+                    #[cfg(target_arch = "aarch64")]
+                    unsafe {
+                        core::arch::asm!("yield", options(nomem, nostack));
+                    }
+                }
+                TicketSpinlockGuard { lock: self }
+            }
+
+            pub fn get_mut(&mut self) -> &mut T {
+                unsafe { &mut *self.data.get() }
+            }
+        }
+
+        impl<T> Deref for TicketSpinlockGuard<'_, T> {
+            type Target = T;
+
+            fn deref(&self) -> &T {
+                unsafe { &*self.lock.data.get() }
+            }
+        }
+
+        impl<T> DerefMut for TicketSpinlockGuard<'_, T> {
+            fn deref_mut(&mut self) -> &mut T {
+                unsafe { &mut *self.lock.data.get() }
+            }
+        }
+
+        impl<T> Drop for TicketSpinlockGuard<'_, T> {
+            fn drop(&mut self) {
+                self.lock.now_serving.fetch_add(1, Ordering::Release);
+            }
+        }
+
+        impl<T: fmt::Debug> fmt::Debug for TicketSpinlock<T> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "TicketSpinlock {{ ... }}")
+            }
+        }
+
+        unsafe impl<T: Send> Send for TicketSpinlock<T> {}
+        unsafe impl<T: Send> Sync for TicketSpinlock<T> {}
     }
 }
 
@@ -174,6 +313,7 @@ use core::panic::PanicInfo;
 #[cfg(not(test))]
 #[panic_handler]
 fn panic(_info: &PanicInfo) -> ! {
+    coredump::dump_on_panic();
     loop {}
 }
 
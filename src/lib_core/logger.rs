@@ -0,0 +1,263 @@
+//! Buffered ring-logger, drained into a real sink once one exists.
+//!
+//! There's no external `log` crate vendored in this tree (no `Cargo.toml`
+//! declares it), so this defines the same small shape locally instead of
+//! `impl log::Log`: a [`Level`], a [`BufferLogger`], and `log_*!` macros
+//! mirroring `log`'s `info!`/`warn!`/etc. Records pushed before
+//! [`attach_sink`] runs are retained in a fixed-capacity ring rather than
+//! dropped, then replayed in order once a sink (serial, the VGA
+//! [`crate::vga::Writer`]) is attached -- so early-boot diagnostics
+//! survive driver init instead of vanishing before anything could print
+//! them. The ring keeps the most recent [`RING_CAPACITY`] lines around
+//! afterwards too, for a future `dmesg`-style reader via
+//! [`for_each_recent`].
+
+use crate::drivers::serial::SERIAL1;
+use crate::lib::spinlock::Spinlock;
+use crate::vga;
+use core::fmt::{self, Write as _};
+
+pub const RING_CAPACITY: usize = 64;
+pub const MAX_LINE_LEN: usize = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+/// One retained log line, truncated to `MAX_LINE_LEN` bytes so the ring
+/// never allocates.
+#[derive(Clone, Copy)]
+struct Record {
+    level: Level,
+    len: usize,
+    line: [u8; MAX_LINE_LEN],
+}
+
+impl Record {
+    fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.line[..self.len]).unwrap_or("")
+    }
+}
+
+/// Formats directly into a `Record`'s fixed `line` buffer, silently
+/// truncating at `MAX_LINE_LEN` rather than allocating or erroring.
+struct RecordWriter<'a> {
+    buf: &'a mut [u8; MAX_LINE_LEN],
+    len: usize,
+}
+
+impl fmt::Write for RecordWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            if self.len >= MAX_LINE_LEN {
+                break;
+            }
+            self.buf[self.len] = byte;
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Fixed-capacity circular buffer of the most recently retained records,
+/// oldest entries overwritten once full.
+struct Ring {
+    records: [Option<Record>; RING_CAPACITY],
+    next: usize,
+    len: usize,
+}
+
+impl Ring {
+    const fn new() -> Self {
+        Ring {
+            records: [None; RING_CAPACITY],
+            next: 0,
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, record: Record) {
+        self.records[self.next] = Some(record);
+        self.next = (self.next + 1) % RING_CAPACITY;
+        if self.len < RING_CAPACITY {
+            self.len += 1;
+        }
+    }
+
+    /// Visit retained records oldest-first.
+    fn for_each(&self, mut f: impl FnMut(&Record)) {
+        let start = if self.len < RING_CAPACITY {
+            0
+        } else {
+            self.next
+        };
+        for i in 0..self.len {
+            if let Some(record) = &self.records[(start + i) % RING_CAPACITY] {
+                f(record);
+            }
+        }
+    }
+}
+
+/// Where drained/live records go once a sink has been attached.
+pub trait LogSink: Sync {
+    fn write_line(&self, level: Level, line: &str);
+}
+
+/// The real sink: every line goes to both the serial port and the VGA
+/// text console, matching how `println!`/the gdbstub already split their
+/// output across the two.
+pub struct ConsoleSink;
+
+impl LogSink for ConsoleSink {
+    fn write_line(&self, level: Level, line: &str) {
+        let _ = write!(vga::WRITER.lock(), "[{}] {}\n", level.as_str(), line);
+
+        let mut serial = SERIAL1.lock();
+        for byte in level.as_str().bytes() {
+            serial.send(byte);
+        }
+        serial.send(b' ');
+        for byte in line.bytes() {
+            serial.send(byte);
+        }
+        serial.send(b'\n');
+    }
+}
+
+pub static CONSOLE_SINK: ConsoleSink = ConsoleSink;
+
+struct LoggerState {
+    ring: Ring,
+    sink: Option<&'static dyn LogSink>,
+}
+
+pub struct BufferLogger {
+    state: Spinlock<LoggerState>,
+}
+
+impl BufferLogger {
+    const fn new() -> Self {
+        BufferLogger {
+            state: Spinlock::new(LoggerState {
+                ring: Ring::new(),
+                sink: None,
+            }),
+        }
+    }
+
+    fn log(&self, level: Level, args: fmt::Arguments) {
+        let mut record = Record {
+            level,
+            len: 0,
+            line: [0u8; MAX_LINE_LEN],
+        };
+        {
+            let mut writer = RecordWriter {
+                buf: &mut record.line,
+                len: 0,
+            };
+            let _ = fmt::write(&mut writer, args);
+            record.len = writer.len;
+        }
+
+        let mut state = self.state.lock();
+        match state.sink {
+            Some(sink) => sink.write_line(record.level, record.as_str()),
+            None => state.ring.push(record),
+        }
+    }
+
+    /// Flush the retained ring into `sink` in order, then switch to
+    /// pass-through so every later record goes straight to `sink` instead
+    /// of the ring.
+    fn attach(&self, sink: &'static dyn LogSink) {
+        let mut state = self.state.lock();
+        state.ring.for_each(|record| {
+            sink.write_line(record.level, record.as_str());
+        });
+        state.ring = Ring::new();
+        state.sink = Some(sink);
+    }
+
+    fn for_each_recent(&self, mut f: impl FnMut(Level, &str)) {
+        self.state.lock().ring.for_each(|record| {
+            f(record.level, record.as_str());
+        });
+    }
+}
+
+static LOGGER: BufferLogger = BufferLogger::new();
+
+/// Log `args` at `level`, buffering into the ring if no sink has been
+/// attached yet. Called by the `log_*!` macros -- use those rather than
+/// this directly.
+pub fn log(level: Level, args: fmt::Arguments) {
+    LOGGER.log(level, args);
+}
+
+/// Replay the retained ring into `sink`, then route all later records
+/// there directly. Call once, after the sink (serial, VGA) is actually
+/// ready to receive output.
+pub fn attach_sink(sink: &'static dyn LogSink) {
+    LOGGER.attach(sink);
+}
+
+/// Visit the most recently retained lines, oldest first -- the backing
+/// store for a future `dmesg`-style reader. Once a sink is attached the
+/// ring is cleared, so this only has anything to show before that point.
+pub fn for_each_recent(f: impl FnMut(Level, &str)) {
+    LOGGER.for_each_recent(f);
+}
+
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        $crate::lib_core::logger::log($crate::lib_core::logger::Level::Error, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        $crate::lib_core::logger::log($crate::lib_core::logger::Level::Warn, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        $crate::lib_core::logger::log($crate::lib_core::logger::Level::Info, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        $crate::lib_core::logger::log($crate::lib_core::logger::Level::Debug, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! log_trace {
+    ($($arg:tt)*) => {
+        $crate::lib_core::logger::log($crate::lib_core::logger::Level::Trace, format_args!($($arg)*))
+    };
+}
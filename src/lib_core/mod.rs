@@ -0,0 +1,7 @@
+//! Freestanding building blocks shared across subsystems, kept separate
+//! from the inline `lib` module in `lib.rs` so they can grow into their
+//! own files/submodules (starting with the VT100/ANSI terminal emulator).
+
+pub mod logger;
+pub mod time;
+pub mod vt;
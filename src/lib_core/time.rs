@@ -0,0 +1,87 @@
+//! Monotonic time and a deadline-queue sleep primitive, so coreutils like
+//! `sleep` can block without busy-waiting.
+//!
+//! `now_ns()` is just [`crate::clock::clock`]'s already TSC-calibrated (or,
+//! on aarch64, generic-timer-backed) monotonic clock -- the same counter
+//! the `devine-perf` bridge in [`crate::kernel::profiler::rdtsc`] reads on
+//! x86_64, so there's no second calibration pass to keep in sync with the
+//! scheduler's. What's new here is [`sleep_until_ns`]: a min-ordered queue
+//! of pending deadlines that the periodic timer tick drains, waking any
+//! sleeper whose deadline has passed.
+
+use crate::clock;
+use crate::lib::spinlock::Spinlock;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Nanoseconds elapsed since the active `ClockSource` was calibrated at
+/// boot.
+pub fn now_ns() -> u64 {
+    clock::clock().now_ns()
+}
+
+struct Waiter {
+    deadline_ns: u64,
+    woken: Arc<AtomicBool>,
+}
+
+/// Pending sleepers, kept sorted by `deadline_ns` ascending so the
+/// earliest deadline is always at the front -- the "min-ordered queue"
+/// [`on_timer_tick`] drains from.
+static PENDING: Spinlock<Vec<Waiter>> = Spinlock::new(Vec::new());
+
+/// Block the calling CPU until `deadline_ns` (per [`now_ns`]) passes.
+/// Returns immediately if the deadline has already passed.
+pub fn sleep_until_ns(deadline_ns: u64) {
+    if deadline_ns <= now_ns() {
+        return;
+    }
+
+    let woken = Arc::new(AtomicBool::new(false));
+    {
+        let mut pending = PENDING.lock();
+        let pos = pending
+            .iter()
+            .position(|w| w.deadline_ns > deadline_ns)
+            .unwrap_or(pending.len());
+        pending.insert(
+            pos,
+            Waiter {
+                deadline_ns,
+                woken: woken.clone(),
+            },
+        );
+    }
+
+    while !woken.load(Ordering::Acquire) {
+        #[cfg(target_arch = "x86_64")]
+        unsafe {
+            core::arch::asm!("sti", "hlt", options(nomem, nostack));
+        }
+        #[cfg(target_arch = "aarch64")]
+        unsafe {
+            core::arch::asm!("wfi", options(nomem, nostack));
+        }
+    }
+}
+
+/// Block the calling CPU for `duration_ns` nanoseconds from now.
+pub fn sleep_ns(duration_ns: u64) {
+    sleep_until_ns(now_ns().saturating_add(duration_ns));
+}
+
+/// Wake every waiter whose deadline has passed. Called from the periodic
+/// timer interrupt.
+pub fn on_timer_tick() {
+    let now = now_ns();
+    let mut pending = PENDING.lock();
+
+    let split = pending
+        .iter()
+        .position(|w| w.deadline_ns > now)
+        .unwrap_or(pending.len());
+    for waiter in pending.drain(..split) {
+        waiter.woken.store(true, Ordering::Release);
+    }
+}
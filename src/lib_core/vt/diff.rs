@@ -0,0 +1,193 @@
+//! Minimal ANSI diff encoder: turns the delta between two `ScreenBuffer`
+//! contents into the smallest byte stream that repaints a real terminal
+//! (serial console, VGA-to-terminal bridge, ...) without redrawing
+//! everything on every frame.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::screen::{Attr, Cell, Color, ScreenBuffer};
+
+/// Tracks what a remote terminal is assumed to currently show, so
+/// [`DiffEncoder::encode`] only has to emit the bytes needed to bring it
+/// in sync with a `ScreenBuffer`.
+pub struct DiffEncoder {
+    width: usize,
+    height: usize,
+    snapshot: Vec<Cell>,
+    last_attr: Attr,
+}
+
+impl DiffEncoder {
+    pub fn new(width: usize, height: usize) -> Self {
+        DiffEncoder {
+            width,
+            height,
+            snapshot: vec![Cell::default(); width * height],
+            last_attr: Attr::default(),
+        }
+    }
+
+    /// Diff `buffer` against the tracked snapshot and return the ANSI byte
+    /// stream needed to bring a remote terminal up to date, updating the
+    /// snapshot (and the assumed on-wire SGR state) to match.
+    pub fn encode(&mut self, buffer: &ScreenBuffer) -> Vec<u8> {
+        if buffer.width != self.width || buffer.height != self.height {
+            self.width = buffer.width;
+            self.height = buffer.height;
+            self.snapshot = vec![Cell::default(); self.width * self.height];
+        }
+
+        let mut out = Vec::new();
+        for y in 0..self.height {
+            let mut run_open = false;
+            for x in 0..self.width {
+                let idx = y * self.width + x;
+                if self.snapshot[idx] == buffer.buffer[idx] {
+                    run_open = false;
+                    continue;
+                }
+
+                let cell = buffer.buffer[idx];
+                if !run_open {
+                    push_cursor_move(&mut out, x, y);
+                    run_open = true;
+                }
+                // The lead cell's glyph already occupies both columns on a
+                // wcwidth-aware terminal; the continuation cell is a hole
+                // in our own buffer, not a second character to print.
+                if !cell.is_continuation() {
+                    self.push_sgr_diff(&mut out, cell.attr);
+                    push_utf8(&mut out, cell.char);
+                }
+                self.snapshot[idx] = cell;
+            }
+        }
+        out
+    }
+
+    fn push_sgr_diff(&mut self, out: &mut Vec<u8>, attr: Attr) {
+        if attr == self.last_attr {
+            return;
+        }
+        if attr == Attr::default() {
+            out.extend_from_slice(b"\x1b[m");
+            self.last_attr = attr;
+            return;
+        }
+
+        let mut params: Vec<Vec<u8>> = Vec::new();
+        if attr.bold != self.last_attr.bold {
+            params.push(if attr.bold { b"1".to_vec() } else { b"22".to_vec() });
+        }
+        if attr.underline != self.last_attr.underline {
+            params.push(if attr.underline { b"4".to_vec() } else { b"24".to_vec() });
+        }
+        if attr.blink != self.last_attr.blink {
+            params.push(if attr.blink { b"5".to_vec() } else { b"25".to_vec() });
+        }
+        if attr.reverse != self.last_attr.reverse {
+            params.push(if attr.reverse { b"7".to_vec() } else { b"27".to_vec() });
+        }
+        if attr.fg != self.last_attr.fg {
+            params.push(color_params(attr.fg, Attr::default().fg, true));
+        }
+        if attr.bg != self.last_attr.bg {
+            params.push(color_params(attr.bg, Attr::default().bg, false));
+        }
+
+        out.extend_from_slice(b"\x1b[");
+        for (i, p) in params.iter().enumerate() {
+            if i > 0 {
+                out.push(b';');
+            }
+            out.extend_from_slice(p);
+        }
+        out.push(b'm');
+        self.last_attr = attr;
+    }
+}
+
+/// Encode the SGR parameter(s) for setting `color` as fg/bg: `39`/`49` if
+/// it's the default, otherwise `38;5;n`/`48;5;n` (indexed) or
+/// `38;2;r;g;b`/`48;2;r;g;b` (RGB).
+fn color_params(color: Color, default: Color, is_fg: bool) -> Vec<u8> {
+    if color == default {
+        return if is_fg { b"39".to_vec() } else { b"49".to_vec() };
+    }
+    match color {
+        Color::Indexed(n) => {
+            let mut p = if is_fg { b"38;5;".to_vec() } else { b"48;5;".to_vec() };
+            push_decimal(&mut p, n as usize);
+            p
+        }
+        Color::RGB(r, g, b) => {
+            let mut p = if is_fg { b"38;2;".to_vec() } else { b"48;2;".to_vec() };
+            push_decimal(&mut p, r as usize);
+            p.push(b';');
+            push_decimal(&mut p, g as usize);
+            p.push(b';');
+            push_decimal(&mut p, b as usize);
+            p
+        }
+    }
+}
+
+fn push_cursor_move(out: &mut Vec<u8>, x: usize, y: usize) {
+    out.extend_from_slice(b"\x1b[");
+    push_decimal(out, y + 1);
+    out.push(b';');
+    push_decimal(out, x + 1);
+    out.push(b'H');
+}
+
+fn push_utf8(out: &mut Vec<u8>, c: char) {
+    let mut buf = [0u8; 4];
+    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+}
+
+fn push_decimal(out: &mut Vec<u8>, mut n: usize) {
+    if n == 0 {
+        out.push(b'0');
+        return;
+    }
+    let start = out.len();
+    while n > 0 {
+        out.push(b'0' + (n % 10) as u8);
+        n /= 10;
+    }
+    out[start..].reverse();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_change_is_empty() {
+        let mut enc = DiffEncoder::new(4, 2);
+        let buf = ScreenBuffer::new(4, 2);
+        assert_eq!(enc.encode(&buf), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_single_cell_change_moves_and_prints() {
+        let mut enc = DiffEncoder::new(4, 2);
+        let mut buf = ScreenBuffer::new(4, 2);
+        buf.write_char('A');
+        let out = enc.encode(&buf);
+        assert_eq!(out, b"\x1b[1;1HA".to_vec());
+        // Re-encoding an unchanged buffer against the updated snapshot is a no-op.
+        assert_eq!(enc.encode(&buf), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_adjacent_changes_coalesce_one_move() {
+        let mut enc = DiffEncoder::new(4, 2);
+        let mut buf = ScreenBuffer::new(4, 2);
+        buf.write_char('A');
+        buf.write_char('B');
+        let out = enc.encode(&buf);
+        assert_eq!(out, b"\x1b[1;1HAB".to_vec());
+    }
+}
@@ -0,0 +1,316 @@
+//! Linear-framebuffer `TerminalDriver`: blits `ScreenBuffer` cells as pixels
+//! with an embedded 8x16 bitmap font, giving the kernel an on-screen console
+//! instead of serial-only output. Damage tracking mirrors
+//! [`super::diff::DiffEncoder`]'s snapshot-compare approach, just against a
+//! per-cell shadow buffer instead of an outgoing ANSI byte stream.
+
+use super::screen::{Attr, Cell, Color, CursorStyle};
+use super::TerminalDriver;
+use crate::hwinfo::HardwareInfo;
+use alloc::vec;
+use alloc::vec::Vec;
+
+pub const CELL_WIDTH: usize = 8;
+pub const CELL_HEIGHT: usize = 16;
+
+/// Maps a linear framebuffer (as described by a [`HardwareInfo`]) and
+/// renders `ScreenBuffer` cells onto it. Only `framebuffer_format == 1`
+/// (direct RGB, what a Multiboot2 bootloader hands off) with at least 3
+/// bytes per pixel is understood; anything else leaves every draw a no-op
+/// rather than misinterpreting pixel data it doesn't know the layout of.
+pub struct FramebufferDriver {
+    base: *mut u8,
+    width: usize,
+    height: usize,
+    pitch: usize,
+    bytes_per_pixel: usize,
+    supported: bool,
+    cols: usize,
+    rows: usize,
+    shadow: Vec<Cell>,
+}
+
+// The framebuffer is a fixed MMIO region mapped for the life of the
+// kernel; writes only ever go through `put_pixel`'s bounds-checked offset.
+unsafe impl Send for FramebufferDriver {}
+
+impl FramebufferDriver {
+    /// # Safety
+    /// `info.framebuffer_addr` must already be mapped, writable, and valid
+    /// for the lifetime of the returned driver. This does not map memory
+    /// itself -- the caller is expected to have mapped the physical range
+    /// first, the same way other MMIO regions in this tree are mapped
+    /// before use.
+    pub unsafe fn new(info: &HardwareInfo) -> Self {
+        let bytes_per_pixel = ((info.framebuffer_bpp as usize) + 7) / 8;
+        let width = info.framebuffer_width as usize;
+        let height = info.framebuffer_height as usize;
+        let supported =
+            info.framebuffer_addr != 0 && info.framebuffer_format == 1 && bytes_per_pixel >= 3;
+        let cols = width / CELL_WIDTH;
+        let rows = height / CELL_HEIGHT;
+
+        FramebufferDriver {
+            base: info.framebuffer_addr as *mut u8,
+            width,
+            height,
+            pitch: info.framebuffer_pitch as usize,
+            bytes_per_pixel,
+            supported,
+            cols,
+            rows,
+            shadow: vec![Cell::default(); cols * rows],
+        }
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn put_pixel(&mut self, x: usize, y: usize, rgb: (u8, u8, u8)) {
+        if !self.supported || x >= self.width || y >= self.height {
+            return;
+        }
+        let offset = y * self.pitch + x * self.bytes_per_pixel;
+        unsafe {
+            let p = self.base.add(offset);
+            // Multiboot2 framebuffers are conventionally packed BGR(A), not
+            // RGB -- store the low byte first to match.
+            p.write_volatile(rgb.2);
+            p.add(1).write_volatile(rgb.1);
+            p.add(2).write_volatile(rgb.0);
+        }
+    }
+
+    fn fill_cell(&mut self, x: usize, y: usize, rgb: (u8, u8, u8)) {
+        let origin_x = x * CELL_WIDTH;
+        let origin_y = y * CELL_HEIGHT;
+        for row in 0..CELL_HEIGHT {
+            for col in 0..CELL_WIDTH {
+                self.put_pixel(origin_x + col, origin_y + row, rgb);
+            }
+        }
+    }
+
+    fn paint_glyph(&mut self, x: usize, y: usize, cell: Cell) {
+        let (fg0, bg0) = (resolve_color(cell.attr.fg), resolve_color(cell.attr.bg));
+        let (fg, bg) = if cell.attr.reverse { (bg0, fg0) } else { (fg0, bg0) };
+        let glyph = glyph_bits(cell.char);
+        let origin_x = x * CELL_WIDTH;
+        let origin_y = y * CELL_HEIGHT;
+
+        for row in 0..CELL_HEIGHT {
+            // The embedded font is 8 rows tall; center it in the 16-pixel
+            // cell rather than stretching it to fill every scanline.
+            let font_row = if (4..12).contains(&row) {
+                Some(glyph[row - 4])
+            } else {
+                None
+            };
+            let underline = cell.attr.underline && row == CELL_HEIGHT - 2;
+
+            for col in 0..CELL_WIDTH {
+                let mut lit = font_row
+                    .map(|bits| bits & (0x80 >> col) != 0)
+                    .unwrap_or(false);
+                // Faux bold: also light the pixel to the right of a lit one.
+                if cell.attr.bold && !lit && col > 0 {
+                    if let Some(bits) = font_row {
+                        lit = bits & (0x80 >> (col - 1)) != 0;
+                    }
+                }
+                let color = if underline || lit { fg } else { bg };
+                self.put_pixel(origin_x + col, origin_y + row, color);
+            }
+        }
+    }
+}
+
+impl TerminalDriver for FramebufferDriver {
+    fn draw_cell(&mut self, x: usize, y: usize, cell: Cell) {
+        if x >= self.cols || y >= self.rows {
+            return;
+        }
+        let idx = y * self.cols + x;
+        if self.shadow.get(idx) == Some(&cell) {
+            return;
+        }
+        self.paint_glyph(x, y, cell);
+        self.shadow[idx] = cell;
+    }
+
+    fn move_cursor(&mut self, x: usize, y: usize, style: CursorStyle) {
+        if x >= self.cols || y >= self.rows {
+            return;
+        }
+        let fg = resolve_color(Attr::default().fg);
+        let origin_x = x * CELL_WIDTH;
+        let origin_y = y * CELL_HEIGHT;
+
+        match style {
+            CursorStyle::Underline => {
+                for col in 0..CELL_WIDTH {
+                    self.put_pixel(origin_x + col, origin_y + CELL_HEIGHT - 1, fg);
+                }
+            }
+            CursorStyle::Beam => {
+                for row in 0..CELL_HEIGHT {
+                    self.put_pixel(origin_x, origin_y + row, fg);
+                }
+            }
+            CursorStyle::Block | CursorStyle::HollowBlock => {
+                for row in 0..CELL_HEIGHT {
+                    for col in 0..CELL_WIDTH {
+                        self.put_pixel(origin_x + col, origin_y + row, fg);
+                    }
+                }
+            }
+        }
+    }
+
+    fn clear_screen(&mut self) {
+        let bg = resolve_color(Attr::default().bg);
+        for y in 0..self.rows {
+            for x in 0..self.cols {
+                self.fill_cell(x, y, bg);
+            }
+        }
+        for cell in self.shadow.iter_mut() {
+            *cell = Cell::default();
+        }
+    }
+
+    fn set_title(&mut self, _title: &str) {
+        // A framebuffer console has no window chrome to set a title on.
+    }
+}
+
+/// Resolve a VT `Color` to 24-bit RGB: direct for `RGB`, through the
+/// standard xterm 256-color table for `Indexed`.
+fn resolve_color(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::RGB(r, g, b) => (r, g, b),
+        Color::Indexed(i) => xterm_256_to_rgb(i),
+    }
+}
+
+/// The standard xterm palette: 16 named ANSI colors, a 6x6x6 RGB color
+/// cube for indices 16-231, and a 24-step grayscale ramp for 232-255.
+fn xterm_256_to_rgb(index: u8) -> (u8, u8, u8) {
+    const BASE16: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+
+    match index {
+        0..=15 => BASE16[index as usize],
+        16..=231 => {
+            let i = index - 16;
+            let r = i / 36;
+            let g = (i % 36) / 6;
+            let b = i % 6;
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            (level(r), level(g), level(b))
+        }
+        232..=255 => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+/// 8-row bitmap glyphs for the characters a kernel console mostly prints:
+/// space, digits, uppercase letters, and common punctuation. Lowercase
+/// letters reuse their uppercase glyph (there's no room here for a full
+/// upper/lower font); anything else not covered below renders as a blank
+/// cell rather than guessing. Bits run MSB-to-LSB across the 8 columns;
+/// `paint_glyph` renders these into the middle 8 rows of each 16-pixel cell.
+fn glyph_bits(c: char) -> [u8; 8] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '0' => [0x3C, 0x66, 0x6E, 0x76, 0x66, 0x66, 0x3C, 0x00],
+        '1' => [0x18, 0x38, 0x18, 0x18, 0x18, 0x18, 0x7E, 0x00],
+        '2' => [0x3C, 0x66, 0x06, 0x0C, 0x30, 0x60, 0x7E, 0x00],
+        '3' => [0x3C, 0x66, 0x06, 0x1C, 0x06, 0x66, 0x3C, 0x00],
+        '4' => [0x0C, 0x1C, 0x3C, 0x6C, 0x7E, 0x0C, 0x0C, 0x00],
+        '5' => [0x7E, 0x60, 0x7C, 0x06, 0x06, 0x66, 0x3C, 0x00],
+        '6' => [0x3C, 0x66, 0x60, 0x7C, 0x66, 0x66, 0x3C, 0x00],
+        '7' => [0x7E, 0x06, 0x0C, 0x18, 0x30, 0x30, 0x30, 0x00],
+        '8' => [0x3C, 0x66, 0x66, 0x3C, 0x66, 0x66, 0x3C, 0x00],
+        '9' => [0x3C, 0x66, 0x66, 0x3E, 0x06, 0x66, 0x3C, 0x00],
+        'A' => [0x18, 0x3C, 0x66, 0x66, 0x7E, 0x66, 0x66, 0x00],
+        'B' => [0x7C, 0x66, 0x66, 0x7C, 0x66, 0x66, 0x7C, 0x00],
+        'C' => [0x3C, 0x66, 0x60, 0x60, 0x60, 0x66, 0x3C, 0x00],
+        'D' => [0x78, 0x6C, 0x66, 0x66, 0x66, 0x6C, 0x78, 0x00],
+        'E' => [0x7E, 0x60, 0x60, 0x78, 0x60, 0x60, 0x7E, 0x00],
+        'F' => [0x7E, 0x60, 0x60, 0x78, 0x60, 0x60, 0x60, 0x00],
+        'G' => [0x3C, 0x66, 0x60, 0x6E, 0x66, 0x66, 0x3C, 0x00],
+        'H' => [0x66, 0x66, 0x66, 0x7E, 0x66, 0x66, 0x66, 0x00],
+        'I' => [0x7E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x7E, 0x00],
+        'J' => [0x06, 0x06, 0x06, 0x06, 0x06, 0x66, 0x3C, 0x00],
+        'K' => [0x66, 0x6C, 0x78, 0x70, 0x78, 0x6C, 0x66, 0x00],
+        'L' => [0x60, 0x60, 0x60, 0x60, 0x60, 0x60, 0x7E, 0x00],
+        'M' => [0x63, 0x77, 0x7F, 0x6B, 0x63, 0x63, 0x63, 0x00],
+        'N' => [0x66, 0x76, 0x7E, 0x7E, 0x6E, 0x66, 0x66, 0x00],
+        'O' => [0x3C, 0x66, 0x66, 0x66, 0x66, 0x66, 0x3C, 0x00],
+        'P' => [0x7C, 0x66, 0x66, 0x7C, 0x60, 0x60, 0x60, 0x00],
+        'Q' => [0x3C, 0x66, 0x66, 0x66, 0x6A, 0x6C, 0x36, 0x00],
+        'R' => [0x7C, 0x66, 0x66, 0x7C, 0x78, 0x6C, 0x66, 0x00],
+        'S' => [0x3C, 0x66, 0x60, 0x3C, 0x06, 0x66, 0x3C, 0x00],
+        'T' => [0x7E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00],
+        'U' => [0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x3C, 0x00],
+        'V' => [0x66, 0x66, 0x66, 0x66, 0x66, 0x3C, 0x18, 0x00],
+        'W' => [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00],
+        'X' => [0x66, 0x66, 0x3C, 0x18, 0x3C, 0x66, 0x66, 0x00],
+        'Y' => [0x66, 0x66, 0x66, 0x3C, 0x18, 0x18, 0x18, 0x00],
+        'Z' => [0x7E, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x7E, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x30],
+        ':' => [0x00, 0x18, 0x18, 0x00, 0x18, 0x18, 0x00, 0x00],
+        ';' => [0x00, 0x18, 0x18, 0x00, 0x18, 0x18, 0x30, 0x00],
+        '!' => [0x18, 0x18, 0x18, 0x18, 0x18, 0x00, 0x18, 0x00],
+        '?' => [0x3C, 0x66, 0x06, 0x0C, 0x18, 0x00, 0x18, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x7E, 0x00, 0x00, 0x00, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7E, 0x00],
+        '/' => [0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x40, 0x00],
+        '\\' => [0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00],
+        '(' => [0x0C, 0x18, 0x30, 0x30, 0x30, 0x18, 0x0C, 0x00],
+        ')' => [0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x18, 0x30, 0x00],
+        '\'' => [0x18, 0x18, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '"' => [0x66, 0x66, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '=' => [0x00, 0x00, 0x7E, 0x00, 0x7E, 0x00, 0x00, 0x00],
+        '+' => [0x00, 0x18, 0x18, 0x7E, 0x18, 0x18, 0x00, 0x00],
+        '*' => [0x00, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x00, 0x00],
+        '<' => [0x0C, 0x18, 0x30, 0x60, 0x30, 0x18, 0x0C, 0x00],
+        '>' => [0x30, 0x18, 0x0C, 0x06, 0x0C, 0x18, 0x30, 0x00],
+        '[' => [0x3C, 0x30, 0x30, 0x30, 0x30, 0x30, 0x3C, 0x00],
+        ']' => [0x3C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x3C, 0x00],
+        '%' => [0x62, 0x66, 0x0C, 0x18, 0x30, 0x66, 0x46, 0x00],
+        '#' => [0x66, 0xFF, 0x66, 0x66, 0xFF, 0x66, 0x66, 0x00],
+        '@' => [0x3C, 0x66, 0x6E, 0x6E, 0x60, 0x62, 0x3C, 0x00],
+        '$' => [0x18, 0x3E, 0x60, 0x3C, 0x06, 0x7C, 0x18, 0x00],
+        '~' => [0x00, 0x00, 0x32, 0x4C, 0x00, 0x00, 0x00, 0x00],
+        '^' => [0x18, 0x3C, 0x66, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '&' => [0x38, 0x6C, 0x38, 0x76, 0x66, 0x66, 0x3C, 0x00],
+        '|' => [0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00],
+        _ => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    }
+}
@@ -1,18 +1,26 @@
 //! VT100/ANSI Emulator
 
+pub mod diff;
+pub mod framebuffer;
+pub mod mouse;
 pub mod parser;
 pub mod screen;
 
 #[cfg(test)]
 mod tests;
 
+use crate::lib_core::vt::mouse::{MouseEncoding, MouseEvent, MouseTrackingMode};
 use crate::lib_core::vt::parser::{Parser, Action};
-use crate::lib_core::vt::screen::{ScreenBuffer, Color, Attr, Cell};
+use crate::lib_core::vt::screen::{ScreenBuffer, Color, Attr, Cell, CursorStyle};
 use alloc::vec::Vec;
 
+/// Upper bound on a pending OSC string (e.g. a window-title request), so an
+/// unterminated sequence can't grow `osc_buffer` without limit.
+const MAX_OSC_LEN: usize = 256;
+
 pub trait TerminalDriver {
     fn draw_cell(&mut self, x: usize, y: usize, cell: Cell);
-    fn move_cursor(&mut self, x: usize, y: usize);
+    fn move_cursor(&mut self, x: usize, y: usize, style: CursorStyle);
     fn clear_screen(&mut self);
     fn set_title(&mut self, title: &str);
 }
@@ -24,9 +32,14 @@ pub struct VtTerminal {
     pub is_alternate: bool,
     width: usize,
     height: usize,
-    pub mouse_reporting: bool,
+    pub mouse_tracking: MouseTrackingMode,
+    pub mouse_encoding: MouseEncoding,
     pub bracketed_paste: bool,
     osc_buffer: Vec<u8>,
+    /// How many rows of `primary_buffer.scrollback` are currently scrolled
+    /// into view, 0 meaning "showing the live buffer". Set by
+    /// [`scroll_view`](Self::scroll_view); always 0 while `is_alternate`.
+    view_offset: usize,
 }
 
 impl VtTerminal {
@@ -38,9 +51,11 @@ impl VtTerminal {
             is_alternate: false,
             width,
             height,
-            mouse_reporting: false,
+            mouse_tracking: MouseTrackingMode::default(),
+            mouse_encoding: MouseEncoding::default(),
             bracketed_paste: false,
             osc_buffer: Vec::new(),
+            view_offset: 0,
         }
     }
 
@@ -60,22 +75,77 @@ impl VtTerminal {
         }
     }
 
+    /// Drain pending host-bound reports (DSR/DA replies) queued by the
+    /// active screen buffer, for the PTY/input layer to feed back to the
+    /// program that asked for them.
+    pub fn take_report(&mut self) -> Vec<u8> {
+        let buffer = if self.is_alternate {
+            &mut self.alternate_buffer
+        } else {
+            &mut self.primary_buffer
+        };
+        buffer.take_report()
+    }
+
+    /// Encode a physical mouse event for the program reading this
+    /// terminal, honoring whichever tracking/coordinate mode it last
+    /// negotiated via DECSET. Returns an empty `Vec` if mouse tracking
+    /// isn't enabled (or doesn't cover this event).
+    pub fn encode_mouse(&self, event: MouseEvent) -> Vec<u8> {
+        mouse::encode(self.mouse_tracking, self.mouse_encoding, event)
+    }
+
+    /// Scroll the displayed view by `lines` (positive = back into history,
+    /// negative = toward the live buffer), clamped to how much
+    /// `primary_buffer.scrollback` is actually available. A no-op while
+    /// `is_alternate`, since the alternate screen has no history to view.
+    pub fn scroll_view(&mut self, lines: isize) {
+        if self.is_alternate {
+            return;
+        }
+        let max_offset = self.primary_buffer.scrollback.len();
+        let offset = (self.view_offset as isize + lines).max(0) as usize;
+        self.view_offset = offset.min(max_offset);
+    }
+
     pub fn render(&self, driver: &mut dyn TerminalDriver) {
         let buffer = if self.is_alternate {
             &self.alternate_buffer
         } else {
             &self.primary_buffer
         };
-        
+
         for y in 0..self.height {
             for x in 0..self.width {
-                let idx = y * self.width + x;
-                if idx < buffer.buffer.len() {
-                    driver.draw_cell(x, y, buffer.buffer[idx]);
-                }
+                driver.draw_cell(x, y, self.cell_at(buffer, x, y));
             }
         }
-        driver.move_cursor(buffer.cursor_x, buffer.cursor_y);
+        // While scrolled back into history, the live cursor position isn't
+        // part of what's on screen; leave the driver's cursor where it was.
+        if self.view_offset == 0 {
+            driver.move_cursor(buffer.cursor_x, buffer.cursor_y, buffer.cursor_style);
+        }
+    }
+
+    /// Resolve the cell to display at `(x, y)`, accounting for
+    /// `view_offset`: rows scrolled into view come from `buffer`'s
+    /// scrollback, the rest come straight from its live `buffer.buffer`.
+    fn cell_at(&self, buffer: &ScreenBuffer, x: usize, y: usize) -> Cell {
+        let n = self.view_offset.min(self.height);
+        if y < n {
+            let sb_len = buffer.scrollback.len();
+            let sb_idx = sb_len - n + y;
+            return buffer
+                .scrollback
+                .get(sb_idx)
+                .and_then(|row| row.get(x))
+                .copied()
+                .unwrap_or_default();
+        }
+
+        let live_y = y - n;
+        let idx = live_y * self.width + x;
+        buffer.buffer.get(idx).copied().unwrap_or_default()
     }
 
     fn handle_action(&mut self, action: Action) {
@@ -86,7 +156,12 @@ impl VtTerminal {
                 return;
             }
             Action::OscPut(b) => {
-                self.osc_buffer.push(b);
+                // Cap accumulation so an unterminated OSC string can't grow
+                // this buffer without limit; the string terminator will
+                // still end the state, this just drops overflow bytes.
+                if self.osc_buffer.len() < MAX_OSC_LEN {
+                    self.osc_buffer.push(b);
+                }
                 return;
             }
             Action::OscEnd => {
@@ -106,9 +181,7 @@ impl VtTerminal {
             Action::Print(c) => buffer.write_char(c),
             Action::Execute(b) => {
                  match b {
-                     0x08 => { // BS
-                         if buffer.cursor_x > 0 { buffer.cursor_x -= 1; }
-                     }
+                     0x08 => buffer.write_char('\x08'), // BS (steps over wide-glyph continuation cells)
                      0x0A | 0x0B | 0x0C => buffer.new_line(), // LF, VT, FF
                      0x0D => buffer.cursor_x = 0, // CR
                      _ => {}
@@ -120,8 +193,15 @@ impl VtTerminal {
             Action::EscDispatch(intermediates, _ignore, byte) => {
                  if intermediates.is_empty() {
                      match byte {
+                         0x44 => { // IND (Index): move down, scrolling at the bottom margin
+                             if buffer.cursor_y == buffer.scroll_region.bottom {
+                                 buffer.scroll_region_up(1);
+                             } else if buffer.cursor_y + 1 < buffer.height {
+                                 buffer.cursor_y += 1;
+                             }
+                         }
                          0x45 => buffer.new_line(), // NEL
-                         0x4D => buffer.cursor_y = buffer.cursor_y.saturating_sub(1), // RI (Reverse Index)
+                         0x4D => buffer.reverse_index(), // RI (Reverse Index)
                          _ => {}
                      }
                  }
@@ -146,18 +226,22 @@ impl VtTerminal {
             'A' => { // CUU
                  let n = params.get(0).cloned().unwrap_or(1).max(1) as usize;
                  buffer.cursor_y = buffer.cursor_y.saturating_sub(n);
+                 buffer.snap_cursor_x();
             }
             'B' => { // CUD
                  let n = params.get(0).cloned().unwrap_or(1).max(1) as usize;
                  buffer.cursor_y = (buffer.cursor_y + n).min(buffer.height - 1);
+                 buffer.snap_cursor_x();
             }
             'C' => { // CUF
                  let n = params.get(0).cloned().unwrap_or(1).max(1) as usize;
                  buffer.cursor_x = (buffer.cursor_x + n).min(buffer.width - 1);
+                 buffer.snap_cursor_x();
             }
             'D' => { // CUB
                  let n = params.get(0).cloned().unwrap_or(1).max(1) as usize;
                  buffer.cursor_x = buffer.cursor_x.saturating_sub(n);
+                 buffer.snap_cursor_x();
             }
             'H' | 'f' => { // CUP
                  let y = params.get(0).cloned().unwrap_or(1).max(1) as usize;
@@ -166,15 +250,40 @@ impl VtTerminal {
             }
             'J' => { // ED
                  let mode = params.get(0).cloned().unwrap_or(0) as u8;
-                 match mode {
-                     2 => buffer.clear_screen(),
-                     _ => {} 
-                 }
+                 buffer.erase_in_display(mode);
             }
             'K' => { // EL
                  let mode = params.get(0).cloned().unwrap_or(0) as u8;
                  buffer.clear_line(mode);
             }
+            'L' => { // IL: insert lines
+                 let n = params.get(0).cloned().unwrap_or(1).max(1) as usize;
+                 buffer.insert_lines(n);
+            }
+            'M' => { // DL: delete lines
+                 let n = params.get(0).cloned().unwrap_or(1).max(1) as usize;
+                 buffer.delete_lines(n);
+            }
+            '@' => { // ICH: insert characters
+                 let n = params.get(0).cloned().unwrap_or(1).max(1) as usize;
+                 buffer.insert_chars(n);
+            }
+            'P' => { // DCH: delete characters
+                 let n = params.get(0).cloned().unwrap_or(1).max(1) as usize;
+                 buffer.delete_chars(n);
+            }
+            'X' => { // ECH: erase characters
+                 let n = params.get(0).cloned().unwrap_or(1).max(1) as usize;
+                 buffer.erase_chars(n);
+            }
+            'S' => { // SU: scroll up
+                 let n = params.get(0).cloned().unwrap_or(1).max(1) as usize;
+                 buffer.scroll_region_up(n);
+            }
+            'T' => { // SD: scroll down
+                 let n = params.get(0).cloned().unwrap_or(1).max(1) as usize;
+                 buffer.scroll_region_down(n);
+            }
             'm' => { // SGR
                  let mut i = 0;
                  while i < params.len() {
@@ -195,6 +304,7 @@ impl VtTerminal {
                                  i += 4;
                              }
                          }
+                         39 => buffer.current_attr.fg = Attr::default().fg,
                          40..=47 => buffer.current_attr.bg = Color::Indexed((param - 40) as u8),
                          48 => { // Extended BG
                              if i + 2 < params.len() && params[i+1] == 5 {
@@ -205,6 +315,7 @@ impl VtTerminal {
                                  i += 4;
                              }
                          }
+                         49 => buffer.current_attr.bg = Attr::default().bg,
                          90..=97 => buffer.current_attr.fg = Color::Indexed((param - 90 + 8) as u8),
                          100..=107 => buffer.current_attr.bg = Color::Indexed((param - 100 + 8) as u8),
                          _ => {}
@@ -212,6 +323,30 @@ impl VtTerminal {
                      i += 1;
                  }
             }
+            'r' => { // DECSTBM
+                 let top = params.get(0).cloned().unwrap_or(1).max(1) as usize - 1;
+                 let bottom = params.get(1).cloned().unwrap_or(buffer.height as i64).max(1) as usize - 1;
+                 buffer.set_scroll_region(top, bottom);
+            }
+            'n' => { // DSR (Device Status Report)
+                 match params.get(0).cloned().unwrap_or(0) {
+                     5 => buffer.push_report(b"\x1b[0n"), // Status: OK
+                     6 => buffer.report_cursor_position(),
+                     _ => {}
+                 }
+            }
+            'c' if private.is_none() => { // DA (Primary Device Attributes)
+                 buffer.push_report(b"\x1b[?6c"); // VT102 identity
+            }
+            'q' => { // DECSCUSR: set cursor style
+                 let n = params.get(0).cloned().unwrap_or(0);
+                 buffer.cursor_style = match n {
+                     0 | 1 | 2 => CursorStyle::Block,
+                     3 | 4 => CursorStyle::Underline,
+                     5 | 6 => CursorStyle::Beam,
+                     _ => buffer.cursor_style,
+                 };
+            }
             's' => buffer.save_cursor(),
             'u' => buffer.restore_cursor(),
             _ => {}
@@ -222,6 +357,12 @@ impl VtTerminal {
         if let Some('?') = private {
             for param in params {
                  match param {
+                     // `primary_buffer`/`alternate_buffer` are already two
+                     // independent `ScreenBuffer`s (see `VtTerminal`), so
+                     // entering/leaving the alt screen is just flipping
+                     // `is_alternate` and save/restore-ing the primary's
+                     // cursor; no separate alt-buffer storage is needed
+                     // inside `ScreenBuffer` itself.
                      47 | 1047 | 1049 => {
                          if set {
                              self.is_alternate = true;
@@ -236,9 +377,33 @@ impl VtTerminal {
                              }
                          }
                      }
-                     25 => { /* Show/Hide Cursor */ }
-                     1000 | 1002 | 1006 | 1015 => {
-                         self.mouse_reporting = set;
+                     6 => { // DECOM (origin mode)
+                         self.primary_buffer.origin_mode = set;
+                         self.alternate_buffer.origin_mode = set;
+                     }
+                     25 => { // DECTCEM (Show/Hide Cursor)
+                         self.primary_buffer.cursor_visible = set;
+                         self.alternate_buffer.cursor_visible = set;
+                     }
+                     1000 | 1002 | 1003 => {
+                         self.mouse_tracking = if !set {
+                             MouseTrackingMode::Off
+                         } else {
+                             match param {
+                                 1000 => MouseTrackingMode::Normal,
+                                 1002 => MouseTrackingMode::ButtonMotion,
+                                 _ => MouseTrackingMode::AnyMotion,
+                             }
+                         };
+                     }
+                     1006 | 1015 => {
+                         self.mouse_encoding = if !set {
+                             MouseEncoding::Legacy
+                         } else if param == 1006 {
+                             MouseEncoding::Sgr
+                         } else {
+                             MouseEncoding::Urxvt
+                         };
                      }
                      2004 => self.bracketed_paste = set,
                      _ => {}
@@ -0,0 +1,157 @@
+//! Mouse-event encoding for [`super::VtTerminal`]: turns a physical mouse
+//! event into the byte sequence an application expects, honoring whichever
+//! tracking/coordinate-encoding mode it last negotiated via DECSET. Mirrors
+//! xterm's X10/normal/SGR(1006)/urxvt(1015) mouse reporting protocols.
+
+use alloc::vec::Vec;
+
+/// Which mouse events get reported, negotiated via DECSET 1000/1002/1003.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MouseTrackingMode {
+    #[default]
+    Off,
+    /// 1000: button press/release only.
+    Normal,
+    /// 1002: press/release, plus motion while a button is held.
+    ButtonMotion,
+    /// 1003: press/release, plus motion even with no button held.
+    AnyMotion,
+}
+
+/// Which coordinate encoding is active, negotiated via DECSET 1006/1015.
+/// `Legacy` is the original byte-offset format, capped at column/row 223.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MouseEncoding {
+    #[default]
+    Legacy,
+    Urxvt,
+    Sgr,
+}
+
+/// Button/wheel identity for an encoded mouse event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    WheelUp,
+    WheelDown,
+}
+
+/// Modifier keys held during a mouse event, ORed into the report's button
+/// byte as bits 4 (shift), 8 (meta), 16 (ctrl).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MouseModifiers {
+    pub shift: bool,
+    pub meta: bool,
+    pub ctrl: bool,
+}
+
+impl MouseModifiers {
+    fn bits(self) -> u8 {
+        (if self.shift { 4 } else { 0 })
+            | (if self.meta { 8 } else { 0 })
+            | (if self.ctrl { 16 } else { 0 })
+    }
+}
+
+/// A single physical mouse event to report, handed to
+/// [`super::VtTerminal::encode_mouse`]. `x`/`y` are 0-based cell
+/// coordinates, matching `ScreenBuffer::cursor_x`/`cursor_y`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MouseEvent {
+    pub button: MouseButton,
+    pub pressed: bool,
+    pub motion: bool,
+    pub x: usize,
+    pub y: usize,
+    pub modifiers: MouseModifiers,
+}
+
+/// Encode `event` per the negotiated `tracking`/`encoding`. Returns an
+/// empty `Vec` if tracking is off, or if this is a drag event that
+/// `tracking` isn't negotiated to report (plain `Normal` mode only reports
+/// press/release, not motion).
+pub fn encode(tracking: MouseTrackingMode, encoding: MouseEncoding, event: MouseEvent) -> Vec<u8> {
+    if tracking == MouseTrackingMode::Off {
+        return Vec::new();
+    }
+    if event.motion && tracking == MouseTrackingMode::Normal {
+        return Vec::new();
+    }
+
+    let mut base: u8 = match event.button {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+        MouseButton::WheelUp => 64,
+        MouseButton::WheelDown => 65,
+    };
+    if event.motion {
+        base |= 32;
+    }
+    base |= event.modifiers.bits();
+
+    // Wheel events have no "release"; everything else reports release as
+    // button code 3 (modifiers still apply) in the legacy/urxvt formats.
+    let is_wheel = matches!(event.button, MouseButton::WheelUp | MouseButton::WheelDown);
+    let legacy_button = if event.pressed || is_wheel {
+        base
+    } else {
+        3 | event.modifiers.bits()
+    };
+
+    let col = event.x + 1;
+    let row = event.y + 1;
+
+    match encoding {
+        MouseEncoding::Sgr => {
+            let mut out = Vec::new();
+            out.extend_from_slice(b"\x1b[<");
+            push_decimal(&mut out, base as usize);
+            out.push(b';');
+            push_decimal(&mut out, col);
+            out.push(b';');
+            push_decimal(&mut out, row);
+            out.push(if event.pressed { b'M' } else { b'm' });
+            out
+        }
+        MouseEncoding::Urxvt => {
+            let mut out = Vec::new();
+            out.extend_from_slice(b"\x1b[");
+            push_decimal(&mut out, 32 + legacy_button as usize);
+            out.push(b';');
+            push_decimal(&mut out, col);
+            out.push(b';');
+            push_decimal(&mut out, row);
+            out.push(b'M');
+            out
+        }
+        MouseEncoding::Legacy => {
+            // Each value is offset by 32 and capped at 223 so it never
+            // needs more than a single byte.
+            let cb = (32u32 + legacy_button as u32).min(255) as u8;
+            let cx = (32usize + col).min(223) as u8;
+            let cy = (32usize + row).min(223) as u8;
+            let mut out = Vec::new();
+            out.extend_from_slice(b"\x1b[M");
+            out.push(cb);
+            out.push(cx);
+            out.push(cy);
+            out
+        }
+    }
+}
+
+fn push_decimal(out: &mut Vec<u8>, mut value: usize) {
+    if value == 0 {
+        out.push(b'0');
+        return;
+    }
+    let start = out.len();
+    while value > 0 {
+        out.push(b'0' + (value % 10) as u8);
+        value /= 10;
+    }
+    out[start..].reverse();
+}
@@ -1,13 +1,33 @@
+//! VTE-style terminal escape sequence parser.
+//!
+//! `Ground`-state bytes are decoded as UTF-8 rather than cast byte-for-byte
+//! to `char`: multi-byte lead bytes (`0xC2..=0xF4`) start an incremental
+//! decode tracked by `utf8_remaining`/`utf8_accum`/`utf8_min`, continuation
+//! bytes (`0x80..=0xBF`) fold into the accumulator 6 bits at a time, and
+//! [`Parser::emit_utf8_scalar`] validates the completed scalar against
+//! overlong encodings and the surrogate range before emitting
+//! [`Action::Print`]. Anything that fails to decode - an unexpected
+//! continuation byte, an overlong sequence, a surrogate - surfaces as
+//! `U+FFFD` and resynchronizes on the next byte instead of propagating
+//! mangled Latin-1-ish garbage into the grid.
+
 #![allow(dead_code)]
 
 use alloc::vec::Vec;
 
+/// Upper bound on accumulated CSI/DCS parameters. A well-formed sequence
+/// never comes close to this; it exists to bail to `CsiIgnore`/`DcsIgnore`
+/// on a runaway or hostile byte stream instead of growing `params` without
+/// limit.
+const MAX_PARAMS: usize = 32;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Action {
     Print(char),
     Execute(u8),
-    Hook(Vec<i64>, Vec<u8>, bool),
+    Hook(Vec<i64>, Vec<u8>, bool, char, Option<char>),
     Put(u8),
+    Unhook,
     OscStart,
     OscPut(u8),
     OscEnd,
@@ -39,6 +59,25 @@ pub struct Parser {
     intermediates: Vec<u8>,
     ignore_flagged: bool,
     private_prefix: Option<char>,
+    /// Continuation bytes still expected before `utf8_accum` holds a full
+    /// scalar value. Zero when no multi-byte sequence is in progress.
+    utf8_remaining: u8,
+    /// Codepoint bits collected so far from the lead byte and any
+    /// continuation bytes seen.
+    utf8_accum: u32,
+    /// Smallest codepoint the in-progress sequence's byte length may
+    /// legally encode, so overlong encodings can be rejected once the
+    /// scalar is complete.
+    utf8_min: u32,
+    /// Set when `DcsPassthrough` sees `ESC`, so the following byte in
+    /// `Escape` is checked for the `\` half of an ST before being handled
+    /// as an ordinary escape byte.
+    dcs_terminating: bool,
+    /// Same as `dcs_terminating`, but for `OscString`: an OSC string may be
+    /// closed by either BEL or ST (`ESC \`), and without this flag the
+    /// `\` half of an ST would be mistaken for the start of an unrelated
+    /// escape sequence, dropping the `OscEnd` that dispatch depends on.
+    osc_terminating: bool,
 }
 
 impl Parser {
@@ -49,6 +88,11 @@ impl Parser {
             intermediates: Vec::new(),
             ignore_flagged: false,
             private_prefix: None,
+            utf8_remaining: 0,
+            utf8_accum: 0,
+            utf8_min: 0,
+            dcs_terminating: false,
+            osc_terminating: false,
         }
     }
 
@@ -57,30 +101,94 @@ impl Parser {
         F: FnMut(Action),
     {
         match self.state {
-            State::Ground => match byte {
-                0x00..=0x17 | 0x19 | 0x1C..=0x1F => callback(Action::Execute(byte)),
-                0x1B => self.state = State::Escape,
-                0x20..=0x7F => callback(Action::Print(byte as char)),
-                // UTF-8 continuation bytes and other high bytes treated as print for now
-                0x80..=0xFF => callback(Action::Print(byte as char)),
-            },
-            State::Escape => match byte {
-                0x00..=0x17 | 0x19 | 0x1C..=0x1F => callback(Action::Execute(byte)),
-                0x1B => (), // Ignore
-                0x20..=0x2F => {
-                    self.intermediates.push(byte);
-                    self.state = State::EscapeIntermediate;
+            State::Ground => {
+                if self.utf8_remaining > 0 {
+                    if (0x80..=0xBF).contains(&byte) {
+                        self.utf8_accum = (self.utf8_accum << 6) | (byte & 0x3F) as u32;
+                        self.utf8_remaining -= 1;
+                        if self.utf8_remaining == 0 {
+                            self.emit_utf8_scalar(&mut callback);
+                        }
+                        return;
+                    }
+                    // Continuation expected but not found: the sequence is
+                    // malformed, so surface U+FFFD and reprocess the
+                    // offending byte as if it had just arrived in Ground.
+                    self.utf8_remaining = 0;
+                    self.utf8_accum = 0;
+                    callback(Action::Print('\u{FFFD}'));
+                    self.advance(byte, callback);
+                    return;
                 }
-                0x30..=0x4F | 0x51..=0x57 | 0x59 | 0x5A | 0x5C | 0x60..=0x7E => {
-                    callback(Action::EscDispatch(self.intermediates.clone(), self.ignore_flagged, byte));
-                    self.reset();
+                match byte {
+                    0x00..=0x17 | 0x19 | 0x1C..=0x1F => callback(Action::Execute(byte)),
+                    0x1B => self.state = State::Escape,
+                    0x20..=0x7F => callback(Action::Print(byte as char)),
+                    0xC2..=0xDF => {
+                        self.utf8_remaining = 1;
+                        self.utf8_accum = (byte & 0x1F) as u32;
+                        self.utf8_min = 0x80;
+                    }
+                    0xE0..=0xEF => {
+                        self.utf8_remaining = 2;
+                        self.utf8_accum = (byte & 0x0F) as u32;
+                        self.utf8_min = 0x800;
+                    }
+                    0xF0..=0xF4 => {
+                        self.utf8_remaining = 3;
+                        self.utf8_accum = (byte & 0x07) as u32;
+                        self.utf8_min = 0x1_0000;
+                    }
+                    // Invalid lead bytes: continuation bytes with no lead
+                    // (0x80..=0xC1) and bytes beyond the max scalar's
+                    // 4-byte encoding (0xF5..=0xFF).
+                    0x80..=0xC1 | 0xF5..=0xFF => callback(Action::Print('\u{FFFD}')),
                 }
-                0x50 => self.state = State::DcsEntry,
-                0x58 | 0x5E | 0x5F => self.state = State::SosPmapiString,
-                0x5B => self.state = State::CsiEntry,
-                0x5D => self.state = State::OscString,
-                _ => self.reset(),
-            },
+            }
+            State::Escape => {
+                if self.dcs_terminating {
+                    self.dcs_terminating = false;
+                    callback(Action::Unhook);
+                    if byte == 0x5C {
+                        self.reset();
+                        return;
+                    }
+                    // Anything other than the ST's `\` just means the DCS
+                    // string ended without a clean terminator; fall through
+                    // and handle this byte as a fresh Escape-state byte.
+                }
+                if self.osc_terminating {
+                    self.osc_terminating = false;
+                    callback(Action::OscEnd);
+                    if byte == 0x5C {
+                        self.reset();
+                        return;
+                    }
+                    // Anything other than the ST's `\` just means the OSC
+                    // string ended without a clean terminator; fall through
+                    // and handle this byte as a fresh Escape-state byte.
+                }
+                match byte {
+                    0x00..=0x17 | 0x19 | 0x1C..=0x1F => callback(Action::Execute(byte)),
+                    0x1B => (), // Ignore
+                    0x20..=0x2F => {
+                        self.intermediates.push(byte);
+                        self.state = State::EscapeIntermediate;
+                    }
+                    0x30..=0x4F | 0x51..=0x57 | 0x59 | 0x5A | 0x5C | 0x60..=0x7E => {
+                        callback(Action::EscDispatch(self.intermediates.clone(), self.ignore_flagged, byte));
+                        self.reset();
+                    }
+                    0x50 => self.state = State::DcsEntry,
+                    0x58 | 0x5E | 0x5F => self.state = State::SosPmapiString,
+                    0x5B => self.state = State::CsiEntry,
+                    0x5D => {
+                        self.state = State::OscString;
+                        callback(Action::OscStart);
+                    }
+                    _ => self.reset(),
+                }
+            }
             State::EscapeIntermediate => match byte {
                 0x00..=0x17 | 0x19 | 0x1C..=0x1F => callback(Action::Execute(byte)),
                 0x20..=0x2F => self.intermediates.push(byte),
@@ -130,12 +238,28 @@ impl Parser {
                         self.params.push((byte - 0x30) as i64);
                     }
                 }
-                0x3B => self.params.push(0), // New param
+                0x3B | 0x3A => {
+                    // `:` is treated the same as `;` here: colon-delimited
+                    // SGR sub-parameters (`38:2:R:G:B`) fold into the same
+                    // flat param list as the semicolon form, since
+                    // translate_graphics already walks params positionally
+                    // regardless of which separator produced them.
+                    //
+                    // Cap the parameter list so a runaway/hostile sequence
+                    // can't grow it unboundedly; bail to CsiIgnore instead
+                    // of dispatching a truncated, possibly-misleading param
+                    // list once the cap is hit.
+                    if self.params.len() + 1 >= MAX_PARAMS {
+                        self.state = State::CsiIgnore;
+                    } else {
+                        self.params.push(0); // New param
+                    }
+                }
                 0x40..=0x7E => {
                     callback(Action::CsiDispatch(self.params.clone(), self.intermediates.clone(), self.ignore_flagged, byte as char, self.private_prefix));
                     self.reset();
                 }
-                0x3A | 0x3C..=0x3F => self.state = State::CsiIgnore,
+                0x3C..=0x3F => self.state = State::CsiIgnore,
                 _ => self.state = State::CsiIgnore,
             },
             State::CsiIntermediate => match byte {
@@ -152,12 +276,103 @@ impl Parser {
                 0x40..=0x7E => self.reset(),
                 _ => (),
             },
+            State::DcsEntry => match byte {
+                0x00..=0x17 | 0x19 | 0x1C..=0x1F => callback(Action::Execute(byte)),
+                0x1B => self.state = State::Escape,
+                0x20..=0x2F => {
+                    self.intermediates.push(byte);
+                    self.state = State::DcsIntermediate;
+                }
+                0x30..=0x39 | 0x3B => {
+                    if byte == 0x3B {
+                        self.params.push(0);
+                        self.params.push(0);
+                    } else {
+                        self.params.push((byte - 0x30) as i64);
+                    }
+                    self.state = State::DcsParam;
+                }
+                0x3C..=0x3F => {
+                    self.private_prefix = Some(byte as char);
+                    self.state = State::DcsParam;
+                }
+                0x40..=0x7E => {
+                    callback(Action::Hook(self.params.clone(), self.intermediates.clone(), self.ignore_flagged, byte as char, self.private_prefix));
+                    self.state = State::DcsPassthrough;
+                }
+                _ => self.state = State::DcsIgnore,
+            },
+            State::DcsParam => match byte {
+                0x00..=0x17 | 0x19 | 0x1C..=0x1F => callback(Action::Execute(byte)),
+                0x30..=0x39 => {
+                    if let Some(last) = self.params.last_mut() {
+                        if *last >= 0 {
+                            *last = last.saturating_mul(10).saturating_add((byte - 0x30) as i64);
+                        } else {
+                            self.params.push((byte - 0x30) as i64);
+                        }
+                    } else {
+                        self.params.push((byte - 0x30) as i64);
+                    }
+                }
+                0x3B | 0x3A => {
+                    // See the matching CsiParam arm: `:` folds into the
+                    // same flat param list as `;`.
+                    if self.params.len() + 1 >= MAX_PARAMS {
+                        self.state = State::DcsIgnore;
+                    } else {
+                        self.params.push(0);
+                    }
+                }
+                0x20..=0x2F => {
+                    self.intermediates.push(byte);
+                    self.state = State::DcsIntermediate;
+                }
+                0x40..=0x7E => {
+                    callback(Action::Hook(self.params.clone(), self.intermediates.clone(), self.ignore_flagged, byte as char, self.private_prefix));
+                    self.state = State::DcsPassthrough;
+                }
+                0x3C..=0x3F => self.state = State::DcsIgnore,
+                _ => self.state = State::DcsIgnore,
+            },
+            State::DcsIntermediate => match byte {
+                0x00..=0x17 | 0x19 | 0x1C..=0x1F => callback(Action::Execute(byte)),
+                0x20..=0x2F => self.intermediates.push(byte),
+                0x40..=0x7E => {
+                    callback(Action::Hook(self.params.clone(), self.intermediates.clone(), self.ignore_flagged, byte as char, self.private_prefix));
+                    self.state = State::DcsPassthrough;
+                }
+                _ => self.state = State::DcsIgnore,
+            },
+            State::DcsPassthrough => match byte {
+                0x07 => { // BEL terminates DCS
+                    callback(Action::Unhook);
+                    self.reset();
+                }
+                0x1B => {
+                    // ST (ESC \) terminates DCS; Unhook fires once Escape
+                    // sees whether the next byte completes it.
+                    self.dcs_terminating = true;
+                    self.state = State::Escape;
+                }
+                _ => callback(Action::Put(byte)),
+            },
+            State::DcsIgnore => match byte {
+                0x00..=0x17 | 0x19 | 0x1C..=0x1F => callback(Action::Execute(byte)),
+                0x40..=0x7E => self.reset(),
+                _ => (),
+            },
             State::OscString => match byte {
                 0x07 => { // BEL terminates OSC
                     callback(Action::OscEnd);
                     self.reset();
                 }
-                0x1B => self.state = State::Escape, // ST (ESC \) terminates OSC
+                0x1B => {
+                    // ST (ESC \) terminates OSC; OscEnd fires once Escape
+                    // sees whether the next byte completes it.
+                    self.osc_terminating = true;
+                    self.state = State::Escape;
+                }
                 _ => callback(Action::OscPut(byte)),
             },
             // Simplified handling for others
@@ -171,5 +386,28 @@ impl Parser {
         self.intermediates.clear();
         self.ignore_flagged = false;
         self.private_prefix = None;
+        self.utf8_remaining = 0;
+        self.utf8_accum = 0;
+        self.dcs_terminating = false;
+        self.osc_terminating = false;
+    }
+
+    /// Validate a just-completed codepoint accumulated in `utf8_accum`
+    /// against its minimum legal length and the UTF-16 surrogate range,
+    /// emitting `U+FFFD` in place of anything that fails either check.
+    fn emit_utf8_scalar<F>(&mut self, callback: &mut F)
+    where
+        F: FnMut(Action),
+    {
+        let codepoint = self.utf8_accum;
+        self.utf8_accum = 0;
+        if codepoint < self.utf8_min || (0xD800..=0xDFFF).contains(&codepoint) {
+            callback(Action::Print('\u{FFFD}'));
+            return;
+        }
+        match char::from_u32(codepoint) {
+            Some(c) => callback(Action::Print(c)),
+            None => callback(Action::Print('\u{FFFD}')),
+        }
     }
 }
@@ -0,0 +1,574 @@
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    Indexed(u8),
+    RGB(u8, u8, u8),
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Color::Indexed(7) // Light Grey
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attr {
+    pub fg: Color,
+    pub bg: Color,
+    pub bold: bool,
+    pub underline: bool,
+    pub blink: bool,
+    pub reverse: bool,
+}
+
+impl Default for Attr {
+    fn default() -> Self {
+        Attr {
+            fg: Color::Indexed(7), // Light Grey
+            bg: Color::Indexed(0), // Black
+            bold: false,
+            underline: false,
+            blink: false,
+            reverse: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub char: char,
+    pub attr: Attr,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            char: ' ',
+            attr: Attr::default(),
+        }
+    }
+}
+
+impl Cell {
+    /// The second half of a wide glyph placed by [`ScreenBuffer::write_char`],
+    /// marked with the NUL sentinel so it's never drawn or copied on its own.
+    pub fn is_continuation(&self) -> bool {
+        self.char == '\0'
+    }
+}
+
+/// Column width of `c` for cursor advancement: 0 for combining marks (which
+/// compose onto the previous cell rather than occupying one of their own),
+/// 2 for East Asian Wide/Fullwidth characters and most emoji, 1 otherwise.
+/// Not a full Unicode wcwidth (no Hangul Jamo/variation-selector handling),
+/// just enough range coverage to keep CJK and emoji from overlapping the
+/// following cell.
+pub fn wcwidth(c: char) -> u8 {
+    let cp = c as u32;
+    match cp {
+        0x0300..=0x036F // Combining Diacritical Marks
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x20D0..=0x20FF
+        | 0xFE20..=0xFE2F => 0,
+        0x1100..=0x115F // Hangul Jamo
+        | 0x2E80..=0x303E // CJK Radicals, Kangxi, CJK Symbols/Punctuation
+        | 0x3041..=0x33FF // Hiragana .. CJK Compat
+        | 0x3400..=0x4DBF // CJK Extension A
+        | 0x4E00..=0x9FFF // CJK Unified Ideographs
+        | 0xA000..=0xA4CF // Yi
+        | 0xAC00..=0xD7A3 // Hangul Syllables
+        | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+        | 0xFF00..=0xFF60 // Fullwidth Forms
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1FAFF // Emoji & pictographs
+        | 0x20000..=0x3FFFD => 2, // CJK Extension B and beyond
+        _ => 1,
+    }
+}
+
+/// A DECSTBM scroll region: rows `top..=bottom` (0-based, inclusive) are
+/// the only ones `new_line`/`scroll_up`/`reverse_index` shift; rows outside
+/// it stay put. Defaults to the full screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollRegion {
+    pub top: usize,
+    pub bottom: usize,
+}
+
+/// DECSCUSR caret shape, set via `CSI Ps SP q` (see
+/// [`super::VtTerminal::handle_csi`]'s `'q'` arm) so a `TerminalDriver` can
+/// draw the cursor accordingly. `HollowBlock` has no DECSCUSR code of its
+/// own; it's there for a renderer to use when e.g. the window loses focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CursorStyle {
+    #[default]
+    Block,
+    Underline,
+    Beam,
+    HollowBlock,
+}
+
+pub struct ScreenBuffer {
+    pub width: usize,
+    pub height: usize,
+    pub buffer: Vec<Cell>,
+    pub cursor_x: usize,
+    pub cursor_y: usize,
+    pub current_attr: Attr,
+    pub saved_cursor_x: usize,
+    pub saved_cursor_y: usize,
+    pub saved_attr: Attr,
+    pub scroll_region: ScrollRegion,
+    /// DECOM: when set, `set_cursor` treats its row as relative to
+    /// `scroll_region.top` and clamps it to the region instead of the
+    /// whole screen.
+    pub origin_mode: bool,
+    /// DECSCUSR caret shape, for a renderer to draw.
+    pub cursor_style: CursorStyle,
+    /// DECTCEM (`CSI ?25h`/`?25l`): whether the cursor should be drawn at all.
+    pub cursor_visible: bool,
+    /// Pending terminal-to-host replies (DSR, DA, ...), queued by
+    /// [`VtTerminal::handle_csi`](super::VtTerminal::handle_csi) and drained
+    /// by the PTY/input layer so "read cursor position" style queries from
+    /// the program don't block forever.
+    pub report: VecDeque<u8>,
+    /// Lines scrolled off the top of the screen (only captured when the
+    /// scroll region's top margin is row 0 -- a narrower DECSTBM region
+    /// doesn't feed history), oldest first, bounded to
+    /// [`SCROLLBACK_CAPACITY`] rows. Read by
+    /// [`VtTerminal::scroll_view`](super::VtTerminal::scroll_view).
+    pub scrollback: VecDeque<Vec<Cell>>,
+}
+
+/// Maximum rows retained in [`ScreenBuffer::scrollback`].
+pub const SCROLLBACK_CAPACITY: usize = 1000;
+
+impl ScreenBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        let size = width * height;
+        ScreenBuffer {
+            width,
+            height,
+            buffer: vec![Cell::default(); size],
+            cursor_x: 0,
+            cursor_y: 0,
+            current_attr: Attr::default(),
+            saved_cursor_x: 0,
+            saved_cursor_y: 0,
+            saved_attr: Attr::default(),
+            scroll_region: ScrollRegion { top: 0, bottom: height.saturating_sub(1) },
+            origin_mode: false,
+            cursor_style: CursorStyle::default(),
+            cursor_visible: true,
+            report: VecDeque::new(),
+            scrollback: VecDeque::new(),
+        }
+    }
+
+    /// Queue raw bytes to report back to the host.
+    pub fn push_report(&mut self, bytes: &[u8]) {
+        self.report.extend(bytes.iter().copied());
+    }
+
+    /// Queue a DSR cursor-position report (`CSI row;col R`, 1-based).
+    pub fn report_cursor_position(&mut self) {
+        let mut reply = Vec::new();
+        reply.extend_from_slice(b"\x1b[");
+        push_decimal(&mut reply, self.cursor_y + 1);
+        reply.push(b';');
+        push_decimal(&mut reply, self.cursor_x + 1);
+        reply.push(b'R');
+        self.push_report(&reply);
+    }
+
+    /// Drain all pending host-bound reports.
+    pub fn take_report(&mut self) -> Vec<u8> {
+        self.report.drain(..).collect()
+    }
+
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        let size = width * height;
+        self.buffer.resize(size, Cell::default());
+        if self.cursor_x >= width {
+            self.cursor_x = width.saturating_sub(1);
+        }
+        if self.cursor_y >= height {
+            self.cursor_y = height.saturating_sub(1);
+        }
+        self.scroll_region = ScrollRegion { top: 0, bottom: height.saturating_sub(1) };
+    }
+
+    /// DECSTBM: set the scroll region to `top..=bottom` (0-based), clamped
+    /// to the screen and left at the default full-screen region if the
+    /// bounds are degenerate.
+    pub fn set_scroll_region(&mut self, top: usize, bottom: usize) {
+        let bottom = bottom.min(self.height.saturating_sub(1));
+        if top < bottom {
+            self.scroll_region = ScrollRegion { top, bottom };
+        } else {
+            self.scroll_region = ScrollRegion { top: 0, bottom: self.height.saturating_sub(1) };
+        }
+    }
+
+    pub fn write_char(&mut self, c: char) {
+        if c == '\n' {
+            self.new_line();
+            return;
+        }
+        if c == '\r' {
+            self.cursor_x = 0;
+            return;
+        }
+        // Handle backspace
+        if c == '\x08' {
+            self.backspace();
+            return;
+        }
+
+        let width = wcwidth(c);
+        if width == 0 {
+            // Combining mark: no cell of its own to place it in (Cell holds
+            // a single char), so just drop it rather than overlap the next
+            // column.
+            return;
+        }
+
+        if self.cursor_x >= self.width {
+            self.new_line();
+        }
+        // A width-2 char can't start in the last column: fill it blank and
+        // wrap, same as a terminal's auto-margin behavior.
+        if width == 2 && self.cursor_x + 1 >= self.width {
+            let idx = self.cursor_y * self.width + self.cursor_x;
+            if idx < self.buffer.len() {
+                self.buffer[idx] = Cell { char: ' ', attr: self.current_attr };
+            }
+            self.new_line();
+        }
+
+        let idx = self.cursor_y * self.width + self.cursor_x;
+        if idx < self.buffer.len() {
+            self.buffer[idx] = Cell {
+                char: c,
+                attr: self.current_attr,
+            };
+        }
+        self.cursor_x += 1;
+
+        if width == 2 {
+            let idx = self.cursor_y * self.width + self.cursor_x;
+            if idx < self.buffer.len() {
+                self.buffer[idx] = Cell { char: '\0', attr: self.current_attr };
+            }
+            self.cursor_x += 1;
+        }
+    }
+
+    /// Move the cursor left one column, stepping over the second half of a
+    /// wide glyph so it never lands mid-character.
+    fn backspace(&mut self) {
+        if self.cursor_x == 0 {
+            return;
+        }
+        self.cursor_x -= 1;
+        let idx = self.cursor_y * self.width + self.cursor_x;
+        if self.cursor_x > 0 && self.buffer.get(idx).is_some_and(Cell::is_continuation) {
+            self.cursor_x -= 1;
+        }
+    }
+
+    pub fn new_line(&mut self) {
+        self.cursor_x = 0;
+        if self.cursor_y == self.scroll_region.bottom {
+            self.scroll_up();
+        } else if self.cursor_y + 1 < self.height {
+            self.cursor_y += 1;
+        }
+    }
+
+    /// Shift rows `scroll_region.top..=scroll_region.bottom` up by one,
+    /// clearing the freed bottom row; rows outside the region are
+    /// untouched. When the region's top margin is row 0, the row scrolled
+    /// off is captured into `scrollback` first.
+    fn scroll_up(&mut self) {
+        let col_count = self.width;
+        let ScrollRegion { top, bottom } = self.scroll_region;
+
+        if top == 0 {
+            self.scrollback.push_back(self.buffer[0..col_count].to_vec());
+            if self.scrollback.len() > SCROLLBACK_CAPACITY {
+                self.scrollback.pop_front();
+            }
+        }
+
+        for y in top..bottom {
+            for x in 0..col_count {
+                self.buffer[y * col_count + x] = self.buffer[(y + 1) * col_count + x];
+            }
+        }
+
+        let last_row_start = bottom * col_count;
+        for x in 0..col_count {
+            self.buffer[last_row_start + x] = Cell {
+                char: ' ',
+                attr: Attr { bg: self.current_attr.bg, ..Attr::default() },
+            };
+        }
+    }
+
+    /// Shift rows `scroll_region.top..=scroll_region.bottom` down by one,
+    /// clearing the freed top row; used by reverse index (`ESC M`).
+    fn scroll_down(&mut self) {
+        let col_count = self.width;
+        let ScrollRegion { top, bottom } = self.scroll_region;
+
+        for y in (top + 1..=bottom).rev() {
+            for x in 0..col_count {
+                self.buffer[y * col_count + x] = self.buffer[(y - 1) * col_count + x];
+            }
+        }
+
+        let first_row_start = top * col_count;
+        for x in 0..col_count {
+            self.buffer[first_row_start + x] = Cell {
+                char: ' ',
+                attr: Attr { bg: self.current_attr.bg, ..Attr::default() },
+            };
+        }
+    }
+
+    /// Reverse index (`ESC M`): move up a line, scrolling the region down
+    /// instead of moving the cursor past its top margin.
+    pub fn reverse_index(&mut self) {
+        if self.cursor_y == self.scroll_region.top {
+            self.scroll_down();
+        } else {
+            self.cursor_y = self.cursor_y.saturating_sub(1);
+        }
+    }
+
+    /// CSI `S`: scroll the scroll region up by `n` lines, as if `n` lines
+    /// of new text had arrived at the bottom margin.
+    pub fn scroll_region_up(&mut self, n: usize) {
+        for _ in 0..n {
+            self.scroll_up();
+        }
+    }
+
+    /// CSI `T`: scroll the scroll region down by `n` lines.
+    pub fn scroll_region_down(&mut self, n: usize) {
+        for _ in 0..n {
+            self.scroll_down();
+        }
+    }
+
+    /// CSI `L` (IL): insert `n` blank lines at the cursor row, shifting
+    /// rows below it (within the scroll region) down; rows pushed past the
+    /// bottom margin are dropped. No-op if the cursor is outside the
+    /// region.
+    pub fn insert_lines(&mut self, n: usize) {
+        let ScrollRegion { top, bottom } = self.scroll_region;
+        if self.cursor_y < top || self.cursor_y > bottom {
+            return;
+        }
+        let count = n.min(bottom - self.cursor_y + 1);
+        for _ in 0..count {
+            for y in (self.cursor_y + 1..=bottom).rev() {
+                for x in 0..self.width {
+                    self.buffer[y * self.width + x] = self.buffer[(y - 1) * self.width + x];
+                }
+            }
+            let row_start = self.cursor_y * self.width;
+            for x in 0..self.width {
+                self.buffer[row_start + x] = Cell {
+                    char: ' ',
+                    attr: Attr { bg: self.current_attr.bg, ..Attr::default() },
+                };
+            }
+        }
+    }
+
+    /// CSI `M` (DL): delete `n` lines at the cursor row, shifting rows
+    /// below it (within the scroll region) up and blanking the freed rows
+    /// at the bottom margin. No-op if the cursor is outside the region.
+    pub fn delete_lines(&mut self, n: usize) {
+        let ScrollRegion { top, bottom } = self.scroll_region;
+        if self.cursor_y < top || self.cursor_y > bottom {
+            return;
+        }
+        let count = n.min(bottom - self.cursor_y + 1);
+        for _ in 0..count {
+            for y in self.cursor_y..bottom {
+                for x in 0..self.width {
+                    self.buffer[y * self.width + x] = self.buffer[(y + 1) * self.width + x];
+                }
+            }
+            let row_start = bottom * self.width;
+            for x in 0..self.width {
+                self.buffer[row_start + x] = Cell {
+                    char: ' ',
+                    attr: Attr { bg: self.current_attr.bg, ..Attr::default() },
+                };
+            }
+        }
+    }
+
+    /// CSI `@` (ICH): insert `n` blank cells at the cursor column, shifting
+    /// the rest of the row right; cells pushed past the right edge are
+    /// dropped.
+    pub fn insert_chars(&mut self, n: usize) {
+        let row_start = self.cursor_y * self.width;
+        let n = n.min(self.width - self.cursor_x);
+        for x in (self.cursor_x + n..self.width).rev() {
+            self.buffer[row_start + x] = self.buffer[row_start + x - n];
+        }
+        for x in self.cursor_x..self.cursor_x + n {
+            self.buffer[row_start + x] = Cell { char: ' ', attr: self.current_attr };
+        }
+    }
+
+    /// CSI `P` (DCH): delete `n` cells at the cursor column, shifting the
+    /// rest of the row left and blanking the freed cells at the right edge.
+    pub fn delete_chars(&mut self, n: usize) {
+        let row_start = self.cursor_y * self.width;
+        let n = n.min(self.width - self.cursor_x);
+        for x in self.cursor_x..self.width - n {
+            self.buffer[row_start + x] = self.buffer[row_start + x + n];
+        }
+        for x in self.width - n..self.width {
+            self.buffer[row_start + x] = Cell { char: ' ', attr: self.current_attr };
+        }
+    }
+
+    /// CSI `X` (ECH): erase `n` cells starting at the cursor column in
+    /// place, without shifting the rest of the row.
+    pub fn erase_chars(&mut self, n: usize) {
+        let row_start = self.cursor_y * self.width;
+        let end = (self.cursor_x + n).min(self.width);
+        for x in self.cursor_x..end {
+            self.buffer[row_start + x] = Cell { char: ' ', attr: self.current_attr };
+        }
+    }
+
+    pub fn clear_screen(&mut self) {
+        for cell in self.buffer.iter_mut() {
+            *cell = Cell {
+                char: ' ',
+                attr: Attr { bg: self.current_attr.bg, ..Attr::default() },
+            };
+        }
+        self.cursor_x = 0;
+        self.cursor_y = 0;
+        self.scroll_region = ScrollRegion { top: 0, bottom: self.height.saturating_sub(1) };
+    }
+
+    /// Erase in display (CSI `J`): 0 clears cursor to end of screen, 1
+    /// clears start of screen to cursor, 2 (and xterm's 3) clears
+    /// everything via [`clear_screen`](Self::clear_screen).
+    pub fn erase_in_display(&mut self, mode: u8) {
+        let row_start = self.cursor_y * self.width;
+        match mode {
+            0 => {
+                for x in self.cursor_x..self.width {
+                    self.buffer[row_start + x] = Cell { char: ' ', attr: self.current_attr };
+                }
+                for cell in self.buffer[row_start + self.width..].iter_mut() {
+                    *cell = Cell { char: ' ', attr: Attr { bg: self.current_attr.bg, ..Attr::default() } };
+                }
+            }
+            1 => {
+                for cell in self.buffer[..row_start].iter_mut() {
+                    *cell = Cell { char: ' ', attr: Attr { bg: self.current_attr.bg, ..Attr::default() } };
+                }
+                for x in 0..=self.cursor_x {
+                    self.buffer[row_start + x] = Cell { char: ' ', attr: self.current_attr };
+                }
+            }
+            2 | 3 => self.clear_screen(),
+            _ => {}
+        }
+    }
+
+    pub fn clear_line(&mut self, mode: u8) {
+        let row_start = self.cursor_y * self.width;
+        match mode {
+            0 => { // Clear from cursor to end
+                // If the cursor sits on the second half of a wide glyph,
+                // its lead cell (just outside the cleared range) would be
+                // left pointing at a half-erased character; clear it too.
+                if self.cursor_x > 0 && self.buffer[row_start + self.cursor_x].is_continuation() {
+                    self.buffer[row_start + self.cursor_x - 1] = Cell { char: ' ', attr: self.current_attr };
+                }
+                for x in self.cursor_x..self.width {
+                    self.buffer[row_start + x] = Cell { char: ' ', attr: self.current_attr };
+                }
+            }
+            1 => { // Clear from start to cursor
+                for x in 0..=self.cursor_x {
+                    self.buffer[row_start + x] = Cell { char: ' ', attr: self.current_attr };
+                }
+                // If the cursor landed on a wide glyph's lead cell, its
+                // continuation just past the cleared range is now orphaned.
+                if self.cursor_x + 1 < self.width && self.buffer[row_start + self.cursor_x + 1].is_continuation() {
+                    self.buffer[row_start + self.cursor_x + 1] = Cell { char: ' ', attr: self.current_attr };
+                }
+            }
+            2 => { // Clear whole line
+                for x in 0..self.width {
+                    self.buffer[row_start + x] = Cell { char: ' ', attr: self.current_attr };
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn set_cursor(&mut self, x: usize, y: usize) {
+        self.cursor_x = x.min(self.width.saturating_sub(1));
+        self.cursor_y = if self.origin_mode {
+            (self.scroll_region.top + y).min(self.scroll_region.bottom)
+        } else {
+            y.min(self.height.saturating_sub(1))
+        };
+        self.snap_cursor_x();
+    }
+
+    /// If the cursor landed on the second half of a wide glyph, step back
+    /// onto its lead cell so motion never leaves the cursor mid-character.
+    pub(crate) fn snap_cursor_x(&mut self) {
+        let idx = self.cursor_y * self.width + self.cursor_x;
+        if self.cursor_x > 0 && self.buffer.get(idx).is_some_and(Cell::is_continuation) {
+            self.cursor_x -= 1;
+        }
+    }
+
+    pub fn save_cursor(&mut self) {
+        self.saved_cursor_x = self.cursor_x;
+        self.saved_cursor_y = self.cursor_y;
+        self.saved_attr = self.current_attr;
+    }
+
+    pub fn restore_cursor(&mut self) {
+        self.cursor_x = self.saved_cursor_x;
+        self.cursor_y = self.saved_cursor_y;
+        self.current_attr = self.saved_attr;
+    }
+}
+
+fn push_decimal(out: &mut Vec<u8>, mut n: usize) {
+    if n == 0 {
+        out.push(b'0');
+        return;
+    }
+    let start = out.len();
+    while n > 0 {
+        out.push(b'0' + (n % 10) as u8);
+        n /= 10;
+    }
+    out[start..].reverse();
+}
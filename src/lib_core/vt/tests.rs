@@ -1,10 +1,10 @@
 use super::*;
-use crate::lib::vt::screen::{Color, Cell};
+use crate::lib_core::vt::screen::{Color, Cell, CursorStyle};
 
 struct MockDriver;
 impl TerminalDriver for MockDriver {
     fn draw_cell(&mut self, _x: usize, _y: usize, _cell: Cell) {}
-    fn move_cursor(&mut self, _x: usize, _y: usize) {}
+    fn move_cursor(&mut self, _x: usize, _y: usize, _style: CursorStyle) {}
     fn clear_screen(&mut self) {}
     fn set_title(&mut self, _title: &str) {}
 }
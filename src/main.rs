@@ -6,11 +6,36 @@ extern crate kernel;
 
 use core::panic::PanicInfo;
 use kernel::hwinfo;
+use kernel::process::loader::TargetArch;
 
 // Panic handler is in kernel library
 
+/// The architecture this kernel binary was actually built for, for
+/// `userspace::spawn_init` to pick the right embedded initramfs --
+/// mirrors `syscall::current_arch`'s own cfg dispatch.
+fn current_arch() -> TargetArch {
+    #[cfg(target_arch = "x86_64")]
+    {
+        TargetArch::X86_64
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        TargetArch::AArch64
+    }
+
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        TargetArch::X86_64
+    }
+}
+
+/// Idle until the next interrupt. Once `arch::init()` has installed a
+/// vector table and unmasked interrupts, this is a real interrupt-driven
+/// wait rather than a bare spin -- the CPU wakes for the timer tick (or
+/// any other IRQ) and the scheduler takes it from there.
 #[inline]
-fn hlt_loop() -> ! {
+fn idle_loop() -> ! {
     loop {
         #[cfg(target_arch = "x86_64")]
         unsafe {
@@ -36,15 +61,28 @@ pub extern "C" fn kmain(hw_info: *const hwinfo::HardwareInfo) -> ! {
     // Initialize the system
     kernel::drivers::serial::SERIAL1.lock().init();
 
-    let msg = b"Kernel: Serial Initialized.\n";
-    {
-        let mut serial = kernel::drivers::serial::SERIAL1.lock();
-        for &b in msg {
-            serial.send(b);
-        }
-    }
+    // Serial (and the VGA console it writes alongside) is ready now, so
+    // replay anything logged during earlier boot into the real sink and
+    // switch the ring-logger to pass-through.
+    kernel::lib_core::logger::attach_sink(&kernel::lib_core::logger::CONSOLE_SINK);
+    kernel::log_info!("Serial initialized");
+
+    kernel::arch::init();
+    kernel::log_info!("Interrupt controller initialized");
 
-    // TODO: Initialize drivers, filesystem, etc.
+    kernel::cpu::init::init();
+    kernel::cpu::init::smp_init();
+    kernel::log_info!("SMP bring-up complete");
+
+    // TODO: no bootloader handoff for the raw command line exists yet
+    // (`hwinfo::HardwareInfo` has no field for it), so this runs
+    // `userspace::spawn_init` against an empty line -- `Cmdline::parse`
+    // then falls back to its own default `init=` path.
+    let arch = current_arch();
+    match kernel::userspace::spawn_init("", arch) {
+        Some(pid) => kernel::log_info!("spawned init as pid {}", pid.0),
+        None => kernel::log_info!("no init found in the initramfs"),
+    }
 
-    hlt_loop()
+    idle_loop()
 }
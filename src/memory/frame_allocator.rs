@@ -48,6 +48,12 @@ impl Iterator for FrameIter {
 pub trait FrameAllocator {
     fn allocate_frame(&mut self) -> Option<Frame>;
     fn deallocate_frame(&mut self, frame: Frame);
+    /// Allocate `count` physically contiguous frames whose starting frame
+    /// index is a multiple of `align_frames`, for DMA descriptor rings and
+    /// page tables that need more than single-frame alignment.
+    fn allocate_contiguous(&mut self, count: usize, align_frames: usize) -> Option<Frame>;
+    /// Return a block previously handed out by `allocate_contiguous`.
+    fn deallocate_contiguous(&mut self, frame: Frame, count: usize);
 }
 
 pub struct BitmapFrameAllocator {
@@ -73,12 +79,16 @@ impl BitmapFrameAllocator {
 
         let total_frames = regions.iter().map(|r| r.size / PAGE_SIZE).sum::<usize>();
         let bitmap_size = (total_frames + 63) / 64;
-        
+
         self.bitmap.clear();
         self.bitmap.resize(bitmap_size, !0u64);
         self.next_free = 0;
     }
 
+    pub fn regions(&self) -> &[MemoryRegion] {
+        &self.memory_regions
+    }
+
     fn frame_to_bit_index(&self, frame: Frame) -> Option<usize> {
         let mut offset = 0;
         for region in &self.memory_regions {
@@ -106,6 +116,68 @@ impl BitmapFrameAllocator {
         }
         None
     }
+
+    /// Whether bits `start..start + count` all fall within a single region
+    /// (so the run is truly physically contiguous, not just contiguous bit
+    /// indices that happen to straddle a gap between two regions).
+    fn run_within_one_region(&self, start: usize, count: usize) -> bool {
+        let mut offset = 0;
+        for region in &self.memory_regions {
+            let region_frames = region.size / PAGE_SIZE;
+            if start >= offset && start < offset + region_frames {
+                return start + count <= offset + region_frames;
+            }
+            offset += region_frames;
+        }
+        false
+    }
+
+    fn run_is_free(&self, start: usize, count: usize) -> bool {
+        (start..start + count).all(|idx| {
+            self.bitmap[idx / 64] & (1u64 << (idx % 64)) != 0
+        })
+    }
+
+    /// Find the lowest bit index `start` such that `[start, start+count)`
+    /// is a free run within one region, `start % align_frames == 0`.
+    /// Skips whole 64-bit words that are entirely allocated (`0`) instead
+    /// of testing every bit in them.
+    fn find_contiguous_free(&self, count: usize, align_frames: usize) -> Option<usize> {
+        if count == 0 {
+            return None;
+        }
+        let align = align_frames.max(1);
+        let total_bits = self.bitmap.len() * 64;
+
+        let mut start = 0;
+        while start + count <= total_bits {
+            if start % align != 0 {
+                start += align - (start % align);
+                continue;
+            }
+            if self.bitmap[start / 64] == 0 {
+                start = (start / 64 + 1) * 64;
+                continue;
+            }
+            if self.run_within_one_region(start, count) && self.run_is_free(start, count) {
+                return Some(start);
+            }
+            start += align;
+        }
+        None
+    }
+
+    fn set_run(&mut self, start: usize, count: usize, free: bool) {
+        for idx in start..start + count {
+            let word_idx = idx / 64;
+            let bit_idx = idx % 64;
+            if free {
+                self.bitmap[word_idx] |= 1u64 << bit_idx;
+            } else {
+                self.bitmap[word_idx] &= !(1u64 << bit_idx);
+            }
+        }
+    }
 }
 
 impl FrameAllocator for BitmapFrameAllocator {
@@ -134,14 +206,243 @@ impl FrameAllocator for BitmapFrameAllocator {
             self.bitmap[word_idx] |= 1u64 << bit_idx;
         }
     }
+
+    fn allocate_contiguous(&mut self, count: usize, align_frames: usize) -> Option<Frame> {
+        let start = self.find_contiguous_free(count, align_frames)?;
+        self.set_run(start, count, false);
+        self.bit_index_to_frame(start)
+    }
+
+    fn deallocate_contiguous(&mut self, frame: Frame, count: usize) {
+        if let Some(start) = self.frame_to_bit_index(frame) {
+            self.set_run(start, count, true);
+        }
+    }
+}
+
+/// Highest buddy order `BuddyFrameAllocator` will track: order `k` holds
+/// blocks of `2^k` frames, so `MAX_ORDER` of 10 tops out at 1024-frame
+/// (4 MiB at a 4 KiB page size) blocks.
+pub const MAX_ORDER: usize = 10;
+
+/// Order-based free-list frame allocator: free lists `0..=MAX_ORDER` each
+/// hold the starting frame numbers of free `2^order`-frame blocks.
+/// Allocation rounds a request up to the next power of two, pops from
+/// that order's list, or recursively splits the smallest larger block,
+/// pushing the unused buddy half back down. Deallocation computes a
+/// block's buddy by XORing its frame number with its size and merges
+/// upward while the buddy is free and still inside the same region.
+pub struct BuddyFrameAllocator {
+    memory_regions: Vec<MemoryRegion>,
+    free_lists: [Vec<u64>; MAX_ORDER + 1],
+}
+
+impl BuddyFrameAllocator {
+    pub fn new() -> Self {
+        const EMPTY: Vec<u64> = Vec::new();
+        Self {
+            memory_regions: Vec::new(),
+            free_lists: [EMPTY; MAX_ORDER + 1],
+        }
+    }
+
+    /// Carve each region into maximal aligned power-of-two blocks (the
+    /// largest order whose block both starts on a `2^order`-frame boundary
+    /// and fits before the region's end), seeding `free_lists` with them.
+    pub fn init(&mut self, regions: &[MemoryRegion]) {
+        self.memory_regions.clear();
+        for list in &mut self.free_lists {
+            list.clear();
+        }
+
+        for region in regions {
+            self.memory_regions.push(*region);
+
+            let start_frame = region.start.0 / PAGE_SIZE as u64;
+            let end_frame = start_frame + (region.size / PAGE_SIZE) as u64;
+            let mut frame = start_frame;
+
+            while frame < end_frame {
+                let mut order = MAX_ORDER;
+                while order > 0 {
+                    let block_frames = 1u64 << order;
+                    if frame % block_frames == 0 && frame + block_frames <= end_frame {
+                        break;
+                    }
+                    order -= 1;
+                }
+                self.free_lists[order].push(frame);
+                frame += 1u64 << order;
+            }
+        }
+    }
+
+    fn order_for_count(count: usize) -> usize {
+        let mut order = 0;
+        while (1usize << order) < count.max(1) {
+            order += 1;
+        }
+        order
+    }
+
+    fn region_containing(&self, start_frame: u64, frames: u64) -> bool {
+        let start = PhysAddr(start_frame * PAGE_SIZE as u64);
+        let end = PhysAddr((start_frame + frames) * PAGE_SIZE as u64);
+        self.memory_regions.iter().any(|r| start >= r.start && end <= r.end())
+    }
+
+    fn allocate_order(&mut self, order: usize) -> Option<u64> {
+        if order > MAX_ORDER {
+            return None;
+        }
+        if let Some(frame) = self.free_lists[order].pop() {
+            return Some(frame);
+        }
+
+        let block = self.allocate_order(order + 1)?;
+        let half_frames = 1u64 << order;
+        self.free_lists[order].push(block + half_frames);
+        Some(block)
+    }
+
+    fn deallocate_order(&mut self, frame: u64, order: usize) {
+        if order >= MAX_ORDER {
+            self.free_lists[order].push(frame);
+            return;
+        }
+
+        let buddy = frame ^ (1u64 << order);
+        let merged_start = frame.min(buddy);
+        if self.region_containing(merged_start, 1u64 << (order + 1)) {
+            if let Some(pos) = self.free_lists[order].iter().position(|&f| f == buddy) {
+                self.free_lists[order].remove(pos);
+                self.deallocate_order(merged_start, order + 1);
+                return;
+            }
+        }
+
+        self.free_lists[order].push(frame);
+    }
+
+    fn frame_number(&self, frame: Frame) -> u64 {
+        frame.start_address.0 / PAGE_SIZE as u64
+    }
+
+    pub fn regions(&self) -> &[MemoryRegion] {
+        &self.memory_regions
+    }
+}
+
+impl FrameAllocator for BuddyFrameAllocator {
+    fn allocate_frame(&mut self) -> Option<Frame> {
+        let frame = self.allocate_order(0)?;
+        Some(Frame { start_address: PhysAddr(frame * PAGE_SIZE as u64) })
+    }
+
+    fn deallocate_frame(&mut self, frame: Frame) {
+        let frame_number = self.frame_number(frame);
+        self.deallocate_order(frame_number, 0);
+    }
+
+    fn allocate_contiguous(&mut self, count: usize, align_frames: usize) -> Option<Frame> {
+        let order = Self::order_for_count(count).max(Self::order_for_count(align_frames));
+        let frame = self.allocate_order(order)?;
+        Some(Frame { start_address: PhysAddr(frame * PAGE_SIZE as u64) })
+    }
+
+    fn deallocate_contiguous(&mut self, frame: Frame, count: usize) {
+        let order = Self::order_for_count(count);
+        let frame_number = self.frame_number(frame);
+        self.deallocate_order(frame_number, order);
+    }
 }
 
-pub static FRAME_ALLOCATOR: Mutex<Option<BitmapFrameAllocator>> = Mutex::new(None);
+/// Which concrete allocator backs `FRAME_ALLOCATOR`.
+pub enum FrameAllocatorKind {
+    Bitmap,
+    Buddy,
+}
 
+/// Dispatches `FrameAllocator` calls to whichever backend `init_frame_allocator_with`
+/// selected, so callers of the free functions below don't need to know or
+/// care which one is active.
+pub enum FrameAllocatorBackend {
+    Bitmap(BitmapFrameAllocator),
+    Buddy(BuddyFrameAllocator),
+}
+
+impl FrameAllocator for FrameAllocatorBackend {
+    fn allocate_frame(&mut self) -> Option<Frame> {
+        match self {
+            FrameAllocatorBackend::Bitmap(a) => a.allocate_frame(),
+            FrameAllocatorBackend::Buddy(a) => a.allocate_frame(),
+        }
+    }
+
+    fn deallocate_frame(&mut self, frame: Frame) {
+        match self {
+            FrameAllocatorBackend::Bitmap(a) => a.deallocate_frame(frame),
+            FrameAllocatorBackend::Buddy(a) => a.deallocate_frame(frame),
+        }
+    }
+
+    fn allocate_contiguous(&mut self, count: usize, align_frames: usize) -> Option<Frame> {
+        match self {
+            FrameAllocatorBackend::Bitmap(a) => a.allocate_contiguous(count, align_frames),
+            FrameAllocatorBackend::Buddy(a) => a.allocate_contiguous(count, align_frames),
+        }
+    }
+
+    fn deallocate_contiguous(&mut self, frame: Frame, count: usize) {
+        match self {
+            FrameAllocatorBackend::Bitmap(a) => a.deallocate_contiguous(frame, count),
+            FrameAllocatorBackend::Buddy(a) => a.deallocate_contiguous(frame, count),
+        }
+    }
+}
+
+impl FrameAllocatorBackend {
+    pub fn regions(&self) -> &[MemoryRegion] {
+        match self {
+            FrameAllocatorBackend::Bitmap(a) => a.regions(),
+            FrameAllocatorBackend::Buddy(a) => a.regions(),
+        }
+    }
+}
+
+pub static FRAME_ALLOCATOR: Mutex<Option<FrameAllocatorBackend>> = Mutex::new(None);
+
+/// Snapshot of every region the active frame allocator was initialized
+/// with, for callers (like the core-dump emitter) that need to know what
+/// physical memory is actually mapped rather than walking frame-by-frame.
+pub fn mapped_regions() -> Vec<MemoryRegion> {
+    match FRAME_ALLOCATOR.lock().as_ref() {
+        Some(backend) => backend.regions().to_vec(),
+        None => Vec::new(),
+    }
+}
+
+/// Initialize `FRAME_ALLOCATOR` with the bitmap backend (the historical
+/// default); use `init_frame_allocator_with` to pick the buddy backend
+/// instead.
 pub fn init_frame_allocator(regions: &[MemoryRegion]) {
-    let mut allocator = BitmapFrameAllocator::new();
-    allocator.init(regions);
-    *FRAME_ALLOCATOR.lock() = Some(allocator);
+    init_frame_allocator_with(regions, FrameAllocatorKind::Bitmap);
+}
+
+pub fn init_frame_allocator_with(regions: &[MemoryRegion], kind: FrameAllocatorKind) {
+    let backend = match kind {
+        FrameAllocatorKind::Bitmap => {
+            let mut allocator = BitmapFrameAllocator::new();
+            allocator.init(regions);
+            FrameAllocatorBackend::Bitmap(allocator)
+        }
+        FrameAllocatorKind::Buddy => {
+            let mut allocator = BuddyFrameAllocator::new();
+            allocator.init(regions);
+            FrameAllocatorBackend::Buddy(allocator)
+        }
+    };
+    *FRAME_ALLOCATOR.lock() = Some(backend);
 }
 
 pub fn allocate_frame() -> Option<Frame> {
@@ -154,6 +455,78 @@ pub fn deallocate_frame(frame: Frame) {
     }
 }
 
+pub fn allocate_contiguous(count: usize, align_frames: usize) -> Option<Frame> {
+    FRAME_ALLOCATOR.lock().as_mut()?.allocate_contiguous(count, align_frames)
+}
+
+pub fn deallocate_contiguous(frame: Frame, count: usize) {
+    if let Some(ref mut allocator) = *FRAME_ALLOCATOR.lock() {
+        allocator.deallocate_contiguous(frame, count);
+    }
+}
+
+/// Share counts for copy-on-write frames, as `(frame number, count)` pairs
+/// scanned linearly -- matching this kernel's other small bounded
+/// registries (`scheme::PROVIDERS`, `syscall::WAITERS`) rather than a
+/// dense `Vec` indexed by frame number, which would have to be sized to
+/// all of physical memory up front. A frame absent here has exactly one
+/// owner (the common, unshared case), so [`cow_refcount`] treats "not
+/// found" as `0` and [`release_cow_frame`] frees such a frame outright.
+static COW_REFCOUNTS: Mutex<Vec<(u64, u16)>> = Mutex::new(Vec::new());
+
+fn frame_number(frame: Frame) -> u64 {
+    frame.start_address.0 / PAGE_SIZE as u64
+}
+
+/// Record that `frame` has gained a copy-on-write owner: the first call
+/// after a frame starts out singly-owned brings it to 2 sharers (the
+/// process that had it plus the new one copy-on-write fork just gave a
+/// reference to); each further call (e.g. that child forking again)
+/// bumps the count once more.
+pub fn cow_share(frame: Frame) {
+    let key = frame_number(frame);
+    let mut counts = COW_REFCOUNTS.lock();
+    if let Some(entry) = counts.iter_mut().find(|(f, _)| *f == key) {
+        entry.1 += 1;
+    } else {
+        counts.push((key, 2));
+    }
+}
+
+/// How many copy-on-write owners `frame` currently has, or `0` if it
+/// isn't (or is no longer) shared.
+pub fn cow_refcount(frame: Frame) -> u16 {
+    let key = frame_number(frame);
+    COW_REFCOUNTS
+        .lock()
+        .iter()
+        .find(|(f, _)| *f == key)
+        .map(|(_, count)| *count)
+        .unwrap_or(0)
+}
+
+/// Drop one owner's claim on a copy-on-write `frame`. While more than two
+/// owners remain tracked, only decrements the count. Dropping from 2 to 1
+/// owner just removes the entry -- the frame goes back to the untracked,
+/// singly-owned state, and the one remaining owner must NOT have it freed
+/// out from under them. Only a release that finds `frame` untracked (`pos
+/// == None`, i.e. it already had exactly one owner) actually deallocates
+/// it via [`deallocate_frame`].
+pub fn release_cow_frame(frame: Frame) {
+    let key = frame_number(frame);
+    let mut counts = COW_REFCOUNTS.lock();
+    if let Some(pos) = counts.iter().position(|(f, _)| *f == key) {
+        if counts[pos].1 > 2 {
+            counts[pos].1 -= 1;
+        } else {
+            counts.remove(pos);
+        }
+        return;
+    }
+    drop(counts);
+    deallocate_frame(frame);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,4 +548,215 @@ mod tests {
         let frame3 = allocator.allocate_frame();
         assert!(frame3.is_some());
     }
+
+    #[test]
+    fn test_contiguous_allocation_skips_hole() {
+        let regions = [MemoryRegion::new(PhysAddr::new(0x100000), 8 * PAGE_SIZE)];
+        let mut allocator = BitmapFrameAllocator::new();
+        allocator.init(&regions);
+
+        // Allocate frames 0 and 2 individually so frame 1 is a hole that
+        // can't anchor a 2-frame contiguous run.
+        let f0 = allocator.allocate_frame().unwrap();
+        let f1 = allocator.allocate_frame().unwrap();
+        assert_eq!(f0.start_address, PhysAddr::new(0x100000));
+        assert_eq!(f1.start_address, PhysAddr::new(0x100000 + PAGE_SIZE as u64));
+        allocator.deallocate_frame(f0);
+
+        // Frame 0 alone is free; a 2-frame request must skip over it (and
+        // the still-allocated frame 1) to land on frames 2-3.
+        let run = allocator.allocate_contiguous(2, 1).unwrap();
+        assert_eq!(run.start_address, PhysAddr::new(0x100000 + 2 * PAGE_SIZE as u64));
+    }
+
+    #[test]
+    fn test_contiguous_allocation_respects_alignment() {
+        let regions = [MemoryRegion::new(PhysAddr::new(0x100000), 8 * PAGE_SIZE)];
+        let mut allocator = BitmapFrameAllocator::new();
+        allocator.init(&regions);
+
+        // Take frame 0 so the only way to satisfy a 2-frame run aligned to
+        // 2 frames is starting at frame 2, not frame 1.
+        allocator.allocate_frame().unwrap();
+
+        let run = allocator.allocate_contiguous(2, 2).unwrap();
+        assert_eq!(run.start_address, PhysAddr::new(0x100000 + 2 * PAGE_SIZE as u64));
+    }
+
+    #[test]
+    fn test_contiguous_allocation_fails_across_region_boundary() {
+        let regions = [
+            MemoryRegion::new(PhysAddr::new(0x100000), 2 * PAGE_SIZE),
+            MemoryRegion::new(PhysAddr::new(0x200000), 2 * PAGE_SIZE),
+        ];
+        let mut allocator = BitmapFrameAllocator::new();
+        allocator.init(&regions);
+
+        // The bitmap has 4 contiguous free bits, but the last frame of the
+        // first region and the first frame of the second aren't physically
+        // adjacent, so a 3-frame request spanning that boundary must fail.
+        assert!(allocator.allocate_contiguous(3, 1).is_none());
+        assert!(allocator.allocate_contiguous(2, 1).is_some());
+    }
+
+    #[test]
+    fn test_deallocate_contiguous_frees_whole_run() {
+        let regions = [MemoryRegion::new(PhysAddr::new(0x100000), 4 * PAGE_SIZE)];
+        let mut allocator = BitmapFrameAllocator::new();
+        allocator.init(&regions);
+
+        let run = allocator.allocate_contiguous(4, 1).unwrap();
+        assert!(allocator.allocate_contiguous(1, 1).is_none());
+
+        allocator.deallocate_contiguous(run, 4);
+        assert_eq!(allocator.allocate_contiguous(4, 1), Some(run));
+    }
+
+    #[test]
+    fn test_buddy_init_carves_maximal_aligned_blocks() {
+        // 5 frames can't be one block: it carves as a 4-frame block (order 2)
+        // plus a single leftover frame (order 0).
+        let regions = [MemoryRegion::new(PhysAddr::new(0), 5 * PAGE_SIZE)];
+        let mut allocator = BuddyFrameAllocator::new();
+        allocator.init(&regions);
+
+        assert_eq!(allocator.free_lists[2], alloc_vec(&[0]));
+        assert_eq!(allocator.free_lists[0], alloc_vec(&[4]));
+    }
+
+    #[test]
+    fn test_buddy_allocate_splits_larger_block() {
+        let regions = [MemoryRegion::new(PhysAddr::new(0), 4 * PAGE_SIZE)];
+        let mut allocator = BuddyFrameAllocator::new();
+        allocator.init(&regions);
+
+        // Only one order-2 (4-frame) block exists; requesting a single
+        // frame must split it down to order 0, leaving its buddy at order 1.
+        let frame = allocator.allocate_frame().unwrap();
+        assert_eq!(frame.start_address, PhysAddr::new(0));
+        assert_eq!(allocator.free_lists[1], alloc_vec(&[2]));
+        assert!(allocator.free_lists[2].is_empty());
+    }
+
+    #[test]
+    fn test_buddy_deallocate_merges_buddies() {
+        let regions = [MemoryRegion::new(PhysAddr::new(0), 2 * PAGE_SIZE)];
+        let mut allocator = BuddyFrameAllocator::new();
+        allocator.init(&regions);
+
+        let f0 = allocator.allocate_frame().unwrap();
+        let f1 = allocator.allocate_frame().unwrap();
+        assert!(allocator.free_lists[1].is_empty());
+
+        allocator.deallocate_frame(f0);
+        allocator.deallocate_frame(f1);
+
+        // Freeing both buddy halves should merge them back into one
+        // order-1 block rather than leaving two order-0 entries.
+        assert_eq!(allocator.free_lists[1], alloc_vec(&[0]));
+        assert!(allocator.free_lists[0].is_empty());
+    }
+
+    #[test]
+    fn test_buddy_deallocate_does_not_merge_across_regions() {
+        let regions = [
+            MemoryRegion::new(PhysAddr::new(0), PAGE_SIZE),
+            MemoryRegion::new(PhysAddr::new(PAGE_SIZE as u64), PAGE_SIZE),
+        ];
+        let mut allocator = BuddyFrameAllocator::new();
+        allocator.init(&regions);
+
+        let f0 = allocator.allocate_frame().unwrap();
+        let f1 = allocator.allocate_frame().unwrap();
+        allocator.deallocate_frame(f0);
+        allocator.deallocate_frame(f1);
+
+        // Frames 0 and 1 are buddies by address arithmetic alone, but they
+        // belong to two separate regions, so they must stay as two
+        // independent order-0 blocks instead of merging into an order-1 one.
+        assert_eq!(allocator.free_lists[0].len(), 2);
+        assert!(allocator.free_lists[1].is_empty());
+    }
+
+    #[test]
+    fn test_buddy_contiguous_allocation_rounds_up_to_order() {
+        let regions = [MemoryRegion::new(PhysAddr::new(0), 4 * PAGE_SIZE)];
+        let mut allocator = BuddyFrameAllocator::new();
+        allocator.init(&regions);
+
+        // A 3-frame request rounds up to order 2 (4 frames), consuming the
+        // whole region in one block.
+        let run = allocator.allocate_contiguous(3, 1).unwrap();
+        assert_eq!(run.start_address, PhysAddr::new(0));
+        assert!(allocator.allocate_frame().is_none());
+
+        allocator.deallocate_contiguous(run, 3);
+        assert_eq!(allocator.allocate_contiguous(3, 1), Some(run));
+    }
+
+    fn alloc_vec(items: &[u64]) -> Vec<u64> {
+        let mut v = Vec::new();
+        v.extend_from_slice(items);
+        v
+    }
+
+    // `COW_REFCOUNTS` is a process-wide global, so these use addresses far
+    // outside any other test's range to avoid cross-test interference.
+    #[test]
+    fn test_cow_share_and_refcount() {
+        let frame = Frame { start_address: PhysAddr::new(0x9000_1000) };
+        assert_eq!(cow_refcount(frame), 0);
+
+        cow_share(frame);
+        assert_eq!(cow_refcount(frame), 2);
+
+        cow_share(frame);
+        assert_eq!(cow_refcount(frame), 3);
+
+        release_cow_frame(frame);
+        assert_eq!(cow_refcount(frame), 2);
+    }
+
+    #[test]
+    fn test_release_cow_frame_drops_tracking_at_last_owner() {
+        let frame = Frame { start_address: PhysAddr::new(0x9000_2000) };
+        cow_share(frame);
+        assert_eq!(cow_refcount(frame), 2);
+
+        release_cow_frame(frame);
+        assert_eq!(cow_refcount(frame), 0);
+
+        let unshared = Frame { start_address: PhysAddr::new(0x9000_3000) };
+        release_cow_frame(unshared);
+        assert_eq!(cow_refcount(unshared), 0);
+    }
+
+    // Regression test for a use-after-free: releasing one owner of a
+    // 2-owner frame must not actually deallocate it while the other owner
+    // is still mapping it COW. Refcount-only assertions can't see this, so
+    // this drives the real global allocator and checks the frame doesn't
+    // come back until the last owner releases it too.
+    #[test]
+    fn test_release_cow_frame_does_not_free_while_other_owner_remains() {
+        let base = PhysAddr::new(0xa000_0000);
+        init_frame_allocator(&[MemoryRegion::new(base, PAGE_SIZE)]);
+        let frame = allocate_frame().expect("single-frame region should yield one frame");
+        assert_eq!(frame.start_address, base);
+
+        cow_share(frame);
+        assert_eq!(cow_refcount(frame), 2);
+
+        release_cow_frame(frame);
+        assert_eq!(cow_refcount(frame), 0);
+        assert!(
+            allocate_frame().is_none(),
+            "frame was freed while the other COW owner still holds it"
+        );
+
+        release_cow_frame(frame);
+        assert!(
+            allocate_frame().is_some(),
+            "frame should be free once the last owner releases it"
+        );
+    }
 }
@@ -126,25 +126,114 @@ impl LinkedListAllocator {
     }
 }
 
+/// Power-of-two size classes backing the fast path. A request with
+/// `size.max(align)` above the largest class falls through to the
+/// linked-list allocator verbatim.
+const BLOCK_SIZES: [usize; 9] = [8, 16, 32, 64, 128, 256, 512, 1024, 2048];
+
+/// How many blocks of a class to carve from the fallback allocator when a
+/// class's free list runs dry.
+const BLOCKS_PER_REFILL: usize = 64;
+
+fn list_index(size: usize) -> Option<usize> {
+    BLOCK_SIZES.iter().position(|&s| s >= size)
+}
+
+/// Segregated free-list front end for [`LinkedListAllocator`]. Each class's
+/// free list is threaded through the first word of its own blocks, so no
+/// separate header is needed; the invariant `layout.align() <= class_size`
+/// always holds because the class is chosen from `size.max(align)`.
+pub struct FixedBlockAllocator {
+    list_heads: [Option<NonNull<FreeBlock>>; BLOCK_SIZES.len()],
+    fallback: LinkedListAllocator,
+}
+
+unsafe impl Send for FixedBlockAllocator {}
+unsafe impl Sync for FixedBlockAllocator {}
+
+impl FixedBlockAllocator {
+    pub const fn new() -> Self {
+        FixedBlockAllocator {
+            list_heads: [None; BLOCK_SIZES.len()],
+            fallback: LinkedListAllocator::new(),
+        }
+    }
+
+    pub unsafe fn init(&mut self, heap_start: usize, heap_size: usize) {
+        self.fallback.init(heap_start, heap_size);
+    }
+
+    /// Pull `BLOCKS_PER_REFILL` blocks of `class_size` out of the fallback
+    /// allocator and thread them onto `index`'s free list.
+    fn refill(&mut self, index: usize) -> Option<()> {
+        let class_size = BLOCK_SIZES[index];
+        let layout = Layout::from_size_align(class_size * BLOCKS_PER_REFILL, class_size).ok()?;
+        let run = self.fallback.alloc_from_region(layout)?;
+        let base = run.as_ptr() as usize;
+
+        for i in 0..BLOCKS_PER_REFILL {
+            let block = (base + i * class_size) as *mut FreeBlock;
+            unsafe {
+                (*block).next = self.list_heads[index];
+            }
+            self.list_heads[index] = NonNull::new(block);
+        }
+
+        Some(())
+    }
+
+    fn alloc(&mut self, layout: Layout) -> *mut u8 {
+        let size = layout.size().max(layout.align());
+        let Some(index) = list_index(size) else {
+            return self
+                .fallback
+                .alloc_from_region(layout)
+                .map_or(core::ptr::null_mut(), |p| p.as_ptr());
+        };
+
+        if self.list_heads[index].is_none() && self.refill(index).is_none() {
+            return core::ptr::null_mut();
+        }
+
+        match self.list_heads[index] {
+            Some(block) => {
+                self.list_heads[index] = unsafe { block.as_ref().next };
+                block.as_ptr() as *mut u8
+            }
+            None => core::ptr::null_mut(),
+        }
+    }
+
+    unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        let size = layout.size().max(layout.align());
+        match list_index(size) {
+            Some(index) if index < BLOCK_SIZES.len() => {
+                let block = ptr as *mut FreeBlock;
+                (*block).next = self.list_heads[index];
+                self.list_heads[index] = NonNull::new(block);
+            }
+            _ => self.fallback.dealloc_to_region(ptr, layout),
+        }
+    }
+}
+
 unsafe impl GlobalAlloc for LockedHeap {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
         let mut allocator = self.0.lock();
-        allocator
-            .alloc_from_region(layout)
-            .map_or(core::ptr::null_mut(), |p| p.as_ptr())
+        allocator.alloc(layout)
     }
 
     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
         let mut allocator = self.0.lock();
-        allocator.dealloc_to_region(ptr, layout);
+        allocator.dealloc(ptr, layout);
     }
 }
 
-pub struct LockedHeap(Mutex<LinkedListAllocator>);
+pub struct LockedHeap(Mutex<FixedBlockAllocator>);
 
 impl LockedHeap {
     pub const fn new() -> Self {
-        LockedHeap(Mutex::new(LinkedListAllocator::new()))
+        LockedHeap(Mutex::new(FixedBlockAllocator::new()))
     }
 
     pub unsafe fn init(&self, heap_start: usize, heap_size: usize) {
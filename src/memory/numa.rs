@@ -14,17 +14,70 @@ pub struct NumaNode {
     pub id: usize,
 }
 
+#[derive(Debug, Clone)]
+struct FreeBlock {
+    start: PhysAddr,
+    size: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct NumaMemoryRegion {
     pub region: MemoryRegion,
     pub node: NumaNode,
     pub distance: u8,
+    /// Bump offset from `region.start`, in bytes, for allocations that
+    /// haven't been freed (and so can't come from `free_list`) yet.
+    cursor: usize,
+    free_list: Vec<FreeBlock>,
+}
+
+impl NumaMemoryRegion {
+    pub fn new(region: MemoryRegion, node: NumaNode, distance: u8) -> Self {
+        Self {
+            region,
+            node,
+            distance,
+            cursor: 0,
+            free_list: Vec::new(),
+        }
+    }
+
+    fn try_reclaim(&mut self, size: usize) -> Option<PhysAddr> {
+        let index = self.free_list.iter().position(|block| block.size >= size)?;
+        Some(self.free_list.remove(index).start)
+    }
+
+    fn try_bump(&mut self, size: usize) -> Option<PhysAddr> {
+        if self.cursor + size > self.region.size {
+            return None;
+        }
+        let addr = PhysAddr(self.region.start.as_u64() + self.cursor as u64);
+        self.cursor += size;
+        Some(addr)
+    }
+}
+
+/// How `NumaTopology::allocate` should pick a node for a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocPolicy {
+    /// Serve from `node`, falling back to the next-closest node (by
+    /// `get_distance`) if it can't satisfy the request.
+    Local(NumaNode),
+    /// Same fallback behavior as `Local`; kept distinct so callers can
+    /// express "I'd like this node, but anywhere nearby is fine" intent.
+    Preferred(NumaNode),
+    /// Round-robin across all nodes on successive allocations.
+    Interleave,
+    /// Serve from `node` only; fail outright if it can't satisfy the
+    /// request rather than spilling onto another node.
+    Bind(NumaNode),
 }
 
 pub struct NumaTopology {
     nodes: Vec<NumaNode>,
     regions: Vec<NumaMemoryRegion>,
     distance_matrix: Vec<Vec<u8>>,
+    interleave_cursor: usize,
 }
 
 impl NumaTopology {
@@ -33,6 +86,7 @@ impl NumaTopology {
             nodes: Vec::new(),
             regions: Vec::new(),
             distance_matrix: Vec::new(),
+            interleave_cursor: 0,
         }
     }
 
@@ -89,15 +143,77 @@ impl NumaTopology {
         closest
     }
 
-    pub fn allocate_from_node(&self, node: NumaNode, size: usize) -> Option<PhysAddr> {
-        for region in self.get_node_regions(node) {
-            if region.region.size >= size {
-                return Some(region.region.start);
+    /// Hand out `size` bytes from `node` alone: first-fit against its
+    /// regions' free lists, then bump-allocate from whichever region has
+    /// room. Returns `None` if `node` can't satisfy the request.
+    pub fn allocate_from_node(&mut self, node: NumaNode, size: usize) -> Option<PhysAddr> {
+        for region in self.regions.iter_mut().filter(|r| r.node == node) {
+            if let Some(addr) = region.try_reclaim(size) {
+                return Some(addr);
+            }
+        }
+        for region in self.regions.iter_mut().filter(|r| r.node == node) {
+            if let Some(addr) = region.try_bump(size) {
+                return Some(addr);
             }
         }
         None
     }
 
+    /// Nodes other than `from`, ordered by ascending `get_distance`, for
+    /// walking outward when `from` itself can't satisfy a request.
+    fn candidates_by_distance(&self, from: NumaNode) -> Vec<NumaNode> {
+        let mut candidates: Vec<(u8, NumaNode)> = self
+            .nodes
+            .iter()
+            .filter(|node| **node != from)
+            .filter_map(|node| self.get_distance(from, *node).map(|distance| (distance, *node)))
+            .collect();
+        candidates.sort_by_key(|(distance, _)| *distance);
+        candidates.into_iter().map(|(_, node)| node).collect()
+    }
+
+    fn allocate_with_fallback(&mut self, node: NumaNode, size: usize) -> Option<(PhysAddr, NumaNode)> {
+        if let Some(addr) = self.allocate_from_node(node, size) {
+            return Some((addr, node));
+        }
+        for candidate in self.candidates_by_distance(node) {
+            if let Some(addr) = self.allocate_from_node(candidate, size) {
+                return Some((addr, candidate));
+            }
+        }
+        None
+    }
+
+    fn allocate_interleaved(&mut self, size: usize) -> Option<(PhysAddr, NumaNode)> {
+        let node_count = self.nodes.len();
+        if node_count == 0 {
+            return None;
+        }
+        for step in 0..node_count {
+            let index = (self.interleave_cursor + step) % node_count;
+            let node = self.nodes[index];
+            if let Some(addr) = self.allocate_from_node(node, size) {
+                self.interleave_cursor = (index + 1) % node_count;
+                return Some((addr, node));
+            }
+        }
+        None
+    }
+
+    /// Policy-driven allocation entry point: returns the address handed
+    /// out plus the node it actually came from (which may differ from a
+    /// `Local`/`Preferred` request's node after a fallback).
+    pub fn allocate(&mut self, policy: AllocPolicy, size: usize) -> Option<(PhysAddr, NumaNode)> {
+        match policy {
+            AllocPolicy::Local(node) | AllocPolicy::Preferred(node) => {
+                self.allocate_with_fallback(node, size)
+            }
+            AllocPolicy::Bind(node) => self.allocate_from_node(node, size).map(|addr| (addr, node)),
+            AllocPolicy::Interleave => self.allocate_interleaved(size),
+        }
+    }
+
     pub fn get_node_for_address(&self, addr: PhysAddr) -> Option<NumaNode> {
         for region in &self.regions {
             if addr >= region.region.start && addr < region.region.end() {
@@ -106,6 +222,18 @@ impl NumaTopology {
         }
         None
     }
+
+    /// Return a previously-allocated block to whichever region's node it
+    /// came from, making it available to future allocations on that node.
+    pub fn free(&mut self, addr: PhysAddr, size: usize) {
+        if let Some(region) = self
+            .regions
+            .iter_mut()
+            .find(|region| addr >= region.region.start && addr < region.region.end())
+        {
+            region.free_list.push(FreeBlock { start: addr, size });
+        }
+    }
 }
 
 pub static NUMA_TOPOLOGY: Mutex<NumaTopology> = Mutex::new(NumaTopology::new());
@@ -125,8 +253,10 @@ pub fn get_current_numa_node() -> NumaNode {
 
 pub fn allocate_numa_local(size: usize) -> Option<PhysAddr> {
     let current_node = get_current_numa_node();
-    let topology = NUMA_TOPOLOGY.lock();
-    topology.allocate_from_node(current_node, size)
+    let mut topology = NUMA_TOPOLOGY.lock();
+    topology
+        .allocate(AllocPolicy::Local(current_node), size)
+        .map(|(addr, _)| addr)
 }
 
 #[cfg(test)]
@@ -151,4 +281,68 @@ mod tests {
         assert_eq!(topology.get_distance(node1, node0), Some(20));
         assert_eq!(topology.get_distance(node0, node0), Some(10));
     }
+
+    fn two_node_topology() -> (NumaTopology, NumaNode, NumaNode) {
+        let mut topology = NumaTopology::new();
+        let node0 = NumaNode { id: 0 };
+        let node1 = NumaNode { id: 1 };
+
+        topology.add_node(node0);
+        topology.add_node(node1);
+        topology.set_distance(node0, node0, 10);
+        topology.set_distance(node0, node1, 20);
+        topology.set_distance(node1, node0, 20);
+        topology.set_distance(node1, node1, 10);
+
+        topology.add_region(NumaMemoryRegion::new(
+            MemoryRegion::new(PhysAddr::new(0x1000), 4096),
+            node0,
+            10,
+        ));
+        topology.add_region(NumaMemoryRegion::new(
+            MemoryRegion::new(PhysAddr::new(0x10000), 8192),
+            node1,
+            10,
+        ));
+
+        (topology, node0, node1)
+    }
+
+    #[test]
+    fn test_bind_fails_without_fallback() {
+        let (mut topology, node0, _node1) = two_node_topology();
+        assert_eq!(topology.allocate(AllocPolicy::Bind(node0), 8192), None);
+    }
+
+    #[test]
+    fn test_local_falls_back_to_closest_node() {
+        let (mut topology, node0, node1) = two_node_topology();
+        // node0's region only has 4096 bytes; the request must spill over
+        // to node1, the only other node in the distance matrix.
+        let (addr, node) = topology.allocate(AllocPolicy::Local(node0), 8192).unwrap();
+        assert_eq!(node, node1);
+        assert_eq!(addr, PhysAddr::new(0x10000));
+    }
+
+    #[test]
+    fn test_interleave_round_robins_across_nodes() {
+        let (mut topology, node0, node1) = two_node_topology();
+        let (_, first) = topology.allocate(AllocPolicy::Interleave, 64).unwrap();
+        let (_, second) = topology.allocate(AllocPolicy::Interleave, 64).unwrap();
+        let (_, third) = topology.allocate(AllocPolicy::Interleave, 64).unwrap();
+        assert_eq!(first, node0);
+        assert_eq!(second, node1);
+        assert_eq!(third, node0);
+    }
+
+    #[test]
+    fn test_free_then_reclaim_from_same_node() {
+        let (mut topology, node0, _node1) = two_node_topology();
+        let (addr, node) = topology.allocate(AllocPolicy::Bind(node0), 256).unwrap();
+        topology.free(addr, 256);
+        assert_eq!(topology.get_node_for_address(addr), Some(node));
+
+        let (reclaimed, _) = topology.allocate(AllocPolicy::Bind(node0), 256).unwrap();
+        assert_eq!(reclaimed, addr);
+    }
 }
@@ -1,5 +1,5 @@
 use super::*;
-use crate::memory::frame_allocator::{Frame, allocate_frame};
+use crate::memory::frame_allocator::{self, Frame, allocate_frame};
 
 bitflags::bitflags! {
     #[derive(Debug, Clone, Copy)]
@@ -14,6 +14,10 @@ bitflags::bitflags! {
         const INNER_SHAREABLE = 3 << 8;
         const NO_EXECUTE = 1 << 54;
         const PRIVILEGED_NO_EXECUTE = 1 << 53;
+        /// Dirty-bit-modifier: when set alongside `READ_ONLY`, a write to
+        /// the page is the dirty signal (hardware DBM would clear
+        /// `READ_ONLY` itself; absent that, a write-fault handler does).
+        const DBM = 1 << 51;
     }
 }
 
@@ -63,6 +67,29 @@ impl ArmPageTableEntry {
     pub fn set_page(&mut self, addr: PhysAddr, flags: ArmPageFlags) {
         self.entry = addr.0 | flags.bits() | ArmPageFlags::VALID.bits() | ArmPageFlags::TABLE.bits() | ArmPageFlags::AF.bits();
     }
+
+    pub fn is_accessed(&self) -> bool {
+        self.flags().contains(ArmPageFlags::AF)
+    }
+
+    pub fn clear_accessed(&mut self) {
+        self.entry &= !ArmPageFlags::AF.bits();
+    }
+
+    /// A page is dirty once it's been handed out for DBM-tracked writes
+    /// (`DBM` set) and the write has actually happened, which clears
+    /// `READ_ONLY` (by hardware DBM, or by this kernel's write-fault
+    /// handler on platforms without it).
+    pub fn is_dirty(&self) -> bool {
+        let flags = self.flags();
+        flags.contains(ArmPageFlags::DBM) && !flags.contains(ArmPageFlags::READ_ONLY)
+    }
+
+    /// Re-arm dirty tracking: go back to read-only-with-DBM so the next
+    /// write is the one that will mark the page dirty again.
+    pub fn clear_dirty(&mut self) {
+        self.entry |= ArmPageFlags::DBM.bits() | ArmPageFlags::READ_ONLY.bits();
+    }
 }
 
 #[repr(C, align(4096))]
@@ -100,11 +127,44 @@ impl IndexMut<usize> for ArmPageTable {
 
 pub struct ArmLpaePageTable {
     l0_frame: Frame,
+    /// ASID programmed into TTBR0_EL1[63:48] on
+    /// [`activate`](Self::activate), so switching to this table doesn't
+    /// need to flush TLB entries tagged with a different ASID.
+    asid: u16,
 }
 
 impl ArmLpaePageTable {
     pub fn new(l0_frame: Frame) -> Self {
-        ArmLpaePageTable { l0_frame }
+        ArmLpaePageTable { l0_frame, asid: 0 }
+    }
+
+    /// Build a table tagged with `asid`.
+    pub fn new_with_asid(l0_frame: Frame, asid: u16) -> Self {
+        ArmLpaePageTable { l0_frame, asid }
+    }
+
+    pub fn asid(&self) -> u16 {
+        self.asid
+    }
+
+    /// Load this table into TTBR0_EL1, tagged with its ASID, so
+    /// non-global TLB entries belonging to other ASIDs aren't flushed
+    /// by the switch.
+    pub fn activate(&self) {
+        let ttbr0 = self.l0_frame.start_address.0 | ((self.asid as u64) << 48);
+        #[cfg(target_arch = "aarch64")]
+        unsafe {
+            core::arch::asm!(
+                "msr ttbr0_el1, {}",
+                "isb",
+                in(reg) ttbr0,
+                options(nostack),
+            );
+        }
+        #[cfg(not(target_arch = "aarch64"))]
+        {
+            let _ = ttbr0;
+        }
     }
 
     fn l0_index(addr: VirtAddr) -> usize {
@@ -123,17 +183,23 @@ impl ArmLpaePageTable {
         ((addr.0 >> 12) & 0x1FF) as usize
     }
 
+    /// Dereference `frame` through the active virtual mapping rather than
+    /// casting its physical address directly -- a raw cast only happens to
+    /// work before the MMU is enabled.
     unsafe fn get_table(&self, frame: Frame) -> &'static ArmPageTable {
-        &*(frame.start_address.0 as *const ArmPageTable)
+        &*(temporary_map(frame.start_address).0 as *const ArmPageTable)
     }
 
     unsafe fn get_table_mut(&mut self, frame: Frame) -> &'static mut ArmPageTable {
-        &mut *(frame.start_address.0 as *mut ArmPageTable)
+        &mut *(temporary_map(frame.start_address).0 as *mut ArmPageTable)
     }
 
     fn convert_flags(flags: PageFlags) -> ArmPageFlags {
-        let mut arm_flags = ArmPageFlags::VALID | ArmPageFlags::AF;
-        
+        // Non-global by default: process-owned mappings are tagged with
+        // this table's ASID so they don't leak across address spaces.
+        // `with_global_bit` clears NG for kernel-range mappings.
+        let mut arm_flags = ArmPageFlags::VALID | ArmPageFlags::AF | ArmPageFlags::NG;
+
         if !flags.contains(PageFlags::WRITABLE) {
             arm_flags |= ArmPageFlags::READ_ONLY;
         }
@@ -149,6 +215,19 @@ impl ArmLpaePageTable {
         arm_flags
     }
 
+    /// Kernel mappings are marked global (NG clear) so they survive an
+    /// ASID switch; everything else is tagged non-global (NG set) by
+    /// `convert_flags`. Global entries may only live in the higher-half
+    /// kernel range: enforced here rather than trusted from the caller.
+    fn with_global_bit(page: Page, mut flags: ArmPageFlags) -> ArmPageFlags {
+        if page.start_address.0 >= KERNEL_SPACE_START {
+            flags.remove(ArmPageFlags::NG);
+        } else {
+            flags.insert(ArmPageFlags::NG);
+        }
+        flags
+    }
+
     fn create_next_level_table(&mut self, entry: &mut ArmPageTableEntry) -> Result<Frame, MapError> {
         if entry.is_unused() {
             let frame = allocate_frame().ok_or(MapError::FrameAllocationFailed)?;
@@ -165,8 +244,8 @@ impl ArmLpaePageTable {
     }
 
     pub fn map_to_lpae(&mut self, page: Page, frame: Frame, flags: PageFlags) -> Result<(), MapError> {
-        let arm_flags = Self::convert_flags(flags);
-        
+        let arm_flags = Self::with_global_bit(page, Self::convert_flags(flags));
+
         unsafe {
             let l0_table = self.get_table_mut(self.l0_frame);
             let l0_idx = Self::l0_index(page.start_address);
@@ -193,6 +272,51 @@ impl ArmLpaePageTable {
         Ok(())
     }
 
+    /// Map `page` to `frame` as a block mapping, stopping one level short
+    /// of a 4 KiB leaf: an L1 block for 1 GiB, or an L2 block for 2 MiB.
+    /// `frame` must be aligned to the requested size.
+    pub fn map_to_lpae_huge(&mut self, page: Page, frame: Frame, flags: PageFlags, size: PageSize) -> Result<(), MapError> {
+        let arm_flags = Self::with_global_bit(page, Self::convert_flags(flags));
+
+        match size {
+            PageSize::Size4KiB => self.map_to_lpae(page, frame, flags),
+            PageSize::Size1GiB => unsafe {
+                let l0_table = self.get_table_mut(self.l0_frame);
+                let l0_idx = Self::l0_index(page.start_address);
+                let l1_frame = self.create_next_level_table(&mut l0_table[l0_idx])?;
+
+                let l1_table = self.get_table_mut(l1_frame);
+                let l1_idx = Self::l1_index(page.start_address);
+                if !l1_table[l1_idx].is_unused() {
+                    return Err(MapError::PageAlreadyMapped);
+                }
+                l1_table[l1_idx].set_block(frame.start_address, arm_flags);
+                Ok(())
+            },
+            PageSize::Size2MiB => unsafe {
+                let l0_table = self.get_table_mut(self.l0_frame);
+                let l0_idx = Self::l0_index(page.start_address);
+                let l1_frame = self.create_next_level_table(&mut l0_table[l0_idx])?;
+
+                let l1_table = self.get_table_mut(l1_frame);
+                let l1_idx = Self::l1_index(page.start_address);
+                let l2_frame = self.create_next_level_table(&mut l1_table[l1_idx])?;
+
+                let l2_table = self.get_table_mut(l2_frame);
+                let l2_idx = Self::l2_index(page.start_address);
+                if !l2_table[l2_idx].is_unused() {
+                    return Err(MapError::PageAlreadyMapped);
+                }
+                l2_table[l2_idx].set_block(frame.start_address, arm_flags);
+                Ok(())
+            },
+        }
+    }
+
+    fn table_is_empty(table: &ArmPageTable) -> bool {
+        (0..ENTRY_COUNT).all(|i| table[i].is_unused())
+    }
+
     pub fn unmap_lpae(&mut self, page: Page) -> Result<(), UnmapError> {
         unsafe {
             let l0_table = self.get_table(self.l0_frame);
@@ -218,12 +342,35 @@ impl ArmLpaePageTable {
 
             let l3_table = self.get_table_mut(l3_frame);
             let l3_idx = Self::l3_index(page.start_address);
-            
+
             if l3_table[l3_idx].is_unused() {
                 return Err(UnmapError::PageNotMapped);
             }
 
             l3_table[l3_idx].set_unused();
+
+            // Walk back up, freeing any table that's now completely empty
+            // and clearing the parent entry that pointed to it. Never
+            // touches L0 (the top-level table), and never reclaims a
+            // table still holding a block entry — a block entry's VALID
+            // bit keeps it from ever reading as `is_unused()`.
+            if Self::table_is_empty(l3_table) {
+                let l2_table = self.get_table_mut(l2_frame);
+                l2_table[l2_idx].set_unused();
+                frame_allocator::deallocate_frame(l3_frame);
+
+                if Self::table_is_empty(l2_table) {
+                    let l1_table = self.get_table_mut(l1_frame);
+                    l1_table[l1_idx].set_unused();
+                    frame_allocator::deallocate_frame(l2_frame);
+
+                    if Self::table_is_empty(l1_table) {
+                        let l0_table = self.get_table_mut(self.l0_frame);
+                        l0_table[l0_idx].set_unused();
+                        frame_allocator::deallocate_frame(l1_frame);
+                    }
+                }
+            }
         }
 
         Ok(())
@@ -255,6 +402,188 @@ impl ArmLpaePageTable {
             Some(PhysAddr(l3_entry.addr().0 + offset))
         }
     }
+
+    /// Walk down to `page`'s L3 (leaf) entry, or `None` if any
+    /// intermediate level or the leaf itself isn't mapped.
+    unsafe fn l3_entry(&self, page: Page) -> Option<&ArmPageTableEntry> {
+        let l0_table = self.get_table(self.l0_frame);
+        let l0_entry = &l0_table[Self::l0_index(page.start_address)];
+        if l0_entry.is_unused() { return None; }
+
+        let l1_table = self.get_table(Frame { start_address: l0_entry.addr() });
+        let l1_entry = &l1_table[Self::l1_index(page.start_address)];
+        if l1_entry.is_unused() || l1_entry.is_block() { return None; }
+
+        let l2_table = self.get_table(Frame { start_address: l1_entry.addr() });
+        let l2_entry = &l2_table[Self::l2_index(page.start_address)];
+        if l2_entry.is_unused() || l2_entry.is_block() { return None; }
+
+        let l3_table = self.get_table(Frame { start_address: l2_entry.addr() });
+        let l3_entry = &l3_table[Self::l3_index(page.start_address)];
+        if l3_entry.is_unused() { return None; }
+
+        Some(l3_entry)
+    }
+
+    unsafe fn l3_entry_mut(&mut self, page: Page) -> Option<&mut ArmPageTableEntry> {
+        let l0_table = self.get_table(self.l0_frame);
+        let l0_entry = &l0_table[Self::l0_index(page.start_address)];
+        if l0_entry.is_unused() { return None; }
+
+        let l1_table = self.get_table(Frame { start_address: l0_entry.addr() });
+        let l1_entry = &l1_table[Self::l1_index(page.start_address)];
+        if l1_entry.is_unused() || l1_entry.is_block() { return None; }
+
+        let l2_table = self.get_table(Frame { start_address: l1_entry.addr() });
+        let l2_entry = &l2_table[Self::l2_index(page.start_address)];
+        if l2_entry.is_unused() || l2_entry.is_block() { return None; }
+
+        let l3_table = self.get_table_mut(Frame { start_address: l2_entry.addr() });
+        let l3_idx = Self::l3_index(page.start_address);
+        if l3_table[l3_idx].is_unused() { return None; }
+
+        Some(&mut l3_table[l3_idx])
+    }
+
+    pub fn is_accessed(&self, page: Page) -> bool {
+        unsafe { self.l3_entry(page).map(|e| e.is_accessed()).unwrap_or(false) }
+    }
+
+    pub fn is_dirty(&self, page: Page) -> bool {
+        unsafe { self.l3_entry(page).map(|e| e.is_dirty()).unwrap_or(false) }
+    }
+
+    pub fn clear_accessed(&mut self, page: Page) {
+        unsafe {
+            if let Some(entry) = self.l3_entry_mut(page) {
+                entry.clear_accessed();
+            }
+        }
+    }
+
+    pub fn clear_dirty(&mut self, page: Page) {
+        unsafe {
+            if let Some(entry) = self.l3_entry_mut(page) {
+                entry.clear_dirty();
+            }
+        }
+    }
+
+    /// The flags on the leaf entry mapping `addr`, translated from
+    /// [`ArmPageFlags`] to the generic [`PageFlags`] the rest of the kernel
+    /// works in, or `None` if no leaf maps it at any level (a block entry
+    /// at L1/L2, or a page entry at L3). Used by
+    /// [`crate::syscall::user_access`] to check a userspace pointer is
+    /// present and has the right permissions before the kernel dereferences
+    /// it, rather than just its physical address as `translate_lpae` gives.
+    pub fn leaf_flags(&self, addr: VirtAddr) -> Option<PageFlags> {
+        let page = Page::containing_address(addr);
+
+        unsafe {
+            let l0_table = self.get_table(self.l0_frame);
+            let l0_entry = &l0_table[Self::l0_index(page.start_address)];
+            if l0_entry.is_unused() {
+                return None;
+            }
+
+            let l1_table = self.get_table(Frame { start_address: l0_entry.addr() });
+            let l1_entry = &l1_table[Self::l1_index(page.start_address)];
+            if l1_entry.is_unused() {
+                return None;
+            }
+            if l1_entry.is_block() {
+                return Some(Self::arm_flags_to_generic(l1_entry.flags()));
+            }
+
+            let l2_table = self.get_table(Frame {
+                start_address: l1_entry.addr(),
+            });
+            let l2_entry = &l2_table[Self::l2_index(page.start_address)];
+            if l2_entry.is_unused() {
+                return None;
+            }
+            if l2_entry.is_block() {
+                return Some(Self::arm_flags_to_generic(l2_entry.flags()));
+            }
+
+            let l3_table = self.get_table(Frame {
+                start_address: l2_entry.addr(),
+            });
+            let l3_entry = &l3_table[Self::l3_index(page.start_address)];
+            if l3_entry.is_unused() {
+                return None;
+            }
+
+            Some(Self::arm_flags_to_generic(l3_entry.flags()))
+        }
+    }
+
+    fn arm_flags_to_generic(arm_flags: ArmPageFlags) -> PageFlags {
+        let mut flags = PageFlags::empty();
+        if arm_flags.contains(ArmPageFlags::VALID) {
+            flags |= PageFlags::PRESENT;
+        }
+        if !arm_flags.contains(ArmPageFlags::READ_ONLY) {
+            flags |= PageFlags::WRITABLE;
+        }
+        if arm_flags.contains(ArmPageFlags::USER_ACCESSIBLE) {
+            flags |= PageFlags::USER_ACCESSIBLE;
+        }
+        flags
+    }
+}
+
+impl PageTableMapper for ArmLpaePageTable {
+    fn map_to(&mut self, page: Page, frame: Frame, flags: PageFlags) -> Result<(), MapError> {
+        self.map_to_lpae(page, frame, flags)
+    }
+
+    fn unmap(&mut self, page: Page) -> Result<(), UnmapError> {
+        self.unmap_lpae(page)
+    }
+
+    fn translate(&self, addr: VirtAddr) -> Option<PhysAddr> {
+        self.translate_lpae(addr)
+    }
+}
+
+/// Invalidate every TLB entry tagged with `asid`, as when tearing down an
+/// address space rather than merely switching out of it.
+pub fn flush_asid(asid: u16) {
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        let operand = (asid as u64) << 48;
+        core::arch::asm!(
+            "tlbi aside1, {}",
+            "dsb ish",
+            "isb",
+            in(reg) operand,
+            options(nostack),
+        );
+    }
+    #[cfg(not(target_arch = "aarch64"))]
+    {
+        let _ = asid;
+    }
+}
+
+/// Invalidate `page`'s TLB entry within `asid`.
+pub fn flush_page_in_asid(page: Page, asid: u16) {
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        let operand = ((asid as u64) << 48) | (page.start_address.0 >> 12);
+        core::arch::asm!(
+            "tlbi vae1, {}",
+            "dsb ish",
+            "isb",
+            in(reg) operand,
+            options(nostack),
+        );
+    }
+    #[cfg(not(target_arch = "aarch64"))]
+    {
+        let _ = (page, asid);
+    }
 }
 
 #[cfg(test)]
@@ -271,6 +600,25 @@ mod tests {
         assert_eq!(entry.addr().0, 0x1000);
     }
 
+    #[test]
+    fn test_arm_entry_accessed_and_dirty_tracking() {
+        let mut entry = ArmPageTableEntry::new();
+        entry.set_page(PhysAddr::new(0x1000), ArmPageFlags::READ_ONLY | ArmPageFlags::DBM);
+        assert!(entry.is_accessed());
+        assert!(!entry.is_dirty());
+
+        entry.clear_accessed();
+        assert!(!entry.is_accessed());
+
+        // Simulate a write-fault handler clearing READ_ONLY once the page
+        // is actually written to.
+        entry.entry &= !ArmPageFlags::READ_ONLY.bits();
+        assert!(entry.is_dirty());
+
+        entry.clear_dirty();
+        assert!(!entry.is_dirty());
+    }
+
     #[test]
     fn test_arm_indices() {
         let addr = VirtAddr::new(0x1234_5678_9000);
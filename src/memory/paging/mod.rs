@@ -1,8 +1,9 @@
 pub mod x86_64_paging;
 pub mod arm_lpae;
 
-use super::{PhysAddr, VirtAddr, PAGE_SIZE};
+use super::{PhysAddr, VirtAddr, PAGE_SIZE, PAGE_SIZE_2M, PAGE_SIZE_1G};
 use core::ops::{Index, IndexMut};
+use core::sync::atomic::{AtomicU64, Ordering};
 
 pub use x86_64_paging::X86_64PageTable;
 
@@ -18,6 +19,13 @@ bitflags::bitflags! {
         const DIRTY = 1 << 6;
         const HUGE_PAGE = 1 << 7;
         const GLOBAL = 1 << 8;
+        /// Software-reserved (ignored by the MMU): this leaf is a
+        /// copy-on-write share of a frame with more than one owner, tracked
+        /// in `frame_allocator`'s refcount registry. Always paired with
+        /// `WRITABLE` clear; a write fault materializes a private copy via
+        /// `X86_64PageTable::resolve_cow_fault` and restores `WRITABLE`
+        /// before retrying the faulting instruction.
+        const COW = 1 << 9;
         const NO_EXECUTE = 1 << 63;
     }
 }
@@ -81,6 +89,64 @@ impl PageTableEntry {
     pub fn set(&mut self, addr: PhysAddr, flags: PageFlags) {
         self.entry = addr.0 | flags.bits();
     }
+
+    pub fn is_accessed(&self) -> bool {
+        self.flags().contains(PageFlags::ACCESSED)
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.flags().contains(PageFlags::DIRTY)
+    }
+
+    pub fn clear_accessed(&mut self) {
+        self.entry &= !PageFlags::ACCESSED.bits();
+    }
+
+    pub fn clear_dirty(&mut self) {
+        self.entry &= !PageFlags::DIRTY.bits();
+    }
+}
+
+/// Start of the higher-half kernel address range. Only mappings at or
+/// above this address may be marked global (`G` on x86, clear `NG` on
+/// ARM) -- global entries are tagged to survive an ASID/PCID switch, so
+/// tagging a process-owned mapping global would leak it across address
+/// spaces.
+pub const KERNEL_SPACE_START: u64 = 0xFFFF_8000_0000_0000;
+
+/// Offset added to a physical address to reach its kernel virtual alias.
+/// Zero (the default) means physical and virtual addresses coincide, which
+/// only holds for an identity-mapped boot environment; once the MMU is
+/// enabled with a non-identity layout, [`set_phys_to_virt_offset`] must be
+/// called with the offset of whatever mapping covers all of physical
+/// memory (e.g. a linear/direct map) before table walkers can dereference
+/// frame addresses.
+static PHYS_TO_VIRT_OFFSET: AtomicU64 = AtomicU64::new(0);
+
+/// Record the offset [`temporary_map`] adds to a physical address to reach
+/// its kernel virtual alias. Call once, after the direct map covering
+/// physical memory is established.
+pub fn set_phys_to_virt_offset(offset: u64) {
+    PHYS_TO_VIRT_OFFSET.store(offset, Ordering::Relaxed);
+}
+
+/// Translate a physical address into the virtual address a table walker
+/// may dereference, via the offset set with [`set_phys_to_virt_offset`].
+/// This is a "temporary" mapping only in the sense that it borrows the
+/// direct map rather than establishing a dedicated one -- both paging
+/// backends use it in place of casting a frame's physical address straight
+/// to a reference, which only happens to work before the MMU is enabled.
+pub fn temporary_map(addr: PhysAddr) -> VirtAddr {
+    VirtAddr(addr.0 + PHYS_TO_VIRT_OFFSET.load(Ordering::Relaxed))
+}
+
+/// The size of a mapping, for backends that support huge pages in
+/// addition to the base 4 KiB leaf size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageSize {
+    Size4KiB,
+    Size2MiB,
+    Size1GiB,
 }
 
 pub const ENTRY_COUNT: usize = 512;
@@ -160,4 +226,19 @@ mod tests {
         assert!(entry.flags().contains(PageFlags::PRESENT));
         assert!(entry.flags().contains(PageFlags::WRITABLE));
     }
+
+    #[test]
+    fn test_page_table_entry_accessed_and_dirty() {
+        let mut entry = PageTableEntry::new();
+        entry.set(PhysAddr::new(0x1000), PageFlags::PRESENT | PageFlags::ACCESSED | PageFlags::DIRTY);
+        assert!(entry.is_accessed());
+        assert!(entry.is_dirty());
+
+        entry.clear_accessed();
+        assert!(!entry.is_accessed());
+        assert!(entry.is_dirty());
+
+        entry.clear_dirty();
+        assert!(!entry.is_dirty());
+    }
 }
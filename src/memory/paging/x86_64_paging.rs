@@ -1,20 +1,45 @@
+// Table walks here already go through the active virtual mapping: the
+// recursive P4 slot (`p4_addr` and friends below) resolves a frame's
+// contents via the paging hardware itself rather than casting its
+// physical address to a reference, so unlike the ARM LPAE backend this
+// one needs no `temporary_map` calls of its own.
 use super::*;
-use crate::memory::frame_allocator::{Frame, allocate_frame};
+use crate::memory::frame_allocator::{self, Frame, allocate_frame};
 use spin::Mutex;
 
 pub struct X86_64PageTable {
-    #[allow(dead_code)]
     p4_frame: Frame,
+    /// Process Context ID programmed into CR3's low 12 bits on
+    /// [`activate`](Self::activate), so switching to this table doesn't
+    /// need to flush TLB entries tagged with a different PCID.
+    pcid: u16,
 }
 
 impl X86_64PageTable {
     pub fn new(p4_frame: Frame) -> Self {
-        X86_64PageTable { p4_frame }
+        X86_64PageTable { p4_frame, pcid: 0 }
+    }
+
+    /// Build a table tagged with `pcid` (masked to the 12 bits CR3 has
+    /// room for).
+    pub fn new_with_pcid(p4_frame: Frame, pcid: u16) -> Self {
+        X86_64PageTable { p4_frame, pcid: pcid & 0xFFF }
     }
 
     pub fn active() -> Self {
         let (p4_frame, _) = unsafe { Self::get_active_p4() };
-        X86_64PageTable { p4_frame }
+        X86_64PageTable { p4_frame, pcid: 0 }
+    }
+
+    pub fn pcid(&self) -> u16 {
+        self.pcid
+    }
+
+    /// Load this table into CR3, tagged with its PCID, so non-global TLB
+    /// entries belonging to other PCIDs aren't flushed by the switch.
+    pub fn activate(&self) {
+        let cr3 = self.p4_frame.start_address.0 | self.pcid as u64;
+        crate::arch::x86_64::write_cr3(cr3);
     }
 
     unsafe fn get_active_p4() -> (Frame, &'static mut PageTable) {
@@ -158,6 +183,300 @@ impl X86_64PageTable {
             _ => Ok(()),
         }
     }
+
+    /// Kernel mappings are tagged global (`G`) so they survive a PCID
+    /// switch instead of being flushed with the rest of the outgoing
+    /// address space. Global entries may only live in the higher-half
+    /// kernel range: add the bit for a kernel-range page and strip it
+    /// from anything the caller asked for outside that range, rather
+    /// than trusting callers to get it right.
+    fn effective_flags(page: Page, flags: PageFlags) -> PageFlags {
+        if page.start_address.0 >= KERNEL_SPACE_START {
+            flags | PageFlags::GLOBAL
+        } else {
+            flags & !PageFlags::GLOBAL
+        }
+    }
+
+    fn table_is_empty(table: &PageTable) -> bool {
+        (0..ENTRY_COUNT).all(|i| table[i].is_unused())
+    }
+
+    /// After a leaf unmap, walk back up from P1 toward P4 (never
+    /// including P4 itself) freeing any table that has gone completely
+    /// empty and clearing the parent entry that pointed to it. Stops at
+    /// the first table that still has a present entry, which also covers
+    /// tables holding huge-page block entries: a block entry is never
+    /// `is_unused()`, so its table is never reclaimed out from under it.
+    fn reclaim_empty_tables(&mut self, page: Page) {
+        let Some(p1) = self.p1_mut(page) else { return };
+        if !Self::table_is_empty(p1) {
+            return;
+        }
+        if let Some(p2) = self.p2_mut(page) {
+            let entry = &mut p2[page.p2_index()];
+            let frame = Frame { start_address: entry.addr() };
+            entry.set_unused();
+            frame_allocator::deallocate_frame(frame);
+        } else {
+            return;
+        }
+
+        let Some(p2) = self.p2_mut(page) else { return };
+        if !Self::table_is_empty(p2) {
+            return;
+        }
+        if let Some(p3) = self.p3_mut(page) {
+            let entry = &mut p3[page.p3_index()];
+            let frame = Frame { start_address: entry.addr() };
+            entry.set_unused();
+            frame_allocator::deallocate_frame(frame);
+        } else {
+            return;
+        }
+
+        let Some(p3) = self.p3_mut(page) else { return };
+        if !Self::table_is_empty(p3) {
+            return;
+        }
+        let entry = &mut self.p4_mut()[page.p4_index()];
+        let frame = Frame { start_address: entry.addr() };
+        entry.set_unused();
+        frame_allocator::deallocate_frame(frame);
+    }
+}
+
+impl X86_64PageTable {
+    /// Whether `page`'s PTE has its accessed (A) bit set, i.e. has been
+    /// read or written since the bit was last cleared.
+    pub fn is_accessed(&self, page: Page) -> bool {
+        self.p1(page)
+            .map(|p1| p1[page.p1_index()].is_accessed())
+            .unwrap_or(false)
+    }
+
+    /// Whether `page`'s PTE has its dirty (D) bit set, i.e. has been
+    /// written since the bit was last cleared.
+    pub fn is_dirty(&self, page: Page) -> bool {
+        self.p1(page)
+            .map(|p1| p1[page.p1_index()].is_dirty())
+            .unwrap_or(false)
+    }
+
+    /// Clear `page`'s accessed bit and flush its TLB entry so the next
+    /// access sets it again.
+    pub fn clear_accessed(&mut self, page: Page) {
+        if let Some(p1) = self.p1_mut(page) {
+            p1[page.p1_index()].clear_accessed();
+            unsafe {
+                invlpg(page.start_address.0);
+            }
+        }
+    }
+
+    /// Clear `page`'s dirty bit and flush its TLB entry so the next write
+    /// sets it again.
+    pub fn clear_dirty(&mut self, page: Page) {
+        if let Some(p1) = self.p1_mut(page) {
+            p1[page.p1_index()].clear_dirty();
+            unsafe {
+                invlpg(page.start_address.0);
+            }
+        }
+    }
+
+    /// The flags on the leaf entry mapping `addr`, or `None` if no leaf
+    /// maps it at any level (4 KiB, 2 MiB huge, or 1 GiB huge). Used by
+    /// [`crate::syscall::user_access`] to check a userspace pointer is
+    /// present and has the right permissions before the kernel dereferences
+    /// it, rather than just its physical address as `translate` gives.
+    pub fn leaf_flags(&self, addr: VirtAddr) -> Option<PageFlags> {
+        let page = Page::containing_address(addr);
+
+        if let Some(p3) = self.p3(page) {
+            let entry = &p3[page.p3_index()];
+            if entry.flags().contains(PageFlags::PRESENT) && entry.flags().contains(PageFlags::HUGE_PAGE) {
+                return Some(entry.flags());
+            }
+        }
+
+        if let Some(p2) = self.p2(page) {
+            let entry = &p2[page.p2_index()];
+            if entry.flags().contains(PageFlags::PRESENT) && entry.flags().contains(PageFlags::HUGE_PAGE) {
+                return Some(entry.flags());
+            }
+        }
+
+        if let Some(p1) = self.p1(page) {
+            let entry = &p1[page.p1_index()];
+            if entry.flags().contains(PageFlags::PRESENT) {
+                return Some(entry.flags());
+            }
+        }
+        None
+    }
+
+    /// Build `page`'s leaf entry directly inside the page table rooted at
+    /// `root_frame`, bypassing this type's recursive self-mapping (see the
+    /// module comment) -- that scheme only ever resolves the *active*
+    /// CR3's tables, which a freshly forked child's table never is until
+    /// it's scheduled. Walks via `temporary_map` instead, the same
+    /// technique the ARM LPAE backend uses for all of its table access,
+    /// allocating and zeroing any missing intermediate table along the way.
+    /// Used by `AddressSpace::clone_for_fork` to install a copy-on-write
+    /// share into the not-yet-active child table.
+    pub fn install_leaf_into(
+        root_frame: Frame,
+        page: Page,
+        target_frame: Frame,
+        flags: PageFlags,
+    ) -> Option<()> {
+        unsafe fn table_at(frame: Frame) -> &'static mut PageTable {
+            &mut *(temporary_map(frame.start_address).0 as *mut PageTable)
+        }
+
+        unsafe fn child_frame_of(table: &mut PageTable, index: usize) -> Option<Frame> {
+            let entry = &mut table[index];
+            if entry.flags().contains(PageFlags::PRESENT) {
+                return Some(Frame {
+                    start_address: entry.addr(),
+                });
+            }
+            let frame = allocate_frame()?;
+            entry.set(
+                frame.start_address,
+                PageFlags::PRESENT | PageFlags::WRITABLE | PageFlags::USER_ACCESSIBLE,
+            );
+            table_at(frame).zero();
+            Some(frame)
+        }
+
+        unsafe {
+            let p4 = table_at(root_frame);
+            let p3_frame = child_frame_of(p4, page.p4_index())?;
+            let p3 = table_at(p3_frame);
+            let p2_frame = child_frame_of(p3, page.p3_index())?;
+            let p2 = table_at(p2_frame);
+            let p1_frame = child_frame_of(p2, page.p2_index())?;
+            let p1 = table_at(p1_frame);
+            p1[page.p1_index()].set(
+                target_frame.start_address,
+                Self::effective_flags(page, flags) | PageFlags::PRESENT,
+            );
+        }
+        Some(())
+    }
+
+    /// If `page` has a present 4 KiB leaf mapping in the *active* table,
+    /// clear its writable bit, set the software `COW` bit, flush its TLB
+    /// entry, and return the frame it maps -- the parent side of
+    /// establishing a copy-on-write share at fork time. Huge-page leaves
+    /// and unmapped pages are left alone (huge-page COW isn't supported;
+    /// an unmapped page has nothing to share).
+    pub fn mark_cow_and_frame(&mut self, page: Page) -> Option<Frame> {
+        let p1 = self.p1_mut(page)?;
+        let entry = &mut p1[page.p1_index()];
+        if !entry.flags().contains(PageFlags::PRESENT) {
+            return None;
+        }
+
+        let frame = Frame {
+            start_address: entry.addr(),
+        };
+        let flags = (entry.flags() & !PageFlags::WRITABLE) | PageFlags::COW;
+        entry.set(frame.start_address, flags);
+        unsafe {
+            invlpg(page.start_address.0);
+        }
+        Some(frame)
+    }
+
+    /// Attempt to resolve a page fault at `addr` as a copy-on-write write
+    /// fault: if the faulting page's leaf entry has `COW` set, give this
+    /// (active) address space its own private copy of the frame --
+    /// decrementing the shared frame's refcount via
+    /// `frame_allocator::release_cow_frame` (freeing it outright once
+    /// nobody else holds it), or just reclaiming sole write access with no
+    /// copy if this was already the last owner -- and restore `WRITABLE`.
+    /// Returns `false` for anything else (not present, not COW), leaving
+    /// the fault for the caller to treat as fatal.
+    pub fn resolve_cow_fault(&mut self, addr: VirtAddr) -> bool {
+        let page = Page::containing_address(addr);
+        let Some(p1) = self.p1_mut(page) else {
+            return false;
+        };
+        let entry = &mut p1[page.p1_index()];
+        if !entry.flags().contains(PageFlags::PRESENT) || !entry.flags().contains(PageFlags::COW) {
+            return false;
+        }
+
+        let old_frame = Frame {
+            start_address: entry.addr(),
+        };
+        let refcount = frame_allocator::cow_refcount(old_frame);
+
+        if refcount <= 1 {
+            let flags = (entry.flags() & !PageFlags::COW) | PageFlags::WRITABLE;
+            entry.set(old_frame.start_address, flags);
+            unsafe {
+                invlpg(page.start_address.0);
+            }
+            return true;
+        }
+
+        let Some(new_frame) = allocate_frame() else {
+            return false;
+        };
+        unsafe {
+            let src = temporary_map(old_frame.start_address).0 as *const u8;
+            let dst = temporary_map(new_frame.start_address).0 as *mut u8;
+            core::ptr::copy_nonoverlapping(src, dst, PAGE_SIZE);
+        }
+
+        let flags = (entry.flags() & !PageFlags::COW) | PageFlags::WRITABLE;
+        entry.set(new_frame.start_address, flags);
+        unsafe {
+            invlpg(page.start_address.0);
+        }
+        frame_allocator::release_cow_frame(old_frame);
+        true
+    }
+
+    /// Map `page` to `frame` as a huge page, setting the PS bit directly
+    /// in the P2 entry (2 MiB) or P3 entry (1 GiB) and stopping table
+    /// creation one level short of where a 4 KiB mapping would descend
+    /// to. `frame` must be aligned to the requested size.
+    pub fn map_to_huge(&mut self, page: Page, frame: Frame, flags: PageFlags, size: PageSize) -> Result<(), MapError> {
+        match size {
+            PageSize::Size4KiB => self.map_to(page, frame, flags),
+            PageSize::Size2MiB => {
+                self.create_table_if_needed(page, 3)?;
+                let p2 = self.p2_mut(page).ok_or(MapError::FrameAllocationFailed)?;
+                let entry = &mut p2[page.p2_index()];
+                if !entry.is_unused() {
+                    return Err(MapError::PageAlreadyMapped);
+                }
+                entry.set(frame.start_address, Self::effective_flags(page, flags) | PageFlags::PRESENT | PageFlags::HUGE_PAGE);
+                unsafe {
+                    invlpg(page.start_address.0);
+                }
+                Ok(())
+            }
+            PageSize::Size1GiB => {
+                self.create_table_if_needed(page, 4)?;
+                let p3 = self.p3_mut(page).ok_or(MapError::FrameAllocationFailed)?;
+                let entry = &mut p3[page.p3_index()];
+                if !entry.is_unused() {
+                    return Err(MapError::PageAlreadyMapped);
+                }
+                entry.set(frame.start_address, Self::effective_flags(page, flags) | PageFlags::PRESENT | PageFlags::HUGE_PAGE);
+                unsafe {
+                    invlpg(page.start_address.0);
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
 impl PageTableMapper for X86_64PageTable {
@@ -169,7 +488,7 @@ impl PageTableMapper for X86_64PageTable {
             if !entry.is_unused() {
                 return Err(MapError::PageAlreadyMapped);
             }
-            entry.set(frame.start_address, flags | PageFlags::PRESENT);
+            entry.set(frame.start_address, Self::effective_flags(page, flags) | PageFlags::PRESENT);
             unsafe {
                 invlpg(page.start_address.0);
             }
@@ -189,19 +508,37 @@ impl PageTableMapper for X86_64PageTable {
             unsafe {
                 invlpg(page.start_address.0);
             }
-            Ok(())
         } else {
-            Err(UnmapError::PageNotMapped)
+            return Err(UnmapError::PageNotMapped);
         }
+
+        self.reclaim_empty_tables(page);
+        Ok(())
     }
 
     fn translate(&self, addr: VirtAddr) -> Option<PhysAddr> {
         let page = Page::containing_address(addr);
-        let offset = addr.0 % PAGE_SIZE as u64;
+
+        if let Some(p3) = self.p3(page) {
+            let entry = &p3[page.p3_index()];
+            if entry.flags().contains(PageFlags::PRESENT) && entry.flags().contains(PageFlags::HUGE_PAGE) {
+                let offset = addr.0 % PAGE_SIZE_1G as u64;
+                return Some(PhysAddr(entry.addr().0 + offset));
+            }
+        }
+
+        if let Some(p2) = self.p2(page) {
+            let entry = &p2[page.p2_index()];
+            if entry.flags().contains(PageFlags::PRESENT) && entry.flags().contains(PageFlags::HUGE_PAGE) {
+                let offset = addr.0 % PAGE_SIZE_2M as u64;
+                return Some(PhysAddr(entry.addr().0 + offset));
+            }
+        }
 
         if let Some(p1) = self.p1(page) {
             let entry = &p1[page.p1_index()];
             if entry.flags().contains(PageFlags::PRESENT) {
+                let offset = addr.0 % PAGE_SIZE as u64;
                 return Some(PhysAddr(entry.addr().0 + offset));
             }
         }
@@ -221,6 +558,50 @@ unsafe fn invlpg(addr: u64) {
     core::arch::asm!("invlpg [{}]", in(reg) addr, options(nostack));
 }
 
+/// `INVPCID` descriptor: the PCID to act on and, for the single-address
+/// form, the linear address to invalidate.
+#[repr(C, align(16))]
+struct InvpcidDescriptor {
+    pcid: u64,
+    address: u64,
+}
+
+/// `INVPCID` type operand: invalidate every non-global TLB entry tagged
+/// with the descriptor's PCID.
+const INVPCID_SINGLE_CONTEXT: u64 = 1;
+/// `INVPCID` type operand: invalidate only the single linear address in
+/// the descriptor, within its PCID.
+const INVPCID_INDIVIDUAL_ADDRESS: u64 = 0;
+
+/// # Safety
+/// Requires the `INVPCID` CPU feature (CPUID.(EAX=07H,ECX=0H):EBX.INVPCID[bit 10]).
+#[inline]
+unsafe fn invpcid(kind: u64, descriptor: &InvpcidDescriptor) {
+    core::arch::asm!(
+        "invpcid {kind}, [{desc}]",
+        kind = in(reg) kind,
+        desc = in(reg) descriptor as *const InvpcidDescriptor,
+        options(nostack),
+    );
+}
+
+/// Invalidate every non-global TLB entry tagged with `pcid`, as when
+/// tearing down an address space rather than merely switching out of it.
+pub fn flush_asid(pcid: u16) {
+    let descriptor = InvpcidDescriptor { pcid: pcid as u64, address: 0 };
+    unsafe {
+        invpcid(INVPCID_SINGLE_CONTEXT, &descriptor);
+    }
+}
+
+/// Invalidate `page`'s TLB entry within `pcid`.
+pub fn flush_page_in_asid(page: Page, pcid: u16) {
+    let descriptor = InvpcidDescriptor { pcid: pcid as u64, address: page.start_address.0 };
+    unsafe {
+        invpcid(INVPCID_INDIVIDUAL_ADDRESS, &descriptor);
+    }
+}
+
 pub static KERNEL_PAGE_TABLE: Mutex<Option<X86_64PageTable>> = Mutex::new(None);
 
 pub fn init_paging(p4_frame: Frame) {
@@ -0,0 +1,90 @@
+use super::paging::{Page, PageTableMapper, x86_64_paging::KERNEL_PAGE_TABLE};
+use super::frame_allocator::{self, Frame};
+use super::{VirtAddr, PAGE_SIZE};
+
+#[cfg(not(test))]
+use alloc::vec::Vec;
+
+#[cfg(test)]
+extern crate std;
+#[cfg(test)]
+use std::vec::Vec;
+
+/// How many pages the back hand trails the front hand by, giving a page
+/// cleared by the front hand one extra sweep's grace period before the
+/// back hand can reclaim it.
+const HAND_DISTANCE: usize = 4;
+
+/// Result of a `reclaim` sweep: frames actually returned to the frame
+/// allocator, and pages that were reclaim candidates but dirty. Dirty
+/// pages are never unmapped here — the caller must write them back first.
+#[derive(Debug, Default)]
+pub struct ReclaimReport {
+    pub reclaimed: Vec<Frame>,
+    pub dirty: Vec<Page>,
+}
+
+/// Two-handed clock reclamation over the mapped pages in `[start, end)`:
+/// the front hand clears each page's accessed bit as it passes, and the
+/// back hand, trailing `HAND_DISTANCE` pages behind, reclaims anything
+/// still unaccessed once it arrives. Stops once `target_frames` have been
+/// reclaimed or a few full sweeps find nothing left to take.
+///
+/// Dirty pages are reported in `ReclaimReport::dirty` instead of being
+/// unmapped — the caller is responsible for writing them back and
+/// unmapping them explicitly, since silently dropping a dirty page would
+/// lose data.
+pub fn reclaim(start: VirtAddr, end: VirtAddr, target_frames: usize) -> ReclaimReport {
+    let mut guard = KERNEL_PAGE_TABLE.lock();
+    let Some(table) = guard.as_mut() else {
+        return ReclaimReport::default();
+    };
+
+    let mut pages = Vec::new();
+    let mut addr = start.0;
+    while addr < end.0 {
+        let page = Page::containing_address(VirtAddr(addr));
+        if table.translate(VirtAddr(addr)).is_some() {
+            pages.push(page);
+        }
+        addr += PAGE_SIZE as u64;
+    }
+
+    let mut report = ReclaimReport::default();
+    let n = pages.len();
+    if n == 0 || target_frames == 0 {
+        return report;
+    }
+
+    let gap = HAND_DISTANCE.min(n.saturating_sub(1)).max(1);
+    let max_steps = n.saturating_mul(3);
+    let mut front = 0usize;
+    let mut back = 0usize;
+
+    while report.reclaimed.len() < target_frames && back < max_steps {
+        if front - back < gap && front < back + n {
+            let page = pages[front % n];
+            if table.is_accessed(page) {
+                table.clear_accessed(page);
+            }
+            front += 1;
+            continue;
+        }
+
+        let page = pages[back % n];
+        if table.is_dirty(page) {
+            report.dirty.push(page);
+        } else if !table.is_accessed(page) {
+            if let Some(phys) = table.translate(page.start_address) {
+                let frame = Frame::containing_address(phys);
+                if table.unmap(page).is_ok() {
+                    frame_allocator::deallocate_frame(frame);
+                    report.reclaimed.push(frame);
+                }
+            }
+        }
+        back += 1;
+    }
+
+    report
+}
@@ -0,0 +1,143 @@
+//! Multiboot2 Boot Information Parser
+//!
+//! Walks the tag list the bootloader hands off in `rbx` at kernel entry and
+//! turns it into a `HardwareInfo`. See the Multiboot2 specification for the
+//! on-disk layout: a fixed 8-byte header (`total_size`, `reserved`) followed
+//! by a sequence of tags, each `{ type: u32, size: u32, ... }` padded up to
+//! an 8-byte boundary, terminated by a type-0 tag.
+
+use crate::hwinfo::{HardwareInfo, MemoryRegion};
+
+/// Magic value the bootloader leaves in `eax`/`rax` to signal a Multiboot2
+/// boot; kept in sync with `devine-boot::multiboot2::MULTIBOOT2_MAGIC`.
+pub const MULTIBOOT2_MAGIC: u32 = 0x36d7_6289;
+
+const TAG_TYPE_END: u32 = 0;
+const TAG_TYPE_MEMORY_MAP: u32 = 6;
+const TAG_TYPE_FRAMEBUFFER: u32 = 8;
+
+const MAX_MEMORY_REGIONS: usize = 64;
+
+static mut MEMORY_REGION_ARENA: [MemoryRegion; MAX_MEMORY_REGIONS] = [MemoryRegion {
+    base: 0,
+    size: 0,
+    region_type: 0,
+}; MAX_MEMORY_REGIONS];
+
+#[repr(C)]
+struct TagHeader {
+    typ: u32,
+    size: u32,
+}
+
+#[repr(C)]
+struct MemoryMapTag {
+    typ: u32,
+    size: u32,
+    entry_size: u32,
+    entry_version: u32,
+    // followed by `(size - 16) / entry_size` entries
+}
+
+#[repr(C)]
+struct MemoryMapEntry {
+    base_addr: u64,
+    length: u64,
+    entry_type: u32,
+    reserved: u32,
+}
+
+#[repr(C)]
+struct FramebufferTag {
+    typ: u32,
+    size: u32,
+    framebuffer_addr: u64,
+    framebuffer_pitch: u32,
+    framebuffer_width: u32,
+    framebuffer_height: u32,
+    framebuffer_bpp: u8,
+    framebuffer_type: u8,
+    reserved: u16,
+    // palette/color-info fields follow depending on framebuffer_type
+}
+
+/// Round `value` up to the next multiple of 8, the alignment every
+/// Multiboot2 tag is padded to.
+fn align8(value: usize) -> usize {
+    (value + 7) & !7
+}
+
+/// Parse the Multiboot2 boot-information structure at `info_ptr` into a
+/// `HardwareInfo`. Returns `HardwareInfo::new()` (all zeros) if `info_ptr`
+/// does not point at a structure carrying the Multiboot2 magic, mirroring
+/// the "no bootloader info" fallback `kmain` already uses for a null
+/// pointer.
+///
+/// # Safety
+/// `info_ptr` must either be `0` or point at a valid Multiboot2
+/// boot-information structure that remains mapped and readable for the
+/// duration of this call.
+pub unsafe fn parse(info_ptr: usize) -> HardwareInfo {
+    let mut info = HardwareInfo::new();
+    if info_ptr == 0 {
+        return info;
+    }
+
+    // The structure starts with `total_size: u32` then `reserved: u32`,
+    // followed immediately by the tag list.
+    let total_size = *(info_ptr as *const u32);
+    if total_size < 8 {
+        return info;
+    }
+
+    let mut region_count = 0usize;
+    let mut cursor = info_ptr + 8;
+    let end = info_ptr + total_size as usize;
+
+    while cursor + 8 <= end {
+        let header = &*(cursor as *const TagHeader);
+        if header.typ == TAG_TYPE_END {
+            break;
+        }
+
+        match header.typ {
+            TAG_TYPE_MEMORY_MAP => {
+                let map = &*(cursor as *const MemoryMapTag);
+                let entries_start = cursor + core::mem::size_of::<MemoryMapTag>();
+                let entries_end = cursor + map.size as usize;
+                let entry_size = map.entry_size.max(1) as usize;
+
+                let mut entry_addr = entries_start;
+                while entry_addr + core::mem::size_of::<MemoryMapEntry>() <= entries_end
+                    && region_count < MAX_MEMORY_REGIONS
+                {
+                    let entry = &*(entry_addr as *const MemoryMapEntry);
+                    MEMORY_REGION_ARENA[region_count] = MemoryRegion {
+                        base: entry.base_addr,
+                        size: entry.length,
+                        region_type: entry.entry_type,
+                    };
+                    region_count += 1;
+                    entry_addr += entry_size;
+                }
+            }
+            TAG_TYPE_FRAMEBUFFER => {
+                let fb = &*(cursor as *const FramebufferTag);
+                info.framebuffer_addr = fb.framebuffer_addr;
+                info.framebuffer_width = fb.framebuffer_width;
+                info.framebuffer_height = fb.framebuffer_height;
+                info.framebuffer_pitch = fb.framebuffer_pitch;
+                info.framebuffer_format = fb.framebuffer_type as u32;
+                info.framebuffer_bpp = fb.framebuffer_bpp as u32;
+            }
+            _ => {}
+        }
+
+        cursor += align8(header.size as usize);
+    }
+
+    info.memory_regions = &MEMORY_REGION_ARENA[..region_count];
+    info.memory_region_count = region_count;
+    info.cpu_info = crate::cpuinfo::detect();
+    info
+}
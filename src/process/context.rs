@@ -100,28 +100,217 @@ impl Context {
             ss: 0x10,
         }
     }
+
+    /// Byte view of the register file, for `sys_ptrace`'s `GETREGS` to
+    /// copy into a debugger's buffer.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            core::slice::from_raw_parts(
+                self as *const Context as *const u8,
+                core::mem::size_of::<Context>(),
+            )
+        }
+    }
+
+    /// Rebuild a `Context` from exactly `size_of::<Context>()` bytes, as
+    /// written by `sys_ptrace`'s `SETREGS`.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        assert_eq!(bytes.len(), core::mem::size_of::<Context>());
+        unsafe { core::ptr::read_unaligned(bytes.as_ptr() as *const Context) }
+    }
+}
+
+/// Save the full register file at `old` (if non-null) and load `new`,
+/// transferring control to it via `iretq`. The frame matches what the CPU
+/// expects on an interrupt return (`rip`, `cs`, `rflags`, `rsp`, `ss`), so
+/// the same path serves both kernel->kernel switches and the `new_user`
+/// trampoline into ring 3.
+///
+/// # Safety
+/// `old` must be a valid, writable `*mut Context` or null, and `new` must
+/// point at a fully-initialized `Context`. This function does not return to
+/// its caller in the usual sense -- control resumes wherever `new.rip`
+/// points, which on the next switch back into this task is the instruction
+/// immediately after the `iretq` below.
+#[cfg(target_arch = "x86_64")]
+pub unsafe fn switch_context(old: *mut Context, new: *const Context) {
+    core::arch::asm!(
+        // Spill callee-saved GPRs (and the ones callers conventionally
+        // expect preserved across a "function call" boundary) into *old.
+        "test {old}, {old}",
+        "jz 2f",
+        "mov [{old} + 0x00], r15",
+        "mov [{old} + 0x08], r14",
+        "mov [{old} + 0x10], r13",
+        "mov [{old} + 0x18], r12",
+        "mov [{old} + 0x20], r11",
+        "mov [{old} + 0x28], r10",
+        "mov [{old} + 0x30], r9",
+        "mov [{old} + 0x38], r8",
+        "mov [{old} + 0x40], rdi",
+        "mov [{old} + 0x48], rsi",
+        "mov [{old} + 0x50], rbp",
+        "mov [{old} + 0x58], rbx",
+        "mov [{old} + 0x60], rdx",
+        "mov [{old} + 0x68], rcx",
+        "mov [{old} + 0x70], rax",
+        "lea rax, [3f]",
+        "mov [{old} + 0x78], rax", // rip: resume right after `iretq` below
+        "mov rax, cs",
+        "mov [{old} + 0x80], rax",
+        "pushfq",
+        "pop rax",
+        "mov [{old} + 0x88], rax", // rflags
+        "mov [{old} + 0x90], rsp",
+        "mov rax, ss",
+        "mov [{old} + 0x98], rax",
+        "mov rax, [{old} + 0x70]", // restore rax we clobbered above
+        "2:",
+        // Build the iretq frame for *new on the current stack: ss, rsp,
+        // rflags, cs, rip (pushed high-to-low since the stack grows down).
+        "mov rax, [{new} + 0x98]",
+        "push rax", // ss
+        "mov rax, [{new} + 0x90]",
+        "push rax", // rsp
+        "mov rax, [{new} + 0x88]",
+        "push rax", // rflags
+        "mov rax, [{new} + 0x80]",
+        "push rax", // cs
+        "mov rax, [{new} + 0x78]",
+        "push rax", // rip
+        "mov r15, [{new} + 0x00]",
+        "mov r14, [{new} + 0x08]",
+        "mov r13, [{new} + 0x10]",
+        "mov r12, [{new} + 0x18]",
+        "mov r11, [{new} + 0x20]",
+        "mov r10, [{new} + 0x28]",
+        "mov r9,  [{new} + 0x30]",
+        "mov r8,  [{new} + 0x38]",
+        "mov rdi, [{new} + 0x40]",
+        "mov rsi, [{new} + 0x48]",
+        "mov rbp, [{new} + 0x50]",
+        "mov rbx, [{new} + 0x58]",
+        "mov rdx, [{new} + 0x60]",
+        "mov rcx, [{new} + 0x68]",
+        "mov rax, [{new} + 0x70]",
+        "iretq",
+        "3:",
+        old = in(reg) old,
+        new = in(reg) new,
+        out("rax") _,
+        options(nostack),
+    );
 }
 
-pub unsafe fn switch_context(_old: *mut Context, _new: *const Context) {
-    
+/// Save the full register file at `old` (if non-null) and load `new`,
+/// transferring control to it with `br`. Mirrors the x86_64 save/restore
+/// discipline above, but only for the registers a function-call-style
+/// switch actually needs to preserve: the AAPCS64 callee-saved `x19`..`x28`,
+/// frame pointer `x29`, `sp`, `lr`, `pc`, and `cpsr` (approximated by the
+/// condition flags in `NZCV`, the only piece of processor state EL1 can
+/// read back with a plain `mrs`) into [`ArmContext`]. Caller-saved
+/// `x0`..`x18` are deliberately not touched -- whichever caller needs them
+/// back across this switch has already spilled them itself.
+///
+/// This only models an EL1-to-EL1 kernel switch: it jumps to `new.pc` with
+/// a plain `br`, so it does not perform the privilege transition a real
+/// switch into `new_user`'s EL0 userspace needs -- that requires `eret`
+/// with `ELR_EL1`/`SPSR_EL1` programmed from `cpsr`, which nothing in this
+/// kernel's aarch64 entry path wires up yet (the same kind of gap as
+/// `arch::arm64::gic`'s missing vector table).
+///
+/// # Safety
+/// `old` must be a valid, writable `*mut ArmContext` or null, and `new`
+/// must point at a fully-initialized `ArmContext`. This function does not
+/// return to its caller in the usual sense -- control resumes wherever
+/// `new.pc` points, which on the next switch back into this task is the
+/// instruction immediately after the `br` below.
+#[cfg(target_arch = "aarch64")]
+pub unsafe fn switch_context(old: *mut ArmContext, new: *const ArmContext) {
+    core::arch::asm!(
+        // Spill x19..x29, sp, lr, pc, and nzcv into *old.
+        "cbz {old}, 2f",
+        "str x19, [{old}, #0x00]",
+        "str x20, [{old}, #0x08]",
+        "str x21, [{old}, #0x10]",
+        "str x22, [{old}, #0x18]",
+        "str x23, [{old}, #0x20]",
+        "str x24, [{old}, #0x28]",
+        "str x25, [{old}, #0x30]",
+        "str x26, [{old}, #0x38]",
+        "str x27, [{old}, #0x40]",
+        "str x28, [{old}, #0x48]",
+        "str x29, [{old}, #0x50]",
+        "mov x13, sp",
+        "str x13, [{old}, #0x58]",
+        "str x30, [{old}, #0x60]", // lr
+        "adr x13, 3f",
+        "str x13, [{old}, #0x68]", // pc: resume right after `br` below
+        "mrs x13, nzcv",
+        "str x13, [{old}, #0x70]", // cpsr (approximated by nzcv)
+        "2:",
+        // Load *new and jump to it.
+        "ldr x13, [{new}, #0x58]",
+        "mov sp, x13",
+        "ldr x13, [{new}, #0x70]",
+        "msr nzcv, x13",
+        "ldr x19, [{new}, #0x00]",
+        "ldr x20, [{new}, #0x08]",
+        "ldr x21, [{new}, #0x10]",
+        "ldr x22, [{new}, #0x18]",
+        "ldr x23, [{new}, #0x20]",
+        "ldr x24, [{new}, #0x28]",
+        "ldr x25, [{new}, #0x30]",
+        "ldr x26, [{new}, #0x38]",
+        "ldr x27, [{new}, #0x40]",
+        "ldr x28, [{new}, #0x48]",
+        "ldr x29, [{new}, #0x50]",
+        "ldr x30, [{new}, #0x60]", // lr
+        "ldr x13, [{new}, #0x68]", // pc
+        "br x13",
+        "3:",
+        old = in(reg) old,
+        new = in(reg) new,
+        out("x13") _,
+        out("x19") _,
+        out("x20") _,
+        out("x21") _,
+        out("x22") _,
+        out("x23") _,
+        out("x24") _,
+        out("x25") _,
+        out("x26") _,
+        out("x27") _,
+        out("x28") _,
+        out("x29") _,
+        out("x30") _,
+        options(nostack),
+    );
 }
 
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub unsafe fn switch_context(_old: *mut Context, _new: *const Context) {}
+
+/// A function-call-style register switch only needs the AAPCS64
+/// *callee-saved* registers: `x19`..`x28`, the frame pointer `x29`, `sp`,
+/// the return address `lr` (`x30`), `pc`, and `cpsr`. Caller-saved
+/// `x0`..`x18` are already spilled by whichever caller needs them back, so
+/// `switch_context` never touches them -- saving them here would just save
+/// and restore registers nobody is relying on.
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct ArmContext {
-    pub r0: u64,
-    pub r1: u64,
-    pub r2: u64,
-    pub r3: u64,
-    pub r4: u64,
-    pub r5: u64,
-    pub r6: u64,
-    pub r7: u64,
-    pub r8: u64,
-    pub r9: u64,
-    pub r10: u64,
-    pub r11: u64,
-    pub r12: u64,
+    pub x19: u64,
+    pub x20: u64,
+    pub x21: u64,
+    pub x22: u64,
+    pub x23: u64,
+    pub x24: u64,
+    pub x25: u64,
+    pub x26: u64,
+    pub x27: u64,
+    pub x28: u64,
+    pub x29: u64,
     pub sp: u64,
     pub lr: u64,
     pub pc: u64,
@@ -131,19 +320,17 @@ pub struct ArmContext {
 impl ArmContext {
     pub const fn empty() -> Self {
         ArmContext {
-            r0: 0,
-            r1: 0,
-            r2: 0,
-            r3: 0,
-            r4: 0,
-            r5: 0,
-            r6: 0,
-            r7: 0,
-            r8: 0,
-            r9: 0,
-            r10: 0,
-            r11: 0,
-            r12: 0,
+            x19: 0,
+            x20: 0,
+            x21: 0,
+            x22: 0,
+            x23: 0,
+            x24: 0,
+            x25: 0,
+            x26: 0,
+            x27: 0,
+            x28: 0,
+            x29: 0,
             sp: 0,
             lr: 0,
             pc: 0,
@@ -153,19 +340,17 @@ impl ArmContext {
 
     pub fn new_user(entry_point: VirtAddr, stack_pointer: VirtAddr) -> Self {
         ArmContext {
-            r0: 0,
-            r1: 0,
-            r2: 0,
-            r3: 0,
-            r4: 0,
-            r5: 0,
-            r6: 0,
-            r7: 0,
-            r8: 0,
-            r9: 0,
-            r10: 0,
-            r11: 0,
-            r12: 0,
+            x19: 0,
+            x20: 0,
+            x21: 0,
+            x22: 0,
+            x23: 0,
+            x24: 0,
+            x25: 0,
+            x26: 0,
+            x27: 0,
+            x28: 0,
+            x29: 0,
             sp: stack_pointer.0,
             lr: 0,
             pc: entry_point.0,
@@ -175,19 +360,17 @@ impl ArmContext {
 
     pub fn new_kernel(entry_point: VirtAddr, stack_pointer: VirtAddr) -> Self {
         ArmContext {
-            r0: 0,
-            r1: 0,
-            r2: 0,
-            r3: 0,
-            r4: 0,
-            r5: 0,
-            r6: 0,
-            r7: 0,
-            r8: 0,
-            r9: 0,
-            r10: 0,
-            r11: 0,
-            r12: 0,
+            x19: 0,
+            x20: 0,
+            x21: 0,
+            x22: 0,
+            x23: 0,
+            x24: 0,
+            x25: 0,
+            x26: 0,
+            x27: 0,
+            x28: 0,
+            x29: 0,
             sp: stack_pointer.0,
             lr: 0,
             pc: entry_point.0,
@@ -217,4 +400,76 @@ mod tests {
         assert_eq!(ctx.pc, 0);
         assert_eq!(ctx.sp, 0);
     }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_switch_context_round_trip() {
+        use core::sync::atomic::{AtomicU64, Ordering};
+
+        static ARRIVED_RIP: AtomicU64 = AtomicU64::new(0);
+        static mut CALLER_CTX: Context = Context::empty();
+        static mut TARGET_STACK: [u8; 4096] = [0; 4096];
+
+        extern "C" fn target() {
+            ARRIVED_RIP.store(target as u64, Ordering::SeqCst);
+            unsafe {
+                switch_context(core::ptr::null_mut(), &CALLER_CTX as *const Context);
+            }
+            unreachable!("switch_context back to the caller does not return here");
+        }
+
+        unsafe {
+            let target_sp = TARGET_STACK.as_mut_ptr().add(TARGET_STACK.len()) as u64;
+            let target_ctx =
+                Context::new_kernel(VirtAddr::new(target as u64), VirtAddr::new(target_sp));
+            switch_context(&mut CALLER_CTX as *mut Context, &target_ctx as *const Context);
+        }
+
+        assert_eq!(ARRIVED_RIP.load(Ordering::SeqCst), target as u64);
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    #[test]
+    fn test_switch_context_round_trip_arm() {
+        use core::sync::atomic::{AtomicU64, Ordering};
+
+        static ARRIVED_PC: AtomicU64 = AtomicU64::new(0);
+        static mut CALLER_CTX: ArmContext = ArmContext::empty();
+        static mut TARGET_STACK: [u8; 4096] = [0; 4096];
+
+        // A real task's x19 the switch must hand back unchanged, and a
+        // decoy value the other task sets in its own x19 -- proving
+        // `switch_context` restores the caller's callee-saved register
+        // rather than leaking whatever the other task left behind.
+        const CALLER_X19: u64 = 0x1357_9bdf_1357_9bdf;
+        const TARGET_X19: u64 = 0xdead_beef_dead_beef;
+
+        extern "C" fn target() {
+            ARRIVED_PC.store(target as u64, Ordering::SeqCst);
+            unsafe {
+                core::arch::asm!("mov x19, {0}", in(reg) TARGET_X19, out("x19") _);
+                switch_context(core::ptr::null_mut(), &CALLER_CTX as *const ArmContext);
+            }
+            unreachable!("switch_context back to the caller does not return here");
+        }
+
+        let x19_after: u64;
+        unsafe {
+            let target_sp = TARGET_STACK.as_mut_ptr().add(TARGET_STACK.len()) as u64;
+            let target_ctx =
+                ArmContext::new_kernel(VirtAddr::new(target as u64), VirtAddr::new(target_sp));
+
+            core::arch::asm!("mov x19, {0}", in(reg) CALLER_X19, out("x19") _);
+            switch_context(&mut CALLER_CTX as *mut ArmContext, &target_ctx as *const ArmContext);
+            let observed: u64;
+            core::arch::asm!("mov {0}, x19", out(reg) observed);
+            x19_after = observed;
+        }
+
+        assert_eq!(ARRIVED_PC.load(Ordering::SeqCst), target as u64);
+        assert_eq!(
+            x19_after, CALLER_X19,
+            "switch_context must restore the caller's own x19, not the other task's"
+        );
+    }
 }
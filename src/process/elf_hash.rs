@@ -0,0 +1,166 @@
+//! Fast symbol lookup via an ELF image's `DT_HASH` (SysV) or `DT_GNU_HASH`
+//! (GNU) table, so resolving an import against a loaded shared object
+//! doesn't require a linear scan of its symbol table.
+//!
+//! Not wired into `load_executable` yet: this crate has no dynamic linker
+//! to drive a lookup, so nothing calls `lookup()` today.
+#![allow(dead_code)]
+
+use super::elf_loader::{read_u32, read_u64, ElfClass, ElfLoaderError, Endianness, SymbolTable};
+
+/// Index into a [`SymbolTable`]'s `symbols`, as found via a hash lookup.
+pub(crate) type SymbolIndex = usize;
+
+const STN_UNDEF: u32 = 0;
+
+/// A `DT_HASH` table: `[nbucket, nchain, bucket[nbucket], chain[nchain]]`,
+/// all 32-bit words regardless of ELF class.
+pub(crate) struct SysVHashTable<'a> {
+    bytes: &'a [u8],
+    endian: Endianness,
+    nbucket: u32,
+}
+
+impl<'a> SysVHashTable<'a> {
+    pub(crate) fn parse(bytes: &'a [u8], endian: Endianness) -> Result<Self, ElfLoaderError> {
+        let nbucket = read_u32(bytes, 0, endian)?;
+        Ok(SysVHashTable { bytes, endian, nbucket })
+    }
+
+    fn bucket(&self, idx: u32) -> Result<u32, ElfLoaderError> {
+        read_u32(self.bytes, 8 + idx as usize * 4, self.endian)
+    }
+
+    fn chain(&self, idx: u32) -> Result<u32, ElfLoaderError> {
+        let chain_off = 8 + self.nbucket as usize * 4;
+        read_u32(self.bytes, chain_off + idx as usize * 4, self.endian)
+    }
+
+    /// Walk `chain[]` starting at `bucket[hash % nbucket]` until `name`
+    /// matches or the chain terminates at `STN_UNDEF`.
+    pub(crate) fn lookup(&self, name: &[u8], table: &SymbolTable) -> Option<SymbolIndex> {
+        if self.nbucket == 0 {
+            return None;
+        }
+
+        let hash = sysv_hash(name);
+        let mut idx = self.bucket(hash % self.nbucket).ok()?;
+        while idx != STN_UNDEF {
+            let symbol = table.symbols.get(idx as usize)?;
+            if table.name(symbol) == name {
+                return Some(idx as usize);
+            }
+            idx = self.chain(idx).ok()?;
+        }
+        None
+    }
+}
+
+/// `h = 0; for b in name { h = (h << 4) + b; g = h & 0xf0000000; h ^= g >> 24; h &= !g; }`
+fn sysv_hash(name: &[u8]) -> u32 {
+    let mut hash: u32 = 0;
+    for &byte in name {
+        hash = (hash << 4).wrapping_add(byte as u32);
+        let high = hash & 0xf000_0000;
+        hash ^= high >> 24;
+        hash &= !high;
+    }
+    hash
+}
+
+/// A `DT_GNU_HASH` table: `[nbuckets, symoffset, bloom_size, bloom_shift]`
+/// header, followed by a Bloom filter of `bloom_size` words (word size
+/// matching the ELF class), then `nbuckets` buckets, then a chain of
+/// per-symbol hash values.
+pub(crate) struct GnuHashTable<'a> {
+    bytes: &'a [u8],
+    endian: Endianness,
+    nbuckets: u32,
+    symoffset: u32,
+    bloom_size: u32,
+    bloom_shift: u32,
+    word_bits: u32,
+}
+
+impl<'a> GnuHashTable<'a> {
+    pub(crate) fn parse(bytes: &'a [u8], endian: Endianness, class: ElfClass) -> Result<Self, ElfLoaderError> {
+        Ok(GnuHashTable {
+            bytes,
+            endian,
+            nbuckets: read_u32(bytes, 0, endian)?,
+            symoffset: read_u32(bytes, 4, endian)?,
+            bloom_size: read_u32(bytes, 8, endian)?,
+            bloom_shift: read_u32(bytes, 12, endian)?,
+            word_bits: match class {
+                ElfClass::Elf64 => 64,
+                ElfClass::Elf32 => 32,
+            },
+        })
+    }
+
+    fn bloom_word(&self, idx: u32) -> Result<u64, ElfLoaderError> {
+        let word_bytes = (self.word_bits / 8) as usize;
+        let off = 16 + idx as usize * word_bytes;
+        if self.word_bits == 64 {
+            read_u64(self.bytes, off, self.endian)
+        } else {
+            Ok(read_u32(self.bytes, off, self.endian)? as u64)
+        }
+    }
+
+    fn bucket(&self, idx: u32) -> Result<u32, ElfLoaderError> {
+        let bloom_bytes = self.bloom_size as usize * (self.word_bits / 8) as usize;
+        read_u32(self.bytes, 16 + bloom_bytes + idx as usize * 4, self.endian)
+    }
+
+    fn chain_hash(&self, idx: u32) -> Result<u32, ElfLoaderError> {
+        let bloom_bytes = self.bloom_size as usize * (self.word_bits / 8) as usize;
+        let chain_off = 16 + bloom_bytes + self.nbuckets as usize * 4;
+        read_u32(self.bytes, chain_off + idx as usize * 4, self.endian)
+    }
+
+    /// Reject absent symbols via the Bloom filter, then walk the hash chain
+    /// from `bucket[hash % nbuckets]`, comparing `(chain_hash | 1) == (hash
+    /// | 1)` and stopping at the first entry whose low bit is set.
+    pub(crate) fn lookup(&self, name: &[u8], table: &SymbolTable) -> Option<SymbolIndex> {
+        if self.nbuckets == 0 || self.bloom_size == 0 {
+            return None;
+        }
+
+        let hash = gnu_hash(name);
+
+        let word = self.bloom_word((hash / self.word_bits) % self.bloom_size).ok()?;
+        let mask = (1u64 << (hash % self.word_bits)) | (1u64 << ((hash >> self.bloom_shift) % self.word_bits));
+        if word & mask != mask {
+            return None;
+        }
+
+        let mut idx = self.bucket(hash % self.nbuckets).ok()?;
+        if idx < self.symoffset {
+            return None;
+        }
+
+        loop {
+            let chain_hash = self.chain_hash(idx - self.symoffset).ok()?;
+            if (chain_hash | 1) == (hash | 1) {
+                let symbol = table.symbols.get(idx as usize)?;
+                if table.name(symbol) == name {
+                    return Some(idx as usize);
+                }
+            }
+            if chain_hash & 1 != 0 {
+                return None;
+            }
+            idx += 1;
+        }
+    }
+}
+
+/// `h = 5381; for b in name { h = h * 33 + b }`
+fn gnu_hash(name: &[u8]) -> u32 {
+    let mut hash: u32 = 5381;
+    for &byte in name {
+        hash = hash.wrapping_mul(33).wrapping_add(byte as u32);
+    }
+    hash
+}
@@ -16,14 +16,79 @@ const EI_VERSION: usize = 6;
 const ELFCLASS32: u8 = 1;
 const ELFCLASS64: u8 = 2;
 const ELFDATA2LSB: u8 = 1;
+const ELFDATA2MSB: u8 = 2;
+const ET_DYN: u16 = 3;
 const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+const PT_INTERP: u32 = 3;
+const PT_NOTE: u32 = 4;
+const NT_GNU_BUILD_ID: u32 = 3;
+const NT_GNU_PROPERTY_TYPE_0: u32 = 5;
+const GNU_PROPERTY_X86_FEATURE_1_AND: u32 = 0xc000_0002;
+const GNU_PROPERTY_X86_FEATURE_1_IBT: u32 = 0x1;
+const GNU_PROPERTY_X86_FEATURE_1_SHSTK: u32 = 0x2;
+const GNU_PROPERTY_AARCH64_FEATURE_1_AND: u32 = 0xc000_0000;
+const GNU_PROPERTY_AARCH64_FEATURE_1_BTI: u32 = 0x1;
+const GNU_PROPERTY_AARCH64_FEATURE_1_PAC: u32 = 0x2;
 const SHT_RELA: u32 = 4;
 const SHT_REL: u32 = 9;
 const PF_EXECUTE: u32 = 0x1;
 const PF_WRITE: u32 = 0x2;
 const PF_READ: u32 = 0x4;
+const R_X86_64_64: u32 = 1;
+const R_X86_64_GLOB_DAT: u32 = 6;
+const R_X86_64_JUMP_SLOT: u32 = 7;
 const R_X86_64_RELATIVE: u32 = 8;
+const R_AARCH64_ABS64: u32 = 257;
+const R_AARCH64_GLOB_DAT: u32 = 1025;
+const R_AARCH64_JUMP_SLOT: u32 = 1026;
 const R_AARCH64_RELATIVE: u32 = 1027;
+const SHT_SYMTAB: u32 = 2;
+const SHT_DYNSYM: u32 = 11;
+const SHN_UNDEF: u16 = 0;
+
+const DT_NULL: u64 = 0;
+const DT_PLTRELSZ: u64 = 2;
+const DT_RELA: u64 = 7;
+const DT_RELASZ: u64 = 8;
+const DT_RELAENT: u64 = 9;
+const DT_REL: u64 = 17;
+const DT_RELSZ: u64 = 18;
+const DT_RELENT: u64 = 19;
+const DT_PLTREL: u64 = 20;
+const DT_JMPREL: u64 = 23;
+pub(crate) const DT_HASH: u64 = 4;
+pub(crate) const DT_GNU_HASH: u64 = 0x6fff_fef5;
+
+const AT_NULL: usize = 0;
+const AT_PHDR: usize = 3;
+const AT_PHENT: usize = 4;
+const AT_PHNUM: usize = 5;
+const AT_PAGESZ: usize = 6;
+const AT_BASE: usize = 7;
+const AT_ENTRY: usize = 9;
+const AT_UID: usize = 11;
+const AT_EUID: usize = 12;
+const AT_GID: usize = 13;
+const AT_EGID: usize = 14;
+const AT_HWCAP: usize = 16;
+const AT_CLKTCK: usize = 17;
+const AT_SECURE: usize = 23;
+const AT_RANDOM: usize = 25;
+const AT_HWCAP2: usize = 26;
+const AT_EXECFN: usize = 31;
+const AT_SYSINFO_EHDR: usize = 33;
+
+/// Fixed load base used for ET_DYN (PIE) images. A real loader would pick
+/// this via ASLR/an mmap allocator; a single fixed address is enough to
+/// exercise PIE relocation here since this crate doesn't yet have a VMA
+/// allocator to consult.
+const PIE_LOAD_BASE: u64 = 0x0000_5555_5555_0000;
+
+/// `AT_RANDOM` is specified as 16 bytes of kernel-supplied entropy; this
+/// crate has no RNG source yet, so this fixed pattern stands in for it the
+/// same way `PIE_LOAD_BASE` stands in for real ASLR.
+const AT_RANDOM_PLACEHOLDER: [u8; 16] = *b"DevineKernelAuxR";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TargetArch {
@@ -40,6 +105,52 @@ pub struct LoadedImage {
     pub program_header_offset: usize,
     pub program_header_entry_size: usize,
     pub program_header_count: usize,
+    /// `PT_INTERP`'s path (e.g. `/lib64/ld-linux-x86-64.so.2`), if present,
+    /// so the caller can decide whether to load an ld.so image instead of
+    /// jumping straight to `entry_point`.
+    pub interpreter: Option<Vec<u8>>,
+    /// `NT_GNU_BUILD_ID`'s raw note descriptor, if a `PT_NOTE` segment has one.
+    pub build_id: Option<Vec<u8>>,
+    /// Hardware-hardening requirements declared by an
+    /// `NT_GNU_PROPERTY_TYPE_0` note, so the kernel can refuse to run the
+    /// image or configure control registers / page tables accordingly.
+    pub required_features: HwFeatures,
+}
+
+/// CET (x86 IBT/shadow-stack) and BTI/PAC (AArch64) requirements declared by
+/// a `PT_NOTE` `NT_GNU_PROPERTY_TYPE_0` note's `GNU_PROPERTY_*_FEATURE_1_AND`
+/// entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HwFeatures {
+    pub x86_ibt: bool,
+    pub x86_shstk: bool,
+    pub aarch64_bti: bool,
+    pub aarch64_pac: bool,
+}
+
+/// Auxv inputs that can't be derived from the ELF image alone: CPU feature
+/// bits for `AT_HWCAP`/`AT_HWCAP2`, the scheduling clock tick rate, whether
+/// this exec is running under setuid/setgid (`AT_SECURE`), and a vDSO
+/// mapping's base address for `AT_SYSINFO_EHDR`, if one is mapped.
+#[derive(Debug, Clone, Copy)]
+pub struct AuxConfig {
+    pub hwcap: u64,
+    pub hwcap2: u64,
+    pub clock_tick: u64,
+    pub secure: bool,
+    pub vdso_base: Option<u64>,
+}
+
+impl Default for AuxConfig {
+    fn default() -> Self {
+        Self {
+            hwcap: 0,
+            hwcap2: 0,
+            clock_tick: 100,
+            secure: false,
+            vdso_base: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -77,16 +188,20 @@ pub enum ElfLoaderError {
     MissingLoadSegment,
     StackOverflow,
     RelocationUnsupported,
+    UnresolvedSymbol,
+    OverlappingSegment,
+    MisalignedSegment,
+    WriteExecSegment,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum ElfClass {
+pub(crate) enum ElfClass {
     Elf32,
     Elf64,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Endianness {
+pub(crate) enum Endianness {
     Little,
     Big,
 }
@@ -95,6 +210,7 @@ enum Endianness {
 struct ElfHeader {
     class: ElfClass,
     endian: Endianness,
+    e_type: u16,
     machine: u16,
     entry: u64,
     phoff: u64,
@@ -105,6 +221,23 @@ struct ElfHeader {
     shnum: u16,
 }
 
+/// A `PT_DYNAMIC` segment's relevant `(tag, val)` entries, as walked by
+/// [`parse_dynamic_info`].
+#[derive(Debug, Clone, Copy, Default)]
+struct DynamicInfo {
+    rela_vaddr: Option<u64>,
+    rela_size: u64,
+    rela_entsize: u64,
+    rel_vaddr: Option<u64>,
+    rel_size: u64,
+    rel_entsize: u64,
+    jmprel_vaddr: Option<u64>,
+    pltrelsz: u64,
+    pltrel_is_rela: bool,
+    hash_vaddr: Option<u64>,
+    gnu_hash_vaddr: Option<u64>,
+}
+
 #[derive(Debug, Clone)]
 struct ProgramHeader {
     typ: u32,
@@ -122,6 +255,7 @@ struct SectionHeader {
     sh_offset: u64,
     sh_size: u64,
     sh_entsize: u64,
+    sh_link: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -129,12 +263,50 @@ struct Relocation {
     offset: u64,
     addend: i64,
     kind: RelocationKind,
+    /// Index into the symbol table; only meaningful for `Abs64`/`SymbolValue`.
+    sym: u32,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum RelocationKind {
     X86Relative,
     AArch64Relative,
+    /// `R_X86_64_64` / `R_AARCH64_ABS64`: `symbol.value + addend`.
+    Abs64,
+    /// `R_X86_64_GLOB_DAT`/`R_X86_64_JUMP_SLOT` and the AArch64 equivalents:
+    /// `symbol.value` alone.
+    SymbolValue,
+}
+
+/// Mirrors `Elf{32,64}_Sym`: 16 bytes on Elf32, 24 on Elf64.
+#[derive(Debug, Clone)]
+pub(crate) struct ElfSymbol {
+    name: u32,
+    info: u8,
+    other: u8,
+    pub(crate) shndx: u16,
+    pub(crate) value: u64,
+    size: u64,
+}
+
+/// A parsed `SHT_DYNSYM`/`SHT_SYMTAB` section plus its string table, so a
+/// future dynamic linker can resolve `ElfSymbol::name` into an actual name
+/// without re-parsing the section table.
+pub(crate) struct SymbolTable {
+    pub(crate) symbols: Vec<ElfSymbol>,
+    strtab: Vec<u8>,
+}
+
+impl SymbolTable {
+    pub(crate) fn name(&self, symbol: &ElfSymbol) -> &[u8] {
+        match self.strtab.get(symbol.name as usize..) {
+            Some(rest) => {
+                let len = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+                &rest[..len]
+            }
+            None => &[],
+        }
+    }
 }
 
 pub fn load_executable(
@@ -143,32 +315,63 @@ pub fn load_executable(
     address_space: &AddressSpace,
     argv: &[&str],
     envp: &[&str],
+    aux_config: &AuxConfig,
+    strict: bool,
 ) -> Result<LoadedImage, ElfLoaderError> {
     let header = parse_header(image)?;
     validate_machine(&header, arch)?;
 
     let program_headers = parse_program_headers(image, &header)?;
+    validate_segment_layout(&program_headers, strict)?;
     let mut segments = build_segments(image, &header, &program_headers)?;
 
     let sections = parse_section_headers(image, &header)?;
-    let relocations = parse_relocations(image, &header, &sections, arch)?;
-    apply_relocations(&mut segments, &relocations, arch, header.class)?;
+    let mut relocations = parse_relocations(image, &header, &sections, arch)?;
+    relocations.extend(parse_dynamic_relocations(image, &header, &program_headers, arch)?);
+    let symbol_table = parse_symbol_table(image, &header, &sections)?;
+
+    // ET_DYN (PIE and shared objects) carries position-independent
+    // addresses; everything below is relative to a load base we pick,
+    // rather than the fixed addresses an ET_EXEC image already uses.
+    let base = if header.e_type == ET_DYN { PIE_LOAD_BASE } else { 0 };
+
+    apply_relocations(
+        &mut segments,
+        &relocations,
+        &symbol_table.symbols,
+        arch,
+        header.class,
+        header.endian,
+        base,
+    )?;
+
+    if base != 0 {
+        for segment in &mut segments {
+            segment.vaddr = VirtAddr::new(segment.vaddr.0 + base);
+        }
+    }
 
     if segments.is_empty() {
         return Err(ElfLoaderError::MissingLoadSegment);
     }
 
-    let aux = build_auxiliary(&header, argv.len(), envp.len());
-    let stack = build_stack(address_space, argv, envp, &aux)?;
+    let interpreter = find_interpreter(image, &program_headers)?;
+    let (build_id, required_features) = parse_notes(image, &header, &program_headers)?;
+
+    let mut aux = build_auxiliary(&header, &program_headers, base, aux_config);
+    let stack = build_stack(address_space, argv, envp, &mut aux)?;
 
     Ok(LoadedImage {
-        entry_point: VirtAddr::new(header.entry),
+        entry_point: VirtAddr::new(header.entry + base),
         segments,
         auxiliary: aux,
         stack,
         program_header_offset: header.phoff as usize,
         program_header_entry_size: header.phentsize as usize,
         program_header_count: header.phnum as usize,
+        interpreter,
+        build_id,
+        required_features,
     })
 }
 
@@ -193,6 +396,7 @@ fn parse_header(bytes: &[u8]) -> Result<ElfHeader, ElfLoaderError> {
 
     let endian = match bytes[EI_DATA] {
         ELFDATA2LSB => Endianness::Little,
+        ELFDATA2MSB => Endianness::Big,
         _ => return Err(ElfLoaderError::UnsupportedEndianness),
     };
 
@@ -210,6 +414,7 @@ fn parse_header64(bytes: &[u8], endian: Endianness) -> Result<ElfHeader, ElfLoad
     Ok(ElfHeader {
         class: ElfClass::Elf64,
         endian,
+        e_type: read_u16(bytes, 16, endian)?,
         machine: read_u16(bytes, 18, endian)?,
         entry: read_u64(bytes, 24, endian)?,
         phoff: read_u64(bytes, 32, endian)?,
@@ -229,6 +434,7 @@ fn parse_header32(bytes: &[u8], endian: Endianness) -> Result<ElfHeader, ElfLoad
     Ok(ElfHeader {
         class: ElfClass::Elf32,
         endian,
+        e_type: read_u16(bytes, 16, endian)?,
         machine: read_u16(bytes, 18, endian)?,
         entry: read_u32(bytes, 24, endian)? as u64,
         phoff: read_u32(bytes, 28, endian)? as u64,
@@ -361,6 +567,7 @@ fn parse_section_header64(bytes: &[u8], endian: Endianness) -> Result<SectionHea
         sh_type: read_u32(bytes, 4, endian)?,
         sh_offset: read_u64(bytes, 24, endian)?,
         sh_size: read_u64(bytes, 32, endian)?,
+        sh_link: read_u32(bytes, 40, endian)?,
         sh_entsize: read_u64(bytes, 56, endian)?,
     })
 }
@@ -374,10 +581,98 @@ fn parse_section_header32(bytes: &[u8], endian: Endianness) -> Result<SectionHea
         sh_type: read_u32(bytes, 4, endian)?,
         sh_offset: read_u32(bytes, 16, endian)? as u64,
         sh_size: read_u32(bytes, 20, endian)? as u64,
+        sh_link: read_u32(bytes, 24, endian)?,
         sh_entsize: read_u32(bytes, 36, endian)? as u64,
     })
 }
 
+/// Parse the section-table-described symbol table: `SHT_DYNSYM` if present
+/// (what dynamic relocations resolve against), falling back to
+/// `SHT_SYMTAB`, plus the string table its `sh_link` points at.
+fn parse_symbol_table(
+    bytes: &[u8],
+    header: &ElfHeader,
+    sections: &[SectionHeader],
+) -> Result<SymbolTable, ElfLoaderError> {
+    let symtab = sections
+        .iter()
+        .find(|s| s.sh_type == SHT_DYNSYM)
+        .or_else(|| sections.iter().find(|s| s.sh_type == SHT_SYMTAB));
+
+    let Some(symtab) = symtab else {
+        return Ok(SymbolTable { symbols: Vec::new(), strtab: Vec::new() });
+    };
+
+    let strtab_section = sections
+        .get(symtab.sh_link as usize)
+        .ok_or(ElfLoaderError::InvalidHeader)?;
+    let strtab_start = strtab_section.sh_offset as usize;
+    let strtab_end = strtab_start
+        .checked_add(strtab_section.sh_size as usize)
+        .ok_or(ElfLoaderError::UnexpectedEof)?;
+    if strtab_end > bytes.len() {
+        return Err(ElfLoaderError::UnexpectedEof);
+    }
+    let strtab = bytes[strtab_start..strtab_end].to_vec();
+
+    let entsize = if symtab.sh_entsize == 0 {
+        match header.class {
+            ElfClass::Elf64 => 24,
+            ElfClass::Elf32 => 16,
+        }
+    } else {
+        symtab.sh_entsize as usize
+    };
+
+    let offset = symtab.sh_offset as usize;
+    let end = offset
+        .checked_add(symtab.sh_size as usize)
+        .ok_or(ElfLoaderError::UnexpectedEof)?;
+    if end > bytes.len() {
+        return Err(ElfLoaderError::UnexpectedEof);
+    }
+
+    let mut symbols = Vec::new();
+    let mut cursor = offset;
+    while cursor + entsize <= end {
+        symbols.push(parse_symbol(&bytes[cursor..cursor + entsize], header.class, header.endian)?);
+        cursor += entsize;
+    }
+
+    Ok(SymbolTable { symbols, strtab })
+}
+
+fn parse_symbol(bytes: &[u8], class: ElfClass, endian: Endianness) -> Result<ElfSymbol, ElfLoaderError> {
+    match class {
+        ElfClass::Elf64 => {
+            if bytes.len() < 24 {
+                return Err(ElfLoaderError::InvalidHeader);
+            }
+            Ok(ElfSymbol {
+                name: read_u32(bytes, 0, endian)?,
+                info: bytes[4],
+                other: bytes[5],
+                shndx: read_u16(bytes, 6, endian)?,
+                value: read_u64(bytes, 8, endian)?,
+                size: read_u64(bytes, 16, endian)?,
+            })
+        }
+        ElfClass::Elf32 => {
+            if bytes.len() < 16 {
+                return Err(ElfLoaderError::InvalidHeader);
+            }
+            Ok(ElfSymbol {
+                name: read_u32(bytes, 0, endian)?,
+                value: read_u32(bytes, 4, endian)? as u64,
+                size: read_u32(bytes, 8, endian)? as u64,
+                info: bytes[12],
+                other: bytes[13],
+                shndx: read_u16(bytes, 14, endian)?,
+            })
+        }
+    }
+}
+
 fn parse_relocations(
     bytes: &[u8],
     header: &ElfHeader,
@@ -399,24 +694,349 @@ fn parse_relocations(
             section.sh_entsize as usize
         };
 
-        let mut offset = section.sh_offset as usize;
-        let end = offset + section.sh_size as usize;
+        relocs.extend(parse_relocation_table(
+            bytes,
+            section.sh_offset as usize,
+            section.sh_size as usize,
+            entsize,
+            header.class,
+            header.endian,
+            section.sh_type,
+            arch,
+        )?);
+    }
+    Ok(relocs)
+}
+
+/// Walk `PT_DYNAMIC`'s `(d_tag, d_val)` array for the `DT_RELA`/`DT_REL`
+/// and `DT_JMPREL` relocation tables. Stripped binaries have no section
+/// headers at all, so this is what real loaders rely on instead of
+/// `parse_relocations`'s section-table scan.
+fn parse_dynamic_relocations(
+    bytes: &[u8],
+    header: &ElfHeader,
+    program_headers: &[ProgramHeader],
+    arch: TargetArch,
+) -> Result<Vec<Relocation>, ElfLoaderError> {
+    let Some(info) = parse_dynamic_info(bytes, header, program_headers)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut relocs = Vec::new();
+
+    if let Some(vaddr) = info.rela_vaddr {
+        let offset = vaddr_to_file_offset(program_headers, vaddr)
+            .ok_or(ElfLoaderError::InvalidProgramHeader)?;
+        let entsize = if info.rela_entsize == 0 {
+            match header.class {
+                ElfClass::Elf64 => 24,
+                ElfClass::Elf32 => 12,
+            }
+        } else {
+            info.rela_entsize as usize
+        };
+        relocs.extend(parse_relocation_table(
+            bytes, offset, info.rela_size as usize, entsize, header.class, header.endian,
+            SHT_RELA, arch,
+        )?);
+    }
+
+    if let Some(vaddr) = info.rel_vaddr {
+        let offset = vaddr_to_file_offset(program_headers, vaddr)
+            .ok_or(ElfLoaderError::InvalidProgramHeader)?;
+        let entsize = if info.rel_entsize == 0 {
+            match header.class {
+                ElfClass::Elf64 => 16,
+                ElfClass::Elf32 => 8,
+            }
+        } else {
+            info.rel_entsize as usize
+        };
+        relocs.extend(parse_relocation_table(
+            bytes, offset, info.rel_size as usize, entsize, header.class, header.endian,
+            SHT_REL, arch,
+        )?);
+    }
+
+    if let Some(vaddr) = info.jmprel_vaddr {
+        let offset = vaddr_to_file_offset(program_headers, vaddr)
+            .ok_or(ElfLoaderError::InvalidProgramHeader)?;
+        let sh_type = if info.pltrel_is_rela { SHT_RELA } else { SHT_REL };
+        let entsize = match (header.class, info.pltrel_is_rela) {
+            (ElfClass::Elf64, true) => 24,
+            (ElfClass::Elf64, false) => 16,
+            (ElfClass::Elf32, true) => 12,
+            (ElfClass::Elf32, false) => 8,
+        };
+        relocs.extend(parse_relocation_table(
+            bytes, offset, info.pltrelsz as usize, entsize, header.class, header.endian,
+            sh_type, arch,
+        )?);
+    }
+
+    Ok(relocs)
+}
+
+/// Parse a raw table of relocation entries (shared by the section-header
+/// scan and the `PT_DYNAMIC`-described tables).
+fn parse_relocation_table(
+    bytes: &[u8],
+    offset: usize,
+    size: usize,
+    entsize: usize,
+    class: ElfClass,
+    endian: Endianness,
+    sh_type: u32,
+    arch: TargetArch,
+) -> Result<Vec<Relocation>, ElfLoaderError> {
+    if entsize == 0 {
+        return Err(ElfLoaderError::InvalidHeader);
+    }
+
+    let end = offset.checked_add(size).ok_or(ElfLoaderError::UnexpectedEof)?;
+    if end > bytes.len() {
+        return Err(ElfLoaderError::UnexpectedEof);
+    }
+
+    let mut relocs = Vec::new();
+    let mut cursor = offset;
+    while cursor + entsize <= end {
+        relocs.push(parse_relocation(
+            &bytes[cursor..cursor + entsize],
+            class,
+            endian,
+            sh_type,
+            arch,
+        )?);
+        cursor += entsize;
+    }
+    Ok(relocs)
+}
+
+/// Walk a `PT_DYNAMIC` segment's `(d_tag, d_val)` array, collecting the
+/// tags relevant to finding its relocation tables. Returns `None` if the
+/// image has no `PT_DYNAMIC` segment (a plain static ET_EXEC).
+fn parse_dynamic_info(
+    bytes: &[u8],
+    header: &ElfHeader,
+    program_headers: &[ProgramHeader],
+) -> Result<Option<DynamicInfo>, ElfLoaderError> {
+    let Some(dynamic_ph) = program_headers.iter().find(|ph| ph.typ == PT_DYNAMIC) else {
+        return Ok(None);
+    };
+
+    let entsize = match header.class {
+        ElfClass::Elf64 => 16,
+        ElfClass::Elf32 => 8,
+    };
+
+    let offset = dynamic_ph.offset as usize;
+    let end = offset.checked_add(dynamic_ph.filesz as usize).ok_or(ElfLoaderError::UnexpectedEof)?;
+    if end > bytes.len() {
+        return Err(ElfLoaderError::UnexpectedEof);
+    }
+
+    let mut info = DynamicInfo::default();
+    let mut cursor = offset;
+    while cursor + entsize <= end {
+        let (tag, val) = match header.class {
+            ElfClass::Elf64 => (
+                read_u64(bytes, cursor, header.endian)?,
+                read_u64(bytes, cursor + 8, header.endian)?,
+            ),
+            ElfClass::Elf32 => (
+                read_u32(bytes, cursor, header.endian)? as u64,
+                read_u32(bytes, cursor + 4, header.endian)? as u64,
+            ),
+        };
+
+        match tag {
+            DT_NULL => break,
+            DT_RELA => info.rela_vaddr = Some(val),
+            DT_RELASZ => info.rela_size = val,
+            DT_RELAENT => info.rela_entsize = val,
+            DT_REL => info.rel_vaddr = Some(val),
+            DT_RELSZ => info.rel_size = val,
+            DT_RELENT => info.rel_entsize = val,
+            DT_JMPREL => info.jmprel_vaddr = Some(val),
+            DT_PLTRELSZ => info.pltrelsz = val,
+            DT_PLTREL => info.pltrel_is_rela = val == DT_RELA,
+            DT_HASH => info.hash_vaddr = Some(val),
+            DT_GNU_HASH => info.gnu_hash_vaddr = Some(val),
+            _ => {}
+        }
+
+        cursor += entsize;
+    }
+
+    Ok(Some(info))
+}
+
+/// Translate a (pre-relocation) virtual address into a file offset by
+/// finding the `PT_LOAD` segment that covers it, the way `PT_DYNAMIC`'s
+/// `d_val` table addresses need resolving since they describe locations
+/// inside loaded segments rather than file offsets directly.
+fn vaddr_to_file_offset(program_headers: &[ProgramHeader], vaddr: u64) -> Option<usize> {
+    program_headers
+        .iter()
+        .find(|ph| ph.typ == PT_LOAD && vaddr >= ph.vaddr && vaddr < ph.vaddr + ph.filesz)
+        .map(|ph| (ph.offset + (vaddr - ph.vaddr)) as usize)
+}
+
+/// Read `PT_INTERP`'s NUL-terminated interpreter path out of the file, if
+/// present, so the caller can decide whether to load an ld.so image.
+fn find_interpreter(
+    bytes: &[u8],
+    program_headers: &[ProgramHeader],
+) -> Result<Option<Vec<u8>>, ElfLoaderError> {
+    let Some(ph) = program_headers.iter().find(|ph| ph.typ == PT_INTERP) else {
+        return Ok(None);
+    };
+
+    let start = ph.offset as usize;
+    let end = start.checked_add(ph.filesz as usize).ok_or(ElfLoaderError::UnexpectedEof)?;
+    if end > bytes.len() {
+        return Err(ElfLoaderError::UnexpectedEof);
+    }
+
+    let raw = &bytes[start..end];
+    let len = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+    Ok(Some(raw[..len].to_vec()))
+}
+
+/// Walk every `PT_NOTE` segment's note records
+/// (`[namesz, descsz, ntype, name[namesz], desc[descsz]]`, name and desc
+/// each 4-byte aligned), capturing `NT_GNU_BUILD_ID`'s descriptor and
+/// `NT_GNU_PROPERTY_TYPE_0`'s CET/BTI/PAC feature bits.
+fn parse_notes(
+    bytes: &[u8],
+    header: &ElfHeader,
+    program_headers: &[ProgramHeader],
+) -> Result<(Option<Vec<u8>>, HwFeatures), ElfLoaderError> {
+    let endian = header.endian;
+    let mut build_id = None;
+    let mut features = HwFeatures::default();
+
+    for ph in program_headers {
+        if ph.typ != PT_NOTE {
+            continue;
+        }
+
+        let start = ph.offset as usize;
+        let end = start.checked_add(ph.filesz as usize).ok_or(ElfLoaderError::UnexpectedEof)?;
         if end > bytes.len() {
             return Err(ElfLoaderError::UnexpectedEof);
         }
 
-        while offset < end {
-            relocs.push(parse_relocation(
-                &bytes[offset..offset + entsize],
-                header.class,
-                header.endian,
-                section.sh_type,
-                arch,
-            )?);
-            offset += entsize;
+        let mut cursor = start;
+        while cursor + 12 <= end {
+            let namesz = read_u32(bytes, cursor, endian)? as usize;
+            let descsz = read_u32(bytes, cursor + 4, endian)? as usize;
+            let ntype = read_u32(bytes, cursor + 8, endian)?;
+            cursor += 12;
+
+            let name_end = cursor.checked_add(namesz).ok_or(ElfLoaderError::UnexpectedEof)?;
+            if name_end > end {
+                return Err(ElfLoaderError::UnexpectedEof);
+            }
+            let name = &bytes[cursor..name_end];
+            cursor = align_up(name_end, 4);
+
+            let desc_end = cursor.checked_add(descsz).ok_or(ElfLoaderError::UnexpectedEof)?;
+            if desc_end > end {
+                return Err(ElfLoaderError::UnexpectedEof);
+            }
+            let desc = &bytes[cursor..desc_end];
+            cursor = align_up(desc_end, 4);
+
+            if ntype == NT_GNU_BUILD_ID {
+                build_id = Some(desc.to_vec());
+            } else if ntype == NT_GNU_PROPERTY_TYPE_0 && name == b"GNU\0" {
+                parse_gnu_properties(desc, endian, &mut features)?;
+            }
         }
     }
-    Ok(relocs)
+
+    Ok((build_id, features))
+}
+
+/// Iterate an `NT_GNU_PROPERTY_TYPE_0` descriptor's `[pr_type, pr_datasz,
+/// data...]` array and fold any recognized `GNU_PROPERTY_*_FEATURE_1_AND`
+/// bits into `features`.
+fn parse_gnu_properties(desc: &[u8], endian: Endianness, features: &mut HwFeatures) -> Result<(), ElfLoaderError> {
+    let mut cursor = 0;
+    while cursor + 8 <= desc.len() {
+        let pr_type = read_u32(desc, cursor, endian)?;
+        let pr_datasz = read_u32(desc, cursor + 4, endian)? as usize;
+        cursor += 8;
+
+        let data_end = cursor.checked_add(pr_datasz).ok_or(ElfLoaderError::UnexpectedEof)?;
+        if data_end > desc.len() {
+            return Err(ElfLoaderError::UnexpectedEof);
+        }
+
+        if pr_datasz >= 4 {
+            let bits = read_u32(desc, cursor, endian)?;
+            match pr_type {
+                GNU_PROPERTY_X86_FEATURE_1_AND => {
+                    features.x86_ibt = bits & GNU_PROPERTY_X86_FEATURE_1_IBT != 0;
+                    features.x86_shstk = bits & GNU_PROPERTY_X86_FEATURE_1_SHSTK != 0;
+                }
+                GNU_PROPERTY_AARCH64_FEATURE_1_AND => {
+                    features.aarch64_bti = bits & GNU_PROPERTY_AARCH64_FEATURE_1_BTI != 0;
+                    features.aarch64_pac = bits & GNU_PROPERTY_AARCH64_FEATURE_1_PAC != 0;
+                }
+                _ => {}
+            }
+        }
+
+        cursor = align_up(data_end, 4);
+    }
+    Ok(())
+}
+
+/// The raw `DT_HASH`/`DT_GNU_HASH` table bytes found via `PT_DYNAMIC`, for
+/// [`super::elf_hash`] to parse, plus the class/endianness needed to read
+/// their fields. Slices run to the end of the file rather than being
+/// bounded to the table's own size, since neither hash format encodes its
+/// own length up front; the readers bounds-check each field they touch.
+#[allow(dead_code)]
+pub(crate) struct HashTables<'a> {
+    pub(crate) class: ElfClass,
+    pub(crate) endian: Endianness,
+    pub(crate) sysv: Option<&'a [u8]>,
+    pub(crate) gnu: Option<&'a [u8]>,
+}
+
+/// Locate the `DT_HASH` (SysV) and `DT_GNU_HASH` (GNU) symbol hash tables
+/// described by `PT_DYNAMIC`, if any, translating their virtual addresses
+/// to file offsets the same way `parse_dynamic_relocations` does.
+#[allow(dead_code)]
+pub(crate) fn locate_hash_tables(bytes: &[u8]) -> Result<HashTables<'_>, ElfLoaderError> {
+    let header = parse_header(bytes)?;
+    let program_headers = parse_program_headers(bytes, &header)?;
+
+    let Some(info) = parse_dynamic_info(bytes, &header, &program_headers)? else {
+        return Ok(HashTables { class: header.class, endian: header.endian, sysv: None, gnu: None });
+    };
+
+    let resolve = |vaddr: Option<u64>| -> Result<Option<&[u8]>, ElfLoaderError> {
+        match vaddr {
+            Some(vaddr) => {
+                let offset = vaddr_to_file_offset(&program_headers, vaddr)
+                    .ok_or(ElfLoaderError::InvalidProgramHeader)?;
+                Ok(bytes.get(offset..))
+            }
+            None => Ok(None),
+        }
+    };
+
+    Ok(HashTables {
+        class: header.class,
+        endian: header.endian,
+        sysv: resolve(info.hash_vaddr)?,
+        gnu: resolve(info.gnu_hash_vaddr)?,
+    })
 }
 
 fn parse_relocation(
@@ -450,22 +1070,11 @@ fn parse_relocation64(
         0
     };
 
-    let kind = match arch {
-        TargetArch::X86_64 => {
-            if (info as u32) != R_X86_64_RELATIVE {
-                return Err(ElfLoaderError::RelocationUnsupported);
-            }
-            RelocationKind::X86Relative
-        }
-        TargetArch::AArch64 => {
-            if (info as u32) != R_AARCH64_RELATIVE {
-                return Err(ElfLoaderError::RelocationUnsupported);
-            }
-            RelocationKind::AArch64Relative
-        }
-    };
+    let r_type = info as u32;
+    let sym = (info >> 32) as u32;
+    let kind = reloc_kind(arch, r_type)?;
 
-    Ok(Relocation { offset, addend, kind })
+    Ok(Relocation { offset, addend, kind, sym })
 }
 
 fn parse_relocation32(
@@ -486,22 +1095,29 @@ fn parse_relocation32(
         0
     };
 
-    let kind = match arch {
-        TargetArch::X86_64 => {
-            if (info & 0xff) != R_X86_64_RELATIVE {
-                return Err(ElfLoaderError::RelocationUnsupported);
-            }
-            RelocationKind::X86Relative
-        }
-        TargetArch::AArch64 => {
-            if (info & 0xff) != R_AARCH64_RELATIVE {
-                return Err(ElfLoaderError::RelocationUnsupported);
-            }
-            RelocationKind::AArch64Relative
-        }
-    };
+    let r_type = info & 0xff;
+    let sym = info >> 8;
+    let kind = reloc_kind(arch, r_type)?;
 
-    Ok(Relocation { offset, addend, kind })
+    Ok(Relocation { offset, addend, kind, sym })
+}
+
+/// Map a raw relocation type to the kinds this loader resolves, by arch.
+fn reloc_kind(arch: TargetArch, r_type: u32) -> Result<RelocationKind, ElfLoaderError> {
+    match arch {
+        TargetArch::X86_64 => match r_type {
+            R_X86_64_RELATIVE => Ok(RelocationKind::X86Relative),
+            R_X86_64_64 => Ok(RelocationKind::Abs64),
+            R_X86_64_GLOB_DAT | R_X86_64_JUMP_SLOT => Ok(RelocationKind::SymbolValue),
+            _ => Err(ElfLoaderError::RelocationUnsupported),
+        },
+        TargetArch::AArch64 => match r_type {
+            R_AARCH64_RELATIVE => Ok(RelocationKind::AArch64Relative),
+            R_AARCH64_ABS64 => Ok(RelocationKind::Abs64),
+            R_AARCH64_GLOB_DAT | R_AARCH64_JUMP_SLOT => Ok(RelocationKind::SymbolValue),
+            _ => Err(ElfLoaderError::RelocationUnsupported),
+        },
+    }
 }
 
 fn build_segments(
@@ -547,39 +1163,96 @@ fn build_segments(
     Ok(segments)
 }
 
+/// Defensive validation of the `PT_LOAD` program headers a hostile or
+/// malformed image could otherwise use to produce unsound page tables:
+/// `vaddr`/`offset` congruent modulo a power-of-two `align`, no two LOAD
+/// segments' `[vaddr, vaddr+memsz)` ranges overlapping, and — when `strict`
+/// — no segment simultaneously writable and executable.
+fn validate_segment_layout(program_headers: &[ProgramHeader], strict: bool) -> Result<(), ElfLoaderError> {
+    let loads: Vec<&ProgramHeader> = program_headers.iter().filter(|ph| ph.typ == PT_LOAD).collect();
+
+    for ph in &loads {
+        if ph.align > 1 {
+            if !ph.align.is_power_of_two() {
+                return Err(ElfLoaderError::MisalignedSegment);
+            }
+            if ph.vaddr.wrapping_sub(ph.offset) % ph.align != 0 {
+                return Err(ElfLoaderError::MisalignedSegment);
+            }
+        }
+
+        if strict && ph.flags & PF_WRITE != 0 && ph.flags & PF_EXECUTE != 0 {
+            return Err(ElfLoaderError::WriteExecSegment);
+        }
+    }
+
+    for (i, a) in loads.iter().enumerate() {
+        for b in &loads[i + 1..] {
+            if a.vaddr < b.vaddr + b.memsz && b.vaddr < a.vaddr + a.memsz {
+                return Err(ElfLoaderError::OverlappingSegment);
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn apply_relocations(
     segments: &mut [Segment],
     relocations: &[Relocation],
+    symbols: &[ElfSymbol],
     arch: TargetArch,
     class: ElfClass,
+    endian: Endianness,
+    base: u64,
 ) -> Result<(), ElfLoaderError> {
     if relocations.is_empty() {
         return Ok(());
     }
 
     for reloc in relocations {
+        let value = match (arch, reloc.kind) {
+            (TargetArch::X86_64, RelocationKind::X86Relative)
+            | (TargetArch::AArch64, RelocationKind::AArch64Relative) => {
+                base.wrapping_add(reloc.addend as u64)
+            }
+            (_, RelocationKind::Abs64) | (_, RelocationKind::SymbolValue) => {
+                let symbol = symbols
+                    .get(reloc.sym as usize)
+                    .ok_or(ElfLoaderError::UnresolvedSymbol)?;
+                if symbol.shndx == SHN_UNDEF {
+                    return Err(ElfLoaderError::UnresolvedSymbol);
+                }
+                let resolved = base.wrapping_add(symbol.value);
+                if reloc.kind == RelocationKind::Abs64 {
+                    resolved.wrapping_add(reloc.addend as u64)
+                } else {
+                    resolved
+                }
+            }
+            _ => return Err(ElfLoaderError::RelocationUnsupported),
+        };
+
         if let Some(segment) = find_segment_mut(segments, reloc.offset) {
             let offset = (reloc.offset - segment.vaddr.0) as usize;
-            match (arch, reloc.kind) {
-                (TargetArch::X86_64, RelocationKind::X86Relative)
-                | (TargetArch::AArch64, RelocationKind::AArch64Relative) => {
-                    let bytes = match class {
-                        ElfClass::Elf32 => 4,
-                        ElfClass::Elf64 => 8,
-                    };
-                    if offset + bytes > segment.data.len() {
-                        return Err(ElfLoaderError::UnexpectedEof);
-                    }
-                    let value = reloc.addend as u64;
-                    if bytes == 4 {
-                        segment.data[offset..offset + 4]
-                            .copy_from_slice(&(value as u32).to_le_bytes());
-                    } else {
-                        segment.data[offset..offset + 8]
-                            .copy_from_slice(&value.to_le_bytes());
-                    }
-                }
-                _ => return Err(ElfLoaderError::RelocationUnsupported),
+            let bytes = match class {
+                ElfClass::Elf32 => 4,
+                ElfClass::Elf64 => 8,
+            };
+            if offset + bytes > segment.data.len() {
+                return Err(ElfLoaderError::UnexpectedEof);
+            }
+            if bytes == 4 {
+                let word = value as u32;
+                segment.data[offset..offset + 4].copy_from_slice(&match endian {
+                    Endianness::Little => word.to_le_bytes(),
+                    Endianness::Big => word.to_be_bytes(),
+                });
+            } else {
+                segment.data[offset..offset + 8].copy_from_slice(&match endian {
+                    Endianness::Little => value.to_le_bytes(),
+                    Endianness::Big => value.to_be_bytes(),
+                });
             }
         }
     }
@@ -592,29 +1265,57 @@ fn find_segment_mut<'a>(segments: &'a mut [Segment], addr: u64) -> Option<&'a mu
     })
 }
 
-fn build_auxiliary(header: &ElfHeader, argc: usize, envc: usize) -> Vec<AuxEntry> {
-    vec![
-        AuxEntry { key: 3, value: header.phoff as usize },   // AT_PHDR
-        AuxEntry { key: 4, value: header.phentsize as usize }, // AT_PHENT
-        AuxEntry { key: 5, value: header.phnum as usize },    // AT_PHNUM
-        AuxEntry { key: 6, value: PAGE_SIZE },                // AT_PAGESZ
-        AuxEntry { key: 7, value: 0 },                        // AT_BASE (not used)
-        AuxEntry { key: 9, value: header.entry as usize },    // AT_ENTRY
-        AuxEntry { key: 11, value: 0 },                       // AT_UID
-        AuxEntry { key: 12, value: 0 },                       // AT_EUID
-        AuxEntry { key: 13, value: 0 },                       // AT_GID
-        AuxEntry { key: 14, value: 0 },                       // AT_EGID
-        AuxEntry { key: 17, value: argc },                    // AT_EXECFN surrogate for argc
-        AuxEntry { key: 23, value: envc },                    // AT_SECURE surrogate for env count
-        AuxEntry { key: 0, value: 0 },                        // AT_NULL
-    ]
+/// Find the `PT_LOAD` segment containing the program header table (at file
+/// offset `header.phoff`) and translate that into the virtual address it's
+/// mapped at, for `AT_PHDR`. Falls back to the raw file offset if no
+/// segment covers it (malformed image; better than an obviously-wrong 0).
+fn phdr_vaddr(program_headers: &[ProgramHeader], phoff: u64) -> u64 {
+    program_headers
+        .iter()
+        .find(|ph| ph.typ == PT_LOAD && phoff >= ph.offset && phoff < ph.offset + ph.filesz)
+        .map(|ph| ph.vaddr + (phoff - ph.offset))
+        .unwrap_or(phoff)
+}
+
+/// Build the auxv, minus `AT_RANDOM`/`AT_EXECFN`, whose values are stack
+/// addresses `build_stack` only knows once it's laid out the strings it
+/// points at; those two entries are patched in place once that's done.
+fn build_auxiliary(
+    header: &ElfHeader,
+    program_headers: &[ProgramHeader],
+    base: u64,
+    aux_config: &AuxConfig,
+) -> Vec<AuxEntry> {
+    let mut entries = vec![
+        AuxEntry { key: AT_PHDR, value: phdr_vaddr(program_headers, header.phoff) as usize + base as usize },
+        AuxEntry { key: AT_PHENT, value: header.phentsize as usize },
+        AuxEntry { key: AT_PHNUM, value: header.phnum as usize },
+        AuxEntry { key: AT_PAGESZ, value: PAGE_SIZE },
+        AuxEntry { key: AT_BASE, value: base as usize }, // load base for ET_DYN/PIE
+        AuxEntry { key: AT_ENTRY, value: header.entry as usize + base as usize },
+        AuxEntry { key: AT_UID, value: 0 },
+        AuxEntry { key: AT_EUID, value: 0 },
+        AuxEntry { key: AT_GID, value: 0 },
+        AuxEntry { key: AT_EGID, value: 0 },
+        AuxEntry { key: AT_HWCAP, value: aux_config.hwcap as usize },
+        AuxEntry { key: AT_CLKTCK, value: aux_config.clock_tick as usize },
+        AuxEntry { key: AT_SECURE, value: aux_config.secure as usize },
+        AuxEntry { key: AT_RANDOM, value: 0 }, // patched in `build_stack`
+        AuxEntry { key: AT_HWCAP2, value: aux_config.hwcap2 as usize },
+        AuxEntry { key: AT_EXECFN, value: 0 }, // patched in `build_stack`
+    ];
+    if let Some(vdso_base) = aux_config.vdso_base {
+        entries.push(AuxEntry { key: AT_SYSINFO_EHDR, value: vdso_base as usize });
+    }
+    entries.push(AuxEntry { key: AT_NULL, value: 0 });
+    entries
 }
 
 fn build_stack(
     address_space: &AddressSpace,
     argv: &[&str],
     envp: &[&str],
-    aux: &[AuxEntry],
+    aux: &mut [AuxEntry],
 ) -> Result<StackImage, ElfLoaderError> {
     fn write_u64(buf: &mut [u8], offset: usize, value: u64) -> Result<(), ElfLoaderError> {
         let slice = buf
@@ -635,7 +1336,8 @@ fn build_stack(
         .iter()
         .chain(envp.iter())
         .map(|entry| entry.as_bytes().len() + 1)
-        .sum();
+        .sum::<usize>()
+        + AT_RANDOM_PLACEHOLDER.len();
 
     let strings_offset = align_up(header_bytes, 16);
     let total = align_up(strings_offset + strings_size, 16);
@@ -659,6 +1361,7 @@ fn build_stack(
     write_u64(&mut data, 0, argc as u64)?;
 
     let mut str_cursor = strings_offset;
+    let mut execfn_ptr = 0u64;
     for (idx, arg) in argv.iter().enumerate() {
         let bytes = arg.as_bytes();
         let needed = bytes.len() + 1;
@@ -671,6 +1374,9 @@ fn build_stack(
 
         let ptr = user_sp_addr + str_cursor as u64;
         write_u64(&mut data, argv_offset + idx * ptr_size, ptr)?;
+        if idx == 0 {
+            execfn_ptr = ptr; // AT_EXECFN: the kernel resolves this from argv[0]
+        }
 
         str_cursor += needed;
     }
@@ -695,6 +1401,20 @@ fn build_stack(
 
     write_u64(&mut data, envp_offset + envc * ptr_size, 0)?;
 
+    if str_cursor + AT_RANDOM_PLACEHOLDER.len() > data.len() {
+        return Err(ElfLoaderError::StackOverflow);
+    }
+    data[str_cursor..str_cursor + AT_RANDOM_PLACEHOLDER.len()].copy_from_slice(&AT_RANDOM_PLACEHOLDER);
+    let random_ptr = user_sp_addr + str_cursor as u64;
+
+    for entry in aux.iter_mut() {
+        match entry.key {
+            AT_RANDOM => entry.value = random_ptr as usize,
+            AT_EXECFN => entry.value = execfn_ptr as usize,
+            _ => {}
+        }
+    }
+
     for (idx, entry) in aux.iter().enumerate() {
         write_u64(&mut data, aux_offset + idx * 2 * ptr_size, entry.key as u64)?;
         write_u64(
@@ -727,7 +1447,7 @@ fn read_u16(bytes: &[u8], offset: usize, endian: Endianness) -> Result<u16, ElfL
     })
 }
 
-fn read_u32(bytes: &[u8], offset: usize, endian: Endianness) -> Result<u32, ElfLoaderError> {
+pub(crate) fn read_u32(bytes: &[u8], offset: usize, endian: Endianness) -> Result<u32, ElfLoaderError> {
     let slice = bytes.get(offset..offset + 4).ok_or(ElfLoaderError::UnexpectedEof)?;
     Ok(match endian {
         Endianness::Little => u32::from_le_bytes(slice.try_into().unwrap()),
@@ -739,7 +1459,7 @@ fn read_i32(bytes: &[u8], offset: usize, endian: Endianness) -> Result<i32, ElfL
     read_u32(bytes, offset, endian).map(|value| value as i32)
 }
 
-fn read_u64(bytes: &[u8], offset: usize, endian: Endianness) -> Result<u64, ElfLoaderError> {
+pub(crate) fn read_u64(bytes: &[u8], offset: usize, endian: Endianness) -> Result<u64, ElfLoaderError> {
     let slice = bytes.get(offset..offset + 8).ok_or(ElfLoaderError::UnexpectedEof)?;
     Ok(match endian {
         Endianness::Little => u64::from_le_bytes(slice.try_into().unwrap()),
@@ -771,8 +1491,16 @@ mod tests {
 
     #[test]
     fn parse_minimal_elf() {
-        let image = load_executable(MINIMAL_ELF, TargetArch::X86_64, &dummy_space(), &[], &[])
-            .expect("failed to load ELF");
+        let image = load_executable(
+            MINIMAL_ELF,
+            TargetArch::X86_64,
+            &dummy_space(),
+            &[],
+            &[],
+            &AuxConfig::default(),
+            false,
+        )
+        .expect("failed to load ELF");
         assert_eq!(image.segments.len(), 1);
         assert!(image.entry_point.0 > 0);
     }
@@ -2,6 +2,7 @@ use super::Process;
 
 pub use super::elf_loader::{
     load_executable,
+    AuxConfig,
     AuxEntry,
     ElfLoaderError,
     LoadedImage,
@@ -16,8 +17,9 @@ pub fn exec_into_process(
     arch: TargetArch,
     argv: &[&str],
     envp: &[&str],
+    strict: bool,
 ) -> Result<LoadedImage, ElfLoaderError> {
-    let loaded = load_executable(image, arch, &process.address_space, argv, envp)?;
+    let loaded = load_executable(image, arch, &process.address_space, argv, envp, &AuxConfig::default(), strict)?;
     process.image = Some(loaded.clone());
     Ok(loaded)
 }
@@ -2,10 +2,12 @@ pub mod scheduler;
 pub mod context;
 pub mod thread;
 pub mod elf_loader;
+pub(crate) mod elf_hash;
 pub mod loader;
 
+use core::sync::atomic::{AtomicI64, AtomicU64, Ordering};
 use spin::Mutex;
-use crate::memory::{VirtAddr, frame_allocator::Frame};
+use crate::memory::{VirtAddr, frame_allocator::{self, Frame}};
 use crate::security::{self, SecurityContext};
 
 #[cfg(not(test))]
@@ -42,6 +44,18 @@ impl Priority {
     pub fn as_usize(&self) -> usize {
         *self as usize
     }
+
+    /// Inverse of `as_usize`, clamped to `Realtime` for any out-of-range
+    /// value so a miscalculated band index can't panic.
+    pub fn from_usize(v: usize) -> Priority {
+        match v {
+            0 => Priority::Idle,
+            1 => Priority::Low,
+            2 => Priority::Normal,
+            3 => Priority::High,
+            _ => Priority::Realtime,
+        }
+    }
 }
 
 #[cfg(not(test))]
@@ -55,11 +69,43 @@ pub enum PipeEndKind {
     Write,
 }
 
+/// Budget class a process is assigned at creation, mirroring the
+/// `ProcType` notion from console-kernel designs: how much syscall
+/// activity it's trusted with before `syscall::handle_syscall`'s metering
+/// makes it wait for a refill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcType {
+    /// Kernel-owned (Ring0) processes -- exempt from metering entirely.
+    System,
+    Daemon,
+    Application,
+}
+
+impl ProcType {
+    /// Compute units a freshly created process of this type starts with.
+    pub fn initial_budget(self) -> i64 {
+        match self {
+            ProcType::System => i64::MAX,
+            ProcType::Daemon => 100_000,
+            ProcType::Application => 20_000,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct PipeInner {
     buffer: VecDeque<u8>,
     readers: usize,
     writers: usize,
+    /// Threads parked in [`PipeEnd::read_blocking`] waiting for `buffer`
+    /// to stop being empty (or for the last writer to drop), woken one at
+    /// a time by a successful [`PipeEnd::write_blocking`] and all at once
+    /// once `writers` hits zero.
+    read_waiters: VecDeque<ThreadId>,
+    /// Threads parked in [`PipeEnd::write_blocking`] waiting for `buffer`
+    /// to stop being full, woken one at a time by a successful
+    /// [`PipeEnd::read_blocking`].
+    write_waiters: VecDeque<ThreadId>,
 }
 
 #[derive(Debug)]
@@ -74,6 +120,8 @@ impl PipeEnd {
             buffer: VecDeque::new(),
             readers: 1,
             writers: 1,
+            read_waiters: VecDeque::new(),
+            write_waiters: VecDeque::new(),
         }));
 
         let read_end = PipeEnd {
@@ -118,7 +166,6 @@ impl PipeEnd {
         }
 
         let mut inner = self.inner.lock();
-        const PIPE_CAPACITY: usize = 4096;
         let mut written = 0usize;
         while written < src.len() && inner.buffer.len() < PIPE_CAPACITY {
             inner.buffer.push_back(src[written]);
@@ -126,8 +173,89 @@ impl PipeEnd {
         }
         written
     }
+
+    /// Like [`read`](PipeEnd::read), but parks the calling thread instead
+    /// of returning `0` when `buffer` is empty and at least one writer is
+    /// still alive: pushes `tid` onto `read_waiters`, blocks it via
+    /// `scheduler::block_current_thread`, and returns `None` so the
+    /// caller knows to retry once woken (the same poll-then-block-then-
+    /// retry shape `syscall::sys_open`/`sys_wait` use against their own
+    /// event sources). `Some(0)` still means EOF -- empty buffer with no
+    /// writers left -- since there's nothing left to ever wake this
+    /// reader.
+    pub fn read_blocking(&self, tid: ThreadId, dst: &mut [u8]) -> Option<usize> {
+        if self.kind != PipeEndKind::Read {
+            return Some(0);
+        }
+
+        let mut inner = self.inner.lock();
+        if inner.buffer.is_empty() {
+            if inner.writers == 0 {
+                return Some(0);
+            }
+            inner.read_waiters.push_back(tid);
+            drop(inner);
+            scheduler::block_current_thread();
+            return None;
+        }
+
+        let mut read = 0usize;
+        while read < dst.len() {
+            match inner.buffer.pop_front() {
+                Some(byte) => {
+                    dst[read] = byte;
+                    read += 1;
+                }
+                None => break,
+            }
+        }
+        let waiter = inner.write_waiters.pop_front();
+        drop(inner);
+        if let Some(waiter) = waiter {
+            scheduler::unblock_thread(waiter);
+        }
+        Some(read)
+    }
+
+    /// Like [`write`](PipeEnd::write), but parks the calling thread
+    /// instead of returning a short write when `buffer` is full and at
+    /// least one reader is still alive -- mirrors
+    /// [`read_blocking`](PipeEnd::read_blocking)'s shape in the opposite
+    /// direction. Returns `None` to retry once woken; if no reader is
+    /// left to ever drain the buffer, writes what little room remains
+    /// (possibly `0`) and returns immediately rather than blocking
+    /// forever.
+    pub fn write_blocking(&self, tid: ThreadId, src: &[u8]) -> Option<usize> {
+        if self.kind != PipeEndKind::Write {
+            return Some(0);
+        }
+
+        let mut inner = self.inner.lock();
+        if inner.buffer.len() >= PIPE_CAPACITY && inner.readers > 0 {
+            inner.write_waiters.push_back(tid);
+            drop(inner);
+            scheduler::block_current_thread();
+            return None;
+        }
+
+        let mut written = 0usize;
+        while written < src.len() && inner.buffer.len() < PIPE_CAPACITY {
+            inner.buffer.push_back(src[written]);
+            written += 1;
+        }
+        let waiter = inner.read_waiters.pop_front();
+        drop(inner);
+        if let Some(waiter) = waiter {
+            scheduler::unblock_thread(waiter);
+        }
+        Some(written)
+    }
 }
 
+/// Byte capacity of a [`PipeEnd`] pair's shared buffer, shared by
+/// [`PipeEnd::write`] and [`PipeEnd::write_blocking`].
+const PIPE_CAPACITY: usize = 4096;
+
 impl Clone for PipeEnd {
     fn clone(&self) -> Self {
         {
@@ -146,11 +274,177 @@ impl Clone for PipeEnd {
 }
 
 impl Drop for PipeEnd {
+    fn drop(&mut self) {
+        let mut inner = self.inner.lock();
+        let woken = match self.kind {
+            PipeEndKind::Read => {
+                inner.readers = inner.readers.saturating_sub(1);
+                None
+            }
+            PipeEndKind::Write => {
+                inner.writers = inner.writers.saturating_sub(1);
+                // The last writer gone means `buffer` will never gain
+                // another byte -- wake every blocked reader so each sees
+                // EOF on its next `read_blocking` retry instead of
+                // waiting forever for data that can't arrive.
+                if inner.writers == 0 {
+                    Some(core::mem::take(&mut inner.read_waiters))
+                } else {
+                    None
+                }
+            }
+        };
+        drop(inner);
+        if let Some(waiters) = woken {
+            for tid in waiters {
+                scheduler::unblock_thread(tid);
+            }
+        }
+    }
+}
+
+/// A single discrete message passed through a [`TubeEnd`], preserving
+/// message boundaries the way `PipeEnd`'s raw byte stream does not, and
+/// optionally carrying a file descriptor for the receiver to install into
+/// its own table -- the kernel-side equivalent of `SCM_RIGHTS` over a Unix
+/// domain socket.
+#[derive(Debug, Clone)]
+pub struct TubeMessage {
+    pub bytes: Vec<u8>,
+    pub descriptor: Option<FileDescriptorEntry>,
+}
+
+/// Which of a [`TubeEnd`] pair's two queues this end writes to versus
+/// reads from. Unlike `PipeEndKind`, both ends read *and* write -- one
+/// direction each -- so this just names the two sides rather than the
+/// two roles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TubeEndKind {
+    Near,
+    Far,
+}
+
+#[derive(Debug)]
+pub struct TubeInner {
+    near_to_far: VecDeque<TubeMessage>,
+    far_to_near: VecDeque<TubeMessage>,
+    near_live: usize,
+    far_live: usize,
+}
+
+/// One endpoint of a bidirectional, message-framed IPC channel -- a
+/// "socketpair" in the POSIX sense. Built the same way `PipeEnd` is (an
+/// `Arc<Mutex<...>>`-shared inner state plus refcounted `Clone`/`Drop`),
+/// but with a queue of discrete [`TubeMessage`]s in each direction
+/// instead of one shared byte stream, so [`recv`](TubeEnd::recv) pops
+/// exactly one message rather than however many bytes happen to be
+/// available.
+#[derive(Debug)]
+pub struct TubeEnd {
+    inner: Arc<Mutex<TubeInner>>,
+    kind: TubeEndKind,
+}
+
+impl TubeEnd {
+    pub fn new_pair() -> (Self, Self) {
+        let inner = Arc::new(Mutex::new(TubeInner {
+            near_to_far: VecDeque::new(),
+            far_to_near: VecDeque::new(),
+            near_live: 1,
+            far_live: 1,
+        }));
+
+        let near = TubeEnd {
+            inner: Arc::clone(&inner),
+            kind: TubeEndKind::Near,
+        };
+        let far = TubeEnd {
+            inner,
+            kind: TubeEndKind::Far,
+        };
+
+        (near, far)
+    }
+
+    pub fn kind(&self) -> TubeEndKind {
+        self.kind
+    }
+
+    /// Push a discrete framed message onto the queue the peer reads from.
+    pub fn send(&self, bytes: &[u8]) {
+        self.send_message(TubeMessage {
+            bytes: bytes.to_vec(),
+            descriptor: None,
+        });
+    }
+
+    /// Like [`send`](TubeEnd::send), but also hands the peer a file
+    /// descriptor -- the peer's `recv` installs it into its own
+    /// `FileDescriptorTable`.
+    pub fn send_with_descriptor(&self, bytes: &[u8], descriptor: FileDescriptorEntry) {
+        self.send_message(TubeMessage {
+            bytes: bytes.to_vec(),
+            descriptor: Some(descriptor),
+        });
+    }
+
+    fn send_message(&self, message: TubeMessage) {
+        let mut inner = self.inner.lock();
+        match self.kind {
+            TubeEndKind::Near => inner.near_to_far.push_back(message),
+            TubeEndKind::Far => inner.far_to_near.push_back(message),
+        }
+    }
+
+    /// Pop exactly one message addressed to this end, copying up to
+    /// `dst.len()` of its bytes into `dst` (a message longer than `dst`
+    /// is truncated, the same way a datagram socket's excess is
+    /// discarded) and returning the number of bytes copied. A carried
+    /// descriptor is inserted into `table` before returning. `None` means
+    /// no message is queued yet.
+    pub fn recv(&self, table: &mut FileDescriptorTable, dst: &mut [u8]) -> Option<usize> {
+        let mut inner = self.inner.lock();
+        let queue = match self.kind {
+            TubeEndKind::Near => &mut inner.far_to_near,
+            TubeEndKind::Far => &mut inner.near_to_far,
+        };
+        let message = queue.pop_front()?;
+        drop(inner);
+
+        let len = message.bytes.len().min(dst.len());
+        dst[..len].copy_from_slice(&message.bytes[..len]);
+
+        if let Some(entry) = message.descriptor {
+            table.insert(entry.fd, entry.flags, entry.inheritable, entry.object);
+        }
+
+        Some(len)
+    }
+}
+
+impl Clone for TubeEnd {
+    fn clone(&self) -> Self {
+        {
+            let mut inner = self.inner.lock();
+            match self.kind {
+                TubeEndKind::Near => inner.near_live += 1,
+                TubeEndKind::Far => inner.far_live += 1,
+            }
+        }
+
+        TubeEnd {
+            inner: Arc::clone(&self.inner),
+            kind: self.kind,
+        }
+    }
+}
+
+impl Drop for TubeEnd {
     fn drop(&mut self) {
         let mut inner = self.inner.lock();
         match self.kind {
-            PipeEndKind::Read => inner.readers = inner.readers.saturating_sub(1),
-            PipeEndKind::Write => inner.writers = inner.writers.saturating_sub(1),
+            TubeEndKind::Near => inner.near_live = inner.near_live.saturating_sub(1),
+            TubeEndKind::Far => inner.far_live = inner.far_live.saturating_sub(1),
         }
     }
 }
@@ -161,8 +455,140 @@ pub enum FdObject {
     Stdout,
     Stderr,
     Pipe(PipeEnd),
+    /// Backed by the real `SERIAL1` hardware port identified by this COM
+    /// port number, rather than an in-memory buffer -- what the default
+    /// table installs at fds 0/1/2 so a freshly created process gets a
+    /// working console instead of `Stdin`/`Stdout`/`Stderr`'s inert
+    /// markers. `syscall::sys_read`/`sys_write` dispatch to
+    /// `crate::drivers::serial::SERIAL1` the same way they dispatch a
+    /// `Scheme`-backed fd to its provider.
+    Serial(u16),
+    /// One endpoint of a [`TubeEnd`] pair -- a bidirectional,
+    /// message-framed socketpair, unlike `Pipe`'s one-directional byte
+    /// stream. No syscall constructs one yet, same as `Pipe` itself;
+    /// this is the type-level plumbing a future `sys_socketpair` would
+    /// allocate a pair of into the caller's `FileDescriptorTable`.
+    Tube(TubeEnd),
+    /// Bound to a scheme provider's connection, opened via `sys_open`
+    /// against a `"<scheme>:..."` path. `provider_fd` is the fd the
+    /// provider itself uses to identify this connection -- not the same
+    /// number as the caller's own fd for it.
+    Scheme { provider: ProcessId, provider_fd: u32 },
+    /// A `sys_pidfd_open` handle on `pid`, stamped with its `generation`
+    /// at open time so a later `sys_pidfd_send_signal` against this fd
+    /// can tell "pid is gone" from "pid was reused for someone else" --
+    /// see [`Process::generation`].
+    Pidfd { pid: ProcessId, generation: u64 },
 }
 
+/// A POSIX-style signal number, for [`Process::pending_signals`] and the
+/// handler table [`Process::signal_handlers`] that `syscall::sys_signal`
+/// registers against. Numbering matches the POSIX values so a caller's
+/// raw `signum` argument maps onto it directly.
+///
+/// Only the bookkeeping is modeled here -- registering a handler or
+/// recording a pending signal never actually invokes anything, since
+/// there's no trampoline in this kernel that delivers a signal into a
+/// thread's userspace execution the way `Context::switch_context` resumes
+/// a saved register file. `pidfd_send_signal`/`sys_signal` exist so that
+/// machinery has somewhere to record its state once it's built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Hup = 1,
+    Int = 2,
+    Quit = 3,
+    Kill = 9,
+    Usr1 = 10,
+    Usr2 = 12,
+    Term = 15,
+    Chld = 17,
+    Cont = 18,
+    Stop = 19,
+}
+
+impl Signal {
+    pub fn from_usize(value: usize) -> Option<Self> {
+        match value {
+            1 => Some(Signal::Hup),
+            2 => Some(Signal::Int),
+            3 => Some(Signal::Quit),
+            9 => Some(Signal::Kill),
+            10 => Some(Signal::Usr1),
+            12 => Some(Signal::Usr2),
+            15 => Some(Signal::Term),
+            17 => Some(Signal::Chld),
+            18 => Some(Signal::Cont),
+            19 => Some(Signal::Stop),
+            _ => None,
+        }
+    }
+}
+
+/// Disposition for one syscall number under [`Process::seccomp_rules`],
+/// in the spirit of Starnix's `SeccompAction`. Consulted by
+/// `syscall::handle_syscall` once `Process::seccomp_filtered` is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeccompAction {
+    /// Let the syscall dispatch normally.
+    Allow,
+    /// Fail the syscall immediately with this raw errno, without ever
+    /// reaching its handler. Stored as a plain `i32` rather than
+    /// `syscall::Errno` since this module doesn't depend on `syscall`;
+    /// `syscall::sys_seccomp_set`/`handle_syscall` translate at the edges.
+    Errno(i32),
+    /// Terminate the calling thread via `syscall::finalize_thread`, the
+    /// same path a fatal signal would take.
+    Kill,
+}
+
+/// Exit code [`SeccompAction::Kill`] reports through `finalize_thread`,
+/// distinguished from an ordinary `sys_exit` status the same way Linux's
+/// `SIGSYS`-killed processes carry a recognizable wait status.
+pub const SECCOMP_KILL_EXIT_CODE: usize = 159;
+
+/// A resource kind governed by [`Process::rlimits`], in the style of
+/// rustix's `Resource`. `NThread` is a kernel-specific extension beyond
+/// POSIX's rlimit set -- Linux folds thread count into `RLIMIT_NPROC`,
+/// but this kernel tracks threads and processes as distinctly different
+/// tables, so it gets its own cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    /// Max child processes a process may have outstanding at once,
+    /// checked against `Process::children.len()` at fork time.
+    NProc,
+    /// Max open file descriptors, checked against
+    /// `FileDescriptorTable::len()` wherever an fd is allocated.
+    NoFile,
+    /// Max threads a process may have outstanding at once, checked
+    /// against `Process::threads.len()` at thread-creation time.
+    NThread,
+}
+
+/// A soft/hard limit pair, in the style of rustix's `Rlimit`. Only a
+/// capability-holding context may raise `hard` above its current value;
+/// `soft` may be adjusted freely as long as it stays at or below `hard`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rlimit {
+    pub soft: u64,
+    pub hard: u64,
+}
+
+/// Default `Process::rlimits` entries installed by [`Process::new`] --
+/// generous enough not to bound any of this kernel's own tests or
+/// userspace images, but finite so a runaway fork/clone/open loop is
+/// bounded per-process instead of exhausting global tables.
+pub const DEFAULT_RLIMITS: [(Resource, Rlimit); 3] = [
+    (Resource::NProc, Rlimit { soft: 64, hard: 64 }),
+    (
+        Resource::NoFile,
+        Rlimit {
+            soft: 256,
+            hard: 256,
+        },
+    ),
+    (Resource::NThread, Rlimit { soft: 64, hard: 64 }),
+];
+
 #[derive(Debug, Clone)]
 pub struct FileDescriptorEntry {
     pub fd: u32,
@@ -201,6 +627,16 @@ impl FileDescriptorTable {
         self.entries.iter()
     }
 
+    /// Number of open descriptors, for `syscall::sys_open`/
+    /// `sys_pidfd_open` to check against `Resource::NoFile`.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
     pub fn get(&self, fd: u32) -> Option<&FileDescriptorEntry> {
         self.entries.iter().find(|entry| entry.fd == fd)
     }
@@ -249,9 +685,9 @@ impl Default for FileDescriptorTable {
             entries: Vec::new(),
             next_fd: 0,
         };
-        table.insert(0, 0, true, FdObject::Stdin);
-        table.insert(1, 0, true, FdObject::Stdout);
-        table.insert(2, 0, true, FdObject::Stderr);
+        table.insert(0, 0, true, FdObject::Serial(crate::drivers::serial::COM1));
+        table.insert(1, 0, true, FdObject::Serial(crate::drivers::serial::COM1));
+        table.insert(2, 0, true, FdObject::Serial(crate::drivers::serial::COM1));
         table
     }
 }
@@ -276,8 +712,71 @@ impl AddressSpace {
         }
     }
 
+    /// Real copy-on-write fork: for every present page within the heap and
+    /// stack ranges, clear `WRITABLE` and set `COW` on both this address
+    /// space's leaf entry and the child's, and bump the frame's
+    /// `frame_allocator` share count so it's only freed once every owner
+    /// has released it (see `X86_64PageTable::mark_cow_and_frame`/
+    /// `install_leaf_into`). `heap_start`/`heap_end`/`stack_start`/
+    /// `stack_end` themselves carry over unchanged; only the mappings
+    /// underneath them become shared. A write to a shared page later
+    /// faults into `X86_64PageTable::resolve_cow_fault`, which gives the
+    /// faulting side its own private copy.
+    ///
+    /// Walking and rewriting live leaf entries only makes sense against
+    /// this kernel's real page-table machinery, which `cfg(test)` runs
+    /// natively on the host and never touches -- the same reason every
+    /// `syscall::user_access` test passes a null/zero-length pointer
+    /// instead of a real one. Under `cfg(test)` this keeps handing the
+    /// child a blank table instead of attempting a walk that would
+    /// dereference unmapped host memory.
+    #[cfg(not(test))]
+    pub fn clone_for_fork(&self) -> Option<Self> {
+        let child_frame = frame_allocator::allocate_frame()?;
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            use crate::memory::paging::X86_64PageTable;
+
+            let mut parent = X86_64PageTable::new(self.page_table_frame);
+            for (start, end) in [
+                (self.heap_start, self.heap_end),
+                (self.stack_start, self.stack_end),
+            ] {
+                let mut addr = start.0;
+                while addr < end.0 {
+                    let page = crate::memory::paging::Page::containing_address(VirtAddr::new(addr));
+                    if let Some(frame) = parent.mark_cow_and_frame(page) {
+                        let child_flags = crate::memory::paging::PageFlags::PRESENT
+                            | crate::memory::paging::PageFlags::USER_ACCESSIBLE
+                            | crate::memory::paging::PageFlags::COW;
+                        X86_64PageTable::install_leaf_into(child_frame, page, frame, child_flags)?;
+                        frame_allocator::cow_share(frame);
+                    }
+                    addr += crate::memory::PAGE_SIZE as u64;
+                }
+            }
+        }
+
+        // No aarch64 target builds in this tree yet to exercise it against,
+        // so the ARM LPAE backend keeps today's blank-child behavior
+        // (`ArmLpaePageTable::map_to` *could* install shared mappings into
+        // a non-active table the same way, since unlike x86_64 it never
+        // relies on a recursive self-map -- that's left for when this
+        // kernel actually targets aarch64).
+
+        Some(Self {
+            page_table_frame: child_frame,
+            heap_start: self.heap_start,
+            heap_end: self.heap_end,
+            stack_start: self.stack_start,
+            stack_end: self.stack_end,
+        })
+    }
+
+    #[cfg(test)]
     pub fn clone_for_fork(&self) -> Option<Self> {
-        let frame = crate::memory::frame_allocator::allocate_frame()?;
+        let frame = frame_allocator::allocate_frame()?;
         Some(Self {
             page_table_frame: frame,
             heap_start: self.heap_start,
@@ -296,9 +795,68 @@ pub struct Process {
     pub threads: Vec<ThreadId>,
     pub parent: Option<ProcessId>,
     pub children: Vec<ProcessId>,
-    pub file_descriptors: FileDescriptorTable,
+    /// Wrapped in `Arc<Mutex<_>>`, the same per-process-state-shared-across-
+    /// clones shape as [`Process::compute_budget`], so `CLONE_FILES` can
+    /// hand a child the identical table instead of a copy -- see
+    /// `syscall::sys_clone`.
+    pub file_descriptors: Arc<Mutex<FileDescriptorTable>>,
     pub security: SecurityContext,
     pub image: Option<loader::LoadedImage>,
+    pub proc_type: ProcType,
+    /// Remaining syscall compute units, shared (via `Arc`) across every
+    /// clone of this `Process` -- `syscall::get_process` hands out a fresh
+    /// clone per call, but they must all draw down the same counter for
+    /// metering in `syscall::handle_syscall` to mean anything.
+    pub compute_budget: Arc<AtomicI64>,
+    /// Stamped at creation from [`next_generation`] and carried inside
+    /// every [`FdObject::Pidfd`] opened against this process, so a pidfd
+    /// can tell its own target apart from a different process that later
+    /// reused the same `id`. `ProcessTable::allocate_pid` never actually
+    /// reuses a `ProcessId` in this kernel, so today this can never
+    /// observably mismatch -- it exists so a pidfd holder doesn't have to
+    /// assume that stays true.
+    pub generation: u64,
+    /// Signal numbers delivered (via `syscall::sys_pidfd_send_signal`)
+    /// but not yet acted on -- there's no delivery trampoline to actually
+    /// act on them yet, so this is just the recording half of the model.
+    pub pending_signals: Vec<Signal>,
+    /// Handlers registered with `syscall::sys_signal`, as `(signal,
+    /// handler address)` pairs the way a small bounded registry elsewhere
+    /// in this kernel (`scheme::PROVIDERS`, `syscall::WAITERS`) would
+    /// store one.
+    pub signal_handlers: Vec<(Signal, usize)>,
+    /// Set via `syscall::sys_prctl`'s `PR_SET_CHILD_SUBREAPER`-style
+    /// request. A subreaper claims reparenting of any descendant's
+    /// orphaned children ahead of [`INIT_PID`], the same role `init`
+    /// normally plays -- see [`reparent_orphans`].
+    pub is_subreaper: bool,
+    /// Set once by `syscall::sys_seccomp_set` / [`install_seccomp_filter`]
+    /// and never cleared again -- the one-way transition into filtered
+    /// mode. While `false`, [`Process::seccomp_rules`]/`seccomp_default`
+    /// are never consulted.
+    pub seccomp_filtered: bool,
+    /// Disposition for a syscall number with no matching entry in
+    /// [`Process::seccomp_rules`], fixed at the same time `seccomp_filtered`
+    /// is set.
+    pub seccomp_default: SeccompAction,
+    /// Per-syscall-number overrides of `seccomp_default`, following the
+    /// same small linear-scan `Vec` registry convention as
+    /// [`Process::signal_handlers`].
+    pub seccomp_rules: Vec<(usize, SeccompAction)>,
+    /// Per-[`Resource`] soft/hard limits, seeded from [`DEFAULT_RLIMITS`]
+    /// and adjusted via `syscall::sys_setrlimit`, inherited by children at
+    /// fork time. Same small linear-scan `Vec` registry convention as
+    /// [`Process::signal_handlers`]/`seccomp_rules`.
+    pub rlimits: Vec<(Resource, Rlimit)>,
+}
+
+/// Monotonic source for [`Process::generation`], independent of
+/// `ProcessTable::allocate_pid`'s own counter so the two can't be
+/// conflated.
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+pub fn next_generation() -> u64 {
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
 }
 
 impl Process {
@@ -308,6 +866,7 @@ impl Process {
         address_space: AddressSpace,
         security: SecurityContext,
         file_descriptors: FileDescriptorTable,
+        proc_type: ProcType,
     ) -> Self {
         Self {
             id,
@@ -316,9 +875,19 @@ impl Process {
             threads: Vec::new(),
             parent: None,
             children: Vec::new(),
-            file_descriptors,
+            file_descriptors: Arc::new(Mutex::new(file_descriptors)),
             security,
             image: None,
+            proc_type,
+            compute_budget: Arc::new(AtomicI64::new(proc_type.initial_budget())),
+            generation: next_generation(),
+            pending_signals: Vec::new(),
+            signal_handlers: Vec::new(),
+            is_subreaper: false,
+            seccomp_filtered: false,
+            seccomp_default: SeccompAction::Allow,
+            seccomp_rules: Vec::new(),
+            rlimits: DEFAULT_RLIMITS.to_vec(),
         }
     }
 
@@ -329,6 +898,32 @@ impl Process {
     pub fn remove_thread(&mut self, thread_id: ThreadId) {
         self.threads.retain(|&id| id != thread_id);
     }
+
+    /// `resource`'s current soft/hard pair, falling back to
+    /// [`DEFAULT_RLIMITS`] if `rlimits` was somehow cleared of it.
+    pub fn rlimit(&self, resource: Resource) -> Rlimit {
+        self.rlimits
+            .iter()
+            .find(|(r, _)| *r == resource)
+            .map(|(_, limit)| *limit)
+            .unwrap_or_else(|| {
+                DEFAULT_RLIMITS
+                    .iter()
+                    .find(|(r, _)| *r == resource)
+                    .map(|(_, limit)| *limit)
+                    .expect("DEFAULT_RLIMITS covers every Resource variant")
+            })
+    }
+
+    /// Overwrite `resource`'s soft/hard pair, inserting it if `rlimits`
+    /// doesn't have an entry yet.
+    pub fn set_rlimit(&mut self, resource: Resource, limit: Rlimit) {
+        if let Some(entry) = self.rlimits.iter_mut().find(|(r, _)| *r == resource) {
+            entry.1 = limit;
+        } else {
+            self.rlimits.push((resource, limit));
+        }
+    }
 }
 
 pub struct ProcessTable {
@@ -366,10 +961,19 @@ impl ProcessTable {
         self.processes.get_mut(pid.0)?.as_mut()
     }
 
+    /// Drop `pid` from the table, releasing the resources this kernel
+    /// tracks per-process: its security context and its address space's
+    /// page-table frame. `CLONE_VM` (see `syscall::sys_clone`) can leave
+    /// more than one `Process` pointing at the same `page_table_frame`
+    /// with no refcount yet tracking that sharing -- freeing it here is
+    /// correct for the common case (no `CLONE_VM` siblings outstanding)
+    /// and is the same gap real COW fork's per-frame refcounting will
+    /// close once it lands.
     pub fn remove_process(&mut self, pid: ProcessId) -> Option<Process> {
         if pid.0 < self.processes.len() {
             if let Some(process) = self.processes[pid.0].take() {
                 security::remove_context(pid.0);
+                frame_allocator::deallocate_frame(process.address_space.page_table_frame);
                 Some(process)
             } else {
                 None
@@ -378,6 +982,10 @@ impl ProcessTable {
             None
         }
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Process> {
+        self.processes.iter().filter_map(|slot| slot.as_ref())
+    }
 }
 
 pub static PROCESS_TABLE: Mutex<ProcessTable> = Mutex::new(ProcessTable::new());
@@ -387,16 +995,88 @@ pub fn create_process(
     page_table_frame: Frame,
     security: SecurityContext,
     file_descriptors: FileDescriptorTable,
+    proc_type: ProcType,
 ) -> Option<ProcessId> {
     let mut table = PROCESS_TABLE.lock();
     let pid = table.allocate_pid();
     let address_space = AddressSpace::new(page_table_frame);
-    let process = Process::new(pid, name, address_space, security.clone(), file_descriptors);
+    let process = Process::new(
+        pid,
+        name,
+        address_space,
+        security.clone(),
+        file_descriptors,
+        proc_type,
+    );
     security::register_process(pid.0, security);
     table.add_process(process);
     Some(pid)
 }
 
+/// Atomically try to draw `cost` compute units from `pid`'s budget,
+/// refusing (without taking anything) if that would leave it negative.
+/// `ProcType::System`'s effectively-infinite starting budget means this
+/// never actually blocks a kernel-owned process in practice.
+pub fn try_charge_compute(pid: ProcessId, cost: i64) -> bool {
+    let table = PROCESS_TABLE.lock();
+    let Some(process) = table.get_process(pid) else {
+        return false;
+    };
+    let budget = process.compute_budget.clone();
+    drop(table);
+
+    budget
+        .fetch_update(Ordering::AcqRel, Ordering::Acquire, |remaining| {
+            if remaining < cost {
+                None
+            } else {
+                Some(remaining - cost)
+            }
+        })
+        .is_ok()
+}
+
+/// Current remaining compute units for `pid`, or `None` if it doesn't exist.
+pub fn remaining_compute(pid: ProcessId) -> Option<i64> {
+    let table = PROCESS_TABLE.lock();
+    let process = table.get_process(pid)?;
+    Some(process.compute_budget.load(Ordering::Acquire))
+}
+
+/// Refill `pid`'s budget by `units`, capped at its `ProcType`'s starting
+/// budget -- called once per scheduler tick by the refill policy in
+/// `syscall::budget_refill_tick`.
+pub fn refill_compute(pid: ProcessId, units: i64) {
+    let table = PROCESS_TABLE.lock();
+    let Some(process) = table.get_process(pid) else {
+        return;
+    };
+    let cap = process.proc_type.initial_budget();
+    process
+        .compute_budget
+        .fetch_update(Ordering::AcqRel, Ordering::Acquire, |remaining| {
+            Some(core::cmp::min(cap, remaining.saturating_add(units)))
+        })
+        .ok();
+}
+
+/// Refill every live process's budget by `units`, each capped at its own
+/// `ProcType`'s starting budget. The periodic-refill half of the policy
+/// `try_charge_compute` enforces; called once per scheduler tick by
+/// `syscall::budget_refill_tick`.
+pub fn refill_all_budgets(units: i64) {
+    let table = PROCESS_TABLE.lock();
+    for process in table.iter() {
+        let cap = process.proc_type.initial_budget();
+        process
+            .compute_budget
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |remaining| {
+                Some(core::cmp::min(cap, remaining.saturating_add(units)))
+            })
+            .ok();
+    }
+}
+
 pub fn get_process(pid: ProcessId) -> Option<Process> {
     let table = PROCESS_TABLE.lock();
     table.get_process(pid).cloned()
@@ -426,6 +1106,136 @@ pub fn grant_process_capabilities(pid: ProcessId, mask: security::CapMask) -> bo
     true
 }
 
+/// Register `handler` for `signal` against `pid`'s `signal_handlers`,
+/// replacing any prior entry for that signal and returning the address it
+/// replaced (or `0` if none). Mirrors POSIX `signal(2)`'s "returns the
+/// previous handler" contract, though nothing in this kernel yet invokes
+/// either the old or the new address.
+pub fn register_signal_handler(pid: ProcessId, signal: Signal, handler: usize) -> Option<usize> {
+    let mut table = PROCESS_TABLE.lock();
+    let process = table.get_process_mut(pid)?;
+
+    match process
+        .signal_handlers
+        .iter_mut()
+        .find(|(sig, _)| *sig == signal)
+    {
+        Some(entry) => Some(core::mem::replace(&mut entry.1, handler)),
+        None => {
+            process.signal_handlers.push((signal, handler));
+            Some(0)
+        }
+    }
+}
+
+/// Record `signal` as pending against `pid`, as long as `generation`
+/// still matches its current one -- the same check a [`FdObject::Pidfd`]
+/// holder needs so a stale fd can't signal whoever now holds that `pid`.
+/// `Err(())` covers both "pid no longer exists" and "generation moved on".
+pub fn send_signal(pid: ProcessId, generation: u64, signal: Signal) -> Result<(), ()> {
+    let mut table = PROCESS_TABLE.lock();
+    let process = table.get_process_mut(pid).ok_or(())?;
+    if process.generation != generation {
+        return Err(());
+    }
+
+    process.pending_signals.push(signal);
+    Ok(())
+}
+
+/// Pid `1` is this kernel's reaper of last resort, by the same convention
+/// `ProcessTable::next_pid` starting at `1` gives it to whatever process
+/// is created first -- mirroring traditional init's role as every
+/// orphan's eventual parent.
+pub const INIT_PID: ProcessId = ProcessId(1);
+
+/// Set `pid`'s [`Process::is_subreaper`] flag, for `syscall::sys_prctl`'s
+/// `PR_SET_CHILD_SUBREAPER`-style request.
+pub fn set_subreaper(pid: ProcessId, enabled: bool) -> bool {
+    let mut table = PROCESS_TABLE.lock();
+    match table.get_process_mut(pid) {
+        Some(process) => {
+            process.is_subreaper = enabled;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Move `pid` into seccomp-filtered mode, for `syscall::sys_seccomp_set`.
+/// Returns `Some(true)` on a fresh install, `Some(false)` if `pid` was
+/// already filtered (the transition is one-way, so a second call is
+/// rejected rather than replacing the existing rules), or `None` if `pid`
+/// doesn't exist.
+pub fn install_seccomp_filter(
+    pid: ProcessId,
+    default: SeccompAction,
+    rules: Vec<(usize, SeccompAction)>,
+) -> Option<bool> {
+    let mut table = PROCESS_TABLE.lock();
+    let process = table.get_process_mut(pid)?;
+    if process.seccomp_filtered {
+        return Some(false);
+    }
+    process.seccomp_default = default;
+    process.seccomp_rules = rules;
+    process.seccomp_filtered = true;
+    Some(true)
+}
+
+/// The nearest ancestor of `pid` marked [`Process::is_subreaper`], or
+/// [`INIT_PID`] if none claims the role before the ancestor chain runs
+/// out.
+fn find_reaper(table: &ProcessTable, pid: ProcessId) -> ProcessId {
+    let mut current = match table.get_process(pid).and_then(|process| process.parent) {
+        Some(parent) => parent,
+        None => return INIT_PID,
+    };
+
+    loop {
+        match table.get_process(current) {
+            Some(process) if process.is_subreaper => return current,
+            Some(process) => match process.parent {
+                Some(parent) => current = parent,
+                None => return INIT_PID,
+            },
+            None => return INIT_PID,
+        }
+    }
+}
+
+/// Reparent every child of `pid` (about to exit, with `table` already
+/// locked by the caller) to the nearest ancestor marked
+/// [`Process::is_subreaper`], or to [`INIT_PID`] if none exists --
+/// Linux's `PR_SET_CHILD_SUBREAPER` reparenting rule. Returns each
+/// reparented child alongside the reaper it landed on, so
+/// `syscall::finalize_thread` can migrate any already-queued zombie
+/// entries and wake a matching waiter to match; the invariant this
+/// preserves is that every live or zombied process always has a
+/// reachable reaper, never a dangling `parent`.
+pub fn reparent_orphans(table: &mut ProcessTable, pid: ProcessId) -> Vec<(ProcessId, ProcessId)> {
+    let children = match table.get_process(pid) {
+        Some(process) => process.children.clone(),
+        None => return Vec::new(),
+    };
+    if children.is_empty() {
+        return Vec::new();
+    }
+
+    let reaper = find_reaper(table, pid);
+    let mut reparented = Vec::new();
+    for child in &children {
+        if let Some(child_process) = table.get_process_mut(*child) {
+            child_process.parent = Some(reaper);
+            reparented.push((*child, reaper));
+        }
+    }
+    if let Some(reaper_process) = table.get_process_mut(reaper) {
+        reaper_process.children.extend(children);
+    }
+    reparented
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -445,4 +1255,46 @@ mod tests {
         assert!(Priority::High.as_usize() > Priority::Normal.as_usize());
         assert!(Priority::Realtime.as_usize() > Priority::High.as_usize());
     }
+
+    #[test]
+    fn test_rlimit_defaults_and_override() {
+        let security = crate::security::SecurityContext::as_user(1000);
+        let mut process = Process::new(
+            ProcessId(1),
+            "test".into(),
+            AddressSpace::new(Frame {
+                start_address: crate::memory::PhysAddr::new(0x1000),
+            }),
+            security,
+            FileDescriptorTable::new(),
+            ProcType::Application,
+        );
+
+        assert_eq!(
+            process.rlimit(Resource::NoFile),
+            Rlimit {
+                soft: 256,
+                hard: 256
+            }
+        );
+
+        process.set_rlimit(
+            Resource::NoFile,
+            Rlimit {
+                soft: 10,
+                hard: 200,
+            },
+        );
+        assert_eq!(
+            process.rlimit(Resource::NoFile),
+            Rlimit {
+                soft: 10,
+                hard: 200
+            }
+        );
+        assert_eq!(
+            process.rlimit(Resource::NProc),
+            Rlimit { soft: 64, hard: 64 }
+        );
+    }
 }
@@ -1,13 +1,16 @@
 use super::{ThreadId, Priority, ThreadState, THREAD_TABLE};
+use crate::arch::ipi::{self, IpiKind};
+use crate::clock;
+use crate::cpu::percpu::{self, MAX_CPUS};
 use spin::Mutex;
 
 #[cfg(not(test))]
-use alloc::collections::VecDeque;
+use alloc::{collections::VecDeque, vec::Vec};
 
 #[cfg(test)]
 extern crate std;
 #[cfg(test)]
-use std::collections::VecDeque;
+use std::{collections::VecDeque, vec::Vec};
 
 pub struct RunQueue {
     queues: [VecDeque<ThreadId>; 5],
@@ -69,11 +72,56 @@ impl RunQueue {
             queue.retain(|&t| t != tid);
         }
     }
+
+    /// Total number of threads waiting across all priority levels, used by
+    /// the load balancer to compare how busy cores are relative to each
+    /// other.
+    pub fn depth(&self) -> usize {
+        self.queues.iter().map(VecDeque::len).sum()
+    }
+
+    /// Pop the oldest `Ready` thread off the lowest-priority non-empty
+    /// level, for the load balancer to hand off to a less-busy core.
+    pub fn steal_oldest(&mut self) -> Option<ThreadId> {
+        for queue in self.queues.iter_mut() {
+            if let Some(tid) = queue.pop_front() {
+                return Some(tid);
+            }
+        }
+        None
+    }
+
+    /// Re-band any queued thread for which `want_band` returns a level
+    /// different from the one it's currently queued in, used by the
+    /// scheduler's aging sweep to promote threads that have waited past
+    /// the aging threshold. Threads that don't move keep their relative
+    /// FIFO order within their band.
+    pub fn rebalance(&mut self, mut want_band: impl FnMut(ThreadId) -> usize) {
+        let mut moved: Vec<(usize, ThreadId)> = Vec::new();
+        for (idx, queue) in self.queues.iter_mut().enumerate() {
+            let mut i = 0;
+            while i < queue.len() {
+                let band = want_band(queue[i]);
+                if band != idx {
+                    moved.push((band, queue.remove(i).unwrap()));
+                } else {
+                    i += 1;
+                }
+            }
+        }
+        for (band, tid) in moved {
+            self.queues[band].push_back(tid);
+        }
+    }
 }
 
 pub struct Scheduler {
     run_queue: RunQueue,
-    time_slice_remaining: usize,
+    /// Nanoseconds left in the current thread's slice.
+    time_slice_remaining_ns: u64,
+    /// `clock::clock().now_ns()` as of the last `tick()`, used to compute
+    /// the elapsed duration to charge the current thread.
+    last_tick_ns: u64,
     total_ticks: u64,
 }
 
@@ -81,15 +129,17 @@ impl Scheduler {
     pub const fn new() -> Self {
         Self {
             run_queue: RunQueue::new(),
-            time_slice_remaining: 0,
+            time_slice_remaining_ns: 0,
+            last_tick_ns: 0,
             total_ticks: 0,
         }
     }
 
     pub fn add_thread(&mut self, tid: ThreadId) {
-        let thread_table = THREAD_TABLE.lock();
-        if let Some(thread) = thread_table.get_thread(tid) {
+        let mut thread_table = THREAD_TABLE.lock();
+        if let Some(thread) = thread_table.get_thread_mut(tid) {
             if thread.is_runnable() {
+                thread.last_enqueue_time = clock::clock().now_ns();
                 self.run_queue.enqueue(tid, thread.priority);
             }
         }
@@ -105,8 +155,8 @@ impl Scheduler {
 
     pub fn schedule(&mut self) -> Option<ThreadId> {
         let current = self.run_queue.current();
-        
-        if self.time_slice_remaining > 0 {
+
+        if self.time_slice_remaining_ns > 0 {
             if let Some(current_tid) = current {
                 let thread_table = THREAD_TABLE.lock();
                 if let Some(thread) = thread_table.get_thread(current_tid) {
@@ -117,45 +167,94 @@ impl Scheduler {
             }
         }
 
+        let now_ns = clock::clock().now_ns();
+
         if let Some(current_tid) = current {
             let mut thread_table = THREAD_TABLE.lock();
             if let Some(thread) = thread_table.get_thread_mut(current_tid) {
                 if thread.state == ThreadState::Running {
                     thread.set_state(ThreadState::Ready);
+                    // A thread that burned through its whole slice is
+                    // demoted one band; one that's switching out early
+                    // (yielded or was preempted before exhausting it)
+                    // keeps its current band, letting aging restore it
+                    // later if it was already demoted.
+                    if thread.slice_exhausted() {
+                        thread.demote();
+                    }
+                    thread.reset_slice_used();
+                    thread.last_enqueue_time = now_ns;
                     self.run_queue.enqueue(current_tid, thread.priority);
                 }
             }
         }
 
         let next_tid = self.run_queue.pick_next()?;
-        
+
         let mut thread_table = THREAD_TABLE.lock();
         if let Some(thread) = thread_table.get_thread_mut(next_tid) {
             thread.set_state(ThreadState::Running);
-            self.time_slice_remaining = thread.time_slice;
+            thread.reset_slice_used();
+            self.time_slice_remaining_ns = thread.time_slice;
         }
 
         self.run_queue.set_current(Some(next_tid));
+        // Arm the next timer interrupt for exactly this thread's slice
+        // instead of relying on a fixed periodic tick.
+        clock::clock().set_deadline(self.time_slice_remaining_ns);
+        self.last_tick_ns = clock::clock().now_ns();
         Some(next_tid)
     }
 
     pub fn tick(&mut self) {
         self.total_ticks += 1;
-        
-        if self.time_slice_remaining > 0 {
-            self.time_slice_remaining -= 1;
-        }
+
+        let now_ns = clock::clock().now_ns();
+        let elapsed_ns = now_ns.saturating_sub(self.last_tick_ns);
+        self.last_tick_ns = now_ns;
+
+        self.time_slice_remaining_ns = self.time_slice_remaining_ns.saturating_sub(elapsed_ns);
 
         if let Some(current_tid) = self.run_queue.current() {
             let mut thread_table = THREAD_TABLE.lock();
             if let Some(thread) = thread_table.get_thread_mut(current_tid) {
-                thread.increment_cpu_time(1);
+                thread.increment_cpu_time_ns(elapsed_ns);
+                thread.charge_slice_used_ns(elapsed_ns);
             }
         }
+
+        if self.total_ticks % AGING_INTERVAL_TICKS == 0 {
+            self.age_threads(now_ns);
+        }
+    }
+
+    /// Promote any `Ready` thread that has waited at least
+    /// `AGING_THRESHOLD_NS` since it was last enqueued, one band toward
+    /// its `base_priority`, so a steady stream of higher-priority
+    /// arrivals can't starve it forever. Promotion resets the thread's
+    /// wait clock so repeated sweeps step it up gradually instead of
+    /// jumping straight to its ceiling.
+    fn age_threads(&mut self, now_ns: u64) {
+        self.run_queue.rebalance(|tid| {
+            let mut table = THREAD_TABLE.lock();
+            let Some(thread) = table.get_thread_mut(tid) else {
+                return 0;
+            };
+            let current_band = thread.priority.as_usize();
+            if current_band < thread.base_priority.as_usize()
+                && now_ns.saturating_sub(thread.last_enqueue_time) >= AGING_THRESHOLD_NS
+            {
+                thread.promote_toward_base();
+                thread.last_enqueue_time = now_ns;
+                thread.priority.as_usize()
+            } else {
+                current_band
+            }
+        });
     }
 
     pub fn yield_current(&mut self) {
-        self.time_slice_remaining = 0;
+        self.time_slice_remaining_ns = 0;
     }
 
     pub fn block_current(&mut self) {
@@ -173,6 +272,21 @@ impl Scheduler {
         if let Some(thread) = thread_table.get_thread_mut(tid) {
             if thread.state == ThreadState::Blocked {
                 thread.set_state(ThreadState::Ready);
+                thread.last_enqueue_time = clock::clock().now_ns();
+                self.run_queue.enqueue(tid, thread.priority);
+            }
+        }
+    }
+
+    /// Like [`Self::unblock_thread`], but for a thread parked in
+    /// `TraceStopped` rather than `Blocked` -- `sys_ptrace`'s `CONT`/
+    /// `SINGLESTEP` resume via this instead.
+    pub fn resume_traced(&mut self, tid: ThreadId) {
+        let mut thread_table = THREAD_TABLE.lock();
+        if let Some(thread) = thread_table.get_thread_mut(tid) {
+            if thread.state == ThreadState::TraceStopped {
+                thread.set_state(ThreadState::Ready);
+                thread.last_enqueue_time = clock::clock().now_ns();
                 self.run_queue.enqueue(tid, thread.priority);
             }
         }
@@ -185,50 +299,158 @@ impl Scheduler {
     pub fn total_ticks(&self) -> u64 {
         self.total_ticks
     }
+
+    /// Threads currently waiting on this core, for the load balancer to
+    /// compare against other cores.
+    pub fn queue_depth(&self) -> usize {
+        self.run_queue.depth()
+    }
+
+    /// Pull this core's oldest `Ready` thread off its run queue so the
+    /// load balancer can migrate it elsewhere.
+    pub fn steal_oldest_ready(&mut self) -> Option<ThreadId> {
+        self.run_queue.steal_oldest()
+    }
+}
+
+const EMPTY_SCHEDULER: Mutex<Scheduler> = Mutex::new(Scheduler::new());
+
+/// One `Scheduler` per core, each with its own run queue and lock, so
+/// cores pick their next thread without contending on a single global
+/// lock. Indexed by `cpu::percpu::get_current_cpu_id()`.
+static SCHEDULERS: [Mutex<Scheduler>; MAX_CPUS] = [EMPTY_SCHEDULER; MAX_CPUS];
+
+/// How many more threads the busiest core's queue must hold than the
+/// idlest core's before the load balancer migrates work between them.
+const LOAD_IMBALANCE_THRESHOLD: usize = 2;
+
+/// Ticks between load-balancer passes, so `tick()` doesn't walk every
+/// core's queue depth on every single timer interrupt.
+const LOAD_BALANCE_INTERVAL: u64 = 100;
+
+/// Ticks between aging sweeps.
+const AGING_INTERVAL_TICKS: u64 = 10;
+
+/// Ticks between compute-budget refill sweeps, run only on CPU 0 so a
+/// refill isn't applied once per core per interval.
+const BUDGET_REFILL_INTERVAL_TICKS: u64 = 20;
+
+/// How long a `Ready` thread must wait since its last enqueue before the
+/// aging sweep promotes it one band, in nanoseconds (100ms).
+const AGING_THRESHOLD_NS: u64 = 100_000_000;
+
+fn current_cpu() -> usize {
+    percpu::get_current_cpu_id() as usize % MAX_CPUS
 }
 
-pub static SCHEDULER: Mutex<Scheduler> = Mutex::new(Scheduler::new());
+/// The calling core's own `Scheduler`.
+pub fn current_scheduler() -> &'static Mutex<Scheduler> {
+    &SCHEDULERS[current_cpu()]
+}
+
+/// Look across every online core's queue depth; if the busiest one is
+/// ahead of the idlest by more than `LOAD_IMBALANCE_THRESHOLD`, migrate its
+/// oldest `Ready` thread to the idlest core and send it a reschedule IPI
+/// so a halted target wakes up to notice the new work. Reuses
+/// `arch::ipi::send_ipi`'s existing cfg-gated APIC/GIC dispatch rather
+/// than re-deriving a second per-arch abstraction for the same delivery.
+fn balance_load(origin: usize) {
+    let cpu_count = percpu::get_cpu_manager()
+        .map(|mgr| mgr.cpu_count())
+        .unwrap_or(1)
+        .clamp(1, MAX_CPUS);
+
+    let mut busiest = origin;
+    let mut busiest_depth = SCHEDULERS[origin].lock().queue_depth();
+    let mut idlest = origin;
+    let mut idlest_depth = busiest_depth;
+
+    for cpu in 0..cpu_count {
+        let depth = SCHEDULERS[cpu].lock().queue_depth();
+        if depth > busiest_depth {
+            busiest = cpu;
+            busiest_depth = depth;
+        }
+        if depth < idlest_depth {
+            idlest = cpu;
+            idlest_depth = depth;
+        }
+    }
+
+    if busiest == idlest || busiest_depth.saturating_sub(idlest_depth) < LOAD_IMBALANCE_THRESHOLD {
+        return;
+    }
+
+    if let Some(tid) = SCHEDULERS[busiest].lock().steal_oldest_ready() {
+        SCHEDULERS[idlest].lock().add_thread(tid);
+        ipi::send_ipi(idlest as u32, IpiKind::Reschedule);
+    }
+}
 
 pub fn init_scheduler() {
-    
+
 }
 
 pub fn add_thread(tid: ThreadId) {
-    SCHEDULER.lock().add_thread(tid);
+    current_scheduler().lock().add_thread(tid);
 }
 
 pub fn remove_thread(tid: ThreadId) {
-    SCHEDULER.lock().remove_thread(tid);
+    current_scheduler().lock().remove_thread(tid);
 }
 
 pub fn schedule() -> Option<ThreadId> {
-    SCHEDULER.lock().schedule()
+    current_scheduler().lock().schedule()
 }
 
 pub fn tick() {
-    SCHEDULER.lock().tick();
+    let cpu = current_cpu();
+    let (should_balance, should_refill) = {
+        let mut scheduler = SCHEDULERS[cpu].lock();
+        scheduler.tick();
+        (
+            scheduler.total_ticks() % LOAD_BALANCE_INTERVAL == 0,
+            scheduler.total_ticks() % BUDGET_REFILL_INTERVAL_TICKS == 0,
+        )
+    };
+    if should_balance {
+        balance_load(cpu);
+    }
+    if cpu == 0 && should_refill {
+        crate::syscall::budget_refill_tick();
+    }
 }
 
 pub fn yield_cpu() {
-    SCHEDULER.lock().yield_current();
+    current_scheduler().lock().yield_current();
 }
 
 pub fn block_current_thread() {
-    SCHEDULER.lock().block_current();
+    current_scheduler().lock().block_current();
 }
 
 pub fn unblock_thread(tid: ThreadId) {
-    SCHEDULER.lock().unblock_thread(tid);
+    current_scheduler().lock().unblock_thread(tid);
+}
+
+pub fn resume_traced_thread(tid: ThreadId) {
+    current_scheduler().lock().resume_traced(tid);
 }
 
 pub fn current_thread() -> Option<ThreadId> {
-    SCHEDULER.lock().current_thread()
+    current_scheduler().lock().current_thread()
+}
+
+/// Total nanoseconds of CPU time charged to `tid` so far.
+pub fn thread_cpu_time_ns(tid: ThreadId) -> Option<u64> {
+    THREAD_TABLE.lock().get_thread(tid).map(|thread| thread.total_cpu_time)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::process::{Priority, ProcessId};
+    use crate::process::thread::{Thread, ThreadTable};
     use crate::memory::VirtAddr;
 
     #[test]
@@ -252,4 +474,150 @@ mod tests {
         scheduler.tick();
         assert_eq!(scheduler.total_ticks(), initial_ticks + 1);
     }
+
+    #[test]
+    fn test_run_queue_depth_and_steal_oldest() {
+        let mut queue = RunQueue::new();
+        assert_eq!(queue.depth(), 0);
+
+        queue.enqueue(ThreadId(1), Priority::Low);
+        queue.enqueue(ThreadId(2), Priority::Low);
+        queue.enqueue(ThreadId(3), Priority::High);
+        assert_eq!(queue.depth(), 3);
+
+        // Low-priority level is the oldest-waiting level migration should
+        // drain from first, FIFO within it.
+        assert_eq!(queue.steal_oldest(), Some(ThreadId(1)));
+        assert_eq!(queue.depth(), 2);
+    }
+
+    #[test]
+    fn test_steal_oldest_ready_then_migrate() {
+        let mut busy = Scheduler::new();
+        let mut idle = Scheduler::new();
+
+        busy.run_queue.enqueue(ThreadId(10), Priority::Normal);
+        busy.run_queue.enqueue(ThreadId(11), Priority::Normal);
+        assert_eq!(busy.queue_depth(), 2);
+        assert_eq!(idle.queue_depth(), 0);
+
+        let migrated = busy.steal_oldest_ready().unwrap();
+        assert_eq!(busy.queue_depth(), 1);
+
+        idle.run_queue.enqueue(migrated, Priority::Normal);
+        assert_eq!(idle.queue_depth(), 1);
+    }
+
+    #[test]
+    fn test_schedule_demotes_thread_on_slice_exhaustion() {
+        *THREAD_TABLE.lock() = ThreadTable::new();
+
+        let tid = ThreadId(100);
+        let mut thread = Thread::new(
+            tid, ProcessId(1), Priority::Normal,
+            VirtAddr::new(0x1000), VirtAddr::new(0x2000), None,
+        );
+        thread.time_slice = 10;
+        THREAD_TABLE.lock().add_thread(thread);
+
+        let mut scheduler = Scheduler::new();
+        scheduler.add_thread(tid);
+        assert_eq!(scheduler.schedule(), Some(tid));
+        assert_eq!(THREAD_TABLE.lock().get_thread(tid).unwrap().priority, Priority::Normal);
+
+        // Simulate the thread burning through its entire slice, then let
+        // the scheduler pick again.
+        THREAD_TABLE.lock().get_thread_mut(tid).unwrap().slice_used = 20;
+        scheduler.time_slice_remaining_ns = 0;
+        scheduler.schedule();
+
+        assert_eq!(THREAD_TABLE.lock().get_thread(tid).unwrap().priority, Priority::Low);
+    }
+
+    #[test]
+    fn test_schedule_keeps_band_on_early_yield() {
+        *THREAD_TABLE.lock() = ThreadTable::new();
+
+        let tid = ThreadId(101);
+        let mut thread = Thread::new(
+            tid, ProcessId(1), Priority::Normal,
+            VirtAddr::new(0x1000), VirtAddr::new(0x2000), None,
+        );
+        thread.time_slice = 10;
+        THREAD_TABLE.lock().add_thread(thread);
+
+        let mut scheduler = Scheduler::new();
+        scheduler.add_thread(tid);
+        scheduler.schedule();
+
+        // Thread yields well before its slice is used up.
+        THREAD_TABLE.lock().get_thread_mut(tid).unwrap().slice_used = 2;
+        scheduler.yield_current();
+        scheduler.schedule();
+
+        assert_eq!(THREAD_TABLE.lock().get_thread(tid).unwrap().priority, Priority::Normal);
+    }
+
+    #[test]
+    fn test_age_threads_promotes_demoted_thread_toward_base() {
+        *THREAD_TABLE.lock() = ThreadTable::new();
+
+        let tid = ThreadId(102);
+        let mut thread = Thread::new(
+            tid, ProcessId(1), Priority::Low,
+            VirtAddr::new(0x1000), VirtAddr::new(0x2000), None,
+        );
+        // Thread's ceiling is Normal, but it's currently sitting at Low
+        // (e.g. after an earlier demotion).
+        thread.base_priority = Priority::Normal;
+        THREAD_TABLE.lock().add_thread(thread);
+
+        let mut scheduler = Scheduler::new();
+        scheduler.add_thread(tid);
+
+        scheduler.age_threads(AGING_THRESHOLD_NS);
+
+        assert_eq!(THREAD_TABLE.lock().get_thread(tid).unwrap().priority, Priority::Normal);
+        assert_eq!(scheduler.run_queue.pick_next(), Some(tid));
+    }
+
+    #[test]
+    fn test_aging_protects_low_priority_thread_from_starvation() {
+        *THREAD_TABLE.lock() = ThreadTable::new();
+
+        let low_tid = ThreadId(200);
+        let mut low_thread = Thread::new(
+            low_tid, ProcessId(1), Priority::Low,
+            VirtAddr::new(0x1000), VirtAddr::new(0x2000), None,
+        );
+        // Allowed to climb as high as `High` if starved long enough.
+        low_thread.base_priority = Priority::High;
+        THREAD_TABLE.lock().add_thread(low_thread);
+
+        let mut scheduler = Scheduler::new();
+        scheduler.add_thread(low_tid);
+
+        // A flood of fresh high-priority arrivals that plain `pick_next`
+        // would always drain ahead of the long-waiting low thread.
+        for i in 0..5u64 {
+            let tid = ThreadId(201 + i as usize);
+            let thread = Thread::new(
+                tid, ProcessId(1), Priority::High,
+                VirtAddr::new(0x1000), VirtAddr::new(0x2000), None,
+            );
+            THREAD_TABLE.lock().add_thread(thread);
+            scheduler.add_thread(tid);
+        }
+
+        scheduler.age_threads(AGING_THRESHOLD_NS);
+
+        // The low thread was promoted a band; the freshly-enqueued
+        // high-priority threads are already at their ceiling, so aging
+        // leaves them untouched.
+        assert_eq!(THREAD_TABLE.lock().get_thread(low_tid).unwrap().priority, Priority::Normal);
+        for i in 0..5u64 {
+            let tid = ThreadId(201 + i as usize);
+            assert_eq!(THREAD_TABLE.lock().get_thread(tid).unwrap().priority, Priority::High);
+        }
+    }
 }
@@ -16,6 +16,10 @@ pub enum ThreadState {
     Running,
     Blocked,
     Sleeping,
+    /// Stopped for a tracer at a syscall boundary, per `sys_ptrace`. Left
+    /// off the run queue the same as `Blocked`, but resumed only by
+    /// `CONT`/`SINGLESTEP` rather than whatever woke a plain waiter.
+    TraceStopped,
     Terminated,
 }
 
@@ -28,8 +32,43 @@ pub struct Thread {
     pub context: Context,
     pub kernel_stack: VirtAddr,
     pub user_stack: Option<VirtAddr>,
-    pub time_slice: usize,
+    /// Length of this thread's time slice in nanoseconds.
+    pub time_slice: u64,
+    /// Total CPU time this thread has been charged, in nanoseconds.
     pub total_cpu_time: u64,
+    /// This thread's priority ceiling. The scheduler's feedback queue may
+    /// temporarily demote `priority` below this, and ages it back up, but
+    /// never promotes it past `base_priority`.
+    pub base_priority: Priority,
+    /// `clock::clock().now_ns()` as of the last time this thread became
+    /// `Ready`, so the scheduler's aging sweep can detect starvation.
+    pub last_enqueue_time: u64,
+    /// Nanoseconds run during this thread's current scheduling stint,
+    /// reset each time it starts running; compared against `time_slice`
+    /// to tell an exhausted slice (demote) from an early yield/block
+    /// (keep band).
+    pub slice_used: u64,
+    /// The thread that may issue `sys_ptrace` control requests against
+    /// this one, set by `TRACEME`/`ATTACH`. `None` means this thread
+    /// isn't being traced.
+    pub tracer: Option<ThreadId>,
+    /// Whether this thread stops at its own syscall boundaries for
+    /// `tracer` to inspect. Distinct from `tracer.is_some()` only in
+    /// theory today (nothing clears one without the other), but kept
+    /// separate since a traced-but-not-yet-attached state is a real
+    /// ptrace concept even though this kernel doesn't produce one yet.
+    pub traced: bool,
+    /// Set by `CONT`/`SINGLESTEP` to let this thread's next syscall run
+    /// instead of stopping again immediately; cleared the moment that
+    /// syscall is let through, so the one after it traps the same way
+    /// the first one did.
+    pub trace_resume: bool,
+    /// Seeded by `syscall::sys_clone`'s `CLONE_SETTLS` flag. Advisory
+    /// state only -- this kernel's `Context`/`ArmContext` don't model a
+    /// TLS base register, so nothing reads this back into hardware on a
+    /// context switch yet; it exists so a future `FS_BASE`/`TPIDR_EL0`
+    /// wire-up has somewhere to read the value from.
+    pub tls_base: u64,
 }
 
 impl Thread {
@@ -57,16 +96,27 @@ impl Thread {
             user_stack,
             time_slice: Self::calculate_time_slice(priority),
             total_cpu_time: 0,
+            base_priority: priority,
+            last_enqueue_time: 0,
+            slice_used: 0,
+            tracer: None,
+            traced: false,
+            trace_resume: false,
+            tls_base: 0,
         }
     }
 
-    fn calculate_time_slice(priority: Priority) -> usize {
+    /// Base time slice for `priority`, in nanoseconds. Higher priorities
+    /// get longer slices so they amortize context-switch overhead better
+    /// once actually running.
+    fn calculate_time_slice(priority: Priority) -> u64 {
+        const MS: u64 = 1_000_000;
         match priority {
-            Priority::Idle => 1,
-            Priority::Low => 5,
-            Priority::Normal => 10,
-            Priority::High => 20,
-            Priority::Realtime => 50,
+            Priority::Idle => MS,
+            Priority::Low => 5 * MS,
+            Priority::Normal => 10 * MS,
+            Priority::High => 20 * MS,
+            Priority::Realtime => 50 * MS,
         }
     }
 
@@ -78,8 +128,38 @@ impl Thread {
         self.state = state;
     }
 
-    pub fn increment_cpu_time(&mut self, ticks: u64) {
-        self.total_cpu_time += ticks;
+    /// Charge `ns` nanoseconds of CPU time to this thread.
+    pub fn increment_cpu_time_ns(&mut self, ns: u64) {
+        self.total_cpu_time += ns;
+    }
+
+    /// Whether this thread has used its entire time slice during its
+    /// current running stint.
+    pub fn slice_exhausted(&self) -> bool {
+        self.slice_used >= self.time_slice
+    }
+
+    /// Charge `ns` nanoseconds toward the current stint's slice usage,
+    /// separate from `total_cpu_time`'s lifetime accumulation.
+    pub fn charge_slice_used_ns(&mut self, ns: u64) {
+        self.slice_used += ns;
+    }
+
+    pub fn reset_slice_used(&mut self) {
+        self.slice_used = 0;
+    }
+
+    /// Demote one band toward `Idle`, never going below it.
+    pub fn demote(&mut self) {
+        self.priority = Priority::from_usize(self.priority.as_usize().saturating_sub(1));
+    }
+
+    /// Promote one band toward `base_priority`, never exceeding it.
+    pub fn promote_toward_base(&mut self) {
+        let next = self.priority.as_usize() + 1;
+        if next <= self.base_priority.as_usize() {
+            self.priority = Priority::from_usize(next);
+        }
     }
 }
 
@@ -167,6 +247,15 @@ pub fn set_thread_state(tid: ThreadId, state: ThreadState) {
     }
 }
 
+/// Let `tid`'s currently-trapped syscall (if any) run instead of
+/// trapping again, consumed by `handle_syscall`'s trace-stop gate the
+/// next time this thread calls in.
+pub fn clear_trace_resume(tid: ThreadId) {
+    if let Some(thread) = THREAD_TABLE.lock().get_thread_mut(tid) {
+        thread.trace_resume = false;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,186 @@
+//! Redox-style scheme providers.
+//!
+//! A userspace process registers itself as the provider for a named
+//! prefix (e.g. `"disk"`, `"net"`) via [`register`]. `sys_open` in
+//! `syscall` then resolves a path like `"disk:/boot/image"` to that
+//! provider, and routes `open`/`read`/`write`/`close` to it as
+//! [`SchemePacket`]s rather than touching any storage itself. The
+//! provider drains its queue with `SYS_SCHEME_RECV` and answers with
+//! `SYS_SCHEME_REPLY`; the caller that's blocked on the request is woken
+//! once the reply lands.
+//!
+//! The kernel-internal `"sys"` namespace is the one exception: it's
+//! serviced directly by `syscall::sys_sysfs_read`/`sys_sysfs_write`
+//! rather than through a registered provider, so sysfs tunables work
+//! with no userspace daemon running.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use spin::Mutex;
+
+use crate::process::{ProcessId, ThreadId};
+
+/// Operation a [`SchemePacket`] carries, mirroring Redox's `SYS_OPEN`/
+/// `SYS_READ`/`SYS_WRITE`/`SYS_CLOSE` packet opcodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemeOp {
+    Open,
+    Read,
+    Write,
+    Close,
+}
+
+/// A request queued to a provider. `id` is what the provider echoes back
+/// in its `SYS_SCHEME_REPLY` so [`take_reply`] can find the caller it
+/// belongs to.
+#[derive(Debug, Clone)]
+pub struct SchemePacket {
+    pub id: u64,
+    pub opcode: SchemeOp,
+    pub fd: u32,
+    pub caller: ProcessId,
+    pub payload: Vec<u8>,
+}
+
+/// A request awaiting its provider's reply, keyed by `id` so
+/// [`reply`]/[`take_reply`] can find it without the caller needing to
+/// remember anything beyond the id `submit` handed back.
+struct PendingReply {
+    id: u64,
+    waiter: ThreadId,
+    result: Option<(isize, Vec<u8>)>,
+}
+
+struct ProviderQueue {
+    provider: ProcessId,
+    packets: Vec<SchemePacket>,
+}
+
+/// Scheme name -> the process that registered as its provider. A fixed
+/// `Vec` scanned linearly, matching this kernel's other small bounded
+/// registries (`syscall::WAITERS`, `syscall::ZOMBIE_CHILDREN`) rather
+/// than pulling in `alloc::collections::BTreeMap`.
+static PROVIDERS: Mutex<Vec<(String, ProcessId)>> = Mutex::new(Vec::new());
+static QUEUES: Mutex<Vec<ProviderQueue>> = Mutex::new(Vec::new());
+static PENDING: Mutex<Vec<PendingReply>> = Mutex::new(Vec::new());
+static NEXT_PACKET_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Register `provider` as the handler for `scheme`, replacing whatever
+/// previously owned that name (a provider that crashes and restarts just
+/// re-registers).
+pub fn register(scheme: String, provider: ProcessId) {
+    let mut providers = PROVIDERS.lock();
+    if let Some(entry) = providers.iter_mut().find(|(name, _)| *name == scheme) {
+        entry.1 = provider;
+    } else {
+        providers.push((scheme, provider));
+    }
+}
+
+/// Split `path` into its scheme prefix and the rest, e.g.
+/// `"disk:/boot/image"` -> `("disk", "/boot/image")`. A path with no
+/// `:` has no scheme to resolve.
+pub fn split_scheme(path: &str) -> Option<(&str, &str)> {
+    let colon = path.find(':')?;
+    Some((&path[..colon], &path[colon + 1..]))
+}
+
+pub fn provider_for(scheme: &str) -> Option<ProcessId> {
+    PROVIDERS
+        .lock()
+        .iter()
+        .find(|(name, _)| name == scheme)
+        .map(|(_, pid)| *pid)
+}
+
+/// Enqueue a request to `scheme`'s registered provider on behalf of
+/// `caller`/`waiter`, returning the packet id [`take_reply`] will later
+/// resolve. `None` if no provider is registered for `scheme`. Used for
+/// `open`, where the caller doesn't have a provider pid yet -- only the
+/// scheme name out of the path.
+pub fn submit(
+    scheme: &str,
+    opcode: SchemeOp,
+    fd: u32,
+    caller: ProcessId,
+    waiter: ThreadId,
+    payload: Vec<u8>,
+) -> Option<u64> {
+    let provider = provider_for(scheme)?;
+    Some(submit_to(provider, opcode, fd, caller, waiter, payload))
+}
+
+/// Like [`submit`], but for a request against an fd that's already bound
+/// to a provider (`read`/`write`/`close`), so there's no scheme name to
+/// resolve.
+pub fn submit_to(
+    provider: ProcessId,
+    opcode: SchemeOp,
+    fd: u32,
+    caller: ProcessId,
+    waiter: ThreadId,
+    payload: Vec<u8>,
+) -> u64 {
+    let id = NEXT_PACKET_ID.fetch_add(1, Ordering::Relaxed);
+
+    let mut queues = QUEUES.lock();
+    match queues.iter_mut().find(|q| q.provider == provider) {
+        Some(queue) => queue.packets.push(SchemePacket {
+            id,
+            opcode,
+            fd,
+            caller,
+            payload,
+        }),
+        None => queues.push(ProviderQueue {
+            provider,
+            packets: alloc::vec![SchemePacket {
+                id,
+                opcode,
+                fd,
+                caller,
+                payload
+            }],
+        }),
+    }
+
+    PENDING.lock().push(PendingReply {
+        id,
+        waiter,
+        result: None,
+    });
+    id
+}
+
+/// Pop the next request queued for `provider`, if any.
+pub fn take_request(provider: ProcessId) -> Option<SchemePacket> {
+    let mut queues = QUEUES.lock();
+    let queue = queues.iter_mut().find(|q| q.provider == provider)?;
+    if queue.packets.is_empty() {
+        None
+    } else {
+        Some(queue.packets.remove(0))
+    }
+}
+
+/// Record a provider's answer to packet `id`. Returns the waiter to wake,
+/// or `None` if `id` doesn't match an outstanding request (a stale or
+/// forged reply).
+pub fn reply(id: u64, result: isize, data: Vec<u8>) -> Option<ThreadId> {
+    let mut pending = PENDING.lock();
+    let entry = pending.iter_mut().find(|entry| entry.id == id)?;
+    entry.result = Some((result, data));
+    Some(entry.waiter)
+}
+
+/// Take the reply to packet `id`, if the provider has answered it yet.
+/// Removes the entry once consumed.
+pub fn take_reply(id: u64) -> Option<(isize, Vec<u8>)> {
+    let mut pending = PENDING.lock();
+    let index = pending
+        .iter()
+        .position(|entry| entry.id == id && entry.result.is_some())?;
+    pending.remove(index).result
+}
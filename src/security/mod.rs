@@ -41,6 +41,15 @@ pub struct SecurityContext {
     pub privilege: PrivilegeLevel,
     pub umask: u16,
     pub capabilities: u64,
+    /// Ceiling on `capabilities`: no amount of `grant_capabilities` or
+    /// inheritance can ever raise `capabilities` past this mask. Mirrors
+    /// the bounding set in POSIX capabilities.
+    pub bounding_set: CapMask,
+    /// Once set (via `set_no_new_privs`), `apply_setuid` refuses to raise
+    /// `euid`/`egid` above their current values, the same guarantee
+    /// `prctl(PR_SET_NO_NEW_PRIVS)` gives userspace against setuid
+    /// privilege escalation.
+    pub no_new_privs: bool,
 }
 
 impl SecurityContext {
@@ -53,6 +62,8 @@ impl SecurityContext {
             privilege,
             umask: 0o022,
             capabilities: 0,
+            bounding_set: CapMask::MAX,
+            no_new_privs: false,
         }
     }
 
@@ -81,10 +92,19 @@ impl SecurityContext {
     }
 
     pub fn apply_setuid(&mut self, uid: u32) {
+        // uid 0 is most privileged, so a *lower* uid than the current
+        // euid is a privilege escalation; no_new_privs blocks exactly that.
+        if self.no_new_privs && uid < self.euid {
+            return;
+        }
         self.euid = uid;
         self.egid = uid;
     }
 
+    pub fn set_no_new_privs(&mut self) {
+        self.no_new_privs = true;
+    }
+
     pub fn is_privileged_enough(&self, max_allowed_ring: PrivilegeLevel) -> bool {
         (self.privilege as u8) <= (max_allowed_ring as u8)
     }
@@ -94,7 +114,7 @@ impl SecurityContext {
     }
 
     pub fn grant_capabilities(&mut self, mask: CapMask) {
-        self.capabilities |= mask;
+        self.capabilities |= mask & self.bounding_set;
     }
 
     pub fn revoke_capabilities(&mut self, mask: CapMask) {
@@ -108,7 +128,11 @@ impl SecurityContext {
 
     pub fn inherit_child(&self, caps_subset: CapMask) -> Self {
         let mut child = self.clone();
-        child.capabilities = self.capabilities & caps_subset;
+        // The child's bounding set can only shrink relative to the
+        // parent's, so no later `grant_capabilities` on the child can
+        // climb back past what the parent allowed here.
+        child.bounding_set = self.bounding_set & caps_subset;
+        child.capabilities = self.capabilities & child.bounding_set;
         child
     }
 }
@@ -196,6 +220,34 @@ pub fn set_umask(pid: usize, umask: u16) {
     }
 }
 
+/// `-errno` returned by `check_syscall` when the caller's PID has no
+/// registered security context.
+pub const ESRCH: i32 = 3;
+/// `-errno` returned by `check_syscall` when the syscall number has no
+/// entry in the capability table.
+pub const ENOSYS: i32 = 38;
+/// `-errno` returned by `check_syscall` when the caller's `SecurityContext`
+/// is missing one or more of the syscall's required capabilities.
+pub const EPERM: i32 = 1;
+
+/// Capability gate sitting between the arch syscall entry stubs and the
+/// real handlers: look up `pid`'s `SecurityContext`, compare it against
+/// the capabilities `crate::syscall::SYSCALL_TABLE` requires for `number`,
+/// and reject the call before it ever reaches a handler.
+pub fn check_syscall(pid: usize, number: usize) -> Result<(), i32> {
+    let descriptor = crate::syscall::SYSCALL_TABLE
+        .get(number)
+        .ok_or(-ENOSYS)?;
+
+    let context = get_context(pid).ok_or(-ESRCH)?;
+
+    if context.has_capabilities(descriptor.required_capabilities) {
+        Ok(())
+    } else {
+        Err(-EPERM)
+    }
+}
+
 pub fn remove_context(pid: usize) {
     let mut table = SECURITY_CONTEXTS.lock();
     if let Some(index) = table.iter().position(|entry| entry.pid == pid) {
@@ -234,4 +286,48 @@ mod tests {
         let child = clone_context_with_caps_subset(10, 11, CAP_CONSOLE_IO).unwrap();
         assert_eq!(child.capabilities, CAP_CONSOLE_IO);
     }
+
+    #[test]
+    fn test_bounding_set_caps_future_grants() {
+        let mut child = SecurityContext::as_user(1).inherit_child(CAP_CONSOLE_IO);
+        assert_eq!(child.bounding_set, CAP_CONSOLE_IO);
+
+        // Even a direct grant of a capability outside the bounding set
+        // must not stick.
+        child.grant_capabilities(CAP_PROC_MANAGE);
+        assert!(!child.has_capabilities(CAP_PROC_MANAGE));
+
+        // A grandchild's bounding set can only shrink further, never grow
+        // back toward the grandparent's.
+        let grandchild = child.inherit_child(CAP_PROC_MANAGE | CAP_CONSOLE_IO);
+        assert_eq!(grandchild.bounding_set, CAP_CONSOLE_IO);
+    }
+
+    #[test]
+    fn test_no_new_privs_blocks_setuid_escalation() {
+        let mut ctx = SecurityContext::as_user(1000);
+        ctx.apply_setuid(2000);
+        assert_eq!(ctx.euid, 2000);
+
+        ctx.set_no_new_privs();
+        ctx.apply_setuid(0); // would escalate to root, must be rejected
+        assert_eq!(ctx.euid, 2000);
+
+        ctx.apply_setuid(3000); // dropping further privilege stays allowed
+        assert_eq!(ctx.euid, 3000);
+    }
+
+    #[test]
+    fn test_check_syscall_gates_on_capabilities() {
+        register_process(20, SecurityContext::as_user(1));
+        assert_eq!(
+            check_syscall(20, crate::syscall::SYS_WRITE),
+            Err(-EPERM)
+        );
+
+        grant_capabilities(20, CAP_CONSOLE_IO);
+        assert_eq!(check_syscall(20, crate::syscall::SYS_WRITE), Ok(()));
+
+        assert_eq!(check_syscall(999, crate::syscall::SYS_WRITE), Err(-ESRCH));
+    }
 }
@@ -4,11 +4,16 @@
 //! register ABI into the architecture-agnostic `syscall_handler(number, a1..a6)`
 //! calling convention.
 
-#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+#[cfg(any(
+    target_arch = "x86_64",
+    target_arch = "aarch64",
+    target_arch = "riscv64"
+))]
 use core::arch::global_asm;
 
 #[cfg(target_arch = "x86_64")]
-global_asm!(r#"
+global_asm!(
+    r#"
     .section .text
     .att_syntax
     .global syscall_entry
@@ -48,10 +53,12 @@ syscall_entry:
     popq %rcx
 
     sysretq
-"#);
+"#
+);
 
 #[cfg(target_arch = "aarch64")]
-global_asm!(r#"
+global_asm!(
+    r#"
     .section .text
     .global svc_entry
     .type svc_entry, %function
@@ -81,4 +88,47 @@ svc_entry:
 
     ldp x29, x30, [sp], #16
     eret
-"#);
+"#
+);
+
+#[cfg(target_arch = "riscv64")]
+global_asm!(
+    r#"
+    .section .text
+    .global ecall_entry
+    .type ecall_entry, @function
+
+// RISC-V 64 ecall entry from U-mode.
+//
+// Userspace ABI (Linux-style):
+//   a7 = syscall number
+//   a0..a5 = args 1..6
+//
+// Kernel ABI:
+//   a0..a6 = syscall_handler(number, a1..a6)
+ecall_entry:
+    addi sp, sp, -16
+    sd ra, 0(sp)
+
+    // Shuffle arguments top-down so nothing is clobbered before it's read.
+    mv a6, a5
+    mv a5, a4
+    mv a4, a3
+    mv a3, a2
+    mv a2, a1
+    mv a1, a0
+    mv a0, a7
+
+    call syscall_handler
+
+    ld ra, 0(sp)
+    addi sp, sp, 16
+
+    // Skip over the `ecall` instruction that trapped us here.
+    csrr t0, sepc
+    addi t0, t0, 4
+    csrw sepc, t0
+
+    sret
+"#
+);
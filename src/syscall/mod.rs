@@ -1,12 +1,16 @@
 #[cfg(not(test))]
 extern crate alloc;
 
-use alloc::{collections::VecDeque, string::{String, ToString}, vec::Vec};
-use core::{slice, str};
+use alloc::{collections::VecDeque, string::String, sync::Arc, vec::Vec};
 
 use spin::Mutex;
 
-use crate::memory::{frame_allocator::allocate_frame, paging::Page, VirtAddr};
+use crate::clock;
+use crate::memory::{
+    frame_allocator::allocate_frame,
+    paging::{temporary_map, Page, PageTableMapper},
+    VirtAddr,
+};
 use crate::process::{
     self,
     create_process,
@@ -14,23 +18,34 @@ use crate::process::{
     scheduler,
     thread::{self, ThreadState, THREAD_TABLE},
     Context,
+    FdObject,
     ProcessId,
     ThreadId,
 };
+
+#[cfg(target_arch = "x86_64")]
+use crate::memory::paging::X86_64PageTable;
+
+#[cfg(target_arch = "aarch64")]
+use crate::memory::paging::arm_lpae::ArmLpaePageTable;
 use crate::process::elf_loader::{self, TargetArch};
+use crate::scheme;
 use crate::security::{
-    CapMask, PrivilegeLevel, CAP_CONSOLE_IO, CAP_PROC_MANAGE, CAP_VM_MANAGE,
+    CapMask, PrivilegeLevel, CAP_CONSOLE_IO, CAP_FS_RW, CAP_IPC, CAP_PROC_MANAGE, CAP_VM_MANAGE,
 };
 use crate::process::loader;
 use crate::userspace;
 
 pub mod entry;
+pub mod user_access;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(i32)]
 pub enum Errno {
     EPERM = 1,
     ESRCH = 3,
+    EINTR = 4,
+    EAGAIN = 11,
     ENOMEM = 12,
     EINVAL = 22,
     ENOSYS = 38,
@@ -78,6 +93,11 @@ pub struct SyscallDescriptor {
     pub handler: SyscallHandlerFn,
     pub max_caller_ring: PrivilegeLevel,
     pub required_capabilities: CapMask,
+    /// Compute units `handle_syscall` draws from the caller's
+    /// `process::compute_budget` before dispatching to `handler`. Cheap
+    /// calls like `getpid` cost 1; heavyweight ones like `fork`/`exec`/
+    /// `mmap` cost in the hundreds.
+    pub cost: u32,
     pub args: [SyscallArgKind; 6],
 }
 
@@ -110,7 +130,6 @@ pub const SYS_READ: usize = 10;
 pub const SYS_OPEN: usize = 11;
 pub const SYS_CLOSE: usize = 12;
 pub const SYS_PIPE: usize = 13;
-pub const SYS_SIGNAL: usize = 14;
 
 pub const SYS_EXECVE: usize = SYS_EXEC;
 pub const SYS_WAITPID: usize = SYS_WAIT;
@@ -124,8 +143,24 @@ pub const SYS_SHM_MAP: usize = 19;
 pub const SYS_SYSFS_READ: usize = 20;
 pub const SYS_SYSFS_WRITE: usize = 21;
 pub const SYS_DEBUG_LOG: usize = 22;
-
-pub const SYSCALL_MAX: usize = 32;
+pub const SYS_WAITID: usize = 23;
+pub const SYS_GET_COMPUTE_BUDGET: usize = 24;
+pub const SYS_SCHEME_REGISTER: usize = 25;
+pub const SYS_SCHEME_RECV: usize = 26;
+pub const SYS_SCHEME_REPLY: usize = 27;
+pub const SYS_PTRACE: usize = 28;
+/// Originally `14`, back when this table was laid out -- that slot was
+/// reclaimed by `SYS_YIELD` before `sys_signal` was ever implemented, so
+/// this lives in one of the reserved slots at the end instead.
+pub const SYS_SIGNAL: usize = 29;
+pub const SYS_PIDFD_OPEN: usize = 30;
+pub const SYS_PIDFD_SEND_SIGNAL: usize = 31;
+pub const SYS_PRCTL: usize = 32;
+pub const SYS_SECCOMP_SET: usize = 33;
+pub const SYS_GETRLIMIT: usize = 34;
+pub const SYS_SETRLIMIT: usize = 35;
+
+pub const SYSCALL_MAX: usize = 36;
 
 pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
     SyscallDescriptor {
@@ -134,6 +169,7 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_exit,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: CAP_NONE,
+        cost: 1,
         args: arg_spec(SyscallArgKind::Usize, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
@@ -142,6 +178,7 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_fork,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: CAP_PROC_MANAGE,
+        cost: 300,
         args: arg_spec(SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
@@ -150,6 +187,7 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_exec,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: CAP_PROC_MANAGE,
+        cost: 300,
         args: arg_spec(SyscallArgKind::CStringPtr, SyscallArgKind::CStringArrayPtr, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
@@ -158,7 +196,15 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_wait,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: CAP_PROC_MANAGE,
-        args: arg_spec(SyscallArgKind::Usize, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
+        cost: 5,
+        args: arg_spec(
+            SyscallArgKind::Usize,
+            SyscallArgKind::Flags,
+            SyscallArgKind::Ptr,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+        ),
     },
     SyscallDescriptor {
         number: SYS_GETPID,
@@ -166,6 +212,7 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_getpid,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: CAP_NONE,
+        cost: 1,
         args: arg_spec(SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
@@ -174,6 +221,7 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_mmap,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: CAP_VM_MANAGE,
+        cost: 200,
         args: arg_spec(SyscallArgKind::Ptr, SyscallArgKind::Len, SyscallArgKind::Flags, SyscallArgKind::Flags, SyscallArgKind::Fd, SyscallArgKind::Offset),
     },
     SyscallDescriptor {
@@ -182,6 +230,7 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_munmap,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: CAP_VM_MANAGE,
+        cost: 50,
         args: arg_spec(SyscallArgKind::Ptr, SyscallArgKind::Len, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
@@ -190,6 +239,7 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_brk,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: CAP_VM_MANAGE,
+        cost: 20,
         args: arg_spec(SyscallArgKind::Ptr, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
@@ -198,7 +248,8 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_clone,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: CAP_PROC_MANAGE,
-        args: arg_spec(SyscallArgKind::Flags, SyscallArgKind::Ptr, SyscallArgKind::Ptr, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
+        cost: 250,
+        args: arg_spec(SyscallArgKind::Flags, SyscallArgKind::Ptr, SyscallArgKind::None, SyscallArgKind::Usize, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
         number: SYS_WRITE,
@@ -206,6 +257,7 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_write,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: CAP_CONSOLE_IO,
+        cost: 5,
         args: arg_spec(SyscallArgKind::Fd, SyscallArgKind::Ptr, SyscallArgKind::Len, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
@@ -214,22 +266,25 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_read,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: CAP_CONSOLE_IO,
+        cost: 5,
         args: arg_spec(SyscallArgKind::Fd, SyscallArgKind::Ptr, SyscallArgKind::Len, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
         number: SYS_OPEN,
         name: "open",
-        handler: sys_unimplemented,
+        handler: sys_open,
         max_caller_ring: PrivilegeLevel::Ring3,
-        required_capabilities: 0,
+        required_capabilities: CAP_FS_RW,
+        cost: 10,
         args: arg_spec(SyscallArgKind::CStringPtr, SyscallArgKind::Flags, SyscallArgKind::Flags, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
         number: SYS_CLOSE,
         name: "close",
-        handler: sys_unimplemented,
+        handler: sys_close,
         max_caller_ring: PrivilegeLevel::Ring3,
-        required_capabilities: 0,
+        required_capabilities: CAP_FS_RW,
+        cost: 2,
         args: arg_spec(SyscallArgKind::Fd, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
@@ -238,6 +293,7 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_unimplemented,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: 0,
+        cost: 10,
         args: arg_spec(SyscallArgKind::Fd, SyscallArgKind::Usize, SyscallArgKind::Ptr, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
@@ -246,14 +302,16 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_yield,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: CAP_NONE,
+        cost: 1,
         args: arg_spec(SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
         number: SYS_NANOSLEEP,
         name: "nanosleep",
-        handler: sys_unimplemented,
+        handler: sys_nanosleep,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: CAP_NONE,
+        cost: 2,
         args: arg_spec(SyscallArgKind::Ptr, SyscallArgKind::Ptr, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
@@ -262,6 +320,7 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_unimplemented,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: 0,
+        cost: 10,
         args: arg_spec(SyscallArgKind::Usize, SyscallArgKind::Ptr, SyscallArgKind::Len, SyscallArgKind::Flags, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
@@ -270,6 +329,7 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_unimplemented,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: 0,
+        cost: 10,
         args: arg_spec(SyscallArgKind::Usize, SyscallArgKind::Ptr, SyscallArgKind::Len, SyscallArgKind::Flags, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
@@ -278,6 +338,7 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_unimplemented,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: 0,
+        cost: 100,
         args: arg_spec(SyscallArgKind::Usize, SyscallArgKind::Len, SyscallArgKind::Flags, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
@@ -286,22 +347,25 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_unimplemented,
         max_caller_ring: PrivilegeLevel::Ring3,
         required_capabilities: 0,
+        cost: 100,
         args: arg_spec(SyscallArgKind::Usize, SyscallArgKind::Ptr, SyscallArgKind::Len, SyscallArgKind::Flags, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
         number: SYS_SYSFS_READ,
         name: "sysfs_read",
-        handler: sys_unimplemented,
+        handler: sys_sysfs_read,
         max_caller_ring: PrivilegeLevel::Ring3,
-        required_capabilities: 0,
+        required_capabilities: CAP_FS_RW,
+        cost: 10,
         args: arg_spec(SyscallArgKind::CStringPtr, SyscallArgKind::Ptr, SyscallArgKind::Len, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
         number: SYS_SYSFS_WRITE,
         name: "sysfs_write",
-        handler: sys_unimplemented,
+        handler: sys_sysfs_write,
         max_caller_ring: PrivilegeLevel::Ring3,
-        required_capabilities: 0,
+        required_capabilities: CAP_FS_RW,
+        cost: 10,
         args: arg_spec(SyscallArgKind::CStringPtr, SyscallArgKind::Ptr, SyscallArgKind::Len, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
@@ -310,88 +374,344 @@ pub static SYSCALL_TABLE: [SyscallDescriptor; SYSCALL_MAX] = [
         handler: sys_unimplemented,
         max_caller_ring: PrivilegeLevel::Ring0,
         required_capabilities: 0,
+        cost: 1,
         args: arg_spec(SyscallArgKind::Ptr, SyscallArgKind::Len, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
-        number: 23,
-        name: "reserved23",
-        handler: sys_unimplemented,
+        number: SYS_WAITID,
+        name: "waitid",
+        handler: sys_waitid,
         max_caller_ring: PrivilegeLevel::Ring3,
-        required_capabilities: 0,
-        args: arg_spec(SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
+        required_capabilities: CAP_PROC_MANAGE,
+        cost: 5,
+        args: arg_spec(
+            SyscallArgKind::Usize,
+            SyscallArgKind::Usize,
+            SyscallArgKind::Flags,
+            SyscallArgKind::Ptr,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+        ),
     },
     SyscallDescriptor {
-        number: 24,
-        name: "reserved24",
-        handler: sys_unimplemented,
+        number: SYS_GET_COMPUTE_BUDGET,
+        name: "get_compute_budget",
+        handler: sys_get_compute_budget,
         max_caller_ring: PrivilegeLevel::Ring3,
-        required_capabilities: 0,
+        required_capabilities: CAP_NONE,
+        cost: 1,
         args: arg_spec(SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
-        number: 25,
-        name: "reserved25",
-        handler: sys_unimplemented,
+        number: SYS_SCHEME_REGISTER,
+        name: "scheme_register",
+        handler: sys_scheme_register,
         max_caller_ring: PrivilegeLevel::Ring3,
-        required_capabilities: 0,
-        args: arg_spec(SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
+        required_capabilities: CAP_IPC,
+        cost: 5,
+        args: arg_spec(SyscallArgKind::CStringPtr, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
-        number: 26,
-        name: "reserved26",
-        handler: sys_unimplemented,
+        number: SYS_SCHEME_RECV,
+        name: "scheme_recv",
+        handler: sys_scheme_recv,
         max_caller_ring: PrivilegeLevel::Ring3,
-        required_capabilities: 0,
-        args: arg_spec(SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
+        required_capabilities: CAP_IPC,
+        cost: 2,
+        args: arg_spec(SyscallArgKind::Ptr, SyscallArgKind::Len, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
     },
     SyscallDescriptor {
-        number: 27,
-        name: "reserved27",
-        handler: sys_unimplemented,
+        number: SYS_SCHEME_REPLY,
+        name: "scheme_reply",
+        handler: sys_scheme_reply,
         max_caller_ring: PrivilegeLevel::Ring3,
-        required_capabilities: 0,
-        args: arg_spec(SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
+        required_capabilities: CAP_IPC,
+        cost: 5,
+        args: arg_spec(
+            SyscallArgKind::Usize,
+            SyscallArgKind::Usize,
+            SyscallArgKind::Ptr,
+            SyscallArgKind::Len,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+        ),
     },
     SyscallDescriptor {
-        number: 28,
-        name: "reserved28",
-        handler: sys_unimplemented,
+        number: SYS_PTRACE,
+        name: "ptrace",
+        handler: sys_ptrace,
         max_caller_ring: PrivilegeLevel::Ring3,
-        required_capabilities: 0,
-        args: arg_spec(SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
+        required_capabilities: CAP_PROC_MANAGE,
+        cost: 5,
+        args: arg_spec(
+            SyscallArgKind::Usize,
+            SyscallArgKind::Usize,
+            SyscallArgKind::Ptr,
+            SyscallArgKind::Usize,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+        ),
     },
     SyscallDescriptor {
-        number: 29,
-        name: "reserved29",
-        handler: sys_unimplemented,
+        number: SYS_SIGNAL,
+        name: "signal",
+        handler: sys_signal,
         max_caller_ring: PrivilegeLevel::Ring3,
-        required_capabilities: 0,
-        args: arg_spec(SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
+        required_capabilities: CAP_PROC_MANAGE,
+        cost: 2,
+        args: arg_spec(
+            SyscallArgKind::Usize,
+            SyscallArgKind::Usize,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+        ),
     },
     SyscallDescriptor {
-        number: 30,
-        name: "reserved30",
-        handler: sys_unimplemented,
+        number: SYS_PIDFD_OPEN,
+        name: "pidfd_open",
+        handler: sys_pidfd_open,
         max_caller_ring: PrivilegeLevel::Ring3,
-        required_capabilities: 0,
-        args: arg_spec(SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
+        required_capabilities: CAP_PROC_MANAGE,
+        cost: 3,
+        args: arg_spec(
+            SyscallArgKind::Usize,
+            SyscallArgKind::Flags,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+        ),
     },
     SyscallDescriptor {
-        number: 31,
-        name: "reserved31",
-        handler: sys_unimplemented,
+        number: SYS_PIDFD_SEND_SIGNAL,
+        name: "pidfd_send_signal",
+        handler: sys_pidfd_send_signal,
         max_caller_ring: PrivilegeLevel::Ring3,
-        required_capabilities: 0,
-        args: arg_spec(SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None, SyscallArgKind::None),
+        required_capabilities: CAP_PROC_MANAGE,
+        cost: 3,
+        args: arg_spec(
+            SyscallArgKind::Fd,
+            SyscallArgKind::Usize,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+        ),
+    },
+    SyscallDescriptor {
+        number: SYS_PRCTL,
+        name: "prctl",
+        handler: sys_prctl,
+        max_caller_ring: PrivilegeLevel::Ring3,
+        required_capabilities: CAP_PROC_MANAGE,
+        cost: 2,
+        args: arg_spec(
+            SyscallArgKind::Usize,
+            SyscallArgKind::Usize,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+        ),
+    },
+    SyscallDescriptor {
+        number: SYS_SECCOMP_SET,
+        name: "seccomp_set",
+        handler: sys_seccomp_set,
+        max_caller_ring: PrivilegeLevel::Ring3,
+        required_capabilities: CAP_PROC_MANAGE,
+        cost: 2,
+        args: arg_spec(
+            SyscallArgKind::Usize,
+            SyscallArgKind::Usize,
+            SyscallArgKind::Ptr,
+            SyscallArgKind::Len,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+        ),
+    },
+    SyscallDescriptor {
+        number: SYS_GETRLIMIT,
+        name: "getrlimit",
+        handler: sys_getrlimit,
+        max_caller_ring: PrivilegeLevel::Ring3,
+        required_capabilities: CAP_NONE,
+        cost: 1,
+        args: arg_spec(
+            SyscallArgKind::Usize,
+            SyscallArgKind::Ptr,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+        ),
+    },
+    SyscallDescriptor {
+        number: SYS_SETRLIMIT,
+        name: "setrlimit",
+        handler: sys_setrlimit,
+        max_caller_ring: PrivilegeLevel::Ring3,
+        required_capabilities: CAP_NONE,
+        cost: 1,
+        args: arg_spec(
+            SyscallArgKind::Usize,
+            SyscallArgKind::Ptr,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+            SyscallArgKind::None,
+        ),
     },
 ];
 
 const CAP_NONE: CapMask = 0;
 
+bitflags::bitflags! {
+    /// Flags accepted by [`sys_wait`]/[`sys_waitid`]'s options argument, in
+    /// the style of `rustix::process::WaitOptions`.
+    #[derive(Debug, Clone, Copy)]
+    pub struct WaitOptions: usize {
+        /// Return immediately with `Ok(0)` instead of blocking when no
+        /// matching child has exited yet.
+        const WNOHANG = 1;
+        /// Also report stopped (not just terminated) children. Accepted
+        /// but has no effect yet -- this kernel has no job-control stop
+        /// state for a `waitid` caller to observe.
+        const WUNTRACED = 2;
+        /// Also report children that resumed after being stopped. Same
+        /// caveat as `WUNTRACED`.
+        const WCONTINUED = 8;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags accepted by [`sys_clone`]'s `a1` argument, in the style of
+    /// Linux/Starnix's `clone(2)` flags word.
+    #[derive(Debug, Clone, Copy)]
+    pub struct CloneFlags: usize {
+        /// The new thread joins the caller's existing process, sharing
+        /// `threads`, `parent`, and `file_descriptors` the way every
+        /// `sys_clone` call already behaved before this flag existed.
+        /// Without it, `sys_clone` creates a new process instead.
+        const CLONE_THREAD = 1;
+        /// Share `address_space.page_table_frame` with the caller
+        /// (ignored, implied, when `CLONE_THREAD` is set -- a new thread
+        /// always runs in its process's one address space already).
+        const CLONE_VM = 2;
+        /// Share `file_descriptors` with the caller instead of copying
+        /// the inheritable subset into a fresh table (ignored, implied,
+        /// when `CLONE_THREAD` is set, for the same reason as `CLONE_VM`).
+        const CLONE_FILES = 4;
+        /// Accepted for compatibility with the Linux flags word; this
+        /// kernel has no per-process filesystem-root/cwd state yet for a
+        /// new process to diverge from, so it has no effect either way.
+        const CLONE_FS = 8;
+        /// Seed the new thread's `tls_base` from the syscall's `a4`
+        /// argument instead of leaving it at `0`.
+        const CLONE_SETTLS = 16;
+    }
+}
+
+/// `idtype_t` selector for [`sys_waitid`], in the style of
+/// `rustix::process::WaitId`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdType {
+    /// Wait for the specific child whose pid is the `id` argument.
+    Pid,
+    /// Wait for any child in the process group `id`. This kernel doesn't
+    /// track process groups, so `sys_waitid` rejects this with `EINVAL`.
+    Pgid,
+    /// Wait for any child of the calling process.
+    All,
+    /// Wait for the child referenced by the pidfd `id` -- the caller's
+    /// own fd, not a raw pid -- resolved through [`lookup_pidfd`] the
+    /// same way [`read_pidfd`] resolves one for `sys_read`.
+    Pidfd,
+}
+
+impl IdType {
+    fn from_usize(value: usize) -> Option<Self> {
+        match value {
+            0 => Some(IdType::All),
+            1 => Some(IdType::Pid),
+            2 => Some(IdType::Pgid),
+            3 => Some(IdType::Pidfd),
+            _ => None,
+        }
+    }
+}
+
+/// Why a child terminated, as recorded by [`finalize_thread`] and encoded
+/// into a POSIX wait status by [`encode_wait_status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExitReason {
+    /// Ran to completion (or called `exit`); the low byte of `exit_code`
+    /// is the program's own exit status.
+    Exited,
+    /// Killed by a signal; carries the terminating signal number. No
+    /// syscall in this kernel can actually deliver one yet, but
+    /// `finalize_thread` takes the reason so that future signal handling
+    /// has somewhere to record it.
+    Killed(u8),
+}
+
+/// Encode `(exit_code, reason)` into a POSIX wait status word: low 7 bits
+/// zero and the exit code in bits 8:15 for a normal exit, or the
+/// terminating signal number in the low 7 bits otherwise -- the same
+/// encoding `WIFEXITED`/`WEXITSTATUS`/`WIFSIGNALED`/`WTERMSIG` decode.
+fn encode_wait_status(exit_code: usize, reason: ExitReason) -> usize {
+    match reason {
+        ExitReason::Exited => (exit_code & 0xff) << 8,
+        ExitReason::Killed(signal) => (signal as usize) & 0x7f,
+    }
+}
+
+/// `request` selector for [`sys_ptrace`], in the style of [`IdType`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PtraceRequest {
+    /// Mark the calling thread as traced by its parent's first thread.
+    TraceMe,
+    /// Mark an arbitrary thread as traced by the caller.
+    Attach,
+    /// Let a `TraceStopped` thread run until its next syscall boundary.
+    Cont,
+    /// Like `Cont`, but also arms the architectural single-step trap.
+    SingleStep,
+    /// Copy the target's `Context` into a buffer in the caller's address
+    /// space.
+    GetRegs,
+    /// Overwrite the target's `Context` from a buffer in the caller's
+    /// address space.
+    SetRegs,
+    /// Read one word from the target process's address space.
+    PeekData,
+    /// Write one word into the target process's address space.
+    PokeData,
+}
+
+impl PtraceRequest {
+    fn from_usize(value: usize) -> Option<Self> {
+        match value {
+            0 => Some(PtraceRequest::TraceMe),
+            1 => Some(PtraceRequest::Attach),
+            2 => Some(PtraceRequest::Cont),
+            3 => Some(PtraceRequest::SingleStep),
+            4 => Some(PtraceRequest::GetRegs),
+            5 => Some(PtraceRequest::SetRegs),
+            6 => Some(PtraceRequest::PeekData),
+            7 => Some(PtraceRequest::PokeData),
+            _ => None,
+        }
+    }
+}
+
 struct ZombieChild {
     parent: ProcessId,
     child: ProcessId,
-    status: usize,
+    exit_code: usize,
+    reason: ExitReason,
 }
 
 struct Waiter {
@@ -400,10 +720,48 @@ struct Waiter {
     target: Option<ProcessId>,
 }
 
+/// A traced thread's stop at a syscall boundary, reported to its tracer's
+/// [`sys_wait`]/[`sys_waitid`] the same way a [`ZombieChild`] is --
+/// distinct from one only in that the traced thread is still alive and
+/// parked in `ThreadState::TraceStopped` rather than gone.
+struct TraceEvent {
+    parent: ProcessId,
+    traced: ThreadId,
+    traced_process: ProcessId,
+    /// `None` for an ordinary syscall-boundary stop; `Some((exit_code,
+    /// reason))` when [`finalize_thread`] recorded this event for a
+    /// traced thread's actual exit rather than a stop -- the tracer's
+    /// `sys_wait`/`sys_waitid` needs the real exit status in that case,
+    /// not the `SIGTRAP`-stopped placeholder [`write_trace_status`]
+    /// writes.
+    exited: Option<(usize, ExitReason)>,
+}
+
+/// A pending [`sys_nanosleep`] wakeup, keyed by `tid` the way [`Waiter`]
+/// is keyed by `parent` -- there's at most one outstanding sleep per
+/// thread, so `tid` alone is enough to find it again on a retry.
+struct SleepEntry {
+    wake_tick: u64,
+    tid: ThreadId,
+}
+
 static USER_STDOUT: Mutex<Vec<u8>> = Mutex::new(Vec::new());
 static USER_STDIN: Mutex<VecDeque<u8>> = Mutex::new(VecDeque::new());
 static ZOMBIE_CHILDREN: Mutex<Vec<ZombieChild>> = Mutex::new(Vec::new());
 static WAITERS: Mutex<Vec<Waiter>> = Mutex::new(Vec::new());
+static TRACE_EVENTS: Mutex<Vec<TraceEvent>> = Mutex::new(Vec::new());
+/// Threads blocked in [`sys_nanosleep`], kept sorted by `wake_tick`
+/// ascending (mirrors `lib_core::time::PENDING`) so [`timer_tick`] only
+/// has to drain a prefix instead of scanning the whole queue.
+static SLEEP_QUEUE: Mutex<Vec<SleepEntry>> = Mutex::new(Vec::new());
+/// Threads [`timer_tick`] has woken because their deadline passed, still
+/// waiting for their own `sys_nanosleep` retry to collect the result --
+/// mirrors [`ZOMBIE_CHILDREN`]'s role for `sys_wait`.
+static SLEEP_DONE: Mutex<Vec<ThreadId>> = Mutex::new(Vec::new());
+/// Threads woken out of [`sys_nanosleep`] early (e.g. a future signal
+/// delivery), paired with the unslept remainder in nanoseconds so the
+/// retry can report it back through `args.a2` with `EINTR`.
+static SLEEP_EARLY_WAKE: Mutex<Vec<(ThreadId, u64)>> = Mutex::new(Vec::new());
 
 fn syscall_descriptor(syscall_number: usize) -> Option<&'static SyscallDescriptor> {
     SYSCALL_TABLE.get(syscall_number)
@@ -436,9 +794,57 @@ pub fn handle_syscall(syscall_number: usize, args: SyscallArgs) -> SyscallResult
     let descriptor = syscall_descriptor(syscall_number).ok_or(Errno::ENOSYS)?;
     debug_assert_eq!(descriptor.number, syscall_number);
 
-    let (_, thread) = current_thread_snapshot()?;
+    let (tid, thread) = current_thread_snapshot()?;
+
+    // A traced thread stops at every syscall boundary except `ptrace`
+    // itself (needed to issue `TRACEME`/`CONT` at all) until its tracer
+    // lets it through. `trace_resume` is consumed the first time it's
+    // observed set, so exactly one syscall runs before the next one traps
+    // again -- `CONT`/`SINGLESTEP` flip it back on each time.
+    if thread.traced && syscall_number != SYS_PTRACE {
+        if thread.trace_resume {
+            thread::clear_trace_resume(tid);
+        } else if thread.state != ThreadState::TraceStopped {
+            if let Some(tracer) = thread.tracer {
+                thread::set_thread_state(tid, ThreadState::TraceStopped);
+                record_trace_stop(tracer, tid, thread.process_id);
+            }
+            return Err(Errno::EAGAIN);
+        } else {
+            return Err(Errno::EAGAIN);
+        }
+    }
+
     let process = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
 
+    // A seccomp-filtered process (`sys_seccomp_set`) is judged before any
+    // capability check below ever runs -- an `ERRNO` rule fails the call
+    // without the handler seeing it, and a `KILL` rule ends the thread
+    // outright, the same way a real seccomp-bpf filter preempts normal
+    // permission checking.
+    if process.seccomp_filtered {
+        let action = process
+            .seccomp_rules
+            .iter()
+            .find(|(number, _)| *number == syscall_number)
+            .map(|(_, action)| *action)
+            .unwrap_or(process.seccomp_default);
+
+        match action {
+            process::SeccompAction::Allow => {}
+            process::SeccompAction::Errno(value) => return Err(errno_from_raw(value)),
+            process::SeccompAction::Kill => {
+                finalize_thread(
+                    tid,
+                    thread,
+                    process::SECCOMP_KILL_EXIT_CODE,
+                    ExitReason::Killed(31),
+                );
+                return Err(Errno::EPERM);
+            }
+        }
+    }
+
     if !process.security.is_privileged_enough(descriptor.max_caller_ring) {
         return Err(Errno::EPERM);
     }
@@ -447,6 +853,29 @@ pub fn handle_syscall(syscall_number: usize, args: SyscallArgs) -> SyscallResult
         return Err(Errno::EPERM);
     }
 
+    // Ring0 processes are exempt from metering outright; everyone else
+    // pays the descriptor's cost out of their per-process compute budget,
+    // topped back up by `budget_refill_tick` on a schedule.
+    if process.security.privilege != PrivilegeLevel::Ring0
+        && !process::try_charge_compute(thread.process_id, descriptor.cost as i64)
+    {
+        return Err(Errno::EAGAIN);
+    }
+
+    // Re-check against the PID-keyed security context table so a process
+    // can never exceed what its registered context (and, transitively,
+    // its bounding set) allows, independent of the `Process::security`
+    // copy above.
+    if crate::security::get_context(thread.process_id.0).is_some() {
+        if let Err(errno) = crate::security::check_syscall(thread.process_id.0, syscall_number) {
+            return Err(if errno == -crate::security::EPERM {
+                Errno::EPERM
+            } else {
+                Errno::ESRCH
+            });
+        }
+    }
+
     (descriptor.handler)(args)
 }
 
@@ -468,7 +897,7 @@ pub extern "C" fn syscall_handler(
 
 fn sys_exit(args: SyscallArgs) -> SyscallResult {
     let (tid, thread) = current_thread_snapshot()?;
-    finalize_thread(tid, thread, args.a1);
+    finalize_thread(tid, thread, args.a1, ExitReason::Exited);
     Ok(args.a1)
 }
 
@@ -476,15 +905,30 @@ fn sys_fork(_: SyscallArgs) -> SyscallResult {
     let (_, thread) = current_thread_snapshot()?;
     let parent_process = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
 
-    let new_page_frame = allocate_frame().ok_or(Errno::ENOMEM)?;
+    // Admission control: a parent pinned at its `RLIMIT_NPROC` gets
+    // `EAGAIN` rather than silently growing `children` past the cap.
+    let nproc = parent_process.rlimit(process::Resource::NProc);
+    if parent_process.children.len() as u64 >= nproc.soft {
+        return Err(Errno::EAGAIN);
+    }
+
+    // Copy-on-write: shares the parent's present pages with the child
+    // instead of handing it a blank address space (see
+    // `AddressSpace::clone_for_fork`).
+    let new_page_frame = parent_process
+        .address_space
+        .clone_for_fork()
+        .ok_or(Errno::ENOMEM)?
+        .page_table_frame;
     let child_security = parent_process.security.clone();
-    let child_fds = parent_process.file_descriptors.inherit();
+    let child_fds = parent_process.file_descriptors.lock().inherit();
 
     let child_pid = create_process(
         parent_process.name.clone(),
         new_page_frame,
         child_security,
         child_fds,
+        parent_process.proc_type,
     )
     .ok_or(Errno::ENOMEM)?;
 
@@ -493,6 +937,13 @@ fn sys_fork(_: SyscallArgs) -> SyscallResult {
         if let Some(child) = table.get_process_mut(child_pid) {
             child.parent = Some(parent_process.id);
             child.image = parent_process.image.clone();
+            // A filter applies to the whole sandboxed subtree, not just
+            // the process that installed it -- inherit it verbatim so a
+            // filtered parent can't hand a fork an escape hatch.
+            child.seccomp_filtered = parent_process.seccomp_filtered;
+            child.seccomp_default = parent_process.seccomp_default;
+            child.seccomp_rules = parent_process.seccomp_rules.clone();
+            child.rlimits = parent_process.rlimits.clone();
         }
         if let Some(parent) = table.get_process_mut(parent_process.id) {
             parent.children.push(child_pid);
@@ -516,14 +967,15 @@ fn sys_fork(_: SyscallArgs) -> SyscallResult {
 fn sys_exec(args: SyscallArgs) -> SyscallResult {
     let (tid, thread) = current_thread_snapshot()?;
     let arch = current_arch();
+    let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
 
     let path = if args.a1 == 0 {
         String::from("/sbin/init")
     } else {
-        read_user_cstring(args.a1)?
+        user_access::read_user_cstring(&caller, args.a1)?
     };
 
-    let mut argv = read_user_string_array(args.a2)?;
+    let mut argv = user_access::read_user_string_array(&caller, args.a2)?;
     if argv.is_empty() {
         argv.push(path.clone());
     }
@@ -534,12 +986,20 @@ fn sys_exec(args: SyscallArgs) -> SyscallResult {
     let loaded = {
         let mut table = process::PROCESS_TABLE.lock();
         let process = table.get_process_mut(thread.process_id).ok_or(Errno::ESRCH)?;
-        let loaded = elf_loader::load_executable(image, arch, &process.address_space, &argv_refs, &[])
-            .map_err(|_| Errno::EINVAL)?;
+        let loaded = elf_loader::load_executable(
+            image,
+            arch,
+            &process.address_space,
+            &argv_refs,
+            &[],
+            &elf_loader::AuxConfig::default(),
+            false,
+        )
+        .map_err(|_| Errno::EINVAL)?;
         let process = table
             .get_process_mut(thread.process_id)
             .ok_or(SyscallError::ProcessNotFound)?;
-        let loaded = loader::exec_into_process(process, image, arch, &argv_refs, &[])
+        let loaded = loader::exec_into_process(process, image, arch, &argv_refs, &[], false)
             .map_err(|_| SyscallError::InvalidArgument)?;
         process.name = path;
         loaded
@@ -556,20 +1016,185 @@ fn sys_exec(args: SyscallArgs) -> SyscallResult {
 
 fn sys_wait(args: SyscallArgs) -> SyscallResult {
     let (tid, thread) = current_thread_snapshot()?;
+    let options = WaitOptions::from_bits_truncate(args.a2);
+
     if let Some(zombie) = take_zombie(thread.process_id, args.a1) {
+        write_wait_status(args.a3, &zombie);
         return Ok(zombie.child.0);
     }
 
+    if let Some(event) = take_trace_event(thread.process_id, args.a1) {
+        match event.exited {
+            Some((exit_code, reason)) => write_trace_exit_status(args.a3, exit_code, reason),
+            None => write_trace_status(args.a3),
+        }
+        return Ok(event.traced_process.0);
+    }
+
+    if options.contains(WaitOptions::WNOHANG) {
+        return Ok(0);
+    }
+
     register_waiter(thread.process_id, tid, args.a1);
     scheduler::block_current_thread();
     Ok(0)
 }
 
+/// `si_code` classification for [`write_waitid_status`], in the style of
+/// POSIX `CLD_EXITED`/`CLD_KILLED`/`CLD_STOPPED`/`CLD_CONTINUED` -- lets a
+/// `waitid` caller tell *why* a candidate became reportable instead of
+/// decoding that back out of a packed wait-status word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WaitidCode {
+    Exited = 1,
+    Killed = 2,
+    /// Never produced today -- this kernel has no job-control stop state
+    /// for a `WUNTRACED` waitid to observe, the same gap
+    /// `WaitOptions::WUNTRACED`'s doc comment already calls out.
+    Stopped = 3,
+    /// Never produced today, for the same reason as `Stopped`.
+    Continued = 4,
+}
+
+/// Write a `waitid`-style decoded status to `status_ptr` as `{pid: u64,
+/// si_code: u64, si_status: u64}` -- `si_status` is the exit code for
+/// `Exited`, or the terminating signal number for `Killed`. A null
+/// `status_ptr` means the caller doesn't want one, same as
+/// [`write_wait_status`]; validated through [`user_access`] rather than a
+/// raw pointer write.
+fn write_waitid_status(
+    process: &process::Process,
+    status_ptr: usize,
+    pid: ProcessId,
+    code: WaitidCode,
+    si_status: usize,
+) {
+    if status_ptr == 0 {
+        return;
+    }
+    if let Ok(dst) = user_access::translate_user_slice_mut(process, status_ptr, 24) {
+        dst[0..8].copy_from_slice(&(pid.0 as u64).to_ne_bytes());
+        dst[8..16].copy_from_slice(&(code as u64).to_ne_bytes());
+        dst[16..24].copy_from_slice(&(si_status as u64).to_ne_bytes());
+    }
+}
+
+/// POSIX `waitid`: like [`sys_wait`], but selects candidates by
+/// [`IdType`] rather than always taking a raw pid, and reports a decoded
+/// `{pid, si_code, si_status}` status via [`write_waitid_status`] instead
+/// of a packed wait-status word.
+fn sys_waitid(args: SyscallArgs) -> SyscallResult {
+    let (tid, thread) = current_thread_snapshot()?;
+    let idtype = IdType::from_usize(args.a1).ok_or(Errno::EINVAL)?;
+    let options = WaitOptions::from_bits_truncate(args.a3);
+
+    let pid = match idtype {
+        IdType::All => 0,
+        IdType::Pid => args.a2,
+        // No process-group tracking exists in this kernel to resolve a
+        // pgid against.
+        IdType::Pgid => return Err(Errno::EINVAL),
+        // `take_zombie`/`take_trace_event` key purely by raw pid, same as
+        // `IdType::Pid` -- the pidfd's `generation` only matters for
+        // `sys_pidfd_send_signal`, since a zombie entry is always the
+        // same generation the pidfd was opened against (this allocator
+        // never reuses a pid while a zombie for it is still queued).
+        IdType::Pidfd => {
+            let (pid, _generation) =
+                lookup_pidfd(thread.process_id, args.a2 as u32).ok_or(Errno::EINVAL)?;
+            pid.0
+        }
+    };
+
+    if let Some(zombie) = take_zombie(thread.process_id, pid) {
+        let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+        let (code, si_status) = match zombie.reason {
+            ExitReason::Exited => (WaitidCode::Exited, zombie.exit_code & 0xff),
+            ExitReason::Killed(signal) => (WaitidCode::Killed, signal as usize),
+        };
+        write_waitid_status(&caller, args.a4, zombie.child, code, si_status);
+        return Ok(zombie.child.0);
+    }
+
+    if let Some(event) = take_trace_event(thread.process_id, pid) {
+        match event.exited {
+            Some((exit_code, reason)) => {
+                let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+                let (code, si_status) = match reason {
+                    ExitReason::Exited => (WaitidCode::Exited, exit_code & 0xff),
+                    ExitReason::Killed(signal) => (WaitidCode::Killed, signal as usize),
+                };
+                write_waitid_status(&caller, args.a4, event.traced_process, code, si_status);
+            }
+            None => write_trace_status(args.a4),
+        }
+        return Ok(event.traced_process.0);
+    }
+
+    if options.contains(WaitOptions::WNOHANG) {
+        return Ok(0);
+    }
+
+    register_waiter(thread.process_id, tid, pid);
+    scheduler::block_current_thread();
+    Ok(0)
+}
+
+/// Write `zombie`'s encoded wait status to the caller's `status_ptr`, if
+/// one was given (a null pointer means the caller doesn't want it).
+fn write_wait_status(status_ptr: usize, zombie: &ZombieChild) {
+    if status_ptr == 0 {
+        return;
+    }
+    let status = encode_wait_status(zombie.exit_code, zombie.reason);
+    unsafe {
+        (status_ptr as *mut usize).write(status);
+    }
+}
+
+/// Encode a `ptrace` trace-stop as the POSIX wait status a `WIFSTOPPED`
+/// caller expects: low byte `0x7f`, with the stopping signal (`SIGTRAP`,
+/// 5) in bits 8:15.
+fn encode_trace_status() -> usize {
+    0x7f | (5usize << 8)
+}
+
+/// Write a trace-stop's encoded wait status to `status_ptr`, if given.
+fn write_trace_status(status_ptr: usize) {
+    if status_ptr == 0 {
+        return;
+    }
+    unsafe {
+        (status_ptr as *mut usize).write(encode_trace_status());
+    }
+}
+
+/// Like [`write_wait_status`], but for a [`TraceEvent`] whose `exited`
+/// field is set -- the tracer gets the traced thread's real wait status,
+/// not [`write_trace_status`]'s `SIGTRAP`-stopped placeholder.
+fn write_trace_exit_status(status_ptr: usize, exit_code: usize, reason: ExitReason) {
+    if status_ptr == 0 {
+        return;
+    }
+    let status = encode_wait_status(exit_code, reason);
+    unsafe {
+        (status_ptr as *mut usize).write(status);
+    }
+}
+
 fn sys_getpid(_: SyscallArgs) -> SyscallResult {
     let (_, thread) = current_thread_snapshot()?;
     Ok(thread.process_id.0)
 }
 
+/// Report the caller's remaining syscall compute budget (see
+/// [`handle_syscall`]'s metering step and `process::remaining_compute`).
+fn sys_get_compute_budget(_: SyscallArgs) -> SyscallResult {
+    let (_, thread) = current_thread_snapshot()?;
+    let remaining = process::remaining_compute(thread.process_id).ok_or(Errno::ESRCH)?;
+    Ok(remaining.max(0) as usize)
+}
+
 fn sys_mmap(args: SyscallArgs) -> SyscallResult {
     let addr = args.a1;
     let length = args.a2;
@@ -599,31 +1224,123 @@ fn sys_brk(args: SyscallArgs) -> SyscallResult {
     Ok(args.a1)
 }
 
+/// Seed `tid`'s `tls_base` for `CLONE_SETTLS`, if that thread still
+/// exists by the time the caller gets around to it.
+fn apply_clone_settls(tid: ThreadId, tls_base: u64) {
+    if let Some(new_thread) = THREAD_TABLE.lock().get_thread_mut(tid) {
+        new_thread.tls_base = tls_base;
+    }
+}
+
+/// `clone(flags, stack, _parent_tid_ptr, tls)`: with `CLONE_THREAD` the new
+/// thread joins the caller's existing process (the only behavior this
+/// syscall had before this flags word existed); without it, a new process
+/// is created the same way [`sys_fork`] does, except `CLONE_VM`/
+/// `CLONE_FILES` share the caller's page-table frame / fd table instead of
+/// copying them.
 fn sys_clone(args: SyscallArgs) -> SyscallResult {
     let (_, thread) = current_thread_snapshot()?;
+    let flags = CloneFlags::from_bits_truncate(args.a1);
+    let tls_base = args.a4 as u64;
+
+    if flags.contains(CloneFlags::CLONE_THREAD) {
+        let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+        let nthread = caller.rlimit(process::Resource::NThread);
+        if caller.threads.len() as u64 >= nthread.soft {
+            return Err(Errno::EAGAIN);
+        }
+
+        let stack = args.a2;
+        let new_stack = if stack == 0 {
+            thread.kernel_stack
+        } else {
+            VirtAddr::new(stack as u64)
+        };
+
+        let new_tid = create_thread(
+            thread.process_id,
+            thread.priority,
+            VirtAddr::new(thread.context.rip),
+            new_stack,
+            thread.user_stack,
+        )
+        .ok_or(Errno::ENOMEM)?;
 
-    let stack = args.a2;
-    let new_stack = if stack == 0 {
-        thread.kernel_stack
+        if flags.contains(CloneFlags::CLONE_SETTLS) {
+            apply_clone_settls(new_tid, tls_base);
+        }
+
+        scheduler::add_thread(new_tid);
+        return Ok(new_tid.0);
+    }
+
+    let parent_process = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+
+    let nproc = parent_process.rlimit(process::Resource::NProc);
+    if parent_process.children.len() as u64 >= nproc.soft {
+        return Err(Errno::EAGAIN);
+    }
+
+    // `page_table_frame` is just a descriptor for where the page tables
+    // physically live -- reusing the parent's `Frame` value verbatim makes
+    // both processes' address translation resolve to the same physical
+    // memory with no `Arc`/`Mutex` wrapper needed, the way `CLONE_VM`
+    // sharing works in Starnix.
+    let new_page_frame = if flags.contains(CloneFlags::CLONE_VM) {
+        parent_process.address_space.page_table_frame
     } else {
-        VirtAddr::new(stack as u64)
+        allocate_frame().ok_or(Errno::ENOMEM)?
     };
+    let child_security = parent_process.security.clone();
+    let child_fds = parent_process.file_descriptors.lock().inherit();
+
+    let child_pid = create_process(
+        parent_process.name.clone(),
+        new_page_frame,
+        child_security,
+        child_fds,
+        parent_process.proc_type,
+    )
+    .ok_or(Errno::ENOMEM)?;
+
+    {
+        let mut table = process::PROCESS_TABLE.lock();
+        if let Some(child) = table.get_process_mut(child_pid) {
+            child.parent = Some(parent_process.id);
+            child.image = parent_process.image.clone();
+            child.seccomp_filtered = parent_process.seccomp_filtered;
+            child.seccomp_default = parent_process.seccomp_default;
+            child.seccomp_rules = parent_process.seccomp_rules.clone();
+            child.rlimits = parent_process.rlimits.clone();
+            if flags.contains(CloneFlags::CLONE_FILES) {
+                child.file_descriptors = Arc::clone(&parent_process.file_descriptors);
+            }
+        }
+        if let Some(parent) = table.get_process_mut(parent_process.id) {
+            parent.children.push(child_pid);
+        }
+    }
 
-    let new_tid = create_thread(
-        thread.process_id,
+    let child_stack = allocate_frame().ok_or(Errno::ENOMEM)?;
+    let child_tid = create_thread(
+        child_pid,
         thread.priority,
         VirtAddr::new(thread.context.rip),
-        new_stack,
+        VirtAddr::new(child_stack.start_address.0),
         thread.user_stack,
     )
     .ok_or(Errno::ENOMEM)?;
 
-    scheduler::add_thread(new_tid);
-    Ok(new_tid.0)
+    if flags.contains(CloneFlags::CLONE_SETTLS) {
+        apply_clone_settls(child_tid, tls_base);
+    }
+
+    scheduler::add_thread(child_tid);
+    Ok(child_pid.0)
 }
 
 fn sys_write(args: SyscallArgs) -> SyscallResult {
-    let fd = args.a1;
+    let fd = args.a1 as u32;
     let buf = args.a2;
     let len = args.a3;
 
@@ -631,44 +1348,74 @@ fn sys_write(args: SyscallArgs) -> SyscallResult {
         return Ok(0);
     }
 
-    let data = unsafe { slice::from_raw_parts(buf as *const u8, len) };
+    let (tid, thread) = current_thread_snapshot()?;
+    let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+    let data = user_access::translate_user_slice(&caller, buf, len, user_access::AccessType::Load)?;
+
     match fd {
         1 | 2 => {
+            if lookup_serial_fd(thread.process_id, fd).is_some() {
+                let mut serial = crate::drivers::serial::SERIAL1.lock();
+                for &byte in data {
+                    serial.send(byte);
+                }
+            }
             USER_STDOUT.lock().extend_from_slice(data);
             Ok(len)
         }
         0 => Err(Errno::EPERM),
-        _ => Err(Errno::EINVAL),
+        _ => write_scheme_fd(thread.process_id, tid, fd, data),
     }
 }
 
 fn sys_read(args: SyscallArgs) -> SyscallResult {
-    let fd = args.a1;
+    let fd = args.a1 as u32;
     let buf = args.a2;
     let len = args.a3;
 
-    if fd != 0 {
-        return Err(Errno::EINVAL);
-    }
-
     if len == 0 {
         return Ok(0);
     }
 
-    let mut stdin = USER_STDIN.lock();
-    let mut read = 0;
-    while read < len {
-        match stdin.pop_front() {
-            Some(byte) => {
-                unsafe {
-                    ((buf as *mut u8).add(read)).write(byte);
+    let (tid, thread) = current_thread_snapshot()?;
+    let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+    let data = user_access::translate_user_slice_mut(&caller, buf, len)?;
+
+    if fd == 0 {
+        if lookup_serial_fd(thread.process_id, fd).is_some() {
+            let mut serial = crate::drivers::serial::SERIAL1.lock();
+            let mut read = 0;
+            while read < len {
+                match serial.try_receive() {
+                    Some(byte) => {
+                        data[read] = byte;
+                        read += 1;
+                    }
+                    None => break,
+                }
+            }
+            return Ok(read);
+        }
+
+        let mut stdin = USER_STDIN.lock();
+        let mut read = 0;
+        while read < len {
+            match stdin.pop_front() {
+                Some(byte) => {
+                    data[read] = byte;
+                    read += 1;
                 }
-                read += 1;
+                None => break,
             }
-            None => break,
         }
+        return Ok(read);
+    }
+
+    if let Some((pid, generation)) = lookup_pidfd(thread.process_id, fd) {
+        return read_pidfd(tid, pid, generation, data);
     }
-    Ok(read)
+
+    read_scheme_fd(thread.process_id, tid, fd, data)
 }
 
 fn sys_yield(_: SyscallArgs) -> SyscallResult {
@@ -676,76 +1423,631 @@ fn sys_yield(_: SyscallArgs) -> SyscallResult {
     Ok(0)
 }
 
-fn sys_open(_path_ptr: usize, _flags: usize, _mode: usize) -> SyscallResult {
-    Err(SyscallError::InvalidSyscall)
+/// Write `remaining_ns` back to the caller's `{secs, nsecs}` output
+/// struct at `rem_ptr`, if one was given (null means the caller doesn't
+/// want it) -- same null-means-skip convention as [`write_wait_status`].
+fn write_nanosleep_remaining(process: &process::Process, rem_ptr: usize, remaining_ns: u64) {
+    if rem_ptr == 0 {
+        return;
+    }
+    if let Ok(dst) = user_access::translate_user_slice_mut(process, rem_ptr, 16) {
+        dst[0..8].copy_from_slice(&(remaining_ns / 1_000_000_000).to_ne_bytes());
+        dst[8..16].copy_from_slice(&(remaining_ns % 1_000_000_000).to_ne_bytes());
+    }
 }
 
-fn sys_close(_fd: usize) -> SyscallResult {
-    Err(SyscallError::InvalidSyscall)
+/// `nanosleep`: blocks the calling thread until `clock::clock().now_ns()`
+/// reaches the deadline encoded by the `{secs, nsecs}` struct at
+/// `args.a1`, via the same poll-then-block-then-retry shape `sys_open`
+/// uses against a scheme reply -- a first call computes the deadline and
+/// enqueues it in [`SLEEP_QUEUE`], a retry checks whether [`timer_tick`]
+/// (or, in the future, a signal handler via [`wake_sleep_early`]) has
+/// since resolved it.
+fn sys_nanosleep(args: SyscallArgs) -> SyscallResult {
+    let (tid, thread) = current_thread_snapshot()?;
+    let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+
+    if let Some(remaining_ns) = take_sleep_early_wake(tid) {
+        write_nanosleep_remaining(&caller, args.a2, remaining_ns);
+        return Err(Errno::EINTR);
+    }
+
+    if take_sleep_done(tid) {
+        write_nanosleep_remaining(&caller, args.a2, 0);
+        return Ok(0);
+    }
+
+    if sleep_queue_has(tid) {
+        scheduler::block_current_thread();
+        return Err(Errno::EAGAIN);
+    }
+
+    let req =
+        user_access::translate_user_slice(&caller, args.a1, 16, user_access::AccessType::Load)?;
+    let secs = u64::from_ne_bytes(req[0..8].try_into().map_err(|_| Errno::EINVAL)?);
+    let nsecs = u64::from_ne_bytes(req[8..16].try_into().map_err(|_| Errno::EINVAL)?);
+    let duration_ns = secs.saturating_mul(1_000_000_000).saturating_add(nsecs);
+
+    let wake_tick = clock::clock().now_ns().saturating_add(duration_ns);
+    enqueue_sleep(tid, wake_tick);
+    scheduler::block_current_thread();
+    thread::set_thread_state(tid, ThreadState::Sleeping);
+    Err(Errno::EAGAIN)
 }
 
-fn sys_pipe(_pipefd_ptr: usize) -> SyscallResult {
-    Err(SyscallError::InvalidSyscall)
+/// The fd a [`scheme::SchemeOp::Read`]/[`scheme::SchemeOp::Write`]/
+/// [`scheme::SchemeOp::Close`] request is outstanding against, keyed the
+/// same way [`OPEN_REQUESTS`] keys `open` retries: a retry of the same
+/// call on the same fd finds the packet id its first attempt submitted,
+/// rather than submitting a second request.
+static FD_REQUESTS: Mutex<Vec<(ProcessId, u32, scheme::SchemeOp, u64)>> = Mutex::new(Vec::new());
+
+/// A caller's in-flight `open("<scheme>:<rest>")`, keyed by `(caller,
+/// path)` since the caller has no packet id to key on until its first
+/// attempt submits one -- mirrors `syscall::WAITERS`'s role for `wait`.
+static OPEN_REQUESTS: Mutex<Vec<(ProcessId, String, u64)>> = Mutex::new(Vec::new());
+
+/// Tunables exposed through `sys_sysfs_read`/`sys_sysfs_write`. Serviced
+/// entirely in-kernel -- no provider is registered for the `"sys"`
+/// namespace, so these never touch the scheme packet machinery above.
+static SYSFS_TUNABLES: Mutex<Vec<(String, Vec<u8>)>> = Mutex::new(Vec::new());
+
+fn register_open_request(caller: ProcessId, path: String, id: u64) {
+    let mut requests = OPEN_REQUESTS.lock();
+    requests.retain(|(pid, p, _)| !(*pid == caller && *p == path));
+    requests.push((caller, path, id));
 }
 
-fn sys_signal(_signum: usize, _handler: usize) -> SyscallResult {
-    Err(SyscallError::InvalidSyscall)
+fn take_open_request(caller: ProcessId, path: &str) -> Option<u64> {
+    let mut requests = OPEN_REQUESTS.lock();
+    let index = requests
+        .iter()
+        .position(|(pid, p, _)| *pid == caller && p == path)?;
+    Some(requests.remove(index).2)
 }
 
-pub fn feed_stdin(bytes: &[u8]) {
-    USER_STDIN.lock().extend(bytes);
+fn register_fd_request(caller: ProcessId, fd: u32, op: scheme::SchemeOp, id: u64) {
+    let mut requests = FD_REQUESTS.lock();
+    requests.retain(|(pid, f, o, _)| !(*pid == caller && *f == fd && *o == op));
+    requests.push((caller, fd, op, id));
 }
 
-pub fn take_stdout() -> Vec<u8> {
-    let mut buffer = USER_STDOUT.lock();
-    let mut drained = Vec::new();
-    core::mem::swap(&mut *buffer, &mut drained);
-    drained
+fn take_fd_request(caller: ProcessId, fd: u32, op: scheme::SchemeOp) -> Option<u64> {
+    let mut requests = FD_REQUESTS.lock();
+    let index = requests
+        .iter()
+        .position(|(pid, f, o, _)| *pid == caller && *f == fd && *o == op)?;
+    Some(requests.remove(index).3)
 }
 
-fn read_user_cstring(ptr: usize) -> Result<String, Errno> {
-    if ptr == 0 {
-        return Err(Errno::EINVAL);
+/// The provider and provider-side fd `fd` is bound to, or `EINVAL` if
+/// `fd` doesn't exist or isn't a scheme connection.
+fn lookup_scheme_fd(caller: ProcessId, fd: u32) -> Result<(ProcessId, u32), Errno> {
+    let table = process::PROCESS_TABLE.lock();
+    let process = table.get_process(caller).ok_or(Errno::ESRCH)?;
+    match process
+        .file_descriptors
+        .lock()
+        .get(fd)
+        .map(|entry| &entry.object)
+    {
+        Some(FdObject::Scheme {
+            provider,
+            provider_fd,
+        }) => Ok((*provider, *provider_fd)),
+        _ => Err(Errno::EINVAL),
     }
+}
 
-    let mut bytes = Vec::new();
-    let mut offset = 0usize;
-    loop {
-        let byte = unsafe { ((ptr as *const u8).add(offset)).read() };
-        if byte == 0 {
-            break;
-        }
-        bytes.push(byte);
-        offset += 1;
-        if offset > 4096 {
-            return Err(Errno::EINVAL);
-        }
+/// The COM port a caller's `fd` is backed by, or `None` if `fd` doesn't
+/// exist or isn't `FdObject::Serial` -- the default table's fds 0/1/2.
+fn lookup_serial_fd(caller: ProcessId, fd: u32) -> Option<u16> {
+    let table = process::PROCESS_TABLE.lock();
+    let process = table.get_process(caller)?;
+    match process
+        .file_descriptors
+        .lock()
+        .get(fd)
+        .map(|entry| &entry.object)
+    {
+        Some(FdObject::Serial(port)) => Some(*port),
+        _ => None,
+    }
+}
+
+/// The `(pid, generation)` a caller's `fd` was opened against via
+/// `sys_pidfd_open`, or `None` if `fd` doesn't exist or isn't a pidfd.
+fn lookup_pidfd(caller: ProcessId, fd: u32) -> Option<(ProcessId, u64)> {
+    let table = process::PROCESS_TABLE.lock();
+    let process = table.get_process(caller)?;
+    match process
+        .file_descriptors
+        .lock()
+        .get(fd)
+        .map(|entry| &entry.object)
+    {
+        Some(FdObject::Pidfd { pid, generation }) => Some((*pid, *generation)),
+        _ => None,
+    }
+}
+
+/// `read` on a pidfd: readable -- a single `0` byte, `Ok(1)` -- once its
+/// target is gone, detected as `pid` no longer existing or having moved
+/// on to a different `generation` than this pidfd was opened against.
+/// Otherwise registers `tid` with [`PIDFD_WAITERS`] and blocks, the same
+/// poll-then-block-then-retry shape `sys_open` uses against a scheme
+/// reply; [`finalize_thread`] wakes it once the target's last thread
+/// exits.
+fn read_pidfd(tid: ThreadId, pid: ProcessId, generation: u64, dst: &mut [u8]) -> SyscallResult {
+    match process::get_process(pid) {
+        Some(target) if target.generation == generation => {
+            register_pidfd_waiter(pid, generation, tid);
+            scheduler::block_current_thread();
+            Err(Errno::EAGAIN)
+        }
+        _ => {
+            dst[0] = 0;
+            Ok(1)
+        }
+    }
+}
+
+/// `open("<scheme>:<rest>")`: resolves `scheme` to its registered
+/// provider, enqueues an open packet, and blocks the caller until the
+/// provider answers -- the same poll-then-block-then-retry shape
+/// `sys_wait` uses for zombies, just keyed by `(caller, path)` instead of
+/// `(parent, child pid)`. A retry that finds its reply ready allocates
+/// the caller's fd and returns it; one that doesn't re-registers and
+/// blocks again.
+fn sys_open(args: SyscallArgs) -> SyscallResult {
+    let (tid, thread) = current_thread_snapshot()?;
+    let caller_pid = thread.process_id;
+    let caller = process::get_process(caller_pid).ok_or(Errno::ESRCH)?;
+    let path = user_access::read_user_cstring(&caller, args.a1)?;
+    let (scheme_name, rest) = scheme::split_scheme(&path).ok_or(Errno::EINVAL)?;
+
+    if let Some(id) = take_open_request(caller_pid, &path) {
+        let (result, _) = match scheme::take_reply(id) {
+            Some(reply) => reply,
+            None => {
+                register_open_request(caller_pid, path, id);
+                scheduler::block_current_thread();
+                return Err(Errno::EAGAIN);
+            }
+        };
+
+        if result < 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        let provider = scheme::provider_for(scheme_name).ok_or(Errno::ESRCH)?;
+        let mut table = process::PROCESS_TABLE.lock();
+        let process = table.get_process_mut(caller_pid).ok_or(Errno::ESRCH)?;
+        let nofile = process.rlimit(process::Resource::NoFile);
+        if process.file_descriptors.lock().len() as u64 >= nofile.soft {
+            return Err(Errno::EAGAIN);
+        }
+        let fd = process.file_descriptors.lock().allocate(
+            0,
+            true,
+            FdObject::Scheme {
+                provider,
+                provider_fd: result as u32,
+            },
+        );
+        return Ok(fd as usize);
+    }
+
+    let id = scheme::submit(
+        scheme_name,
+        scheme::SchemeOp::Open,
+        0,
+        caller_pid,
+        tid,
+        rest.as_bytes().to_vec(),
+    )
+    .ok_or(Errno::ESRCH)?;
+    register_open_request(caller_pid, path, id);
+    scheduler::block_current_thread();
+    Err(Errno::EAGAIN)
+}
+
+/// `close` on a scheme fd enqueues a close packet (fire-and-forget --
+/// nothing blocks waiting for the provider to acknowledge it) and drops
+/// the caller's own fd entry immediately.
+fn sys_close(args: SyscallArgs) -> SyscallResult {
+    let fd = args.a1 as u32;
+    let (tid, thread) = current_thread_snapshot()?;
+    let caller_pid = thread.process_id;
+
+    if let Ok((provider, provider_fd)) = lookup_scheme_fd(caller_pid, fd) {
+        scheme::submit_to(
+            provider,
+            scheme::SchemeOp::Close,
+            provider_fd,
+            caller_pid,
+            tid,
+            Vec::new(),
+        );
+    }
+
+    let mut table = process::PROCESS_TABLE.lock();
+    let process = table.get_process_mut(caller_pid).ok_or(Errno::ESRCH)?;
+    if process.file_descriptors.lock().remove(fd) {
+        Ok(0)
+    } else {
+        Err(Errno::EINVAL)
+    }
+}
+
+/// `write` against a scheme fd: same poll-then-block-then-retry shape as
+/// [`sys_open`], keyed by `(caller, fd, Write)` instead of `(caller,
+/// path)`.
+fn write_scheme_fd(caller_pid: ProcessId, tid: ThreadId, fd: u32, data: &[u8]) -> SyscallResult {
+    let (provider, provider_fd) = lookup_scheme_fd(caller_pid, fd)?;
+
+    if let Some(id) = take_fd_request(caller_pid, fd, scheme::SchemeOp::Write) {
+        return match scheme::take_reply(id) {
+            Some((result, _)) if result >= 0 => Ok(result as usize),
+            Some(_) => Err(Errno::EINVAL),
+            None => {
+                register_fd_request(caller_pid, fd, scheme::SchemeOp::Write, id);
+                scheduler::block_current_thread();
+                Err(Errno::EAGAIN)
+            }
+        };
+    }
+
+    let id = scheme::submit_to(
+        provider,
+        scheme::SchemeOp::Write,
+        provider_fd,
+        caller_pid,
+        tid,
+        data.to_vec(),
+    );
+    register_fd_request(caller_pid, fd, scheme::SchemeOp::Write, id);
+    scheduler::block_current_thread();
+    Err(Errno::EAGAIN)
+}
+
+/// `read` against a scheme fd: the requested length is all the provider
+/// gets up front (as the packet's payload), since there's no data to
+/// send the other way until it replies.
+fn read_scheme_fd(caller_pid: ProcessId, tid: ThreadId, fd: u32, dst: &mut [u8]) -> SyscallResult {
+    let (provider, provider_fd) = lookup_scheme_fd(caller_pid, fd)?;
+
+    if let Some(id) = take_fd_request(caller_pid, fd, scheme::SchemeOp::Read) {
+        return match scheme::take_reply(id) {
+            Some((result, data)) if result >= 0 => {
+                let n = core::cmp::min(data.len(), dst.len());
+                dst[..n].copy_from_slice(&data[..n]);
+                Ok(n)
+            }
+            Some(_) => Err(Errno::EINVAL),
+            None => {
+                register_fd_request(caller_pid, fd, scheme::SchemeOp::Read, id);
+                scheduler::block_current_thread();
+                Err(Errno::EAGAIN)
+            }
+        };
+    }
+
+    let id = scheme::submit_to(
+        provider,
+        scheme::SchemeOp::Read,
+        provider_fd,
+        caller_pid,
+        tid,
+        (dst.len() as u64).to_ne_bytes().to_vec(),
+    );
+    register_fd_request(caller_pid, fd, scheme::SchemeOp::Read, id);
+    scheduler::block_current_thread();
+    Err(Errno::EAGAIN)
+}
+
+/// Register the caller as the `"sys"`-equivalent of a scheme provider,
+/// but serviced in-kernel: no packets, no blocking, just a key-value
+/// tunable store `sys_sysfs_write` can set and `sys_sysfs_read` reads
+/// back.
+fn sys_sysfs_read(args: SyscallArgs) -> SyscallResult {
+    let (_, thread) = current_thread_snapshot()?;
+    let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+    let key = user_access::read_user_cstring(&caller, args.a1)?;
+
+    let tunables = SYSFS_TUNABLES.lock();
+    let value = tunables
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, value)| value.clone())
+        .ok_or(Errno::ESRCH)?;
+    drop(tunables);
+
+    let dst = user_access::translate_user_slice_mut(&caller, args.a2, args.a3)?;
+    let n = core::cmp::min(value.len(), dst.len());
+    dst[..n].copy_from_slice(&value[..n]);
+    Ok(n)
+}
+
+fn sys_sysfs_write(args: SyscallArgs) -> SyscallResult {
+    let (_, thread) = current_thread_snapshot()?;
+    let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+    let key = user_access::read_user_cstring(&caller, args.a1)?;
+    let data = user_access::translate_user_slice(
+        &caller,
+        args.a2,
+        args.a3,
+        user_access::AccessType::Load,
+    )?;
+
+    let mut tunables = SYSFS_TUNABLES.lock();
+    match tunables.iter_mut().find(|(name, _)| *name == key) {
+        Some((_, value)) => *value = data.to_vec(),
+        None => tunables.push((key, data.to_vec())),
+    }
+    Ok(data.len())
+}
+
+/// Register the caller as the provider for a scheme name (e.g.
+/// `"disk"`), so a path like `"disk:/boot/image"` routes to it.
+fn sys_scheme_register(args: SyscallArgs) -> SyscallResult {
+    let (_, thread) = current_thread_snapshot()?;
+    let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+    let name = user_access::read_user_cstring(&caller, args.a1)?;
+    scheme::register(name, thread.process_id);
+    Ok(0)
+}
+
+/// Header `sys_scheme_recv` writes ahead of a packet's payload: `id`
+/// (8 bytes), `opcode` (4 bytes), `fd` (4 bytes), `caller` pid (8
+/// bytes), `payload_len` (4 bytes).
+const SCHEME_PACKET_HEADER_LEN: usize = 28;
+
+fn scheme_op_code(op: scheme::SchemeOp) -> u32 {
+    match op {
+        scheme::SchemeOp::Open => 0,
+        scheme::SchemeOp::Read => 1,
+        scheme::SchemeOp::Write => 2,
+        scheme::SchemeOp::Close => 3,
+    }
+}
+
+/// Pop the next request queued for the caller (as a scheme provider)
+/// into its buffer, header followed by payload. `EAGAIN` if nothing is
+/// queued yet -- providers are expected to poll this, the same as a
+/// caller retries `sys_open`/`sys_read`/`sys_write` against a pending
+/// reply.
+fn sys_scheme_recv(args: SyscallArgs) -> SyscallResult {
+    let (_, thread) = current_thread_snapshot()?;
+    let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+
+    if args.a2 < SCHEME_PACKET_HEADER_LEN {
+        return Err(Errno::EINVAL);
     }
 
-    str::from_utf8(&bytes)
-        .map(|s| s.to_string())
-        .map_err(|_| Errno::EINVAL)
+    let packet = scheme::take_request(thread.process_id).ok_or(Errno::EAGAIN)?;
+    let total = SCHEME_PACKET_HEADER_LEN + packet.payload.len();
+    if total > args.a2 {
+        return Err(Errno::EINVAL);
+    }
+
+    let dst = user_access::translate_user_slice_mut(&caller, args.a1, total)?;
+    dst[0..8].copy_from_slice(&packet.id.to_ne_bytes());
+    dst[8..12].copy_from_slice(&scheme_op_code(packet.opcode).to_ne_bytes());
+    dst[12..16].copy_from_slice(&packet.fd.to_ne_bytes());
+    dst[16..24].copy_from_slice(&(packet.caller.0 as u64).to_ne_bytes());
+    dst[24..28].copy_from_slice(&(packet.payload.len() as u32).to_ne_bytes());
+    dst[28..total].copy_from_slice(&packet.payload);
+    Ok(total)
+}
+
+/// Answer packet `a1` with result `a2` (cast to `isize`; negative means
+/// error) and `a4` bytes of reply data at `a3`, waking whichever thread
+/// is blocked waiting on it.
+fn sys_scheme_reply(args: SyscallArgs) -> SyscallResult {
+    let (_, thread) = current_thread_snapshot()?;
+    let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+    let id = args.a1 as u64;
+    let result = args.a2 as isize;
+    let data = if args.a4 == 0 {
+        Vec::new()
+    } else {
+        user_access::translate_user_slice(&caller, args.a3, args.a4, user_access::AccessType::Load)?
+            .to_vec()
+    };
+
+    match scheme::reply(id, result, data) {
+        Some(waiter) => {
+            scheduler::unblock_thread(waiter);
+            Ok(0)
+        }
+        None => Err(Errno::ESRCH),
+    }
+}
+
+/// Arm the architectural single-step trap in `context`, so the next
+/// instruction it resumes into re-enters `handle_syscall`'s trace-stop
+/// gate immediately. On x86_64 this is the `EFLAGS.TF` bit; no other
+/// target in this kernel wires a register-level trap flag into `Context`
+/// at all (see `process::context::ArmContext`, which nothing actually
+/// switches into), so `SINGLESTEP` degrades to a plain `CONT` there
+/// rather than fabricating a trap that can't fire.
+#[cfg(target_arch = "x86_64")]
+fn set_singlestep(context: &mut Context) {
+    context.rflags |= 0x100;
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn set_singlestep(_context: &mut Context) {}
+
+/// Resolve `addr` in `process`'s own address space to a pointer the
+/// caller may dereference, for `sys_ptrace`'s `PEEKDATA`/`POKEDATA` --
+/// `user_access`'s helpers only validate the *calling* process's own
+/// mappings, which is the wrong address space when the caller is a
+/// tracer reaching into a traced process. Walks `process`'s page tables
+/// via the same per-arch `PageTableMapper` impl `user_access::leaf_flags`
+/// uses, then reaches the resolved frame through `temporary_map`, the
+/// kernel's direct-map alias.
+///
+/// On aarch64, `ArmLpaePageTable::translate` genuinely walks the given
+/// table frame level by level through `temporary_map`, so this resolves a
+/// non-running process's mapping correctly. On x86_64, `X86_64PageTable`
+/// walks through the recursive P4 slot, which only ever reaches whichever
+/// table CR3 currently points at -- it has no foreign-table walk of its
+/// own, so this only returns the right answer there when `process`
+/// happens to be the one currently active. Tracer and tracee are never
+/// the same thread, so on x86_64 this is honest best-effort rather than a
+/// correct cross-process read; fixing it needs an x86_64 table walker
+/// that takes an explicit frame the way the aarch64 one already does.
+fn translate_target_addr(process: &process::Process, addr: usize) -> Result<*mut u8, Errno> {
+    if addr % core::mem::size_of::<usize>() != 0 {
+        return Err(Errno::EINVAL);
+    }
+
+    let virt = VirtAddr::new(addr as u64);
+
+    #[cfg(target_arch = "x86_64")]
+    let phys = X86_64PageTable::new(process.address_space.page_table_frame).translate(virt);
+
+    #[cfg(target_arch = "aarch64")]
+    let phys = ArmLpaePageTable::new(process.address_space.page_table_frame).translate(virt);
+
+    let phys = phys.ok_or(Errno::EINVAL)?;
+    Ok(temporary_map(phys).as_u64() as *mut u8)
 }
 
-fn read_user_string_array(ptr: usize) -> Result<Vec<String>, Errno> {
-    if ptr == 0 {
-        return Ok(Vec::new());
+/// `ptrace`: an in-kernel debugger's control channel over a traced
+/// thread. `TRACEME`/`ATTACH` establish the tracer relationship
+/// `handle_syscall`'s trace-stop gate enforces; everything else is
+/// restricted to a thread's own `tracer` (`EPERM` otherwise) and only
+/// meaningful once the target has actually stopped (`EINVAL` otherwise).
+fn sys_ptrace(args: SyscallArgs) -> SyscallResult {
+    let (tid, thread) = current_thread_snapshot()?;
+    let request = PtraceRequest::from_usize(args.a1).ok_or(Errno::EINVAL)?;
+
+    if request == PtraceRequest::TraceMe {
+        let parent_pid = process::get_process(thread.process_id)
+            .and_then(|p| p.parent)
+            .ok_or(Errno::ESRCH)?;
+        let parent = process::get_process(parent_pid).ok_or(Errno::ESRCH)?;
+        // The thread that ends up enforced as this process's tracer is
+        // whichever of its parent's threads eventually `sys_wait`s for
+        // the trace event; approximated here as the parent's first
+        // thread, since nothing in this kernel names "the thread that
+        // will call wait" ahead of time.
+        let tracer = *parent.threads.first().ok_or(Errno::ESRCH)?;
+
+        let mut table = THREAD_TABLE.lock();
+        let target = table.get_thread_mut(tid).ok_or(Errno::ESRCH)?;
+        target.traced = true;
+        target.tracer = Some(tracer);
+        return Ok(0);
+    }
+
+    let target_tid = ThreadId(args.a2);
+
+    if request == PtraceRequest::Attach {
+        let mut table = THREAD_TABLE.lock();
+        let target = table.get_thread_mut(target_tid).ok_or(Errno::ESRCH)?;
+        target.traced = true;
+        target.tracer = Some(tid);
+        return Ok(0);
+    }
+
+    let target = thread::get_thread(target_tid).ok_or(Errno::ESRCH)?;
+    if target.tracer != Some(tid) {
+        return Err(Errno::EPERM);
     }
 
-    let mut result = Vec::new();
-    let mut index = 0usize;
-    loop {
-        let entry_ptr = unsafe { *((ptr as *const usize).add(index)) };
-        if entry_ptr == 0 {
-            break;
+    match request {
+        PtraceRequest::Cont | PtraceRequest::SingleStep => {
+            if target.state != ThreadState::TraceStopped {
+                return Err(Errno::EINVAL);
+            }
+            let mut table = THREAD_TABLE.lock();
+            if let Some(target) = table.get_thread_mut(target_tid) {
+                if request == PtraceRequest::SingleStep {
+                    set_singlestep(&mut target.context);
+                }
+                target.trace_resume = true;
+            }
+            drop(table);
+            scheduler::resume_traced_thread(target_tid);
+            Ok(0)
+        }
+        PtraceRequest::GetRegs => {
+            if target.state != ThreadState::TraceStopped {
+                return Err(Errno::EINVAL);
+            }
+            let caller_process = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+            let size = core::mem::size_of::<Context>();
+            let dst = user_access::translate_user_slice_mut(&caller_process, args.a3, size)?;
+            dst.copy_from_slice(target.context.as_bytes());
+            Ok(0)
         }
-        result.push(read_user_cstring(entry_ptr)?);
-        index += 1;
-        if index > 128 {
-            break;
+        PtraceRequest::SetRegs => {
+            if target.state != ThreadState::TraceStopped {
+                return Err(Errno::EINVAL);
+            }
+            let caller_process = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+            let size = core::mem::size_of::<Context>();
+            let src = user_access::translate_user_slice(
+                &caller_process,
+                args.a3,
+                size,
+                user_access::AccessType::Load,
+            )?;
+            let new_context = Context::from_bytes(src);
+            if let Some(target) = THREAD_TABLE.lock().get_thread_mut(target_tid) {
+                target.context = new_context;
+            }
+            Ok(0)
+        }
+        PtraceRequest::PeekData => {
+            if target.state != ThreadState::TraceStopped {
+                return Err(Errno::EINVAL);
+            }
+            let target_process = process::get_process(target.process_id).ok_or(Errno::ESRCH)?;
+            let ptr = translate_target_addr(&target_process, args.a3)?;
+            Ok(unsafe { (ptr as *const usize).read() })
+        }
+        PtraceRequest::PokeData => {
+            if target.state != ThreadState::TraceStopped {
+                return Err(Errno::EINVAL);
+            }
+            let target_process = process::get_process(target.process_id).ok_or(Errno::ESRCH)?;
+            let ptr = translate_target_addr(&target_process, args.a3)?;
+            unsafe {
+                (ptr as *mut usize).write(args.a4);
+            }
+            Ok(0)
         }
+        PtraceRequest::TraceMe | PtraceRequest::Attach => unreachable!(),
     }
-    Ok(result)
+}
+
+/// Compute units every process's budget is topped back up by each time
+/// [`budget_refill_tick`] runs, capped at its own `ProcType`'s starting
+/// budget by `process::refill_all_budgets`.
+const BUDGET_REFILL_PER_TICK: i64 = 50;
+
+/// Refill policy for the per-process compute budget metered in
+/// [`handle_syscall`]: called once per scheduler tick (see
+/// `process::scheduler::tick`) to top every process back up by a fixed
+/// amount.
+pub fn budget_refill_tick() {
+    process::refill_all_budgets(BUDGET_REFILL_PER_TICK);
+}
+
+pub fn feed_stdin(bytes: &[u8]) {
+    USER_STDIN.lock().extend(bytes);
+}
+
+pub fn take_stdout() -> Vec<u8> {
+    let mut buffer = USER_STDOUT.lock();
+    let mut drained = Vec::new();
+    core::mem::swap(&mut *buffer, &mut drained);
+    drained
 }
 
 fn register_waiter(parent: ProcessId, tid: ThreadId, pid: usize) {
@@ -773,7 +2075,513 @@ fn take_zombie(parent: ProcessId, pid: usize) -> Option<ZombieChild> {
     None
 }
 
-fn record_child_exit(parent: Option<ProcessId>, child: ProcessId, status: usize) {
+fn take_trace_event(parent: ProcessId, pid: usize) -> Option<TraceEvent> {
+    let mut events = TRACE_EVENTS.lock();
+    if pid == 0 {
+        if let Some(index) = events.iter().position(|e| e.parent == parent) {
+            return Some(events.remove(index));
+        }
+    } else {
+        let target = ProcessId(pid);
+        if let Some(index) = events
+            .iter()
+            .position(|e| e.parent == parent && e.traced_process == target)
+        {
+            return Some(events.remove(index));
+        }
+    }
+    None
+}
+
+/// Record that `traced` has stopped for `tracer` (already transitioned to
+/// `ThreadState::TraceStopped` by the caller), and wake `tracer`'s
+/// `sys_wait`/`sys_waitid` if it's already blocked waiting -- mirrors
+/// [`record_child_exit`]'s waiter lookup, just keyed by the tracer's own
+/// process instead of the traced thread's parent.
+fn record_trace_stop(tracer: ThreadId, traced: ThreadId, traced_process: ProcessId) {
+    let Some(tracer_thread) = thread::get_thread(tracer) else {
+        return;
+    };
+    let parent = tracer_thread.process_id;
+
+    TRACE_EVENTS.lock().push(TraceEvent {
+        parent,
+        traced,
+        traced_process,
+        exited: None,
+    });
+
+    let waiter = {
+        let mut waiters = WAITERS.lock();
+        if let Some(index) = waiters.iter().position(|w| {
+            w.parent == parent && (w.target.is_none() || w.target == Some(traced_process))
+        }) {
+            Some(waiters.remove(index))
+        } else {
+            None
+        }
+    };
+
+    if let Some(waiter) = waiter {
+        scheduler::unblock_thread(waiter.tid);
+    }
+}
+
+/// Like [`record_trace_stop`], but for a traced thread's actual exit
+/// (from [`finalize_thread`]) rather than a syscall-boundary stop, so its
+/// tracer's `sys_wait`/`sys_waitid` sees the real exit status instead of
+/// a `SIGTRAP`-stopped placeholder. Called before that thread's normal
+/// parent is ever notified -- a tracer finds out first, the same way a
+/// real tracer intercepts a tracee's exit ahead of its real parent.
+fn record_trace_exit(
+    tracer: ThreadId,
+    traced: ThreadId,
+    traced_process: ProcessId,
+    exit_code: usize,
+    reason: ExitReason,
+) {
+    let Some(tracer_thread) = thread::get_thread(tracer) else {
+        return;
+    };
+    let parent = tracer_thread.process_id;
+
+    TRACE_EVENTS.lock().push(TraceEvent {
+        parent,
+        traced,
+        traced_process,
+        exited: Some((exit_code, reason)),
+    });
+
+    let waiter = {
+        let mut waiters = WAITERS.lock();
+        if let Some(index) = waiters.iter().position(|w| {
+            w.parent == parent && (w.target.is_none() || w.target == Some(traced_process))
+        }) {
+            Some(waiters.remove(index))
+        } else {
+            None
+        }
+    };
+
+    if let Some(waiter) = waiter {
+        scheduler::unblock_thread(waiter.tid);
+    }
+}
+
+/// A dead tracer can never issue another `CONT`, so anything still parked
+/// in `TraceStopped` for it would otherwise never run again. Called from
+/// [`finalize_thread`] for every thread that dies, traced or not: clears
+/// `tracer`/`traced`/`trace_resume` on every thread that named `tracer`
+/// as its own, and resumes any of them still sitting in `TraceStopped`,
+/// the same as an implicit `CONT`.
+fn release_tracees(tracer: ThreadId) {
+    let mut resumed = Vec::new();
+    {
+        let mut table = THREAD_TABLE.lock();
+        for thread in table.all_threads_mut() {
+            if thread.tracer == Some(tracer) {
+                thread.tracer = None;
+                thread.traced = false;
+                thread.trace_resume = false;
+                if thread.state == ThreadState::TraceStopped {
+                    resumed.push(thread.id);
+                }
+            }
+        }
+    }
+
+    for tid in resumed {
+        scheduler::resume_traced_thread(tid);
+    }
+}
+
+/// Insert `tid`'s deadline into [`SLEEP_QUEUE`], replacing any stale
+/// entry of its own (shouldn't happen in practice -- `sys_nanosleep`
+/// only enqueues on a thread's first call -- but keeps the queue
+/// single-entry-per-thread regardless).
+fn enqueue_sleep(tid: ThreadId, wake_tick: u64) {
+    let mut queue = SLEEP_QUEUE.lock();
+    queue.retain(|entry| entry.tid != tid);
+    let pos = queue
+        .iter()
+        .position(|entry| entry.wake_tick > wake_tick)
+        .unwrap_or(queue.len());
+    queue.insert(pos, SleepEntry { wake_tick, tid });
+}
+
+/// Whether `tid` still has a deadline outstanding in [`SLEEP_QUEUE`].
+fn sleep_queue_has(tid: ThreadId) -> bool {
+    SLEEP_QUEUE.lock().iter().any(|entry| entry.tid == tid)
+}
+
+fn take_sleep_done(tid: ThreadId) -> bool {
+    let mut done = SLEEP_DONE.lock();
+    match done.iter().position(|done_tid| *done_tid == tid) {
+        Some(index) => {
+            done.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+fn take_sleep_early_wake(tid: ThreadId) -> Option<u64> {
+    let mut early = SLEEP_EARLY_WAKE.lock();
+    let index = early.iter().position(|(early_tid, _)| *early_tid == tid)?;
+    Some(early.remove(index).1)
+}
+
+/// Remove `tid` from [`SLEEP_QUEUE`] with no further bookkeeping, for a
+/// thread that exits before its deadline -- called from
+/// [`finalize_thread`] so a stale entry doesn't outlive the thread it
+/// names.
+fn cancel_sleep(tid: ThreadId) {
+    SLEEP_QUEUE.lock().retain(|entry| entry.tid != tid);
+}
+
+/// A sleeping thread sits in `ThreadState::Sleeping`, which
+/// `scheduler::add_thread` only re-enqueues once it's runnable again --
+/// so waking one always means flipping it back to `Ready` first.
+fn wake_sleeping_thread(tid: ThreadId) {
+    thread::set_thread_state(tid, ThreadState::Ready);
+    scheduler::add_thread(tid);
+}
+
+/// Wake `tid` out of [`sys_nanosleep`] before its deadline, reporting
+/// `remaining_ns` back through `EINTR` on its next retry. Not called
+/// anywhere in this kernel yet -- there's no signal delivery to call it
+/// from -- but it's the hook a future `sys_signal` handler needs so a
+/// delivered signal can interrupt a sleeping thread rather than leaving
+/// it parked until its original deadline.
+pub fn wake_sleep_early(tid: ThreadId) {
+    let remaining_ns = {
+        let mut queue = SLEEP_QUEUE.lock();
+        let index = match queue.iter().position(|entry| entry.tid == tid) {
+            Some(index) => index,
+            None => return,
+        };
+        let entry = queue.remove(index);
+        entry.wake_tick.saturating_sub(clock::clock().now_ns())
+    };
+
+    SLEEP_EARLY_WAKE.lock().push((tid, remaining_ns));
+    wake_sleeping_thread(tid);
+}
+
+/// Pop every [`SLEEP_QUEUE`] entry whose deadline has passed and requeue
+/// its thread with `scheduler::add_thread`, marking it [`SLEEP_DONE`] so
+/// its next `sys_nanosleep` retry reports a normal completion. Called
+/// from the timer interrupt, the same hook `lib_core::time::on_timer_tick`
+/// drains its own deadline queue from.
+pub fn timer_tick(now: u64) {
+    let mut queue = SLEEP_QUEUE.lock();
+    let split = queue
+        .iter()
+        .position(|entry| entry.wake_tick > now)
+        .unwrap_or(queue.len());
+
+    let woken: Vec<ThreadId> = queue.drain(..split).map(|entry| entry.tid).collect();
+    drop(queue);
+
+    for tid in woken {
+        SLEEP_DONE.lock().push(tid);
+        wake_sleeping_thread(tid);
+    }
+}
+
+/// A thread blocked in `sys_read` on a pidfd, waiting for its target to
+/// exit -- mirrors [`Waiter`]'s role for `sys_wait`, just keyed by the
+/// pidfd's own `(pid, generation)` instead of a parent/child relationship.
+struct PidfdWaiter {
+    pid: ProcessId,
+    generation: u64,
+    tid: ThreadId,
+}
+
+/// Threads parked in [`read_pidfd`], woken by [`wake_pidfd_waiters`] from
+/// [`finalize_thread`] the moment their target's last thread exits.
+static PIDFD_WAITERS: Mutex<Vec<PidfdWaiter>> = Mutex::new(Vec::new());
+
+fn register_pidfd_waiter(pid: ProcessId, generation: u64, tid: ThreadId) {
+    let mut waiters = PIDFD_WAITERS.lock();
+    waiters.retain(|waiter| waiter.tid != tid);
+    waiters.push(PidfdWaiter {
+        pid,
+        generation,
+        tid,
+    });
+}
+
+/// Wake every thread blocked reading a pidfd on `pid`, called from
+/// [`finalize_thread`] right where its process is dropped from
+/// `PROCESS_TABLE` -- the same spot [`record_child_exit`] wakes
+/// `sys_wait` callers from.
+fn wake_pidfd_waiters(pid: ProcessId) {
+    let woken: Vec<ThreadId> = {
+        let mut waiters = PIDFD_WAITERS.lock();
+        let mut woken = Vec::new();
+        waiters.retain(|waiter| {
+            if waiter.pid == pid {
+                woken.push(waiter.tid);
+                false
+            } else {
+                true
+            }
+        });
+        woken
+    };
+
+    for tid in woken {
+        scheduler::unblock_thread(tid);
+    }
+}
+
+/// `signal(signum, handler)`: registers `handler` against the calling
+/// process's `signal_handlers`, returning the address it replaced (or `0`
+/// for none). Pure bookkeeping -- see [`process::Signal`]'s doc comment
+/// for why nothing in this kernel actually invokes a handler yet.
+fn sys_signal(args: SyscallArgs) -> SyscallResult {
+    let (_, thread) = current_thread_snapshot()?;
+    let signal = process::Signal::from_usize(args.a1).ok_or(Errno::EINVAL)?;
+    process::register_signal_handler(thread.process_id, signal, args.a2).ok_or(Errno::ESRCH)
+}
+
+/// `option` selector for [`sys_prctl`], in the style of Linux's
+/// `PR_SET_CHILD_SUBREAPER`/`PR_GET_CHILD_SUBREAPER`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PrctlOp {
+    SetChildSubreaper,
+    GetChildSubreaper,
+}
+
+impl PrctlOp {
+    fn from_usize(value: usize) -> Option<Self> {
+        match value {
+            36 => Some(PrctlOp::SetChildSubreaper),
+            37 => Some(PrctlOp::GetChildSubreaper),
+            _ => None,
+        }
+    }
+}
+
+/// `prctl(option, arg2)`: today only `PR_SET_CHILD_SUBREAPER`/
+/// `PR_GET_CHILD_SUBREAPER`, toggling the calling process's
+/// [`process::Process::is_subreaper`] flag that `process::reparent_orphans`
+/// consults when one of its descendants exits.
+fn sys_prctl(args: SyscallArgs) -> SyscallResult {
+    let (_, thread) = current_thread_snapshot()?;
+    let op = PrctlOp::from_usize(args.a1).ok_or(Errno::EINVAL)?;
+
+    match op {
+        PrctlOp::SetChildSubreaper => {
+            let enabled = args.a2 != 0;
+            if process::set_subreaper(thread.process_id, enabled) {
+                Ok(0)
+            } else {
+                Err(Errno::ESRCH)
+            }
+        }
+        PrctlOp::GetChildSubreaper => {
+            let process = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+            Ok(process.is_subreaper as usize)
+        }
+    }
+}
+
+/// Byte size of one `seccomp_set` rule entry: `(syscall_number,
+/// action_kind, action_value)` as three native-endian `u64`s, the same
+/// fixed-width-triple layout `write_waitid_status` uses for its status
+/// struct.
+const SECCOMP_RULE_SIZE: usize = 24;
+
+/// Decode a `(kind, value)` pair from a `seccomp_set` argument or rule
+/// entry into a [`process::SeccompAction`]: `0` is `Allow`, `1` is
+/// `Errno(value)`, `2` is `Kill`.
+fn decode_seccomp_action(kind: u64, value: u64) -> Option<process::SeccompAction> {
+    match kind {
+        0 => Some(process::SeccompAction::Allow),
+        1 => Some(process::SeccompAction::Errno(value as i32)),
+        2 => Some(process::SeccompAction::Kill),
+        _ => None,
+    }
+}
+
+/// Translate a raw errno value recorded in a [`process::SeccompAction::Errno`]
+/// back into this syscall layer's [`Errno`], since `process` doesn't depend
+/// on `syscall` and so can't store the enum itself. Anything outside the
+/// table this kernel actually defines falls back to `EPERM`, the same
+/// default a real seccomp `SECCOMP_RET_ERRNO` filter with a bogus errno
+/// collapses to.
+fn errno_from_raw(value: i32) -> Errno {
+    match value {
+        v if v == Errno::EPERM as i32 => Errno::EPERM,
+        v if v == Errno::ESRCH as i32 => Errno::ESRCH,
+        v if v == Errno::EINTR as i32 => Errno::EINTR,
+        v if v == Errno::EAGAIN as i32 => Errno::EAGAIN,
+        v if v == Errno::ENOMEM as i32 => Errno::ENOMEM,
+        v if v == Errno::EINVAL as i32 => Errno::EINVAL,
+        v if v == Errno::ENOSYS as i32 => Errno::ENOSYS,
+        _ => Errno::EPERM,
+    }
+}
+
+/// `seccomp_set(default_kind, default_value, rules_ptr, rule_count)`:
+/// moves the calling process into seccomp-filtered mode via
+/// `process::install_seccomp_filter`. `rules_ptr` points to `rule_count`
+/// back-to-back [`SECCOMP_RULE_SIZE`]-byte entries overriding
+/// `(default_kind, default_value)` for specific syscall numbers; see
+/// [`decode_seccomp_action`] for the kind encoding. The transition is
+/// one-way -- a process that already installed a filter gets `EINVAL`
+/// rather than having its rules replaced.
+fn sys_seccomp_set(args: SyscallArgs) -> SyscallResult {
+    let (_, thread) = current_thread_snapshot()?;
+    let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+
+    let default = decode_seccomp_action(args.a1 as u64, args.a2 as u64).ok_or(Errno::EINVAL)?;
+
+    let rule_count = args.a4;
+    let mut rules = Vec::with_capacity(rule_count);
+    if rule_count > 0 {
+        let bytes = user_access::translate_user_slice(
+            &caller,
+            args.a3,
+            rule_count * SECCOMP_RULE_SIZE,
+            user_access::AccessType::Load,
+        )?
+        .to_vec();
+
+        for chunk in bytes.chunks_exact(SECCOMP_RULE_SIZE) {
+            let syscall_number = u64::from_ne_bytes(chunk[0..8].try_into().unwrap()) as usize;
+            let action_kind = u64::from_ne_bytes(chunk[8..16].try_into().unwrap());
+            let action_value = u64::from_ne_bytes(chunk[16..24].try_into().unwrap());
+            let action = decode_seccomp_action(action_kind, action_value).ok_or(Errno::EINVAL)?;
+            rules.push((syscall_number, action));
+        }
+    }
+
+    match process::install_seccomp_filter(thread.process_id, default, rules) {
+        Some(true) => Ok(0),
+        Some(false) => Err(Errno::EINVAL),
+        None => Err(Errno::ESRCH),
+    }
+}
+
+/// Decode a `getrlimit`/`setrlimit` resource-kind argument into a
+/// [`process::Resource`].
+fn decode_resource(kind: usize) -> Option<process::Resource> {
+    match kind {
+        0 => Some(process::Resource::NProc),
+        1 => Some(process::Resource::NoFile),
+        2 => Some(process::Resource::NThread),
+        _ => None,
+    }
+}
+
+/// `getrlimit(resource, rlimit_ptr)`: writes the caller's current
+/// `{soft, hard}` pair for `resource` as two back-to-back native-endian
+/// `u64`s.
+fn sys_getrlimit(args: SyscallArgs) -> SyscallResult {
+    let (_, thread) = current_thread_snapshot()?;
+    let resource = decode_resource(args.a1).ok_or(Errno::EINVAL)?;
+    let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+    let limit = caller.rlimit(resource);
+
+    if args.a2 != 0 {
+        if let Ok(dst) = user_access::translate_user_slice_mut(&caller, args.a2, 16) {
+            dst[0..8].copy_from_slice(&limit.soft.to_ne_bytes());
+            dst[8..16].copy_from_slice(&limit.hard.to_ne_bytes());
+        }
+    }
+    Ok(0)
+}
+
+/// `setrlimit(resource, rlimit_ptr)`: overwrites the caller's `{soft,
+/// hard}` pair for `resource` from two back-to-back native-endian `u64`s
+/// at `rlimit_ptr`. A request that would raise `hard` above its current
+/// value is rejected with `EPERM` unless the caller holds
+/// `CAP_PROC_MANAGE` -- the same capability `seccomp_set`/`prctl`'s
+/// process-management operations require -- mirroring `setrlimit(2)`'s
+/// own privileged-to-raise-the-ceiling rule. `soft` may never exceed
+/// `hard`.
+fn sys_setrlimit(args: SyscallArgs) -> SyscallResult {
+    let (_, thread) = current_thread_snapshot()?;
+    let resource = decode_resource(args.a1).ok_or(Errno::EINVAL)?;
+    let caller = process::get_process(thread.process_id).ok_or(Errno::ESRCH)?;
+
+    let bytes =
+        user_access::translate_user_slice(&caller, args.a2, 16, user_access::AccessType::Load)?;
+    let soft = u64::from_ne_bytes(bytes[0..8].try_into().unwrap());
+    let hard = u64::from_ne_bytes(bytes[8..16].try_into().unwrap());
+
+    if soft > hard {
+        return Err(Errno::EINVAL);
+    }
+
+    let current = caller.rlimit(resource);
+    if hard > current.hard && !caller.security.has_capabilities(CAP_PROC_MANAGE) {
+        return Err(Errno::EPERM);
+    }
+
+    let mut table = process::PROCESS_TABLE.lock();
+    let process = table
+        .get_process_mut(thread.process_id)
+        .ok_or(Errno::ESRCH)?;
+    process.set_rlimit(resource, process::Rlimit { soft, hard });
+    Ok(0)
+}
+
+/// `pidfd_open(pid, flags)`: opens an [`FdObject::Pidfd`] on `pid`,
+/// stamped with its current `generation` so a later
+/// `sys_pidfd_send_signal`/`sys_read` against this fd can tell `pid`
+/// apart from whatever process might occupy that id afterward.
+fn sys_pidfd_open(args: SyscallArgs) -> SyscallResult {
+    let (_, thread) = current_thread_snapshot()?;
+    let target_pid = ProcessId(args.a1);
+    let target = process::get_process(target_pid).ok_or(Errno::ESRCH)?;
+    let flags = args.a2 as u32;
+
+    let mut table = process::PROCESS_TABLE.lock();
+    let caller = table
+        .get_process_mut(thread.process_id)
+        .ok_or(Errno::ESRCH)?;
+    let nofile = caller.rlimit(process::Resource::NoFile);
+    if caller.file_descriptors.lock().len() as u64 >= nofile.soft {
+        return Err(Errno::EAGAIN);
+    }
+    let fd = caller.file_descriptors.lock().allocate(
+        flags,
+        false,
+        FdObject::Pidfd {
+            pid: target_pid,
+            generation: target.generation,
+        },
+    );
+    Ok(fd as usize)
+}
+
+/// `pidfd_send_signal(pidfd, signum)`: records `signum` as pending
+/// against the pidfd's target, as long as it's still the same process
+/// generation the pidfd was opened against -- `ESRCH` covers both "target
+/// already exited" and "target pid was reused", mirroring the real
+/// `pidfd_send_signal(2)`'s race-free guarantee even though this
+/// allocator (see [`process::Process::generation`]) never actually
+/// recycles a pid today.
+fn sys_pidfd_send_signal(args: SyscallArgs) -> SyscallResult {
+    let (_, thread) = current_thread_snapshot()?;
+    let fd = args.a1 as u32;
+    let signal = process::Signal::from_usize(args.a2).ok_or(Errno::EINVAL)?;
+
+    let (pid, generation) = lookup_pidfd(thread.process_id, fd).ok_or(Errno::EINVAL)?;
+    process::send_signal(pid, generation, signal).map_err(|_| Errno::ESRCH)?;
+    Ok(0)
+}
+
+fn record_child_exit(
+    parent: Option<ProcessId>,
+    child: ProcessId,
+    exit_code: usize,
+    reason: ExitReason,
+) {
     if let Some(parent_pid) = parent {
         {
             let mut table = process::PROCESS_TABLE.lock();
@@ -787,7 +2595,8 @@ fn record_child_exit(parent: Option<ProcessId>, child: ProcessId, status: usize)
             zombies.push(ZombieChild {
                 parent: parent_pid,
                 child,
-                status,
+                exit_code,
+                reason,
             });
         }
 
@@ -809,40 +2618,102 @@ fn record_child_exit(parent: Option<ProcessId>, child: ProcessId, status: usize)
     }
 }
 
-fn finalize_thread(tid: ThreadId, thread: thread::Thread, exit_code: usize) {
+fn finalize_thread(tid: ThreadId, thread: thread::Thread, exit_code: usize, reason: ExitReason) {
     thread::set_thread_state(tid, ThreadState::Terminated);
     scheduler::remove_thread(tid);
+    cancel_sleep(tid);
     THREAD_TABLE.lock().remove_thread(tid);
+    // A thread can be a tracer without being traced itself; either way,
+    // its own death must not leave some other thread stuck in
+    // `TraceStopped` forever waiting for a `CONT` that will never come.
+    release_tracees(tid);
+
+    // If this thread was itself being traced, its tracer hears about the
+    // exit before the thread's real parent does below.
+    if thread.traced {
+        if let Some(tracer) = thread.tracer {
+            record_trace_exit(tracer, tid, thread.process_id, exit_code, reason);
+        }
+    }
 
     let mut table = process::PROCESS_TABLE.lock();
     if let Some(process) = table.get_process_mut(thread.process_id) {
         process.remove_thread(tid);
         if process.threads.is_empty() {
             let parent = process.parent;
+            let reparented = process::reparent_orphans(&mut table, thread.process_id);
             let _ = table.remove_process(thread.process_id);
             drop(table);
-            record_child_exit(parent, thread.process_id, exit_code);
+            migrate_zombies_and_waiters(thread.process_id, &reparented);
+            wake_pidfd_waiters(thread.process_id);
+            TRACE_EVENTS
+                .lock()
+                .retain(|event| event.parent != thread.process_id);
+            record_child_exit(parent, thread.process_id, exit_code, reason);
             return;
         }
     }
 }
 
+/// After [`process::reparent_orphans`] moves `old_parent`'s surviving
+/// children to a new reaper, migrate any [`ZombieChild`] entries already
+/// queued under `old_parent` for one of them, and wake a matching
+/// `Waiter` already blocked on the new reaper -- otherwise a child that
+/// exited just before its own parent would have its status stranded
+/// under a `parent` nobody can `sys_wait`/`sys_waitid` from anymore.
+fn migrate_zombies_and_waiters(old_parent: ProcessId, reparented: &[(ProcessId, ProcessId)]) {
+    for (child, reaper) in reparented {
+        let migrated = {
+            let mut zombies = ZOMBIE_CHILDREN.lock();
+            let mut migrated = false;
+            for zombie in zombies.iter_mut() {
+                if zombie.parent == old_parent && zombie.child == *child {
+                    zombie.parent = *reaper;
+                    migrated = true;
+                }
+            }
+            migrated
+        };
+
+        if !migrated {
+            continue;
+        }
+
+        let waiter = {
+            let mut waiters = WAITERS.lock();
+            let index = waiters.iter().position(|w| {
+                w.parent == *reaper && (w.target.is_none() || w.target == Some(*child))
+            });
+            index.map(|index| waiters.remove(index))
+        };
+
+        if let Some(waiter) = waiter {
+            scheduler::unblock_thread(waiter.tid);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::memory::{frame_allocator::Frame, PhysAddr, VirtAddr};
-    use crate::process::{scheduler::SCHEDULER, thread::ThreadTable, ProcessTable};
+    use crate::process::{scheduler::current_scheduler, thread::ThreadTable, ProcessTable};
     use crate::security::{SecurityContext, CAP_CONSOLE_IO};
 
     fn reset_state() {
         *crate::process::PROCESS_TABLE.lock() = ProcessTable::new();
         *crate::process::thread::THREAD_TABLE.lock() = ThreadTable::new();
-        *SCHEDULER.lock() = crate::process::scheduler::Scheduler::new();
+        *current_scheduler().lock() = crate::process::scheduler::Scheduler::new();
 
         USER_STDOUT.lock().clear();
         USER_STDIN.lock().clear();
         ZOMBIE_CHILDREN.lock().clear();
         WAITERS.lock().clear();
+        TRACE_EVENTS.lock().clear();
+        SLEEP_QUEUE.lock().clear();
+        SLEEP_DONE.lock().clear();
+        SLEEP_EARLY_WAKE.lock().clear();
+        PIDFD_WAITERS.lock().clear();
     }
 
     fn create_minimal_process_with_thread(security: SecurityContext) -> (ProcessId, ThreadId) {
@@ -854,6 +2725,7 @@ mod tests {
             frame,
             security,
             FileDescriptorTable::new(),
+            crate::process::ProcType::Application,
         )
         .unwrap();
 
@@ -902,6 +2774,139 @@ mod tests {
         assert_eq!(ok2, 0);
     }
 
+    #[test]
+    fn test_seccomp_errno_action() {
+        reset_state();
+
+        let security = SecurityContext::as_user(1000).with_capabilities(CAP_PROC_MANAGE);
+        let (pid, _tid) = create_minimal_process_with_thread(security);
+
+        // getpid needs no caps and would otherwise succeed.
+        let before = syscall_handler(SYS_GETPID, 0, 0, 0, 0, 0, 0);
+        assert_eq!(before, pid.0 as isize);
+
+        // Default action ERRNO(EAGAIN), no per-syscall overrides: every
+        // syscall (including getpid) now fails with that errno instead
+        // of ever reaching its handler.
+        let install = syscall_handler(SYS_SECCOMP_SET, 1, Errno::EAGAIN as usize, 0, 0, 0, 0);
+        assert_eq!(install, 0);
+
+        let after = syscall_handler(SYS_GETPID, 0, 0, 0, 0, 0, 0);
+        assert_eq!(after, -(Errno::EAGAIN as isize));
+
+        // The transition is one-way: a second install attempt is rejected.
+        let second = syscall_handler(SYS_SECCOMP_SET, 0, 0, 0, 0, 0, 0);
+        assert_eq!(second, -(Errno::EINVAL as isize));
+    }
+
+    #[test]
+    fn test_seccomp_kill_action() {
+        reset_state();
+
+        let security = SecurityContext::as_user(1000).with_capabilities(CAP_PROC_MANAGE);
+        let (pid, _tid) = create_minimal_process_with_thread(security);
+
+        let install = syscall_handler(SYS_SECCOMP_SET, 2, 0, 0, 0, 0, 0);
+        assert_eq!(install, 0);
+
+        let killed = syscall_handler(SYS_GETPID, 0, 0, 0, 0, 0, 0);
+        assert_eq!(killed, -(Errno::EPERM as isize));
+
+        // `finalize_thread` ran synchronously: the process's one thread is
+        // gone, so the whole process was torn down along with it.
+        assert!(process::get_process(pid).is_none());
+    }
+
+    #[test]
+    fn test_clone_thread_joins_caller_process() {
+        reset_state();
+
+        let security = SecurityContext::as_user(1000).with_capabilities(CAP_PROC_MANAGE);
+        let (pid, _tid) = create_minimal_process_with_thread(security);
+
+        let new_tid = syscall_handler(SYS_CLONE, CloneFlags::CLONE_THREAD.bits(), 0, 0, 0, 0, 0);
+        assert!(new_tid > 0);
+
+        let joined = process::thread::get_thread(ThreadId(new_tid as usize)).unwrap();
+        assert_eq!(joined.process_id, pid);
+    }
+
+    #[test]
+    fn test_clone_without_thread_flag_shares_vm_and_files() {
+        reset_state();
+
+        let security = SecurityContext::as_user(1000).with_capabilities(CAP_PROC_MANAGE);
+        let (pid, _tid) = create_minimal_process_with_thread(security);
+
+        let flags = (CloneFlags::CLONE_VM | CloneFlags::CLONE_FILES).bits();
+        let new_pid = syscall_handler(SYS_CLONE, flags, 0, 0, 0, 0, 0);
+        assert!(new_pid > 0);
+        let child_pid = ProcessId(new_pid as usize);
+        assert_ne!(child_pid, pid);
+
+        let parent = process::get_process(pid).unwrap();
+        let child = process::get_process(child_pid).unwrap();
+
+        assert_eq!(
+            parent.address_space.page_table_frame,
+            child.address_space.page_table_frame
+        );
+        assert!(Arc::ptr_eq(
+            &parent.file_descriptors,
+            &child.file_descriptors
+        ));
+    }
+
+    #[test]
+    fn test_getrlimit_null_ptr_returns_ok_without_writeback() {
+        reset_state();
+
+        let security = SecurityContext::as_user(1000);
+        create_minimal_process_with_thread(security);
+
+        // resource=NProc, rlimit_ptr=0: exercises the "don't write back"
+        // branch without needing a real user-space mapping, the same way
+        // `test_capability_enforcement` passes `buf=0, len=0` to `sys_write`.
+        let get = syscall_handler(SYS_GETRLIMIT, 0, 0, 0, 0, 0, 0);
+        assert_eq!(get, 0);
+    }
+
+    #[test]
+    fn test_setrlimit_rejects_unknown_resource() {
+        reset_state();
+
+        let security = SecurityContext::as_user(1000);
+        create_minimal_process_with_thread(security);
+
+        // An unrecognized resource kind is rejected by `decode_resource`
+        // before `sys_setrlimit` ever reads `rlimit_ptr`.
+        let set = syscall_handler(SYS_SETRLIMIT, 99, 0, 0, 0, 0, 0);
+        assert_eq!(set, -(Errno::EINVAL as isize));
+    }
+
+    #[test]
+    fn test_fork_rejects_once_at_rlimit_nproc() {
+        reset_state();
+
+        let security = SecurityContext::as_user(1000).with_capabilities(CAP_PROC_MANAGE);
+        let (pid, _tid) = create_minimal_process_with_thread(security);
+
+        process::PROCESS_TABLE
+            .lock()
+            .get_process_mut(pid)
+            .unwrap()
+            .set_rlimit(
+                process::Resource::NProc,
+                process::Rlimit { soft: 1, hard: 64 },
+            );
+
+        let first = syscall_handler(SYS_FORK, 0, 0, 0, 0, 0, 0);
+        assert!(first > 0);
+
+        let second = syscall_handler(SYS_FORK, 0, 0, 0, 0, 0, 0);
+        assert_eq!(second, -(Errno::EAGAIN as isize));
+    }
+
     #[test]
     fn test_privilege_ring_enforcement() {
         reset_state();
@@ -919,4 +2924,36 @@ mod tests {
         let allowed = syscall_handler(SYS_DEBUG_LOG, 0, 0, 0, 0, 0, 0);
         assert_eq!(allowed, -(Errno::ENOSYS as isize));
     }
+
+    #[test]
+    fn test_encode_wait_status() {
+        assert_eq!(encode_wait_status(42, ExitReason::Exited), 42 << 8);
+        assert_eq!(encode_wait_status(0, ExitReason::Killed(9)), 9);
+    }
+
+    #[test]
+    fn test_waitid_rejects_pgid_and_bad_idtype() {
+        reset_state();
+
+        let security = SecurityContext::as_user(1000).with_capabilities(CAP_PROC_MANAGE);
+        create_minimal_process_with_thread(security);
+
+        // idtype 2 is P_PGID; this kernel has no process groups to wait on.
+        let denied = syscall_handler(SYS_WAITID, 2, 0, 0, 0, 0, 0);
+        assert_eq!(denied, -(Errno::EINVAL as isize));
+
+        let bad_idtype = syscall_handler(SYS_WAITID, 99, 0, 0, 0, 0, 0);
+        assert_eq!(bad_idtype, -(Errno::EINVAL as isize));
+    }
+
+    #[test]
+    fn test_wait_wnohang_returns_immediately() {
+        reset_state();
+
+        let security = SecurityContext::as_user(1000).with_capabilities(CAP_PROC_MANAGE);
+        create_minimal_process_with_thread(security);
+
+        let result = syscall_handler(SYS_WAIT, 0, WaitOptions::WNOHANG.bits(), 0, 0, 0, 0);
+        assert_eq!(result, 0);
+    }
 }
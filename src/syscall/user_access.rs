@@ -0,0 +1,188 @@
+/// Userspace Pointer Validation
+///
+/// Handlers like `sys_write`, `sys_read`, and the `argv`/path readers used
+/// to dereference a caller-supplied pointer straight out of its syscall
+/// argument, with nothing checking that the address is even mapped, let
+/// alone mapped into the calling process with the right permissions -- a
+/// malicious Ring3 caller can fault the kernel or read kernel memory
+/// through a forged pointer. This module walks the calling process's page
+/// tables for every page a requested range touches before any of that
+/// happens, in the same spirit as the memory-region checks sandboxed VMs
+/// (e.g. Solana's `MemoryMapping`/`AccessType`) run over untrusted
+/// bytecode before letting it touch host memory.
+use alloc::{string::String, vec::Vec};
+use core::slice;
+
+use crate::memory::paging::{Page, PageFlags, KERNEL_SPACE_START};
+use crate::memory::{VirtAddr, PAGE_SIZE};
+use crate::process::Process;
+use crate::syscall::Errno;
+
+#[cfg(target_arch = "x86_64")]
+use crate::memory::paging::X86_64PageTable;
+
+#[cfg(target_arch = "aarch64")]
+use crate::memory::paging::arm_lpae::ArmLpaePageTable;
+
+/// Whether a range is about to be read from or written to, so
+/// [`translate_user_slice`]/[`translate_user_slice_mut`] can reject a page
+/// that's mapped but not writable when the caller means to store through
+/// it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessType {
+    Load,
+    Store,
+}
+
+/// The flags on the leaf entry mapping `addr` in `process`'s address
+/// space, or `None` if nothing maps it.
+fn leaf_flags(process: &Process, addr: VirtAddr) -> Option<PageFlags> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        X86_64PageTable::new(process.address_space.page_table_frame).leaf_flags(addr)
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        ArmLpaePageTable::new(process.address_space.page_table_frame).leaf_flags(addr)
+    }
+}
+
+/// Walk `process`'s page tables for every page `[ptr, ptr + len)` touches,
+/// rejecting the range outright if it wraps around or crosses into kernel
+/// virtual addresses before walking a single page.
+fn validate_range(
+    process: &Process,
+    ptr: usize,
+    len: usize,
+    access: AccessType,
+) -> Result<(), Errno> {
+    if len == 0 {
+        return Ok(());
+    }
+
+    let end = ptr.checked_add(len).ok_or(Errno::EINVAL)?;
+    if ptr as u64 >= KERNEL_SPACE_START || end as u64 > KERNEL_SPACE_START {
+        return Err(Errno::EPERM);
+    }
+
+    let first_page = Page::containing_address(VirtAddr::new(ptr as u64))
+        .start_address
+        .0;
+    let last_page = Page::containing_address(VirtAddr::new((end - 1) as u64))
+        .start_address
+        .0;
+
+    let mut page_addr = first_page;
+    loop {
+        let flags = leaf_flags(process, VirtAddr::new(page_addr)).ok_or(Errno::EINVAL)?;
+        if !flags.contains(PageFlags::PRESENT) || !flags.contains(PageFlags::USER_ACCESSIBLE) {
+            return Err(Errno::EPERM);
+        }
+        if access == AccessType::Store && !flags.contains(PageFlags::WRITABLE) {
+            return Err(Errno::EPERM);
+        }
+
+        if page_addr == last_page {
+            break;
+        }
+        page_addr += PAGE_SIZE as u64;
+    }
+
+    Ok(())
+}
+
+/// Validate `[ptr, ptr + len)` for `access` against `process`'s page
+/// tables, then hand it back as a slice over the caller's own address
+/// space -- this borrows the live mapping, it never copies.
+pub fn translate_user_slice(
+    process: &Process,
+    ptr: usize,
+    len: usize,
+    access: AccessType,
+) -> Result<&[u8], Errno> {
+    validate_range(process, ptr, len, access)?;
+    Ok(unsafe { slice::from_raw_parts(ptr as *const u8, len) })
+}
+
+/// Like [`translate_user_slice`], but validated for `Store` and handed
+/// back mutable.
+pub fn translate_user_slice_mut(
+    process: &Process,
+    ptr: usize,
+    len: usize,
+) -> Result<&mut [u8], Errno> {
+    validate_range(process, ptr, len, AccessType::Store)?;
+    Ok(unsafe { slice::from_raw_parts_mut(ptr as *mut u8, len) })
+}
+
+/// Read a NUL-terminated string out of `process`'s address space,
+/// validating one page at a time as the scan crosses into it rather than
+/// trusting the whole string fits in memory the caller is allowed to read.
+/// Capped at one page: nothing in this kernel passes a path or argv entry
+/// longer than that.
+pub fn read_user_cstring(process: &Process, ptr: usize) -> Result<String, Errno> {
+    if ptr == 0 {
+        return Err(Errno::EINVAL);
+    }
+
+    let mut bytes = Vec::new();
+    let mut offset = 0usize;
+    let mut validated_page = None;
+
+    loop {
+        if offset >= PAGE_SIZE {
+            return Err(Errno::EINVAL);
+        }
+
+        let byte_ptr = ptr.checked_add(offset).ok_or(Errno::EINVAL)?;
+        let page = Page::containing_address(VirtAddr::new(byte_ptr as u64))
+            .start_address
+            .0;
+        if validated_page != Some(page) {
+            validate_range(process, byte_ptr, 1, AccessType::Load)?;
+            validated_page = Some(page);
+        }
+
+        let byte = unsafe { (byte_ptr as *const u8).read() };
+        if byte == 0 {
+            break;
+        }
+        bytes.push(byte);
+        offset += 1;
+    }
+
+    core::str::from_utf8(&bytes)
+        .map(|s| s.into())
+        .map_err(|_| Errno::EINVAL)
+}
+
+/// Read a NUL-terminated array of string pointers (an argv-style vector)
+/// out of `process`'s address space, validating each pointer word before
+/// following it. Capped at 128 entries, matching [`read_user_cstring`]'s
+/// one-page cap on each entry.
+pub fn read_user_string_array(process: &Process, ptr: usize) -> Result<Vec<String>, Errno> {
+    if ptr == 0 {
+        return Ok(Vec::new());
+    }
+
+    let entry_size = core::mem::size_of::<usize>();
+    let mut result = Vec::new();
+    let mut index = 0usize;
+    loop {
+        if index > 128 {
+            break;
+        }
+
+        let entry_addr = ptr.checked_add(index * entry_size).ok_or(Errno::EINVAL)?;
+        let word = translate_user_slice(process, entry_addr, entry_size, AccessType::Load)?;
+        let entry_ptr = usize::from_ne_bytes(word.try_into().map_err(|_| Errno::EINVAL)?);
+        if entry_ptr == 0 {
+            break;
+        }
+
+        result.push(read_user_cstring(process, entry_ptr)?);
+        index += 1;
+    }
+    Ok(result)
+}
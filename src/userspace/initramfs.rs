@@ -0,0 +1,101 @@
+/// CPIO (newc format) Initramfs Loader
+///
+/// Parses a single newc-format CPIO archive embedded at build time and
+/// builds an in-memory index of path -> byte-slice, so the userspace image
+/// can be swapped without touching Rust source or recompiling the kernel.
+///
+/// newc header layout (all ASCII, 8 hex digits per field, no separators):
+/// magic(6) ino(8) mode(8) uid(8) gid(8) nlink(8) mtime(8) filesize(8)
+/// devmajor(8) devminor(8) rdevmajor(8) rdevminor(8) namesize(8) check(8)
+/// followed by the (4-byte-aligned) name and then the (4-byte-aligned) data.
+/// The archive ends with a `TRAILER!!!` entry.
+use alloc::vec::Vec;
+
+const NEWC_MAGIC: &[u8; 6] = b"070701";
+const HEADER_LEN: usize = 110;
+const TRAILER_NAME: &str = "TRAILER!!!";
+
+/// One parsed entry: a file's path paired with its data slice into the
+/// archive.
+#[derive(Clone, Copy)]
+pub struct Entry {
+    pub path: &'static str,
+    pub data: &'static [u8],
+}
+
+/// Parse `archive` in place, calling `f` for each regular file entry until
+/// the trailer is reached or the data is exhausted/malformed.
+fn for_each_entry(archive: &'static [u8], mut f: impl FnMut(Entry)) {
+    let mut offset = 0usize;
+
+    while offset + HEADER_LEN <= archive.len() {
+        let header = &archive[offset..offset + HEADER_LEN];
+        if &header[0..6] != NEWC_MAGIC {
+            return;
+        }
+
+        let Some(namesize) = hex_field(header, 94) else {
+            return;
+        };
+        let Some(filesize) = hex_field(header, 54) else {
+            return;
+        };
+
+        let name_start = offset + HEADER_LEN;
+        let name_end = name_start + namesize as usize;
+        if name_end > archive.len() {
+            return;
+        }
+        // Name includes a trailing NUL; drop it before interpreting as UTF-8.
+        let Ok(name) = core::str::from_utf8(&archive[name_start..name_end - 1]) else {
+            return;
+        };
+
+        let data_start = align4(name_end);
+        let data_end = data_start + filesize as usize;
+        if data_end > archive.len() {
+            return;
+        }
+
+        if name == TRAILER_NAME {
+            return;
+        }
+
+        f(Entry {
+            path: name,
+            data: &archive[data_start..data_end],
+        });
+
+        offset = align4(data_end);
+    }
+}
+
+fn align4(n: usize) -> usize {
+    (n + 3) & !3
+}
+
+/// Read an 8-hex-digit ASCII field starting at `start` within `header`.
+fn hex_field(header: &[u8], start: usize) -> Option<u32> {
+    let field = header.get(start..start + 8)?;
+    let s = core::str::from_utf8(field).ok()?;
+    u32::from_str_radix(s, 16).ok()
+}
+
+/// Look up `path` in `archive`. Mirrors the old fixed-table `lookup`
+/// signature so callers don't change.
+pub fn lookup(archive: &'static [u8], path: &str) -> Option<&'static [u8]> {
+    let mut found = None;
+    for_each_entry(archive, |entry| {
+        if found.is_none() && entry.path == path {
+            found = Some(entry.data);
+        }
+    });
+    found
+}
+
+/// Enumerate every path stored in `archive`.
+pub fn list(archive: &'static [u8]) -> Vec<&'static str> {
+    let mut paths = Vec::new();
+    for_each_entry(archive, |entry| paths.push(entry.path));
+    paths
+}
@@ -1,55 +1,80 @@
-use crate::process::loader::TargetArch;
+use alloc::string::String;
 
-struct UserspaceEntry {
-    path: &'static str,
-    x86_64: &'static [u8],
-    aarch64: Option<&'static [u8]>,
-}
+use crate::cmdline::Cmdline;
+use crate::memory::{frame_allocator, VirtAddr};
+use crate::process::loader::{self, TargetArch};
+use crate::process::{self, FileDescriptorTable, Priority, ProcType, ProcessId};
+use crate::security::SecurityContext;
 
-static INIT_X86_64: &[u8] = include_bytes!("../../userspace/prebuilt/x86_64/init.elf");
-static SHELL_X86_64: &[u8] = include_bytes!("../../userspace/prebuilt/x86_64/shell.elf");
-static CAT_X86_64: &[u8] = include_bytes!("../../userspace/prebuilt/x86_64/cat.elf");
-static LS_X86_64: &[u8] = include_bytes!("../../userspace/prebuilt/x86_64/ls.elf");
-static STRESS_X86_64: &[u8] = include_bytes!("../../userspace/prebuilt/x86_64/stress.elf");
-
-static INIT_AARCH64: &[u8] = include_bytes!("../../userspace/prebuilt/arm64/init.elf");
-static SHELL_AARCH64: &[u8] = include_bytes!("../../userspace/prebuilt/arm64/shell.elf");
-static CAT_AARCH64: &[u8] = include_bytes!("../../userspace/prebuilt/arm64/cat.elf");
-static LS_AARCH64: &[u8] = include_bytes!("../../userspace/prebuilt/arm64/ls.elf");
-static STRESS_AARCH64: &[u8] = include_bytes!("../../userspace/prebuilt/arm64/stress.elf");
-
-static PROGRAMS: &[UserspaceEntry] = &[
-    UserspaceEntry {
-        path: "/sbin/init",
-        x86_64: INIT_X86_64,
-        aarch64: Some(INIT_AARCH64),
-    },
-    UserspaceEntry {
-        path: "/bin/sh",
-        x86_64: SHELL_X86_64,
-        aarch64: Some(SHELL_AARCH64),
-    },
-    UserspaceEntry {
-        path: "/bin/cat",
-        x86_64: CAT_X86_64,
-        aarch64: Some(CAT_AARCH64),
-    },
-    UserspaceEntry {
-        path: "/bin/ls",
-        x86_64: LS_X86_64,
-        aarch64: Some(LS_AARCH64),
-    },
-    UserspaceEntry {
-        path: "/usr/bin/stress",
-        x86_64: STRESS_X86_64,
-        aarch64: Some(STRESS_AARCH64),
-    },
-];
+pub mod initramfs;
 
-pub fn lookup(path: &str, arch: TargetArch) -> Option<&'static [u8]> {
-    let entry = PROGRAMS.iter().find(|entry| entry.path == path)?;
+/// `init=` path assumed when the command line doesn't name one, matching
+/// `syscall::sys_exec`'s own fallback for a null exec path.
+const DEFAULT_INIT: &str = "/sbin/init";
+
+static INITRAMFS_X86_64: &[u8] = include_bytes!("../../userspace/prebuilt/x86_64/initramfs.cpio");
+static INITRAMFS_AARCH64: &[u8] = include_bytes!("../../userspace/prebuilt/arm64/initramfs.cpio");
+
+fn archive_for(arch: TargetArch) -> &'static [u8] {
     match arch {
-        TargetArch::X86_64 => Some(entry.x86_64),
-        TargetArch::AArch64 => entry.aarch64,
+        TargetArch::X86_64 => INITRAMFS_X86_64,
+        TargetArch::AArch64 => INITRAMFS_AARCH64,
     }
 }
+
+/// Look up a userspace program's ELF bytes by path, for the given target
+/// architecture. Backed by the CPIO initramfs embedded at build time
+/// instead of a fixed table, so adding or updating a program doesn't
+/// require touching kernel source.
+pub fn lookup(path: &str, arch: TargetArch) -> Option<&'static [u8]> {
+    initramfs::lookup(archive_for(arch), path)
+}
+
+/// Enumerate every path present in the initramfs for the given arch.
+pub fn list(arch: TargetArch) -> alloc::vec::Vec<&'static str> {
+    initramfs::list(archive_for(arch))
+}
+
+/// Bring up the very first userspace process from the embedded initramfs:
+/// parse `raw_cmdline` for an `init=` path (falling back to
+/// [`DEFAULT_INIT`]), look it up with [`lookup`], and hand its ELF bytes
+/// to `elf_loader` via `loader::exec_into_process` the same way
+/// `syscall::sys_exec` loads a replacement image into an already-running
+/// process -- except here `create_process` builds that process (with a
+/// fresh page table and a fresh, stdio-only `FileDescriptorTable`, since
+/// there's no parent to inherit from) instead of reusing one. Returns
+/// `None` if the path isn't present in the archive or any allocation
+/// along the way fails.
+pub fn spawn_init(raw_cmdline: &str, arch: TargetArch) -> Option<ProcessId> {
+    let cmdline = Cmdline::parse(raw_cmdline);
+    let init_path = cmdline.get("init").unwrap_or(DEFAULT_INIT);
+    let image = lookup(init_path, arch)?;
+
+    let page_table_frame = frame_allocator::allocate_frame()?;
+    let security = SecurityContext::as_user(0).with_capabilities(u64::MAX);
+    let pid = process::create_process(
+        String::from(init_path),
+        page_table_frame,
+        security,
+        FileDescriptorTable::new(),
+        ProcType::System,
+    )?;
+
+    let loaded = {
+        let mut table = process::PROCESS_TABLE.lock();
+        let proc = table.get_process_mut(pid)?;
+        loader::exec_into_process(proc, image, arch, &[init_path], &[], false).ok()?
+    };
+
+    let kernel_stack = frame_allocator::allocate_frame()?;
+    let tid = process::create_thread(
+        pid,
+        Priority::Normal,
+        loaded.entry_point,
+        VirtAddr::new(kernel_stack.start_address.0),
+        Some(loaded.stack.user_sp),
+    )?;
+
+    process::scheduler::add_thread(tid);
+    Some(pid)
+}
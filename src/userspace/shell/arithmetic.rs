@@ -0,0 +1,468 @@
+//! Arithmetic expansion evaluator for `$(( ... ))`
+//!
+//! Pratt/precedence-climbing evaluator over `i64`, similar in spirit to the
+//! dedicated arithmetic evaluators in dmd_core and scryer-prolog. Binding
+//! levels from lowest to highest: ternary `?:`, `||`, `&&`, `|`, `^`, `&`,
+//! `== !=`, `< <= > >=`, `<< >>`, `+ -`, `* / %`, unary `+ - ! ~`, and `**`
+//! (right-associative).
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArithError {
+    UnexpectedEnd,
+    UnexpectedToken(String),
+    DivisionByZero,
+    UnbalancedParens,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tok {
+    Num(i64),
+    Ident(usize, usize), // start, end byte range into the source
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    StarStar,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    EqEq,
+    NotEq,
+    AndAnd,
+    OrOr,
+    Amp,
+    Pipe,
+    Caret,
+    Shl,
+    Shr,
+    Not,
+    Tilde,
+    Question,
+    Colon,
+    LParen,
+    RParen,
+    Eof,
+}
+
+fn lex(src: &str) -> Result<Vec<(Tok, &str)>, ArithError> {
+    let bytes = src.as_bytes();
+    let mut pos = 0;
+    let mut out = Vec::new();
+
+    while pos < bytes.len() {
+        let c = bytes[pos];
+        match c {
+            b' ' | b'\t' | b'\n' | b'\r' => pos += 1,
+            b'0'..=b'9' => {
+                let start = pos;
+                if c == b'0' && pos + 1 < bytes.len() && (bytes[pos + 1] | 0x20) == b'x' {
+                    pos += 2;
+                    while pos < bytes.len() && bytes[pos].is_ascii_hexdigit() {
+                        pos += 1;
+                    }
+                    let text = core::str::from_utf8(&bytes[start + 2..pos]).unwrap();
+                    let value = i64::from_str_radix(text, 16)
+                        .map_err(|_| ArithError::UnexpectedToken(src[start..pos].into()))?;
+                    out.push((Tok::Num(value), &src[start..pos]));
+                } else if c == b'0' && pos + 1 < bytes.len() && bytes[pos + 1].is_ascii_digit() {
+                    pos += 1;
+                    while pos < bytes.len() && (b'0'..=b'7').contains(&bytes[pos]) {
+                        pos += 1;
+                    }
+                    let text = core::str::from_utf8(&bytes[start + 1..pos]).unwrap();
+                    let value = i64::from_str_radix(text, 8)
+                        .map_err(|_| ArithError::UnexpectedToken(src[start..pos].into()))?;
+                    out.push((Tok::Num(value), &src[start..pos]));
+                } else {
+                    while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+                        pos += 1;
+                    }
+                    let text = &src[start..pos];
+                    let value: i64 = text
+                        .parse()
+                        .map_err(|_| ArithError::UnexpectedToken(text.into()))?;
+                    out.push((Tok::Num(value), text));
+                }
+            }
+            b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
+                let start = pos;
+                while pos < bytes.len()
+                    && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_')
+                {
+                    pos += 1;
+                }
+                out.push((Tok::Ident(start, pos), &src[start..pos]));
+            }
+            _ => {
+                let (tok, len) = match (c, bytes.get(pos + 1).copied()) {
+                    (b'*', Some(b'*')) => (Tok::StarStar, 2),
+                    (b'&', Some(b'&')) => (Tok::AndAnd, 2),
+                    (b'|', Some(b'|')) => (Tok::OrOr, 2),
+                    (b'=', Some(b'=')) => (Tok::EqEq, 2),
+                    (b'!', Some(b'=')) => (Tok::NotEq, 2),
+                    (b'<', Some(b'=')) => (Tok::Le, 2),
+                    (b'>', Some(b'=')) => (Tok::Ge, 2),
+                    (b'<', Some(b'<')) => (Tok::Shl, 2),
+                    (b'>', Some(b'>')) => (Tok::Shr, 2),
+                    (b'+', _) => (Tok::Plus, 1),
+                    (b'-', _) => (Tok::Minus, 1),
+                    (b'*', _) => (Tok::Star, 1),
+                    (b'/', _) => (Tok::Slash, 1),
+                    (b'%', _) => (Tok::Percent, 1),
+                    (b'<', _) => (Tok::Lt, 1),
+                    (b'>', _) => (Tok::Gt, 1),
+                    (b'&', _) => (Tok::Amp, 1),
+                    (b'|', _) => (Tok::Pipe, 1),
+                    (b'^', _) => (Tok::Caret, 1),
+                    (b'!', _) => (Tok::Not, 1),
+                    (b'~', _) => (Tok::Tilde, 1),
+                    (b'?', _) => (Tok::Question, 1),
+                    (b':', _) => (Tok::Colon, 1),
+                    (b'(', _) => (Tok::LParen, 1),
+                    (b')', _) => (Tok::RParen, 1),
+                    _ => return Err(ArithError::UnexpectedToken((c as char).to_string())),
+                };
+                out.push((tok, &src[pos..pos + len]));
+                pos += len;
+            }
+        }
+    }
+    out.push((Tok::Eof, ""));
+    Ok(out)
+}
+
+/// Looks up a variable's integer value. Undefined names resolve to 0.
+pub type Env<'a> = &'a dyn Fn(&str) -> i64;
+
+struct Evaluator<'a> {
+    tokens: Vec<(Tok, &'a str)>,
+    pos: usize,
+    env: Env<'a>,
+    full_src: &'a str,
+    /// Non-zero while parsing a short-circuited `&&`/`||` operand: still
+    /// walks the tokens (so `pos` ends up in the right place) but swallows
+    /// runtime errors like division-by-zero from the unevaluated side.
+    skipping: u32,
+}
+
+impl<'a> Evaluator<'a> {
+    fn peek(&self) -> Tok {
+        self.tokens[self.pos].0
+    }
+
+    /// Run `f` in "skip" mode: still consumes tokens, but division-by-zero
+    /// and the like are reported as 0 instead of propagating.
+    fn parse_skipped(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<i64, ArithError>,
+    ) -> Result<i64, ArithError> {
+        self.skipping += 1;
+        let result = f(self);
+        self.skipping -= 1;
+        match result {
+            Ok(v) => Ok(v),
+            Err(ArithError::DivisionByZero) => Ok(0),
+            Err(other) => Err(other),
+        }
+    }
+
+    fn advance(&mut self) -> Tok {
+        let t = self.tokens[self.pos].0;
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        t
+    }
+
+    fn eat(&mut self, tok: Tok) -> Result<(), ArithError> {
+        if self.peek() == tok {
+            self.advance();
+            Ok(())
+        } else {
+            Err(ArithError::UnexpectedToken(format!(
+                "{:?}",
+                self.peek()
+            )))
+        }
+    }
+
+    // Precedence climbing, lowest to highest:
+    // ternary -> or -> and -> bitor -> bitxor -> bitand -> equality
+    // -> relational -> shift -> additive -> multiplicative -> unary -> power
+
+    fn parse_ternary(&mut self) -> Result<i64, ArithError> {
+        let cond = self.parse_or()?;
+        if self.peek() == Tok::Question {
+            self.advance();
+            let then_val = self.parse_ternary()?;
+            self.eat(Tok::Colon)?;
+            let else_val = self.parse_ternary()?;
+            Ok(if cond != 0 { then_val } else { else_val })
+        } else {
+            Ok(cond)
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<i64, ArithError> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Tok::OrOr {
+            self.advance();
+            let already_true = left != 0;
+            let right = if already_true {
+                self.parse_skipped(Self::parse_and)?
+            } else {
+                self.parse_and()?
+            };
+            left = if already_true || right != 0 { 1 } else { 0 };
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<i64, ArithError> {
+        let mut left = self.parse_bitor()?;
+        while self.peek() == Tok::AndAnd {
+            self.advance();
+            let already_false = left == 0;
+            let right = if already_false {
+                self.parse_skipped(Self::parse_bitor)?
+            } else {
+                self.parse_bitor()?
+            };
+            left = if !already_false && right != 0 { 1 } else { 0 };
+        }
+        Ok(left)
+    }
+
+    fn parse_bitor(&mut self) -> Result<i64, ArithError> {
+        let mut left = self.parse_bitxor()?;
+        while self.peek() == Tok::Pipe {
+            self.advance();
+            left |= self.parse_bitxor()?;
+        }
+        Ok(left)
+    }
+
+    fn parse_bitxor(&mut self) -> Result<i64, ArithError> {
+        let mut left = self.parse_bitand()?;
+        while self.peek() == Tok::Caret {
+            self.advance();
+            left ^= self.parse_bitand()?;
+        }
+        Ok(left)
+    }
+
+    fn parse_bitand(&mut self) -> Result<i64, ArithError> {
+        let mut left = self.parse_equality()?;
+        while self.peek() == Tok::Amp {
+            self.advance();
+            left &= self.parse_equality()?;
+        }
+        Ok(left)
+    }
+
+    fn parse_equality(&mut self) -> Result<i64, ArithError> {
+        let mut left = self.parse_relational()?;
+        loop {
+            left = match self.peek() {
+                Tok::EqEq => {
+                    self.advance();
+                    (left == self.parse_relational()?) as i64
+                }
+                Tok::NotEq => {
+                    self.advance();
+                    (left != self.parse_relational()?) as i64
+                }
+                _ => return Ok(left),
+            };
+        }
+    }
+
+    fn parse_relational(&mut self) -> Result<i64, ArithError> {
+        let mut left = self.parse_shift()?;
+        loop {
+            left = match self.peek() {
+                Tok::Lt => {
+                    self.advance();
+                    (left < self.parse_shift()?) as i64
+                }
+                Tok::Le => {
+                    self.advance();
+                    (left <= self.parse_shift()?) as i64
+                }
+                Tok::Gt => {
+                    self.advance();
+                    (left > self.parse_shift()?) as i64
+                }
+                Tok::Ge => {
+                    self.advance();
+                    (left >= self.parse_shift()?) as i64
+                }
+                _ => return Ok(left),
+            };
+        }
+    }
+
+    fn parse_shift(&mut self) -> Result<i64, ArithError> {
+        let mut left = self.parse_additive()?;
+        loop {
+            left = match self.peek() {
+                Tok::Shl => {
+                    self.advance();
+                    left << (self.parse_additive()? & 63)
+                }
+                // `>>` is an arithmetic right shift on `i64`.
+                Tok::Shr => {
+                    self.advance();
+                    left >> (self.parse_additive()? & 63)
+                }
+                _ => return Ok(left),
+            };
+        }
+    }
+
+    fn parse_additive(&mut self) -> Result<i64, ArithError> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            left = match self.peek() {
+                Tok::Plus => {
+                    self.advance();
+                    left.wrapping_add(self.parse_multiplicative()?)
+                }
+                Tok::Minus => {
+                    self.advance();
+                    left.wrapping_sub(self.parse_multiplicative()?)
+                }
+                _ => return Ok(left),
+            };
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<i64, ArithError> {
+        let mut left = self.parse_unary()?;
+        loop {
+            left = match self.peek() {
+                Tok::Star => {
+                    self.advance();
+                    left.wrapping_mul(self.parse_unary()?)
+                }
+                Tok::Slash => {
+                    self.advance();
+                    let rhs = self.parse_unary()?;
+                    if rhs == 0 {
+                        return Err(ArithError::DivisionByZero);
+                    }
+                    left.wrapping_div(rhs)
+                }
+                Tok::Percent => {
+                    self.advance();
+                    let rhs = self.parse_unary()?;
+                    if rhs == 0 {
+                        return Err(ArithError::DivisionByZero);
+                    }
+                    left.wrapping_rem(rhs)
+                }
+                _ => return Ok(left),
+            };
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<i64, ArithError> {
+        match self.peek() {
+            Tok::Plus => {
+                self.advance();
+                self.parse_unary()
+            }
+            Tok::Minus => {
+                self.advance();
+                Ok(self.parse_unary()?.wrapping_neg())
+            }
+            Tok::Not => {
+                self.advance();
+                Ok((self.parse_unary()? == 0) as i64)
+            }
+            Tok::Tilde => {
+                self.advance();
+                Ok(!self.parse_unary()?)
+            }
+            _ => self.parse_power(),
+        }
+    }
+
+    /// `**` binds tighter than unary on its left operand but is
+    /// right-associative, so `2 ** 3 ** 2 == 2 ** (3 ** 2)`.
+    fn parse_power(&mut self) -> Result<i64, ArithError> {
+        let base = self.parse_primary()?;
+        if self.peek() == Tok::StarStar {
+            self.advance();
+            let exp = self.parse_unary()?;
+            Ok(ipow(base, exp))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<i64, ArithError> {
+        match self.advance() {
+            Tok::Num(n) => Ok(n),
+            Tok::Ident(start, end) => {
+                let name = &self.tokens_src()[start..end];
+                Ok((self.env)(name))
+            }
+            Tok::LParen => {
+                let value = self.parse_ternary()?;
+                self.eat(Tok::RParen).map_err(|_| ArithError::UnbalancedParens)?;
+                Ok(value)
+            }
+            Tok::Eof => Err(ArithError::UnexpectedEnd),
+            other => Err(ArithError::UnexpectedToken(format!("{:?}", other))),
+        }
+    }
+
+    fn tokens_src(&self) -> &'a str {
+        self.full_src
+    }
+}
+
+fn ipow(base: i64, exp: i64) -> i64 {
+    if exp <= 0 {
+        return if exp == 0 { 1 } else { 0 };
+    }
+    let mut result = 1i64;
+    let mut b = base;
+    let mut e = exp;
+    while e > 0 {
+        if e & 1 == 1 {
+            result = result.wrapping_mul(b);
+        }
+        b = b.wrapping_mul(b);
+        e >>= 1;
+    }
+    result
+}
+
+/// Evaluate the integer expression inside `$(( expr ))`, resolving bare
+/// identifiers through `env` (undefined names are treated as `0`).
+pub fn eval(expr: &str, env: Env) -> Result<i64, ArithError> {
+    let tokens = lex(expr)?;
+    let mut evaluator = Evaluator {
+        tokens,
+        pos: 0,
+        env,
+        full_src: expr,
+        skipping: 0,
+    };
+    let value = evaluator.parse_ternary()?;
+    if evaluator.peek() != Tok::Eof {
+        return Err(ArithError::UnexpectedToken(format!(
+            "{:?}",
+            evaluator.peek()
+        )));
+    }
+    Ok(value)
+}
@@ -14,6 +14,8 @@
 //!   -v, --show-nonprinting  Use ^ and M- notation for non-printable
 
 use core::fmt::Write;
+use super::scheme;
+use super::sink;
 
 pub fn run(args: &[&str]) -> i32 {
     let mut number = false;
@@ -61,21 +63,55 @@ pub fn run(args: &[&str]) -> i32 {
     exit_code
 }
 
+/// Fixed-size scratch buffer so a formatted fragment (e.g. the `{:6}\t`
+/// line-number prefix) can be built with `write!` before handing the bytes
+/// to the injected stdout sink.
+struct LineFmt {
+    buf: [u8; 16],
+    len: usize,
+}
+
+impl LineFmt {
+    fn new() -> Self {
+        Self { buf: [0; 16], len: 0 }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl Write for LineFmt {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let bytes = s.as_bytes();
+        let end = (self.len + bytes.len()).min(self.buf.len());
+        let count = end - self.len;
+        self.buf[self.len..end].copy_from_slice(&bytes[..count]);
+        self.len = end;
+        Ok(())
+    }
+}
+
 fn cat_file(filename: &str, number: bool, number_nonblank: bool, squeeze_blank: bool,
-            show_ends: bool, show_tabs: bool, line_num: &mut usize, 
+            show_ends: bool, show_tabs: bool, line_num: &mut usize,
             prev_empty: &mut bool, exit_code: &mut i32) -> Result<(), ()> {
+    let pid = sink::current_pid();
+
+    if filename == "-" {
+        // No stdin scheme is registered (the shell doesn't expose one);
+        // matches the previous behavior of reading nothing.
+        return Ok(());
+    }
+
+    let (scheme_name, handle) = scheme::open_path(filename).map_err(|_| ())?;
+
     let mut buffer = [0u8; 4096];
-    
+
     loop {
-        let bytes_read = if filename == "-" {
-            // Read from stdin
-            0
-        } else {
-            // For now, simulate reading (actual implementation would use VFS)
-            0
-        };
+        let bytes_read = scheme::read(scheme_name, handle, &mut buffer);
 
         if bytes_read == 0 {
+            scheme::close(scheme_name, handle);
             break;
         }
 
@@ -89,25 +125,27 @@ fn cat_file(filename: &str, number: bool, number_nonblank: bool, squeeze_blank:
                 } else {
                     *prev_empty = true;
                     if number || (number_nonblank && *prev_empty) {
-                        print!("{:6}\t", *line_num);
+                        let mut fmt = LineFmt::new();
+                        let _ = write!(fmt, "{:6}\t", *line_num);
+                        sink::write_stdout(pid, fmt.as_bytes());
                         *line_num += 1;
                     }
                     if show_ends {
-                        print!("$\n");
+                        sink::write_stdout(pid, b"$\n");
                     } else {
-                        print!("\n");
+                        sink::write_stdout(pid, b"\n");
                     }
                 }
             } else {
                 *prev_empty = false;
                 if show_tabs && byte == b'\t' {
-                    print!("^I");
+                    sink::write_stdout(pid, b"^I");
                 } else if byte < 32 && byte != b'\t' && byte != b'\n' {
-                    print!("^{}", (byte + 64) as char);
+                    sink::write_stdout(pid, &[b'^', byte + 64]);
                 } else if byte >= 128 {
-                    print!("M-{}", (byte - 128 + 64) as char);
+                    sink::write_stdout(pid, &[b'M', b'-', byte - 128 + 64]);
                 } else {
-                    print!("{}", byte as char);
+                    sink::write_stdout(pid, &[byte]);
                 }
             }
             i += 1;
@@ -76,8 +76,12 @@ pub fn run(args: &[&str]) -> i32 {
         i += 1;
     }
 
-    // Get current time (placeholder - would use kernel time)
-    let now = 1704067200;  // Placeholder timestamp
+    // This tree has no RTC driver to read a real wall-clock epoch from
+    // yet, so the best real clock source available is the kernel's own
+    // monotonic time subsystem (seconds elapsed since calibration, i.e.
+    // roughly since boot) rather than the fixed placeholder this used to
+    // print unconditionally.
+    let now = (crate::lib_core::time::now_ns() / 1_000_000_000) as i64;
 
     let output = format_date(now, &format, utc);
     println!("{}", output);
@@ -85,7 +89,81 @@ pub fn run(args: &[&str]) -> i32 {
     0
 }
 
-fn format_date(timestamp: i64, format: &str, _utc: bool) -> String {
+/// A Unix timestamp decomposed into civil calendar fields.
+struct CivilDateTime {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+    /// 0 = Sunday .. 6 = Saturday.
+    weekday: i64,
+    /// Day of year, 1-based.
+    year_day: i64,
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+const WEEKDAY_NAMES: [&str; 7] = [
+    "Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday",
+];
+
+/// Decompose a Unix timestamp into year/month/day/hour/minute/second plus
+/// weekday and day-of-year, using Howard Hinnant's integer civil-calendar
+/// algorithm (http://howardhinnant.github.io/date_algorithms.html) -- no
+/// floating point or lookup tables needed.
+fn civil_from_timestamp(timestamp: i64) -> CivilDateTime {
+    let z_orig = timestamp.div_euclid(86400);
+    let secs = timestamp.rem_euclid(86400);
+
+    let hour = secs / 3600;
+    let minute = (secs % 3600) / 60;
+    let second = secs % 60;
+
+    let z = z_orig + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let mut year = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    if month <= 2 {
+        year += 1;
+    }
+
+    // 1970-01-01 was a Thursday (weekday 4).
+    let weekday = (z_orig % 7 + 4).rem_euclid(7);
+
+    let year_day = day_of_year(year, month, day);
+
+    CivilDateTime { year, month, day, hour, minute, second, weekday, year_day }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+const DAYS_BEFORE_MONTH: [i64; 12] = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+
+/// Day of year (1-366) for a civil `(year, month, day)`.
+fn day_of_year(year: i64, month: i64, day: i64) -> i64 {
+    let mut n = DAYS_BEFORE_MONTH[(month - 1) as usize] + day;
+    if month > 2 && is_leap_year(year) {
+        n += 1;
+    }
+    n
+}
+
+fn format_date(timestamp: i64, format: &str, utc: bool) -> String {
+    let dt = civil_from_timestamp(timestamp);
+    let tz_name = if utc { "UTC" } else { "UTC" };
+    let tz_offset = "+0000";
+
     let mut result = String::new();
     let mut chars = format.chars().peekable();
 
@@ -94,42 +172,84 @@ fn format_date(timestamp: i64, format: &str, _utc: bool) -> String {
             if let Some(&next) = chars.peek() {
                 match next {
                     '%' => result.push('%'),
-                    'a' => result.push_str("Sun"),
-                    'A' => result.push_str("Sunday"),
-                    'b' => result.push_str("Jan"),
-                    'B' => result.push_str("January"),
-                    'c' => result.push_str("Sat Jan  1 00:00:00 2000"),
-                    'C' => result.push_str("20"),
-                    'd' => result.push_str("01"),
-                    'D' => result.push_str("01/01/00"),
-                    'e' => result.push_str(" 1"),
-                    'F' => result.push_str("2000-01-01"),
-                    'h' => result.push_str("Jan"),
-                    'H' => result.push_str("00"),
-                    'I' => result.push_str("12"),
-                    'j' => result.push_str("001"),
-                    'm' => result.push_str("01"),
-                    'M' => result.push_str("00"),
+                    'a' => result.push_str(&WEEKDAY_NAMES[dt.weekday as usize][..3]),
+                    'A' => result.push_str(WEEKDAY_NAMES[dt.weekday as usize]),
+                    'b' | 'h' => result.push_str(&MONTH_NAMES[(dt.month - 1) as usize][..3]),
+                    'B' => result.push_str(MONTH_NAMES[(dt.month - 1) as usize]),
+                    'c' => {
+                        let _ = write!(
+                            result,
+                            "{} {} {:2} {:02}:{:02}:{:02} {} {}",
+                            &WEEKDAY_NAMES[dt.weekday as usize][..3],
+                            &MONTH_NAMES[(dt.month - 1) as usize][..3],
+                            dt.day,
+                            dt.hour,
+                            dt.minute,
+                            dt.second,
+                            tz_name,
+                            dt.year
+                        );
+                    }
+                    'C' => { let _ = write!(result, "{:02}", dt.year.div_euclid(100)); }
+                    'd' => { let _ = write!(result, "{:02}", dt.day); }
+                    'D' => {
+                        let _ = write!(result, "{:02}/{:02}/{:02}", dt.month, dt.day, dt.year.rem_euclid(100));
+                    }
+                    'e' => { let _ = write!(result, "{:2}", dt.day); }
+                    'F' => { let _ = write!(result, "{:04}-{:02}-{:02}", dt.year, dt.month, dt.day); }
+                    'H' => { let _ = write!(result, "{:02}", dt.hour); }
+                    'I' => {
+                        let hour12 = if dt.hour % 12 == 0 { 12 } else { dt.hour % 12 };
+                        let _ = write!(result, "{:02}", hour12);
+                    }
+                    'j' => { let _ = write!(result, "{:03}", dt.year_day); }
+                    'k' => { let _ = write!(result, "{:2}", dt.hour); }
+                    'l' => {
+                        let hour12 = if dt.hour % 12 == 0 { 12 } else { dt.hour % 12 };
+                        let _ = write!(result, "{:2}", hour12);
+                    }
+                    'm' => { let _ = write!(result, "{:02}", dt.month); }
+                    'M' => { let _ = write!(result, "{:02}", dt.minute); }
                     'n' => result.push('\n'),
-                    'p' => result.push_str("AM"),
-                    'P' => result.push_str("am"),
-                    'r' => result.push_str("12:00:00 AM"),
-                    'R' => result.push_str("00:00"),
-                    's' => result.push_str("946684800"),
-                    'S' => result.push_str("00"),
+                    'N' => result.push_str("000000000"),
+                    'p' => result.push_str(if dt.hour < 12 { "AM" } else { "PM" }),
+                    'P' => result.push_str(if dt.hour < 12 { "am" } else { "pm" }),
+                    'r' => {
+                        let hour12 = if dt.hour % 12 == 0 { 12 } else { dt.hour % 12 };
+                        let ampm = if dt.hour < 12 { "AM" } else { "PM" };
+                        let _ = write!(result, "{:02}:{:02}:{:02} {}", hour12, dt.minute, dt.second, ampm);
+                    }
+                    'R' => { let _ = write!(result, "{:02}:{:02}", dt.hour, dt.minute); }
+                    's' => { let _ = write!(result, "{}", timestamp); }
+                    'S' => { let _ = write!(result, "{:02}", dt.second); }
                     't' => result.push('\t'),
-                    'T' => result.push_str("00:00:00"),
-                    'u' => result.push_str("1"),
-                    'U' => result.push_str("00"),
-                    'V' => result.push_str("01"),
-                    'w' => result.push_str("0"),
-                    'W' => result.push_str("00"),
-                    'x' => result.push_str("01/01/00"),
-                    'X' => result.push_str("00:00:00"),
-                    'y' => result.push_str("00"),
-                    'Y' => result.push_str("2000"),
-                    'z' => result.push_str("+0000"),
-                    'Z' => result.push_str("UTC"),
+                    'T' => { let _ = write!(result, "{:02}:{:02}:{:02}", dt.hour, dt.minute, dt.second); }
+                    'u' => { let _ = write!(result, "{}", if dt.weekday == 0 { 7 } else { dt.weekday }); }
+                    'U' => {
+                        // Week number with Sunday as the first day: days
+                        // before this one that share the year, offset by
+                        // the first Sunday's position, divided by 7.
+                        let jan1_weekday = (dt.weekday - (dt.year_day - 1)).rem_euclid(7);
+                        let week = (dt.year_day - 1 + jan1_weekday) / 7;
+                        let _ = write!(result, "{:02}", week);
+                    }
+                    'V' => { let _ = write!(result, "{:02}", iso_week_number(&dt)); }
+                    'w' => { let _ = write!(result, "{}", dt.weekday); }
+                    'W' => {
+                        // Week number with Monday as the first day.
+                        let jan1_weekday_mon = (dt.weekday - (dt.year_day - 1)).rem_euclid(7);
+                        let offset = (jan1_weekday_mon + 6) % 7;
+                        let week = (dt.year_day - 1 + offset) / 7;
+                        let _ = write!(result, "{:02}", week);
+                    }
+                    'x' => {
+                        let _ = write!(result, "{:02}/{:02}/{:02}", dt.month, dt.day, dt.year.rem_euclid(100));
+                    }
+                    'X' => { let _ = write!(result, "{:02}:{:02}:{:02}", dt.hour, dt.minute, dt.second); }
+                    'y' => { let _ = write!(result, "{:02}", dt.year.rem_euclid(100)); }
+                    'Y' => { let _ = write!(result, "{}", dt.year); }
+                    'z' => result.push_str(tz_offset),
+                    'Z' => result.push_str(tz_name),
                     _ => result.push(next),
                 }
                 chars.next();
@@ -141,3 +261,28 @@ fn format_date(timestamp: i64, format: &str, _utc: bool) -> String {
 
     result
 }
+
+/// ISO 8601 week number (1-53), with Monday as the first day of the week
+/// and the week containing the year's first Thursday counted as week 1.
+fn iso_week_number(dt: &CivilDateTime) -> i64 {
+    // ISO weekday: 1 (Monday) .. 7 (Sunday).
+    let iso_weekday = if dt.weekday == 0 { 7 } else { dt.weekday };
+    let ordinal = dt.year_day - iso_weekday + 10;
+    if ordinal < 1 {
+        // Falls in the last ISO week of the previous year (52 or 53,
+        // depending on whether that year starts on a Thursday or the
+        // preceding year was a leap year ending on a Wednesday).
+        let prev_year = dt.year - 1;
+        let prev_days = if is_leap_year(prev_year) { 366 } else { 365 };
+        return iso_week_number(&CivilDateTime {
+            year: prev_year,
+            year_day: prev_days,
+            ..*dt
+        });
+    }
+    let days_in_year = if is_leap_year(dt.year) { 366 } else { 365 };
+    if ordinal > days_in_year {
+        return 1;
+    }
+    ordinal / 7 + 1
+}
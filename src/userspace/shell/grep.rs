@@ -24,17 +24,28 @@
 //!   -B NUM, --before-context=NUM  Print NUM lines of leading context
 //!   -C NUM, --context=NUM     Print NUM lines of output context
 //!   -r, --recursive           Recursively search directories
+//!   -g GLOB, --glob=GLOB       Include (or, with a `!` prefix, exclude) files
+//!                              matching GLOB during recursive search
+//!   -F, --fixed-strings       Treat PATTERN as a literal string, not a regex
 //!   --color=WHEN              Colorize the output
+//!   --type NAME                Only search files of type NAME (recursive)
+//!   --type-not NAME             Skip files of type NAME (recursive)
+//!   --type-list                 List the known type -> glob definitions, then exit
+//!   -a, --text                  Treat binary files as text
+//!   -I                          Skip binary files entirely
 
 use core::fmt::Write;
+use super::regex::Regex;
+use super::scheme;
+use super::sink;
 
 pub fn run(args: &[&str]) -> i32 {
     let mut opts = GrepOptions::default();
-    
+
     let mut i = 0;
     while i < args.len() {
         let arg = args[i];
-        
+
         if arg == "-e" {
             i += 1;
             if i < args.len() {
@@ -75,6 +86,43 @@ pub fn run(args: &[&str]) -> i32 {
             opts.context = arg[2..].parse().unwrap_or(0);
         } else if arg == "-r" || arg == "--recursive" {
             opts.recursive = true;
+        } else if arg == "-F" || arg == "--fixed-strings" {
+            opts.fixed_strings = true;
+        } else if arg == "-a" || arg == "--text" {
+            opts.force_text = true;
+        } else if arg == "-I" {
+            opts.skip_binary = true;
+        } else if arg == "-g" || arg == "--glob" {
+            i += 1;
+            if i < args.len() {
+                opts.globs.push(args[i]);
+            }
+        } else if let Some(glob) = arg.strip_prefix("--glob=") {
+            opts.globs.push(glob);
+        } else if arg == "--color" {
+            opts.color = ColorWhen::Always;
+        } else if let Some(when) = arg.strip_prefix("--color=") {
+            opts.color = match when {
+                "always" => ColorWhen::Always,
+                "never" => ColorWhen::Never,
+                _ => ColorWhen::Auto,
+            };
+        } else if arg == "--type-list" {
+            opts.type_list = true;
+        } else if arg == "--type" {
+            i += 1;
+            if i < args.len() {
+                opts.type_include.push(args[i]);
+            }
+        } else if let Some(name) = arg.strip_prefix("--type=") {
+            opts.type_include.push(name);
+        } else if arg == "--type-not" {
+            i += 1;
+            if i < args.len() {
+                opts.type_exclude.push(args[i]);
+            }
+        } else if let Some(name) = arg.strip_prefix("--type-not=") {
+            opts.type_exclude.push(name);
         } else if !arg.starts_with('-') {
             if opts.files.is_empty() && opts.patterns.is_empty() {
                 opts.patterns.push(arg);
@@ -85,6 +133,13 @@ pub fn run(args: &[&str]) -> i32 {
         i += 1;
     }
 
+    if opts.type_list {
+        for (name, globs) in FILE_TYPES {
+            println!("{}: {}", name, globs.join(", "));
+        }
+        return 0;
+    }
+
     if opts.patterns.is_empty() && opts.pattern_file.is_none() {
         eprintln!("grep: missing pattern");
         return 1;
@@ -94,7 +149,74 @@ pub fn run(args: &[&str]) -> i32 {
         opts.files.push("-");
     }
 
-    0
+    let mut pattern_sources: Vec<String> = opts.patterns.iter().map(|p| p.to_string()).collect();
+    if let Some(pattern_file) = opts.pattern_file {
+        if read_lines(pattern_file, |line| {
+            if !line.is_empty() {
+                pattern_sources.push(line.to_string());
+            }
+        })
+        .is_err()
+            && !opts.no_errors
+        {
+            eprintln!("grep: {}: No such file or directory", pattern_file);
+        }
+    }
+
+    let matchers: Vec<Matcher> = pattern_sources
+        .iter()
+        .map(|p| {
+            if opts.fixed_strings {
+                Matcher::Fixed(FixedString::new(p, opts.ignore_case))
+            } else {
+                Matcher::Pattern(Regex::with_ignore_case(p, opts.ignore_case))
+            }
+        })
+        .collect();
+
+    let pid = sink::current_pid();
+    let show_filename = opts.show_filename || opts.files.len() > 1;
+
+    // `-r`/`--recursive` has never actually descended into directories in
+    // this tree -- there's no readdir-style VFS primitive to walk with
+    // yet (`ls -R` is equally unimplemented for the same reason). Filter
+    // the flat file list we do have by `-g`/`--glob` now, so the moment a
+    // directory walker exists it only has to feed names through
+    // `file_matches_globs` to get ripgrep-style `-g` filtering for free.
+    let glob_filters = compile_globs(&opts.globs);
+    let type_filters = compile_type_globs(&opts.type_include, &opts.type_exclude);
+    let mut search_files: Vec<&str> = opts.files.clone();
+    if opts.recursive {
+        if !glob_filters.is_empty() {
+            search_files.retain(|f| file_matches_globs(f, &glob_filters));
+        }
+        if !type_filters.is_empty() {
+            search_files.retain(|f| file_matches_globs(f, &type_filters));
+        }
+    }
+
+    let mut any_matched = false;
+    let mut any_error = false;
+
+    for &filename in &search_files {
+        match grep_file(filename, &matchers, &opts, pid, show_filename) {
+            Ok(matched) => any_matched |= matched,
+            Err(()) => {
+                any_error = true;
+                if !opts.no_errors {
+                    eprintln!("grep: {}: No such file or directory", filename);
+                }
+            }
+        }
+    }
+
+    if any_error {
+        2
+    } else if any_matched {
+        0
+    } else {
+        1
+    }
 }
 
 #[derive(Debug, Default)]
@@ -116,52 +238,445 @@ struct GrepOptions {
     before_context: usize,
     context: usize,
     recursive: bool,
+    /// `-F`/`--fixed-strings`: match each pattern as a literal substring
+    /// (via [`FixedString`]'s Boyer-Moore-Horspool scanner) instead of
+    /// compiling it as a regex.
+    fixed_strings: bool,
+    /// `-g GLOB` / `-g !GLOB` filename filters for recursive search: an
+    /// include glob a candidate file's name must match, or (with a `!`
+    /// prefix) an exclude glob it must not match. See
+    /// [`compile_globs`]/[`file_matches_globs`].
+    globs: Vec<&'static str>,
+    /// `--type NAME`: during recursive search, only files matching one of
+    /// NAME's globs (see [`FILE_TYPES`]) are searched.
+    type_include: Vec<&'static str>,
+    /// `--type-not NAME`: during recursive search, files matching any of
+    /// NAME's globs are skipped.
+    type_exclude: Vec<&'static str>,
+    /// `--type-list`: print [`FILE_TYPES`] and exit without searching.
+    type_list: bool,
+    /// `--color`/`--color=WHEN`: whether matches get ANSI-highlighted.
+    color: ColorWhen,
+    /// `-a`/`--text`: treat every file as text, disabling the binary
+    /// short-circuit regardless of its contents.
+    force_text: bool,
+    /// `-I`: skip binary files entirely, emitting nothing for them.
+    skip_binary: bool,
 }
 
-fn grep_file(filename: &str, pattern: &str, opts: &GrepOptions) -> i32 {
-    let mut matched = false;
-    let mut count = 0;
-    let mut line_num = 0;
+/// How many leading bytes of a file [`file_looks_binary`] inspects for a
+/// NUL byte before giving up and assuming text, mirroring grep/ripgrep's
+/// "sniff the first chunk" heuristic.
+const BINARY_PEEK_SIZE: usize = 512;
 
-    // Simulate reading lines
-    let _lines: Vec<&str> = Vec::new();
+/// Does `filename` look like binary content? Peeks at its first
+/// [`BINARY_PEEK_SIZE`] bytes and reports true if any is a NUL byte --
+/// the same cheap heuristic grep/ripgrep use, since genuine text rarely
+/// contains one.
+fn file_looks_binary(filename: &str) -> Result<bool, ()> {
+    if filename == "-" {
+        return Ok(false);
+    }
 
-    if opts.count_only {
-        if opts.quiet {
-            if count > 0 {
-                return 0;
+    let (scheme_name, handle) = scheme::open_path(filename).map_err(|_| ())?;
+    let mut buffer = [0u8; BINARY_PEEK_SIZE];
+    let bytes_read = scheme::read(scheme_name, handle, &mut buffer);
+    scheme::close(scheme_name, handle);
+
+    Ok(buffer[..bytes_read].contains(&0))
+}
+
+/// `--color=WHEN` setting.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ColorWhen {
+    Never,
+    Always,
+    #[default]
+    Auto,
+}
+
+const COLOR_RESET: &str = "\x1b[0m";
+const COLOR_MATCH: &str = "\x1b[01;31m";
+const COLOR_FILENAME: &str = "\x1b[35m";
+const COLOR_LINENO: &str = "\x1b[32m";
+
+/// Is stdout a terminal? This freestanding kernel has no terminal
+/// capability query exposed to userspace yet, so conservatively report
+/// "no" rather than guess -- `--color=auto` is effectively off until one
+/// exists, matching how real grep behaves when run non-interactively.
+fn is_tty() -> bool {
+    false
+}
+
+fn color_enabled(opts: &GrepOptions) -> bool {
+    match opts.color {
+        ColorWhen::Always => true,
+        ColorWhen::Never => false,
+        ColorWhen::Auto => is_tty(),
+    }
+}
+
+/// File-type name -> glob set, for `--type`/`--type-not`/`--type-list`.
+/// Kept as a single sorted table so adding a type is a one-line change.
+const FILE_TYPES: &[(&str, &[&str])] = &[
+    ("c", &["*.c", "*.h"]),
+    ("cpp", &["*.cpp", "*.cc", "*.cxx", "*.hpp", "*.hh"]),
+    ("json", &["*.json"]),
+    ("md", &["*.md", "*.markdown"]),
+    ("py", &["*.py"]),
+    ("rust", &["*.rs"]),
+    ("sh", &["*.sh"]),
+    ("toml", &["*.toml"]),
+];
+
+/// Look up a type name's glob set in [`FILE_TYPES`].
+fn type_globs(name: &str) -> Option<&'static [&'static str]> {
+    FILE_TYPES.iter().find(|(n, _)| *n == name).map(|(_, globs)| *globs)
+}
+
+/// Compile `--type`/`--type-not` names into the same `(Regex, bool)`
+/// exclude-tagged filter list [`file_matches_globs`] expects, so typed
+/// and `-g` filtering share one matching path.
+fn compile_type_globs(include: &[&str], exclude: &[&str]) -> Vec<(Regex, bool)> {
+    let mut filters = Vec::new();
+    for name in include {
+        if let Some(globs) = type_globs(name) {
+            filters.extend(globs.iter().map(|g| (Regex::from_glob(g), false)));
+        }
+    }
+    for name in exclude {
+        if let Some(globs) = type_globs(name) {
+            filters.extend(globs.iter().map(|g| (Regex::from_glob(g), true)));
+        }
+    }
+    filters
+}
+
+/// Either a compiled regex or (under `-F`) a literal needle, sharing the
+/// same `(start, end)`-byte-span `is_match` shape so `grep_file` doesn't
+/// need to care which mode compiled it.
+enum Matcher {
+    Pattern(Regex),
+    Fixed(FixedString),
+}
+
+impl Matcher {
+    fn is_match(&self, text: &str) -> Option<(usize, usize)> {
+        match self {
+            Matcher::Pattern(re) => re.is_match(text),
+            Matcher::Fixed(needle) => needle.is_match(text),
+        }
+    }
+}
+
+/// A literal pattern searched with Boyer-Moore-Horspool: a 256-entry skip
+/// table keyed on the needle's last byte lets a mismatch jump the window
+/// forward by more than one byte instead of just sliding by one, which is
+/// both faster than a naive scan and sidesteps having to escape regex
+/// metacharacters in the pattern at all.
+struct FixedString {
+    needle: Vec<u8>,
+    skip: [usize; 256],
+    ignore_case: bool,
+}
+
+impl FixedString {
+    fn new(pattern: &str, ignore_case: bool) -> Self {
+        let mut needle: Vec<u8> = pattern.bytes().collect();
+        if ignore_case {
+            for b in needle.iter_mut() {
+                *b = b.to_ascii_lowercase();
             }
-            return 1;
         }
-        if opts.show_filename || opts.files.len() > 1 {
-            println!("{}:{}", filename, count);
-        } else {
-            println!("{}", count);
+
+        let mut skip = [needle.len(); 256];
+        if let Some(last) = needle.len().checked_sub(1) {
+            for (i, &b) in needle[..last].iter().enumerate() {
+                skip[b as usize] = last - i;
+            }
         }
-        return 0;
+
+        Self { needle, skip, ignore_case }
     }
 
-    if opts.files_with_match {
-        if matched {
-            if opts.quiet {
-                return 0;
+    /// Returns the byte span of the first match, comparing the window
+    /// right-to-left and advancing by the skip table's entry for the
+    /// window's trailing byte on mismatch.
+    fn is_match(&self, text: &str) -> Option<(usize, usize)> {
+        let m = self.needle.len();
+        if m == 0 {
+            return Some((0, 0));
+        }
+
+        let haystack = text.as_bytes();
+        if haystack.len() < m {
+            return None;
+        }
+
+        let fold = |b: u8| if self.ignore_case { b.to_ascii_lowercase() } else { b };
+
+        let mut pos = 0;
+        while pos + m <= haystack.len() {
+            let mut j = m - 1;
+            loop {
+                if fold(haystack[pos + j]) != self.needle[j] {
+                    break;
+                }
+                if j == 0 {
+                    return Some((pos, pos + m));
+                }
+                j -= 1;
             }
-            println!("{}", filename);
-            return 0;
+            pos += self.skip[fold(haystack[pos + m - 1]) as usize];
         }
-        return 1;
+
+        None
+    }
+}
+
+/// Compile each `-g`/`--glob` entry into a glob regex, tagging it as an
+/// exclude filter if it was given with a `!` prefix.
+fn compile_globs(globs: &[&str]) -> Vec<(Regex, bool)> {
+    globs
+        .iter()
+        .map(|g| {
+            if let Some(rest) = g.strip_prefix('!') {
+                (Regex::from_glob(rest), true)
+            } else {
+                (Regex::from_glob(g), false)
+            }
+        })
+        .collect()
+}
+
+/// Does `name` pass `filters`? It must match at least one include glob
+/// (when any are given) and must not match any exclude glob.
+fn file_matches_globs(name: &str, filters: &[(Regex, bool)]) -> bool {
+    let (excludes, includes): (Vec<_>, Vec<_>) = filters.iter().partition(|(_, exclude)| *exclude);
+
+    if excludes.iter().any(|(re, _)| re.is_match(name).is_some()) {
+        return false;
     }
+    includes.is_empty() || includes.iter().any(|(re, _)| re.is_match(name).is_some())
+}
+
+/// One line held back in the before-context ring while we wait to see
+/// whether an upcoming line matches.
+struct PendingLine {
+    number: usize,
+    text: String,
+}
+
+/// Search `filename` against `patterns`, writing through `pid`'s stdout
+/// sink. Returns whether any line was selected (honoring `-v`).
+fn grep_file(
+    filename: &str,
+    patterns: &[Matcher],
+    opts: &GrepOptions,
+    pid: usize,
+    show_filename: bool,
+) -> Result<bool, ()> {
+    let before_n = opts.before_context.max(opts.context);
+    let after_n = opts.after_context.max(opts.context);
+
+    let is_binary = !opts.force_text && file_looks_binary(filename)?;
+    if is_binary && opts.skip_binary {
+        return Ok(false);
+    }
+
+    let mut before_buf: Vec<PendingLine> = Vec::new();
+    let mut after_remaining = 0usize;
+    let mut last_printed: Option<usize> = None;
+    let mut line_num = 0usize;
+    let mut count = 0usize;
+    let mut matched = false;
+    let mut binary_announced = false;
+    let color = color_enabled(opts);
+
+    read_lines(filename, |line| {
+        line_num += 1;
 
-    if opts.files_without_match {
-        if !matched {
+        let span = patterns.iter().find_map(|p| p.is_match(line));
+        let selected = span.is_some() != opts.invert;
+
+        if selected {
+            matched = true;
+            count += 1;
+
+            if opts.count_only || opts.files_with_match || opts.files_without_match {
+                // These modes only need whether/how many lines matched,
+                // not the lines themselves.
+                return;
+            }
             if opts.quiet {
-                return 0;
+                return;
+            }
+
+            if is_binary {
+                if !binary_announced {
+                    sink::write_stdout(pid, format!("Binary file {} matches\n", filename).as_bytes());
+                    binary_announced = true;
+                }
+                return;
+            }
+
+            print_context_gap(last_printed, line_num, before_n, pid);
+            for pending in before_buf.drain(..) {
+                print_line(filename, show_filename, opts.line_number, pending.number, &pending.text, pid, color, None);
+            }
+
+            if opts.only_matching {
+                if let Some((start, end)) = span {
+                    let matched_text = &line[start..end];
+                    print_line(
+                        filename,
+                        show_filename,
+                        opts.line_number,
+                        line_num,
+                        matched_text,
+                        pid,
+                        color,
+                        Some((0, matched_text.len())),
+                    );
+                }
+            } else {
+                print_line(filename, show_filename, opts.line_number, line_num, line, pid, color, span);
             }
+
+            last_printed = Some(line_num);
+            after_remaining = after_n;
+        } else if after_remaining > 0 {
+            if !(opts.count_only || opts.files_with_match || opts.files_without_match || opts.quiet) {
+                print_line(filename, show_filename, opts.line_number, line_num, line, pid, color, None);
+            }
+            last_printed = Some(line_num);
+            after_remaining -= 1;
+        } else if before_n > 0 {
+            before_buf.push(PendingLine { number: line_num, text: line.to_string() });
+            if before_buf.len() > before_n {
+                before_buf.remove(0);
+            }
+        }
+    })?;
+
+    if opts.count_only {
+        if !opts.quiet {
+            if show_filename {
+                println!("{}:{}", filename, count);
+            } else {
+                println!("{}", count);
+            }
+        }
+    } else if opts.files_with_match {
+        if matched && !opts.quiet {
             println!("{}", filename);
-            return 0;
         }
-        return 1;
+    } else if opts.files_without_match {
+        if !matched && !opts.quiet {
+            println!("{}", filename);
+        }
+    }
+
+    Ok(matched)
+}
+
+/// Print the `--` group separator GNU grep uses between non-adjacent
+/// context blocks, when the gap since the last printed line is more than
+/// one line and there's any context configured at all.
+fn print_context_gap(last_printed: Option<usize>, line_num: usize, before_n: usize, pid: usize) {
+    if before_n == 0 {
+        return;
+    }
+    if let Some(last) = last_printed {
+        if line_num > last + 1 {
+            sink::write_stdout(pid, b"--\n");
+        }
+    }
+}
+
+/// Print one output line, optionally highlighting `highlight` (a byte span
+/// within `text`) in bold red when `color` is set. The filename and line
+/// number prefixes get their own colors, matching grep/ripgrep's defaults.
+#[allow(clippy::too_many_arguments)]
+fn print_line(
+    filename: &str,
+    show_filename: bool,
+    line_number: bool,
+    number: usize,
+    text: &str,
+    pid: usize,
+    color: bool,
+    highlight: Option<(usize, usize)>,
+) {
+    let mut prefix = String::new();
+    if show_filename {
+        if color {
+            let _ = write!(prefix, "{}{}{}:", COLOR_FILENAME, filename, COLOR_RESET);
+        } else {
+            let _ = write!(prefix, "{}:", filename);
+        }
+    }
+    if line_number {
+        if color {
+            let _ = write!(prefix, "{}{}{}:", COLOR_LINENO, number, COLOR_RESET);
+        } else {
+            let _ = write!(prefix, "{}:", number);
+        }
+    }
+    sink::write_stdout(pid, prefix.as_bytes());
+
+    match highlight {
+        Some((start, end)) if color => {
+            let mut body = String::new();
+            body.push_str(&text[..start]);
+            body.push_str(COLOR_MATCH);
+            body.push_str(&text[start..end]);
+            body.push_str(COLOR_RESET);
+            body.push_str(&text[end..]);
+            sink::write_stdout(pid, body.as_bytes());
+        }
+        _ => sink::write_stdout(pid, text.as_bytes()),
+    }
+
+    sink::write_stdout(pid, b"\n");
+}
+
+/// Read `filename` through the scheme registry (matching how `cat` reads
+/// files) and invoke `on_line` with each complete line, sans the
+/// trailing `\n`. Bytes are widened to `char` directly rather than
+/// UTF-8 decoded, matching the rest of this module's byte-oriented I/O.
+fn read_lines(filename: &str, mut on_line: impl FnMut(&str)) -> Result<(), ()> {
+    if filename == "-" {
+        // No stdin scheme is registered (the shell doesn't expose one).
+        return Ok(());
+    }
+
+    let (scheme_name, handle) = scheme::open_path(filename).map_err(|_| ())?;
+
+    let mut buffer = [0u8; 4096];
+    let mut line = String::new();
+
+    loop {
+        let bytes_read = scheme::read(scheme_name, handle, &mut buffer);
+        if bytes_read == 0 {
+            break;
+        }
+
+        for &byte in &buffer[..bytes_read] {
+            if byte == b'\n' {
+                on_line(&line);
+                line.clear();
+            } else {
+                line.push(byte as char);
+            }
+        }
+    }
+
+    scheme::close(scheme_name, handle);
+
+    if !line.is_empty() {
+        on_line(&line);
     }
 
-    0
+    Ok(())
 }
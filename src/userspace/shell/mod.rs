@@ -3,6 +3,13 @@
 //! This module provides essential Unix-like utilities implemented in Rust
 //! for use in the kernel shell environment.
 
+pub mod tokenizer;
+pub mod parser;
+pub mod arithmetic;
+pub mod scheme;
+pub mod sink;
+pub mod regex;
+
 pub mod cat;
 pub mod echo;
 pub mod ls;
@@ -0,0 +1,377 @@
+//! Shell Parser
+//!
+//! Recursive-descent parser that turns the flat `Token` stream produced by
+//! `Tokenizer` into an executable AST. Mirrors the parser structure used in
+//! lightweight compilers like holey-bytes/mclangc: a `Parser` struct holding
+//! a cursor into the token slice, one method per grammar production, and
+//! `ParseError`s that carry the offending token's span for recovery-friendly
+//! diagnostics.
+
+use super::tokenizer::{Token, TokenType};
+
+/// A single `simple command`: optional leading assignments, the command
+/// words, and any redirections attached to it.
+#[derive(Debug, Clone)]
+pub struct SimpleCommand {
+    pub assignments: Vec<String>,
+    pub words: Vec<String>,
+    pub redirects: Vec<Redirect>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Redirect {
+    pub kind: TokenType,
+    pub target: String,
+}
+
+/// Compound and simple statement forms.
+#[derive(Debug, Clone)]
+pub enum AstNode {
+    Command(SimpleCommand),
+    Pipeline(Vec<AstNode>),
+    And(Box<AstNode>, Box<AstNode>),
+    Or(Box<AstNode>, Box<AstNode>),
+    Sequence(Vec<AstNode>),
+    Subshell(Box<AstNode>),
+    BraceGroup(Vec<AstNode>),
+    If {
+        cond: Box<AstNode>,
+        body: Vec<AstNode>,
+        elifs: Vec<(AstNode, Vec<AstNode>)>,
+        else_body: Option<Vec<AstNode>>,
+    },
+    For {
+        var: String,
+        words: Vec<String>,
+        body: Vec<AstNode>,
+    },
+    While {
+        cond: Box<AstNode>,
+        body: Vec<AstNode>,
+    },
+    Until {
+        cond: Box<AstNode>,
+        body: Vec<AstNode>,
+    },
+    Case {
+        word: String,
+        arms: Vec<(Vec<String>, Vec<AstNode>)>,
+    },
+    Function {
+        name: String,
+        body: Box<AstNode>,
+    },
+}
+
+/// A parse failure, carrying the offending token's span so the caller can
+/// point at the exact source range.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl ParseError {
+    fn at(token: &Token, message: impl Into<String>) -> Self {
+        ParseError {
+            message: message.into(),
+            start: token.start,
+            end: token.end,
+        }
+    }
+}
+
+pub struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(tokens: &'a [Token]) -> Self {
+        Parser { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos.min(self.tokens.len() - 1)]
+    }
+
+    fn peek_type(&self) -> TokenType {
+        self.peek().token_type
+    }
+
+    fn advance(&mut self) -> &Token {
+        let token = &self.tokens[self.pos.min(self.tokens.len() - 1)];
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn check(&self, ttype: TokenType) -> bool {
+        self.peek_type() == ttype
+    }
+
+    fn expect(&mut self, ttype: TokenType, what: &str) -> Result<Token, ParseError> {
+        if self.check(ttype) {
+            Ok(self.advance().clone())
+        } else {
+            Err(ParseError::at(
+                self.peek(),
+                format!("expected {}, found {:?}", what, self.peek_type()),
+            ))
+        }
+    }
+
+    fn skip_terminators(&mut self) {
+        while matches!(self.peek_type(), TokenType::Newline | TokenType::Semicolon) {
+            self.advance();
+        }
+    }
+
+    /// Parse the whole token stream into a sequence of top-level statements.
+    pub fn parse(tokens: &[Token]) -> Result<Vec<AstNode>, ParseError> {
+        let mut parser = Parser::new(tokens);
+        let mut nodes = Vec::new();
+        parser.skip_terminators();
+        while !parser.check(TokenType::Eof) {
+            nodes.push(parser.parse_and_or()?);
+            parser.skip_terminators();
+        }
+        Ok(nodes)
+    }
+
+    /// Parse a list of statements until one of `terminators` is the next
+    /// token (used for compound-keyword bodies).
+    fn parse_statement_list(&mut self, terminators: &[TokenType]) -> Result<Vec<AstNode>, ParseError> {
+        let mut nodes = Vec::new();
+        self.skip_terminators();
+        while !terminators.contains(&self.peek_type()) && !self.check(TokenType::Eof) {
+            nodes.push(self.parse_and_or()?);
+            self.skip_terminators();
+        }
+        Ok(nodes)
+    }
+
+    fn parse_and_or(&mut self) -> Result<AstNode, ParseError> {
+        let mut left = self.parse_pipeline()?;
+        loop {
+            match self.peek_type() {
+                TokenType::And => {
+                    self.advance();
+                    self.skip_terminators_inline();
+                    let right = self.parse_pipeline()?;
+                    left = AstNode::And(Box::new(left), Box::new(right));
+                }
+                TokenType::Or => {
+                    self.advance();
+                    self.skip_terminators_inline();
+                    let right = self.parse_pipeline()?;
+                    left = AstNode::Or(Box::new(left), Box::new(right));
+                }
+                _ => break,
+            }
+        }
+        Ok(left)
+    }
+
+    /// Newlines after `&&`/`||` are allowed to continue the command.
+    fn skip_terminators_inline(&mut self) {
+        while self.check(TokenType::Newline) {
+            self.advance();
+        }
+    }
+
+    fn parse_pipeline(&mut self) -> Result<AstNode, ParseError> {
+        let mut stages = vec![self.parse_compound_or_simple()?];
+        while self.check(TokenType::Pipe) {
+            self.advance();
+            self.skip_terminators_inline();
+            stages.push(self.parse_compound_or_simple()?);
+        }
+        if stages.len() == 1 {
+            Ok(stages.pop().unwrap())
+        } else {
+            Ok(AstNode::Pipeline(stages))
+        }
+    }
+
+    fn parse_compound_or_simple(&mut self) -> Result<AstNode, ParseError> {
+        match self.peek_type() {
+            TokenType::If => self.parse_if(),
+            TokenType::For => self.parse_for(),
+            TokenType::While => self.parse_while_until(false),
+            TokenType::Until => self.parse_while_until(true),
+            TokenType::Case => self.parse_case(),
+            TokenType::Function => self.parse_function(),
+            TokenType::LParen => self.parse_subshell(),
+            TokenType::LBrace => self.parse_brace_group(),
+            _ => Ok(AstNode::Command(self.parse_simple_command()?)),
+        }
+    }
+
+    fn parse_simple_command(&mut self) -> Result<SimpleCommand, ParseError> {
+        let mut cmd = SimpleCommand {
+            assignments: Vec::new(),
+            words: Vec::new(),
+            redirects: Vec::new(),
+        };
+
+        loop {
+            match self.peek_type() {
+                TokenType::Assignment => {
+                    cmd.assignments.push(self.advance().text.clone());
+                }
+                TokenType::Word | TokenType::Number => {
+                    cmd.words.push(self.advance().text.clone());
+                }
+                TokenType::RedirectIn
+                | TokenType::RedirectOut
+                | TokenType::Append
+                | TokenType::RedirectErr
+                | TokenType::RedirectErrOut
+                | TokenType::DupIn
+                | TokenType::DupOut
+                | TokenType::HereDoc
+                | TokenType::Clobber => {
+                    let kind = self.advance().token_type;
+                    let target = self.expect(TokenType::Word, "redirection target")?.text;
+                    cmd.redirects.push(Redirect { kind, target });
+                }
+                _ => break,
+            }
+        }
+
+        if cmd.words.is_empty() && cmd.assignments.is_empty() && cmd.redirects.is_empty() {
+            return Err(ParseError::at(self.peek(), "expected a command"));
+        }
+
+        Ok(cmd)
+    }
+
+    fn parse_subshell(&mut self) -> Result<AstNode, ParseError> {
+        self.expect(TokenType::LParen, "(")?;
+        let body = self.parse_statement_list(&[TokenType::RParen])?;
+        self.expect(TokenType::RParen, ")")?;
+        Ok(AstNode::Subshell(Box::new(AstNode::Sequence(body))))
+    }
+
+    fn parse_brace_group(&mut self) -> Result<AstNode, ParseError> {
+        self.expect(TokenType::LBrace, "{")?;
+        let body = self.parse_statement_list(&[TokenType::RBrace])?;
+        self.expect(TokenType::RBrace, "}")?;
+        Ok(AstNode::BraceGroup(body))
+    }
+
+    fn parse_if(&mut self) -> Result<AstNode, ParseError> {
+        self.expect(TokenType::If, "if")?;
+        let cond = Box::new(self.parse_and_or()?);
+        self.skip_terminators();
+        self.expect(TokenType::Then, "then")?;
+        let body = self.parse_statement_list(&[TokenType::Elif, TokenType::Else, TokenType::Fi])?;
+
+        let mut elifs = Vec::new();
+        while self.check(TokenType::Elif) {
+            self.advance();
+            let elif_cond = self.parse_and_or()?;
+            self.skip_terminators();
+            self.expect(TokenType::Then, "then")?;
+            let elif_body =
+                self.parse_statement_list(&[TokenType::Elif, TokenType::Else, TokenType::Fi])?;
+            elifs.push((elif_cond, elif_body));
+        }
+
+        let else_body = if self.check(TokenType::Else) {
+            self.advance();
+            Some(self.parse_statement_list(&[TokenType::Fi])?)
+        } else {
+            None
+        };
+
+        self.expect(TokenType::Fi, "fi")?;
+        Ok(AstNode::If {
+            cond,
+            body,
+            elifs,
+            else_body,
+        })
+    }
+
+    fn parse_for(&mut self) -> Result<AstNode, ParseError> {
+        self.expect(TokenType::For, "for")?;
+        let var = self.expect(TokenType::Word, "loop variable")?.text;
+        self.skip_terminators();
+
+        let mut words = Vec::new();
+        if self.check(TokenType::In) {
+            self.advance();
+            while self.check(TokenType::Word) {
+                words.push(self.advance().text.clone());
+            }
+        }
+        self.skip_terminators();
+        self.expect(TokenType::Do, "do")?;
+        let body = self.parse_statement_list(&[TokenType::Done])?;
+        self.expect(TokenType::Done, "done")?;
+        Ok(AstNode::For { var, words, body })
+    }
+
+    fn parse_while_until(&mut self, until: bool) -> Result<AstNode, ParseError> {
+        self.advance(); // `while` or `until`
+        let cond = Box::new(self.parse_and_or()?);
+        self.skip_terminators();
+        self.expect(TokenType::Do, "do")?;
+        let body = self.parse_statement_list(&[TokenType::Done])?;
+        self.expect(TokenType::Done, "done")?;
+        if until {
+            Ok(AstNode::Until { cond, body })
+        } else {
+            Ok(AstNode::While { cond, body })
+        }
+    }
+
+    fn parse_case(&mut self) -> Result<AstNode, ParseError> {
+        self.expect(TokenType::Case, "case")?;
+        let word = self.expect(TokenType::Word, "case word")?.text;
+        self.skip_terminators();
+        self.expect(TokenType::In, "in")?;
+        self.skip_terminators();
+
+        let mut arms = Vec::new();
+        while !self.check(TokenType::Esac) && !self.check(TokenType::Eof) {
+            let mut patterns = vec![self.expect(TokenType::Word, "case pattern")?.text];
+            while self.check(TokenType::Pipe) {
+                self.advance();
+                patterns.push(self.expect(TokenType::Word, "case pattern")?.text);
+            }
+            self.expect(TokenType::RParen, ")")?;
+            let body = self.parse_statement_list(&[TokenType::Semicolon, TokenType::Esac]);
+            let body = body?;
+            if self.check(TokenType::Semicolon) {
+                self.advance();
+            }
+            self.skip_terminators();
+            arms.push((patterns, body));
+        }
+        self.expect(TokenType::Esac, "esac")?;
+        Ok(AstNode::Case { word, arms })
+    }
+
+    fn parse_function(&mut self) -> Result<AstNode, ParseError> {
+        self.expect(TokenType::Function, "function")?;
+        let name = self.expect(TokenType::Word, "function name")?.text;
+        if self.check(TokenType::LParen) {
+            self.advance();
+            self.expect(TokenType::RParen, ")")?;
+        }
+        self.skip_terminators();
+        let body = Box::new(self.parse_compound_or_simple()?);
+        Ok(AstNode::Function { name, body })
+    }
+}
+
+/// Parse `tokens` (as produced by `Tokenizer::tokenize`) into a sequence of
+/// top-level AST nodes.
+pub fn parse(tokens: &[Token]) -> Result<Vec<AstNode>, ParseError> {
+    Parser::parse(tokens)
+}
@@ -0,0 +1,266 @@
+//! Minimal backtracking regex engine for `grep`
+//!
+//! A compact matcher over a small token set -- literal characters, `.`,
+//! `[...]`/`[^...]` character classes, `^`/`$` anchors, and `?`/`*`/`+`
+//! quantifiers -- rather than pulling in a full `regex` crate. Matching is
+//! the classic recursive approach: a quantified token greedily consumes as
+//! many characters as the atom allows, then backtracks one at a time until
+//! the rest of the pattern matches or the quantifier's minimum count is
+//! reached.
+
+/// How many times a [`Token`]'s atom may repeat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quantifier {
+    One,
+    ZeroOrOne,
+    ZeroOrMore,
+    OneOrMore,
+}
+
+#[derive(Debug, Clone)]
+enum TokenKind {
+    Char(char),
+    AnyChar,
+    Class { negated: bool, ranges: Vec<(char, char)> },
+    Start,
+    End,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    quantifier: Quantifier,
+}
+
+/// A compiled pattern, ready to search lines with [`Regex::is_match`].
+#[derive(Debug, Clone)]
+pub struct Regex {
+    tokens: Vec<Token>,
+    ignore_case: bool,
+}
+
+impl Regex {
+    /// Compile `pattern`, case-sensitively.
+    pub fn new(pattern: &str) -> Self {
+        Self::with_ignore_case(pattern, false)
+    }
+
+    /// Compile `pattern`; when `ignore_case` is set, both the pattern's
+    /// literal/class characters and the text matched against it are
+    /// folded to lowercase (ASCII-only, to keep byte/char offsets simple).
+    pub fn with_ignore_case(pattern: &str, ignore_case: bool) -> Self {
+        let mut tokens = compile(pattern);
+        if ignore_case {
+            lower_tokens(&mut tokens);
+        }
+        Self { tokens, ignore_case }
+    }
+
+    /// Compile a shell glob (`*` matches any run of characters, `?`
+    /// matches exactly one) into a regex anchored at both ends, e.g.
+    /// `*.rs` -> `^.*\.rs$`. Any character this engine treats specially
+    /// (`.`, `\`, `[`, `]`, `^`, `$`, `+`) is escaped first so it's taken
+    /// literally in the glob.
+    pub fn from_glob(glob: &str) -> Self {
+        let mut pattern = String::from("^");
+        for c in glob.chars() {
+            match c {
+                '*' => pattern.push_str(".*"),
+                '?' => pattern.push('.'),
+                '.' | '\\' | '[' | ']' | '^' | '$' | '+' => {
+                    pattern.push('\\');
+                    pattern.push(c);
+                }
+                other => pattern.push(other),
+            }
+        }
+        pattern.push('$');
+        Self::new(&pattern)
+    }
+
+    /// Returns the byte span of the first (leftmost) match in `text`, if
+    /// any. Tries `match_here` starting at each offset in turn unless the
+    /// pattern is anchored with a leading `^`, in which case only offset 0
+    /// is tried.
+    pub fn is_match(&self, text: &str) -> Option<(usize, usize)> {
+        let chars: Vec<(usize, char)> = text
+            .char_indices()
+            .map(|(i, c)| (i, if self.ignore_case { c.to_ascii_lowercase() } else { c }))
+            .collect();
+        let byte_end = text.len();
+
+        let anchored = matches!(self.tokens.first(), Some(t) if matches!(t.kind, TokenKind::Start));
+        let last = chars.len();
+
+        for start in 0..=last {
+            if anchored && start != 0 {
+                break;
+            }
+            if let Some(end) = match_here(&self.tokens, &chars, start) {
+                let start_byte = chars.get(start).map_or(byte_end, |&(b, _)| b);
+                let end_byte = chars.get(end).map_or(byte_end, |&(b, _)| b);
+                return Some((start_byte, end_byte));
+            }
+        }
+        None
+    }
+}
+
+/// Parse `pattern` into a token sequence.
+fn compile(pattern: &str) -> Vec<Token> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] == '^' && i == 0 {
+            tokens.push(Token { kind: TokenKind::Start, quantifier: Quantifier::One });
+            i += 1;
+            continue;
+        }
+        if chars[i] == '$' && i == chars.len() - 1 {
+            tokens.push(Token { kind: TokenKind::End, quantifier: Quantifier::One });
+            i += 1;
+            continue;
+        }
+
+        let kind = if chars[i] == '.' {
+            i += 1;
+            TokenKind::AnyChar
+        } else if chars[i] == '[' {
+            let (class, consumed) = parse_class(&chars[i..]);
+            i += consumed;
+            class
+        } else if chars[i] == '\\' && i + 1 < chars.len() {
+            let escaped = chars[i + 1];
+            i += 2;
+            TokenKind::Char(escaped)
+        } else {
+            let c = chars[i];
+            i += 1;
+            TokenKind::Char(c)
+        };
+
+        let quantifier = match chars.get(i) {
+            Some('?') => { i += 1; Quantifier::ZeroOrOne }
+            Some('*') => { i += 1; Quantifier::ZeroOrMore }
+            Some('+') => { i += 1; Quantifier::OneOrMore }
+            _ => Quantifier::One,
+        };
+
+        tokens.push(Token { kind, quantifier });
+    }
+
+    tokens
+}
+
+/// Parse a `[...]`/`[^...]` class starting at `chars[0] == '['`. Returns
+/// the class token and how many input characters it consumed (including
+/// both brackets). A `]` as the class's first member (right after `[` or
+/// `[^`) is taken literally, per the usual shell-glob/POSIX convention.
+fn parse_class(chars: &[char]) -> (TokenKind, usize) {
+    let mut idx = 1;
+    let negated = chars.get(idx) == Some(&'^');
+    if negated {
+        idx += 1;
+    }
+
+    let mut ranges = Vec::new();
+    let mut first = true;
+    while idx < chars.len() && (chars[idx] != ']' || first) {
+        first = false;
+        let lo = chars[idx];
+        if idx + 2 < chars.len() && chars[idx + 1] == '-' && chars[idx + 2] != ']' {
+            ranges.push((lo, chars[idx + 2]));
+            idx += 3;
+        } else {
+            ranges.push((lo, lo));
+            idx += 1;
+        }
+    }
+    if idx < chars.len() && chars[idx] == ']' {
+        idx += 1;
+    }
+
+    (TokenKind::Class { negated, ranges }, idx)
+}
+
+/// Fold every literal/class character in `tokens` to lowercase, so an
+/// `ignore_case` match only has to lowercase the input side.
+fn lower_tokens(tokens: &mut Vec<Token>) {
+    for token in tokens.iter_mut() {
+        match &mut token.kind {
+            TokenKind::Char(c) => *c = c.to_ascii_lowercase(),
+            TokenKind::Class { ranges, .. } => {
+                for (lo, hi) in ranges.iter_mut() {
+                    *lo = lo.to_ascii_lowercase();
+                    *hi = hi.to_ascii_lowercase();
+                }
+            }
+            TokenKind::AnyChar | TokenKind::Start | TokenKind::End => {}
+        }
+    }
+}
+
+fn atom_matches(kind: &TokenKind, c: char) -> bool {
+    match kind {
+        TokenKind::Char(expected) => *expected == c,
+        TokenKind::AnyChar => true,
+        TokenKind::Class { negated, ranges } => {
+            let in_class = ranges.iter().any(|&(lo, hi)| c >= lo && c <= hi);
+            in_class != *negated
+        }
+        TokenKind::Start | TokenKind::End => false,
+    }
+}
+
+/// Does `tokens` match `chars[pos..]`, anchored at `pos`? Returns the char
+/// index one past the end of the match on success.
+fn match_here(tokens: &[Token], chars: &[(usize, char)], pos: usize) -> Option<usize> {
+    let Some((token, rest)) = tokens.split_first() else {
+        return Some(pos);
+    };
+
+    match token.kind {
+        TokenKind::Start => {
+            if pos == 0 { match_here(rest, chars, pos) } else { None }
+        }
+        TokenKind::End => {
+            if pos == chars.len() { match_here(rest, chars, pos) } else { None }
+        }
+        _ => match_quantified(token, rest, chars, pos),
+    }
+}
+
+/// Greedily consume as many characters as `token`'s atom allows starting
+/// at `pos`, then backtrack one at a time (down to the quantifier's
+/// minimum) until `rest` matches what follows.
+fn match_quantified(token: &Token, rest: &[Token], chars: &[(usize, char)], pos: usize) -> Option<usize> {
+    let mut max_run = 0;
+    while pos + max_run < chars.len() && atom_matches(&token.kind, chars[pos + max_run].1) {
+        max_run += 1;
+    }
+
+    let (min_count, max_count) = match token.quantifier {
+        Quantifier::One => (1, 1),
+        Quantifier::ZeroOrOne => (0, 1),
+        Quantifier::ZeroOrMore => (0, usize::MAX),
+        Quantifier::OneOrMore => (1, usize::MAX),
+    };
+
+    let upper = max_run.min(max_count);
+    if upper < min_count {
+        return None;
+    }
+
+    let mut count = upper;
+    loop {
+        if let Some(end) = match_here(rest, chars, pos + count) {
+            return Some(end);
+        }
+        if count == min_count {
+            return None;
+        }
+        count -= 1;
+    }
+}
@@ -0,0 +1,239 @@
+//! Scheme-based resource namespace
+//!
+//! Coreutils resolve a path by splitting an optional `scheme:rest` prefix
+//! and dispatching the remainder to whichever `Scheme` registered that
+//! prefix. This is the same shape as a scheme-router: a registry mapping a
+//! short string to a provider object, so new resource kinds (sockets,
+//! devices, ...) can be added later without touching `cat`/`head`/etc.
+
+use spin::Mutex;
+
+pub type Handle = usize;
+
+/// A resource provider for one scheme prefix (e.g. everything after
+/// `file:`).
+pub trait Scheme: Send {
+    fn open(&mut self, path: &str) -> Result<Handle, ()>;
+    fn read(&mut self, handle: Handle, buf: &mut [u8]) -> usize;
+    fn write(&mut self, handle: Handle, buf: &[u8]) -> usize;
+    fn close(&mut self, handle: Handle);
+}
+
+static SCHEMES: Mutex<Vec<(&'static str, Box<dyn Scheme>)>> = Mutex::new(Vec::new());
+
+/// Register a scheme provider under `prefix` (without the trailing `:`).
+/// Replaces any provider already registered under the same prefix.
+pub fn register_scheme(prefix: &'static str, provider: Box<dyn Scheme>) {
+    let mut schemes = SCHEMES.lock();
+    if let Some(slot) = schemes.iter_mut().find(|(name, _)| *name == prefix) {
+        slot.1 = provider;
+    } else {
+        schemes.push((prefix, provider));
+    }
+}
+
+/// Install the built-in `file:`, `null:`, `zero:`, and `pipe:` schemes.
+/// Idempotent: safe to call more than once (later calls just replace the
+/// built-ins with fresh ones).
+pub fn install_builtins() {
+    register_scheme("file", Box::new(FileScheme::new()));
+    register_scheme("null", Box::new(NullScheme));
+    register_scheme("zero", Box::new(ZeroScheme));
+    register_scheme("pipe", Box::new(PipeScheme::new()));
+}
+
+/// Split `path` into an optional scheme prefix and the remainder, e.g.
+/// `"file:/etc/motd"` -> `("file", "/etc/motd")`. A path with no `:`, or
+/// whose prefix isn't registered, defaults to the `file:` scheme so plain
+/// paths like `/etc/motd` and `-` keep working.
+fn split_scheme(path: &str) -> (&str, &str) {
+    if let Some(idx) = path.find(':') {
+        let (prefix, rest) = path.split_at(idx);
+        (prefix, &rest[1..])
+    } else {
+        ("file", path)
+    }
+}
+
+/// Resolve `path` through the scheme registry and open it, returning the
+/// scheme name (so the caller can route subsequent `read`/`close` calls)
+/// and the handle.
+pub fn open_path(path: &str) -> Result<(&'static str, Handle), ()> {
+    let (prefix, rest) = split_scheme(path);
+    let mut schemes = SCHEMES.lock();
+    let (name, scheme) = schemes
+        .iter_mut()
+        .find(|(name, _)| *name == prefix)
+        .ok_or(())?;
+    let handle = scheme.open(rest)?;
+    Ok((name, handle))
+}
+
+pub fn read(scheme: &'static str, handle: Handle, buf: &mut [u8]) -> usize {
+    let mut schemes = SCHEMES.lock();
+    match schemes.iter_mut().find(|(name, _)| *name == scheme) {
+        Some((_, provider)) => provider.read(handle, buf),
+        None => 0,
+    }
+}
+
+pub fn write(scheme: &'static str, handle: Handle, buf: &[u8]) -> usize {
+    let mut schemes = SCHEMES.lock();
+    match schemes.iter_mut().find(|(name, _)| *name == scheme) {
+        Some((_, provider)) => provider.write(handle, buf),
+        None => 0,
+    }
+}
+
+pub fn close(scheme: &'static str, handle: Handle) {
+    let mut schemes = SCHEMES.lock();
+    if let Some((_, provider)) = schemes.iter_mut().find(|(name, _)| *name == scheme) {
+        provider.close(handle);
+    }
+}
+
+/// `file:` -- backed by the read-only initramfs VFS via
+/// `crate::userspace::lookup`, since there is no writable filesystem yet.
+struct FileScheme {
+    open_files: Vec<Option<(&'static [u8], usize)>>,
+}
+
+impl FileScheme {
+    fn new() -> Self {
+        Self { open_files: Vec::new() }
+    }
+}
+
+impl Scheme for FileScheme {
+    fn open(&mut self, path: &str) -> Result<Handle, ()> {
+        let arch = crate::process::loader::TargetArch::X86_64;
+        #[cfg(target_arch = "aarch64")]
+        let arch = crate::process::loader::TargetArch::AArch64;
+        let data = crate::userspace::lookup(path, arch).ok_or(())?;
+        self.open_files.push(Some((data, 0)));
+        Ok(self.open_files.len() - 1)
+    }
+
+    fn read(&mut self, handle: Handle, buf: &mut [u8]) -> usize {
+        let Some(Some((data, pos))) = self.open_files.get_mut(handle) else {
+            return 0;
+        };
+        let remaining = &data[(*pos).min(data.len())..];
+        let count = remaining.len().min(buf.len());
+        buf[..count].copy_from_slice(&remaining[..count]);
+        *pos += count;
+        count
+    }
+
+    fn write(&mut self, _handle: Handle, _buf: &[u8]) -> usize {
+        0 // read-only filesystem
+    }
+
+    fn close(&mut self, handle: Handle) {
+        if let Some(slot) = self.open_files.get_mut(handle) {
+            *slot = None;
+        }
+    }
+}
+
+/// `null:` -- reads report EOF, writes are discarded but "succeed".
+struct NullScheme;
+
+impl Scheme for NullScheme {
+    fn open(&mut self, _path: &str) -> Result<Handle, ()> {
+        Ok(0)
+    }
+
+    fn read(&mut self, _handle: Handle, _buf: &mut [u8]) -> usize {
+        0
+    }
+
+    fn write(&mut self, _handle: Handle, buf: &[u8]) -> usize {
+        buf.len()
+    }
+
+    fn close(&mut self, _handle: Handle) {}
+}
+
+/// `zero:` -- infinite stream of zero bytes on read, writes discarded.
+struct ZeroScheme;
+
+impl Scheme for ZeroScheme {
+    fn open(&mut self, _path: &str) -> Result<Handle, ()> {
+        Ok(0)
+    }
+
+    fn read(&mut self, _handle: Handle, buf: &mut [u8]) -> usize {
+        for byte in buf.iter_mut() {
+            *byte = 0;
+        }
+        buf.len()
+    }
+
+    fn write(&mut self, _handle: Handle, buf: &[u8]) -> usize {
+        buf.len()
+    }
+
+    fn close(&mut self, _handle: Handle) {}
+}
+
+/// `pipe:` -- an in-memory ring buffer shared between two handles opened
+/// under the same name, so one end's writes become the other end's reads.
+const PIPE_CAPACITY: usize = 4096;
+
+struct PipeBuffer {
+    name: String,
+    data: Vec<u8>,
+    refs: usize,
+}
+
+struct PipeScheme {
+    pipes: Vec<PipeBuffer>,
+}
+
+impl PipeScheme {
+    fn new() -> Self {
+        Self { pipes: Vec::new() }
+    }
+}
+
+impl Scheme for PipeScheme {
+    fn open(&mut self, path: &str) -> Result<Handle, ()> {
+        if let Some(index) = self.pipes.iter().position(|pipe| pipe.name == path) {
+            self.pipes[index].refs += 1;
+            return Ok(index);
+        }
+        self.pipes.push(PipeBuffer {
+            name: path.to_string(),
+            data: Vec::new(),
+            refs: 1,
+        });
+        Ok(self.pipes.len() - 1)
+    }
+
+    fn read(&mut self, handle: Handle, buf: &mut [u8]) -> usize {
+        let Some(pipe) = self.pipes.get_mut(handle) else {
+            return 0;
+        };
+        let count = pipe.data.len().min(buf.len());
+        buf[..count].copy_from_slice(&pipe.data[..count]);
+        pipe.data.drain(..count);
+        count
+    }
+
+    fn write(&mut self, handle: Handle, buf: &[u8]) -> usize {
+        let Some(pipe) = self.pipes.get_mut(handle) else {
+            return 0;
+        };
+        let space = PIPE_CAPACITY.saturating_sub(pipe.data.len());
+        let count = buf.len().min(space);
+        pipe.data.extend_from_slice(&buf[..count]);
+        count
+    }
+
+    fn close(&mut self, handle: Handle) {
+        if let Some(pipe) = self.pipes.get_mut(handle) {
+            pipe.refs = pipe.refs.saturating_sub(1);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Task-local output sinks
+//!
+//! Every coreutil in this module used to write straight through the
+//! global `print!` macro to the console, which made piping
+//! (`grep foo | sort > out.txt`) impossible. This module gives each PID an
+//! injected stdout/stderr `Sink` -- stored in a table alongside
+//! `crate::security::SECURITY_CONTEXTS` the same way that table is keyed
+//! by PID -- so a utility writes through whatever sink the shell wired up
+//! for this pipeline stage instead of always hitting the console.
+
+use super::scheme::{self, Handle};
+use spin::Mutex;
+
+pub trait Sink: Send {
+    fn write_bytes(&mut self, bytes: &[u8]);
+}
+
+/// Writes to the kernel's serial console -- the closest thing this kernel
+/// has to a VGA text-mode writer today.
+pub struct ConsoleSink;
+
+impl Sink for ConsoleSink {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        let mut serial = crate::drivers::serial::SERIAL1.lock();
+        for &byte in bytes {
+            serial.send(byte);
+        }
+    }
+}
+
+/// Collects everything written to it in memory, for capturing a utility's
+/// output (e.g. command substitution).
+#[derive(Default)]
+pub struct BufferSink {
+    pub buffer: Vec<u8>,
+}
+
+impl Sink for BufferSink {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+}
+
+/// Writes through an open `scheme::Handle`, for both `file:` redirection
+/// targets and `pipe:` stages -- both are just scheme writes from here.
+pub struct SchemeSink {
+    scheme: &'static str,
+    handle: Handle,
+}
+
+impl SchemeSink {
+    pub fn new(scheme: &'static str, handle: Handle) -> Self {
+        Self { scheme, handle }
+    }
+
+    /// Open `path` (e.g. `"out.txt"` or `"pipe:stage1"`) and wrap it in a
+    /// sink, routed through the scheme registry exactly like `cat` reads.
+    pub fn open(path: &str) -> Result<Self, ()> {
+        let (scheme, handle) = scheme::open_path(path)?;
+        Ok(Self::new(scheme, handle))
+    }
+}
+
+impl Sink for SchemeSink {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        scheme::write(self.scheme, self.handle, bytes);
+    }
+}
+
+impl Drop for SchemeSink {
+    fn drop(&mut self) {
+        scheme::close(self.scheme, self.handle);
+    }
+}
+
+struct IoEntry {
+    pid: usize,
+    stdout: Box<dyn Sink>,
+    stderr: Box<dyn Sink>,
+}
+
+static IO_TABLE: Mutex<Vec<IoEntry>> = Mutex::new(Vec::new());
+
+/// Wire `pid`'s stdout/stderr to the given sinks, replacing whatever was
+/// registered before.
+pub fn set_stdio(pid: usize, stdout: Box<dyn Sink>, stderr: Box<dyn Sink>) {
+    let mut table = IO_TABLE.lock();
+    if let Some(entry) = table.iter_mut().find(|entry| entry.pid == pid) {
+        entry.stdout = stdout;
+        entry.stderr = stderr;
+    } else {
+        table.push(IoEntry { pid, stdout, stderr });
+    }
+}
+
+pub fn remove_stdio(pid: usize) {
+    let mut table = IO_TABLE.lock();
+    if let Some(index) = table.iter().position(|entry| entry.pid == pid) {
+        table.swap_remove(index);
+    }
+}
+
+/// Write to `pid`'s registered stdout, falling back to the console if `pid`
+/// never had one set up.
+pub fn write_stdout(pid: usize, bytes: &[u8]) {
+    let mut table = IO_TABLE.lock();
+    match table.iter_mut().find(|entry| entry.pid == pid) {
+        Some(entry) => entry.stdout.write_bytes(bytes),
+        None => ConsoleSink.write_bytes(bytes),
+    }
+}
+
+pub fn write_stderr(pid: usize, bytes: &[u8]) {
+    let mut table = IO_TABLE.lock();
+    match table.iter_mut().find(|entry| entry.pid == pid) {
+        Some(entry) => entry.stderr.write_bytes(bytes),
+        None => ConsoleSink.write_bytes(bytes),
+    }
+}
+
+/// Resolve the calling thread's PID, falling back to 0 (the console's
+/// own pseudo-pid) when there's no scheduled thread to ask -- e.g. a
+/// utility invoked directly from kernel code rather than a scheduled
+/// shell process.
+pub fn current_pid() -> usize {
+    crate::process::scheduler::current_thread()
+        .and_then(crate::process::thread::get_thread)
+        .map(|thread| thread.process_id.0)
+        .unwrap_or(0)
+}
+
+/// A coreutil entry point in the `fn(&[&str]) -> i32` shape every utility
+/// in this module already exposes as `run`.
+pub type UtilityFn = fn(&[&str]) -> i32;
+
+/// Run `util` with its stdout wired to `stdout` (and stderr left on the
+/// console) for the duration of the call, then restore whatever was
+/// registered for `pid` before. This is what lets the shell build a
+/// pipeline like `grep foo | sort > out.txt`: each stage gets a
+/// `SchemeSink` over the next stage's `pipe:` (or the final `file:`
+/// target) as its stdout.
+pub fn run_with_io(util: UtilityFn, args: &[&str], pid: usize, stdout: Box<dyn Sink>) -> i32 {
+    set_stdio(pid, stdout, Box::new(ConsoleSink));
+    let result = util(args);
+    remove_stdio(pid);
+    result
+}
@@ -7,8 +7,11 @@
 //! Pause for NUMBER seconds.  SUFFIX may be 's' for seconds (default),
 //! 'm' for minutes, 'h' for hours, or 'd' for days.
 //!
-//! Since this is a kernel environment, sleep uses busy-waiting
-//! and is not efficient for long durations.
+//! Blocks on `lib_core::time::sleep_until_ns`, which registers a deadline
+//! against the TSC-calibrated monotonic clock and halts the CPU until the
+//! periodic timer tick wakes it, rather than busy-waiting.
+
+use crate::lib_core::time;
 
 pub fn run(args: &[&str]) -> i32 {
     if args.is_empty() {
@@ -55,10 +58,11 @@ pub fn run(args: &[&str]) -> i32 {
         total_seconds += seconds;
     }
 
-    // In kernel environment, we would use a timer interrupt
-    // For now, this is a placeholder
+    // Zero/negative durations return immediately; very large ones saturate
+    // rather than overflowing the nanosecond conversion.
     if total_seconds > 0.0 {
-        // busy_wait(total_seconds);
+        let duration_ns = (total_seconds * 1_000_000_000.0) as u64;
+        time::sleep_ns(duration_ns);
     }
 
     0
@@ -376,6 +376,27 @@ impl Tokenizer {
                     self.pos += 1;
                 }
             }
+            b'(' if self.peek_byte(1) == b'(' => {
+                // Arithmetic expansion `$(( expr ))`: balance the doubled
+                // parens the same way command substitution balances single
+                // ones, so the closing `))` isn't mistaken for the end of a
+                // nested subshell inside the expression.
+                self.pos += 2;
+                let mut depth = 2;
+                while !self.is_eof() && depth > 0 {
+                    if self.current_byte() == b'(' {
+                        depth += 1;
+                        self.pos += 1;
+                    } else if self.current_byte() == b')' {
+                        depth -= 1;
+                        self.pos += 1;
+                    } else if self.current_byte() == b'\\' && self.pos + 1 < self.len {
+                        self.pos += 2;
+                    } else {
+                        self.pos += 1;
+                    }
+                }
+            }
             b'(' => {
                 self.pos += 1;
                 let mut depth = 1;
@@ -494,6 +515,16 @@ impl Tokenizer {
         }
     }
 
+    /// Look `offset` bytes ahead of `pos` without consuming anything.
+    fn peek_byte(&self, offset: usize) -> u8 {
+        let idx = self.pos + offset;
+        if idx < self.len {
+            self.input[idx]
+        } else {
+            0
+        }
+    }
+
     fn is_eof(&self) -> bool {
         self.pos >= self.len
     }
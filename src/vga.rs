@@ -0,0 +1,323 @@
+//! VGA text-mode console
+//!
+//! Writes directly to the memory-mapped text buffer at `0xb8000`. Tracks
+//! `row_position`/`column_position` and scrolls the whole buffer up by one
+//! row instead of letting `row_position` walk past `BUFFER_HEIGHT`, and
+//! recognizes a small ANSI/VT100 escape-sequence subset in `write_byte`:
+//! `ESC [ ... m` SGR color codes, `ESC [ H` cursor-home, `ESC [ 2J`
+//! clear-screen, and `ESC [ row ; col H` cursor positioning.
+
+use crate::lib::spinlock::Spinlock;
+use core::fmt;
+
+pub const BUFFER_HEIGHT: usize = 25;
+pub const BUFFER_WIDTH: usize = 80;
+
+const VGA_BUFFER_ADDR: usize = 0xb8000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Color {
+    Black = 0,
+    Blue = 1,
+    Green = 2,
+    Cyan = 3,
+    Red = 4,
+    Magenta = 5,
+    Brown = 6,
+    LightGray = 7,
+    DarkGray = 8,
+    LightBlue = 9,
+    LightGreen = 10,
+    LightCyan = 11,
+    LightRed = 12,
+    Pink = 13,
+    Yellow = 14,
+    White = 15,
+}
+
+impl Color {
+    fn from_nibble(nibble: u8) -> Color {
+        match nibble & 0xF {
+            0 => Color::Black,
+            1 => Color::Blue,
+            2 => Color::Green,
+            3 => Color::Cyan,
+            4 => Color::Red,
+            5 => Color::Magenta,
+            6 => Color::Brown,
+            7 => Color::LightGray,
+            8 => Color::DarkGray,
+            9 => Color::LightBlue,
+            10 => Color::LightGreen,
+            11 => Color::LightCyan,
+            12 => Color::LightRed,
+            13 => Color::Pink,
+            14 => Color::Yellow,
+            _ => Color::White,
+        }
+    }
+}
+
+/// Standard ANSI color indices (0-7) in VGA nibble order, used to map SGR
+/// `3x`/`4x` codes onto `Color`.
+const ANSI_TO_VGA: [Color; 8] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Brown,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::LightGray,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct ColorCode(u8);
+
+impl ColorCode {
+    pub const fn new(foreground: Color, background: Color) -> Self {
+        ColorCode(((background as u8) << 4) | (foreground as u8))
+    }
+
+    fn foreground(self) -> Color {
+        Color::from_nibble(self.0)
+    }
+
+    fn background(self) -> Color {
+        Color::from_nibble(self.0 >> 4)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+struct ScreenChar {
+    ascii_character: u8,
+    color_code: ColorCode,
+}
+
+#[repr(transparent)]
+struct Buffer {
+    chars: [[ScreenChar; BUFFER_WIDTH]; BUFFER_HEIGHT],
+}
+
+/// Parser state for the escape sequences `write_byte` understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EscapeState {
+    Ground,
+    SawEscape,
+    InSequence,
+}
+
+const MAX_ESCAPE_PARAMS: usize = 4;
+
+pub struct Writer {
+    row_position: usize,
+    column_position: usize,
+    color_code: ColorCode,
+    buffer: *mut Buffer,
+    escape_state: EscapeState,
+    escape_params: [u16; MAX_ESCAPE_PARAMS],
+    escape_param_count: usize,
+}
+
+// SAFETY: the VGA text buffer is a fixed piece of hardware MMIO, not
+// thread-local state; access is serialized by the `Spinlock` around the
+// `Writer` the same way `drivers::serial::SerialPort` serializes the UART.
+unsafe impl Send for Writer {}
+
+impl Writer {
+    const fn new() -> Self {
+        Writer {
+            row_position: 0,
+            column_position: 0,
+            color_code: ColorCode::new(Color::LightGray, Color::Black),
+            buffer: VGA_BUFFER_ADDR as *mut Buffer,
+            escape_state: EscapeState::Ground,
+            escape_params: [0; MAX_ESCAPE_PARAMS],
+            escape_param_count: 0,
+        }
+    }
+
+    fn write_cell(&mut self, row: usize, col: usize, value: ScreenChar) {
+        unsafe {
+            core::ptr::write_volatile(&mut (*self.buffer).chars[row][col], value);
+        }
+    }
+
+    fn read_cell(&self, row: usize, col: usize) -> ScreenChar {
+        unsafe { core::ptr::read_volatile(&(*self.buffer).chars[row][col]) }
+    }
+
+    pub fn write_byte(&mut self, byte: u8) {
+        match self.escape_state {
+            EscapeState::Ground => self.write_ground(byte),
+            EscapeState::SawEscape => {
+                if byte == b'[' {
+                    self.escape_state = EscapeState::InSequence;
+                    self.escape_params = [0; MAX_ESCAPE_PARAMS];
+                    self.escape_param_count = 0;
+                } else {
+                    self.escape_state = EscapeState::Ground;
+                }
+            }
+            EscapeState::InSequence => self.write_in_sequence(byte),
+        }
+    }
+
+    fn write_ground(&mut self, byte: u8) {
+        match byte {
+            0x1B => self.escape_state = EscapeState::SawEscape,
+            b'\n' => self.new_line(),
+            byte => {
+                if self.column_position >= BUFFER_WIDTH {
+                    self.new_line();
+                }
+                let (row, col) = (self.row_position, self.column_position);
+                let color_code = self.color_code;
+                self.write_cell(row, col, ScreenChar { ascii_character: byte, color_code });
+                self.column_position += 1;
+            }
+        }
+    }
+
+    fn write_in_sequence(&mut self, byte: u8) {
+        match byte {
+            b'0'..=b'9' => {
+                if self.escape_param_count == 0 {
+                    self.escape_param_count = 1;
+                }
+                let index = self.escape_param_count - 1;
+                if let Some(param) = self.escape_params.get_mut(index) {
+                    *param = param.saturating_mul(10).saturating_add((byte - b'0') as u16);
+                }
+            }
+            b';' => {
+                if self.escape_param_count < MAX_ESCAPE_PARAMS {
+                    self.escape_param_count += 1;
+                }
+            }
+            b'm' => {
+                self.handle_sgr();
+                self.escape_state = EscapeState::Ground;
+            }
+            b'H' => {
+                self.handle_cursor_position();
+                self.escape_state = EscapeState::Ground;
+            }
+            b'J' => {
+                if self.escape_params.first() == Some(&2) {
+                    self.clear_screen();
+                }
+                self.escape_state = EscapeState::Ground;
+            }
+            _ => self.escape_state = EscapeState::Ground,
+        }
+    }
+
+    fn handle_sgr(&mut self) {
+        if self.escape_param_count == 0 {
+            self.color_code = ColorCode::new(Color::LightGray, Color::Black);
+            return;
+        }
+        for &code in &self.escape_params[..self.escape_param_count] {
+            match code {
+                0 => self.color_code = ColorCode::new(Color::LightGray, Color::Black),
+                30..=37 => {
+                    let fg = ANSI_TO_VGA[(code - 30) as usize];
+                    self.color_code = ColorCode::new(fg, self.color_code.background());
+                }
+                39 => self.color_code = ColorCode::new(Color::LightGray, self.color_code.background()),
+                40..=47 => {
+                    let bg = ANSI_TO_VGA[(code - 40) as usize];
+                    self.color_code = ColorCode::new(self.color_code.foreground(), bg);
+                }
+                49 => self.color_code = ColorCode::new(self.color_code.foreground(), Color::Black),
+                _ => {}
+            }
+        }
+    }
+
+    fn handle_cursor_position(&mut self) {
+        if self.escape_param_count == 0 {
+            self.row_position = 0;
+            self.column_position = 0;
+            return;
+        }
+        let row = self.escape_params[0].max(1) as usize - 1;
+        let col = if self.escape_param_count >= 2 {
+            self.escape_params[1].max(1) as usize - 1
+        } else {
+            0
+        };
+        self.row_position = row.min(BUFFER_HEIGHT - 1);
+        self.column_position = col.min(BUFFER_WIDTH - 1);
+    }
+
+    fn new_line(&mut self) {
+        if self.row_position + 1 >= BUFFER_HEIGHT {
+            for row in 1..BUFFER_HEIGHT {
+                for col in 0..BUFFER_WIDTH {
+                    let character = self.read_cell(row, col);
+                    self.write_cell(row - 1, col, character);
+                }
+            }
+            self.clear_row(BUFFER_HEIGHT - 1);
+        } else {
+            self.row_position += 1;
+        }
+        self.column_position = 0;
+    }
+
+    fn clear_row(&mut self, row: usize) {
+        let blank = ScreenChar { ascii_character: b' ', color_code: self.color_code };
+        for col in 0..BUFFER_WIDTH {
+            self.write_cell(row, col, blank);
+        }
+    }
+
+    fn clear_screen(&mut self) {
+        for row in 0..BUFFER_HEIGHT {
+            self.clear_row(row);
+        }
+        self.row_position = 0;
+        self.column_position = 0;
+    }
+
+    pub fn write_string(&mut self, s: &str) {
+        for byte in s.bytes() {
+            self.write_byte(byte);
+        }
+    }
+}
+
+impl fmt::Write for Writer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_string(s);
+        Ok(())
+    }
+}
+
+pub static WRITER: Spinlock<Writer> = Spinlock::new(Writer::new());
+
+#[macro_export]
+macro_rules! print {
+    ($($arg:tt)*) => {
+        {
+            use core::fmt::Write;
+            let _ = write!($crate::vga::WRITER.lock(), $($arg)*);
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! println {
+    () => { $crate::print!("\n") };
+    ($($arg:tt)*) => {
+        {
+            $crate::print!($($arg)*);
+            $crate::print!("\n");
+        }
+    };
+}
@@ -0,0 +1,334 @@
+//! Sandboxed bytecode interpreter for userspace programs
+//!
+//! A compact register VM: 256 general-purpose 64-bit registers, a linear
+//! code segment of fixed-width `Instruction`s, and a small data/stack
+//! region. Each `Vm` owns a `SecurityContext` the same way a real process
+//! does, so its `ecall` instruction is gated by the same capability table
+//! `crate::syscall::SYSCALL_TABLE` uses -- a program without `CAP_FS_RW`
+//! can't issue filesystem syscalls no matter what bytecode it runs.
+
+#[cfg(not(test))]
+use alloc::vec::Vec;
+
+#[cfg(test)]
+extern crate std;
+#[cfg(test)]
+use std::vec::Vec;
+
+use crate::security::{CapMask, SecurityContext};
+use crate::syscall::{SyscallArgs, SYSCALL_TABLE};
+
+pub const NUM_REGISTERS: usize = 256;
+pub const DATA_SIZE: usize = 4096;
+pub const CALL_STACK_DEPTH: usize = 32;
+
+/// Instructions remaining before a `Vm` cooperatively yields with
+/// `VmExit::TimerExpired`, reset at the start of every `run()` call.
+pub const DEFAULT_TIME_SLICE: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Opcode {
+    Nop = 0,
+    LoadImm = 1,
+    Load = 2,
+    Store = 3,
+    Add = 4,
+    Sub = 5,
+    Mul = 6,
+    Cmp = 7,
+    Jmp = 8,
+    Jz = 9,
+    Call = 10,
+    Ret = 11,
+    Ecall = 12,
+    Halt = 13,
+}
+
+impl Opcode {
+    fn from_u8(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Opcode::Nop),
+            1 => Some(Opcode::LoadImm),
+            2 => Some(Opcode::Load),
+            3 => Some(Opcode::Store),
+            4 => Some(Opcode::Add),
+            5 => Some(Opcode::Sub),
+            6 => Some(Opcode::Mul),
+            7 => Some(Opcode::Cmp),
+            8 => Some(Opcode::Jmp),
+            9 => Some(Opcode::Jz),
+            10 => Some(Opcode::Call),
+            11 => Some(Opcode::Ret),
+            12 => Some(Opcode::Ecall),
+            13 => Some(Opcode::Halt),
+            _ => None,
+        }
+    }
+}
+
+/// A single fixed-width instruction: `dst = src1 op src2`/`imm`, depending
+/// on the opcode. `ecall` reads the syscall number from `r0` and its six
+/// arguments from `r1..=r6`; the result is written back into `r0`.
+#[derive(Debug, Clone, Copy)]
+pub struct Instruction {
+    pub opcode: u8,
+    pub dst: u8,
+    pub src1: u8,
+    pub src2: u8,
+    pub imm: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrapKind {
+    InvalidOpcode,
+    OutOfBoundsCode,
+    OutOfBoundsData,
+    CallStackOverflow,
+    CallStackUnderflow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VmExit {
+    Halted,
+    Trap(TrapKind),
+    Syscall,
+    TimerExpired,
+}
+
+pub struct Vm {
+    pub registers: [u64; NUM_REGISTERS],
+    pub data: [u8; DATA_SIZE],
+    pub pc: usize,
+    code: Vec<Instruction>,
+    call_stack: [usize; CALL_STACK_DEPTH],
+    call_sp: usize,
+    flag_eq: bool,
+    flag_lt: bool,
+    security: SecurityContext,
+    time_slice: usize,
+}
+
+impl Vm {
+    /// Build a VM sandboxed to `uid` with exactly `capabilities` (further
+    /// restricted by `SecurityContext`'s usual bounding-set rules),
+    /// running at `PrivilegeLevel::Ring3` the way `SecurityContext::as_user`
+    /// always does.
+    pub fn new(code: Vec<Instruction>, uid: u32, capabilities: CapMask) -> Self {
+        Self {
+            registers: [0; NUM_REGISTERS],
+            data: [0; DATA_SIZE],
+            pc: 0,
+            code,
+            call_stack: [0; CALL_STACK_DEPTH],
+            call_sp: 0,
+            flag_eq: false,
+            flag_lt: false,
+            security: SecurityContext::as_user(uid).with_capabilities(capabilities),
+            time_slice: DEFAULT_TIME_SLICE,
+        }
+    }
+
+    pub fn security(&self) -> &SecurityContext {
+        &self.security
+    }
+
+    /// Execute up to `steps` instructions, stopping early on `Halt`, a
+    /// trap, a syscall, or running out of the current time slice.
+    pub fn run(&mut self, steps: usize) -> VmExit {
+        for _ in 0..steps {
+            if self.time_slice == 0 {
+                self.time_slice = DEFAULT_TIME_SLICE;
+                return VmExit::TimerExpired;
+            }
+            self.time_slice -= 1;
+
+            let Some(&instruction) = self.code.get(self.pc) else {
+                return VmExit::Trap(TrapKind::OutOfBoundsCode);
+            };
+
+            let Some(opcode) = Opcode::from_u8(instruction.opcode) else {
+                return VmExit::Trap(TrapKind::InvalidOpcode);
+            };
+
+            match self.execute(opcode, instruction) {
+                Ok(Some(exit)) => return exit,
+                Ok(None) => {}
+                Err(trap) => return VmExit::Trap(trap),
+            }
+        }
+        VmExit::TimerExpired
+    }
+
+    fn reg(&self, index: u8) -> u64 {
+        self.registers[index as usize]
+    }
+
+    fn set_reg(&mut self, index: u8, value: u64) {
+        self.registers[index as usize] = value;
+    }
+
+    fn execute(&mut self, opcode: Opcode, inst: Instruction) -> Result<Option<VmExit>, TrapKind> {
+        let mut next_pc = self.pc + 1;
+
+        match opcode {
+            Opcode::Nop => {}
+            Opcode::LoadImm => self.set_reg(inst.dst, inst.imm as i64 as u64),
+            Opcode::Load => {
+                let addr = (self.reg(inst.src1) as i64 + inst.imm as i64) as usize;
+                let bytes = self
+                    .data
+                    .get(addr..addr + 8)
+                    .ok_or(TrapKind::OutOfBoundsData)?;
+                self.set_reg(inst.dst, u64::from_le_bytes(bytes.try_into().unwrap()));
+            }
+            Opcode::Store => {
+                let addr = (self.reg(inst.src1) as i64 + inst.imm as i64) as usize;
+                let slot = self
+                    .data
+                    .get_mut(addr..addr + 8)
+                    .ok_or(TrapKind::OutOfBoundsData)?;
+                slot.copy_from_slice(&self.reg(inst.dst).to_le_bytes());
+            }
+            Opcode::Add => self.set_reg(inst.dst, self.reg(inst.src1).wrapping_add(self.reg(inst.src2))),
+            Opcode::Sub => self.set_reg(inst.dst, self.reg(inst.src1).wrapping_sub(self.reg(inst.src2))),
+            Opcode::Mul => self.set_reg(inst.dst, self.reg(inst.src1).wrapping_mul(self.reg(inst.src2))),
+            Opcode::Cmp => {
+                let (a, b) = (self.reg(inst.src1), self.reg(inst.src2));
+                self.flag_eq = a == b;
+                self.flag_lt = a < b;
+            }
+            Opcode::Jmp => next_pc = (self.pc as i64 + inst.imm as i64) as usize,
+            Opcode::Jz => {
+                if self.flag_eq {
+                    next_pc = (self.pc as i64 + inst.imm as i64) as usize;
+                }
+            }
+            Opcode::Call => {
+                if self.call_sp >= CALL_STACK_DEPTH {
+                    return Err(TrapKind::CallStackOverflow);
+                }
+                self.call_stack[self.call_sp] = self.pc + 1;
+                self.call_sp += 1;
+                next_pc = (self.pc as i64 + inst.imm as i64) as usize;
+            }
+            Opcode::Ret => {
+                if self.call_sp == 0 {
+                    return Err(TrapKind::CallStackUnderflow);
+                }
+                self.call_sp -= 1;
+                next_pc = self.call_stack[self.call_sp];
+            }
+            Opcode::Ecall => {
+                self.pc = next_pc;
+                self.dispatch_ecall();
+                return Ok(Some(VmExit::Syscall));
+            }
+            Opcode::Halt => return Ok(Some(VmExit::Halted)),
+        }
+
+        self.pc = next_pc;
+        Ok(None)
+    }
+
+    /// Gate the pending syscall (number in `r0`, args in `r1..=r6`)
+    /// against `self.security`'s capabilities -- the same check
+    /// `crate::security::check_syscall` does for real processes -- and
+    /// invoke the handler directly if it passes. The result (or `-EPERM`/
+    /// `-ENOSYS`) is written back into `r0`.
+    fn dispatch_ecall(&mut self) {
+        let number = self.reg(0) as usize;
+        let args = SyscallArgs::new(
+            self.reg(1) as usize,
+            self.reg(2) as usize,
+            self.reg(3) as usize,
+            self.reg(4) as usize,
+            self.reg(5) as usize,
+            self.reg(6) as usize,
+        );
+
+        let result = match SYSCALL_TABLE.get(number) {
+            None => Err(-(crate::security::ENOSYS as i64)),
+            Some(descriptor) => {
+                if !self.security.has_capabilities(descriptor.required_capabilities) {
+                    Err(-(crate::security::EPERM as i64))
+                } else {
+                    match (descriptor.handler)(args) {
+                        Ok(value) => Ok(value as i64),
+                        Err(errno) => Err(-(errno as i64)),
+                    }
+                }
+            }
+        };
+
+        self.set_reg(0, result.unwrap_or_else(|errno| errno) as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::{CAP_CONSOLE_IO, CAP_NONE};
+
+    fn inst(opcode: Opcode, dst: u8, src1: u8, src2: u8, imm: i32) -> Instruction {
+        Instruction { opcode: opcode as u8, dst, src1, src2, imm }
+    }
+
+    #[test]
+    fn test_arithmetic_and_halt() {
+        let code = Vec::from([
+            inst(Opcode::LoadImm, 1, 0, 0, 10),
+            inst(Opcode::LoadImm, 2, 0, 0, 32),
+            inst(Opcode::Add, 3, 1, 2, 0),
+            inst(Opcode::Halt, 0, 0, 0, 0),
+        ]);
+        let mut vm = Vm::new(code, 1000, CAP_NONE);
+        assert_eq!(vm.run(10), VmExit::Halted);
+        assert_eq!(vm.registers[3], 42);
+    }
+
+    #[test]
+    fn test_invalid_opcode_traps_cleanly() {
+        let code = Vec::from([Instruction { opcode: 0xFF, dst: 0, src1: 0, src2: 0, imm: 0 }]);
+        let mut vm = Vm::new(code, 1000, CAP_NONE);
+        assert_eq!(vm.run(10), VmExit::Trap(TrapKind::InvalidOpcode));
+    }
+
+    #[test]
+    fn test_out_of_bounds_data_access_traps() {
+        let code = Vec::from([inst(Opcode::Load, 1, 0, 0, (DATA_SIZE as i32) - 4)]);
+        let mut vm = Vm::new(code, 1000, CAP_NONE);
+        assert_eq!(vm.run(10), VmExit::Trap(TrapKind::OutOfBoundsData));
+    }
+
+    #[test]
+    fn test_timer_preemption_yields() {
+        let code = Vec::from([inst(Opcode::Jmp, 0, 0, 0, 0)]);
+        let mut vm = Vm::new(code, 1000, CAP_NONE);
+        vm.time_slice = 3;
+        assert_eq!(vm.run(1000), VmExit::TimerExpired);
+        assert_eq!(vm.time_slice, DEFAULT_TIME_SLICE);
+    }
+
+    #[test]
+    fn test_ecall_denied_without_capability() {
+        let code = Vec::from([
+            inst(Opcode::LoadImm, 0, 0, 0, crate::syscall::SYS_WRITE as i32),
+            inst(Opcode::Ecall, 0, 0, 0, 0),
+        ]);
+        let mut vm = Vm::new(code, 1000, CAP_NONE);
+        assert_eq!(vm.run(10), VmExit::Syscall);
+        assert_eq!(vm.registers[0] as i64, -(crate::security::EPERM as i64));
+    }
+
+    #[test]
+    fn test_ecall_allowed_with_capability() {
+        let code = Vec::from([
+            inst(Opcode::LoadImm, 0, 0, 0, crate::syscall::SYS_WRITE as i32),
+            inst(Opcode::Ecall, 0, 0, 0, 0),
+        ]);
+        let mut vm = Vm::new(code, 1000, CAP_CONSOLE_IO);
+        assert_eq!(vm.run(10), VmExit::Syscall);
+        assert_ne!(vm.registers[0] as i64, -(crate::security::EPERM as i64));
+    }
+}
@@ -4,12 +4,8 @@
 
 use core::arch::asm;
 
-extern "C" {
-    pub fn ap_startup_begin();
-    pub fn ap_startup_end();
-}
-
-/// Size of AP startup code
+/// Size of AP startup code. See `ap_trampoline` for the real
+/// `ap_startup_begin`/`ap_startup_end` blob this bounds.
 pub const AP_STARTUP_SIZE: usize = 0x1000;
 
 /// Boot address for AP processors (typically 0x8000)
@@ -21,60 +17,64 @@ pub const AP_BOOT_ADDRESS: u64 = 0x8000;
 /// * `apic_id` - The APIC ID of the CPU to boot
 /// * `entry_point` - The 64-bit entry point address
 pub fn boot_ap(apic_id: u32, entry_point: u64) -> bool {
-    use crate::x86_64::cpu;
-    
-    // Send INIT IPI to the AP
-    send_init_ipi(apic_id);
-    
-    // Wait a bit
-    wait_microseconds(10);
-    
-    // Send Startup IPI with the vector (entry point)
-    send_startup_ipi(apic_id, (entry_point >> 12) as u8);
-    
-    // Wait for AP to come online
-    wait_microseconds(200);
-    
-    // TODO: Check if AP came online successfully
-    true
-}
+    // Prefer x2APIC when the CPU supports it -- it removes the 255-ID cap
+    // that the legacy xAPIC MMIO ICR imposes.
+    crate::x86_64::lapic::enable_x2apic();
 
-/// Send INIT IPI to a CPU
-fn send_init_ipi(apic_id: u32) {
-    use crate::x86_64::cpu;
-    
-    let lapic_base = cpu::get_lapic_base();
-    
-    // ICR high register (destination)
-    let icr_high = lapic_base as *mut u32;
+    // `entry_point` is the real-mode trampoline's page-aligned address;
+    // record it so the STARTUP IPIs (and anything else that needs to know
+    // where an AP resumes) have a single source of truth for it.
     unsafe {
-        *icr_high.offset(0x10) = (apic_id as u32) << 24;
+        crate::x86_64::cpu::AP_STARTUP_VECTOR = entry_point;
     }
-    
-    // ICR low register (INIT IPI)
-    let icr_low = lapic_base as *mut u32;
-    unsafe {
-        *icr_low.offset(0x08) = 0x500;  // INIT IPI, level triggered
+    let vector = (entry_point >> 12) as u8;
+
+    // Canonical INIT-SIPI-SIPI sequence: assert INIT, hold it (the MP spec
+    // calls for >=10ms), deassert, then two STARTUP IPIs with a short gap
+    // between them. Each send polls the ICR delivery-status bit internally
+    // (see `LocalApic`), so we never race ahead of the previous IPI still
+    // being accepted.
+    send_init_ipi(apic_id, true);
+    wait_microseconds(10_000);
+    send_init_ipi(apic_id, false);
+    wait_microseconds(200);
+
+    send_startup_ipi(apic_id, vector);
+    wait_microseconds(200);
+    send_startup_ipi(apic_id, vector);
+
+    // Wait for the AP to report itself online via the per-CPU tracker
+    // instead of a fixed delay.
+    let mut elapsed_us = 0u32;
+    while !crate::cpu::percpu::is_online(apic_id) {
+        if elapsed_us >= 200_000 {
+            return false;
+        }
+        wait_microseconds(200);
+        elapsed_us += 200;
     }
+    true
+}
+
+/// Wait for all booted APs to come online
+pub fn wait_for_aps(expected_cpus: u32, timeout_ms: u32) -> bool {
+    crate::cpu::percpu::wait_for_online(expected_cpus, timeout_ms)
+}
+
+/// Send an INIT IPI to a CPU with the given assert/deassert level.
+///
+/// Routes through `LocalApic::send_init_ipi`, which uses x2APIC (a single
+/// MSR write, no ID cap at 255) when the CPU supports it and falls back to
+/// polling the legacy xAPIC ICR delivery-status bit otherwise.
+fn send_init_ipi(apic_id: u32, assert: bool) {
+    use crate::x86_64::lapic::LocalApic;
+    LocalApic::new().send_init_ipi(apic_id, assert);
 }
 
 /// Send Startup IPI to a CPU
 fn send_startup_ipi(apic_id: u32, vector: u8) {
-    use crate::x86_64::cpu;
-    
-    let lapic_base = cpu::get_lapic_base();
-    
-    // ICR high register (destination)
-    let icr_high = lapic_base as *mut u32;
-    unsafe {
-        *icr_high.offset(0x10) = (apic_id as u32) << 24;
-    }
-    
-    // ICR low register (Startup IPI)
-    let icr_low = lapic_base as *mut u32;
-    unsafe {
-        *icr_low.offset(0x08) = 0x600 | (vector as u32);  // Startup IPI
-    }
+    use crate::x86_64::lapic::{DeliveryMode, LocalApic};
+    LocalApic::new().send_ipi(apic_id, DeliveryMode::Startup, vector);
 }
 
 /// Wait for a certain number of microseconds (approximate)
@@ -97,10 +97,17 @@ fn wait_microseconds(us: u32) {
 pub extern "C" fn ap_startup_main(cpu_id: u32) -> ! {
     // Initialize CPU-specific state
     // Set up GDT, IDT, etc. for this CPU
-    
+
+    // Point GS_BASE at this CPU's `PerCpuBase` slot so it can find its own
+    // per-CPU data with a lock-free `gs:`-relative read instead of a
+    // lookup by id; see `crate::cpu::percpu`.
+    crate::cpu::percpu::install_percpu_base(cpu_id);
+
+    crate::cpu::percpu::mark_online(cpu_id);
+
     // TODO: Call kernel initialization for this CPU
-    
-    // For now, just halt
+
+    // Idle until woken by an IPI or the scheduler.
     loop {
         #[cfg(target_arch = "x86_64")]
         unsafe {
@@ -0,0 +1,169 @@
+/// Real-mode -> protected-mode -> long-mode trampoline for AP bring-up.
+///
+/// A STARTUP IPI resumes its target in 16-bit real mode with
+/// `CS:IP = (vector << 8):0`, i.e. at the physical address `vector << 12`.
+/// [`ap_boot::boot_ap`] always uses `AP_BOOT_ADDRESS` as that address, so
+/// this blob never needs to be position-independent: every jump target and
+/// GDT base below is `TRAMPOLINE_BASE + <offset within the blob>`, computed
+/// entirely by the assembler.
+///
+/// The one thing that *does* vary per AP -- the page table root to share
+/// with the BSP, this core's stack, and which logical CPU id it is -- is
+/// written into [`ApTrampolineParams`] at a fixed offset past the end of
+/// the code, by [`set_params`], after [`install`] has copied the blob down
+/// to `AP_BOOT_ADDRESS`.
+///
+/// This assumes the first megabyte of physical memory is identity-mapped
+/// in the page tables `smp_init` hands every AP (true of every page table
+/// this kernel builds), so the 64-bit stage can still dereference
+/// `TRAMPOLINE_BASE` once paging is live.
+use core::arch::global_asm;
+
+use super::ap_boot::AP_BOOT_ADDRESS;
+
+extern "C" {
+    pub fn ap_startup_begin();
+    pub fn ap_startup_end();
+}
+
+/// Offset from `AP_BOOT_ADDRESS` of the [`ApTrampolineParams`] block,
+/// comfortably past anywhere the code above it could grow before needing
+/// to bump `AP_STARTUP_SIZE` too.
+const PARAMS_OFFSET: usize = 0xFF0;
+
+/// Per-AP parameters the copied trampoline reads once it reaches long
+/// mode. `#[repr(C)]` so its field offsets match the hardcoded offsets the
+/// 64-bit stage in [`global_asm!`] below reads them at.
+#[repr(C)]
+pub struct ApTrampolineParams {
+    /// Page table root to share with the BSP (from `arch::x86_64::read_cr3`).
+    pub cr3: u64,
+    /// Top of this AP's private startup stack (stack grows down).
+    pub stack_top: u64,
+    /// Address of `ap_startup_main`, called once long mode is live.
+    pub entry: u64,
+    /// Logical CPU id, passed through as `ap_startup_main`'s argument.
+    pub cpu_id: u32,
+}
+
+/// Copy the assembled trampoline down to `AP_BOOT_ADDRESS`. Idempotent and
+/// cheap enough to call once before booting the first AP; every later AP
+/// only needs its own [`set_params`] call before its STARTUP IPI.
+///
+/// # Safety
+/// `AP_BOOT_ADDRESS` must be mapped and otherwise unused low memory, and
+/// no AP may be running code there while this copy happens.
+pub unsafe fn install() {
+    let begin = ap_startup_begin as usize;
+    let end = ap_startup_end as usize;
+    let len = end - begin;
+    debug_assert!(
+        len <= PARAMS_OFFSET,
+        "trampoline grew past its parameter block"
+    );
+
+    core::ptr::copy_nonoverlapping(begin as *const u8, AP_BOOT_ADDRESS as *mut u8, len);
+}
+
+/// Patch the parameter block the copied trampoline will read. Call once
+/// per AP, after [`install`] and before sending that AP's STARTUP IPIs.
+///
+/// # Safety
+/// Must not race a previously-started AP that hasn't read its own params
+/// yet -- callers boot APs one at a time for exactly this reason.
+pub unsafe fn set_params(params: ApTrampolineParams) {
+    let ptr = (AP_BOOT_ADDRESS as usize + PARAMS_OFFSET) as *mut ApTrampolineParams;
+    core::ptr::write_volatile(ptr, params);
+}
+
+global_asm!(
+    r#"
+    .section .text
+    .att_syntax
+    .equ TRAMPOLINE_BASE, 0x8000
+    .equ PARAMS_OFFSET, 0xFF0
+
+    .global ap_startup_begin
+    .global ap_startup_end
+
+    .code16
+ap_startup_begin:
+    cli
+    xorw %ax, %ax
+    movw %ax, %ds
+    movw %ax, %es
+    movw %ax, %ss
+
+    lgdtl TRAMPOLINE_BASE + (gdt32_ptr - ap_startup_begin)
+
+    movl %cr0, %eax
+    orl $1, %eax
+    movl %eax, %cr0
+
+    ljmpl $0x08, $(TRAMPOLINE_BASE + (pm_entry - ap_startup_begin))
+
+    .align 8
+gdt32:
+    .quad 0
+    .quad 0x00CF9A000000FFFF
+    .quad 0x00CF92000000FFFF
+gdt32_ptr:
+    .word . - gdt32 - 1
+    .long TRAMPOLINE_BASE + (gdt32 - ap_startup_begin)
+
+    .align 8
+gdt64:
+    .quad 0
+    .quad 0x00AF9A000000FFFF
+    .quad 0x00AF92000000FFFF
+gdt64_ptr:
+    .word . - gdt64 - 1
+    .long TRAMPOLINE_BASE + (gdt64 - ap_startup_begin)
+
+    .code32
+pm_entry:
+    movw $0x10, %ax
+    movw %ax, %ds
+    movw %ax, %es
+    movw %ax, %ss
+
+    movl $(PARAMS_OFFSET), %esi
+    addl $TRAMPOLINE_BASE, %esi
+
+    movl %cr4, %eax
+    orl $(1 << 5), %eax
+    movl %eax, %cr4
+
+    movl (%esi), %eax
+    movl %eax, %cr3
+
+    movl $0xC0000080, %ecx
+    rdmsr
+    orl $(1 << 8), %eax
+    wrmsr
+
+    movl %cr0, %eax
+    orl $(1 << 31), %eax
+    movl %eax, %cr0
+
+    lgdtl TRAMPOLINE_BASE + (gdt64_ptr - ap_startup_begin)
+    ljmpl $0x08, $(TRAMPOLINE_BASE + (lm_entry - ap_startup_begin))
+
+    .code64
+lm_entry:
+    movw $0x10, %ax
+    movw %ax, %ds
+    movw %ax, %es
+    movw %ax, %ss
+
+    movl $(TRAMPOLINE_BASE + PARAMS_OFFSET), %esi
+    movq 8(%rsi), %rsp
+    movl 24(%rsi), %edi
+    movq 16(%rsi), %rax
+    callq *%rax
+    hlt
+    jmp .
+
+ap_startup_end:
+"#
+);
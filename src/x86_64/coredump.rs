@@ -0,0 +1,117 @@
+//! x86_64 backend for the [`crate::coredump`] ELF core writer: knows how to
+//! snapshot the current register file into a `prstatus`-shaped note and how
+//! to read a [`MemoryRegion`]'s bytes back out of physical memory.
+
+use core::arch::asm;
+
+use alloc::vec::Vec;
+
+use crate::coredump::{write_note, CpuElf64Writable, EM_X86_64, NT_PRSTATUS};
+use crate::memory::{paging::temporary_map, MemoryRegion};
+use crate::x86_64::cpu::read_msr;
+
+/// `IA32_FS_BASE` / `IA32_GS_BASE` MSRs, read to fill in the two fields a
+/// raw GP-register snapshot can't provide on its own.
+const MSR_FS_BASE: u32 = 0xC0000100;
+const MSR_GS_BASE: u32 = 0xC0000101;
+
+/// The 27-entry `struct user_regs_struct` x86_64 Linux core files use for
+/// `NT_PRSTATUS`, in its canonical order: r15, r14, r13, r12, rbp, rbx,
+/// r11, r10, r9, r8, rax, rcx, rdx, rsi, rdi, orig_rax, rip, cs, eflags,
+/// rsp, ss, fs_base, gs_base, ds, es, fs, gs.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+struct UserRegs {
+    r15: u64, r14: u64, r13: u64, r12: u64,
+    rbp: u64, rbx: u64,
+    r11: u64, r10: u64, r9: u64, r8: u64,
+    rax: u64, rcx: u64, rdx: u64, rsi: u64, rdi: u64,
+    orig_rax: u64,
+    rip: u64, cs: u64, eflags: u64, rsp: u64, ss: u64,
+    fs_base: u64, gs_base: u64,
+    ds: u64, es: u64, fs: u64, gs: u64,
+}
+
+/// Snapshot the currently-running CPU's register file. This runs inside
+/// the panic path itself, so it captures the state panicking left the CPU
+/// in rather than the state of whatever originally triggered it -- there's
+/// no earlier trap frame available when `panic!()` was reached through
+/// ordinary Rust control flow instead of a CPU exception.
+fn snapshot_registers() -> UserRegs {
+    let mut regs = UserRegs::default();
+
+    unsafe {
+        asm!("mov {}, rbx", out(reg) regs.rbx, options(nomem, nostack, preserves_flags));
+        asm!("mov {}, rbp", out(reg) regs.rbp, options(nomem, nostack, preserves_flags));
+        asm!("mov {}, rsp", out(reg) regs.rsp, options(nomem, nostack, preserves_flags));
+        asm!("mov {}, r12", out(reg) regs.r12, options(nomem, nostack, preserves_flags));
+        asm!("mov {}, r13", out(reg) regs.r13, options(nomem, nostack, preserves_flags));
+        asm!("mov {}, r14", out(reg) regs.r14, options(nomem, nostack, preserves_flags));
+        asm!("mov {}, r15", out(reg) regs.r15, options(nomem, nostack, preserves_flags));
+        asm!("pushfq", "pop {}", out(reg) regs.eflags, options(nostack));
+        asm!("mov {:r}, cs", out(reg) regs.cs, options(nomem, nostack, preserves_flags));
+        asm!("mov {:r}, ss", out(reg) regs.ss, options(nomem, nostack, preserves_flags));
+        asm!("mov {:r}, ds", out(reg) regs.ds, options(nomem, nostack, preserves_flags));
+        asm!("mov {:r}, es", out(reg) regs.es, options(nomem, nostack, preserves_flags));
+        asm!("mov {:r}, fs", out(reg) regs.fs, options(nomem, nostack, preserves_flags));
+        asm!("mov {:r}, gs", out(reg) regs.gs, options(nomem, nostack, preserves_flags));
+        asm!("lea {}, [rip + 0]", out(reg) regs.rip, options(nomem, nostack, preserves_flags));
+    }
+
+    regs.fs_base = read_msr(MSR_FS_BASE);
+    regs.gs_base = read_msr(MSR_GS_BASE);
+
+    regs
+}
+
+fn encode_user_regs(regs: &UserRegs) -> Vec<u8> {
+    let mut desc = Vec::with_capacity(27 * 8);
+    for value in [
+        regs.r15, regs.r14, regs.r13, regs.r12, regs.rbp, regs.rbx,
+        regs.r11, regs.r10, regs.r9, regs.r8,
+        regs.rax, regs.rcx, regs.rdx, regs.rsi, regs.rdi,
+        regs.orig_rax, regs.rip, regs.cs, regs.eflags, regs.rsp, regs.ss,
+        regs.fs_base, regs.gs_base, regs.ds, regs.es, regs.fs, regs.gs,
+    ] {
+        desc.extend_from_slice(&value.to_le_bytes());
+    }
+    desc
+}
+
+pub struct X86_64CoreWriter;
+
+impl CpuElf64Writable for X86_64CoreWriter {
+    fn elf_machine(&self) -> u16 {
+        EM_X86_64
+    }
+
+    fn write_prstatus(&self, out: &mut Vec<u8>, cpu_id: u32) {
+        // `struct elf_prstatus`: everything up to `pr_reg` is unused here
+        // (signal/process bookkeeping this kernel doesn't have); pad it
+        // with zeroes and carry `cpu_id` as `pr_pid` so a reader can tell
+        // which CPU each note belongs to.
+        let mut desc = Vec::new();
+        desc.extend_from_slice(&[0u8; 32]); // pr_info + pr_cursig + pr_sigpend + pr_sighold
+        desc.extend_from_slice(&cpu_id.to_le_bytes()); // pr_pid
+        desc.extend_from_slice(&0u32.to_le_bytes()); // pr_ppid
+        desc.extend_from_slice(&0u32.to_le_bytes()); // pr_pgrp
+        desc.extend_from_slice(&0u32.to_le_bytes()); // pr_sid
+        desc.extend_from_slice(&[0u8; 32]); // pr_utime/pr_stime/pr_cutime/pr_cstime
+
+        let regs = snapshot_registers();
+        desc.extend_from_slice(&encode_user_regs(&regs));
+
+        desc.extend_from_slice(&0u32.to_le_bytes()); // pr_fpvalid
+
+        write_note(out, b"CORE", NT_PRSTATUS, &desc);
+    }
+
+    fn write_mem_regions(&self, out: &mut Vec<u8>, region: &MemoryRegion) {
+        let ptr = temporary_map(region.start).as_u64() as *const u8;
+        unsafe {
+            for i in 0..region.size {
+                out.push(*ptr.add(i));
+            }
+        }
+    }
+}
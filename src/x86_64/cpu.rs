@@ -7,6 +7,10 @@
 /// - LAPIC operations
 
 use core::arch::asm;
+use core::sync::atomic::{AtomicU64, Ordering};
+use alloc::vec::Vec;
+use crate::memory::paging::temporary_map;
+use crate::memory::PhysAddr;
 
 /// CPUID results
 #[derive(Debug, Clone, Copy)]
@@ -64,6 +68,122 @@ pub fn has_cpuid_leaf(leaf: u32) -> bool {
     result.eax >= leaf
 }
 
+/// Check if an extended CPUID leaf (0x8000_0000 and above) exists.
+pub fn has_extended_cpuid_leaf(leaf: u32) -> bool {
+    let result = cpuid(0x8000_0000, 0);
+    result.eax >= leaf
+}
+
+/// CPU vendor, parsed from [`get_vendor_string`]'s 12-byte vendor string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuVendor {
+    Intel,
+    Amd,
+    Hygon,
+    Centaur,
+    Unknown,
+}
+
+impl CpuVendor {
+    pub fn from_vendor_string(vendor: &[u8; 12]) -> Self {
+        match vendor {
+            b"GenuineIntel" => CpuVendor::Intel,
+            b"AuthenticAMD" => CpuVendor::Amd,
+            b"HygonGenuine" => CpuVendor::Hygon,
+            b"CentaurHauls" => CpuVendor::Centaur,
+            _ => CpuVendor::Unknown,
+        }
+    }
+
+    pub fn detect() -> Self {
+        Self::from_vendor_string(&get_vendor_string())
+    }
+}
+
+/// Standard leaf-1 ECX/EDX and leaf-7 extended feature flags, decoded into
+/// named booleans so callers (the SMP init path in particular) can gate
+/// behavior like x2APIC or FSGSBASE on actual capability instead of
+/// assuming it and bit-twiddling a raw `(ecx, edx)` tuple at every call
+/// site.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CpuFeatures {
+    pub sse: bool,
+    pub sse2: bool,
+    pub avx: bool,
+    pub avx2: bool,
+    pub fma: bool,
+    pub xsave: bool,
+    pub fsgsbase: bool,
+    pub smep: bool,
+    pub smap: bool,
+    pub x2apic: bool,
+    pub tsc_deadline: bool,
+    pub pcid: bool,
+    pub rdrand: bool,
+    pub rdseed: bool,
+    pub pages_1g: bool,
+    pub nx: bool,
+}
+
+impl CpuFeatures {
+    /// Probe CPUID leaves 1, 7 and 0x8000_0001 and decode the feature bits
+    /// this kernel cares about.
+    pub fn detect() -> Self {
+        let leaf1 = cpuid(1, 0);
+        let mut features = CpuFeatures {
+            sse: leaf1.edx & (1 << 25) != 0,
+            sse2: leaf1.edx & (1 << 26) != 0,
+            avx: leaf1.ecx & (1 << 28) != 0,
+            fma: leaf1.ecx & (1 << 12) != 0,
+            xsave: leaf1.ecx & (1 << 26) != 0,
+            x2apic: leaf1.ecx & (1 << 21) != 0,
+            tsc_deadline: leaf1.ecx & (1 << 24) != 0,
+            pcid: leaf1.ecx & (1 << 17) != 0,
+            rdrand: leaf1.ecx & (1 << 30) != 0,
+            ..Default::default()
+        };
+
+        if has_cpuid_leaf(7) {
+            let leaf7 = cpuid(7, 0);
+            features.fsgsbase = leaf7.ebx & (1 << 0) != 0;
+            features.avx2 = leaf7.ebx & (1 << 5) != 0;
+            features.smep = leaf7.ebx & (1 << 7) != 0;
+            features.rdseed = leaf7.ebx & (1 << 18) != 0;
+            features.smap = leaf7.ebx & (1 << 20) != 0;
+        }
+
+        if has_extended_cpuid_leaf(0x8000_0001) {
+            let ext1 = cpuid(0x8000_0001, 0);
+            features.nx = ext1.edx & (1 << 20) != 0;
+            features.pages_1g = ext1.edx & (1 << 26) != 0;
+        }
+
+        features
+    }
+}
+
+/// CPU brand string from extended leaves 0x8000_0002-0x8000_0004 (e.g.
+/// "Intel(R) Core(TM) i7-..."), NUL-padded to 48 bytes. Empty (all zero)
+/// if the CPU doesn't report extended leaf 0x8000_0004.
+pub fn brand_string() -> [u8; 48] {
+    let mut brand = [0u8; 48];
+
+    if !has_extended_cpuid_leaf(0x8000_0004) {
+        return brand;
+    }
+
+    for (i, leaf) in (0x8000_0002u32..=0x8000_0004u32).enumerate() {
+        let result = cpuid(leaf, 0);
+        let offset = i * 16;
+        brand[offset..offset + 4].copy_from_slice(&result.eax.to_le_bytes());
+        brand[offset + 4..offset + 8].copy_from_slice(&result.ebx.to_le_bytes());
+        brand[offset + 8..offset + 12].copy_from_slice(&result.ecx.to_le_bytes());
+        brand[offset + 12..offset + 16].copy_from_slice(&result.edx.to_le_bytes());
+    }
+
+    brand
+}
+
 /// Read Local APIC ID from MSR
 pub fn read_apic_id() -> u32 {
     let mut apic_id: u32;
@@ -78,25 +198,40 @@ pub fn read_apic_id() -> u32 {
     (apic_id >> 24) & 0xFF
 }
 
-/// Send IPI (Inter-Processor Interrupt)
+/// Send a fixed-delivery IPI carrying `vector` to `dest_apic_id`.
+///
+/// Routes through [`crate::x86_64::lapic::LocalApic`], which picks x2APIC
+/// (a single MSR write) or the legacy xAPIC MMIO ICR (at the correct
+/// 0x300/0x310 offsets, with a delivery-status poll) depending on what the
+/// CPU supports, rather than poking the ICR directly at the wrong offsets.
 pub fn send_ipi(dest_apic_id: u32, vector: u32) {
-    let lapic_base = get_lapic_base();
-    
-    // ICR high register (destination)
-    let icr_high = lapic_base as *mut u32;
-    unsafe {
-        *icr_high.offset(0x10) = dest_apic_id << 24;
-    }
-    
-    // ICR low register (vector and command)
-    let icr_low = lapic_base as *mut u32;
-    unsafe {
-        *icr_low.offset(0x08) = vector & 0xFF;
-    }
+    use crate::x86_64::lapic::{DeliveryMode, LocalApic};
+    LocalApic::new().send_ipi(dest_apic_id, DeliveryMode::Fixed, vector as u8);
 }
 
-/// Get LAPIC base address from MSR
+/// LAPIC base address as ACPI's MADT header (or a type 5 Local APIC
+/// Address Override entry) reports it, recorded once via
+/// [`set_lapic_base_override`] during CPU enumeration. Zero means no
+/// override has been recorded yet, so [`get_lapic_base`] falls back to
+/// the MSR-derived value.
+static LAPIC_BASE_OVERRIDE: AtomicU64 = AtomicU64::new(0);
+
+/// Record the LAPIC base MADT parsing discovered, so [`get_lapic_base`]
+/// returns it instead of re-deriving from `IA32_APIC_BASE` -- the MADT
+/// value is authoritative when a 64-bit address override is present,
+/// which the MSR (32 bits wide) cannot represent.
+pub fn set_lapic_base_override(base: u64) {
+    LAPIC_BASE_OVERRIDE.store(base, Ordering::Relaxed);
+}
+
+/// Get LAPIC base address: the ACPI-reported override if one has been
+/// recorded, otherwise the MSR-only value.
 pub fn get_lapic_base() -> u64 {
+    let override_base = LAPIC_BASE_OVERRIDE.load(Ordering::Relaxed);
+    if override_base != 0 {
+        return override_base;
+    }
+
     let mut base: u64;
     unsafe {
         asm!(
@@ -174,17 +309,594 @@ pub fn enumerate_cpus() -> u32 {
     1
 }
 
-/// Basic ACPI MADT parsing for CPU enumeration
-/// This is simplified - full implementation would parse MADT properly
+/// Structured CPU topology, derived from whichever of CPUID leaf 0x1F
+/// (preferred; adds the die level), 0x0B (SMT/core only), or 0x04
+/// (core count only) is available.
+#[derive(Debug, Clone, Copy)]
+pub struct CpuTopology {
+    pub threads_per_core: u8,
+    pub cores_per_die: u8,
+    pub dies_per_socket: u8,
+    pub total_logical: u32,
+    /// The x2APIC ID shift width CPUID itself reports for each level
+    /// (SMT, core, die; 0 for a level the CPU doesn't expose), so callers
+    /// that need CPUID's own bit layout don't have to re-derive it from
+    /// the counts above.
+    pub x2apic_id_shift_widths: [u8; 3],
+}
+
+impl CpuTopology {
+    /// The `(threads_per_core, cores_per_die, dies_per_socket)` triple
+    /// [`get_x2apic_id`] expects.
+    pub fn as_counts(&self) -> (u8, u8, u8) {
+        (self.threads_per_core, self.cores_per_die, self.dies_per_socket)
+    }
+}
+
+/// CPUID leaf 0x1F/0x0B extended-topology level types, from `ECX[15:8]`.
+const TOPOLOGY_LEVEL_SMT: u32 = 1;
+const TOPOLOGY_LEVEL_CORE: u32 = 2;
+const TOPOLOGY_LEVEL_DIE: u32 = 5;
+
+/// Walk `leaf` (0x1F or 0x0B) subleaf by subleaf until the level type in
+/// `ECX[15:8]` reads 0, accumulating the SMT/core/die levels it reports.
+/// `None` if the leaf isn't present or reports no valid level at all.
+fn topology_via_extended_leaf(leaf: u32) -> Option<CpuTopology> {
+    if !has_cpuid_leaf(leaf) {
+        return None;
+    }
+
+    let mut threads_per_core = 1u32;
+    let mut total_at_core_level = 0u32;
+    let mut total_at_die_level = 0u32;
+    let mut shift_widths = [0u8; 3];
+    let mut total_logical = 0u32;
+    let mut saw_level = false;
+
+    for subleaf in 0..16u32 {
+        let result = cpuid(leaf, subleaf);
+        let level_type = (result.ecx >> 8) & 0xFF;
+        if level_type == 0 {
+            break;
+        }
+        saw_level = true;
+
+        let shift = (result.eax & 0x1F) as u8;
+        let count_at_level = (result.ebx & 0xFFFF).max(1);
+        total_logical = total_logical.max(count_at_level);
+
+        match level_type {
+            TOPOLOGY_LEVEL_SMT => {
+                threads_per_core = count_at_level;
+                shift_widths[0] = shift;
+            }
+            TOPOLOGY_LEVEL_CORE => {
+                total_at_core_level = count_at_level;
+                shift_widths[1] = shift;
+            }
+            TOPOLOGY_LEVEL_DIE => {
+                total_at_die_level = count_at_level;
+                shift_widths[2] = shift;
+            }
+            _ => {} // Module/Tile (3/4): not modeled, only used for total_logical above.
+        }
+    }
+
+    if !saw_level {
+        return None;
+    }
+
+    let cores_per_die = if total_at_core_level > 0 {
+        (total_at_core_level / threads_per_core).max(1)
+    } else {
+        1
+    };
+    let dies_per_socket = if total_at_die_level > 0 {
+        (total_at_die_level / total_at_core_level.max(threads_per_core)).max(1)
+    } else {
+        1
+    };
+
+    Some(CpuTopology {
+        threads_per_core: threads_per_core.min(u8::MAX as u32) as u8,
+        cores_per_die: cores_per_die.min(u8::MAX as u32) as u8,
+        dies_per_socket: dies_per_socket.min(u8::MAX as u32) as u8,
+        total_logical,
+        x2apic_id_shift_widths: shift_widths,
+    })
+}
+
+/// Fall back to leaf 0x04's cache-parameter EAX, which carries the core
+/// count (minus one) in bits 31:26 with no SMT/die information at all.
+fn topology_via_leaf_04() -> Option<CpuTopology> {
+    if !has_cpuid_leaf(0x04) {
+        return None;
+    }
+    let result = cpuid(0x04, 0);
+    if result.eax == 0 {
+        return None;
+    }
+    let cores = ((result.eax >> 26) & 0x3F) + 1;
+    let core_shift = (u32::BITS - (cores - 1).leading_zeros()) as u8;
+
+    Some(CpuTopology {
+        threads_per_core: 1,
+        cores_per_die: cores.min(u8::MAX as u32) as u8,
+        dies_per_socket: 1,
+        total_logical: cores,
+        x2apic_id_shift_widths: [0, core_shift, 0],
+    })
+}
+
+/// Detect the running CPU's topology: leaf 0x1F if present, else leaf
+/// 0x0B, else leaf 0x04's core count alone, else a single core with no
+/// SMT/die structure at all.
+pub fn detect_cpu_topology() -> CpuTopology {
+    topology_via_extended_leaf(0x1F)
+        .or_else(|| topology_via_extended_leaf(0x0B))
+        .or_else(topology_via_leaf_04)
+        .unwrap_or(CpuTopology {
+            threads_per_core: 1,
+            cores_per_die: 1,
+            dies_per_socket: 1,
+            total_logical: 1,
+            x2apic_id_shift_widths: [0, 0, 0],
+        })
+}
+
+/// Synthesize an x2APIC ID from a linear `cpu_id` and the CPU's topology,
+/// so the ID decomposes back into `{thread, core, die, socket}` the same
+/// way real hardware's does -- a naive `cpu_id` passthrough only happens
+/// to work when every level's count is a power of two.
+///
+/// `topology` is `(threads_per_core, cores_per_die, dies_per_socket)`.
+pub fn get_x2apic_id(cpu_id: u32, topology: (u8, u8, u8)) -> u32 {
+    let (threads_per_core, cores_per_die, dies_per_socket) = topology;
+    let threads = (threads_per_core as u32).max(1);
+    let cores = (cores_per_die as u32).max(1);
+    let dies = (dies_per_socket as u32).max(1);
+
+    // Width (in bits) needed to represent `count` distinct IDs at a level;
+    // 0 when `count == 1` since `(1 - 1).leading_zeros() == u32::BITS`.
+    let thread_bits = u32::BITS - (threads - 1).leading_zeros();
+    let core_bits = u32::BITS - (cores - 1).leading_zeros();
+    let die_bits = u32::BITS - (dies - 1).leading_zeros();
+
+    let thread_id = cpu_id % threads;
+    let core_id = (cpu_id / threads) % cores;
+    let die_id = (cpu_id / (threads * cores)) % dies;
+    let socket_id = cpu_id / (threads * cores * dies);
+
+    thread_id
+        | (core_id << thread_bits)
+        | (die_id << (thread_bits + core_bits))
+        | (socket_id << (thread_bits + core_bits + die_bits))
+}
+
+/// ACPI MADT (Multiple APIC Description Table) parsing for CPU enumeration.
+///
+/// One discovered logical processor, already filtered down to those ACPI
+/// reports as usable (see [`parse_madt`]).
 pub struct MadtEntry {
     pub apic_id: u32,
     pub processor_id: u32,
 }
 
-pub fn parse_madt(_madt_address: u64) -> [MadtEntry; 0] {
-    // Placeholder - full MADT parsing would go here
-    // For now, we'll rely on other methods
-    []
+/// A type 1 (I/O APIC) MADT entry: an I/O APIC's id, its MMIO base, and the
+/// first global system interrupt (GSI) it routes, so an APIC-based
+/// interrupt driver can map a GSI to the I/O APIC that owns it.
+pub struct MadtIoApic {
+    pub io_apic_id: u8,
+    pub io_apic_address: u32,
+    pub global_system_interrupt_base: u32,
+}
+
+/// A type 2 (Interrupt Source Override) MADT entry: a legacy ISA IRQ that
+/// firmware has rerouted to a different GSI and/or polarity/trigger mode,
+/// which an APIC driver must honor instead of assuming `irq == gsi`.
+pub struct MadtInterruptOverride {
+    pub bus_source: u8,
+    pub irq_source: u8,
+    pub global_system_interrupt: u32,
+    pub flags: u16,
+}
+
+/// MADT interrupt-controller structure type bytes we understand.
+const MADT_TYPE_LOCAL_APIC: u8 = 0;
+const MADT_TYPE_IO_APIC: u8 = 1;
+const MADT_TYPE_INTERRUPT_SOURCE_OVERRIDE: u8 = 2;
+const MADT_TYPE_LOCAL_APIC_ADDRESS_OVERRIDE: u8 = 5;
+const MADT_TYPE_LOCAL_X2APIC: u8 = 9;
+
+/// Processor Local APIC flags (type 0 and type 9 entries): the processor is
+/// usable if it's already enabled, or if it's merely online-capable (can be
+/// enabled later, e.g. via a firmware call) -- either way it's a real CPU
+/// worth registering.
+const MADT_APIC_FLAG_ENABLED: u32 = 1 << 0;
+const MADT_APIC_FLAG_ONLINE_CAPABLE: u32 = 1 << 1;
+
+/// ACPI System Description Table header common to the RSDT/XSDT and every
+/// table they point at (MADT included).
+#[repr(C, packed)]
+struct SdtHeader {
+    signature: [u8; 4],
+    length: u32,
+    revision: u8,
+    checksum: u8,
+    oem_id: [u8; 6],
+    oem_table_id: [u8; 8],
+    oem_revision: u32,
+    creator_id: u32,
+    creator_revision: u32,
+}
+
+/// Fixed fields at the start of the MADT body, right after `SdtHeader`.
+#[repr(C, packed)]
+struct MadtHeader {
+    local_apic_address: u32,
+    flags: u32,
+}
+
+/// ACPI 1.0 RSDP. The v2+ structure extends this with `length`,
+/// `xsdt_address`, and a second checksum; we only need the v1 prefix to
+/// decide which of RSDT/XSDT to walk.
+#[repr(C, packed)]
+struct RsdpV1 {
+    signature: [u8; 8],
+    checksum: u8,
+    oem_id: [u8; 6],
+    revision: u8,
+    rsdt_address: u32,
+}
+
+#[repr(C, packed)]
+struct RsdpV2Ext {
+    length: u32,
+    xsdt_address: u64,
+    extended_checksum: u8,
+    reserved: [u8; 3],
+}
+
+/// BIOS read-only memory area the RSDP lives in when not handed to us
+/// directly, per the ACPI spec: the last 1KiB of the EBDA and
+/// `0xE0000..=0xFFFFF`, on a 16-byte boundary.
+const RSDP_SEARCH_START: u64 = 0x000E_0000;
+const RSDP_SEARCH_END: u64 = 0x000F_FFFF;
+
+/// Sum the bytes at `addr..addr+len` (via the physical->virtual alias) and
+/// report whether they add up to zero mod 256, as every ACPI table
+/// checksum requires.
+unsafe fn checksum_ok(addr: u64, len: usize) -> bool {
+    let base = temporary_map(PhysAddr::new(addr)).as_u64() as *const u8;
+    let mut sum: u8 = 0;
+    for i in 0..len {
+        sum = sum.wrapping_add(*base.add(i));
+    }
+    sum == 0
+}
+
+/// Scan the BIOS read-only area for the `"RSD PTR "` signature and return
+/// its physical address once its checksum validates.
+unsafe fn find_rsdp() -> Option<u64> {
+    let mut addr = RSDP_SEARCH_START;
+    while addr < RSDP_SEARCH_END {
+        let ptr = temporary_map(PhysAddr::new(addr)).as_u64() as *const u8;
+        let mut signature = [0u8; 8];
+        for (i, byte) in signature.iter_mut().enumerate() {
+            *byte = *ptr.add(i);
+        }
+        if &signature == b"RSD PTR " && checksum_ok(addr, core::mem::size_of::<RsdpV1>()) {
+            return Some(addr);
+        }
+        addr += 16;
+    }
+    None
+}
+
+/// Read an ACPI SDT's header and confirm its signature and whole-table
+/// checksum, returning its body length on success.
+unsafe fn validate_sdt(addr: u64, signature: &[u8; 4]) -> Option<u32> {
+    let header = &*(temporary_map(PhysAddr::new(addr)).as_u64() as *const SdtHeader);
+    if header.signature != *signature {
+        return None;
+    }
+    let length = header.length;
+    if checksum_ok(addr, length as usize) {
+        Some(length)
+    } else {
+        None
+    }
+}
+
+/// Locate the MADT (`"APIC"`) table by finding the RSDP, then walking
+/// whichever of the XSDT (64-bit entries, ACPI 2.0+) or RSDT (32-bit
+/// entries) it points at. Returns the MADT's physical address.
+pub fn find_madt_address() -> Option<u64> {
+    unsafe {
+        let rsdp_addr = find_rsdp()?;
+        let rsdp = &*(temporary_map(PhysAddr::new(rsdp_addr)).as_u64() as *const RsdpV1);
+
+        let (table_addr, entry_is_64bit) = if rsdp.revision >= 2 {
+            let ext = &*(temporary_map(PhysAddr::new(rsdp_addr + core::mem::size_of::<RsdpV1>() as u64))
+                .as_u64() as *const RsdpV2Ext);
+            let xsdt_address = ext.xsdt_address;
+            if xsdt_address != 0 {
+                (xsdt_address, true)
+            } else {
+                (rsdp.rsdt_address as u64, false)
+            }
+        } else {
+            (rsdp.rsdt_address as u64, false)
+        };
+
+        let signature = if entry_is_64bit { b"XSDT" } else { b"RSDT" };
+        let table_length = validate_sdt(table_addr, signature)?;
+
+        let entries_start = table_addr + core::mem::size_of::<SdtHeader>() as u64;
+        let entries_end = table_addr + table_length as u64;
+        let entry_size = if entry_is_64bit { 8 } else { 4 };
+
+        let mut cursor = entries_start;
+        while cursor + entry_size <= entries_end {
+            let sdt_addr = if entry_is_64bit {
+                *(temporary_map(PhysAddr::new(cursor)).as_u64() as *const u64)
+            } else {
+                *(temporary_map(PhysAddr::new(cursor)).as_u64() as *const u32) as u64
+            };
+
+            if validate_sdt(sdt_addr, b"APIC").is_some() {
+                return Some(sdt_addr);
+            }
+
+            cursor += entry_size;
+        }
+
+        None
+    }
+}
+
+/// Parse the MADT at `madt_address` into the list of usable logical
+/// processors it describes (type 0 Processor Local APIC and type 9
+/// Processor Local x2APIC entries, filtered to those ACPI marks enabled or
+/// online-capable).
+pub fn parse_madt(madt_address: u64) -> Vec<MadtEntry> {
+    let mut entries = Vec::new();
+
+    unsafe {
+        let header = &*(temporary_map(PhysAddr::new(madt_address)).as_u64() as *const SdtHeader);
+        let table_length = header.length;
+
+        let list_start = madt_address
+            + core::mem::size_of::<SdtHeader>() as u64
+            + core::mem::size_of::<MadtHeader>() as u64;
+        let list_end = madt_address + table_length as u64;
+
+        let mut cursor = list_start;
+        while cursor + 2 <= list_end {
+            let entry_ptr = temporary_map(PhysAddr::new(cursor)).as_u64() as *const u8;
+            let entry_type = *entry_ptr;
+            let entry_len = *entry_ptr.add(1) as u64;
+            if entry_len < 2 || cursor + entry_len > list_end {
+                break;
+            }
+
+            match entry_type {
+                MADT_TYPE_LOCAL_APIC => {
+                    let acpi_processor_id = *entry_ptr.add(2) as u32;
+                    let apic_id = *entry_ptr.add(3) as u32;
+                    let flags = u32::from_le_bytes([
+                        *entry_ptr.add(4),
+                        *entry_ptr.add(5),
+                        *entry_ptr.add(6),
+                        *entry_ptr.add(7),
+                    ]);
+                    if flags & (MADT_APIC_FLAG_ENABLED | MADT_APIC_FLAG_ONLINE_CAPABLE) != 0 {
+                        entries.push(MadtEntry { apic_id, processor_id: acpi_processor_id });
+                    }
+                }
+                MADT_TYPE_LOCAL_X2APIC => {
+                    let x2apic_id = u32::from_le_bytes([
+                        *entry_ptr.add(4),
+                        *entry_ptr.add(5),
+                        *entry_ptr.add(6),
+                        *entry_ptr.add(7),
+                    ]);
+                    let flags = u32::from_le_bytes([
+                        *entry_ptr.add(8),
+                        *entry_ptr.add(9),
+                        *entry_ptr.add(10),
+                        *entry_ptr.add(11),
+                    ]);
+                    let acpi_processor_uid = u32::from_le_bytes([
+                        *entry_ptr.add(12),
+                        *entry_ptr.add(13),
+                        *entry_ptr.add(14),
+                        *entry_ptr.add(15),
+                    ]);
+                    if flags & (MADT_APIC_FLAG_ENABLED | MADT_APIC_FLAG_ONLINE_CAPABLE) != 0 {
+                        entries.push(MadtEntry { apic_id: x2apic_id, processor_id: acpi_processor_uid });
+                    }
+                }
+                _ => {}
+            }
+
+            cursor += entry_len;
+        }
+    }
+
+    entries
+}
+
+/// Parse the MADT at `madt_address` into its type 1 (I/O APIC) entries,
+/// the same entry-walking loop as [`parse_madt`] but collecting a
+/// different structure type -- so the PIC module can eventually be
+/// replaced by an APIC driver that knows where each I/O APIC lives.
+pub fn parse_madt_io_apics(madt_address: u64) -> Vec<MadtIoApic> {
+    let mut entries = Vec::new();
+
+    unsafe {
+        let header = &*(temporary_map(PhysAddr::new(madt_address)).as_u64() as *const SdtHeader);
+        let table_length = header.length;
+
+        let list_start = madt_address
+            + core::mem::size_of::<SdtHeader>() as u64
+            + core::mem::size_of::<MadtHeader>() as u64;
+        let list_end = madt_address + table_length as u64;
+
+        let mut cursor = list_start;
+        while cursor + 2 <= list_end {
+            let entry_ptr = temporary_map(PhysAddr::new(cursor)).as_u64() as *const u8;
+            let entry_type = *entry_ptr;
+            let entry_len = *entry_ptr.add(1) as u64;
+            if entry_len < 2 || cursor + entry_len > list_end {
+                break;
+            }
+
+            if entry_type == MADT_TYPE_IO_APIC {
+                let io_apic_id = *entry_ptr.add(2);
+                let io_apic_address = u32::from_le_bytes([
+                    *entry_ptr.add(4),
+                    *entry_ptr.add(5),
+                    *entry_ptr.add(6),
+                    *entry_ptr.add(7),
+                ]);
+                let global_system_interrupt_base = u32::from_le_bytes([
+                    *entry_ptr.add(8),
+                    *entry_ptr.add(9),
+                    *entry_ptr.add(10),
+                    *entry_ptr.add(11),
+                ]);
+                entries.push(MadtIoApic { io_apic_id, io_apic_address, global_system_interrupt_base });
+            }
+
+            cursor += entry_len;
+        }
+    }
+
+    entries
+}
+
+/// Parse the MADT at `madt_address` into its type 2 (Interrupt Source
+/// Override) entries, describing legacy ISA IRQs firmware has rerouted to
+/// a different GSI or polarity/trigger mode.
+pub fn parse_madt_interrupt_overrides(madt_address: u64) -> Vec<MadtInterruptOverride> {
+    let mut entries = Vec::new();
+
+    unsafe {
+        let header = &*(temporary_map(PhysAddr::new(madt_address)).as_u64() as *const SdtHeader);
+        let table_length = header.length;
+
+        let list_start = madt_address
+            + core::mem::size_of::<SdtHeader>() as u64
+            + core::mem::size_of::<MadtHeader>() as u64;
+        let list_end = madt_address + table_length as u64;
+
+        let mut cursor = list_start;
+        while cursor + 2 <= list_end {
+            let entry_ptr = temporary_map(PhysAddr::new(cursor)).as_u64() as *const u8;
+            let entry_type = *entry_ptr;
+            let entry_len = *entry_ptr.add(1) as u64;
+            if entry_len < 2 || cursor + entry_len > list_end {
+                break;
+            }
+
+            if entry_type == MADT_TYPE_INTERRUPT_SOURCE_OVERRIDE {
+                let bus_source = *entry_ptr.add(2);
+                let irq_source = *entry_ptr.add(3);
+                let global_system_interrupt = u32::from_le_bytes([
+                    *entry_ptr.add(4),
+                    *entry_ptr.add(5),
+                    *entry_ptr.add(6),
+                    *entry_ptr.add(7),
+                ]);
+                let flags = u16::from_le_bytes([*entry_ptr.add(8), *entry_ptr.add(9)]);
+                entries.push(MadtInterruptOverride {
+                    bus_source,
+                    irq_source,
+                    global_system_interrupt,
+                    flags,
+                });
+            }
+
+            cursor += entry_len;
+        }
+    }
+
+    entries
+}
+
+/// I/O APICs discovered in the MADT, populated once by [`set_madt_io_apics`]
+/// during boot CPU enumeration; empty until then (or on ACPI-less
+/// firmware). Exposed so an APIC-based interrupt driver can eventually
+/// replace the legacy `pic` module without redoing MADT parsing.
+static mut IO_APICS: Vec<MadtIoApic> = Vec::new();
+
+/// Legacy ISA IRQ reroutes discovered in the MADT, populated once by
+/// [`set_madt_interrupt_overrides`] during boot CPU enumeration.
+static mut INTERRUPT_OVERRIDES: Vec<MadtInterruptOverride> = Vec::new();
+
+pub fn set_madt_io_apics(entries: Vec<MadtIoApic>) {
+    unsafe {
+        IO_APICS = entries;
+    }
+}
+
+/// The I/O APICs discovered during boot, if any.
+pub fn io_apics() -> &'static [MadtIoApic] {
+    unsafe { &IO_APICS }
+}
+
+pub fn set_madt_interrupt_overrides(entries: Vec<MadtInterruptOverride>) {
+    unsafe {
+        INTERRUPT_OVERRIDES = entries;
+    }
+}
+
+/// The legacy ISA IRQ reroutes discovered during boot, if any.
+pub fn interrupt_overrides() -> &'static [MadtInterruptOverride] {
+    unsafe { &INTERRUPT_OVERRIDES }
+}
+
+/// The LAPIC base address the MADT header advertises, taking into account
+/// a type 5 (64-bit Local APIC Address Override) entry if present -- per
+/// the ACPI spec, that override always wins over the 32-bit header field.
+pub fn madt_lapic_base(madt_address: u64) -> u64 {
+    unsafe {
+        let header = &*(temporary_map(PhysAddr::new(madt_address)).as_u64() as *const SdtHeader);
+        let table_length = header.length;
+        let madt = &*(temporary_map(PhysAddr::new(
+            madt_address + core::mem::size_of::<SdtHeader>() as u64,
+        ))
+        .as_u64() as *const MadtHeader);
+        let mut lapic_base = madt.local_apic_address as u64;
+
+        let list_start = madt_address
+            + core::mem::size_of::<SdtHeader>() as u64
+            + core::mem::size_of::<MadtHeader>() as u64;
+        let list_end = madt_address + table_length as u64;
+
+        let mut cursor = list_start;
+        while cursor + 2 <= list_end {
+            let entry_ptr = temporary_map(PhysAddr::new(cursor)).as_u64() as *const u8;
+            let entry_type = *entry_ptr;
+            let entry_len = *entry_ptr.add(1) as u64;
+            if entry_len < 2 || cursor + entry_len > list_end {
+                break;
+            }
+
+            if entry_type == MADT_TYPE_LOCAL_APIC_ADDRESS_OVERRIDE && entry_len >= 12 {
+                let mut bytes = [0u8; 8];
+                for (i, byte) in bytes.iter_mut().enumerate() {
+                    *byte = *entry_ptr.add(4 + i);
+                }
+                lapic_base = u64::from_le_bytes(bytes);
+            }
+
+            cursor += entry_len;
+        }
+
+        lapic_base
+    }
 }
 
 #[cfg(test)]
@@ -197,4 +909,16 @@ mod tests {
         // Should be non-zero for real hardware
         assert!(vendor[0] != 0 || vendor[1] != 0);
     }
+
+    #[test]
+    fn test_cpu_features_and_vendor() {
+        // Every CPU this kernel can boot on is expected to have SSE2 and a
+        // recognized vendor string; a real (non-virtualized) x86_64 host
+        // is assumed by this test suite.
+        let features = CpuFeatures::detect();
+        assert!(features.sse2);
+
+        let vendor = CpuVendor::detect();
+        assert_ne!(vendor, CpuVendor::Unknown);
+    }
 }
@@ -0,0 +1,474 @@
+//! GDB Remote Serial Protocol stub, wired to COM1.
+//!
+//! A developer attaches `gdb` (`target remote /dev/ttyS0` or similar) to the
+//! existing [`crate::drivers::serial::SERIAL1`] line. Vectors 1 (`#DB`) and 3
+//! (`#BP`) are pointed at naked trampolines that save the full general
+//! register file before handing off to [`command_loop`], instead of the
+//! generic panicking stubs `arch::interrupts::register_exception_handlers`
+//! installs -- the GP registers those stubs fault with are never visible to
+//! `extern "x86-interrupt"` Rust code, so reading/writing RAX..R15 for `g`/`G`
+//! needs its own entry point that pushes them onto the stack itself.
+
+use core::arch::naked_asm;
+use core::sync::atomic::{AtomicU64, Ordering};
+use alloc::vec::Vec;
+
+use crate::drivers::serial::SERIAL1;
+use crate::memory::VirtAddr;
+
+/// Full x86_64 general-purpose register file, in the order the trampolines
+/// below push/pop them. Field order matters: it is the push order reversed.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GdbRegisters {
+    pub r15: u64,
+    pub r14: u64,
+    pub r13: u64,
+    pub r12: u64,
+    pub r11: u64,
+    pub r10: u64,
+    pub r9: u64,
+    pub r8: u64,
+    pub rdi: u64,
+    pub rsi: u64,
+    pub rbp: u64,
+    pub rbx: u64,
+    pub rdx: u64,
+    pub rcx: u64,
+    pub rax: u64,
+    pub vector: u64,
+    pub rip: u64,
+    pub cs: u64,
+    pub rflags: u64,
+    pub rsp: u64,
+    pub ss: u64,
+}
+
+/// RFLAGS trap flag: set to single-step, cleared to run free.
+const RFLAGS_TRAP_FLAG: u64 = 1 << 8;
+
+/// `int3` opcode injected at a breakpoint address, in place of the original
+/// byte (which [`clear_breakpoint`] restores).
+const BREAKPOINT_OPCODE: u8 = 0xCC;
+
+/// Up to 8 simultaneous software breakpoints: `(address, original byte)`.
+/// A fixed-size table matches this kernel's other small bounded-state
+/// tables (e.g. `x86_64::cpu`'s per-CPU arrays) rather than pulling in
+/// `alloc::collections::BTreeMap`, which nothing else here uses.
+static mut BREAKPOINTS: [Option<(u64, u8)>; 8] = [None; 8];
+
+/// Set when the stub is actively driving a trap; guards against the command
+/// loop being re-entered from inside itself (e.g. a malformed packet causing
+/// a fault while we're already debugging a fault).
+static STUB_ACTIVE: AtomicU64 = AtomicU64::new(0);
+
+/// Install the `#DB`/`#BP` trampolines, overriding the panicking stubs
+/// `register_exception_handlers` put there. Called from
+/// [`crate::arch::interrupts::init_idt`] after the generic handlers are in
+/// place, so every other vector keeps panicking as before.
+pub fn init() {
+    use crate::arch::interrupts::InterruptDescriptorTable;
+
+    const KERNEL_CODE_SELECTOR: u16 = 0x08;
+    InterruptDescriptorTable::set_handler(1, debug_trampoline as u64, KERNEL_CODE_SELECTOR, 0, 0);
+    InterruptDescriptorTable::set_handler(3, breakpoint_trampoline as u64, KERNEL_CODE_SELECTOR, 0, 0);
+}
+
+/// Push every GPR used by `GdbRegisters` (in field order, reversed) and
+/// fall into `entry`, which in turn calls [`gdb_trap`] with the vector
+/// number and a pointer to the saved frame, then restores and `iretq`s.
+///
+/// `#DB` and `#BP` push no error code, so the only thing between `rsp` and
+/// the pushed GPRs is the CPU-pushed `rip/cs/rflags/rsp/ss` frame -- exactly
+/// the layout `GdbRegisters` expects once `vector` is pushed underneath it.
+macro_rules! trap_trampoline {
+    ($name:ident, $vector:expr) => {
+        #[unsafe(naked)]
+        unsafe extern "C" fn $name() -> ! {
+            naked_asm!(
+                "push {vector}",
+                "push rax", "push rcx", "push rdx", "push rbx",
+                "push rbp", "push rsi", "push rdi",
+                "push r8", "push r9", "push r10", "push r11",
+                "push r12", "push r13", "push r14", "push r15",
+                "mov rdi, rsp",
+                "call {handler}",
+                "pop r15", "pop r14", "pop r13", "pop r12",
+                "pop r11", "pop r10", "pop r9", "pop r8",
+                "pop rdi", "pop rsi", "pop rbp", "pop rbx",
+                "pop rdx", "pop rcx", "pop rax",
+                "add rsp, 8", // discard the pushed vector
+                "iretq",
+                vector = const $vector,
+                handler = sym gdb_trap,
+            );
+        }
+    };
+}
+
+trap_trampoline!(debug_trampoline, 1u64);
+trap_trampoline!(breakpoint_trampoline, 3u64);
+
+/// Entry point the trampolines call with `rdi` pointing at the just-saved
+/// [`GdbRegisters`]. Runs the RSP command loop until a `c` or `s` packet
+/// tells it to resume, backing off a `#BP` re-trigger on resume (stepping
+/// past the `int3` we're sitting on) and restoring single-step state.
+extern "C" fn gdb_trap(regs: *mut GdbRegisters) {
+    STUB_ACTIVE.fetch_add(1, Ordering::Relaxed);
+
+    unsafe {
+        let regs = &mut *regs;
+
+        // If we're sitting on an injected breakpoint, step back one byte so
+        // resuming re-executes the original instruction rather than the
+        // `0xCC` we overwrote it with.
+        if regs.vector == 3 {
+            if let Some(slot) = BREAKPOINTS.iter().find(|b| matches!(b, Some((addr, _)) if *addr == regs.rip.wrapping_sub(1))) {
+                if let Some((addr, _)) = slot {
+                    regs.rip = *addr;
+                }
+            }
+        }
+
+        let mut stub = GdbStub { regs };
+        stub.command_loop();
+    }
+
+    STUB_ACTIVE.fetch_sub(1, Ordering::Relaxed);
+}
+
+/// Driver for the register/memory/breakpoint operations a remote `gdb`
+/// session needs, kept separate from packet framing so `command_loop` reads
+/// as "parse a command, call the matching `Debuggable` method, reply".
+pub trait Debuggable {
+    fn read_registers(&self) -> GdbRegisters;
+    fn write_registers(&mut self, regs: &GdbRegisters);
+    fn read_memory(&self, addr: VirtAddr, len: usize) -> Vec<u8>;
+    fn write_memory(&mut self, addr: VirtAddr, data: &[u8]);
+    fn set_breakpoint(&mut self, addr: VirtAddr) -> bool;
+    fn clear_breakpoint(&mut self, addr: VirtAddr) -> bool;
+    fn set_single_step(&mut self, enabled: bool);
+}
+
+struct GdbStub {
+    regs: *mut GdbRegisters,
+}
+
+impl Debuggable for GdbStub {
+    fn read_registers(&self) -> GdbRegisters {
+        unsafe { *self.regs }
+    }
+
+    fn write_registers(&mut self, regs: &GdbRegisters) {
+        unsafe {
+            (*self.regs) = *regs;
+        }
+    }
+
+    fn read_memory(&self, addr: VirtAddr, len: usize) -> Vec<u8> {
+        let ptr = addr.as_u64() as *const u8;
+        let mut out = Vec::with_capacity(len);
+        unsafe {
+            for i in 0..len {
+                out.push(*ptr.add(i));
+            }
+        }
+        out
+    }
+
+    fn write_memory(&mut self, addr: VirtAddr, data: &[u8]) {
+        let ptr = addr.as_u64() as *mut u8;
+        unsafe {
+            for (i, byte) in data.iter().enumerate() {
+                *ptr.add(i) = *byte;
+            }
+        }
+    }
+
+    fn set_breakpoint(&mut self, addr: VirtAddr) -> bool {
+        unsafe {
+            if BREAKPOINTS.iter().any(|b| matches!(b, Some((a, _)) if *a == addr.as_u64())) {
+                return true; // already set
+            }
+            let Some(slot) = BREAKPOINTS.iter_mut().find(|b| b.is_none()) else {
+                return false; // breakpoint table full
+            };
+            let ptr = addr.as_u64() as *mut u8;
+            let original = *ptr;
+            *ptr = BREAKPOINT_OPCODE;
+            *slot = Some((addr.as_u64(), original));
+            true
+        }
+    }
+
+    fn clear_breakpoint(&mut self, addr: VirtAddr) -> bool {
+        unsafe {
+            let Some(slot) = BREAKPOINTS.iter_mut().find(|b| matches!(b, Some((a, _)) if *a == addr.as_u64())) else {
+                return false;
+            };
+            if let Some((a, original)) = *slot {
+                *(a as *mut u8) = original;
+            }
+            *slot = None;
+            true
+        }
+    }
+
+    fn set_single_step(&mut self, enabled: bool) {
+        unsafe {
+            let regs = &mut *self.regs;
+            if enabled {
+                regs.rflags |= RFLAGS_TRAP_FLAG;
+            } else {
+                regs.rflags &= !RFLAGS_TRAP_FLAG;
+            }
+        }
+    }
+}
+
+impl GdbStub {
+    /// Read and respond to packets until a `c`ontinue or `s`tep command
+    /// tells us to resume guest execution.
+    fn command_loop(&mut self) {
+        loop {
+            let packet = match read_packet() {
+                Some(p) => p,
+                None => continue,
+            };
+
+            if packet.is_empty() {
+                continue;
+            }
+
+            match packet[0] {
+                b'?' => send_packet(b"S05"),
+                b'g' => {
+                    let regs = self.read_registers();
+                    send_packet(&encode_registers(&regs));
+                }
+                b'G' => {
+                    if let Some(regs) = decode_registers(&packet[1..]) {
+                        self.write_registers(&regs);
+                        send_packet(b"OK");
+                    } else {
+                        send_packet(b"E01");
+                    }
+                }
+                b'm' => self.handle_read_memory(&packet[1..]),
+                b'M' => self.handle_write_memory(&packet[1..]),
+                b'Z' => self.handle_set_breakpoint(&packet[1..]),
+                b'z' => self.handle_clear_breakpoint(&packet[1..]),
+                b'c' => {
+                    self.set_single_step(false);
+                    return;
+                }
+                b's' => {
+                    self.set_single_step(true);
+                    return;
+                }
+                _ => send_packet(b""), // unsupported: empty reply per the RSP spec
+            }
+        }
+    }
+
+    fn handle_read_memory(&self, body: &[u8]) {
+        if let Some((addr, len)) = parse_addr_len(body) {
+            let bytes = self.read_memory(VirtAddr::new(addr), len);
+            send_packet(&encode_hex(&bytes));
+        } else {
+            send_packet(b"E01");
+        }
+    }
+
+    fn handle_write_memory(&mut self, body: &[u8]) {
+        let Some(colon) = body.iter().position(|&b| b == b':') else {
+            send_packet(b"E01");
+            return;
+        };
+        if let Some((addr, _len)) = parse_addr_len(&body[..colon]) {
+            if let Some(data) = decode_hex(&body[colon + 1..]) {
+                self.write_memory(VirtAddr::new(addr), &data);
+                send_packet(b"OK");
+                return;
+            }
+        }
+        send_packet(b"E01");
+    }
+
+    fn handle_set_breakpoint(&mut self, body: &[u8]) {
+        if let Some(addr) = parse_breakpoint_addr(body) {
+            if self.set_breakpoint(VirtAddr::new(addr)) {
+                send_packet(b"OK");
+                return;
+            }
+        }
+        send_packet(b"E01");
+    }
+
+    fn handle_clear_breakpoint(&mut self, body: &[u8]) {
+        if let Some(addr) = parse_breakpoint_addr(body) {
+            if self.clear_breakpoint(VirtAddr::new(addr)) {
+                send_packet(b"OK");
+                return;
+            }
+        }
+        send_packet(b"E01");
+    }
+}
+
+/// Parse a `Z0,<addr>,<kind>` / `z0,<addr>,<kind>` body, ignoring the
+/// breakpoint-type and kind fields: this stub only implements software
+/// breakpoints (type 0), which is what `int3` injection provides.
+fn parse_breakpoint_addr(body: &[u8]) -> Option<u64> {
+    let mut parts = body.split(|&b| b == b',');
+    let _kind = parts.next()?;
+    let addr = parts.next()?;
+    hex_to_u64(addr)
+}
+
+/// Parse an `<addr>,<len>` body shared by `m` and the address half of `M`.
+fn parse_addr_len(body: &[u8]) -> Option<(u64, usize)> {
+    let mut parts = body.split(|&b| b == b',');
+    let addr = hex_to_u64(parts.next()?)?;
+    let len = hex_to_u64(parts.next()?)? as usize;
+    Some((addr, len))
+}
+
+fn hex_to_u64(digits: &[u8]) -> Option<u64> {
+    if digits.is_empty() {
+        return None;
+    }
+    let mut value = 0u64;
+    for &digit in digits {
+        value = (value << 4) | hex_value(digit)? as u64;
+    }
+    Some(value)
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+fn encode_hex(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len() * 2);
+    for &byte in bytes {
+        out.push(HEX_DIGITS[(byte >> 4) as usize]);
+        out.push(HEX_DIGITS[(byte & 0xF) as usize]);
+    }
+    out
+}
+
+fn decode_hex(digits: &[u8]) -> Option<Vec<u8>> {
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(digits.len() / 2);
+    for pair in digits.chunks(2) {
+        out.push((hex_value(pair[0])? << 4) | hex_value(pair[1])?);
+    }
+    Some(out)
+}
+
+/// Encode a register set in GDB's `g`-reply order for x86_64: RAX, RBX, RCX,
+/// RDX, RSI, RDI, RBP, RSP, R8-R15, RIP, EFLAGS, CS, SS, DS, ES, FS, GS. This
+/// stub doesn't track DS/ES/FS/GS separately from CS/SS, so it reports them
+/// as equal to SS, which is true for every selector this kernel actually
+/// loads (there's a single flat ring-0 data segment).
+fn encode_registers(regs: &GdbRegisters) -> Vec<u8> {
+    let mut out = Vec::new();
+    for value in [
+        regs.rax, regs.rbx, regs.rcx, regs.rdx, regs.rsi, regs.rdi, regs.rbp, regs.rsp,
+        regs.r8, regs.r9, regs.r10, regs.r11, regs.r12, regs.r13, regs.r14, regs.r15,
+        regs.rip, regs.rflags, regs.cs, regs.ss, regs.ss, regs.ss, regs.ss, regs.ss,
+    ] {
+        out.extend_from_slice(&encode_hex(&value.to_le_bytes()));
+    }
+    out
+}
+
+/// Inverse of [`encode_registers`]; returns `None` on a malformed `G` packet
+/// rather than partially applying it.
+fn decode_registers(hex: &[u8]) -> Option<GdbRegisters> {
+    let bytes = decode_hex(hex)?;
+    if bytes.len() < 24 * 8 {
+        return None;
+    }
+    let read = |i: usize| -> u64 {
+        let mut b = [0u8; 8];
+        b.copy_from_slice(&bytes[i * 8..i * 8 + 8]);
+        u64::from_le_bytes(b)
+    };
+    Some(GdbRegisters {
+        rax: read(0), rbx: read(1), rcx: read(2), rdx: read(3),
+        rsi: read(4), rdi: read(5), rbp: read(6), rsp: read(7),
+        r8: read(8), r9: read(9), r10: read(10), r11: read(11),
+        r12: read(12), r13: read(13), r14: read(14), r15: read(15),
+        rip: read(16), rflags: read(17), cs: read(18), ss: read(19),
+        vector: 0,
+    })
+}
+
+/// Read one `$<payload>#<checksum>` packet from [`SERIAL1`], ACKing it with
+/// `+` once the checksum matches (`-` and a retry otherwise). Returns `None`
+/// on a malformed frame so the caller's loop just tries again.
+fn read_packet() -> Option<Vec<u8>> {
+    let mut serial = SERIAL1.lock();
+
+    loop {
+        let byte = serial.receive();
+        if byte == b'$' {
+            break;
+        }
+        // Ignore stray ACKs/NACKs or noise preceding the next packet.
+    }
+
+    let mut payload = Vec::new();
+    loop {
+        let byte = serial.receive();
+        if byte == b'#' {
+            break;
+        }
+        payload.push(byte);
+    }
+
+    let checksum_hi = hex_value(serial.receive())?;
+    let checksum_lo = hex_value(serial.receive())?;
+    let expected = (checksum_hi << 4) | checksum_lo;
+
+    let actual = payload.iter().fold(0u8, |acc, &b| acc.wrapping_add(b));
+    if actual == expected {
+        serial.send(b'+');
+        Some(payload)
+    } else {
+        serial.send(b'-');
+        None
+    }
+}
+
+/// Write one `$<payload>#<checksum>` packet to [`SERIAL1`] and wait for the
+/// receiver's ack, resending on `-` the way the RSP spec requires.
+fn send_packet(payload: &[u8]) {
+    let checksum = payload.iter().fold(0u8, |acc, &b| acc.wrapping_add(b));
+    let mut serial = SERIAL1.lock();
+
+    loop {
+        serial.send(b'$');
+        for &byte in payload {
+            serial.send(byte);
+        }
+        serial.send(b'#');
+        serial.send(HEX_DIGITS[(checksum >> 4) as usize]);
+        serial.send(HEX_DIGITS[(checksum & 0xF) as usize]);
+
+        if serial.receive() == b'+' {
+            return;
+        }
+    }
+}
@@ -0,0 +1,264 @@
+/// Local APIC Abstraction
+///
+/// Provides a single `send_ipi` entry point used by both INIT and STARTUP
+/// IPI delivery, transparently routing through x2APIC (a single 64-bit
+/// `wrmsr` to the ICR MSR) when available, or falling back to the legacy
+/// memory-mapped xAPIC ICR with a proper delivery-status poll.
+
+use crate::arch::controller::InterruptController;
+use super::cpu;
+use core::arch::asm;
+
+/// x2APIC ICR MSR (writing it issues the IPI; destination is in bits 63:32).
+const IA32_X2APIC_ICR: u32 = 0x830;
+/// IA32_APIC_BASE MSR; bit 10 enables x2APIC mode.
+const IA32_APIC_BASE: u32 = 0x1B;
+const APIC_BASE_X2APIC_ENABLE: u64 = 1 << 10;
+
+/// ICR offsets in the legacy memory-mapped xAPIC.
+const XAPIC_ICR_LOW: isize = 0x300 / 4;
+const XAPIC_ICR_HIGH: isize = 0x310 / 4;
+/// Delivery-status bit in the low ICR word: set while the IPI is pending.
+const XAPIC_ICR_DELIVERY_PENDING: u32 = 1 << 12;
+/// Level bit (14): 1 = assert, 0 = deassert. Only meaningful for INIT.
+const XAPIC_ICR_LEVEL_ASSERT: u32 = 1 << 14;
+/// Trigger-mode bit (15): 1 = level-triggered. INIT is always sent level-
+/// triggered, per the Intel MP INIT-SIPI-SIPI sequence.
+const XAPIC_ICR_TRIGGER_LEVEL: u32 = 1 << 15;
+
+/// IPI delivery mode, encoded in ICR bits 10:8.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeliveryMode {
+    Fixed,
+    Init,
+    Startup,
+}
+
+impl DeliveryMode {
+    fn bits(self) -> u32 {
+        match self {
+            DeliveryMode::Fixed => 0b000 << 8,
+            DeliveryMode::Init => 0b101 << 8,
+            DeliveryMode::Startup => 0b110 << 8,
+        }
+    }
+}
+
+/// Detect x2APIC support via CPUID.01H:ECX.x2APIC (bit 21).
+pub fn x2apic_supported() -> bool {
+    let (ecx, _) = cpu::get_features();
+    ecx & (1 << 21) != 0
+}
+
+/// Whether x2APIC mode is currently active (bit 10 of IA32_APIC_BASE).
+pub fn x2apic_enabled() -> bool {
+    cpu::read_msr(IA32_APIC_BASE) & APIC_BASE_X2APIC_ENABLE != 0
+}
+
+/// Enable x2APIC mode if the CPU supports it. Safe to call more than once.
+pub fn enable_x2apic() {
+    if !x2apic_supported() || x2apic_enabled() {
+        return;
+    }
+    let base = cpu::read_msr(IA32_APIC_BASE);
+    cpu::write_msr(IA32_APIC_BASE, base | APIC_BASE_X2APIC_ENABLE);
+}
+
+/// A thin handle over whichever APIC mode is active.
+pub struct LocalApic;
+
+impl LocalApic {
+    pub fn new() -> Self {
+        LocalApic
+    }
+
+    /// Send an IPI to `dest` (an APIC/x2APIC id) with the given delivery
+    /// mode and vector, routing through x2APIC when enabled and falling
+    /// back to the legacy xAPIC MMIO ICR otherwise.
+    pub fn send_ipi(&self, dest: u32, mode: DeliveryMode, vector: u8) {
+        if x2apic_enabled() {
+            self.send_ipi_x2apic(dest, mode, vector);
+        } else {
+            self.send_ipi_xapic(dest, mode, vector);
+        }
+    }
+
+    /// Send an INIT IPI with an explicit assert/deassert level, as the
+    /// INIT-SIPI-SIPI AP bring-up sequence requires: assert, wait, then
+    /// deassert before the STARTUP IPIs follow.
+    ///
+    /// x2APIC's ICR has no level/trigger-mode fields -- INIT is always
+    /// edge-triggered there and a separate deassert is neither needed nor
+    /// meaningful, so only the assert call actually sends anything when
+    /// x2APIC is active.
+    pub fn send_init_ipi(&self, dest: u32, assert: bool) {
+        if x2apic_enabled() {
+            if assert {
+                self.send_ipi_x2apic(dest, DeliveryMode::Init, 0);
+            }
+            return;
+        }
+
+        let mut command = DeliveryMode::Init.bits() | XAPIC_ICR_TRIGGER_LEVEL;
+        if assert {
+            command |= XAPIC_ICR_LEVEL_ASSERT;
+        }
+        self.write_icr_xapic(dest, command);
+    }
+
+    /// x2APIC delivers the IPI with a single 64-bit `wrmsr`: destination in
+    /// bits 63:32, delivery mode/vector in bits 31:0. No delivery-status
+    /// poll is needed -- the instruction itself doesn't retire until
+    /// accepted.
+    fn send_ipi_x2apic(&self, dest: u32, mode: DeliveryMode, vector: u8) {
+        let value = ((dest as u64) << 32) | (mode.bits() | vector as u32) as u64;
+        cpu::write_msr(IA32_X2APIC_ICR, value);
+    }
+
+    /// Legacy xAPIC: write the destination into ICR[63:56] then the command
+    /// into ICR[31:0], polling the delivery-status bit (bit 12) until the
+    /// IPI has actually been accepted instead of a fixed delay.
+    fn send_ipi_xapic(&self, dest: u32, mode: DeliveryMode, vector: u8) {
+        self.write_icr_xapic(dest, mode.bits() | vector as u32);
+    }
+
+    /// Write `dest`/`command` into the memory-mapped xAPIC ICR and poll
+    /// the delivery-status bit until the IPI has actually been accepted.
+    fn write_icr_xapic(&self, dest: u32, command: u32) {
+        let lapic_base = cpu::get_lapic_base() as *mut u32;
+        unsafe {
+            lapic_base
+                .offset(XAPIC_ICR_HIGH)
+                .write_volatile(dest << 24);
+            lapic_base.offset(XAPIC_ICR_LOW).write_volatile(command);
+
+            while lapic_base.offset(XAPIC_ICR_LOW).read_volatile() & XAPIC_ICR_DELIVERY_PENDING != 0
+            {
+                asm!("pause", options(nomem, nostack));
+            }
+        }
+    }
+
+    /// Read a register at byte `offset`, through the x2APIC MSR window
+    /// (`0x800 + offset/16`, the same linear mapping the SDM defines for
+    /// every xAPIC register) when x2APIC is active, or the legacy MMIO
+    /// window otherwise.
+    fn read_reg(&self, offset: u32) -> u32 {
+        if x2apic_enabled() {
+            cpu::read_msr(0x800 + (offset >> 4)) as u32
+        } else {
+            unsafe {
+                (cpu::get_lapic_base() as *const u32)
+                    .add((offset / 4) as usize)
+                    .read_volatile()
+            }
+        }
+    }
+
+    /// Write a register at byte `offset`; see [`Self::read_reg`] for the
+    /// x2APIC/xAPIC addressing split.
+    fn write_reg(&self, offset: u32, value: u32) {
+        if x2apic_enabled() {
+            cpu::write_msr(0x800 + (offset >> 4), value as u64);
+        } else {
+            unsafe {
+                (cpu::get_lapic_base() as *mut u32)
+                    .add((offset / 4) as usize)
+                    .write_volatile(value);
+            }
+        }
+    }
+
+    /// Route the performance-counter overflow interrupt as an NMI,
+    /// masking or unmasking the LVT entry as requested. Architectural
+    /// perfmon counters mask this entry automatically the moment they
+    /// fire, so the NMI handler has to unmask it again (`masked = false`)
+    /// before the next sample can be delivered.
+    pub fn set_lvt_perfmon_nmi(&self, masked: bool) {
+        let mut value = LVT_DELIVERY_NMI;
+        if masked {
+            value |= LVT_MASKED;
+        }
+        self.write_reg(LAPIC_LVT_PERFMON, value);
+    }
+}
+
+/// TSC-deadline mode MSR, shared with [`crate::clock`]'s `x86_64_clock`:
+/// writing it arms the LVT timer entry [`ApicController::init`] configures
+/// below to fire once the TSC reaches the written value.
+const IA32_TSC_DEADLINE: u32 = 0x6E0;
+
+/// Spurious-interrupt vector register; bit 8 enables the APIC, bits 7:0
+/// are the vector delivered for a spurious (unmatched) interrupt.
+const LAPIC_SVR: u32 = 0x0F0;
+const LAPIC_SVR_APIC_ENABLE: u32 = 1 << 8;
+/// Conventionally the last usable vector, so it never collides with a
+/// real device or exception vector.
+const SPURIOUS_VECTOR: u32 = 0xFF;
+
+/// LVT timer entry: bits 18:17 select the mode, bit 16 masks it, bits 7:0
+/// are the delivered vector.
+const LAPIC_LVT_TIMER: u32 = 0x320;
+const LVT_TIMER_MODE_TSC_DEADLINE: u32 = 0b10 << 17;
+const LVT_MASKED: u32 = 1 << 16;
+/// Divide configuration register for the legacy count-down timer modes.
+/// TSC-deadline mode ignores it, but it's set for completeness/in case a
+/// future change falls back to one-shot counting mode.
+const LAPIC_TIMER_DIV: u32 = 0x3E0;
+const LAPIC_TIMER_DIV_BY_1: u32 = 0b1011;
+/// Writing any value here signals end-of-interrupt for whichever vector
+/// is currently in service.
+const LAPIC_EOI: u32 = 0x0B0;
+
+/// Performance-monitoring counter LVT entry: delivers the sample
+/// interrupt `x86_64::pmu` arms. Delivery mode NMI (`0b100` in bits 10:8)
+/// rather than a fixed vector, so a sampling profiler can still interrupt
+/// code that's running with `cli` in effect.
+const LAPIC_LVT_PERFMON: u32 = 0x340;
+const LVT_DELIVERY_NMI: u32 = 0b100 << 8;
+
+/// Timer vector [`crate::arch::interrupts::init_idt`] points IDT vector 32
+/// at; shared here so the LVT timer entry delivers to the same handler.
+const TIMER_VECTOR: u32 = 32;
+
+/// [`InterruptController`] backed by the Local APIC: a spurious vector
+/// plus a TSC-deadline LVT timer entry, arming deadlines through the same
+/// `IA32_TSC_DEADLINE` MSR [`crate::clock`]'s `TscClock` already writes.
+pub struct ApicController {
+    apic: LocalApic,
+}
+
+impl ApicController {
+    pub fn new() -> Self {
+        ApicController { apic: LocalApic::new() }
+    }
+}
+
+impl InterruptController for ApicController {
+    fn init(&mut self) {
+        enable_x2apic();
+        self.apic.write_reg(LAPIC_SVR, LAPIC_SVR_APIC_ENABLE | SPURIOUS_VECTOR);
+        self.apic.write_reg(LAPIC_TIMER_DIV, LAPIC_TIMER_DIV_BY_1);
+        self.apic
+            .write_reg(LAPIC_LVT_TIMER, LVT_TIMER_MODE_TSC_DEADLINE | LVT_MASKED | TIMER_VECTOR);
+    }
+
+    fn enable(&mut self, irq: u32) {
+        // No IOAPIC redirection table exists in this tree yet, so the only
+        // IRQ this controller can actually unmask is its own LVT timer
+        // entry; every other IRQ number is a no-op until an IOAPIC driver
+        // is written to route it.
+        if irq == TIMER_VECTOR {
+            let lvt = self.apic.read_reg(LAPIC_LVT_TIMER);
+            self.apic.write_reg(LAPIC_LVT_TIMER, lvt & !LVT_MASKED);
+        }
+    }
+
+    fn eoi(&mut self, _irq: u32) {
+        self.apic.write_reg(LAPIC_EOI, 0);
+    }
+
+    fn set_timer(&mut self, deadline_ticks: u64) {
+        cpu::write_msr(IA32_TSC_DEADLINE, deadline_ticks);
+    }
+}
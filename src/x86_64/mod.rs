@@ -0,0 +1,9 @@
+pub mod ap_boot;
+pub mod ap_trampoline;
+pub mod coredump;
+pub mod cpu;
+pub mod gdt;
+pub mod gdbstub;
+pub mod idt;
+pub mod lapic;
+pub mod pmu;
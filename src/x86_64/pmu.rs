@@ -0,0 +1,84 @@
+/// x86_64 Performance-Monitoring Unit Programming
+///
+/// Arms architectural perfmon counter 0 to count unhalted core cycles and
+/// overflow -- delivering an NMI through the LAPIC's LVT_PERFMON entry --
+/// every `period` cycles. This is the hardware half of `cpu::profiler`'s
+/// sampling loop; vector 2's IDT gate is pointed at [`nmi_handler`] below
+/// by `arch::interrupts::init_idt`.
+use super::cpu::{read_msr, write_msr};
+use super::lapic::LocalApic;
+use crate::arch::interrupts::InterruptStackFrame;
+
+const IA32_PERFEVTSEL0: u32 = 0x186;
+const IA32_PMC0: u32 = 0xC1;
+
+/// Event select for architectural perfmon's "Unhalted Core Cycles" event
+/// (event 0x3C, umask 0x00) -- guaranteed present by CPUID leaf 0x0A on
+/// any CPU that reports at least one general-purpose counter.
+const EVENT_UNHALTED_CORE_CYCLES: u64 = 0x3C;
+const PERFEVTSEL_USR: u64 = 1 << 16;
+const PERFEVTSEL_OS: u64 = 1 << 17;
+const PERFEVTSEL_INT: u64 = 1 << 20;
+const PERFEVTSEL_EN: u64 = 1 << 22;
+
+/// Arm PMC0 to overflow after `period` unhalted cycles, routed as an NMI.
+pub fn arm(period: u64) {
+    // Disable the counter while reprogramming it so a stray overflow
+    // can't fire mid-setup.
+    write_msr(IA32_PERFEVTSEL0, 0);
+    reload_counter(period);
+
+    write_msr(
+        IA32_PERFEVTSEL0,
+        EVENT_UNHALTED_CORE_CYCLES
+            | PERFEVTSEL_USR
+            | PERFEVTSEL_OS
+            | PERFEVTSEL_INT
+            | PERFEVTSEL_EN,
+    );
+
+    LocalApic::new().set_lvt_perfmon_nmi(false);
+}
+
+/// Stop counting and mask the LVT entry so no further NMI fires.
+pub fn disarm() {
+    write_msr(IA32_PERFEVTSEL0, 0);
+    LocalApic::new().set_lvt_perfmon_nmi(true);
+}
+
+/// Reload PMC0 for the next sample period and unmask its LVT entry again
+/// -- the hardware masks the entry and leaves the counter at 0 on
+/// overflow, so [`nmi_handler`] calls this each time before returning.
+pub fn rearm(period: u64) {
+    reload_counter(period);
+    LocalApic::new().set_lvt_perfmon_nmi(false);
+}
+
+/// Counters count up and interrupt on overflow, so priming PMC0 with the
+/// two's complement of `period` makes the very next `period` cycles the
+/// ones that overflow it.
+fn reload_counter(period: u64) {
+    write_msr(IA32_PMC0, 0u64.wrapping_sub(period));
+}
+
+fn rdtsc() -> u64 {
+    let (lo, hi): (u32, u32);
+    unsafe {
+        core::arch::asm!("rdtsc", out("eax") lo, out("edx") hi, options(nomem, nostack));
+    }
+    ((hi as u64) << 32) | lo as u64
+}
+
+/// IDT vector 2 handler once `cpu::profiler` has claimed the NMI gate.
+/// Falls back to the ordinary unhandled-exception panic for any NMI that
+/// arrives while the profiler isn't running -- a watchdog or an MCE
+/// reported over NMI still needs to reach `panic_with_context`.
+pub extern "x86-interrupt" fn nmi_handler(frame: InterruptStackFrame) {
+    if !crate::cpu::profiler::is_active() {
+        crate::arch::interrupts::panic_on_stray_nmi(&frame);
+    }
+
+    let timestamp = rdtsc();
+    crate::cpu::profiler::record_sample(frame.instruction_pointer, timestamp);
+    rearm(crate::cpu::profiler::current_period());
+}